@@ -0,0 +1,402 @@
+#[cfg(feature = "binaries")]
+mod binaries {
+  pub(crate) use core::ops::Deref;
+  pub(crate) use std::{collections::HashSet, io::BufRead};
+
+  pub(crate) use zeroize::Zeroizing;
+  pub(crate) use rand_core::OsRng;
+
+  pub(crate) use curve25519_dalek::{scalar::Scalar, edwards::CompressedEdwardsY};
+
+  pub(crate) use monero_serai::{
+    ringct::generate_key_image,
+    transaction::{Input, Timelock, Transaction},
+    rpc::{Rpc, HttpRpc},
+    wallet::{
+      address::{Network, AddressSpec, MoneroAddress},
+      seed::{classic, Seed, SeedType},
+      cold::{write_outputs, read_outputs, export_key_images, write_key_images, read_key_images},
+      ViewPair, Scanner, SpendableOutput, Decoys, FeePriority, Change, SignableTransaction,
+      InputSelection,
+    },
+  };
+
+  // Outputs must be this many blocks old before they can be spent
+  pub(crate) const LOCK_WINDOW: usize = 10;
+
+  pub(crate) fn usage() -> ! {
+    eprintln!("usage: wallet <command> [args]");
+    eprintln!();
+    eprintln!("  generate [network]              create a new seed and print it with its address");
+    eprintln!("  address [network]               print the address for the seed read from stdin");
+    eprintln!("  view-keys                       print the watch-only keys for the seed on stdin");
+    eprintln!("  scan <node> <start>             scan with the watch-only keys read from stdin");
+    eprintln!("  send <node> <start> <address> <amount> [network] [priority]");
+    eprintln!("                                  send the atomic amount to the address,");
+    eprintln!("                                  spending outputs received since the start block,");
+    eprintln!("                                  with the seed read from stdin");
+    eprintln!();
+    eprintln!("cold signing, where the seed is only ever read by an offline machine:");
+    eprintln!("  export-outputs <node> <start>   (online) print the outputs received since the");
+    eprintln!("                                  start block, with the watch-only keys on stdin");
+    eprintln!("  key-images                      (offline) print the signed key images for the");
+    eprintln!("                                  outputs, with the seed then outputs on stdin");
+    eprintln!("  create <node> <start> <address> <amount> [network] [priority]");
+    eprintln!("                                  (online) print an unsigned transaction, with the");
+    eprintln!("                                  watch-only keys then key images on stdin");
+    eprintln!("  sign                            (offline) print the signed transaction, with the");
+    eprintln!("                                  seed then unsigned transaction on stdin");
+    eprintln!("  publish <node>                  (online) publish the signed transaction on stdin");
+    eprintln!();
+    eprintln!("network may be one of mainnet (default), testnet, or stagenet");
+    eprintln!("priority may be one of low (default), normal, high, or highest");
+    std::process::exit(1);
+  }
+
+  pub(crate) fn network(arg: Option<&String>) -> Network {
+    match arg.map(String::as_str) {
+      None | Some("mainnet") => Network::Mainnet,
+      Some("testnet") => Network::Testnet,
+      Some("stagenet") => Network::Stagenet,
+      Some(network) => panic!("unknown network {network}"),
+    }
+  }
+
+  pub(crate) fn priority(arg: Option<&String>) -> FeePriority {
+    match arg.map(String::as_str) {
+      None | Some("low") => FeePriority::Low,
+      Some("normal") => FeePriority::Medium,
+      Some("high") => FeePriority::High,
+      Some("highest") => FeePriority::Highest,
+      Some(priority) => panic!("unknown priority {priority}"),
+    }
+  }
+
+  pub(crate) fn read_line() -> Zeroizing<String> {
+    let mut line = Zeroizing::new(String::new());
+    std::io::stdin().lock().read_line(&mut line).expect("couldn't read from stdin");
+    line
+  }
+
+  pub(crate) fn keys(seed: &Seed) -> (Zeroizing<Scalar>, ViewPair) {
+    (seed.spend_key(), seed.view_pair())
+  }
+
+  pub(crate) fn read_seed() -> (Zeroizing<Scalar>, ViewPair) {
+    keys(&Seed::from_string(read_line()).expect("invalid seed"))
+  }
+
+  // Read a line of hex-encoded data, as printed by a prior command
+  pub(crate) fn read_hex() -> Zeroizing<Vec<u8>> {
+    Zeroizing::new(hex::decode(read_line().trim()).expect("input wasn't hex"))
+  }
+
+  pub(crate) fn read_view_pair() -> ViewPair {
+    let line = read_line();
+    let mut keys = line.split_whitespace();
+    let mut key = || -> [u8; 32] {
+      hex::decode(keys.next().expect("missing key"))
+        .expect("key wasn't hex")
+        .try_into()
+        .expect("key wasn't 32 bytes")
+    };
+
+    let spend = CompressedEdwardsY(key()).decompress().expect("invalid spend key");
+    let view =
+      Zeroizing::new(Option::from(Scalar::from_canonical_bytes(key())).expect("invalid view key"));
+    ViewPair::new(spend, view)
+  }
+
+  pub(crate) fn rpc(url: &str) -> Rpc<HttpRpc> {
+    HttpRpc::new(url.to_string())
+      .unwrap_or_else(|_| panic!("couldn't create HttpRpc connected to {url}"))
+  }
+
+  // Scan the blocks from start to the tip, returning every output received, the key images spent
+  // within those blocks, and the height scanned to
+  pub(crate) async fn scan(
+    rpc: &Rpc<HttpRpc>,
+    pair: ViewPair,
+    start: usize,
+  ) -> (Vec<SpendableOutput>, HashSet<[u8; 32]>, usize) {
+    let mut scanner = Scanner::from_view(pair, Some(HashSet::new()));
+
+    let height = rpc.get_height().await.expect("couldn't get the height");
+    let mut outputs = vec![];
+    let mut spent = HashSet::new();
+    // Fetch blocks in batches, as the latency of each request dominates the time to scan
+    const BATCH: usize = 100;
+    for batch_start in (start .. height).step_by(BATCH) {
+      let blocks = rpc
+        .get_blocks_range(batch_start, BATCH.min(height - batch_start))
+        .await
+        .expect("couldn't get blocks");
+      for (block_i, (block, txs)) in (batch_start ..).zip(blocks) {
+        // Track every key image spent, instead of asking the node about ours, so the node doesn't
+        // learn which outputs are ours
+        for tx in &txs {
+          for input in &tx.prefix.inputs {
+            if let Input::ToKey { key_image, .. } = input {
+              spent.insert(key_image.compress().to_bytes());
+            }
+          }
+        }
+
+        for timelocked in
+          scanner.scan_with_transactions(rpc, &block, txs).await.expect("couldn't scan block")
+        {
+          // Timelocked outputs are kept, and only filtered when selecting inputs to spend
+          if timelocked.timelock() != Timelock::None {
+            println!(
+              "received output(s) in block {block_i} timelocked until {:?}",
+              timelocked.timelock()
+            );
+          }
+          for output in timelocked.ignore_timelock() {
+            println!(
+              "received {} in block {block_i} (tx {})",
+              output.commitment().amount,
+              hex::encode(output.output.absolute.tx),
+            );
+            outputs.push((block_i, output));
+          }
+        }
+      }
+    }
+
+    // Remove outputs which haven't yet aged
+    let outputs = outputs
+      .into_iter()
+      .filter(|(block_i, _)| (block_i + LOCK_WINDOW) <= height)
+      .map(|(_, output)| output)
+      .collect();
+    (outputs, spent, height)
+  }
+
+  // Remove outputs whose key images were spent
+  pub(crate) fn unspent(
+    spend: &Zeroizing<Scalar>,
+    outputs: Vec<SpendableOutput>,
+    spent: &HashSet<[u8; 32]>,
+  ) -> Vec<SpendableOutput> {
+    outputs
+      .into_iter()
+      .filter(|output| {
+        let image = generate_key_image(&Zeroizing::new(spend.deref() + output.key_offset()));
+        !spent.contains(&image.compress().to_bytes())
+      })
+      .collect()
+  }
+
+  // Create a transaction sending the amount to the destination, spending from the outputs
+  pub(crate) async fn create(
+    rpc: &Rpc<HttpRpc>,
+    pair: &ViewPair,
+    outputs: Vec<SpendableOutput>,
+    height: usize,
+    destination: MoneroAddress,
+    amount: u64,
+    priority: FeePriority,
+  ) -> SignableTransaction {
+    // Only spend outputs whose timelocks have expired
+    let time = rpc
+      .get_block_by_number(height - 1)
+      .await
+      .expect("couldn't get the latest block")
+      .header
+      .timestamp;
+    let outputs = outputs
+      .into_iter()
+      .filter(|output| output.timelock().is_unlocked(height, time))
+      .collect::<Vec<_>>();
+
+    let protocol = rpc.get_protocol().await.expect("couldn't get the protocol");
+    let fee = rpc.get_fee(protocol, priority).await.expect("couldn't get the fee");
+
+    // Spend the largest outputs first, until they cover the amount and the fee
+    let payments = vec![(destination, amount)];
+    let inputs = InputSelection::LargestFirst
+      .select(protocol, fee, outputs, &payments, true, &[])
+      .expect("couldn't select inputs");
+
+    let decoys = Decoys::select(&mut OsRng, rpc, protocol.ring_len(), height, &inputs)
+      .await
+      .expect("couldn't select decoys");
+
+    SignableTransaction::new(
+      protocol,
+      None,
+      inputs.into_iter().zip(decoys).collect(),
+      payments,
+      Some(Change::new(pair, false)),
+      vec![],
+      fee,
+    )
+    .expect("couldn't create transaction")
+  }
+
+  pub(crate) async fn publish(rpc: &Rpc<HttpRpc>, tx: &Transaction) {
+    rpc.publish_transaction(tx).await.expect("couldn't publish transaction");
+    println!("published {}", hex::encode(tx.hash()));
+  }
+}
+
+#[cfg(feature = "binaries")]
+#[tokio::main]
+async fn main() {
+  use binaries::*;
+
+  let args = std::env::args().collect::<Vec<String>>();
+  let Some(command) = args.get(1) else { usage() };
+
+  match command.as_str() {
+    "generate" => {
+      let seed = Seed::new(&mut OsRng, SeedType::Classic(classic::Language::English));
+      let (_, pair) = keys(&seed);
+      println!("seed: {}", seed.to_string().as_str());
+      println!(
+        "address: {}",
+        pair.address(network(args.get(2)), AddressSpec::Standard).to_string()
+      );
+    }
+
+    "address" => {
+      let (_, pair) = read_seed();
+      println!("{}", pair.address(network(args.get(2)), AddressSpec::Standard).to_string());
+    }
+
+    // The view keys let a hot machine scan for received outputs without being able to spend them
+    "view-keys" => {
+      let (_, pair) = read_seed();
+      println!(
+        "{} {}",
+        hex::encode(pair.spend().compress().to_bytes()),
+        hex::encode(pair.private_view_key().to_bytes()),
+      );
+    }
+
+    "scan" => {
+      if args.len() < 4 {
+        usage();
+      }
+      let pair = read_view_pair();
+      let start = args[3].parse::<usize>().expect("invalid start block");
+      // Without the spend key, the key images can't be calculated, so this can't tell which
+      // outputs were spent
+      let (outputs, _, height) = scan(&rpc(&args[2]), pair, start).await;
+      println!(
+        "scanned through block {}, received {} output(s) totalling {}",
+        height - 1,
+        outputs.len(),
+        outputs.iter().map(|output| output.commitment().amount).sum::<u64>(),
+      );
+    }
+
+    "send" => {
+      if args.len() < 6 {
+        usage();
+      }
+      let (spend, pair) = read_seed();
+      let rpc = rpc(&args[2]);
+      let start = args[3].parse::<usize>().expect("invalid start block");
+      let network = network(args.get(6));
+      let destination = MoneroAddress::from_str(network, &args[4]).expect("invalid address");
+      let amount = args[5].parse::<u64>().expect("invalid amount");
+      let priority = priority(args.get(7));
+
+      let (outputs, spent, height) = scan(&rpc, pair.clone(), start).await;
+      let outputs = unspent(&spend, outputs, &spent);
+
+      let tx = create(&rpc, &pair, outputs, height, destination, amount, priority)
+        .await
+        .sign(&mut OsRng, &spend)
+        .expect("couldn't sign transaction");
+      publish(&rpc, &tx).await;
+    }
+
+    "export-outputs" => {
+      if args.len() < 4 {
+        usage();
+      }
+      let pair = read_view_pair();
+      let start = args[3].parse::<usize>().expect("invalid start block");
+      let (outputs, _, _) = scan(&rpc(&args[2]), pair, start).await;
+      let mut serialized = vec![];
+      write_outputs(&outputs, &mut serialized).unwrap();
+      println!("{}", hex::encode(serialized));
+    }
+
+    "key-images" => {
+      let (spend, _) = read_seed();
+      let outputs = read_outputs(&mut read_hex().as_slice()).expect("invalid outputs");
+      let key_images =
+        export_key_images(&mut OsRng, &spend, &outputs).expect("outputs weren't this wallet's");
+      let mut serialized = vec![];
+      write_key_images(&key_images, &mut serialized).unwrap();
+      println!("{}", hex::encode(serialized));
+    }
+
+    "create" => {
+      if args.len() < 6 {
+        usage();
+      }
+      let pair = read_view_pair();
+      let key_images = read_key_images(&mut read_hex().as_slice()).expect("invalid key images");
+      let rpc = rpc(&args[2]);
+      let start = args[3].parse::<usize>().expect("invalid start block");
+      let network = network(args.get(6));
+      let destination = MoneroAddress::from_str(network, &args[4]).expect("invalid address");
+      let amount = args[5].parse::<u64>().expect("invalid amount");
+      let priority = priority(args.get(7));
+
+      let (outputs, spent, height) = scan(&rpc, pair.clone(), start).await;
+      // Only spend outputs whose key images were signed by the offline signer, as whether the
+      // other outputs were spent can't be determined
+      let received = outputs.len();
+      let outputs = outputs
+        .into_iter()
+        .filter(|output| {
+          key_images.iter().find(|key_image| key_image.verify(output)).map_or(false, |key_image| {
+            !spent.contains(&key_image.key_image().compress().to_bytes())
+          })
+        })
+        .collect::<Vec<_>>();
+      if outputs.len() != received {
+        eprintln!(
+          "{} output(s) were spent or lacked key images, export the outputs again to spend them",
+          received - outputs.len()
+        );
+      }
+
+      let tx = create(&rpc, &pair, outputs, height, destination, amount, priority).await;
+      println!("{}", hex::encode(tx.serialize()));
+    }
+
+    "sign" => {
+      let (spend, _) = read_seed();
+      let tx = SignableTransaction::read(&mut read_hex().as_slice()).expect("invalid transaction");
+      // Show what's being signed, as the online machine may not be trustworthy
+      for (address, amount) in tx.payments() {
+        eprintln!("paying {amount} to {address}");
+      }
+      eprintln!("with a fee of {}", tx.fee());
+      let tx = tx.sign(&mut OsRng, &spend).expect("couldn't sign transaction");
+      println!("{}", hex::encode(tx.serialize()));
+    }
+
+    "publish" => {
+      if args.len() < 3 {
+        usage();
+      }
+      let tx = Transaction::read(&mut read_hex().as_slice()).expect("invalid transaction");
+      publish(&rpc(&args[2]), &tx).await;
+    }
+
+    _ => usage(),
+  }
+}
+
+#[cfg(not(feature = "binaries"))]
+fn main() {
+  panic!("To run binaries, please build with `--feature binaries`.");
+}
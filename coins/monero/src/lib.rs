@@ -65,6 +65,42 @@ pub enum Protocol {
 }
 
 impl Protocol {
+  /// The protocol to use under the specified hard fork, by the version of the blocks produced.
+  ///
+  /// Returns None if the hard fork isn't supported.
+  pub fn from_hard_fork(version: u64) -> Option<Protocol> {
+    match version {
+      13 | 14 => Some(Protocol::v14),
+      15 | 16 => Some(Protocol::v16),
+      _ => None,
+    }
+  }
+
+  /// Whether or not transactions created under this protocol are valid under the specified hard
+  /// fork.
+  ///
+  /// Custom protocols aren't considered valid under any hard fork.
+  pub fn valid_under_hard_fork(&self, version: u64) -> bool {
+    match self {
+      Protocol::v14 => (13 ..= 15).contains(&version),
+      Protocol::v16 => (15 ..= 16).contains(&version),
+      Protocol::Custom { .. } => false,
+    }
+  }
+
+  /// The protocol to use for transactions which must be valid both under the current hard fork and
+  /// under the next one, such as transactions created shortly before a fork which may not be
+  /// included in a block until after it.
+  ///
+  /// Returns None if no supported protocol is valid under both hard forks, in which case
+  /// transactions shouldn't be created until the fork activates.
+  pub fn for_hard_fork_transition(current: u64, next: u64) -> Option<Protocol> {
+    // Prefer the newer protocol, as it'll remain valid for longer
+    [Protocol::v16, Protocol::v14].into_iter().find(|protocol| {
+      protocol.valid_under_hard_fork(current) && protocol.valid_under_hard_fork(next)
+    })
+  }
+
   /// Amount of ring members under this protocol version.
   pub fn ring_len(&self) -> usize {
     match self {
@@ -0,0 +1,83 @@
+use std_shims::vec::Vec;
+
+use rand_core::{RngCore, CryptoRng};
+use zeroize::Zeroize;
+
+use curve25519_dalek::{scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{
+  H,
+  transaction::{Input, Transaction},
+  ringct::RctPrunable,
+};
+
+/// A batch verifier for the RingCT signatures of transactions using CLSAGs.
+///
+/// The range proofs of every queued transaction are verified with a single multiexp. CLSAGs
+/// can't be batched, as each challenge is the hash of the prior round's commitments, so each
+/// CLSAG is verified, and each transaction's balance checked, as the transaction is queued.
+pub struct BatchVerifier<ID: Copy + Zeroize> {
+  bulletproofs: multiexp::BatchVerifier<ID, dalek_ff_group::EdwardsPoint>,
+}
+
+impl<ID: Copy + Zeroize> BatchVerifier<ID> {
+  /// Create a new batch verifier, with capacity for the specified amount of transactions.
+  pub fn new(capacity: usize) -> BatchVerifier<ID> {
+    BatchVerifier { bulletproofs: multiexp::BatchVerifier::new(capacity) }
+  }
+
+  /// Queue a transaction's RingCT signatures for verification.
+  ///
+  /// `rings` is the ring for each input, in order, with each ring member being its output key
+  /// and commitment. These must be fetched from the blockchain by the caller.
+  ///
+  /// Returns false if the transaction's CLSAGs or balance are invalid, or if the transaction
+  /// isn't a CLSAG transaction, without mutating the BatchVerifier. Returns true otherwise,
+  /// regardless of the validity of the transaction's range proof.
+  #[must_use]
+  pub fn queue<R: RngCore + CryptoRng>(
+    &mut self,
+    rng: &mut R,
+    id: ID,
+    tx: &Transaction,
+    rings: &[Vec<[EdwardsPoint; 2]>],
+  ) -> bool {
+    let RctPrunable::Clsag { bulletproofs, clsags, pseudo_outs } = &tx.rct_signatures.prunable
+    else {
+      return false;
+    };
+    let base = &tx.rct_signatures.base;
+
+    let inputs = tx.prefix.inputs.len();
+    if (clsags.len() != inputs) || (pseudo_outs.len() != inputs) || (rings.len() != inputs) {
+      return false;
+    }
+
+    // The sum of the inputs' commitments must equal the sum of the outputs' and the fee
+    let outputs = base.commitments.iter().sum::<EdwardsPoint>() + (Scalar::from(base.fee) * H());
+    if pseudo_outs.iter().sum::<EdwardsPoint>() != outputs {
+      return false;
+    }
+
+    let msg = tx.signature_hash();
+    for (((input, clsag), pseudo_out), ring) in
+      tx.prefix.inputs.iter().zip(clsags).zip(pseudo_outs).zip(rings)
+    {
+      let Input::ToKey { key_offsets, key_image, .. } = input else { return false };
+      if key_offsets.len() != ring.len() {
+        return false;
+      }
+      if clsag.verify(ring, key_image, pseudo_out, &msg).is_err() {
+        return false;
+      }
+    }
+
+    bulletproofs.batch_verify(rng, &mut self.bulletproofs, id, &base.commitments)
+  }
+
+  /// Verify every queued transaction's range proof, returning the ID of a transaction with an
+  /// invalid range proof if the batch is invalid.
+  pub fn verify(&self) -> Result<(), ID> {
+    self.bulletproofs.verify_vartime_with_vartime_blame()
+  }
+}
@@ -149,6 +149,7 @@ fn core(
   to_hash.extend(msg);
 
   // Configure the loop based on if we're signing or verifying
+  let signing = matches!(A_c1, Mode::Sign(..));
   let start;
   let end;
   let mut c;
@@ -191,7 +192,8 @@ fn core(
   }
 
   // This first tuple is needed to continue signing, the latter is the c to be tested/worked with
-  ((D, c * mu_P, c * mu_C), c1.unwrap_or(c))
+  // When verifying, that's the c after completing the loop, not the c1 the loop started with
+  ((D, c * mu_P, c * mu_C), if signing { c1.unwrap_or(c) } else { c })
 }
 
 /// CLSAG signature, as used in Monero.
@@ -217,10 +219,10 @@ impl Clsag {
     let r: usize = input.decoys.i.into();
 
     let pseudo_out = Commitment::new(mask, input.commitment.amount).calculate();
-    let z = input.commitment.mask - mask;
+    let z = Zeroizing::new(input.commitment.mask - mask);
 
     let H = hash_to_point(input.decoys.ring[r][0]);
-    let D = H * z;
+    let D = H * z.deref();
     let mut s = Vec::with_capacity(input.decoys.ring.len());
     for _ in 0 .. input.decoys.ring.len() {
       s.push(random_scalar(rng));
@@ -228,7 +230,7 @@ impl Clsag {
     let ((D, p, c), c1) =
       core(&input.decoys.ring, I, &pseudo_out, msg, &D, &s, Mode::Sign(r, A, AH));
 
-    (Clsag { D, s, c1 }, pseudo_out, p, c * z)
+    (Clsag { D, s, c1 }, pseudo_out, p, c * z.deref())
   }
 
   /// Generate CLSAG signatures for the given inputs.
@@ -241,13 +243,13 @@ impl Clsag {
     msg: [u8; 32],
   ) -> Vec<(Clsag, EdwardsPoint)> {
     let mut res = Vec::with_capacity(inputs.len());
-    let mut sum_pseudo_outs = Scalar::ZERO;
+    let mut sum_pseudo_outs = Zeroizing::new(Scalar::ZERO);
     for i in 0 .. inputs.len() {
-      let mut mask = random_scalar(rng);
+      let mut mask = Zeroizing::new(random_scalar(rng));
       if i == (inputs.len() - 1) {
-        mask = sum_outputs - sum_pseudo_outs;
+        *mask = sum_outputs - sum_pseudo_outs.deref();
       } else {
-        sum_pseudo_outs += mask;
+        *sum_pseudo_outs += mask.deref();
       }
 
       let mut nonce = Zeroizing::new(random_scalar(rng));
@@ -255,7 +257,7 @@ impl Clsag {
         rng,
         &inputs[i].1,
         &inputs[i].2,
-        mask,
+        *mask,
         &msg,
         nonce.deref() * ED25519_BASEPOINT_TABLE,
         nonce.deref() *
@@ -73,6 +73,58 @@ pub struct ClsagAddendum {
   dleq: DLEqProof<dfg::EdwardsPoint>,
 }
 
+impl ClsagAddendum {
+  // Create our share of the key image for an output, where H is the hash to point of its key
+  #[allow(non_snake_case)]
+  pub(crate) fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    H: EdwardsPoint,
+    keys: &ThresholdKeys<Ed25519>,
+  ) -> ClsagAddendum {
+    ClsagAddendum {
+      key_image: dfg::EdwardsPoint(H) * keys.secret_share().deref(),
+      dleq: DLEqProof::prove(
+        rng,
+        // Doesn't take in a larger transcript object due to the usage of this
+        // Every prover would immediately write their own DLEq proof, when they can only do so in
+        // the proper order if they want to reach consensus
+        // It'd be a poor API to have CLSAG define a new transcript solely to pass here, just to
+        // try to merge later in some form, when it should instead just merge xH (as it does)
+        &mut dleq_transcript(),
+        &[dfg::EdwardsPoint::generator(), dfg::EdwardsPoint(H)],
+        keys.secret_share(),
+      ),
+    }
+  }
+
+  pub(crate) fn read<R: Read>(reader: &mut R) -> io::Result<ClsagAddendum> {
+    let mut bytes = [0; 32];
+    reader.read_exact(&mut bytes)?;
+    // dfg ensures the point is torsion free
+    let xH = Option::<dfg::EdwardsPoint>::from(dfg::EdwardsPoint::from_bytes(&bytes))
+      .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid key image"))?;
+    // Ensure this is a canonical point
+    if xH.to_bytes() != bytes {
+      Err(io::Error::new(io::ErrorKind::Other, "non-canonical key image"))?;
+    }
+
+    Ok(ClsagAddendum { key_image: xH, dleq: DLEqProof::<dfg::EdwardsPoint>::read(reader)? })
+  }
+
+  // Verify this key image share was created by the holder of the specified verification share
+  #[allow(non_snake_case)]
+  pub(crate) fn verify(&self, H: EdwardsPoint, verification_share: dfg::EdwardsPoint) -> bool {
+    self
+      .dleq
+      .verify(
+        &mut dleq_transcript(),
+        &[dfg::EdwardsPoint::generator(), dfg::EdwardsPoint(H)],
+        &[verification_share, self.key_image],
+      )
+      .is_ok()
+  }
+}
+
 impl WriteAddendum for ClsagAddendum {
   fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
     writer.write_all(self.key_image.compress().to_bytes().as_ref())?;
@@ -84,7 +136,7 @@ impl WriteAddendum for ClsagAddendum {
 #[derive(Clone, PartialEq, Eq, Debug)]
 struct Interim {
   p: Scalar,
-  c: Scalar,
+  c: Zeroizing<Scalar>,
 
   clsag: Clsag,
   pseudo_out: EdwardsPoint,
@@ -163,34 +215,11 @@ impl Algorithm<Ed25519> for ClsagMultisig {
     rng: &mut R,
     keys: &ThresholdKeys<Ed25519>,
   ) -> ClsagAddendum {
-    ClsagAddendum {
-      key_image: dfg::EdwardsPoint(self.H) * keys.secret_share().deref(),
-      dleq: DLEqProof::prove(
-        rng,
-        // Doesn't take in a larger transcript object due to the usage of this
-        // Every prover would immediately write their own DLEq proof, when they can only do so in
-        // the proper order if they want to reach consensus
-        // It'd be a poor API to have CLSAG define a new transcript solely to pass here, just to
-        // try to merge later in some form, when it should instead just merge xH (as it does)
-        &mut dleq_transcript(),
-        &[dfg::EdwardsPoint::generator(), dfg::EdwardsPoint(self.H)],
-        keys.secret_share(),
-      ),
-    }
+    ClsagAddendum::new(rng, self.H, keys)
   }
 
   fn read_addendum<R: Read>(&self, reader: &mut R) -> io::Result<ClsagAddendum> {
-    let mut bytes = [0; 32];
-    reader.read_exact(&mut bytes)?;
-    // dfg ensures the point is torsion free
-    let xH = Option::<dfg::EdwardsPoint>::from(dfg::EdwardsPoint::from_bytes(&bytes))
-      .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid key image"))?;
-    // Ensure this is a canonical point
-    if xH.to_bytes() != bytes {
-      Err(io::Error::new(io::ErrorKind::Other, "non-canonical key image"))?;
-    }
-
-    Ok(ClsagAddendum { key_image: xH, dleq: DLEqProof::<dfg::EdwardsPoint>::read(reader)? })
+    ClsagAddendum::read(reader)
   }
 
   fn process_addendum(
@@ -207,14 +236,9 @@ impl Algorithm<Ed25519> for ClsagMultisig {
 
     self.transcript.append_message(b"participant", l.to_bytes());
 
-    addendum
-      .dleq
-      .verify(
-        &mut dleq_transcript(),
-        &[dfg::EdwardsPoint::generator(), dfg::EdwardsPoint(self.H)],
-        &[view.original_verification_share(l), addendum.key_image],
-      )
-      .map_err(|_| FrostError::InvalidPreprocess(l))?;
+    if !addendum.verify(self.H, view.original_verification_share(l)) {
+      Err(FrostError::InvalidPreprocess(l))?;
+    }
 
     self.transcript.append_message(b"key_image_share", addendum.key_image.compress().to_bytes());
     add_key_image_share(
@@ -259,7 +283,7 @@ impl Algorithm<Ed25519> for ClsagMultisig {
       nonce_sums[0][0].0,
       nonce_sums[0][1].0,
     );
-    self.interim = Some(Interim { p, c, clsag, pseudo_out });
+    self.interim = Some(Interim { p, c: Zeroizing::new(c), clsag, pseudo_out });
 
     (-(dfg::Scalar(p) * view.secret_share().deref())) + nonces[0].deref()
   }
@@ -273,7 +297,7 @@ impl Algorithm<Ed25519> for ClsagMultisig {
   ) -> Option<Self::Signature> {
     let interim = self.interim.as_ref().unwrap();
     let mut clsag = interim.clsag.clone();
-    clsag.s[usize::from(self.input().decoys.i)] = sum.0 - interim.c;
+    clsag.s[usize::from(self.input().decoys.i)] = sum.0 - interim.c.deref();
     if clsag
       .verify(
         &self.input().decoys.ring,
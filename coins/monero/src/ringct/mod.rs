@@ -19,6 +19,8 @@ pub mod clsag;
 pub mod borromean;
 /// Bulletproofs(+) structs, along with proving and verifying functionality.
 pub mod bulletproofs;
+/// BatchVerifier struct, for verifying the RingCT signatures of many transactions.
+pub mod batch;
 
 use crate::{
   Protocol,
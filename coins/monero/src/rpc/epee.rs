@@ -0,0 +1,264 @@
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+};
+
+use crate::serialize::{read_byte, read_bytes, read_u16, read_u32, read_u64, read_raw_vec};
+
+// Header for EPEE, an 8-byte magic and a version
+const HEADER: &[u8] = b"\x01\x11\x01\x01\x01\x01\x02\x01\x01";
+
+// How deeply objects may be nested, matching monerod
+const MAX_DEPTH: usize = 100;
+
+const I64: u8 = 1;
+const I32: u8 = 2;
+const I16: u8 = 3;
+const I8: u8 = 4;
+const U64: u8 = 5;
+const U32: u8 = 6;
+const U16: u8 = 7;
+const U8: u8 = 8;
+const DOUBLE: u8 = 9;
+const STRING: u8 = 10;
+const BOOL: u8 = 11;
+const OBJECT: u8 = 12;
+const ARRAY_FLAG: u8 = 0x80;
+
+fn err(msg: &'static str) -> io::Error {
+  io::Error::new(io::ErrorKind::Other, msg)
+}
+
+// Read an EPEE VarInt, distinct from the VarInts used throughout the rest of the protocol
+pub(crate) fn read_epee_vi<R: Read>(reader: &mut R) -> io::Result<u64> {
+  let vi_start = read_byte(reader)?;
+  let len = match vi_start & 0b11 {
+    0 => 1,
+    1 => 2,
+    2 => 4,
+    3 => 8,
+    _ => unreachable!(),
+  };
+  let mut vi = u64::from(vi_start >> 2);
+  for i in 1 .. len {
+    vi |= u64::from(read_byte(reader)?) << (((i - 1) * 8) + 6);
+  }
+  Ok(vi)
+}
+
+pub(crate) fn write_epee_vi<W: Write>(vi: u64, writer: &mut W) -> io::Result<()> {
+  if vi < (1 << 6) {
+    writer.write_all(&[u8::try_from(vi << 2).unwrap()])
+  } else if vi < (1 << 14) {
+    writer.write_all(&u16::try_from((vi << 2) | 1).unwrap().to_le_bytes())
+  } else if vi < (1 << 30) {
+    writer.write_all(&u32::try_from((vi << 2) | 2).unwrap().to_le_bytes())
+  } else if vi < (1 << 62) {
+    writer.write_all(&((vi << 2) | 3).to_le_bytes())
+  } else {
+    Err(err("value too large for an EPEE VarInt"))
+  }
+}
+
+fn read_len<R: Read>(reader: &mut R) -> io::Result<usize> {
+  usize::try_from(read_epee_vi(reader)?).map_err(|_| err("u64 length exceeded usize"))
+}
+
+/// A value within an EPEE object.
+///
+/// Integers are read from any width, and written as 64-bit.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Value {
+  Int(i64),
+  Uint(u64),
+  Double(f64),
+  String(Vec<u8>),
+  Bool(bool),
+  Object(Object),
+  Array(Vec<Value>),
+}
+
+impl Value {
+  fn kind(&self) -> io::Result<u8> {
+    Ok(match self {
+      Value::Int(_) => I64,
+      Value::Uint(_) => U64,
+      Value::Double(_) => DOUBLE,
+      Value::String(_) => STRING,
+      Value::Bool(_) => BOOL,
+      Value::Object(_) => OBJECT,
+      Value::Array(_) => Err(err("nested arrays aren't supported"))?,
+    })
+  }
+
+  // Write this value, without its type
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    match self {
+      Value::Int(value) => writer.write_all(&value.to_le_bytes()),
+      Value::Uint(value) => writer.write_all(&value.to_le_bytes()),
+      Value::Double(value) => writer.write_all(&value.to_le_bytes()),
+      Value::String(value) => {
+        write_epee_vi(u64::try_from(value.len()).unwrap(), writer)?;
+        writer.write_all(value)
+      }
+      Value::Bool(value) => writer.write_all(&[u8::from(*value)]),
+      Value::Object(value) => value.write(writer),
+      Value::Array(_) => Err(err("nested arrays aren't supported")),
+    }
+  }
+
+  fn read<R: Read>(reader: &mut R, kind: u8, depth: usize) -> io::Result<Value> {
+    Ok(match kind {
+      I64 => Value::Int(i64::from_le_bytes(read_bytes(reader)?)),
+      I32 => Value::Int(i32::from_le_bytes(read_bytes(reader)?).into()),
+      I16 => Value::Int(i16::from_le_bytes(read_bytes(reader)?).into()),
+      I8 => Value::Int(i8::from_le_bytes(read_bytes(reader)?).into()),
+      U64 => Value::Uint(read_u64(reader)?),
+      U32 => Value::Uint(read_u32(reader)?.into()),
+      U16 => Value::Uint(read_u16(reader)?.into()),
+      U8 => Value::Uint(read_byte(reader)?.into()),
+      DOUBLE => Value::Double(f64::from_le_bytes(read_bytes(reader)?)),
+      STRING => Value::String(read_raw_vec(read_byte, read_len(reader)?, reader)?),
+      BOOL => Value::Bool(match read_byte(reader)? {
+        0 => false,
+        1 => true,
+        _ => Err(err("invalid bool"))?,
+      }),
+      OBJECT => Value::Object(Object::read(reader, depth + 1)?),
+      _ => Err(err("unsupported type"))?,
+    })
+  }
+
+  pub(crate) fn as_u64(&self) -> Option<u64> {
+    match self {
+      Value::Uint(value) => Some(*value),
+      Value::Int(value) => u64::try_from(*value).ok(),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+    match self {
+      Value::String(value) => Some(value),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn as_object(&self) -> Option<&Object> {
+    match self {
+      Value::Object(value) => Some(value),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn as_array(&self) -> Option<&[Value]> {
+    match self {
+      Value::Array(value) => Some(value),
+      _ => None,
+    }
+  }
+}
+
+/// An EPEE object, also referred to as a section, consisting of named fields.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub(crate) struct Object(Vec<(Vec<u8>, Value)>);
+
+impl Object {
+  pub(crate) fn new() -> Object {
+    Object(vec![])
+  }
+
+  /// Add a field to this object.
+  pub(crate) fn with(mut self, name: &str, value: Value) -> Object {
+    self.0.push((name.as_bytes().to_vec(), value));
+    self
+  }
+
+  pub(crate) fn get(&self, name: &str) -> Option<&Value> {
+    self.0.iter().find(|(field, _)| field == name.as_bytes()).map(|(_, value)| value)
+  }
+
+  /// Get an array field, returning an empty array if the field is missing.
+  ///
+  /// EPEE omits empty arrays, so a missing array is equivalent to an empty one.
+  pub(crate) fn get_array(&self, name: &str) -> Option<&[Value]> {
+    match self.get(name) {
+      None => Some(&[]),
+      Some(value) => value.as_array(),
+    }
+  }
+
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    // Empty arrays are omitted, as their type can't be determined
+    let fields = self
+      .0
+      .iter()
+      .filter(|(_, value)| value.as_array().map(|array| !array.is_empty()).unwrap_or(true))
+      .collect::<Vec<_>>();
+
+    write_epee_vi(u64::try_from(fields.len()).unwrap(), writer)?;
+    for (name, value) in fields {
+      writer.write_all(&[u8::try_from(name.len()).map_err(|_| err("field name too long"))?])?;
+      writer.write_all(name)?;
+      if let Value::Array(array) = value {
+        let kind = array[0].kind()?;
+        writer.write_all(&[kind | ARRAY_FLAG])?;
+        write_epee_vi(u64::try_from(array.len()).unwrap(), writer)?;
+        for value in array {
+          if value.kind()? != kind {
+            Err(err("array had values of differing types"))?;
+          }
+          value.write(writer)?;
+        }
+      } else {
+        writer.write_all(&[value.kind()?])?;
+        value.write(writer)?;
+      }
+    }
+    Ok(())
+  }
+
+  fn read<R: Read>(reader: &mut R, depth: usize) -> io::Result<Object> {
+    if depth > MAX_DEPTH {
+      Err(err("objects were nested too deeply"))?;
+    }
+
+    let mut res = Object::new();
+    for _ in 0 .. read_epee_vi(reader)? {
+      let name_len = read_byte(reader)?;
+      let name = read_raw_vec(read_byte, name_len.into(), reader)?;
+
+      let kind = read_byte(reader)?;
+      let value = if (kind & ARRAY_FLAG) == ARRAY_FLAG {
+        let mut array = vec![];
+        for _ in 0 .. read_epee_vi(reader)? {
+          array.push(Value::read(reader, kind & (!ARRAY_FLAG), depth)?);
+        }
+        Value::Array(array)
+      } else {
+        Value::read(reader, kind, depth)?
+      };
+      res.0.push((name, value));
+    }
+    Ok(res)
+  }
+
+  /// Serialize this object as an EPEE document.
+  pub(crate) fn serialize(&self) -> io::Result<Vec<u8>> {
+    let mut res = HEADER.to_vec();
+    self.write(&mut res)?;
+    Ok(res)
+  }
+
+  /// Read an EPEE document.
+  pub(crate) fn deserialize(mut document: &[u8]) -> io::Result<Object> {
+    if read_bytes::<_, { HEADER.len() }>(&mut document)? != HEADER {
+      Err(err("invalid header"))?;
+    }
+    let res = Object::read(&mut document, 0)?;
+    if !document.is_empty() {
+      Err(err("trailing bytes"))?;
+    }
+    Ok(res)
+  }
+}
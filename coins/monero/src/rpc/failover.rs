@@ -0,0 +1,161 @@
+use core::{
+  sync::atomic::{AtomicUsize, Ordering},
+  time::Duration,
+};
+use std::{
+  sync::{Arc, Mutex},
+  time::Instant,
+};
+
+use async_trait::async_trait;
+
+use tokio::time::{sleep, timeout};
+
+use crate::rpc::{RpcError, RpcConnection, Rpc, HttpRpc};
+
+// How long to wait for a node to respond before considering it failed
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+// How long a failed node is initially skipped for, doubled with each consecutive failure
+const MIN_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// How many times a request is attempted, per node, before it's considered failed
+const ATTEMPTS_PER_NODE: usize = 3;
+
+#[derive(Default, Debug)]
+struct Health {
+  // The amount of consecutive failures
+  failures: u32,
+  // When this node may be used again, if it's currently backing off
+  retry_at: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct Node<R: RpcConnection> {
+  connection: R,
+  health: Mutex<Health>,
+}
+
+/// A RPC connection to multiple daemons, failing over between them.
+///
+/// Requests are sent to a single daemon until it times out or fails to respond, at which point
+/// the next daemon is used. Failed daemons are skipped for an exponentially increasing amount of
+/// time, and if every daemon is failing, requests wait for the soonest one to be retried.
+///
+/// Only connection failures are retried. A daemon which responds with an error is trusted to be
+/// correct.
+#[derive(Clone, Debug)]
+pub struct FailoverRpc<R: RpcConnection + Send + Sync> {
+  nodes: Arc<Vec<Node<R>>>,
+  // The index of the node currently preferred
+  current: Arc<AtomicUsize>,
+}
+
+impl FailoverRpc<HttpRpc> {
+  /// Create a new RPC connection to the daemons at the specified URLs, in order of preference.
+  ///
+  /// A daemon requiring authentication can be used via including the username and password in its
+  /// URL.
+  pub fn new(urls: Vec<String>) -> Result<Rpc<FailoverRpc<HttpRpc>>, RpcError> {
+    FailoverRpc::from_connections(
+      urls.into_iter().map(HttpRpc::new).collect::<Result<Vec<_>, _>>()?,
+    )
+  }
+
+  /// Create a new RPC connection to the daemons at the specified URLs, in order of preference,
+  /// routed through the SOCKS5 proxy at the specified address.
+  pub fn new_with_proxy(
+    urls: Vec<String>,
+    proxy: &str,
+  ) -> Result<Rpc<FailoverRpc<HttpRpc>>, RpcError> {
+    // Share a single client, and accordingly its connection to the proxy, across every daemon
+    let client = HttpRpc::proxied_client(proxy)?;
+    FailoverRpc::from_connections(
+      urls
+        .into_iter()
+        .map(|url| HttpRpc::with_client(url, client.clone()))
+        .collect::<Result<Vec<_>, _>>()?,
+    )
+  }
+}
+
+impl<R: RpcConnection + Send + Sync> FailoverRpc<R> {
+  /// Create a new RPC connection failing over between the specified connections, in order of
+  /// preference.
+  pub fn from_connections(connections: Vec<Rpc<R>>) -> Result<Rpc<FailoverRpc<R>>, RpcError> {
+    if connections.is_empty() {
+      Err(RpcError::InvalidNode)?;
+    }
+    Ok(Rpc(FailoverRpc {
+      nodes: Arc::new(
+        connections
+          .into_iter()
+          .map(|rpc| Node { connection: rpc.0, health: Mutex::new(Health::default()) })
+          .collect(),
+      ),
+      current: Arc::new(AtomicUsize::new(0)),
+    }))
+  }
+
+  // Get the node to use, waiting for one to finish backing off if every node recently failed
+  async fn node(&self) -> usize {
+    loop {
+      let now = Instant::now();
+      let start = self.current.load(Ordering::Relaxed);
+      let mut soonest: Option<Instant> = None;
+      for i in (start .. self.nodes.len()).chain(0 .. start) {
+        let retry_at = self.nodes[i].health.lock().unwrap().retry_at;
+        match retry_at {
+          Some(retry_at) if retry_at > now => {
+            soonest = Some(soonest.map_or(retry_at, |soonest| soonest.min(retry_at)));
+          }
+          _ => return i,
+        }
+      }
+      sleep(soonest.unwrap() - now).await;
+    }
+  }
+
+  fn succeeded(&self, i: usize) {
+    *self.nodes[i].health.lock().unwrap() = Health::default();
+    self.current.store(i, Ordering::Relaxed);
+  }
+
+  fn failed(&self, i: usize) {
+    let mut health = self.nodes[i].health.lock().unwrap();
+    let backoff = MIN_BACKOFF.saturating_mul(2u32.saturating_pow(health.failures)).min(MAX_BACKOFF);
+    health.failures = health.failures.saturating_add(1);
+    health.retry_at = Some(Instant::now() + backoff);
+    drop(health);
+
+    // Move on to the next node, unless another request already did
+    let _ = self.current.compare_exchange(
+      i,
+      (i + 1) % self.nodes.len(),
+      Ordering::Relaxed,
+      Ordering::Relaxed,
+    );
+  }
+}
+
+#[async_trait]
+impl<R: RpcConnection + Send + Sync> RpcConnection for FailoverRpc<R> {
+  async fn post(&self, route: &str, body: Vec<u8>) -> Result<Vec<u8>, RpcError> {
+    let mut res = Err(RpcError::ConnectionError);
+    for _ in 0 .. (self.nodes.len() * ATTEMPTS_PER_NODE) {
+      let i = self.node().await;
+      res = timeout(REQUEST_TIMEOUT, self.nodes[i].connection.post(route, body.clone()))
+        .await
+        .unwrap_or(Err(RpcError::ConnectionError));
+      match res {
+        Ok(_) => {
+          self.succeeded(i);
+          break;
+        }
+        // The HTTP RPC returns InvalidNode if it fails to perform authentication
+        Err(RpcError::ConnectionError | RpcError::InvalidNode) => self.failed(i),
+        Err(_) => break,
+      }
+    }
+    res
+  }
+}
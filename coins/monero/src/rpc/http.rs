@@ -1,14 +1,47 @@
+use std::sync::{Arc, Mutex};
+
 use async_trait::async_trait;
 
-use digest_auth::AuthContext;
-use reqwest::Client;
+use digest_auth::{AuthContext, WwwAuthenticateHeader};
+use reqwest::{
+  Client, Proxy, StatusCode,
+  header::{HeaderMap, WWW_AUTHENTICATE},
+};
 
 use crate::rpc::{RpcError, RpcConnection, Rpc};
 
+// The authentication challenge last sent by the daemon
+#[derive(Clone, Debug)]
+enum Challenge {
+  Basic,
+  Digest(WwwAuthenticateHeader),
+}
+
+impl Challenge {
+  // Parse the challenge from a response, preferring digest authentication
+  fn from_headers(headers: &HeaderMap) -> Result<Challenge, RpcError> {
+    let mut basic = false;
+    for header in headers.get_all(WWW_AUTHENTICATE) {
+      let header = header.to_str().map_err(|_| RpcError::InvalidNode)?;
+      if let Ok(digest) = digest_auth::parse(header) {
+        return Ok(Challenge::Digest(digest));
+      }
+      basic |= header.trim_start().to_lowercase().starts_with("basic");
+    }
+    if basic {
+      Ok(Challenge::Basic)
+    } else {
+      Err(RpcError::InvalidNode)
+    }
+  }
+}
+
 #[derive(Clone, Debug)]
 pub struct HttpRpc {
   client: Client,
   userpass: Option<(String, String)>,
+  // Reused across requests, so the daemon doesn't have to be challenged for every request
+  challenge: Arc<Mutex<Option<Challenge>>>,
   url: String,
 }
 
@@ -16,8 +49,29 @@ impl HttpRpc {
   /// Create a new HTTP(S) RPC connection.
   ///
   /// A daemon requiring authentication can be used via including the username and password in the
-  /// URL.
-  pub fn new(mut url: String) -> Result<Rpc<HttpRpc>, RpcError> {
+  /// URL. Both digest authentication, as used by monerod's `--rpc-login`, and basic authentication
+  /// are supported.
+  pub fn new(url: String) -> Result<Rpc<HttpRpc>, RpcError> {
+    Self::with_client(url, Client::new())
+  }
+
+  /// Create a new HTTP(S) RPC connection routed through the SOCKS5 proxy at the specified address,
+  /// such as Tor's `127.0.0.1:9050`.
+  ///
+  /// The daemon's hostname is resolved by the proxy, allowing connecting to `.onion` daemons and
+  /// preventing DNS requests from leaking.
+  pub fn new_with_proxy(url: String, proxy: &str) -> Result<Rpc<HttpRpc>, RpcError> {
+    Self::with_client(url, Self::proxied_client(proxy)?)
+  }
+
+  pub(crate) fn proxied_client(proxy: &str) -> Result<Client, RpcError> {
+    Client::builder()
+      .proxy(Proxy::all(format!("socks5h://{proxy}")).map_err(|_| RpcError::InvalidNode)?)
+      .build()
+      .map_err(|_| RpcError::InvalidNode)
+  }
+
+  pub(crate) fn with_client(mut url: String, client: Client) -> Result<Rpc<HttpRpc>, RpcError> {
     // Parse out the username and password
     let userpass = if url.contains('@') {
       let url_clone = url;
@@ -47,45 +101,52 @@ impl HttpRpc {
       None
     };
 
-    Ok(Rpc(HttpRpc { client: Client::new(), userpass, url }))
+    Ok(Rpc(HttpRpc { client, userpass, challenge: Arc::new(Mutex::new(None)), url }))
   }
 }
 
 #[async_trait]
 impl RpcConnection for HttpRpc {
   async fn post(&self, route: &str, body: Vec<u8>) -> Result<Vec<u8>, RpcError> {
-    let mut builder = self.client.post(self.url.clone() + "/" + route).body(body);
-
-    if let Some((user, pass)) = &self.userpass {
-      let req = self.client.post(&self.url).send().await.map_err(|_| RpcError::InvalidNode)?;
-      // Only provide authentication if this daemon actually expects it
-      if let Some(header) = req.headers().get("www-authenticate") {
-        builder = builder.header(
-          "Authorization",
-          digest_auth::parse(header.to_str().map_err(|_| RpcError::InvalidNode)?)
-            .map_err(|_| RpcError::InvalidNode)?
-            .respond(&AuthContext::new_post::<_, _, _, &[u8]>(
-              user,
-              pass,
-              "/".to_string() + route,
-              None,
-            ))
-            .map_err(|_| RpcError::InvalidNode)?
-            .to_header_string(),
-        );
+    // If we're unauthorized, the challenge is updated and the request retried once
+    // This handles the initial request and the daemon expiring the challenge's nonce
+    for retry in [false, true] {
+      let mut builder = self.client.post(self.url.clone() + "/" + route).body(body.clone());
+
+      if let Some((user, pass)) = &self.userpass {
+        let mut challenge = self.challenge.lock().unwrap();
+        match challenge.as_mut() {
+          // Until we've been challenged, don't provide authentication as it may not be needed
+          None => {}
+          Some(Challenge::Basic) => builder = builder.basic_auth(user, Some(pass)),
+          Some(Challenge::Digest(digest)) => {
+            builder = builder.header(
+              "Authorization",
+              digest
+                .respond(&AuthContext::new_post::<_, _, _, &[u8]>(
+                  user,
+                  pass,
+                  "/".to_string() + route,
+                  None,
+                ))
+                .map_err(|_| RpcError::InvalidNode)?
+                .to_header_string(),
+            );
+          }
+        }
+      }
+
+      let res = builder.send().await.map_err(|_| RpcError::ConnectionError)?;
+      if (res.status() == StatusCode::UNAUTHORIZED) && self.userpass.is_some() {
+        if retry {
+          Err(RpcError::InvalidNode)?;
+        }
+        *self.challenge.lock().unwrap() = Some(Challenge::from_headers(res.headers())?);
+        continue;
       }
-    }
 
-    Ok(
-      builder
-        .send()
-        .await
-        .map_err(|_| RpcError::ConnectionError)?
-        .bytes()
-        .await
-        .map_err(|_| RpcError::ConnectionError)?
-        .slice(..)
-        .to_vec(),
-    )
+      return Ok(res.bytes().await.map_err(|_| RpcError::ConnectionError)?.slice(..).to_vec());
+    }
+    unreachable!()
   }
 }
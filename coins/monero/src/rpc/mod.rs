@@ -1,18 +1,14 @@
 use core::fmt::Debug;
 #[cfg(not(feature = "std"))]
 use alloc::boxed::Box;
-use std_shims::{
-  vec::Vec,
-  io,
-  string::{String, ToString},
-};
+use std_shims::{vec::Vec, string::String};
 
 use async_trait::async_trait;
 
 use curve25519_dalek::edwards::{EdwardsPoint, CompressedEdwardsY};
 
 use serde::{Serialize, Deserialize, de::DeserializeOwned};
-use serde_json::{Value, json};
+use serde_json::json;
 
 use crate::{
   Protocol,
@@ -22,10 +18,17 @@ use crate::{
   wallet::{FeePriority, Fee},
 };
 
+pub(crate) mod epee;
+use epee::{Value, Object};
+
 #[cfg(feature = "http_rpc")]
 mod http;
 #[cfg(feature = "http_rpc")]
 pub use http::*;
+#[cfg(feature = "http_rpc")]
+mod failover;
+#[cfg(feature = "http_rpc")]
+pub use failover::*;
 
 // Number of blocks the fee estimate will be valid for
 // https://github.com/monero-project/monero/blob/94e67bf96bbc010241f29ada6abc89f49a81759c/
@@ -75,6 +78,8 @@ pub enum RpcError {
   InvalidFee,
   #[cfg_attr(feature = "std", error("invalid priority"))]
   InvalidPriority,
+  #[cfg_attr(feature = "std", error("not enough decoys available"))]
+  NotEnoughDecoys,
 }
 
 fn rpc_hex(value: &str) -> Result<Vec<u8>, RpcError> {
@@ -85,31 +90,11 @@ fn hash_hex(hash: &str) -> Result<[u8; 32], RpcError> {
   rpc_hex(hash)?.try_into().map_err(|_| RpcError::InvalidNode)
 }
 
-fn rpc_point(point: &str) -> Result<EdwardsPoint, RpcError> {
-  CompressedEdwardsY(
-    rpc_hex(point)?.try_into().map_err(|_| RpcError::InvalidPoint(point.to_string()))?,
-  )
-  .decompress()
-  .ok_or_else(|| RpcError::InvalidPoint(point.to_string()))
-}
-
-// Read an EPEE VarInt, distinct from the VarInts used throughout the rest of the protocol
-fn read_epee_vi<R: io::Read>(reader: &mut R) -> io::Result<u64> {
-  let vi_start = read_byte(reader)?;
-  let len = match vi_start & 0b11 {
-    0 => 1,
-    1 => 2,
-    2 => 4,
-    3 => 8,
-    _ => unreachable!(),
-  };
-  let mut vi = u64::from(vi_start >> 2);
-  for i in 1 .. len {
-    vi |= u64::from(read_byte(reader)?) << (((i - 1) * 8) + 6);
-  }
-  Ok(vi)
-}
-
+/// A transport for the daemon's RPC.
+///
+/// `HttpRpc` is provided behind the `http_rpc` feature. Alternative HTTP clients, proxies, or
+/// in-memory mocks for tests can be used by implementing this trait and passing the connection to
+/// `Rpc::new`.
 #[async_trait]
 pub trait RpcConnection: Clone + Debug {
   /// Perform a POST request to the specified route with the specified body.
@@ -120,8 +105,18 @@ pub trait RpcConnection: Clone + Debug {
 
 // TODO: Make this provided methods for RpcConnection?
 #[derive(Clone, Debug)]
-pub struct Rpc<R: RpcConnection>(R);
+pub struct Rpc<R: RpcConnection>(pub(crate) R);
 impl<R: RpcConnection> Rpc<R> {
+  /// Create a new RPC instance over the specified connection.
+  pub fn new(connection: R) -> Rpc<R> {
+    Rpc(connection)
+  }
+
+  /// The connection this RPC instance uses.
+  pub fn connection(&self) -> &R {
+    &self.0
+  }
+
   /// Perform a RPC call to the specified route with the provided parameters.
   ///
   /// This is NOT a JSON-RPC call. They use a route of "json_rpc" and are available via
@@ -154,7 +149,7 @@ impl<R: RpcConnection> Rpc<R> {
   pub async fn json_rpc_call<Response: DeserializeOwned + Debug>(
     &self,
     method: &str,
-    params: Option<Value>,
+    params: Option<serde_json::Value>,
   ) -> Result<Response, RpcError> {
     let mut req = json!({ "method": method });
     if let Some(params) = params {
@@ -168,6 +163,18 @@ impl<R: RpcConnection> Rpc<R> {
     self.0.post(route, params).await
   }
 
+  // Perform a binary call with EPEE-encoded parameters, checking the response's status
+  async fn epee_call(&self, route: &str, params: Object) -> Result<Object, RpcError> {
+    let params =
+      params.serialize().map_err(|_| RpcError::InternalError("couldn't serialize EPEE request"))?;
+    let res = Object::deserialize(&self.bin_call(route, params).await?)
+      .map_err(|_| RpcError::InvalidNode)?;
+    if res.get("status").and_then(Value::as_bytes) != Some(b"OK".as_slice()) {
+      Err(RpcError::InvalidNode)?;
+    }
+    Ok(res)
+  }
+
   /// Get the active blockchain protocol version.
   pub async fn get_protocol(&self) -> Result<Protocol, RpcError> {
     #[derive(Deserialize, Debug)]
@@ -180,18 +187,15 @@ impl<R: RpcConnection> Rpc<R> {
       block_header: ProtocolResponse,
     }
 
-    Ok(
-      match self
-        .json_rpc_call::<LastHeaderResponse>("get_last_block_header", None)
-        .await?
-        .block_header
-        .major_version
-      {
-        13 | 14 => Protocol::v14,
-        15 | 16 => Protocol::v16,
-        protocol => Err(RpcError::UnsupportedProtocol(protocol))?,
-      },
-    )
+    let version = self
+      .json_rpc_call::<LastHeaderResponse>("get_last_block_header", None)
+      .await?
+      .block_header
+      .major_version;
+    u64::try_from(version)
+      .ok()
+      .and_then(Protocol::from_hard_fork)
+      .ok_or(RpcError::UnsupportedProtocol(version))
   }
 
   pub async fn get_height(&self) -> Result<usize, RpcError> {
@@ -330,173 +334,106 @@ impl<R: RpcConnection> Rpc<R> {
     Ok(res)
   }
 
-  pub async fn get_block_transactions_by_number(
+  /// Get a range of blocks, and their transactions, not including the miner transactions, by the
+  /// number of the first block and the amount of blocks.
+  ///
+  /// This uses a single binary call per 100 blocks, unlike fetching each block and then its
+  /// transactions.
+  pub async fn get_blocks_range(
     &self,
-    number: usize,
-  ) -> Result<Vec<Transaction>, RpcError> {
-    self.get_block_transactions(self.get_block_hash(number).await?).await
-  }
-
-  /// Get the output indexes of the specified transaction.
-  pub async fn get_o_indexes(&self, hash: [u8; 32]) -> Result<Vec<u64>, RpcError> {
-    /*
-    TODO: Use these when a suitable epee serde lib exists
-
-    #[derive(Serialize, Debug)]
-    struct Request {
-      txid: [u8; 32],
-    }
+    start: usize,
+    count: usize,
+  ) -> Result<Vec<(Block, Vec<Transaction>)>, RpcError> {
+    // Bound the size of each response, which monerod will send at once
+    const BLOCKS_PER_REQUEST: usize = 100;
+
+    let mut res = Vec::with_capacity(count);
+    let mut numbers = (start .. (start + count)).peekable();
+    while numbers.peek().is_some() {
+      let numbers = numbers.by_ref().take(BLOCKS_PER_REQUEST).collect::<Vec<_>>();
+      let blocks = self
+        .epee_call(
+          "get_blocks_by_height.bin",
+          Object::new().with(
+            "heights",
+            Value::Array(
+              numbers.iter().map(|number| Value::Uint(u64::try_from(*number).unwrap())).collect(),
+            ),
+          ),
+        )
+        .await?;
 
-    #[derive(Deserialize, Debug)]
-    struct OIndexes {
-      o_indexes: Vec<u64>,
-    }
-    */
-
-    // Given the immaturity of Rust epee libraries, this is a homegrown one which is only validated
-    // to work against this specific function
-
-    // Header for EPEE, an 8-byte magic and a version
-    const EPEE_HEADER: &[u8] = b"\x01\x11\x01\x01\x01\x01\x02\x01\x01";
-
-    let mut request = EPEE_HEADER.to_vec();
-    // Number of fields (shifted over 2 bits as the 2 LSBs are reserved for metadata)
-    request.push(1 << 2);
-    // Length of field name
-    request.push(4);
-    // Field name
-    request.extend(b"txid");
-    // Type of field
-    request.push(10);
-    // Length of string, since this byte array is technically a string
-    request.push(32 << 2);
-    // The "string"
-    request.extend(hash);
-
-    let indexes_buf = self.bin_call("get_o_indexes.bin", request).await?;
-    let mut indexes: &[u8] = indexes_buf.as_ref();
-
-    (|| {
-      let mut res = None;
-      let mut is_okay = false;
-
-      if read_bytes::<_, { EPEE_HEADER.len() }>(&mut indexes)? != EPEE_HEADER {
-        Err(io::Error::new(io::ErrorKind::Other, "invalid header"))?;
+      let blocks = blocks.get_array("blocks").ok_or(RpcError::InvalidNode)?;
+      if blocks.len() != numbers.len() {
+        Err(RpcError::InvalidNode)?;
       }
+      for (entry, number) in blocks.iter().zip(numbers) {
+        let entry = entry.as_object().ok_or(RpcError::InvalidNode)?;
 
-      let read_object = |reader: &mut &[u8]| -> io::Result<Vec<u64>> {
-        let fields = read_byte(reader)? >> 2;
-
-        for _ in 0 .. fields {
-          let name_len = read_byte(reader)?;
-          let name = read_raw_vec(read_byte, name_len.into(), reader)?;
-
-          let type_with_array_flag = read_byte(reader)?;
-          let kind = type_with_array_flag & (!0x80);
-
-          let iters = if type_with_array_flag != kind { read_epee_vi(reader)? } else { 1 };
-
-          if (&name == b"o_indexes") && (kind != 5) {
-            Err(io::Error::new(io::ErrorKind::Other, "o_indexes weren't u64s"))?;
-          }
-
-          let f = match kind {
-            // i64
-            1 => |reader: &mut &[u8]| read_raw_vec(read_byte, 8, reader),
-            // i32
-            2 => |reader: &mut &[u8]| read_raw_vec(read_byte, 4, reader),
-            // i16
-            3 => |reader: &mut &[u8]| read_raw_vec(read_byte, 2, reader),
-            // i8
-            4 => |reader: &mut &[u8]| read_raw_vec(read_byte, 1, reader),
-            // u64
-            5 => |reader: &mut &[u8]| read_raw_vec(read_byte, 8, reader),
-            // u32
-            6 => |reader: &mut &[u8]| read_raw_vec(read_byte, 4, reader),
-            // u16
-            7 => |reader: &mut &[u8]| read_raw_vec(read_byte, 2, reader),
-            // u8
-            8 => |reader: &mut &[u8]| read_raw_vec(read_byte, 1, reader),
-            // double
-            9 => |reader: &mut &[u8]| read_raw_vec(read_byte, 8, reader),
-            // string, or any collection of bytes
-            10 => |reader: &mut &[u8]| {
-              let len = read_epee_vi(reader)?;
-              read_raw_vec(
-                read_byte,
-                len
-                  .try_into()
-                  .map_err(|_| io::Error::new(io::ErrorKind::Other, "u64 length exceeded usize"))?,
-                reader,
-              )
-            },
-            // bool
-            11 => |reader: &mut &[u8]| read_raw_vec(read_byte, 1, reader),
-            // object, errors here as it shouldn't be used on this call
-            12 => |_: &mut &[u8]| {
-              Err(io::Error::new(
-                io::ErrorKind::Other,
-                "node used object in reply to get_o_indexes",
-              ))
-            },
-            // array, so far unused
-            13 => |_: &mut &[u8]| {
-              Err(io::Error::new(io::ErrorKind::Other, "node used the unused array type"))
-            },
-            _ => {
-              |_: &mut &[u8]| Err(io::Error::new(io::ErrorKind::Other, "node used an invalid type"))
-            }
-          };
-
-          let mut bytes_res = vec![];
-          for _ in 0 .. iters {
-            bytes_res.push(f(reader)?);
-          }
+        let block = Block::read::<&[u8]>(
+          &mut entry.get("block").and_then(Value::as_bytes).ok_or(RpcError::InvalidNode)?,
+        )
+        .map_err(|_| RpcError::InvalidNode)?;
+        // Make sure this is actually the block for this number
+        match block.miner_tx.prefix.inputs.first() {
+          Some(Input::Gen(actual)) if *actual == u64::try_from(number).unwrap() => {}
+          _ => Err(RpcError::InvalidNode)?,
+        }
 
-          let mut actual_res = Vec::with_capacity(bytes_res.len());
-          match name.as_slice() {
-            b"o_indexes" => {
-              for o_index in bytes_res {
-                actual_res.push(u64::from_le_bytes(o_index.try_into().map_err(|_| {
-                  io::Error::new(io::ErrorKind::Other, "node didn't provide 8 bytes for a u64")
-                })?));
-              }
-              res = Some(actual_res);
-            }
-            b"status" => {
-              if bytes_res
-                .get(0)
-                .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "status wasn't a string"))?
-                .as_slice() !=
-                b"OK"
-              {
-                // TODO: Better handle non-OK responses
-                Err(io::Error::new(io::ErrorKind::Other, "response wasn't OK"))?;
-              }
-              is_okay = true;
+        let txs = entry.get_array("txs").ok_or(RpcError::InvalidNode)?;
+        if txs.len() != block.txs.len() {
+          Err(RpcError::InvalidNode)?;
+        }
+        let txs = txs
+          .iter()
+          .zip(&block.txs)
+          .map(|(tx, hash)| {
+            let tx = Transaction::read::<&[u8]>(&mut tx.as_bytes().ok_or(RpcError::InvalidNode)?)
+              .map_err(|_| RpcError::InvalidTransaction(*hash))?;
+            // A pruned node may return pruned transactions, which won't have the expected hash
+            if tx.hash() != *hash {
+              Err(RpcError::InvalidNode)?;
             }
-            _ => continue,
-          }
+            Ok(tx)
+          })
+          .collect::<Result<_, _>>()?;
 
-          if is_okay && res.is_some() {
-            break;
-          }
-        }
+        res.push((block, txs));
+      }
+    }
+    Ok(res)
+  }
 
-        // Didn't return a response with a status
-        // (if the status wasn't okay, we would've already errored)
-        if !is_okay {
-          Err(io::Error::new(io::ErrorKind::Other, "response didn't contain a status"))?;
-        }
+  /// Get a block and its transactions, not including the miner transaction, by the block's number.
+  pub async fn get_block_with_transactions_by_number(
+    &self,
+    number: usize,
+  ) -> Result<(Block, Vec<Transaction>), RpcError> {
+    self.get_blocks_range(number, 1).await.map(|mut blocks| blocks.swap_remove(0))
+  }
 
-        // If the Vec was empty, it would've been omitted, hence the unwrap_or
-        // TODO: Test against a 0-output TX, such as the ones found in block 202612
-        Ok(res.unwrap_or(vec![]))
-      };
+  pub async fn get_block_transactions_by_number(
+    &self,
+    number: usize,
+  ) -> Result<Vec<Transaction>, RpcError> {
+    let (block, txs) = self.get_block_with_transactions_by_number(number).await?;
+    let mut res = vec![block.miner_tx];
+    res.extend(txs);
+    Ok(res)
+  }
 
-      read_object(&mut indexes)
-    })()
-    .map_err(|_| RpcError::InvalidNode)
+  /// Get the output indexes of the specified transaction.
+  pub async fn get_o_indexes(&self, hash: [u8; 32]) -> Result<Vec<u64>, RpcError> {
+    let res = self
+      .epee_call("get_o_indexes.bin", Object::new().with("txid", Value::String(hash.to_vec())))
+      .await?;
+    // TODO: Test against a 0-output TX, such as the ones found in block 202612
+    res
+      .get_array("o_indexes")
+      .ok_or(RpcError::InvalidNode)?
+      .iter()
+      .map(|index| index.as_u64().ok_or(RpcError::InvalidNode))
+      .collect()
   }
 
   /// Get the output distribution, from the specified height to the specified height (both
@@ -506,30 +443,41 @@ impl<R: RpcConnection> Rpc<R> {
     from: usize,
     to: usize,
   ) -> Result<Vec<u64>, RpcError> {
-    #[derive(Deserialize, Debug)]
-    struct Distribution {
-      distribution: Vec<u64>,
-    }
-
-    #[derive(Deserialize, Debug)]
-    struct Distributions {
-      distributions: Vec<Distribution>,
-    }
-
-    let mut distributions: Distributions = self
-      .json_rpc_call(
-        "get_output_distribution",
-        Some(json!({
-          "binary": false,
-          "amounts": [0],
-          "cumulative": true,
-          "from_height": from,
-          "to_height": to,
-        })),
+    let res = self
+      .epee_call(
+        "get_output_distribution.bin",
+        Object::new()
+          .with("amounts", Value::Array(vec![Value::Uint(0)]))
+          .with("from_height", Value::Uint(u64::try_from(from).unwrap()))
+          .with("to_height", Value::Uint(u64::try_from(to).unwrap()))
+          .with("cumulative", Value::Bool(true))
+          .with("binary", Value::Bool(true))
+          .with("compress", Value::Bool(true)),
       )
       .await?;
 
-    Ok(distributions.distributions.swap_remove(0).distribution)
+    let distribution = res
+      .get_array("distributions")
+      .and_then(|distributions| distributions.first())
+      .and_then(Value::as_object)
+      .ok_or(RpcError::InvalidNode)?;
+
+    // If compressed, the distribution is a series of VarInts. If not, it's a series of u64s
+    if let Some(compressed) = distribution.get("compressed_data") {
+      let mut compressed = compressed.as_bytes().ok_or(RpcError::InvalidNode)?;
+      let mut res = vec![];
+      while !compressed.is_empty() {
+        res.push(read_varint(&mut compressed).map_err(|_| RpcError::InvalidNode)?);
+      }
+      Ok(res)
+    } else {
+      let raw =
+        distribution.get("distribution").and_then(Value::as_bytes).ok_or(RpcError::InvalidNode)?;
+      if (raw.len() % 8) != 0 {
+        Err(RpcError::InvalidNode)?;
+      }
+      Ok(raw.chunks(8).map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap())).collect())
+    }
   }
 
   /// Get the specified outputs from the RingCT (zero-amount) pool, but only return them if their
@@ -542,44 +490,78 @@ impl<R: RpcConnection> Rpc<R> {
     indexes: &[u64],
     height: usize,
   ) -> Result<Vec<Option<[EdwardsPoint; 2]>>, RpcError> {
-    #[derive(Deserialize, Debug)]
-    struct Out {
-      key: String,
-      mask: String,
-      txid: String,
-    }
+    Ok(
+      self
+        .get_unlocked_outputs_with_coinbase(indexes, height)
+        .await?
+        .into_iter()
+        .map(|output| output.map(|(output, _)| output))
+        .collect(),
+    )
+  }
 
-    #[derive(Deserialize, Debug)]
-    struct Outs {
-      outs: Vec<Out>,
+  /// Get the specified outputs, as with `get_unlocked_outputs`, along with if each output was
+  /// created by a coinbase (miner) transaction.
+  pub async fn get_unlocked_outputs_with_coinbase(
+    &self,
+    indexes: &[u64],
+    height: usize,
+  ) -> Result<Vec<Option<([EdwardsPoint; 2], bool)>>, RpcError> {
+    if indexes.is_empty() {
+      return Ok(vec![]);
     }
 
-    let outs: Outs = self
-      .rpc_call(
-        "get_outs",
-        Some(json!({
-          "get_txid": true,
-          "outputs": indexes.iter().map(|o| json!({
-            "amount": 0,
-            "index": o
-          })).collect::<Vec<_>>()
-        })),
+    let res = self
+      .epee_call(
+        "get_outs.bin",
+        Object::new()
+          .with(
+            "outputs",
+            Value::Array(
+              indexes
+                .iter()
+                .map(|index| {
+                  Value::Object(
+                    Object::new().with("amount", Value::Uint(0)).with("index", Value::Uint(*index)),
+                  )
+                })
+                .collect(),
+            ),
+          )
+          .with("get_txid", Value::Bool(true)),
       )
       .await?;
 
-    let txs = self
-      .get_transactions(
-        &outs
-          .outs
-          .iter()
-          .map(|out| rpc_hex(&out.txid)?.try_into().map_err(|_| RpcError::InvalidNode))
-          .collect::<Result<Vec<_>, _>>()?,
-      )
-      .await?;
+    struct Out {
+      key: [u8; 32],
+      mask: [u8; 32],
+      txid: [u8; 32],
+    }
+
+    let outs = res
+      .get_array("outs")
+      .ok_or(RpcError::InvalidNode)?
+      .iter()
+      .map(|out| {
+        let out = out.as_object().ok_or(RpcError::InvalidNode)?;
+        let field = |name| -> Result<[u8; 32], RpcError> {
+          out
+            .get(name)
+            .and_then(Value::as_bytes)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(RpcError::InvalidNode)
+        };
+        Ok(Out { key: field("key")?, mask: field("mask")?, txid: field("txid")? })
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    if outs.len() != indexes.len() {
+      Err(RpcError::InvalidNode)?;
+    }
+
+    let txs = self.get_transactions(&outs.iter().map(|out| out.txid).collect::<Vec<_>>()).await?;
 
     // TODO: https://github.com/serai-dex/serai/issues/104
     outs
-      .outs
       .iter()
       .enumerate()
       .map(|(i, out)| {
@@ -588,17 +570,15 @@ impl<R: RpcConnection> Rpc<R> {
         // Only valid keys can be used in CLSAG proofs, hence the need for re-selection, yet
         // invalid keys may honestly exist on the blockchain
         // Only a recent hard fork checked output keys were valid points
-        let Some(key) = CompressedEdwardsY(
-          hex::decode(&out.key)
-            .map_err(|_| RpcError::InvalidNode)?
-            .try_into()
-            .map_err(|_| RpcError::InvalidNode)?,
-        )
-        .decompress() else {
+        let Some(key) = CompressedEdwardsY(out.key).decompress() else {
           return Ok(None);
         };
+        let mask = CompressedEdwardsY(out.mask)
+          .decompress()
+          .ok_or_else(|| RpcError::InvalidPoint(hex::encode(out.mask)))?;
+        let coinbase = matches!(txs[i].prefix.inputs.first(), Some(Input::Gen(_)));
         Ok(
-          Some([key, rpc_point(&out.mask)?])
+          Some(([key, mask], coinbase))
             .filter(|_| Timelock::Block(height) >= txs[i].prefix.timelock),
         )
       })
@@ -684,6 +664,43 @@ impl<R: RpcConnection> Rpc<R> {
     }
   }
 
+  /// Get the hashes of the transactions in the node's transaction pool.
+  ///
+  /// This is sufficient to detect when a transaction enters, or leaves, the pool.
+  pub async fn get_transaction_pool_hashes(&self) -> Result<Vec<[u8; 32]>, RpcError> {
+    #[derive(Deserialize, Debug)]
+    struct PoolHashesResponse {
+      // Omitted by the node when the pool is empty
+      #[serde(default)]
+      tx_hashes: Vec<String>,
+    }
+
+    let res: PoolHashesResponse =
+      self.rpc_call::<Option<()>, _>("get_transaction_pool_hashes", None).await?;
+    res.tx_hashes.iter().map(|hash| hash_hex(hash)).collect()
+  }
+
+  /// Get the transactions in the node's transaction pool.
+  ///
+  /// These transactions are unconfirmed and may never be included in a block.
+  pub async fn get_transaction_pool(&self) -> Result<Vec<Transaction>, RpcError> {
+    let mut hashes = self.get_transaction_pool_hashes().await?;
+    loop {
+      match self.get_transactions(&hashes).await {
+        Ok(txs) => return Ok(txs),
+        // Transactions may leave the pool while this is executing
+        Err(RpcError::TransactionsNotFound(missed)) => {
+          let len = hashes.len();
+          hashes.retain(|hash| !missed.contains(hash));
+          if hashes.len() == len {
+            Err(RpcError::InvalidNode)?;
+          }
+        }
+        Err(e) => Err(e)?,
+      }
+    }
+  }
+
   pub async fn publish_transaction(&self, tx: &Transaction) -> Result<(), RpcError> {
     #[allow(dead_code)]
     #[derive(Deserialize, Debug)]
@@ -712,6 +729,10 @@ impl<R: RpcConnection> Rpc<R> {
     Ok(())
   }
 
+  /// Mine the specified amount of blocks, paying their miner transactions to the specified
+  /// address.
+  ///
+  /// This is only available on regtest nodes, and returns the hashes of the mined blocks.
   // TODO: Take &Address, not &str?
   pub async fn generate_blocks(
     &self,
@@ -740,4 +761,45 @@ impl<R: RpcConnection> Rpc<R> {
     }
     Ok(blocks)
   }
+
+  /// Remove the specified amount of blocks from the tip of the blockchain, returning the new
+  /// height.
+  ///
+  /// This requires an unrestricted RPC, and is intended for regtest nodes.
+  pub async fn pop_blocks(&self, block_count: usize) -> Result<usize, RpcError> {
+    #[derive(Debug, Deserialize)]
+    struct PopBlocksResponse {
+      status: String,
+      height: usize,
+    }
+
+    let res: PopBlocksResponse =
+      self.rpc_call("pop_blocks", Some(json!({ "nblocks": block_count }))).await?;
+    if res.status != "OK" {
+      Err(RpcError::InvalidNode)?;
+    }
+    Ok(res.height)
+  }
+
+  /// Remove the specified transactions from the node's transaction pool, or every transaction if
+  /// none are specified.
+  ///
+  /// This requires an unrestricted RPC, and is intended for regtest nodes.
+  pub async fn flush_txpool(&self, hashes: &[[u8; 32]]) -> Result<(), RpcError> {
+    #[derive(Debug, Deserialize)]
+    struct FlushResponse {
+      status: String,
+    }
+
+    let res: FlushResponse = self
+      .json_rpc_call(
+        "flush_txpool",
+        Some(json!({ "txids": hashes.iter().map(hex::encode).collect::<Vec<_>>() })),
+      )
+      .await?;
+    if res.status != "OK" {
+      Err(RpcError::InvalidNode)?;
+    }
+    Ok(())
+  }
 }
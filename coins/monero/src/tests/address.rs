@@ -4,9 +4,11 @@ use rand_core::{RngCore, OsRng};
 
 use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY};
 
+use base58_monero::base58::encode_check;
+
 use crate::{
   random_scalar,
-  wallet::address::{Network, AddressType, AddressMeta, MoneroAddress},
+  wallet::address::{Network, AddressType, AddressMeta, AddressError, MoneroAddress},
 };
 
 const SPEND: [u8; 32] = hex!("f8631661f6ab4e6fda310c797330d86e23a682f20d5bc8cc27b18051191f16d7");
@@ -67,6 +69,46 @@ fn subaddress() {
   assert_eq!(addr.to_string(), SUBADDRESS);
 }
 
+#[test]
+fn parse() {
+  for (address, kind) in [
+    (STANDARD, AddressType::Standard),
+    (INTEGRATED, AddressType::Integrated(PAYMENT_ID)),
+    (SUBADDRESS, AddressType::Subaddress),
+  ] {
+    let addr = address.parse::<MoneroAddress>().unwrap();
+    assert_eq!(addr.network(), Network::Mainnet);
+    assert_eq!(addr.kind(), kind);
+    assert_eq!(addr.to_string(), address);
+
+    assert_eq!(
+      MoneroAddress::from_str(Network::Testnet, address),
+      Err(AddressError::DifferentNetwork)
+    );
+  }
+
+  // Changing any character should invalidate the checksum
+  let mut invalid = STANDARD.to_string();
+  invalid.replace_range(10 .. 11, "A");
+  assert_eq!(invalid.parse::<MoneroAddress>(), Err(AddressError::InvalidEncoding));
+
+  let featured_byte = 70;
+  let mut raw = vec![featured_byte];
+  raw.extend(SPEND);
+  raw.extend(VIEW);
+  // Featured addresses must have their features byte
+  assert_eq!(
+    encode_check(&raw).unwrap().parse::<MoneroAddress>(),
+    Err(AddressError::InvalidLength)
+  );
+  // Unknown features should be rejected
+  raw.push(1 << 3);
+  assert_eq!(
+    encode_check(&raw).unwrap().parse::<MoneroAddress>(),
+    Err(AddressError::UnknownFeatures)
+  );
+}
+
 #[test]
 fn featured() {
   for (network, first) in
@@ -0,0 +1,186 @@
+use core::ops::Deref;
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE, traits::Identity, scalar::Scalar, edwards::EdwardsPoint,
+};
+
+use crate::{
+  Commitment, random_scalar,
+  wallet::Decoys,
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction, VerificationError},
+  ringct::{
+    generate_key_image, EncryptedAmount, RctBase, RctPrunable, RctSignatures,
+    clsag::{ClsagInput, Clsag},
+    bulletproofs::Bulletproofs,
+    batch::BatchVerifier,
+  },
+};
+
+const RING_LEN: u64 = 11;
+const REAL: u8 = 3;
+
+// Create a transaction spending 1000 to outputs of 600 and 300, with a fee of 100
+// If the range proof shouldn't be valid, it'll be for distinct commitments
+fn transaction(valid_range_proof: bool) -> (Transaction, Vec<[EdwardsPoint; 2]>) {
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+  let mask = random_scalar(&mut OsRng);
+  let mut ring = vec![];
+  for i in 0 .. RING_LEN {
+    ring.push(if i == u64::from(REAL) {
+      [spend.deref() * ED25519_BASEPOINT_TABLE, Commitment::new(mask, 1000).calculate()]
+    } else {
+      [
+        &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+        Commitment::new(random_scalar(&mut OsRng), 1000).calculate(),
+      ]
+    });
+  }
+  let image = generate_key_image(&spend);
+
+  let commitments = [600, 300]
+    .into_iter()
+    .map(|amount| Commitment::new(random_scalar(&mut OsRng), amount))
+    .collect::<Vec<_>>();
+  let proven = if valid_range_proof {
+    commitments.clone()
+  } else {
+    commitments.iter().map(|commitment| Commitment::new(Scalar::ONE, commitment.amount)).collect()
+  };
+
+  let mut tx = Transaction {
+    prefix: TransactionPrefix {
+      version: 2,
+      timelock: Timelock::None,
+      inputs: vec![Input::ToKey {
+        amount: None,
+        key_offsets: vec![1; ring.len()],
+        key_image: image,
+      }],
+      outputs: (0 .. 2)
+        .map(|_| Output {
+          amount: None,
+          key: (&random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE).compress(),
+          view_tag: Some(0),
+        })
+        .collect(),
+      extra: vec![],
+    },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase {
+        fee: 100,
+        encrypted_amounts: vec![EncryptedAmount::Compact { amount: [0; 8] }; 2],
+        pseudo_outs: vec![],
+        commitments: commitments.iter().map(Commitment::calculate).collect(),
+      },
+      prunable: RctPrunable::Clsag {
+        bulletproofs: Bulletproofs::prove(&mut OsRng, &proven, true).unwrap(),
+        clsags: vec![],
+        pseudo_outs: vec![],
+      },
+    },
+  };
+
+  let (clsag, pseudo_out) = Clsag::sign(
+    &mut OsRng,
+    vec![(
+      spend,
+      image,
+      ClsagInput::new(
+        Commitment::new(mask, 1000),
+        Decoys { i: REAL, offsets: vec![1; ring.len()], ring: ring.clone() },
+      )
+      .unwrap(),
+    )],
+    commitments.iter().map(|commitment| commitment.mask).sum(),
+    tx.signature_hash(),
+  )
+  .swap_remove(0);
+  let RctPrunable::Clsag { clsags, pseudo_outs, .. } = &mut tx.rct_signatures.prunable else {
+    unreachable!()
+  };
+  clsags.push(clsag);
+  pseudo_outs.push(pseudo_out);
+
+  (tx, ring)
+}
+
+#[test]
+fn batch_verify() {
+  let mut verifier = BatchVerifier::new(4);
+  for i in 0 .. 3 {
+    let (tx, ring) = transaction(true);
+    assert!(verifier.queue(&mut OsRng, i, &tx, &[ring]));
+  }
+  assert_eq!(verifier.verify(), Ok(()));
+
+  // A transaction with an invalid range proof should be blamed once the batch is verified
+  let (tx, ring) = transaction(false);
+  assert!(verifier.queue(&mut OsRng, 3, &tx, &[ring]));
+  assert_eq!(verifier.verify(), Err(3));
+
+  let mut verifier = BatchVerifier::new(1);
+  let (tx, ring) = transaction(true);
+
+  // The CLSAG should be verified against the ring
+  let mut wrong_ring = ring.clone();
+  wrong_ring[usize::from(REAL)][0] = &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE;
+  assert!(!verifier.queue(&mut OsRng, 0, &tx, &[wrong_ring]));
+  assert!(!verifier.queue(&mut OsRng, 0, &tx, &[]));
+
+  // The transaction should balance
+  let rings = [ring];
+  let mut unbalanced = tx.clone();
+  unbalanced.rct_signatures.base.fee += 1;
+  assert!(!verifier.queue(&mut OsRng, 0, &unbalanced, &rings));
+
+  assert!(verifier.queue(&mut OsRng, 0, &tx, &rings));
+  assert_eq!(verifier.verify(), Ok(()));
+}
+
+#[test]
+fn verify() {
+  let (tx, ring) = transaction(true);
+  let rings = [ring.clone()];
+  assert_eq!(tx.verify(&mut OsRng, &rings, 0), Ok(()));
+
+  let (invalid, invalid_ring) = transaction(false);
+  assert_eq!(
+    invalid.verify(&mut OsRng, &[invalid_ring], 0),
+    Err(VerificationError::InvalidRangeProof)
+  );
+
+  let mut wrong_ring = ring;
+  wrong_ring[usize::from(REAL)][0] = &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE;
+  assert_eq!(tx.verify(&mut OsRng, &[wrong_ring], 0), Err(VerificationError::InvalidSignatures));
+
+  // The fee of 100 is far too low for any real fee rate
+  assert_eq!(tx.verify(&mut OsRng, &rings, 1), Err(VerificationError::FeeTooLow));
+
+  let mut single_output = tx.clone();
+  single_output.prefix.outputs.pop();
+  assert_eq!(single_output.verify(&mut OsRng, &rings, 0), Err(VerificationError::InvalidOutputs));
+
+  let mut duplicated_output = tx.clone();
+  duplicated_output.prefix.outputs[1].key = duplicated_output.prefix.outputs[0].key;
+  assert_eq!(
+    duplicated_output.verify(&mut OsRng, &rings, 0),
+    Err(VerificationError::DuplicatedOutputKey)
+  );
+
+  let mut identity_image = tx.clone();
+  let Input::ToKey { key_image, .. } = &mut identity_image.prefix.inputs[0] else { unreachable!() };
+  *key_image = EdwardsPoint::identity();
+  assert_eq!(identity_image.verify(&mut OsRng, &rings, 0), Err(VerificationError::InvalidKeyImage));
+
+  // Duplicated key images are rejected as they aren't strictly sorted
+  let mut duplicated_image = tx;
+  duplicated_image.prefix.inputs.push(duplicated_image.prefix.inputs[0].clone());
+  assert_eq!(
+    duplicated_image.verify(&mut OsRng, &rings, 0),
+    Err(VerificationError::UnsortedKeyImages)
+  );
+}
@@ -17,7 +17,7 @@ use crate::{
   wallet::Decoys,
   ringct::{
     generate_key_image,
-    clsag::{ClsagInput, Clsag},
+    clsag::{ClsagError, ClsagInput, Clsag},
   },
 };
 #[cfg(feature = "multisig")]
@@ -77,6 +77,22 @@ fn clsag() {
     )
     .swap_remove(0);
     clsag.verify(&ring, &image, &pseudo_out, &msg).unwrap();
+
+    // The signature should be bound to the message, key image, and ring
+    assert_eq!(clsag.verify(&ring, &image, &pseudo_out, &[2; 32]), Err(ClsagError::InvalidC1));
+    let other_image = generate_key_image(&Zeroizing::new(random_scalar(&mut OsRng)));
+    assert!(clsag.verify(&ring, &other_image, &pseudo_out, &msg).is_err());
+    let mut other_ring = ring.clone();
+    other_ring[usize::try_from(real).unwrap()][1] = ring[0][0];
+    assert!(clsag.verify(&other_ring, &image, &pseudo_out, &msg).is_err());
+
+    // Tampered signatures should be rejected
+    let mut tampered = clsag.clone();
+    tampered.s[0] += Scalar::ONE;
+    assert_eq!(tampered.verify(&ring, &image, &pseudo_out, &msg), Err(ClsagError::InvalidC1));
+    let mut tampered = clsag.clone();
+    tampered.c1 += Scalar::ONE;
+    assert_eq!(tampered.verify(&ring, &image, &pseudo_out, &msg), Err(ClsagError::InvalidC1));
   }
 }
 
@@ -0,0 +1,100 @@
+use core::ops::Deref;
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+
+use crate::{
+  random_scalar, Protocol,
+  ringct::generate_key_image,
+  wallet::{
+    Fee, Change, Decoys, SignableTransaction, TransactionError,
+    cold::{
+      write_outputs, read_outputs, SignedKeyImage, export_key_images, import_key_images,
+      write_key_images, read_key_images,
+    },
+  },
+  tests::input_selection::{spendable_output, address},
+};
+
+#[test]
+fn cold_wallet() {
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+
+  let mut key_images = vec![];
+  let mut outputs = vec![];
+  for i in 0 .. 3 {
+    let offset = random_scalar(&mut OsRng);
+    let key = Zeroizing::new(spend.deref() + offset);
+    key_images.push(generate_key_image(&key));
+    outputs.push(spendable_output(
+      key.deref() * ED25519_BASEPOINT_TABLE,
+      offset,
+      1_000_000 * (i + 1),
+      i,
+    ));
+  }
+
+  // The watch-only wallet exports its outputs
+  let mut serialized = vec![];
+  write_outputs(&outputs, &mut serialized).unwrap();
+  let exported = read_outputs::<&[u8]>(&mut serialized.as_ref()).unwrap();
+  assert_eq!(exported, outputs);
+
+  // The offline signer signs their key images
+  let signed = export_key_images(&mut OsRng, &spend, &exported).unwrap();
+  assert_eq!(
+    export_key_images(&mut OsRng, &Zeroizing::new(random_scalar(&mut OsRng)), &exported),
+    Err(TransactionError::WrongPrivateKey)
+  );
+  let mut serialized = vec![];
+  write_key_images(&signed, &mut serialized).unwrap();
+  let signed = read_key_images::<&[u8]>(&mut serialized.as_ref()).unwrap();
+
+  // The watch-only wallet verifies them
+  assert_eq!(import_key_images(&outputs, &signed), Some(key_images));
+  assert!(import_key_images(&outputs[.. 2], &signed).is_none());
+  assert!(import_key_images(&outputs, &[signed[1], signed[0], signed[2]]).is_none());
+  let other = SignedKeyImage::new(&mut OsRng, &spend, &outputs[1]).unwrap();
+  assert!(other.verify(&outputs[1]));
+  assert!(!other.verify(&outputs[0]));
+
+  // The watch-only wallet creates a transaction, which the offline signer signs
+  let ring_len = Protocol::v16.ring_len();
+  let inputs = outputs
+    .into_iter()
+    .map(|output| {
+      let mut ring = (0 .. ring_len)
+        .map(|_| {
+          [
+            &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+            &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+          ]
+        })
+        .collect::<Vec<_>>();
+      ring[3] = [output.key(), output.commitment().calculate()];
+      (output, Decoys { i: 3, offsets: vec![1; ring_len], ring })
+    })
+    .collect::<Vec<_>>();
+  let payment = (address(), 1_000_000);
+  let change = address();
+  let tx = SignableTransaction::new(
+    Protocol::v16,
+    None,
+    inputs,
+    vec![payment],
+    Some(Change::fingerprintable(change)),
+    vec![b"arbitrary data".to_vec()],
+    Fee { per_weight: 20, mask: 1 },
+  )
+  .unwrap();
+
+  let read = SignableTransaction::read::<&[u8]>(&mut tx.serialize().as_ref()).unwrap();
+  assert_eq!(read, tx);
+  assert_eq!(read.payments(), vec![payment, (change, 6_000_000 - 1_000_000 - tx.fee())]);
+
+  let signed = read.sign(&mut OsRng, &spend).unwrap();
+  assert_eq!(signed.prefix.inputs.len(), 3);
+  assert_eq!(signed.rct_signatures.base.fee, tx.fee());
+}
@@ -0,0 +1,225 @@
+use rand_core::{RngCore, OsRng};
+use rand_distr::{Distribution, Gamma};
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_POINT, scalar::Scalar};
+
+use crate::{
+  serialize::{write_varint, write_point},
+  rpc::RpcError,
+  wallet::{SpendableOutput, CoinbaseDecoys, DecoySelection, Decoys, DecoyCache},
+  tests::input_selection::spendable_output,
+};
+
+// A chain with one output per block
+const HEIGHT: u64 = 20_000;
+const RING_LEN: usize = 16;
+const RINGS: usize = 1000;
+
+// A cache with the specified outputs, from a chain with one output per block
+fn cache_with(outputs: &[u64]) -> DecoyCache {
+  let mut buf = vec![];
+  write_varint(&HEIGHT, &mut buf).unwrap();
+  write_varint(&(HEIGHT + 1), &mut buf).unwrap();
+  for _ in 0 ..= HEIGHT {
+    write_varint(&1, &mut buf).unwrap();
+  }
+  write_varint(&u64::try_from(outputs.len()).unwrap(), &mut buf).unwrap();
+  for i in outputs {
+    write_varint(i, &mut buf).unwrap();
+    write_point(&ED25519_BASEPOINT_POINT, &mut buf).unwrap();
+    write_point(&ED25519_BASEPOINT_POINT, &mut buf).unwrap();
+    buf.push(0);
+  }
+  DecoyCache::read::<&[u8]>(&mut buf.as_ref()).unwrap()
+}
+
+fn cache() -> DecoyCache {
+  cache_with(&(0 ..= HEIGHT).collect::<Vec<_>>())
+}
+
+fn input(global_index: u64) -> SpendableOutput {
+  spendable_output(ED25519_BASEPOINT_POINT, Scalar::ZERO, 1, global_index)
+}
+
+// The global indexes of a ring's members
+fn indexes(decoys: &Decoys) -> Vec<u64> {
+  decoys
+    .offsets
+    .iter()
+    .scan(0, |index, offset| {
+      *index += offset;
+      Some(*index)
+    })
+    .collect()
+}
+
+// The quantiles of the ages, in outputs, of decoys selected as Monero does
+fn expected_quantiles(quantiles: &[f64]) -> Vec<f64> {
+  let gamma = Gamma::<f64>::new(19.28, 1.0 / 1.61).unwrap();
+  let mut ages = vec![];
+  while ages.len() != 100_000 {
+    // The unlock time is removed from the age, with anything younger selected from the most
+    // recent blocks
+    let mut age = gamma.sample(&mut OsRng).exp();
+    if age > 1200.0 {
+      age -= 1200.0;
+    } else {
+      age = (OsRng.next_u64() % 1800) as f64;
+    }
+    // One output is created every 120 seconds
+    let age = age / 120.0;
+    if age < (HEIGHT as f64) {
+      ages.push(age);
+    }
+  }
+  ages.sort_by(|a, b| a.partial_cmp(b).unwrap());
+  quantile(&ages, quantiles)
+}
+
+fn quantile(sorted: &[f64], quantiles: &[f64]) -> Vec<f64> {
+  quantiles.iter().map(|q| sorted[((sorted.len() as f64) * q) as usize]).collect()
+}
+
+fn assert_quantiles(mut ages: Vec<f64>) {
+  const QUANTILES: [f64; 3] = [0.25, 0.5, 0.75];
+  ages.sort_by(|a, b| a.partial_cmp(b).unwrap());
+  for (actual, expected) in quantile(&ages, &QUANTILES).iter().zip(expected_quantiles(&QUANTILES)) {
+    assert!(
+      ((actual - expected).abs() / expected) < 0.15,
+      "decoy age quantile {actual} differed from expected {expected}"
+    );
+  }
+}
+
+#[tokio::test]
+async fn gamma_distribution() {
+  let cache = cache();
+  let real = HEIGHT / 2;
+
+  let mut ages = vec![];
+  for _ in 0 .. RINGS {
+    let decoys = Decoys::select_offline(&mut OsRng, &cache, RING_LEN, &[input(real)])
+      .await
+      .unwrap()
+      .swap_remove(0);
+    let indexes = indexes(&decoys);
+    assert_eq!(indexes.len(), RING_LEN);
+    assert_eq!(indexes[usize::from(decoys.i)], real);
+    for index in indexes {
+      if index != real {
+        ages.push((HEIGHT - index) as f64);
+      }
+    }
+  }
+  assert_quantiles(ages);
+}
+
+#[tokio::test]
+async fn binning_distribution() {
+  const MEMBERS: u8 = 4;
+  const WIDTH: u64 = 20;
+
+  let cache = cache();
+  let real = HEIGHT / 2;
+  let selection = DecoySelection::Binning { members: MEMBERS, width: WIDTH };
+
+  let mut ages = vec![];
+  let mut positions = [0; MEMBERS as usize];
+  for _ in 0 .. RINGS {
+    let decoys = Decoys::select_offline_with_algorithm(
+      &mut OsRng,
+      &cache,
+      RING_LEN,
+      &[input(real)],
+      CoinbaseDecoys::Include,
+      selection,
+    )
+    .await
+    .unwrap()
+    .swap_remove(0);
+    let indexes = indexes(&decoys);
+    assert_eq!(indexes.len(), RING_LEN);
+    assert_eq!(indexes[usize::from(decoys.i)], real);
+
+    // The real spend should be in a bin with other members
+    let bin = indexes.iter().filter(|index| index.abs_diff(real) < WIDTH).count();
+    assert!(bin >= usize::from(MEMBERS));
+
+    // Bins rarely overlap, so the real spend's position within its bin is its position in the
+    // ring modulo the size of each bin
+    positions[usize::from(decoys.i % MEMBERS)] += 1;
+
+    for index in indexes {
+      if index.abs_diff(real) >= WIDTH {
+        ages.push((HEIGHT - index) as f64);
+      }
+    }
+  }
+
+  // The real spend should be uniformly positioned within its bin
+  for position in positions {
+    let frequency = f64::from(position) / (RINGS as f64);
+    assert!((0.15 .. 0.35).contains(&frequency), "real spend's position in bin was biased");
+  }
+
+  // The bins should follow the gamma distribution
+  assert_quantiles(ages);
+
+  // Bins which don't divide the ring length are invalid
+  assert!(Decoys::select_offline_with_algorithm(
+    &mut OsRng,
+    &cache,
+    RING_LEN,
+    &[input(real)],
+    CoinbaseDecoys::Include,
+    DecoySelection::Binning { members: 3, width: WIDTH },
+  )
+  .await
+  .is_err());
+}
+
+#[test]
+fn cache_serialization() {
+  let cache = cache();
+  // The serialization should be deterministic, despite the outputs being held in a HashMap
+  let serialized = cache.serialize();
+  let read = DecoyCache::read::<&[u8]>(&mut serialized.as_ref()).unwrap();
+  assert_eq!(read, cache);
+  assert_eq!(read.serialize(), serialized);
+
+  // A height which would overflow when determining the distribution's length is rejected
+  let mut buf = vec![];
+  write_varint(&u64::MAX, &mut buf).unwrap();
+  write_varint(&0, &mut buf).unwrap();
+  assert!(DecoyCache::read::<&[u8]>(&mut buf.as_ref()).is_err());
+
+  // As is a distribution claiming to be far longer than it is, without allocating its length
+  let mut buf = vec![];
+  write_varint(&(u64::from(u32::MAX) << 16), &mut buf).unwrap();
+  write_varint(&((u64::from(u32::MAX) << 16) + 1), &mut buf).unwrap();
+  write_varint(&1, &mut buf).unwrap();
+  assert!(DecoyCache::read::<&[u8]>(&mut buf.as_ref()).is_err());
+}
+
+#[tokio::test]
+async fn sparse_cache() {
+  // A cache with too few outputs to fill a ring should fail, instead of selecting indefinitely
+  let real = HEIGHT / 2;
+  let cache = cache_with(&[real - 1, real, real + 1]);
+  assert_eq!(
+    Decoys::select_offline(&mut OsRng, &cache, RING_LEN, &[input(real)]).await,
+    Err(RpcError::NotEnoughDecoys)
+  );
+  assert_eq!(
+    Decoys::select_offline_with_algorithm(
+      &mut OsRng,
+      &cache,
+      RING_LEN,
+      &[input(real)],
+      CoinbaseDecoys::Include,
+      DecoySelection::Binning { members: 4, width: 20 },
+    )
+    .await,
+    Err(RpcError::NotEnoughDecoys)
+  );
+}
@@ -0,0 +1,131 @@
+use crate::rpc::epee::{Value, Object, read_epee_vi, write_epee_vi};
+
+const HEADER: &[u8] = b"\x01\x11\x01\x01\x01\x01\x02\x01\x01";
+
+#[test]
+fn epee_varint() {
+  for vi in [0, 63, 64, (1 << 14) - 1, 1 << 14, (1 << 30) - 1, 1 << 30, (1 << 62) - 1] {
+    let mut buf = vec![];
+    write_epee_vi(vi, &mut buf).unwrap();
+    assert_eq!(
+      buf.len(),
+      if vi < (1 << 6) {
+        1
+      } else if vi < (1 << 14) {
+        2
+      } else if vi < (1 << 30) {
+        4
+      } else {
+        8
+      }
+    );
+    assert_eq!(read_epee_vi::<&[u8]>(&mut buf.as_ref()).unwrap(), vi);
+  }
+  assert!(write_epee_vi(1 << 62, &mut vec![]).is_err());
+}
+
+#[test]
+fn epee_request() {
+  let hash = [0xab; 32];
+  let mut expected = HEADER.to_vec();
+  // One field, named txid, which is a 32-byte string
+  expected.push(1 << 2);
+  expected.push(4);
+  expected.extend(b"txid");
+  expected.push(10);
+  expected.push(32 << 2);
+  expected.extend(hash);
+  assert_eq!(
+    Object::new().with("txid", Value::String(hash.to_vec())).serialize().unwrap(),
+    expected
+  );
+}
+
+#[test]
+fn epee_round_trip() {
+  let object = Object::new()
+    .with("int", Value::Int(-5))
+    .with("uint", Value::Uint(u64::MAX))
+    .with("double", Value::Double(0.5))
+    .with("string", Value::String(b"OK".to_vec()))
+    .with("bool", Value::Bool(true))
+    .with("object", Value::Object(Object::new().with("index", Value::Uint(1))))
+    .with("uints", Value::Array(vec![Value::Uint(1), Value::Uint(2)]))
+    .with(
+      "objects",
+      Value::Array(vec![
+        Value::Object(Object::new().with("amount", Value::Uint(0))),
+        Value::Object(Object::new().with("amount", Value::Uint(1))),
+      ]),
+    );
+  let read = Object::deserialize(&object.serialize().unwrap()).unwrap();
+  assert_eq!(read, object);
+  assert_eq!(read.get("uint").and_then(Value::as_u64), Some(u64::MAX));
+  assert_eq!(read.get("int").and_then(Value::as_u64), None);
+  assert_eq!(read.get("string").and_then(Value::as_bytes), Some(b"OK".as_slice()));
+  assert_eq!(read.get_array("uints").unwrap().len(), 2);
+
+  // Empty arrays are omitted, and read as empty
+  let read =
+    Object::deserialize(&Object::new().with("empty", Value::Array(vec![])).serialize().unwrap())
+      .unwrap();
+  assert_eq!(read, Object::new());
+  assert_eq!(read.get_array("empty"), Some([].as_slice()));
+
+  // Arrays may not have values of differing types
+  assert!(Object::new()
+    .with("mixed", Value::Array(vec![Value::Uint(1), Value::Bool(true)]))
+    .serialize()
+    .is_err());
+}
+
+#[test]
+fn epee_narrow_integers() {
+  let mut document = HEADER.to_vec();
+  document.push(3 << 2);
+  // A u8
+  document.extend([2, b'u', b'8', 8, 0xff]);
+  // A u32
+  document.extend([3, b'u', b'3', b'2', 6]);
+  document.extend(0x01020304u32.to_le_bytes());
+  // An array of i16s
+  document.extend([3, b'i', b'1', b'6', 3 | 0x80, 2 << 2]);
+  document.extend(1i16.to_le_bytes());
+  document.extend((-1i16).to_le_bytes());
+
+  let read = Object::deserialize(&document).unwrap();
+  assert_eq!(read.get("u8").and_then(Value::as_u64), Some(0xff));
+  assert_eq!(read.get("u32").and_then(Value::as_u64), Some(0x01020304));
+  assert_eq!(read.get_array("i16"), Some([Value::Int(1), Value::Int(-1)].as_slice()));
+}
+
+#[test]
+fn epee_invalid() {
+  let valid = Object::new().with("bool", Value::Bool(true)).serialize().unwrap();
+  assert!(Object::deserialize(&valid).is_ok());
+
+  // Invalid header
+  let mut invalid = valid.clone();
+  invalid[0] = 0;
+  assert!(Object::deserialize(&invalid).is_err());
+
+  // Truncated
+  assert!(Object::deserialize(&valid[.. (valid.len() - 1)]).is_err());
+
+  // Trailing bytes
+  let mut invalid = valid.clone();
+  invalid.push(0);
+  assert!(Object::deserialize(&invalid).is_err());
+
+  // Invalid bool
+  let mut invalid = valid;
+  *invalid.last_mut().unwrap() = 2;
+  assert!(Object::deserialize(&invalid).is_err());
+
+  // Excessively nested objects
+  let mut object = Object::new();
+  for _ in 0 .. 128 {
+    object = Object::new().with("object", Value::Object(object));
+  }
+  assert!(Object::deserialize(&object.serialize().unwrap()).is_err());
+}
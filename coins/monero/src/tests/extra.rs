@@ -0,0 +1,46 @@
+use rand_core::OsRng;
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+
+use crate::{
+  random_scalar,
+  wallet::{
+    TransactionError,
+    extra::{MAX_ARBITRARY_DATA_SIZE, PaymentId, ExtraField, Extra},
+  },
+};
+
+#[test]
+fn extra() {
+  let key = &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE;
+  let additional =
+    (0 .. 3).map(|_| &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE).collect::<Vec<_>>();
+
+  let mut extra = Extra::new(key, additional.clone());
+  extra.push_payment_id(PaymentId::Encrypted([1; 8]));
+  extra.push_data(b"first").unwrap();
+  extra.push_data(&[]).unwrap();
+  extra.push_data(&[0xff; MAX_ARBITRARY_DATA_SIZE]).unwrap();
+  assert_eq!(
+    extra.push_data(&[0xff; MAX_ARBITRARY_DATA_SIZE + 1]),
+    Err(TransactionError::TooMuchData)
+  );
+
+  let read = Extra::read::<&[u8]>(&mut extra.serialize().as_ref()).unwrap();
+  assert_eq!(read, extra);
+  assert_eq!(read.fields().len(), 6);
+  assert_eq!(read.keys(), Some((key, Some(additional))));
+  assert_eq!(read.payment_id(), Some(PaymentId::Encrypted([1; 8])));
+  assert_eq!(read.data(), vec![b"first".to_vec(), vec![], vec![0xff; MAX_ARBITRARY_DATA_SIZE]]);
+
+  // Without additional keys, only the transaction key should be present
+  let mut extra = Extra::new(key, vec![]);
+  assert_eq!(extra.keys(), Some((key, None)));
+  assert!(extra.payment_id().is_none());
+
+  // An empty nonce isn't arbitrary data
+  extra.push(ExtraField::Nonce(vec![]));
+  let read = Extra::read::<&[u8]>(&mut extra.serialize().as_ref()).unwrap();
+  assert_eq!(read.fields(), &[ExtraField::PublicKey(key), ExtraField::Nonce(vec![])]);
+  assert!(read.data().is_empty());
+}
@@ -0,0 +1,93 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::rpc::{RpcError, RpcConnection, Rpc, FailoverRpc};
+
+// A connection which errors a set amount of times before responding with its ID
+#[derive(Clone, Debug)]
+struct Flaky {
+  id: u8,
+  error: RpcError,
+  failures: Arc<AtomicUsize>,
+  calls: Arc<AtomicUsize>,
+}
+
+impl Flaky {
+  fn new(id: u8, error: RpcError, failures: usize) -> Flaky {
+    Flaky {
+      id,
+      error,
+      failures: Arc::new(AtomicUsize::new(failures)),
+      calls: Arc::new(AtomicUsize::new(0)),
+    }
+  }
+
+  fn calls(&self) -> usize {
+    self.calls.load(Ordering::Relaxed)
+  }
+}
+
+#[async_trait]
+impl RpcConnection for Flaky {
+  async fn post(&self, _: &str, _: Vec<u8>) -> Result<Vec<u8>, RpcError> {
+    self.calls.fetch_add(1, Ordering::Relaxed);
+    if self
+      .failures
+      .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |f| f.checked_sub(1))
+      .is_ok()
+    {
+      Err(self.error.clone())?;
+    }
+    Ok(vec![self.id])
+  }
+}
+
+fn failover(nodes: &[Flaky]) -> FailoverRpc<Flaky> {
+  FailoverRpc::from_connections(nodes.iter().cloned().map(Rpc).collect()).unwrap().0
+}
+
+#[test]
+fn failover_without_nodes() {
+  assert_eq!(FailoverRpc::<Flaky>::from_connections(vec![]).unwrap_err(), RpcError::InvalidNode);
+}
+
+#[tokio::test]
+async fn failover_to_next_node() {
+  let nodes = [
+    Flaky::new(0, RpcError::ConnectionError, usize::MAX),
+    Flaky::new(1, RpcError::ConnectionError, 0),
+  ];
+  let rpc = failover(&nodes);
+
+  assert_eq!(rpc.post("", vec![]).await.unwrap(), vec![1]);
+  // The failing node should no longer be tried
+  assert_eq!(rpc.post("", vec![]).await.unwrap(), vec![1]);
+  assert_eq!(nodes[0].calls(), 1);
+  assert_eq!(nodes[1].calls(), 2);
+}
+
+#[tokio::test]
+async fn failover_retries_after_backoff() {
+  let nodes = [Flaky::new(0, RpcError::ConnectionError, 2)];
+  let rpc = failover(&nodes);
+  assert_eq!(rpc.post("", vec![]).await.unwrap(), vec![0]);
+  assert_eq!(nodes[0].calls(), 3);
+}
+
+#[tokio::test]
+async fn failover_gives_up() {
+  let nodes = [Flaky::new(0, RpcError::ConnectionError, usize::MAX)];
+  let rpc = failover(&nodes);
+  assert_eq!(rpc.post("", vec![]).await.unwrap_err(), RpcError::ConnectionError);
+  assert_eq!(nodes[0].calls(), 3);
+}
+
+#[tokio::test]
+async fn failover_doesnt_retry_responses() {
+  let nodes = [Flaky::new(0, RpcError::InvalidFee, 1), Flaky::new(1, RpcError::ConnectionError, 0)];
+  let rpc = failover(&nodes);
+  assert_eq!(rpc.post("", vec![]).await.unwrap_err(), RpcError::InvalidFee);
+  assert_eq!(nodes[1].calls(), 0);
+}
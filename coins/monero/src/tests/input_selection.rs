@@ -0,0 +1,132 @@
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{
+  random_scalar, Protocol,
+  ringct::RctType,
+  wallet::{
+    address::{Network, AddressSpec, MoneroAddress},
+    ViewPair, SpendableOutput, Fee, SignableTransaction, TransactionError, InputSelection,
+  },
+};
+
+const FEE: Fee = Fee { per_weight: 20, mask: 1 };
+
+fn output(amount: u64, global_index: u64) -> SpendableOutput {
+  spendable_output(
+    &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+    Scalar::ZERO,
+    amount,
+    global_index,
+  )
+}
+
+pub(super) fn spendable_output(
+  key: EdwardsPoint,
+  key_offset: Scalar,
+  amount: u64,
+  global_index: u64,
+) -> SpendableOutput {
+  // The output's fields aren't all public, so deserialize one
+  let mut serialized = vec![0; 33];
+  serialized.extend(key.compress().to_bytes());
+  serialized.extend(key_offset.to_bytes());
+  serialized.extend(Scalar::ONE.to_bytes());
+  serialized.extend(amount.to_le_bytes());
+  // The metadata's version, then no subaddress, payment ID, arbitrary data, or timelock
+  serialized.extend([2, 0, 0, 0, 0, 0, 0, 0]);
+  serialized.extend(global_index.to_le_bytes());
+  SpendableOutput::read::<&[u8]>(&mut serialized.as_ref()).unwrap()
+}
+
+pub(super) fn address() -> MoneroAddress {
+  ViewPair::new(
+    &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  )
+  .address(Network::Mainnet, AddressSpec::Standard)
+}
+
+fn select(selection: InputSelection, amount: u64) -> Result<Vec<u64>, TransactionError> {
+  let outputs =
+    vec![output(5_000_000, 3), output(2_000_000, 1), output(1_000_000, 2), output(100_000, 0)];
+  selection
+    .select(Protocol::v16, FEE, outputs, &[(address(), amount)], true, &[])
+    .map(|inputs| inputs.iter().map(|input| input.commitment().amount).collect())
+}
+
+#[test]
+fn input_selection() {
+  // Sanity check the fee is material, yet small enough to not change the selections below
+  let fee = |inputs| SignableTransaction::estimate_fee(Protocol::v16, inputs, 2, &[], FEE);
+  assert!(fee(1) > 10_000);
+  assert!(fee(4) < 100_000);
+
+  assert_eq!(select(InputSelection::LargestFirst, 1_500_000), Ok(vec![5_000_000]));
+  assert_eq!(select(InputSelection::OldestFirst, 1_500_000), Ok(vec![100_000, 2_000_000]));
+  assert_eq!(select(InputSelection::MinimizeInputs, 1_500_000), Ok(vec![2_000_000]));
+  assert_eq!(
+    select(InputSelection::Explicit, 1_500_000),
+    Ok(vec![5_000_000, 2_000_000, 1_000_000, 100_000])
+  );
+
+  assert_eq!(select(InputSelection::LargestFirst, 5_500_000), Ok(vec![5_000_000, 2_000_000]));
+  assert_eq!(select(InputSelection::MinimizeInputs, 5_500_000), Ok(vec![5_000_000, 1_000_000]));
+
+  // The fee must also be covered
+  assert_eq!(select(InputSelection::MinimizeInputs, 4_990_000), Ok(vec![5_000_000, 100_000]));
+
+  for selection in [
+    InputSelection::LargestFirst,
+    InputSelection::OldestFirst,
+    InputSelection::MinimizeInputs,
+    InputSelection::Explicit,
+  ] {
+    assert_eq!(
+      select(selection, 8_100_000),
+      Err(TransactionError::NotEnoughFunds { inputs: 8_100_000, outputs: 8_100_000, fee: fee(4) })
+    );
+  }
+}
+
+#[test]
+fn chunk_payments() {
+  let payments = (0 .. 40).map(|i| (address(), i)).collect::<Vec<_>>();
+
+  // With a change output, each transaction only has room for 15 payments
+  let chunks = SignableTransaction::chunk_payments(&payments, true);
+  assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![15, 15, 10]);
+  assert_eq!(chunks.concat(), payments);
+
+  let chunks = SignableTransaction::chunk_payments(&payments[.. 33], false);
+  assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![16, 16, 1]);
+  assert_eq!(chunks.concat(), &payments[.. 33]);
+
+  assert!(SignableTransaction::chunk_payments(&[], true).is_empty());
+}
+
+#[test]
+fn estimate_weight_and_fee() {
+  let estimate =
+    |protocol, inputs| SignableTransaction::estimate_weight_and_fee(protocol, inputs, 2, &[], FEE);
+
+  let (weight, fee) = estimate(Protocol::v16, 2);
+  assert_eq!(fee, SignableTransaction::estimate_fee(Protocol::v16, 2, 2, &[], FEE));
+  assert_eq!(fee, FEE.calculate_fee_from_weight(weight));
+
+  // Each input adds at least its CLSAG, key image, and pseudo-out
+  let ring_len = Protocol::v16.ring_len();
+  assert!(estimate(Protocol::v16, 3).0 >= (weight + (ring_len * 32) + (4 * 32)));
+
+  // A larger ring size should increase the weight of every input
+  let larger = Protocol::Custom {
+    ring_len: ring_len * 2,
+    bp_plus: true,
+    optimal_rct_type: RctType::BulletproofsPlus,
+    view_tags: true,
+    v16_fee: true,
+  };
+  assert!(estimate(larger, 2).0 >= (weight + (2 * ring_len * 32)));
+}
@@ -2,3 +2,18 @@ mod clsag;
 mod bulletproofs;
 mod address;
 mod seed;
+mod epee;
+mod tx_proof;
+mod timelock;
+mod input_selection;
+mod batch_verify;
+mod extra;
+mod cold;
+mod scan;
+mod rpc;
+mod protocol;
+mod decoys;
+#[cfg(feature = "multisig")]
+mod multisig;
+#[cfg(feature = "http_rpc")]
+mod failover;
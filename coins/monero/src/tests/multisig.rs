@@ -0,0 +1,169 @@
+use std::collections::{HashSet, HashMap};
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+
+use transcript::{Transcript, RecommendedTranscript};
+use frost::{
+  Participant, FrostError,
+  curve::Ed25519,
+  tests::{THRESHOLD, PARTICIPANTS, key_gen, recover_key, sign_without_caching},
+};
+
+use crate::{
+  random_scalar, Protocol,
+  transaction::Input,
+  ringct::{generate_key_image, RctPrunable},
+  wallet::{
+    address::{Network, AddressSpec},
+    ViewPair, Scanner, Fee, Change, Decoys, SignableTransaction, TransactionError,
+    multisig::{KeyImageShares, combine_key_images},
+  },
+  tests::input_selection::{spendable_output, address},
+};
+
+const AMOUNT: u64 = 100_000_000;
+
+#[test]
+fn multisig_transaction() {
+  let keys = key_gen::<_, Ed25519>(&mut OsRng);
+  let group_key = keys[&Participant::new(1).unwrap()].group_key().0;
+
+  let offset = random_scalar(&mut OsRng);
+  let input = spendable_output(group_key + (&offset * ED25519_BASEPOINT_TABLE), offset, AMOUNT, 0);
+
+  let ring_len = Protocol::v16.ring_len();
+  let mut ring = (0 .. ring_len)
+    .map(|_| {
+      [
+        &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+        &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+      ]
+    })
+    .collect::<Vec<_>>();
+  let real = 5;
+  ring[real] = [input.key(), input.commitment().calculate()];
+  let decoys =
+    Decoys { i: u8::try_from(real).unwrap(), offsets: vec![1; ring_len], ring: ring.clone() };
+
+  let view = ViewPair::new(
+    &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  );
+  let signable = SignableTransaction::new(
+    Protocol::v16,
+    None,
+    vec![(input, decoys)],
+    vec![(view.address(Network::Mainnet, AddressSpec::Standard), AMOUNT / 2)],
+    Some(Change::fingerprintable(address())),
+    vec![],
+    Fee { per_weight: 20, mask: 1 },
+  )
+  .unwrap();
+
+  // Keys from another group can't be used
+  assert_eq!(
+    signable
+      .clone()
+      .multisig(
+        key_gen::<_, Ed25519>(&mut OsRng)[&Participant::new(1).unwrap()].clone(),
+        RecommendedTranscript::new(b"Monero Serai Multisig Test"),
+      )
+      .err(),
+    Some(TransactionError::WrongPrivateKey)
+  );
+
+  let mut machines = HashMap::new();
+  for i in (1 ..= THRESHOLD).map(|i| Participant::new(i).unwrap()) {
+    machines.insert(
+      i,
+      signable
+        .clone()
+        .multisig(keys[&i].clone(), RecommendedTranscript::new(b"Monero Serai Multisig Test"))
+        .unwrap(),
+    );
+  }
+  let tx = sign_without_caching(&mut OsRng, machines, &[]);
+
+  // The key image should be the one for the group's key, offset by the output's key offset
+  let Input::ToKey { key_image, .. } = tx.prefix.inputs[0] else { panic!("input wasn't ToKey") };
+  assert_eq!(key_image, generate_key_image(&Zeroizing::new(recover_key(&keys).0 + offset)));
+
+  let RctPrunable::Clsag { clsags, pseudo_outs, .. } = &tx.rct_signatures.prunable else {
+    panic!("transaction didn't use CLSAGs")
+  };
+  clsags[0].verify(&ring, &key_image, &pseudo_outs[0], &tx.signature_hash()).unwrap();
+
+  // The recipient should be able to scan their payment
+  let mut scanner = Scanner::from_view(view, Some(HashSet::new()));
+  let outputs = scanner.scan_transaction(&tx).ignore_timelock();
+  assert_eq!(outputs.len(), 1);
+  assert_eq!(outputs[0].commitment().amount, AMOUNT / 2);
+}
+
+#[test]
+fn multisig_key_images() {
+  let keys = key_gen::<_, Ed25519>(&mut OsRng);
+  let group_key = keys[&Participant::new(1).unwrap()].group_key().0;
+
+  let offsets = (0 .. 3).map(|_| random_scalar(&mut OsRng)).collect::<Vec<_>>();
+  let outputs = offsets
+    .iter()
+    .enumerate()
+    .map(|(i, offset)| {
+      spendable_output(
+        group_key + (offset * ED25519_BASEPOINT_TABLE),
+        *offset,
+        AMOUNT,
+        u64::try_from(i).unwrap(),
+      )
+    })
+    .collect::<Vec<_>>();
+  let expected = offsets
+    .iter()
+    .map(|offset| generate_key_image(&Zeroizing::new(recover_key(&keys).0 + offset)))
+    .collect::<Vec<_>>();
+
+  // Keys from another group can't create shares
+  assert_eq!(
+    KeyImageShares::new(
+      &mut OsRng,
+      &key_gen::<_, Ed25519>(&mut OsRng)[&Participant::new(1).unwrap()],
+      &outputs
+    ),
+    Err(TransactionError::WrongPrivateKey)
+  );
+
+  let mut shares = HashMap::new();
+  for i in (1 ..= PARTICIPANTS).map(|i| Participant::new(i).unwrap()) {
+    let share = KeyImageShares::new(&mut OsRng, &keys[&i], &outputs).unwrap();
+    let read = KeyImageShares::read(outputs.len(), &mut share.serialize().as_slice()).unwrap();
+    assert_eq!(read, share);
+    shares.insert(i, share);
+  }
+
+  // Any threshold of participants should be able to combine their shares
+  let ours = Participant::new(1).unwrap();
+  let mut threshold = shares.clone();
+  threshold.retain(|i, _| u16::from(*i) > (PARTICIPANTS - THRESHOLD));
+  assert_eq!(combine_key_images(&keys[&ours], &outputs, &threshold).unwrap(), expected);
+  assert_eq!(combine_key_images(&keys[&ours], &outputs, &shares).unwrap(), expected);
+
+  // Less than the threshold shouldn't be combinable
+  let mut insufficient = threshold.clone();
+  insufficient.remove(&Participant::new(PARTICIPANTS).unwrap());
+  assert!(combine_key_images(&keys[&ours], &outputs, &insufficient).is_err());
+
+  // Shares for the wrong outputs should be detected
+  let mut invalid = threshold.clone();
+  let last = Participant::new(PARTICIPANTS).unwrap();
+  let mut reordered = outputs.clone();
+  reordered.reverse();
+  invalid.insert(last, KeyImageShares::new(&mut OsRng, &keys[&last], &reordered).unwrap());
+  assert_eq!(
+    combine_key_images(&keys[&ours], &outputs, &invalid),
+    Err(FrostError::InvalidShare(last))
+  );
+}
@@ -0,0 +1,24 @@
+use crate::Protocol;
+
+#[test]
+fn hard_forks() {
+  assert_eq!(Protocol::from_hard_fork(12), None);
+  assert_eq!(Protocol::from_hard_fork(14), Some(Protocol::v14));
+  assert_eq!(Protocol::from_hard_fork(15), Some(Protocol::v16));
+  assert_eq!(Protocol::from_hard_fork(16), Some(Protocol::v16));
+  assert_eq!(Protocol::from_hard_fork(17), None);
+
+  // The protocol for each hard fork should be valid under it
+  for version in 13 ..= 16 {
+    assert!(Protocol::from_hard_fork(version).unwrap().valid_under_hard_fork(version));
+  }
+  assert!(!Protocol::v14.valid_under_hard_fork(16));
+  assert!(!Protocol::v16.valid_under_hard_fork(14));
+
+  // v15 accepted transactions from both v14 and v16, allowing transitioning between them
+  assert_eq!(Protocol::for_hard_fork_transition(14, 15), Some(Protocol::v14));
+  assert_eq!(Protocol::for_hard_fork_transition(15, 16), Some(Protocol::v16));
+  assert_eq!(Protocol::for_hard_fork_transition(16, 16), Some(Protocol::v16));
+  assert_eq!(Protocol::for_hard_fork_transition(14, 16), None);
+  assert_eq!(Protocol::for_hard_fork_transition(16, 17), None);
+}
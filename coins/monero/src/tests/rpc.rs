@@ -0,0 +1,58 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use serde_json::{json, Value};
+
+use crate::rpc::{RpcError, RpcConnection, Rpc};
+
+type Requests = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+
+// An in-memory connection which responds to each route with a fixed response, recording requests
+#[derive(Clone, Debug)]
+struct Mock {
+  responses: Vec<(&'static str, Value)>,
+  requests: Requests,
+}
+
+#[async_trait]
+impl RpcConnection for Mock {
+  async fn post(&self, route: &str, body: Vec<u8>) -> Result<Vec<u8>, RpcError> {
+    self.requests.lock().unwrap().push((route.to_string(), body));
+    let response = self
+      .responses
+      .iter()
+      .find(|(res_route, _)| *res_route == route)
+      .ok_or(RpcError::ConnectionError)?;
+    Ok(serde_json::to_vec(&response.1).unwrap())
+  }
+}
+
+fn mock(responses: Vec<(&'static str, Value)>) -> Rpc<Mock> {
+  Rpc::new(Mock { responses, requests: Arc::new(Mutex::new(vec![])) })
+}
+
+#[tokio::test]
+async fn custom_connection() {
+  let hash = [0xab; 32];
+  let rpc = mock(vec![
+    ("get_height", json!({ "height": 3000000, "status": "OK" })),
+    ("json_rpc", json!({ "result": { "block_header": { "hash": hex::encode(hash) } } })),
+  ]);
+
+  assert_eq!(rpc.get_height().await.unwrap(), 3000000);
+  assert_eq!(rpc.get_block_hash(2999999).await.unwrap(), hash);
+  assert!(rpc.get_transactions(&[]).await.unwrap().is_empty());
+
+  let requests = rpc.connection().requests.lock().unwrap().clone();
+  assert_eq!(requests.len(), 2);
+  assert_eq!(requests[0], ("get_height".to_string(), vec![]));
+  assert_eq!(requests[1].0, "json_rpc");
+  assert_eq!(
+    serde_json::from_slice::<Value>(&requests[1].1).unwrap(),
+    json!({ "method": "get_block_header_by_height", "params": { "height": 2999999 } })
+  );
+
+  // Errors from the connection are passed through
+  assert_eq!(rpc.bin_call("get_blocks.bin", vec![]).await, Err(RpcError::ConnectionError));
+}
@@ -0,0 +1,208 @@
+use core::ops::Deref;
+use std::{time::Instant, collections::HashSet};
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+
+use crate::{
+  random_scalar, Protocol,
+  transaction::{Timelock, Transaction},
+  wallet::{
+    address::{Network, AddressSpec, MoneroAddress, SubaddressIndex},
+    ViewPair, Scanner, Metadata, ReceivedOutput, Timelocked, PaymentId, Fee, Change, Decoys,
+    SignableTransaction,
+  },
+  tests::input_selection::{spendable_output, address},
+};
+
+// Create a signed transaction making the specified payments, with change sent to a random address
+fn transaction(payments: Vec<(MoneroAddress, u64)>) -> Transaction {
+  let spend = Zeroizing::new(random_scalar(&mut OsRng));
+  let offset = random_scalar(&mut OsRng);
+  let input =
+    spendable_output(&(spend.deref() + offset) * ED25519_BASEPOINT_TABLE, offset, 100_000_000, 0);
+
+  let ring_len = Protocol::v16.ring_len();
+  let mut ring = (0 .. ring_len)
+    .map(|_| {
+      [
+        &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+        &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+      ]
+    })
+    .collect::<Vec<_>>();
+  ring[0] = [input.key(), input.commitment().calculate()];
+
+  SignableTransaction::new(
+    Protocol::v16,
+    None,
+    vec![(input, Decoys { i: 0, offsets: vec![1; ring_len], ring })],
+    payments,
+    Some(Change::fingerprintable(address())),
+    vec![],
+    Fee { per_weight: 20, mask: 1 },
+  )
+  .unwrap()
+  .sign(&mut OsRng, &spend)
+  .unwrap()
+}
+
+fn view() -> ViewPair {
+  ViewPair::new(
+    &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  )
+}
+
+// The amounts received by each transaction, sorted as outputs are shuffled when sending
+fn amounts(scanned: Vec<Timelocked<ReceivedOutput>>) -> Vec<Vec<u64>> {
+  scanned
+    .into_iter()
+    .map(|outputs| {
+      let mut amounts = outputs
+        .ignore_timelock()
+        .iter()
+        .map(|output| output.commitment().amount)
+        .collect::<Vec<_>>();
+      amounts.sort();
+      amounts
+    })
+    .collect()
+}
+
+#[test]
+fn scan_transactions() {
+  let view = view();
+  let ours = view.address(Network::Mainnet, AddressSpec::Standard);
+
+  let first = transaction(vec![(ours, 1), (address(), 2), (ours, 3)]);
+  let second = transaction(vec![(address(), 4)]);
+  let third = transaction(vec![(ours, 5)]);
+  // Include the first transaction twice, as the burning bug would
+  let txs = vec![first.clone(), second, third, first];
+
+  // The repeated transaction's outputs should be discarded
+  let mut scanner = Scanner::from_view(view.clone(), Some(HashSet::new()));
+  let expected = vec![vec![1, 3], vec![], vec![5], vec![]];
+  assert_eq!(amounts(scanner.scan_transactions(&txs)), expected);
+
+  // Scanning the transactions one at a time should have the same result
+  let mut scanner = Scanner::from_view(view, Some(HashSet::new()));
+  assert_eq!(amounts(txs.iter().map(|tx| scanner.scan_transaction(tx)).collect()), expected);
+}
+
+#[test]
+fn metadata_serialization() {
+  let metadata = Metadata {
+    subaddress: SubaddressIndex::new(1, 2),
+    payment_id: Some(PaymentId::Unencrypted([0xff; 32])),
+    arbitrary_data: vec![vec![1, 2, 3]],
+    timelock: Timelock::Block(100),
+  };
+  assert_eq!(Metadata::read(&mut metadata.serialize().as_slice()).unwrap(), metadata);
+
+  let metadata = Metadata {
+    subaddress: None,
+    payment_id: None,
+    arbitrary_data: vec![],
+    timelock: Timelock::None,
+  };
+  assert_eq!(Metadata::read(&mut metadata.serialize().as_slice()).unwrap(), metadata);
+}
+
+#[test]
+fn legacy_metadata() {
+  // Metadata as written prior to it being versioned, which had an 8-byte payment ID and no timelock
+  let mut legacy = vec![1];
+  legacy.extend(1u32.to_le_bytes());
+  legacy.extend(2u32.to_le_bytes());
+  legacy.extend([0xaa; 8]);
+  legacy.extend(1u32.to_le_bytes());
+  legacy.extend([3, 1, 2, 3]);
+
+  assert_eq!(
+    Metadata::read(&mut legacy.as_slice()).unwrap(),
+    Metadata {
+      subaddress: SubaddressIndex::new(1, 2),
+      payment_id: Some(PaymentId::Encrypted([0xaa; 8])),
+      arbitrary_data: vec![vec![1, 2, 3]],
+      timelock: Timelock::None,
+    }
+  );
+
+  // Legacy metadata without a subaddress
+  let mut legacy = vec![0];
+  legacy.extend([0xaa; 8]);
+  legacy.extend(0u32.to_le_bytes());
+  assert_eq!(Metadata::read(&mut legacy.as_slice()).unwrap().subaddress, None);
+}
+
+#[test]
+fn reject_invalid_metadata() {
+  let metadata = Metadata {
+    subaddress: None,
+    payment_id: None,
+    arbitrary_data: vec![],
+    timelock: Timelock::None,
+  };
+  let serialized = metadata.serialize();
+
+  // Unknown version
+  let mut invalid = serialized.clone();
+  invalid[0] = 3;
+  assert!(Metadata::read(&mut invalid.as_slice()).is_err());
+
+  // Unknown subaddress flag
+  let mut invalid = serialized.clone();
+  invalid[1] = 2;
+  assert!(Metadata::read(&mut invalid.as_slice()).is_err());
+
+  // Unknown payment ID flag
+  let mut invalid = serialized;
+  invalid[2] = 2;
+  assert!(Metadata::read(&mut invalid.as_slice()).is_err());
+}
+
+// A benchmark for scanning transactions, as done during a wallet's initial sync
+//
+// This is ignored by default due to how long it takes. Run it with
+// `cargo test --release -p monero-serai --lib scan_bench -- --ignored --nocapture`, with and
+// without the `parallel` feature
+#[test]
+#[ignore]
+fn scan_bench() {
+  const TRANSACTIONS: usize = 64;
+  const REPETITIONS: usize = 16;
+
+  let txs = (0 .. TRANSACTIONS)
+    .map(|_| transaction((0 .. 15).map(|i| (address(), i + 1)).collect()))
+    .collect::<Vec<_>>();
+  let view = view();
+
+  let start = Instant::now();
+  for _ in 0 .. REPETITIONS {
+    let mut scanner = Scanner::from_view(view.clone(), Some(HashSet::new()));
+    for tx in &txs {
+      assert!(scanner.scan_transaction(tx).ignore_timelock().is_empty());
+    }
+  }
+  let sequential = start.elapsed() / u32::try_from(REPETITIONS).unwrap();
+
+  let start = Instant::now();
+  for _ in 0 .. REPETITIONS {
+    let mut scanner = Scanner::from_view(view.clone(), Some(HashSet::new()));
+    assert!(scanner
+      .scan_transactions(&txs)
+      .iter()
+      .all(|outputs| outputs.ignore_timelock().is_empty()));
+  }
+  let batched = start.elapsed() / u32::try_from(REPETITIONS).unwrap();
+
+  println!(
+    "scanned {TRANSACTIONS} transactions with 16 outputs each: one at a time {sequential:?}, \
+      batched {batched:?} (parallel: {})",
+    cfg!(feature = "parallel")
+  );
+}
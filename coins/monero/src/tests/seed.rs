@@ -2,7 +2,7 @@ use zeroize::Zeroizing;
 
 use rand_core::OsRng;
 
-use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
 
 use crate::{
   hash,
@@ -167,6 +167,18 @@ fn test_classic_seed() {
         Scalar::from_canonical_bytes(view).unwrap()
       );
 
+      // Which the keys derived from the seed should match
+      assert_eq!(seed.spend_key().to_bytes(), spend);
+      let pair = seed.view_pair();
+      assert_eq!(
+        pair.spend(),
+        &Scalar::from_canonical_bytes(spend).unwrap() * ED25519_BASEPOINT_TABLE
+      );
+      assert_eq!(
+        pair.view(),
+        &Scalar::from_canonical_bytes(view).unwrap() * ED25519_BASEPOINT_TABLE
+      );
+
       assert_eq!(
         Seed::from_entropy(SeedType::Classic(vector.language), Zeroizing::new(spend), None)
           .unwrap(),
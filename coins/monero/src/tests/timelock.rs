@@ -0,0 +1,17 @@
+use crate::transaction::Timelock;
+
+#[test]
+fn timelock_unlocked() {
+  assert!(Timelock::None.is_unlocked(0, 0));
+
+  // Block timelocks unlock once the chain has that many blocks
+  assert!(!Timelock::Block(10).is_unlocked(9, u64::MAX));
+  assert!(Timelock::Block(10).is_unlocked(10, 0));
+  assert!(Timelock::Block(10).is_unlocked(11, 0));
+
+  // Time timelocks unlock up to two minutes early
+  let unlock = 1_600_000_000;
+  assert!(!Timelock::Time(unlock).is_unlocked(usize::MAX, unlock - 121));
+  assert!(Timelock::Time(unlock).is_unlocked(0, unlock - 120));
+  assert!(Timelock::Time(unlock).is_unlocked(0, unlock));
+}
@@ -0,0 +1,112 @@
+use core::ops::Deref;
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{
+  random_scalar,
+  ringct::{RctBase, RctPrunable, RctSignatures},
+  transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
+  wallet::{
+    Extra, ViewPair, shared_key,
+    address::{Network, AddressSpec, SubaddressIndex},
+    proof::{TxProofError, out_proof, in_proof, check_tx_proof},
+  },
+};
+
+// Create a miner transaction paying the address, returning it and its key
+fn miner_tx(view: &ViewPair, spec: AddressSpec) -> (Transaction, Zeroizing<Scalar>) {
+  let address = view.address(Network::Mainnet, spec);
+  let r = Zeroizing::new(random_scalar(&mut OsRng));
+  #[allow(non_snake_case)]
+  let R = if address.is_subaddress() {
+    r.deref() * address.spend
+  } else {
+    r.deref() * ED25519_BASEPOINT_TABLE
+  };
+  let (_, shared_key, _) = shared_key(None, r.deref() * address.view, 0);
+
+  let mut extra = vec![];
+  Extra::new(R, vec![]).write(&mut extra).unwrap();
+  let tx = Transaction {
+    prefix: TransactionPrefix {
+      version: 2,
+      timelock: Timelock::Block(60),
+      inputs: vec![Input::Gen(1)],
+      outputs: vec![Output {
+        amount: Some(5),
+        key: ((shared_key.deref() * ED25519_BASEPOINT_TABLE) + address.spend).compress(),
+        view_tag: None,
+      }],
+      extra,
+    },
+    signatures: vec![],
+    rct_signatures: RctSignatures {
+      base: RctBase { fee: 0, encrypted_amounts: vec![], pseudo_outs: vec![], commitments: vec![] },
+      prunable: RctPrunable::Null,
+    },
+  };
+  (tx, r)
+}
+
+fn view_pair() -> ViewPair {
+  ViewPair::new(
+    &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+    Zeroizing::new(random_scalar(&mut OsRng)),
+  )
+}
+
+#[test]
+fn tx_proof() {
+  let subaddress = SubaddressIndex::new(0, 1).unwrap();
+  for (spec, index) in
+    [(AddressSpec::Standard, None), (AddressSpec::Subaddress(subaddress), Some(subaddress))]
+  {
+    let view = view_pair();
+    let address = view.address(Network::Mainnet, spec);
+    let (tx, r) = miner_tx(&view, spec);
+
+    let out = out_proof(&mut OsRng, &tx, &r, &[], &address, b"message").unwrap();
+    assert!(out.starts_with("OutProofV2"));
+    assert_eq!(check_tx_proof(&tx, &address, b"message", &out), Ok(5));
+
+    let inp = in_proof(&mut OsRng, &tx, &view, index, b"message").unwrap();
+    assert!(inp.starts_with("InProofV2"));
+    assert_eq!(check_tx_proof(&tx, &address, b"message", &inp), Ok(5));
+
+    for proof in [&out, &inp] {
+      // The proofs are bound to the message
+      assert_eq!(check_tx_proof(&tx, &address, b"other", proof), Err(TxProofError::InvalidProof));
+
+      // The proofs are bound to the transaction
+      let (other_tx, _) = miner_tx(&view, spec);
+      assert_eq!(
+        check_tx_proof(&other_tx, &address, b"message", proof),
+        Err(TxProofError::InvalidProof)
+      );
+
+      // The proofs are bound to the address
+      let other = view_pair().address(Network::Mainnet, spec);
+      assert_eq!(check_tx_proof(&tx, &other, b"message", proof), Err(TxProofError::InvalidProof));
+
+      assert_eq!(
+        check_tx_proof(&tx, &address, b"message", &proof[.. (proof.len() - 1)]),
+        Err(TxProofError::InvalidEncoding)
+      );
+    }
+
+    // A valid proof for an address which wasn't paid should report nothing received
+    let other = view_pair();
+    let proof = in_proof(&mut OsRng, &tx, &other, index, b"message").unwrap();
+    let other = other.address(Network::Mainnet, spec);
+    assert_eq!(check_tx_proof(&tx, &other, b"message", &proof), Ok(0));
+
+    // Keys which aren't the transaction's keys can't be used to prove it
+    assert_eq!(
+      out_proof(&mut OsRng, &tx, &Zeroizing::new(random_scalar(&mut OsRng)), &[], &address, b""),
+      Err(TxProofError::WrongKeys)
+    );
+  }
+}
@@ -2,11 +2,14 @@ use core::cmp::Ordering;
 use std_shims::{
   vec::Vec,
   io::{self, Read, Write},
+  collections::HashSet,
 };
 
+use rand_core::{RngCore, CryptoRng};
 use zeroize::Zeroize;
 
 use curve25519_dalek::{
+  traits::IsIdentity,
   scalar::Scalar,
   edwards::{EdwardsPoint, CompressedEdwardsY},
 };
@@ -14,7 +17,11 @@ use curve25519_dalek::{
 use crate::{
   Protocol, hash,
   serialize::*,
-  ringct::{bulletproofs::Bulletproofs, RctType, RctBase, RctPrunable, RctSignatures},
+  ringct::{
+    bulletproofs::{MAX_OUTPUTS, Bulletproofs},
+    batch::BatchVerifier,
+    RctType, RctBase, RctPrunable, RctSignatures,
+  },
 };
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -142,8 +149,11 @@ pub enum Timelock {
   Time(u64),
 }
 
+// How far in advance of its time-based timelock an output may be spent
+const LOCKED_TX_ALLOWED_DELTA_SECONDS: u64 = 120;
+
 impl Timelock {
-  fn from_raw(raw: u64) -> Timelock {
+  pub(crate) fn from_raw(raw: u64) -> Timelock {
     if raw == 0 {
       Timelock::None
     } else if raw < 500_000_000 {
@@ -153,7 +163,7 @@ impl Timelock {
     }
   }
 
-  fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+  pub(crate) fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
     write_varint(
       &match self {
         Timelock::None => 0,
@@ -163,6 +173,19 @@ impl Timelock {
       w,
     )
   }
+
+  /// Whether outputs with this timelock may be spent in the next block.
+  ///
+  /// `height` is the amount of blocks on the chain and `time` is the chain's current time, such as
+  /// the latest block's timestamp. As Monero does, time-based timelocks are treated as unlocked
+  /// up to a block's target time early.
+  pub fn is_unlocked(&self, height: usize, time: u64) -> bool {
+    match self {
+      Timelock::None => true,
+      Timelock::Block(block) => *block <= height,
+      Timelock::Time(unlock) => time.saturating_add(LOCKED_TX_ALLOWED_DELTA_SECONDS) >= *unlock,
+    }
+  }
 }
 
 impl PartialOrd for Timelock {
@@ -249,6 +272,32 @@ impl TransactionPrefix {
   }
 }
 
+/// Errors returned when verifying a transaction.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum VerificationError {
+  #[cfg_attr(feature = "std", error("transaction isn't a RingCT transaction using CLSAGs"))]
+  UnsupportedType,
+  #[cfg_attr(feature = "std", error("invalid input"))]
+  InvalidInput,
+  #[cfg_attr(feature = "std", error("invalid amount of outputs"))]
+  InvalidOutputs,
+  #[cfg_attr(feature = "std", error("invalid key image"))]
+  InvalidKeyImage,
+  #[cfg_attr(feature = "std", error("key images weren't sorted or were duplicated"))]
+  UnsortedKeyImages,
+  #[cfg_attr(feature = "std", error("invalid output key"))]
+  InvalidOutputKey,
+  #[cfg_attr(feature = "std", error("duplicated output key"))]
+  DuplicatedOutputKey,
+  #[cfg_attr(feature = "std", error("fee too low"))]
+  FeeTooLow,
+  #[cfg_attr(feature = "std", error("invalid signatures"))]
+  InvalidSignatures,
+  #[cfg_attr(feature = "std", error("invalid range proof"))]
+  InvalidRangeProof,
+}
+
 /// Monero transaction. For version 1, rct_signatures still contains an accurate fee value.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Transaction {
@@ -446,4 +495,77 @@ impl Transaction {
       blob_size + Bulletproofs::calculate_bp_clawback(bp_plus, self.prefix.outputs.len()).0
     }
   }
+
+  /// Verify a transaction without a daemon.
+  ///
+  /// `rings` is the ring for each input, in order, with each ring member being its output key
+  /// and commitment. These must be fetched from the blockchain by the caller, as must the minimum
+  /// fee per weight. A fee per weight of 0 skips checking the fee.
+  ///
+  /// This checks the transaction's structure, its key images, its output keys, its fee, its
+  /// CLSAGs, its balance, and its range proof. It does not check the key images are unspent, the
+  /// decoys are unlocked, or the timelock and extra, as those require the blockchain or are
+  /// policy rather than consensus.
+  pub fn verify<R: RngCore + CryptoRng>(
+    &self,
+    rng: &mut R,
+    rings: &[Vec<[EdwardsPoint; 2]>],
+    fee_per_weight: u64,
+  ) -> Result<(), VerificationError> {
+    if (self.prefix.version != 2) ||
+      (!matches!(self.rct_signatures.rct_type(), RctType::Clsag | RctType::BulletproofsPlus))
+    {
+      Err(VerificationError::UnsupportedType)?;
+    }
+
+    // Every RingCT transaction has at least two outputs
+    let outputs = self.prefix.outputs.len();
+    if (!(2 ..= MAX_OUTPUTS).contains(&outputs)) ||
+      (self.rct_signatures.base.commitments.len() != outputs) ||
+      (self.rct_signatures.base.encrypted_amounts.len() != outputs)
+    {
+      Err(VerificationError::InvalidOutputs)?;
+    }
+
+    if self.prefix.inputs.is_empty() {
+      Err(VerificationError::InvalidInput)?;
+    }
+    let mut prior: Option<[u8; 32]> = None;
+    for input in &self.prefix.inputs {
+      let Input::ToKey { amount: None, key_image, .. } = input else {
+        Err(VerificationError::InvalidInput)?
+      };
+      if key_image.is_identity() || (!key_image.is_torsion_free()) {
+        Err(VerificationError::InvalidKeyImage)?;
+      }
+      // Key images must be sorted in descending order, which also ensures they're unique
+      let key_image = key_image.compress().to_bytes();
+      if prior.is_some_and(|prior| key_image >= prior) {
+        Err(VerificationError::UnsortedKeyImages)?;
+      }
+      prior = Some(key_image);
+    }
+
+    let mut keys = HashSet::with_capacity(outputs);
+    for output in &self.prefix.outputs {
+      if output.amount.is_some() || output.key.decompress().is_none() {
+        Err(VerificationError::InvalidOutputKey)?;
+      }
+      if !keys.insert(output.key.to_bytes()) {
+        Err(VerificationError::DuplicatedOutputKey)?;
+      }
+    }
+
+    // monerod accepts fees up to 2% below the needed fee
+    let needed = fee_per_weight.saturating_mul(u64::try_from(self.weight()).unwrap());
+    if self.rct_signatures.base.fee < (needed - (needed / 50)) {
+      Err(VerificationError::FeeTooLow)?;
+    }
+
+    let mut verifier = BatchVerifier::new(1);
+    if !verifier.queue(rng, 0, self, rings) {
+      Err(VerificationError::InvalidSignatures)?;
+    }
+    verifier.verify().map_err(|_| VerificationError::InvalidRangeProof)
+  }
 }
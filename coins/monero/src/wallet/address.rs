@@ -1,5 +1,5 @@
-use core::{marker::PhantomData, fmt::Debug};
-use std_shims::string::{String, ToString};
+use core::{marker::PhantomData, fmt, str::FromStr};
+use std_shims::string::ToString;
 
 use zeroize::Zeroize;
 
@@ -79,7 +79,7 @@ impl AddressType {
 }
 
 /// A type which returns the byte for a given address.
-pub trait AddressBytes: Clone + Copy + PartialEq + Eq + Debug {
+pub trait AddressBytes: Clone + Copy + PartialEq + Eq + fmt::Debug {
   fn network_bytes(network: Network) -> (u8, u8, u8, u8);
 }
 
@@ -210,8 +210,8 @@ impl<B: AddressBytes> Zeroize for Address<B> {
   }
 }
 
-impl<B: AddressBytes> ToString for Address<B> {
-  fn to_string(&self) -> String {
+impl<B: AddressBytes> fmt::Display for Address<B> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     let mut data = vec![self.meta.to_byte()];
     data.extend(self.spend.compress().to_bytes());
     data.extend(self.view.compress().to_bytes());
@@ -224,7 +224,15 @@ impl<B: AddressBytes> ToString for Address<B> {
     if let Some(id) = self.meta.kind.payment_id() {
       data.extend(id);
     }
-    encode_check(&data).unwrap()
+    f.write_str(&encode_check(&data).unwrap())
+  }
+}
+
+/// Parse an address for any network, as with `Address::from_str_raw`.
+impl<B: AddressBytes> FromStr for Address<B> {
+  type Err = AddressError;
+  fn from_str(s: &str) -> Result<Self, AddressError> {
+    Self::from_str_raw(s)
   }
 }
 
@@ -249,7 +257,11 @@ impl<B: AddressBytes> Address<B> {
     let mut read = 65;
 
     if matches!(meta.kind, AddressType::Featured { .. }) {
-      if raw[read] >= (2 << 3) {
+      if raw.len() == read {
+        Err(AddressError::InvalidLength)?;
+      }
+      // Only three features are currently defined
+      if raw[read] >= (1 << 3) {
         Err(AddressError::UnknownFeatures)?;
       }
 
@@ -297,6 +309,10 @@ impl<B: AddressBytes> Address<B> {
     self.meta.network
   }
 
+  pub fn kind(&self) -> AddressType {
+    self.meta.kind
+  }
+
   pub fn is_subaddress(&self) -> bool {
     self.meta.is_subaddress()
   }
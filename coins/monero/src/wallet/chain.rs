@@ -0,0 +1,300 @@
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+  collections::{VecDeque, HashSet, HashMap},
+};
+
+use curve25519_dalek::edwards::{EdwardsPoint, CompressedEdwardsY};
+
+use crate::{
+  serialize::{read_bytes, read_varint, read_vec, write_varint, write_vec},
+  transaction::{Input, Transaction},
+  block::Block,
+  rpc::{RpcError, RpcConnection, Rpc},
+  wallet::{Scanner, SpendableOutput, Timelocked},
+};
+
+// How many blocks to request at once
+const BATCH: usize = 100;
+// How many of the most recent blocks' hashes to keep, bounding how deep of a reorg is handled
+const MAX_REORG_DEPTH: usize = 100;
+
+/// Storage for the state of a ChainScanner, enabling it to resume after a restart.
+pub trait ScannerStore {
+  type Error;
+
+  /// Load the most recently saved state, if any state has been saved.
+  fn load(&self) -> Result<Option<Vec<u8>>, Self::Error>;
+
+  /// Save the state, replacing any prior state.
+  ///
+  /// This MUST be atomic, as a partially written state will fail to load.
+  fn save(&mut self, state: &[u8]) -> Result<(), Self::Error>;
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum ChainScannerError<E> {
+  #[cfg_attr(feature = "std", error("rpc error ({0})"))]
+  RpcError(RpcError),
+  #[cfg_attr(feature = "std", error("store error ({0})"))]
+  StoreError(E),
+  #[cfg_attr(feature = "std", error("saved state was invalid"))]
+  InvalidState,
+  #[cfg_attr(feature = "std", error("reorganization was deeper than the blocks tracked"))]
+  ReorgTooDeep,
+}
+
+impl<E> From<RpcError> for ChainScannerError<E> {
+  fn from(err: RpcError) -> ChainScannerError<E> {
+    ChainScannerError::RpcError(err)
+  }
+}
+
+/// An event from scanning the blockchain.
+pub enum ChainEvent {
+  /// A block was scanned.
+  Block {
+    number: usize,
+    hash: [u8; 32],
+    /// The outputs received within this block.
+    outputs: Vec<Timelocked<SpendableOutput>>,
+    /// The watched key images spent within this block.
+    spent: Vec<EdwardsPoint>,
+  },
+  /// The blocks from this number onwards were reorganized off the chain.
+  ///
+  /// Any outputs received, or key images spent, within those blocks must be discarded. Their
+  /// replacements will be emitted as the new chain is scanned.
+  Reorg { number: usize },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct State {
+  // The number of the next block to scan
+  next_block: usize,
+  // The hashes of the most recently scanned blocks, ending with the block prior to next_block
+  recent: VecDeque<[u8; 32]>,
+  // The key of every output received, as encoded on chain, with the block it was received in
+  received: HashMap<[u8; 32], usize>,
+  // The key images to report the spends of
+  watched: HashSet<[u8; 32]>,
+}
+
+impl State {
+  fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_varint(&u64::try_from(self.next_block).unwrap(), w)?;
+    write_vec(|hash, w| w.write_all(*hash), &self.recent.iter().collect::<Vec<_>>(), w)?;
+    write_vec(
+      |(key, block), w| {
+        w.write_all(*key)?;
+        write_varint(&u64::try_from(**block).unwrap(), w)
+      },
+      &self.received.iter().collect::<Vec<_>>(),
+      w,
+    )?;
+    write_vec(|key_image, w| w.write_all(*key_image), &self.watched.iter().collect::<Vec<_>>(), w)
+  }
+
+  fn serialize(&self) -> Vec<u8> {
+    let mut res = vec![];
+    self.write(&mut res).unwrap();
+    res
+  }
+
+  fn read<R: Read>(r: &mut R) -> io::Result<State> {
+    let usize_varint = |r: &mut R| {
+      usize::try_from(read_varint(r)?)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "varint exceeded usize"))
+    };
+    Ok(State {
+      next_block: usize_varint(r)?,
+      recent: read_vec(read_bytes, r)?.into(),
+      received: read_vec(|r| Ok((read_bytes(r)?, usize_varint(r)?)), r)?.into_iter().collect(),
+      watched: read_vec(read_bytes, r)?.into_iter().collect(),
+    })
+  }
+}
+
+/// A scanner which walks the blockchain, emitting the outputs received and watched key images
+/// spent.
+///
+/// Its position is persisted via a ScannerStore, with the state saved once the next event is
+/// requested. Accordingly, if the consumer fails while handling an event, the event will be
+/// emitted again once resumed. Outputs may be deduplicated by their absolute ID.
+///
+/// Reorganizations are detected via each block's prior block hash, and reported via
+/// `ChainEvent::Reorg`.
+pub struct ChainScanner<S: ScannerStore> {
+  scanner: Scanner,
+  store: S,
+  state: State,
+  // If the state has changed since it was last saved
+  dirty: bool,
+  // Blocks fetched yet not yet scanned
+  queued: VecDeque<(Block, Vec<Transaction>)>,
+}
+
+impl<S: ScannerStore> ChainScanner<S> {
+  /// Create a ChainScanner, resuming from the saved state if there is one, else starting from the
+  /// specified block.
+  ///
+  /// If the Scanner is protecting against the burning bug, the output keys previously received
+  /// are restored to it from the saved state.
+  pub fn new(
+    mut scanner: Scanner,
+    store: S,
+    start: usize,
+  ) -> Result<ChainScanner<S>, ChainScannerError<S::Error>> {
+    let state = match store.load().map_err(ChainScannerError::StoreError)? {
+      Some(state) => {
+        let mut state_ref = state.as_slice();
+        let state = State::read(&mut state_ref).map_err(|_| ChainScannerError::InvalidState)?;
+        if !state_ref.is_empty() {
+          Err(ChainScannerError::InvalidState)?;
+        }
+        state
+      }
+      None => State {
+        next_block: start,
+        recent: VecDeque::new(),
+        received: HashMap::new(),
+        watched: HashSet::new(),
+      },
+    };
+
+    if let Some(burning_bug) = scanner.burning_bug.as_mut() {
+      burning_bug.extend(state.received.keys().map(|key| CompressedEdwardsY(*key)));
+    }
+
+    Ok(ChainScanner { scanner, store, state, dirty: false, queued: VecDeque::new() })
+  }
+
+  /// The number of the next block to be scanned.
+  pub fn next_block(&self) -> usize {
+    self.state.next_block
+  }
+
+  pub(crate) fn store(&self) -> &S {
+    &self.store
+  }
+
+  /// Watch for a key image to be spent.
+  ///
+  /// Spends within blocks which have already been scanned will not be reported.
+  pub fn watch(&mut self, key_image: EdwardsPoint) {
+    self.dirty |= self.state.watched.insert(key_image.compress().to_bytes());
+  }
+
+  /// Stop watching for a key image to be spent.
+  pub fn unwatch(&mut self, key_image: EdwardsPoint) {
+    self.dirty |= self.state.watched.remove(&key_image.compress().to_bytes());
+  }
+
+  /// Save the current state, if it has changed.
+  pub fn save(&mut self) -> Result<(), ChainScannerError<S::Error>> {
+    if self.dirty {
+      self.store.save(&self.state.serialize()).map_err(ChainScannerError::StoreError)?;
+      self.dirty = false;
+    }
+    Ok(())
+  }
+
+  // Roll back to the most recent block still on chain
+  async fn reorg<RPC: RpcConnection>(
+    &mut self,
+    rpc: &Rpc<RPC>,
+  ) -> Result<ChainEvent, ChainScannerError<S::Error>> {
+    self.queued.clear();
+
+    // Find the most recent block which is still on chain, not modifying our state until then
+    let mut depth = 1;
+    loop {
+      let Some(hash) = self.state.recent.iter().rev().nth(depth) else {
+        Err(ChainScannerError::ReorgTooDeep)?
+      };
+      if rpc.get_block_hash(self.state.next_block - depth - 1).await? == *hash {
+        break;
+      }
+      depth += 1;
+    }
+    self.state.recent.truncate(self.state.recent.len() - depth);
+    self.state.next_block -= depth;
+
+    let next_block = self.state.next_block;
+    let orphaned = self
+      .state
+      .received
+      .iter()
+      .filter(|(_, block)| **block >= next_block)
+      .map(|(key, _)| *key)
+      .collect::<Vec<_>>();
+    for key in orphaned {
+      self.state.received.remove(&key);
+      if let Some(burning_bug) = self.scanner.burning_bug.as_mut() {
+        burning_bug.remove(&CompressedEdwardsY(key));
+      }
+    }
+
+    self.dirty = true;
+    Ok(ChainEvent::Reorg { number: next_block })
+  }
+
+  /// Scan the next block, returning None if there are no more blocks to scan.
+  ///
+  /// This saves the state resulting from the prior event before scanning.
+  pub async fn next<RPC: RpcConnection>(
+    &mut self,
+    rpc: &Rpc<RPC>,
+  ) -> Result<Option<ChainEvent>, ChainScannerError<S::Error>> {
+    self.save()?;
+
+    if self.queued.is_empty() {
+      let height = rpc.get_height().await?;
+      if self.state.next_block >= height {
+        return Ok(None);
+      }
+      let count = BATCH.min(height - self.state.next_block);
+      self.queued = rpc.get_blocks_range(self.state.next_block, count).await?.into();
+    }
+    let (block, txs) = self.queued.pop_front().unwrap();
+
+    if let Some(prior) = self.state.recent.back() {
+      if block.header.previous != *prior {
+        return self.reorg(rpc).await.map(Some);
+      }
+    }
+
+    let number = self.state.next_block;
+    let hash = block.hash();
+
+    let mut spent = vec![];
+    // The keys of the outputs within this block, as encoded on chain
+    let mut keys = HashMap::new();
+    for tx in [&block.miner_tx].into_iter().chain(&txs) {
+      for input in &tx.prefix.inputs {
+        if let Input::ToKey { key_image, .. } = input {
+          if self.state.watched.contains(&key_image.compress().to_bytes()) {
+            spent.push(*key_image);
+          }
+        }
+      }
+      keys.insert(tx.hash(), tx.prefix.outputs.iter().map(|output| output.key).collect::<Vec<_>>());
+    }
+
+    let outputs = self.scanner.scan_with_transactions(rpc, &block, txs).await?;
+    for output in outputs.iter().flat_map(Timelocked::ignore_timelock) {
+      let key = keys[&output.output.absolute.tx][usize::from(output.output.absolute.o)];
+      self.state.received.insert(key.to_bytes(), number);
+    }
+
+    self.state.next_block += 1;
+    self.state.recent.push_back(hash);
+    while self.state.recent.len() > MAX_REORG_DEPTH {
+      self.state.recent.pop_front();
+    }
+    self.dirty = true;
+
+    Ok(Some(ChainEvent::Block { number, hash, outputs, spent }))
+  }
+}
@@ -0,0 +1,149 @@
+use core::ops::Deref;
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+};
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE, traits::IsIdentity, scalar::Scalar, edwards::EdwardsPoint,
+};
+
+use crate::{
+  hash_to_scalar, random_scalar,
+  serialize::{read_scalar, read_point, read_vec, write_scalar, write_point, write_vec},
+  ringct::{generate_key_image, hash_to_point},
+  wallet::{SpendableOutput, TransactionError},
+};
+
+/// Write the outputs a watch-only wallet has received, for an offline signer to generate their
+/// key images.
+pub fn write_outputs<W: Write>(outputs: &[SpendableOutput], w: &mut W) -> io::Result<()> {
+  write_vec(SpendableOutput::write, outputs, w)
+}
+
+/// Read outputs written with `write_outputs`.
+pub fn read_outputs<R: Read>(r: &mut R) -> io::Result<Vec<SpendableOutput>> {
+  read_vec(SpendableOutput::read, r)
+}
+
+/// A key image with a proof it's the key image of a specific output.
+///
+/// The proof is the single-member ring signature wallet2 uses when exporting key images, signing
+/// the key image itself.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SignedKeyImage {
+  key_image: EdwardsPoint,
+  c: Scalar,
+  r: Scalar,
+}
+
+// The challenge for the ring signature, where the message is the key image
+#[allow(non_snake_case)]
+fn challenge(key_image: EdwardsPoint, L: EdwardsPoint, R: EdwardsPoint) -> Scalar {
+  let mut buf = Vec::with_capacity(96);
+  buf.extend(key_image.compress().to_bytes());
+  buf.extend(L.compress().to_bytes());
+  buf.extend(R.compress().to_bytes());
+  hash_to_scalar(&buf)
+}
+
+impl SignedKeyImage {
+  /// Generate the signed key image for an output, with the wallet's private spend key.
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    spend: &Zeroizing<Scalar>,
+    output: &SpendableOutput,
+  ) -> Result<SignedKeyImage, TransactionError> {
+    let key = Zeroizing::new(spend.deref() + output.key_offset());
+    if (key.deref() * ED25519_BASEPOINT_TABLE) != output.key() {
+      Err(TransactionError::WrongPrivateKey)?;
+    }
+    let key_image = generate_key_image(&key);
+
+    let nonce = Zeroizing::new(random_scalar(rng));
+    let c = challenge(
+      key_image,
+      nonce.deref() * ED25519_BASEPOINT_TABLE,
+      hash_to_point(output.key()) * nonce.deref(),
+    );
+    Ok(SignedKeyImage { key_image, c, r: nonce.deref() - (c * key.deref()) })
+  }
+
+  /// The key image.
+  pub fn key_image(&self) -> EdwardsPoint {
+    self.key_image
+  }
+
+  /// Verify this is the key image for the specified output.
+  #[allow(non_snake_case)]
+  #[must_use]
+  pub fn verify(&self, output: &SpendableOutput) -> bool {
+    // Key images must be within the prime-order subgroup
+    if self.key_image.is_identity() || (!self.key_image.is_torsion_free()) {
+      return false;
+    }
+
+    let key = output.key();
+    let L = (&self.r * ED25519_BASEPOINT_TABLE) + (key * self.c);
+    let R = (hash_to_point(key) * self.r) + (self.key_image * self.c);
+    challenge(self.key_image, L, R) == self.c
+  }
+
+  pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_point(&self.key_image, w)?;
+    write_scalar(&self.c, w)?;
+    write_scalar(&self.r, w)
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut serialized = Vec::with_capacity(96);
+    self.write(&mut serialized).unwrap();
+    serialized
+  }
+
+  pub fn read<R: Read>(r: &mut R) -> io::Result<SignedKeyImage> {
+    Ok(SignedKeyImage { key_image: read_point(r)?, c: read_scalar(r)?, r: read_scalar(r)? })
+  }
+}
+
+/// Generate the signed key images for outputs received by a watch-only wallet, with the wallet's
+/// private spend key.
+pub fn export_key_images<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  spend: &Zeroizing<Scalar>,
+  outputs: &[SpendableOutput],
+) -> Result<Vec<SignedKeyImage>, TransactionError> {
+  outputs.iter().map(|output| SignedKeyImage::new(rng, spend, output)).collect()
+}
+
+/// Verify the signed key images generated by an offline signer, returning the key image for each
+/// output.
+///
+/// The key images must be in the same order as the outputs. These key images can then be watched
+/// for in order to detect when the outputs are spent.
+pub fn import_key_images(
+  outputs: &[SpendableOutput],
+  key_images: &[SignedKeyImage],
+) -> Option<Vec<EdwardsPoint>> {
+  if outputs.len() != key_images.len() {
+    return None;
+  }
+  outputs
+    .iter()
+    .zip(key_images)
+    .map(|(output, key_image)| Some(key_image.key_image).filter(|_| key_image.verify(output)))
+    .collect()
+}
+
+/// Write signed key images generated by `export_key_images`.
+pub fn write_key_images<W: Write>(key_images: &[SignedKeyImage], w: &mut W) -> io::Result<()> {
+  write_vec(SignedKeyImage::write, key_images, w)
+}
+
+/// Read signed key images written with `write_key_images`.
+pub fn read_key_images<R: Read>(r: &mut R) -> io::Result<Vec<SignedKeyImage>> {
+  read_vec(SignedKeyImage::read, r)
+}
@@ -1,4 +1,13 @@
-use std_shims::{sync::OnceLock, vec::Vec, collections::HashSet};
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+use std_shims::{
+  sync::OnceLock,
+  vec::Vec,
+  io::{self, Read, Write},
+  collections::{HashSet, HashMap},
+};
+
+use async_trait::async_trait;
 
 #[cfg(not(feature = "std"))]
 use std_shims::sync::Mutex;
@@ -15,7 +24,10 @@ use rand_distr::num_traits::Float;
 use curve25519_dalek::edwards::EdwardsPoint;
 
 use crate::{
-  serialize::varint_len,
+  serialize::{
+    varint_len, write_varint, write_point, write_vec, read_byte, read_varint, read_point,
+    read_raw_vec, read_vec,
+  },
   wallet::SpendableOutput,
   rpc::{RpcError, RpcConnection, Rpc},
 };
@@ -35,10 +47,301 @@ fn DISTRIBUTION() -> &'static Mutex<Vec<u64>> {
   DISTRIBUTION_CELL.get_or_init(|| Mutex::new(Vec::with_capacity(3000000)))
 }
 
+/// If coinbase (miner) outputs may be selected as decoys.
+///
+/// Coinbase outputs are rarely spent in transactions with other outputs, as miners generally
+/// spend them alone, so using them as decoys may make the real spend more identifiable.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CoinbaseDecoys {
+  /// Select coinbase outputs as decoys, as Monero does.
+  Include,
+  /// Never select coinbase outputs as decoys.
+  ///
+  /// On chains with few non-coinbase outputs, such as test chains, this may require many rounds
+  /// of selection.
+  Exclude,
+}
+
+/// The algorithm used to select decoys.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecoySelection {
+  /// Select each decoy from a gamma distribution over the outputs' ages, as Monero does.
+  Gamma,
+  /// Select bins of outputs with adjacent indexes, where each bin's position is selected from the
+  /// gamma distribution, and then select multiple decoys uniformly from within each bin.
+  ///
+  /// The real spend is placed in a bin of its own, constructed the same as any other bin, so the
+  /// other members of its bin were created around the same time as it. This prevents the age of
+  /// the real spend from distinguishing it when the gamma distribution doesn't match how outputs
+  /// are actually spent.
+  Binning {
+    /// The amount of ring members in each bin, which must divide the ring length.
+    members: u8,
+    /// The amount of outputs spanned by each bin, which must be at least `members`.
+    width: u64,
+  },
+}
+
+// The source of the outputs to use as decoys
+#[async_trait]
+trait DecoySource: Sync {
+  // The maximum amount of outputs to sample when selecting decoys, if there's a limit
+  // A node will eventually have enough outputs, yet a cache which doesn't will never gain more
+  fn sample_limit(&self) -> Option<usize> {
+    None
+  }
+
+  // Returns each output and if it's a coinbase output
+  async fn get_unlocked_outputs(
+    &self,
+    indexes: &[u64],
+    height: usize,
+  ) -> Result<Vec<Option<([EdwardsPoint; 2], bool)>>, RpcError>;
+}
+
+#[async_trait]
+impl<RPC: RpcConnection + Sync> DecoySource for Rpc<RPC> {
+  async fn get_unlocked_outputs(
+    &self,
+    indexes: &[u64],
+    height: usize,
+  ) -> Result<Vec<Option<([EdwardsPoint; 2], bool)>>, RpcError> {
+    Rpc::get_unlocked_outputs_with_coinbase(self, indexes, height).await
+  }
+}
+
+/// The chain data needed to select decoys, fetched ahead of time.
+///
+/// This allows selecting decoys on a machine without a connection to a node, such as an air-gapped
+/// signer. Outputs not present in the cache are treated as unusable, so for the selected decoys to
+/// follow the intended distribution, every output which may be selected should be present.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DecoyCache {
+  height: usize,
+  distribution: Vec<u64>,
+  outputs: HashMap<u64, ([EdwardsPoint; 2], bool)>,
+}
+
+// The amount of outputs which may be sampled when selecting decoys from a cache
+// Sampling is cheap, so this is set to only fail for caches which are effectively unusable
+const CACHE_SAMPLE_LIMIT: usize = 1_000_000;
+
+#[async_trait]
+impl DecoySource for DecoyCache {
+  fn sample_limit(&self) -> Option<usize> {
+    Some(CACHE_SAMPLE_LIMIT)
+  }
+
+  async fn get_unlocked_outputs(
+    &self,
+    indexes: &[u64],
+    height: usize,
+  ) -> Result<Vec<Option<([EdwardsPoint; 2], bool)>>, RpcError> {
+    debug_assert_eq!(height, self.height);
+    Ok(indexes.iter().map(|index| self.outputs.get(index).copied()).collect())
+  }
+}
+
+impl DecoyCache {
+  /// Fetch the data needed to select decoys as of the specified height, including the specified
+  /// outputs if they're unlocked.
+  pub async fn fetch<RPC: RpcConnection>(
+    rpc: &Rpc<RPC>,
+    height: usize,
+    indexes: &[u64],
+  ) -> Result<DecoyCache, RpcError> {
+    if height >= rpc.get_height().await? {
+      Err(RpcError::InternalError("decoys being requested from too young blocks"))?;
+    }
+
+    let distribution = rpc.get_output_distribution(0, height).await?;
+
+    let mut outputs = HashMap::new();
+    // Fetch the outputs in batches to not exceed the node's limits on request size
+    for indexes in indexes.chunks(1000) {
+      for (index, output) in
+        indexes.iter().zip(rpc.get_unlocked_outputs_with_coinbase(indexes, height).await?)
+      {
+        if let Some(output) = output {
+          outputs.insert(*index, output);
+        }
+      }
+    }
+
+    Ok(DecoyCache { height, distribution, outputs })
+  }
+
+  /// The height decoys will be selected as of.
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_varint(&u64::try_from(self.height).unwrap(), w)?;
+    // The distribution is cumulative, so write the differences to keep each value small
+    write_varint(&u64::try_from(self.distribution.len()).unwrap(), w)?;
+    let mut last = 0;
+    for amount in &self.distribution {
+      write_varint(&(amount - last), w)?;
+      last = *amount;
+    }
+    write_varint(&u64::try_from(self.outputs.len()).unwrap(), w)?;
+    // Sort the outputs so the serialization is deterministic
+    let mut outputs = self.outputs.iter().collect::<Vec<_>>();
+    outputs.sort_by_key(|(index, _)| **index);
+    for (index, (output, coinbase)) in outputs {
+      write_varint(index, w)?;
+      write_point(&output[0], w)?;
+      write_point(&output[1], w)?;
+      w.write_all(&[u8::from(*coinbase)])?;
+    }
+    Ok(())
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut serialized = vec![];
+    self.write(&mut serialized).unwrap();
+    serialized
+  }
+
+  pub fn read<R: Read>(r: &mut R) -> io::Result<DecoyCache> {
+    let usize_varint = |r: &mut R| {
+      usize::try_from(read_varint(r)?)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "varint exceeded usize"))
+    };
+
+    let height = usize_varint(r)?;
+    let distribution_len = usize_varint(r)?;
+    if Some(distribution_len) != height.checked_add(1) {
+      Err(io::Error::new(io::ErrorKind::Other, "distribution didn't end at the height"))?;
+    }
+    // The length is untrusted, so the distribution is only allocated as it's actually read
+    let mut distribution = vec![];
+    let mut last = 0u64;
+    for _ in 0 .. distribution_len {
+      last = last
+        .checked_add(read_varint(r)?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "distribution overflowed"))?;
+      distribution.push(last);
+    }
+
+    let mut outputs = HashMap::new();
+    for _ in 0 .. read_varint(r)? {
+      let index = read_varint(r)?;
+      let output = [read_point(r)?, read_point(r)?];
+      let coinbase = match read_byte(r)? {
+        0 => false,
+        1 => true,
+        _ => Err(io::Error::new(io::ErrorKind::Other, "invalid coinbase flag"))?,
+      };
+      outputs.insert(index, (output, coinbase));
+    }
+
+    Ok(DecoyCache { height, distribution, outputs })
+  }
+}
+
+/// A cache of chain data needed when constructing transactions, reusable across the construction
+/// of many transactions.
+///
+/// This caches the global indexes of transactions' outputs and the distribution of outputs. The
+/// outputs fetched as potential decoys aren't cached, as requesting just the outputs not already
+/// cached would reveal the real spend to the node. If a reorganization occurs, this cache must be
+/// discarded.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct OutputCache {
+  o_indexes: HashMap<[u8; 32], Vec<u64>>,
+  distribution: Vec<u64>,
+}
+
+impl OutputCache {
+  /// Create a new, empty cache.
+  pub fn new() -> OutputCache {
+    OutputCache::default()
+  }
+
+  /// Get the global indexes of a transaction's outputs, fetching them if they aren't cached.
+  pub async fn get_o_indexes<RPC: RpcConnection>(
+    &mut self,
+    rpc: &Rpc<RPC>,
+    hash: [u8; 32],
+  ) -> Result<&[u64], RpcError> {
+    if !self.o_indexes.contains_key(&hash) {
+      let indexes = rpc.get_o_indexes(hash).await?;
+      return Ok(self.o_indexes.entry(hash).or_insert(indexes));
+    }
+    Ok(&self.o_indexes[&hash])
+  }
+}
+
+// Extend the distribution to the specified height, fetching the blocks not already present
+async fn extend_distribution<RPC: RpcConnection>(
+  rpc: &Rpc<RPC>,
+  distribution: &mut Vec<u64>,
+  height: usize,
+) -> Result<(), RpcError> {
+  if height >= rpc.get_height().await? {
+    // TODO: Don't use InternalError for the caller's failure
+    Err(RpcError::InternalError("decoys being requested from too young blocks"))?;
+  }
+
+  if distribution.len() <= height {
+    let extension = rpc.get_output_distribution(distribution.len(), height).await?;
+    distribution.extend(extension);
+  }
+  // If asked to use an older height than previously asked, truncate to ensure accuracy
+  // Should never happen, yet risks desyncing if it did
+  distribution.truncate(height + 1); // height is inclusive, and 0 is a valid height
+  Ok(())
+}
+
+// Sample an output's index from the gamma distribution Monero uses, returning None if the sample
+// was unusable
+fn sample_gamma<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  distribution: &[u64],
+  high: u64,
+  per_second: f64,
+) -> Option<u64> {
+  let mut age = Gamma::<f64>::new(19.28, 1.0 / 1.61).unwrap().sample(rng).exp();
+  if age > TIP_APPLICATION {
+    age -= TIP_APPLICATION;
+  } else {
+    // f64 does not have try_from available, which is why these are written with `as`
+    age = (rng.next_u64() % u64::try_from(RECENT_WINDOW * BLOCK_TIME).unwrap()) as f64;
+  }
+
+  let o = (age * per_second) as u64;
+  if o >= high {
+    return None;
+  }
+  let i = distribution.partition_point(|s| *s < (high - 1 - o));
+  let prev = i.saturating_sub(1);
+  let n = distribution[i] - distribution[prev];
+  if n == 0 {
+    return None;
+  }
+  Some(distribution[prev] + (rng.next_u64() % n))
+}
+
+// Sample an output's index, as sample_gamma does, erroring if the sampling budget is exhausted
+fn sample_budgeted<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  distribution: &[u64],
+  high: u64,
+  per_second: f64,
+  budget: &mut Option<usize>,
+) -> Result<Option<u64>, RpcError> {
+  if let Some(budget) = budget {
+    *budget = budget.checked_sub(1).ok_or(RpcError::NotEnoughDecoys)?;
+  }
+  Ok(sample_gamma(rng, distribution, high, per_second))
+}
+
 #[allow(clippy::too_many_arguments)]
-async fn select_n<'a, R: RngCore + CryptoRng, RPC: RpcConnection>(
+async fn select_n<R: RngCore + CryptoRng, S: DecoySource>(
   rng: &mut R,
-  rpc: &Rpc<RPC>,
+  source: &S,
   distribution: &[u64],
   height: usize,
   high: u64,
@@ -46,50 +349,20 @@ async fn select_n<'a, R: RngCore + CryptoRng, RPC: RpcConnection>(
   real: &[u64],
   used: &mut HashSet<u64>,
   count: usize,
+  coinbase: CoinbaseDecoys,
+  budget: &mut Option<usize>,
 ) -> Result<Vec<(u64, [EdwardsPoint; 2])>, RpcError> {
-  if height >= rpc.get_height().await? {
-    // TODO: Don't use InternalError for the caller's failure
-    Err(RpcError::InternalError("decoys being requested from too young blocks"))?;
-  }
-
-  #[cfg(test)]
-  let mut iters = 0;
   let mut confirmed = Vec::with_capacity(count);
   // Retries on failure. Retries are obvious as decoys, yet should be minimal
   while confirmed.len() != count {
     let remaining = count - confirmed.len();
     let mut candidates = Vec::with_capacity(remaining);
     while candidates.len() != remaining {
-      #[cfg(test)]
-      {
-        iters += 1;
-        // This is cheap and on fresh chains, a lot of rounds may be needed
-        if iters == 100 {
-          Err(RpcError::InternalError("hit decoy selection round limit"))?;
-        }
-      }
-
-      // Use a gamma distribution
-      let mut age = Gamma::<f64>::new(19.28, 1.0 / 1.61).unwrap().sample(rng).exp();
-      if age > TIP_APPLICATION {
-        age -= TIP_APPLICATION;
-      } else {
-        // f64 does not have try_from available, which is why these are written with `as`
-        age = (rng.next_u64() % u64::try_from(RECENT_WINDOW * BLOCK_TIME).unwrap()) as f64;
-      }
-
-      let o = (age * per_second) as u64;
-      if o < high {
-        let i = distribution.partition_point(|s| *s < (high - 1 - o));
-        let prev = i.saturating_sub(1);
-        let n = distribution[i] - distribution[prev];
-        if n != 0 {
-          let o = distribution[prev] + (rng.next_u64() % n);
-          if !used.contains(&o) {
-            // It will either actually be used, or is unusable and this prevents trying it again
-            used.insert(o);
-            candidates.push(o);
-          }
+      if let Some(o) = sample_budgeted(rng, distribution, high, per_second, budget)? {
+        if !used.contains(&o) {
+          // It will either actually be used, or is unusable and this prevents trying it again
+          used.insert(o);
+          candidates.push(o);
         }
       }
     }
@@ -109,13 +382,18 @@ async fn select_n<'a, R: RngCore + CryptoRng, RPC: RpcConnection>(
       }
     }
 
-    for (i, output) in rpc.get_unlocked_outputs(&candidates, height).await?.iter_mut().enumerate() {
+    for (i, output) in
+      source.get_unlocked_outputs(&candidates, height).await?.iter_mut().enumerate()
+    {
       // Don't include the real spend as a decoy, despite requesting it
       if real_indexes.contains(&i) {
         continue;
       }
 
-      if let Some(output) = output.take() {
+      if let Some((output, is_coinbase)) = output.take() {
+        if is_coinbase && (coinbase == CoinbaseDecoys::Exclude) {
+          continue;
+        }
         confirmed.push((candidates[i], output));
       }
     }
@@ -124,6 +402,95 @@ async fn select_n<'a, R: RngCore + CryptoRng, RPC: RpcConnection>(
   Ok(confirmed)
 }
 
+// Select the decoys for a single real spend in bins
+#[allow(clippy::too_many_arguments)]
+async fn select_binned<R: RngCore + CryptoRng, S: DecoySource>(
+  rng: &mut R,
+  source: &S,
+  distribution: &[u64],
+  height: usize,
+  high: u64,
+  per_second: f64,
+  real: u64,
+  used: &mut HashSet<u64>,
+  ring_len: usize,
+  members: usize,
+  width: u64,
+  coinbase: CoinbaseDecoys,
+  budget: &mut Option<usize>,
+) -> Result<Vec<(u64, [EdwardsPoint; 2])>, RpcError> {
+  // A bin containing the specified output, at a random position within the bin
+  let width = width.min(high);
+  let bin = |rng: &mut R, o: u64| {
+    let start = o.saturating_sub(rng.next_u64() % width).min(high - width);
+    start .. (start + width)
+  };
+
+  // Each bin and the decoys confirmed within it
+  let mut bins = vec![(bin(rng, real), Vec::with_capacity(members - 1))];
+  while bins.len() != (ring_len / members) {
+    if let Some(o) = sample_budgeted(rng, distribution, high, per_second, budget)? {
+      bins.push((bin(rng, o), Vec::with_capacity(members)));
+    }
+  }
+
+  let mut first = true;
+  loop {
+    let mut candidates = vec![];
+    let mut b = 0;
+    while b < bins.len() {
+      let needed = (if b == 0 { members - 1 } else { members }) - bins[b].1.len();
+      let mut options = bins[b].0.clone().filter(|o| !used.contains(o)).collect::<Vec<_>>();
+      if options.len() < needed {
+        // The real spend's bin can't be moved
+        if b == 0 {
+          Err(RpcError::NotEnoughDecoys)?;
+        }
+        // Replace this bin with a new one
+        if let Some(o) = sample_budgeted(rng, distribution, high, per_second, budget)? {
+          bins[b] = (bin(rng, o), Vec::with_capacity(members));
+        }
+        continue;
+      }
+
+      for i in 0 .. needed {
+        let j =
+          i + usize::try_from(rng.next_u64() % u64::try_from(options.len() - i).unwrap()).unwrap();
+        options.swap(i, j);
+        // It will either actually be used, or is unusable and this prevents trying it again
+        used.insert(options[i]);
+        candidates.push((b, options[i]));
+      }
+      b += 1;
+    }
+
+    if candidates.is_empty() {
+      break;
+    }
+
+    // Include the real spend in the first request, as done when selecting via a gamma distribution
+    let mut indexes = candidates.iter().map(|candidate| candidate.1).collect::<Vec<_>>();
+    if first {
+      indexes.push(real);
+    }
+    first = false;
+    indexes.sort();
+
+    let outputs = source.get_unlocked_outputs(&indexes, height).await?;
+    for (b, o) in candidates {
+      let Some((output, is_coinbase)) = outputs[indexes.binary_search(&o).unwrap()] else {
+        continue;
+      };
+      if is_coinbase && (coinbase == CoinbaseDecoys::Exclude) {
+        continue;
+      }
+      bins[b].1.push((o, output));
+    }
+  }
+
+  Ok(bins.into_iter().flat_map(|bin| bin.1).collect())
+}
+
 fn offset(ring: &[u64]) -> Vec<u64> {
   let mut res = vec![ring[0]];
   res.resize(ring.len(), 0);
@@ -151,19 +518,184 @@ impl Decoys {
     self.offsets.len()
   }
 
+  pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    w.write_all(&[self.i])?;
+    write_vec(write_varint, &self.offsets, w)?;
+    for member in &self.ring {
+      write_point(&member[0], w)?;
+      write_point(&member[1], w)?;
+    }
+    Ok(())
+  }
+
+  pub fn read<R: Read>(r: &mut R) -> io::Result<Decoys> {
+    let i = read_byte(r)?;
+    let offsets = read_vec(read_varint, r)?;
+    if usize::from(i) >= offsets.len() {
+      Err(io::Error::new(io::ErrorKind::Other, "real spend wasn't within the ring"))?;
+    }
+    let ring = read_raw_vec(|r| Ok([read_point(r)?, read_point(r)?]), offsets.len(), r)?;
+    Ok(Decoys { i, offsets, ring })
+  }
+
   /// Select decoys using the same distribution as Monero.
-  pub async fn select<R: RngCore + CryptoRng, RPC: RpcConnection>(
+  pub async fn select<R: RngCore + CryptoRng, RPC: RpcConnection + Sync>(
+    rng: &mut R,
+    rpc: &Rpc<RPC>,
+    ring_len: usize,
+    height: usize,
+    inputs: &[SpendableOutput],
+  ) -> Result<Vec<Decoys>, RpcError> {
+    Self::select_with_coinbase(rng, rpc, ring_len, height, inputs, CoinbaseDecoys::Include).await
+  }
+
+  /// Select decoys using the same distribution as Monero, with the specified policy on coinbase
+  /// outputs.
+  pub async fn select_with_coinbase<R: RngCore + CryptoRng, RPC: RpcConnection + Sync>(
+    rng: &mut R,
+    rpc: &Rpc<RPC>,
+    ring_len: usize,
+    height: usize,
+    inputs: &[SpendableOutput],
+    coinbase: CoinbaseDecoys,
+  ) -> Result<Vec<Decoys>, RpcError> {
+    #[cfg(not(feature = "std"))]
+    let mut distribution = DISTRIBUTION().lock();
+    #[cfg(feature = "std")]
+    let mut distribution = DISTRIBUTION().lock().await;
+
+    extend_distribution(rpc, &mut distribution, height).await?;
+    Self::select_from(
+      rng,
+      rpc,
+      &distribution,
+      ring_len,
+      height,
+      inputs,
+      coinbase,
+      DecoySelection::Gamma,
+    )
+    .await
+  }
+
+  /// Select decoys using the specified algorithm, with the specified policy on coinbase outputs.
+  pub async fn select_with_algorithm<R: RngCore + CryptoRng, RPC: RpcConnection + Sync>(
     rng: &mut R,
     rpc: &Rpc<RPC>,
     ring_len: usize,
     height: usize,
     inputs: &[SpendableOutput],
+    coinbase: CoinbaseDecoys,
+    selection: DecoySelection,
   ) -> Result<Vec<Decoys>, RpcError> {
     #[cfg(not(feature = "std"))]
     let mut distribution = DISTRIBUTION().lock();
     #[cfg(feature = "std")]
     let mut distribution = DISTRIBUTION().lock().await;
 
+    extend_distribution(rpc, &mut distribution, height).await?;
+    Self::select_from(rng, rpc, &distribution, ring_len, height, inputs, coinbase, selection).await
+  }
+
+  /// Select decoys using the same distribution as Monero, with the specified policy on coinbase
+  /// outputs, using (and updating) the specified cache.
+  pub async fn select_cached<R: RngCore + CryptoRng, RPC: RpcConnection + Sync>(
+    rng: &mut R,
+    rpc: &Rpc<RPC>,
+    cache: &mut OutputCache,
+    ring_len: usize,
+    height: usize,
+    inputs: &[SpendableOutput],
+    coinbase: CoinbaseDecoys,
+  ) -> Result<Vec<Decoys>, RpcError> {
+    extend_distribution(rpc, &mut cache.distribution, height).await?;
+    Self::select_from(
+      rng,
+      rpc,
+      &cache.distribution,
+      ring_len,
+      height,
+      inputs,
+      coinbase,
+      DecoySelection::Gamma,
+    )
+    .await
+  }
+
+  /// Select decoys using the same distribution as Monero, from a cache of the chain's data.
+  ///
+  /// The decoys are selected as of the height the cache was fetched for.
+  pub async fn select_offline<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    cache: &DecoyCache,
+    ring_len: usize,
+    inputs: &[SpendableOutput],
+  ) -> Result<Vec<Decoys>, RpcError> {
+    Self::select_offline_with_coinbase(rng, cache, ring_len, inputs, CoinbaseDecoys::Include).await
+  }
+
+  /// Select decoys using the same distribution as Monero, from a cache of the chain's data, with
+  /// the specified policy on coinbase outputs.
+  pub async fn select_offline_with_coinbase<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    cache: &DecoyCache,
+    ring_len: usize,
+    inputs: &[SpendableOutput],
+    coinbase: CoinbaseDecoys,
+  ) -> Result<Vec<Decoys>, RpcError> {
+    Self::select_offline_with_algorithm(
+      rng,
+      cache,
+      ring_len,
+      inputs,
+      coinbase,
+      DecoySelection::Gamma,
+    )
+    .await
+  }
+
+  /// Select decoys using the specified algorithm, from a cache of the chain's data, with the
+  /// specified policy on coinbase outputs.
+  pub async fn select_offline_with_algorithm<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    cache: &DecoyCache,
+    ring_len: usize,
+    inputs: &[SpendableOutput],
+    coinbase: CoinbaseDecoys,
+    selection: DecoySelection,
+  ) -> Result<Vec<Decoys>, RpcError> {
+    Self::select_from(
+      rng,
+      cache,
+      &cache.distribution,
+      ring_len,
+      cache.height,
+      inputs,
+      coinbase,
+      selection,
+    )
+    .await
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  async fn select_from<R: RngCore + CryptoRng, S: DecoySource>(
+    rng: &mut R,
+    source: &S,
+    distribution: &[u64],
+    ring_len: usize,
+    height: usize,
+    inputs: &[SpendableOutput],
+    coinbase: CoinbaseDecoys,
+    selection: DecoySelection,
+  ) -> Result<Vec<Decoys>, RpcError> {
+    if let DecoySelection::Binning { members, width } = selection {
+      let members = usize::from(members);
+      if (members == 0) || ((ring_len % members) != 0) || (width < u64::try_from(members).unwrap())
+      {
+        Err(RpcError::InternalError("invalid binning parameters"))?;
+      }
+    }
+
     let decoy_count = ring_len - 1;
 
     // Convert the inputs in question to the raw output data
@@ -174,14 +706,6 @@ impl Decoys {
       outputs.push((real[real.len() - 1], [input.key(), input.commitment().calculate()]));
     }
 
-    if distribution.len() <= height {
-      let extension = rpc.get_output_distribution(distribution.len(), height).await?;
-      distribution.extend(extension);
-    }
-    // If asked to use an older height than previously asked, truncate to ensure accuracy
-    // Should never happen, yet risks desyncing if it did
-    distribution.truncate(height + 1); // height is inclusive, and 0 is a valid height
-
     let high = distribution[distribution.len() - 1];
     let per_second = {
       let blocks = distribution.len().min(BLOCKS_PER_YEAR);
@@ -194,32 +718,63 @@ impl Decoys {
       used.insert(o.0);
     }
 
+    // The budget on samples is shared by this entire selection, including any reselection
+    let mut budget = source.sample_limit();
+
     // TODO: Create a TX with less than the target amount, as allowed by the protocol
-    if (high - MATURITY) < u64::try_from(inputs.len() * ring_len).unwrap() {
-      Err(RpcError::InternalError("not enough decoy candidates"))?;
+    // Saturate as young chains may have less outputs than the maturity window
+    if high.saturating_sub(MATURITY) < u64::try_from(inputs.len() * ring_len).unwrap() {
+      Err(RpcError::NotEnoughDecoys)?;
     }
 
     // Select all decoys for this transaction, assuming we generate a sane transaction
     // We should almost never naturally generate an insane transaction, hence why this doesn't
     // bother with an overage
-    let mut decoys = select_n(
-      rng,
-      rpc,
-      &distribution,
-      height,
-      high,
-      per_second,
-      &real,
-      &mut used,
-      inputs.len() * decoy_count,
-    )
-    .await?;
+    // When binning, the decoys are selected per input, as each input has its own bins
+    let mut decoys = if selection == DecoySelection::Gamma {
+      select_n(
+        rng,
+        source,
+        distribution,
+        height,
+        high,
+        per_second,
+        &real,
+        &mut used,
+        inputs.len() * decoy_count,
+        coinbase,
+        &mut budget,
+      )
+      .await?
+    } else {
+      vec![]
+    };
     real.zeroize();
 
     let mut res = Vec::with_capacity(inputs.len());
     for o in outputs {
       // Grab the decoys for this specific output
-      let mut ring = decoys.drain((decoys.len() - decoy_count) ..).collect::<Vec<_>>();
+      let mut ring = match selection {
+        DecoySelection::Gamma => decoys.drain((decoys.len() - decoy_count) ..).collect::<Vec<_>>(),
+        DecoySelection::Binning { members, width } => {
+          select_binned(
+            rng,
+            source,
+            distribution,
+            height,
+            high,
+            per_second,
+            o.0,
+            &mut used,
+            ring_len,
+            members.into(),
+            width,
+            coinbase,
+            &mut budget,
+          )
+          .await?
+        }
+      };
       ring.push(o);
       ring.sort_by(|a, b| a.0.cmp(&b.0));
 
@@ -231,7 +786,9 @@ impl Decoys {
       // fine for us to not have perfectly matching rules, especially since this code will infinite
       // loop if it can't determine sanity, which is possible with sufficient inputs on
       // sufficiently small chains
-      if high > 500 {
+      // This isn't run when binning, as the real spend's bin may be older than the target median
+      // and can't be replaced
+      if (selection == DecoySelection::Gamma) && (high > 500) {
         // Make sure the TX passes the sanity check that the median output is within the last 40%
         let target_median = high * 3 / 5;
         while ring[ring_len / 2].0 < target_median {
@@ -253,14 +810,16 @@ impl Decoys {
           ring.extend(
             select_n(
               rng,
-              rpc,
-              &distribution,
+              source,
+              distribution,
               height,
               high,
               per_second,
               &[],
               &mut used,
               ring_len - ring.len(),
+              coinbase,
+              &mut budget,
             )
             .await?,
           );
@@ -8,9 +8,12 @@ use zeroize::Zeroize;
 
 use curve25519_dalek::edwards::EdwardsPoint;
 
-use crate::serialize::{
-  varint_len, read_byte, read_bytes, read_varint, read_point, read_vec, write_byte, write_varint,
-  write_point, write_vec,
+use crate::{
+  serialize::{
+    varint_len, read_byte, read_bytes, read_varint, read_point, read_vec, write_byte, write_varint,
+    write_point, write_vec,
+  },
+  wallet::TransactionError,
 };
 
 pub const MAX_TX_EXTRA_NONCE_SIZE: usize = 255;
@@ -121,9 +124,16 @@ impl ExtraField {
   }
 }
 
+/// A transaction's extra field, as a list of typed fields.
 #[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
 pub struct Extra(Vec<ExtraField>);
 impl Extra {
+  /// The fields within this extra, in order.
+  pub fn fields(&self) -> &[ExtraField] {
+    &self.0
+  }
+
+  /// The transaction key and any additional keys.
   pub fn keys(&self) -> Option<(EdwardsPoint, Option<Vec<EdwardsPoint>>)> {
     let mut key = None;
     let mut additional = None;
@@ -140,6 +150,7 @@ impl Extra {
     key.map(|key| (key, additional))
   }
 
+  /// The payment ID, from the first nonce, if it's a payment ID.
   pub fn payment_id(&self) -> Option<PaymentId> {
     for field in &self.0 {
       if let ExtraField::Nonce(data) = field {
@@ -149,11 +160,12 @@ impl Extra {
     None
   }
 
+  /// The arbitrary data included within nonces.
   pub fn data(&self) -> Vec<Vec<u8>> {
     let mut res = vec![];
     for field in &self.0 {
       if let ExtraField::Nonce(data) = field {
-        if data[0] == ARBITRARY_DATA_MARKER {
+        if data.first() == Some(&ARBITRARY_DATA_MARKER) {
           res.push(data[1 ..].to_vec());
         }
       }
@@ -161,7 +173,10 @@ impl Extra {
     res
   }
 
-  pub(crate) fn new(key: EdwardsPoint, additional: Vec<EdwardsPoint>) -> Extra {
+  /// Create a new extra with the specified transaction key and additional keys.
+  ///
+  /// The additional keys field is omitted if no additional keys are specified.
+  pub fn new(key: EdwardsPoint, additional: Vec<EdwardsPoint>) -> Extra {
     let mut res = Extra(Vec::with_capacity(3));
     res.push(ExtraField::PublicKey(key));
     if !additional.is_empty() {
@@ -170,10 +185,32 @@ impl Extra {
     res
   }
 
-  pub(crate) fn push(&mut self, field: ExtraField) {
+  /// Push a field to the end of this extra.
+  pub fn push(&mut self, field: ExtraField) {
     self.0.push(field);
   }
 
+  /// Push a payment ID, as a nonce, to the end of this extra.
+  pub fn push_payment_id(&mut self, id: PaymentId) {
+    let mut nonce = Vec::with_capacity(1 + 32);
+    id.write(&mut nonce).unwrap();
+    self.push(ExtraField::Nonce(nonce));
+  }
+
+  /// Push arbitrary data, as a nonce, to the end of this extra.
+  ///
+  /// The data must not exceed MAX_ARBITRARY_DATA_SIZE.
+  pub fn push_data(&mut self, data: &[u8]) -> Result<(), TransactionError> {
+    if data.len() > MAX_ARBITRARY_DATA_SIZE {
+      Err(TransactionError::TooMuchData)?;
+    }
+    let mut nonce = Vec::with_capacity(1 + data.len());
+    nonce.push(ARBITRARY_DATA_MARKER);
+    nonce.extend(data);
+    self.push(ExtraField::Nonce(nonce));
+    Ok(())
+  }
+
   #[rustfmt::skip]
   pub(crate) fn fee_weight(
     outputs: usize,
@@ -1,4 +1,4 @@
-use core::ops::Deref;
+use core::ops::{Deref, DerefMut};
 use std_shims::collections::{HashSet, HashMap};
 
 use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
@@ -14,7 +14,7 @@ use crate::{
 };
 
 pub mod extra;
-pub(crate) use extra::{PaymentId, ExtraField, Extra};
+pub(crate) use extra::{PaymentId, Extra};
 
 /// Seed creation and parsing functionality.
 pub mod seed;
@@ -24,13 +24,42 @@ pub mod address;
 use address::{Network, AddressType, SubaddressIndex, AddressSpec, AddressMeta, MoneroAddress};
 
 mod scan;
-pub use scan::{ReceivedOutput, SpendableOutput, Timelocked};
+pub use scan::{Metadata, ReceivedOutput, SpendableOutput, Timelocked};
+
+mod chain;
+pub use chain::{ScannerStore, ChainScannerError, ChainEvent, ChainScanner};
+
+mod restore;
+pub use restore::WalletRestore;
+
+/// Transaction proof functionality.
+pub mod proof;
+
+/// Functionality for a watch-only wallet operating alongside an offline signer.
+///
+/// The watch-only wallet exports the outputs it has received, for which the offline signer
+/// returns signed key images, letting the watch-only wallet detect when its outputs are spent. The
+/// watch-only wallet then creates SignableTransactions, which are written, signed by the offline
+/// signer, and the resulting transactions published by the watch-only wallet.
+///
+/// The key images are signed as wallet2 signs them, yet these serializations aren't wallet2's
+/// encrypted export files.
+pub mod cold;
+
+/// Functionality for a multisig wallet to determine the key images of its outputs.
+///
+/// Each participant shares their shares of the key images, with proofs they're correct, letting
+/// any participant combining a threshold of shares detect when the group's outputs are spent.
+#[cfg(feature = "multisig")]
+pub mod multisig;
 
 pub mod decoys;
-pub use decoys::Decoys;
+pub use decoys::{CoinbaseDecoys, DecoySelection, Decoys, DecoyCache, OutputCache};
 
 mod send;
-pub use send::{FeePriority, Fee, TransactionError, Change, SignableTransaction, Eventuality};
+pub use send::{
+  FeePriority, Fee, TransactionError, Change, SignableTransaction, Eventuality, InputSelection,
+};
 #[cfg(feature = "std")]
 pub use send::SignableTransactionBuilder;
 #[cfg(feature = "multisig")]
@@ -65,43 +94,44 @@ pub(crate) fn shared_key(
   uniqueness: Option<[u8; 32]>,
   ecdh: EdwardsPoint,
   o: usize,
-) -> (u8, Scalar, [u8; 8]) {
+) -> (u8, Zeroizing<Scalar>, [u8; 8]) {
   // 8Ra
-  let mut output_derivation = ecdh.mul_by_cofactor().compress().to_bytes().to_vec();
+  let mut output_derivation = Zeroizing::new(ecdh.mul_by_cofactor().compress().to_bytes().to_vec());
 
   let mut payment_id_xor = [0; 8];
-  payment_id_xor
-    .copy_from_slice(&hash(&[output_derivation.as_ref(), [0x8d].as_ref()].concat())[.. 8]);
+  payment_id_xor.copy_from_slice(
+    &hash(&Zeroizing::new([output_derivation.as_ref(), [0x8d].as_ref()].concat()))[.. 8],
+  );
 
   // || o
-  write_varint(&o.try_into().unwrap(), &mut output_derivation).unwrap();
+  write_varint(&o.try_into().unwrap(), output_derivation.deref_mut()).unwrap();
 
-  let view_tag = hash(&[b"view_tag".as_ref(), &output_derivation].concat())[0];
+  let view_tag = hash(&Zeroizing::new([b"view_tag".as_ref(), &output_derivation].concat()))[0];
 
   // uniqueness ||
   let shared_key = if let Some(uniqueness) = uniqueness {
-    [uniqueness.as_ref(), &output_derivation].concat().to_vec()
+    Zeroizing::new([uniqueness.as_ref(), &output_derivation].concat())
   } else {
     output_derivation
   };
 
-  (view_tag, hash_to_scalar(&shared_key), payment_id_xor)
+  (view_tag, Zeroizing::new(hash_to_scalar(&shared_key)), payment_id_xor)
 }
 
-pub(crate) fn commitment_mask(shared_key: Scalar) -> Scalar {
-  let mut mask = b"commitment_mask".to_vec();
+pub(crate) fn commitment_mask(shared_key: &Scalar) -> Scalar {
+  let mut mask = Zeroizing::new(b"commitment_mask".to_vec());
   mask.extend(shared_key.to_bytes());
   hash_to_scalar(&mask)
 }
 
-pub(crate) fn amount_encryption(amount: u64, key: Scalar) -> [u8; 8] {
-  let mut amount_mask = b"amount".to_vec();
+pub(crate) fn amount_encryption(amount: u64, key: &Scalar) -> [u8; 8] {
+  let mut amount_mask = Zeroizing::new(b"amount".to_vec());
   amount_mask.extend(key.to_bytes());
   (amount ^ u64::from_le_bytes(hash(&amount_mask)[.. 8].try_into().unwrap())).to_le_bytes()
 }
 
 // TODO: Move this under EncryptedAmount?
-fn amount_decryption(amount: &EncryptedAmount, key: Scalar) -> (Scalar, u64) {
+fn amount_decryption(amount: &EncryptedAmount, key: &Scalar) -> (Scalar, u64) {
   match amount {
     EncryptedAmount::Original { mask, amount } => {
       #[cfg(feature = "experimental")]
@@ -145,6 +175,15 @@ impl ViewPair {
     ViewPair { spend, view }
   }
 
+  /// Create a ViewPair from a private spend key, deriving the view key as H(spend) as the
+  /// reference wallet does.
+  pub fn from_spend_key(spend: &Zeroizing<Scalar>) -> ViewPair {
+    ViewPair {
+      spend: spend.deref() * ED25519_BASEPOINT_TABLE,
+      view: Zeroizing::new(hash_to_scalar(Zeroizing::new(spend.to_bytes()).as_ref())),
+    }
+  }
+
   pub fn spend(&self) -> EdwardsPoint {
     self.spend
   }
@@ -153,6 +192,11 @@ impl ViewPair {
     self.view.deref() * ED25519_BASEPOINT_TABLE
   }
 
+  /// The private view key, enabling others to scan for this wallet's outputs.
+  pub fn private_view_key(&self) -> &Zeroizing<Scalar> {
+    &self.view
+  }
+
   fn subaddress_derivation(&self, index: SubaddressIndex) -> Scalar {
     hash_to_scalar(&Zeroizing::new(
       [
@@ -0,0 +1,106 @@
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+  collections::HashMap,
+};
+
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{traits::Identity, edwards::EdwardsPoint};
+
+use group::ff::Field;
+
+use dalek_ff_group as dfg;
+use frost::{Participant, FrostError, ThresholdKeys, curve::Ed25519, algorithm::WriteAddendum};
+
+use crate::{
+  ringct::{
+    hash_to_point,
+    clsag::{ClsagAddendum, add_key_image_share},
+  },
+  wallet::{SpendableOutput, TransactionError},
+};
+
+/// A participant's shares of the key images for a set of outputs, with proofs they're correct.
+///
+/// These are the same key image shares, and proofs, shared when signing a transaction.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KeyImageShares(Vec<ClsagAddendum>);
+
+impl KeyImageShares {
+  /// Create our shares of the key images for the specified outputs.
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    keys: &ThresholdKeys<Ed25519>,
+    outputs: &[SpendableOutput],
+  ) -> Result<KeyImageShares, TransactionError> {
+    let mut shares = Vec::with_capacity(outputs.len());
+    for output in outputs {
+      if keys.offset(dfg::Scalar(output.key_offset())).group_key().0 != output.key() {
+        Err(TransactionError::WrongPrivateKey)?;
+      }
+      shares.push(ClsagAddendum::new(rng, hash_to_point(output.key()), keys));
+    }
+    Ok(KeyImageShares(shares))
+  }
+
+  pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    for share in &self.0 {
+      share.write(w)?;
+    }
+    Ok(())
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut serialized = Vec::with_capacity(self.0.len() * 128);
+    self.write(&mut serialized).unwrap();
+    serialized
+  }
+
+  /// Read the shares of the key images for the specified amount of outputs.
+  pub fn read<R: Read>(outputs: usize, r: &mut R) -> io::Result<KeyImageShares> {
+    (0 .. outputs).map(|_| ClsagAddendum::read(r)).collect::<Result<_, _>>().map(KeyImageShares)
+  }
+}
+
+/// Combine the key image shares from a set of participants into the key images for the specified
+/// outputs.
+///
+/// Shares must be provided by at least the threshold of participants, including our own shares if
+/// we're participating. These key images can then be watched for in order to detect when the
+/// outputs are spent.
+pub fn combine_key_images(
+  keys: &ThresholdKeys<Ed25519>,
+  outputs: &[SpendableOutput],
+  shares: &HashMap<Participant, KeyImageShares>,
+) -> Result<Vec<EdwardsPoint>, FrostError> {
+  let params = keys.params();
+  let mut included = shares.keys().copied().collect::<Vec<_>>();
+  for l in &included {
+    if u16::from(*l) > params.n() {
+      Err(FrostError::InvalidParticipant(params.n(), *l))?;
+    }
+  }
+  included.sort();
+  let view = keys
+    .view(included.clone())
+    .map_err(|_| FrostError::InvalidSigningSet("not enough participants"))?;
+
+  let mut images = Vec::with_capacity(outputs.len());
+  for (i, output) in outputs.iter().enumerate() {
+    #[allow(non_snake_case)]
+    let H = hash_to_point(output.key());
+    let offset = keys.current_offset().unwrap_or(dfg::Scalar::ZERO).0 + output.key_offset();
+
+    let mut image = EdwardsPoint::identity();
+    for l in &included {
+      let share = shares[l].0.get(i).ok_or(FrostError::InvalidShare(*l))?;
+      if !share.verify(H, view.original_verification_share(*l)) {
+        Err(FrostError::InvalidShare(*l))?;
+      }
+      add_key_image_share(&mut image, H, offset, &included, *l, share.key_image.0);
+    }
+    images.push(image);
+  }
+  Ok(images)
+}
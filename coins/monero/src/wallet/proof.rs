@@ -0,0 +1,336 @@
+use core::ops::Deref;
+use std_shims::{vec::Vec, string::String};
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, CryptoRng};
+
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE,
+  traits::Identity,
+  scalar::Scalar,
+  edwards::{EdwardsPoint, CompressedEdwardsY},
+};
+
+use base58_monero::base58::{encode, decode};
+
+use crate::{
+  hash, hash_to_scalar, random_scalar, Commitment,
+  transaction::Transaction,
+  wallet::{
+    Extra, ViewPair, shared_key, amount_decryption,
+    address::{SubaddressIndex, MoneroAddress},
+  },
+};
+
+const OUT_PROOF_HEADER: &str = "OutProofV2";
+const IN_PROOF_HEADER: &str = "InProofV2";
+// Domain separator for V2 proofs
+const DST: &[u8] = b"TXPROOF_V2";
+
+// Length of a base58 encoded shared secret and signature
+const SHARED_SECRET_LEN: usize = 44;
+const SIGNATURE_LEN: usize = 88;
+
+/// An error when generating or checking a transaction proof.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum TxProofError {
+  #[cfg_attr(feature = "std", error("transaction didn't have a key"))]
+  MissingKey,
+  #[cfg_attr(feature = "std", error("keys weren't the transaction's keys"))]
+  WrongKeys,
+  #[cfg_attr(feature = "std", error("invalid proof encoding"))]
+  InvalidEncoding,
+  #[cfg_attr(feature = "std", error("proof had the wrong amount of signatures"))]
+  InvalidSignatureQuantity,
+  #[cfg_attr(feature = "std", error("proof didn't have a valid signature"))]
+  InvalidProof,
+}
+
+// A proof the discrete logarithm of R, with respect to G or B, is the same as that of D with
+// respect to A
+#[derive(Clone, Copy)]
+struct Signature {
+  c: Scalar,
+  s: Scalar,
+}
+
+#[allow(non_snake_case)]
+fn challenge(
+  msg: [u8; 32],
+  R: EdwardsPoint,
+  A: EdwardsPoint,
+  B: Option<EdwardsPoint>,
+  D: EdwardsPoint,
+  X: EdwardsPoint,
+  Y: EdwardsPoint,
+) -> Scalar {
+  let mut transcript = Vec::with_capacity(8 * 32);
+  transcript.extend(msg);
+  transcript.extend(D.compress().to_bytes());
+  transcript.extend(X.compress().to_bytes());
+  transcript.extend(Y.compress().to_bytes());
+  transcript.extend(hash(DST));
+  transcript.extend(R.compress().to_bytes());
+  transcript.extend(A.compress().to_bytes());
+  transcript.extend(B.map(|B| B.compress().to_bytes()).unwrap_or([0; 32]));
+  hash_to_scalar(&transcript)
+}
+
+impl Signature {
+  #[allow(non_snake_case)]
+  fn sign<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    msg: [u8; 32],
+    R: EdwardsPoint,
+    A: EdwardsPoint,
+    B: Option<EdwardsPoint>,
+    D: EdwardsPoint,
+    r: &Scalar,
+  ) -> Signature {
+    let k = Zeroizing::new(random_scalar(rng));
+    let X = match B {
+      Some(B) => k.deref() * B,
+      None => k.deref() * ED25519_BASEPOINT_TABLE,
+    };
+    let Y = k.deref() * A;
+    let c = challenge(msg, R, A, B, D, X, Y);
+    Signature { c, s: k.deref() - (c * r) }
+  }
+
+  #[allow(non_snake_case)]
+  fn verify(
+    &self,
+    msg: [u8; 32],
+    R: EdwardsPoint,
+    A: EdwardsPoint,
+    B: Option<EdwardsPoint>,
+    D: EdwardsPoint,
+  ) -> bool {
+    if !D.is_torsion_free() {
+      return false;
+    }
+    let X = (self.c * R) +
+      match B {
+        Some(B) => self.s * B,
+        None => &self.s * ED25519_BASEPOINT_TABLE,
+      };
+    let Y = (self.c * D) + (self.s * A);
+    challenge(msg, R, A, B, D, X, Y) == self.c
+  }
+}
+
+// The message signed, binding the proof to the transaction
+fn message(tx: &Transaction, message: &[u8]) -> [u8; 32] {
+  hash(&[tx.hash().as_ref(), message].concat())
+}
+
+fn tx_keys(tx: &Transaction) -> Result<Vec<EdwardsPoint>, TxProofError> {
+  let extra =
+    Extra::read::<&[u8]>(&mut tx.prefix.extra.as_ref()).map_err(|_| TxProofError::MissingKey)?;
+  let (key, additional) = extra.keys().ok_or(TxProofError::MissingKey)?;
+  let mut keys = vec![key];
+  keys.extend(additional.unwrap_or(vec![]));
+  Ok(keys)
+}
+
+fn encode_proof(header: &str, proofs: &[(EdwardsPoint, Signature)]) -> String {
+  let mut res = String::from(header);
+  for (shared_secret, signature) in proofs {
+    res.push_str(&encode(&shared_secret.compress().to_bytes()).unwrap());
+    res.push_str(&encode(&[signature.c.to_bytes(), signature.s.to_bytes()].concat()).unwrap());
+  }
+  res
+}
+
+fn decode_proof(proof: &str) -> Result<Vec<(EdwardsPoint, Signature)>, TxProofError> {
+  let invalid = |_| TxProofError::InvalidEncoding;
+  let point = |bytes: Vec<u8>| {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| TxProofError::InvalidEncoding)?;
+    let point = CompressedEdwardsY(bytes).decompress().ok_or(TxProofError::InvalidEncoding)?;
+    // Only accept the canonical encoding, so the challenge is over the same bytes
+    if point.compress().to_bytes() != bytes {
+      Err(TxProofError::InvalidEncoding)?;
+    }
+    Ok(point)
+  };
+  let scalar = |bytes: &[u8]| {
+    Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes.try_into().unwrap()))
+      .ok_or(TxProofError::InvalidEncoding)
+  };
+
+  if !proof.is_ascii() || ((proof.len() % (SHARED_SECRET_LEN + SIGNATURE_LEN)) != 0) {
+    Err(TxProofError::InvalidEncoding)?;
+  }
+  let mut res = vec![];
+  for chunk in proof.as_bytes().chunks(SHARED_SECRET_LEN + SIGNATURE_LEN) {
+    // Safe as this is ASCII
+    let chunk = core::str::from_utf8(chunk).unwrap();
+    let shared_secret = point(decode(&chunk[.. SHARED_SECRET_LEN]).map_err(invalid)?)?;
+    let signature = decode(&chunk[SHARED_SECRET_LEN ..]).map_err(invalid)?;
+    if signature.len() != 64 {
+      Err(TxProofError::InvalidEncoding)?;
+    }
+    res.push((
+      shared_secret,
+      Signature { c: scalar(&signature[.. 32])?, s: scalar(&signature[32 ..])? },
+    ));
+  }
+  Ok(res)
+}
+
+/// Generate an OutProofV2, proving the transaction paid the specified address, from the
+/// transaction's private keys.
+///
+/// This is compatible with the proofs generated by wallet2's `get_tx_proof`.
+pub fn out_proof<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  tx: &Transaction,
+  tx_key: &Zeroizing<Scalar>,
+  additional_keys: &[Zeroizing<Scalar>],
+  address: &MoneroAddress,
+  message: &[u8],
+) -> Result<String, TxProofError> {
+  let msg = self::message(tx, message);
+  let spend = Some(address.spend).filter(|_| address.is_subaddress());
+
+  let mut found = false;
+  let mut proofs = vec![];
+  for key in tx_keys(tx)? {
+    // Find the private key for this public key, as additional keys may not have one
+    let private_key =
+      [tx_key].into_iter().chain(additional_keys).map(|key| key.deref()).find(|private_key| {
+        key ==
+          match spend {
+            Some(spend) => *private_key * spend,
+            None => *private_key * ED25519_BASEPOINT_TABLE,
+          }
+      });
+
+    let Some(private_key) = private_key else {
+      // Include an invalid signature so the signatures align with the keys
+      proofs.push((EdwardsPoint::identity(), Signature { c: Scalar::ZERO, s: Scalar::ZERO }));
+      continue;
+    };
+    found = true;
+
+    let shared_secret = private_key * address.view;
+    proofs.push((
+      shared_secret,
+      Signature::sign(rng, msg, key, address.view, spend, shared_secret, private_key),
+    ));
+  }
+
+  if !found {
+    Err(TxProofError::WrongKeys)?;
+  }
+  Ok(encode_proof(OUT_PROOF_HEADER, &proofs))
+}
+
+/// Generate an InProofV2, proving the transaction paid the specified address, from the
+/// recipient's view key.
+///
+/// This is compatible with the proofs generated by wallet2's `get_tx_proof`.
+pub fn in_proof<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  tx: &Transaction,
+  view: &ViewPair,
+  subaddress: Option<SubaddressIndex>,
+  message: &[u8],
+) -> Result<String, TxProofError> {
+  let msg = self::message(tx, message);
+  let (spend, view_key) = match subaddress {
+    Some(subaddress) => {
+      let (spend, view_key) = view.subaddress_keys(subaddress);
+      (Some(spend), view_key)
+    }
+    None => (None, view.view()),
+  };
+
+  let proofs = tx_keys(tx)?
+    .into_iter()
+    .map(|key| {
+      let shared_secret = view.view.deref() * key;
+      (shared_secret, Signature::sign(rng, msg, view_key, key, spend, shared_secret, &view.view))
+    })
+    .collect::<Vec<_>>();
+  Ok(encode_proof(IN_PROOF_HEADER, &proofs))
+}
+
+/// Check an OutProofV2 or InProofV2, returning the amount the transaction paid the specified
+/// address.
+///
+/// This is compatible with wallet2's `check_tx_proof`, except shared secrets are only used if
+/// their signature is valid. An amount of 0 is returned if the proof is valid yet the address
+/// wasn't paid.
+pub fn check_tx_proof(
+  tx: &Transaction,
+  address: &MoneroAddress,
+  message: &[u8],
+  proof: &str,
+) -> Result<u64, TxProofError> {
+  let (out, proof) = if let Some(proof) = proof.strip_prefix(OUT_PROOF_HEADER) {
+    (true, proof)
+  } else if let Some(proof) = proof.strip_prefix(IN_PROOF_HEADER) {
+    (false, proof)
+  } else {
+    Err(TxProofError::InvalidEncoding)?
+  };
+  let proofs = decode_proof(proof)?;
+
+  let keys = tx_keys(tx)?;
+  if proofs.len() != keys.len() {
+    Err(TxProofError::InvalidSignatureQuantity)?;
+  }
+
+  let msg = self::message(tx, message);
+  let spend = Some(address.spend).filter(|_| address.is_subaddress());
+  let shared_secrets = keys
+    .into_iter()
+    .zip(proofs)
+    .map(|(key, (shared_secret, signature))| {
+      let valid = if out {
+        signature.verify(msg, key, address.view, spend, shared_secret)
+      } else {
+        signature.verify(msg, address.view, key, spend, shared_secret)
+      };
+      Some(shared_secret).filter(|_| valid)
+    })
+    .collect::<Vec<_>>();
+  if shared_secrets.iter().all(Option::is_none) {
+    Err(TxProofError::InvalidProof)?;
+  }
+
+  let mut received = 0u64;
+  for (o, output) in tx.prefix.outputs.iter().enumerate() {
+    // Outputs may use the primary key or their additional key
+    for shared_secret in [shared_secrets[0], shared_secrets.get(o + 1).copied().flatten()] {
+      let Some(shared_secret) = shared_secret else { continue };
+      let (_, shared_key, _) = shared_key(None, shared_secret, o);
+      if output.key != ((shared_key.deref() * ED25519_BASEPOINT_TABLE) + address.spend).compress() {
+        continue;
+      }
+
+      let amount = match output.amount {
+        Some(amount) => amount,
+        None => match tx.rct_signatures.base.encrypted_amounts.get(o) {
+          Some(encrypted) => {
+            let (mask, amount) = amount_decryption(encrypted, &shared_key);
+            // Only credit the amount if it's what was actually committed to
+            if Some(&Commitment::new(mask, amount).calculate()) ==
+              tx.rct_signatures.base.commitments.get(o)
+            {
+              amount
+            } else {
+              0
+            }
+          }
+          None => 0,
+        },
+      };
+      received = received.saturating_add(amount);
+      break;
+    }
+  }
+  Ok(received)
+}
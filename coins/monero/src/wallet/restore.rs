@@ -0,0 +1,265 @@
+use core::{ops::Deref, convert::Infallible};
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+  collections::HashMap,
+};
+
+use zeroize::Zeroizing;
+
+use curve25519_dalek::{scalar::Scalar, edwards::EdwardsPoint};
+
+use crate::{
+  serialize::{read_bytes, read_byte, read_varint, read_vec, write_varint, write_vec},
+  ringct::generate_key_image,
+  rpc::{RpcError, RpcConnection, Rpc},
+  wallet::{Scanner, SpendableOutput, ScannerStore, ChainScannerError, ChainEvent, ChainScanner},
+};
+
+// Blocks are expected every two minutes, yet their timestamps may be inaccurate by hours
+const TIMESTAMP_MARGIN: usize = 720;
+
+// Holds the ChainScanner's state in memory, so it can be saved alongside the restore's state
+struct Buffer(Option<Vec<u8>>);
+impl ScannerStore for Buffer {
+  type Error = Infallible;
+  fn load(&self) -> Result<Option<Vec<u8>>, Infallible> {
+    Ok(self.0.clone())
+  }
+  fn save(&mut self, state: &[u8]) -> Result<(), Infallible> {
+    self.0 = Some(state.to_vec());
+    Ok(())
+  }
+}
+
+fn chain_error<E>(err: ChainScannerError<Infallible>) -> ChainScannerError<E> {
+  match err {
+    ChainScannerError::RpcError(err) => ChainScannerError::RpcError(err),
+    ChainScannerError::StoreError(err) => match err {},
+    ChainScannerError::InvalidState => ChainScannerError::InvalidState,
+    ChainScannerError::ReorgTooDeep => ChainScannerError::ReorgTooDeep,
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Restored {
+  // The block the output was received in
+  received: usize,
+  output: SpendableOutput,
+  // The block the output was spent in, if it's been spent
+  spent: Option<usize>,
+}
+
+impl Restored {
+  fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write_varint(&u64::try_from(self.received).unwrap(), w)?;
+    self.output.write(w)?;
+    match self.spent {
+      Some(block) => {
+        w.write_all(&[1])?;
+        write_varint(&u64::try_from(block).unwrap(), w)
+      }
+      None => w.write_all(&[0]),
+    }
+  }
+
+  fn read<R: Read>(r: &mut R) -> io::Result<Restored> {
+    let usize_varint = |r: &mut R| {
+      usize::try_from(read_varint(r)?)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "varint exceeded usize"))
+    };
+    Ok(Restored {
+      received: usize_varint(r)?,
+      output: SpendableOutput::read(r)?,
+      spent: match read_byte(r)? {
+        0 => None,
+        1 => Some(usize_varint(r)?),
+        _ => Err(io::Error::new(io::ErrorKind::Other, "invalid spent flag"))?,
+      },
+    })
+  }
+}
+
+/// A wallet being restored from the blockchain, tracking the outputs it received and which of them
+/// have been spent.
+///
+/// Restoring is built on a ChainScanner, and accordingly handles reorganizations and may be
+/// resumed. Its state, including the ChainScanner's, is saved to the ScannerStore after each block
+/// is scanned.
+pub struct WalletRestore<S: ScannerStore> {
+  chain: ChainScanner<Buffer>,
+  store: S,
+  spend: Zeroizing<Scalar>,
+  // The outputs received, by their key images
+  outputs: HashMap<[u8; 32], Restored>,
+}
+
+impl<S: ScannerStore> WalletRestore<S> {
+  /// Create a WalletRestore, resuming from the saved state if there is one, else starting from the
+  /// specified block.
+  ///
+  /// The Scanner must be for the wallet with the specified private spend key, which is used to
+  /// determine the key images of the outputs received.
+  pub fn new(
+    scanner: Scanner,
+    spend: Zeroizing<Scalar>,
+    store: S,
+    start: usize,
+  ) -> Result<WalletRestore<S>, ChainScannerError<S::Error>> {
+    let (chain, outputs) = match store.load().map_err(ChainScannerError::StoreError)? {
+      Some(state) => {
+        let mut state_ref = state.as_slice();
+        let mut read = || -> io::Result<_> {
+          let chain = read_vec(read_byte, &mut state_ref)?;
+          let outputs = read_vec(|r| Ok((read_bytes(r)?, Restored::read(r)?)), &mut state_ref)?;
+          Ok((chain, outputs.into_iter().collect()))
+        };
+        let (chain, outputs) = read().map_err(|_| ChainScannerError::InvalidState)?;
+        if !state_ref.is_empty() {
+          Err(ChainScannerError::InvalidState)?;
+        }
+        (Some(chain), outputs)
+      }
+      None => (None, HashMap::new()),
+    };
+
+    Ok(WalletRestore {
+      chain: ChainScanner::new(scanner, Buffer(chain), start).map_err(chain_error)?,
+      store,
+      spend,
+      outputs,
+    })
+  }
+
+  /// Find the block to restore from for a wallet created at the specified UNIX timestamp.
+  ///
+  /// As block timestamps may be inaccurate, the returned block is a day prior to the first block
+  /// with a timestamp after the specified timestamp.
+  pub async fn height_from_timestamp<RPC: RpcConnection>(
+    rpc: &Rpc<RPC>,
+    timestamp: u64,
+  ) -> Result<usize, RpcError> {
+    // Binary search for the first block with a timestamp at or after the specified timestamp
+    let mut low = 0;
+    let mut high = rpc.get_height().await?;
+    while low < high {
+      let mid = (low + high) / 2;
+      if rpc.get_block_by_number(mid).await?.header.timestamp < timestamp {
+        low = mid + 1;
+      } else {
+        high = mid;
+      }
+    }
+    Ok(low.saturating_sub(TIMESTAMP_MARGIN))
+  }
+
+  /// The number of the next block to be scanned.
+  pub fn next_block(&self) -> usize {
+    self.chain.next_block()
+  }
+
+  /// Save the current state.
+  pub fn save(&mut self) -> Result<(), ChainScannerError<S::Error>> {
+    self.chain.save().map_err(chain_error)?;
+    let mut state = vec![];
+    write_vec(
+      |byte, w| w.write_all(&[*byte]),
+      self.chain.store().0.as_deref().unwrap_or(&[]),
+      &mut state,
+    )
+    .unwrap();
+    write_vec(
+      |(key_image, restored), w| {
+        w.write_all(*key_image)?;
+        restored.write(w)
+      },
+      &self.outputs.iter().collect::<Vec<_>>(),
+      &mut state,
+    )
+    .unwrap();
+    self.store.save(&state).map_err(ChainScannerError::StoreError)
+  }
+
+  /// Scan the next block, returning false if there are no more blocks to scan.
+  pub async fn next<RPC: RpcConnection>(
+    &mut self,
+    rpc: &Rpc<RPC>,
+  ) -> Result<bool, ChainScannerError<S::Error>> {
+    let Some(event) = self.chain.next(rpc).await.map_err(chain_error)? else {
+      return Ok(false);
+    };
+
+    match event {
+      ChainEvent::Block { number, outputs, spent, .. } => {
+        for output in outputs.iter().flat_map(|outputs| outputs.ignore_timelock()) {
+          let key_image =
+            generate_key_image(&Zeroizing::new(self.spend.deref() + output.key_offset()));
+          self.chain.watch(key_image);
+          self.outputs.insert(
+            key_image.compress().to_bytes(),
+            Restored { received: number, output, spent: None },
+          );
+        }
+        for key_image in spent {
+          if let Some(restored) = self.outputs.get_mut(&key_image.compress().to_bytes()) {
+            restored.spent = Some(number);
+          }
+        }
+      }
+      ChainEvent::Reorg { number } => {
+        self.outputs.retain(|_, restored| restored.received < number);
+        for restored in self.outputs.values_mut() {
+          if matches!(restored.spent, Some(spent) if spent >= number) {
+            restored.spent = None;
+          }
+        }
+      }
+    }
+
+    self.save()?;
+    Ok(true)
+  }
+
+  /// Scan until there are no more blocks to scan.
+  pub async fn sync<RPC: RpcConnection>(
+    &mut self,
+    rpc: &Rpc<RPC>,
+  ) -> Result<(), ChainScannerError<S::Error>> {
+    while self.next(rpc).await? {}
+    Ok(())
+  }
+
+  /// The outputs received which haven't been spent.
+  ///
+  /// Outputs may still be locked, either as they were recently received or due to their timelock.
+  pub fn outputs(&self) -> Vec<SpendableOutput> {
+    self
+      .outputs
+      .values()
+      .filter(|restored| restored.spent.is_none())
+      .map(|restored| restored.output.clone())
+      .collect()
+  }
+
+  /// The key images of the outputs received which have been spent.
+  pub fn spent(&self) -> Vec<EdwardsPoint> {
+    self
+      .outputs
+      .iter()
+      .filter(|(_, restored)| restored.spent.is_some())
+      .map(|(_, restored)| {
+        generate_key_image(&Zeroizing::new(self.spend.deref() + restored.output.key_offset()))
+      })
+      .collect()
+  }
+
+  /// The sum of the amounts of the outputs received which haven't been spent.
+  pub fn balance(&self) -> u64 {
+    self
+      .outputs
+      .values()
+      .filter(|restored| restored.spent.is_none())
+      .map(|restored| restored.output.commitment().amount)
+      .sum()
+  }
+}
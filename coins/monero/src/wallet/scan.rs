@@ -6,16 +6,23 @@ use std_shims::{
 
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar, edwards::EdwardsPoint};
+use curve25519_dalek::{
+  constants::ED25519_BASEPOINT_TABLE,
+  scalar::Scalar,
+  edwards::{EdwardsPoint, CompressedEdwardsY},
+};
 
 use crate::{
   Commitment,
-  serialize::{read_byte, read_u32, read_u64, read_bytes, read_scalar, read_point, read_raw_vec},
+  serialize::{
+    read_byte, read_u32, read_u64, read_varint, read_bytes, read_scalar, read_point, read_raw_vec,
+  },
   transaction::{Input, Timelock, Transaction},
   block::Block,
   rpc::{RpcError, RpcConnection, Rpc},
   wallet::{
-    PaymentId, Extra, address::SubaddressIndex, Scanner, uniqueness, shared_key, amount_decryption,
+    PaymentId, Extra, address::SubaddressIndex, Scanner, OutputCache, uniqueness, shared_key,
+    amount_decryption,
   },
 };
 
@@ -97,13 +104,15 @@ impl OutputData {
 pub struct Metadata {
   /// The subaddress this output was sent to.
   pub subaddress: Option<SubaddressIndex>,
-  /// The payment ID included with this output.
-  /// This will be gibberish if the payment ID wasn't intended for the recipient or wasn't included.
-  // Could be an Option, as extra doesn't necessarily have a payment ID, yet all Monero TXs should
-  // have this making it simplest for it to be as-is.
-  pub payment_id: [u8; 8],
+  /// The payment ID included with this output, decrypted if it was encrypted.
+  /// This will be None if the transaction didn't include a payment ID. An encrypted payment ID
+  /// will be gibberish if it wasn't intended for the recipient, as Monero includes a dummy payment
+  /// ID with transactions which don't have one.
+  pub payment_id: Option<PaymentId>,
   /// Arbitrary data encoded in TX extra.
   pub arbitrary_data: Vec<Vec<u8>>,
+  /// The timelock of the transaction which created this output.
+  pub timelock: Timelock,
 }
 
 impl core::fmt::Debug for Metadata {
@@ -111,14 +120,28 @@ impl core::fmt::Debug for Metadata {
     fmt
       .debug_struct("Metadata")
       .field("subaddress", &self.subaddress)
-      .field("payment_id", &hex::encode(self.payment_id))
+      .field("payment_id", &self.payment_id)
       .field("arbitrary_data", &self.arbitrary_data.iter().map(hex::encode).collect::<Vec<_>>())
+      .field("timelock", &self.timelock)
       .finish()
   }
 }
 
+// Metadata was originally written without a version, starting with the subaddress flag (0 or 1).
+// The current encoding is prefixed with a version which can't be confused with that flag.
+const METADATA_VERSION: u8 = 2;
+
+fn read_flag<R: Read>(r: &mut R) -> io::Result<bool> {
+  match read_byte(r)? {
+    0 => Ok(false),
+    1 => Ok(true),
+    _ => Err(io::Error::new(io::ErrorKind::Other, "invalid flag in metadata")),
+  }
+}
+
 impl Metadata {
   pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    w.write_all(&[METADATA_VERSION])?;
     if let Some(subaddress) = self.subaddress {
       w.write_all(&[1])?;
       w.write_all(&subaddress.account().to_le_bytes())?;
@@ -126,24 +149,40 @@ impl Metadata {
     } else {
       w.write_all(&[0])?;
     }
-    w.write_all(&self.payment_id)?;
+    if let Some(payment_id) = self.payment_id {
+      w.write_all(&[1])?;
+      payment_id.write(w)?;
+    } else {
+      w.write_all(&[0])?;
+    }
 
     w.write_all(&u32::try_from(self.arbitrary_data.len()).unwrap().to_le_bytes())?;
     for part in &self.arbitrary_data {
       w.write_all(&[u8::try_from(part.len()).unwrap()])?;
       w.write_all(part)?;
     }
-    Ok(())
+    self.timelock.write(w)
   }
 
   pub fn serialize(&self) -> Vec<u8> {
-    let mut serialized = Vec::with_capacity(1 + 8 + 1);
+    let mut serialized = Vec::with_capacity(1 + 1 + 1 + 1 + 8 + 4 + 1);
     self.write(&mut serialized).unwrap();
     serialized
   }
 
+  /// Read Metadata, including Metadata written by prior versions of this library.
+  ///
+  /// Metadata written by prior versions always has an encrypted payment ID (as the 8-byte ID was
+  /// always present) and has a timelock of `Timelock::None`, as the timelock wasn't recorded.
   pub fn read<R: Read>(r: &mut R) -> io::Result<Metadata> {
-    let subaddress = if read_byte(r)? == 1 {
+    let (legacy, has_subaddress) = match read_byte(r)? {
+      0 => (true, false),
+      1 => (true, true),
+      METADATA_VERSION => (false, read_flag(r)?),
+      _ => Err(io::Error::new(io::ErrorKind::Other, "unknown metadata version"))?,
+    };
+
+    let subaddress = if has_subaddress {
       Some(
         SubaddressIndex::new(read_u32(r)?, read_u32(r)?)
           .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid subaddress in metadata"))?,
@@ -152,9 +191,17 @@ impl Metadata {
       None
     };
 
+    let payment_id = if legacy {
+      Some(PaymentId::Encrypted(read_bytes(r)?))
+    } else if read_flag(r)? {
+      Some(PaymentId::read(r)?)
+    } else {
+      None
+    };
+
     Ok(Metadata {
       subaddress,
-      payment_id: read_bytes(r)?,
+      payment_id,
       arbitrary_data: {
         let mut data = vec![];
         for _ in 0 .. read_u32(r)? {
@@ -163,6 +210,7 @@ impl Metadata {
         }
         data
       },
+      timelock: if legacy { Timelock::None } else { Timelock::from_raw(read_varint(r)?) },
     })
   }
 }
@@ -192,6 +240,10 @@ impl ReceivedOutput {
     &self.metadata.arbitrary_data
   }
 
+  pub fn timelock(&self) -> Timelock {
+    self.metadata.timelock
+  }
+
   pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
     self.absolute.write(w)?;
     self.data.write(w)?;
@@ -246,6 +298,20 @@ impl SpendableOutput {
     Ok(output)
   }
 
+  /// Create a spendable output, using (and updating) the specified cache of output indexes.
+  pub async fn from_cached<RPC: RpcConnection>(
+    rpc: &Rpc<RPC>,
+    cache: &mut OutputCache,
+    output: ReceivedOutput,
+  ) -> Result<SpendableOutput, RpcError> {
+    let global_index = *cache
+      .get_o_indexes(rpc, output.absolute.tx)
+      .await?
+      .get(usize::from(output.absolute.o))
+      .ok_or(RpcError::InvalidNode)?;
+    Ok(SpendableOutput { output, global_index })
+  }
+
   pub fn key(&self) -> EdwardsPoint {
     self.output.key()
   }
@@ -262,6 +328,10 @@ impl SpendableOutput {
     self.output.arbitrary_data()
   }
 
+  pub fn timelock(&self) -> Timelock {
+    self.output.timelock()
+  }
+
   pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
     self.output.write(w)?;
     w.write_all(&self.global_index.to_le_bytes())
@@ -319,24 +389,80 @@ impl<O: Clone + Zeroize> Timelocked<O> {
   }
 }
 
+// Outputs found by scanning a transaction, with their keys as they were encoded in the
+// transaction, before the burning bug protection was applied
+struct Scanned(Timelock, Vec<(CompressedEdwardsY, ReceivedOutput)>);
+
 impl Scanner {
   /// Scan a transaction to discover the received outputs.
   pub fn scan_transaction(&mut self, tx: &Transaction) -> Timelocked<ReceivedOutput> {
+    let scanned = self.scan_transaction_without_burning_bug(tx);
+    self.apply_burning_bug(scanned)
+  }
+
+  /// Scan transactions to discover the received outputs, returning the outputs for each
+  /// transaction.
+  ///
+  /// With the `parallel` feature, the transactions are scanned in parallel. The results are the
+  /// same as scanning each transaction, in order, with `scan_transaction`.
+  pub fn scan_transactions(&mut self, txs: &[Transaction]) -> Vec<Timelocked<ReceivedOutput>> {
+    #[cfg(feature = "parallel")]
+    let scanned = {
+      use rayon::prelude::*;
+      txs.par_iter().map(|tx| self.scan_transaction_without_burning_bug(tx)).collect::<Vec<_>>()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let scanned =
+      txs.iter().map(|tx| self.scan_transaction_without_burning_bug(tx)).collect::<Vec<_>>();
+
+    // Apply the burning bug protection in order, so the same outputs are discarded as if these
+    // transactions were scanned one at a time
+    scanned.into_iter().map(|scanned| self.apply_burning_bug(scanned)).collect()
+  }
+
+  fn apply_burning_bug(&mut self, scanned: Scanned) -> Timelocked<ReceivedOutput> {
+    let Scanned(timelock, outputs) = scanned;
+    Timelocked(
+      timelock,
+      outputs
+        .into_iter()
+        .filter_map(|(key, output)| {
+          if let Some(burning_bug) = self.burning_bug.as_mut() {
+            // If this key was already used, whether by a prior transaction or a prior output in
+            // this transaction, drop this output
+            if !burning_bug.insert(key) {
+              return None;
+            }
+          }
+          Some(output)
+        })
+        .collect(),
+    )
+  }
+
+  // Scan a transaction, only checking the burning bug protection against the keys already used
+  fn scan_transaction_without_burning_bug(&self, tx: &Transaction) -> Scanned {
     // Only scan RCT TXs since we can only spend RCT outputs
     if tx.prefix.version != 2 {
-      return Timelocked(tx.prefix.timelock, vec![]);
+      return Scanned(tx.prefix.timelock, vec![]);
     }
 
     let Ok(extra) = Extra::read::<&[u8]>(&mut tx.prefix.extra.as_ref()) else {
-      return Timelocked(tx.prefix.timelock, vec![]);
+      return Scanned(tx.prefix.timelock, vec![]);
     };
 
     let Some((tx_key, additional)) = extra.keys() else {
-      return Timelocked(tx.prefix.timelock, vec![]);
+      return Scanned(tx.prefix.timelock, vec![]);
     };
 
     let payment_id = extra.payment_id();
 
+    // The ECDH with the transaction key, and the uniqueness, are the same for every output, so
+    // only calculate them once
+    let tx_ecdh = self.pair.view.deref() * tx_key;
+    let uniqueness =
+      if self.burning_bug.is_none() { Some(uniqueness(&tx.prefix.inputs)) } else { None };
+
     let mut res = vec![];
     for (o, output) in tx.prefix.outputs.iter().enumerate() {
       // https://github.com/serai-dex/serai/issues/106
@@ -346,16 +472,21 @@ impl Scanner {
         }
       }
 
-      let output_key = output.key.decompress();
-      if output_key.is_none() {
-        continue;
-      }
-      let output_key = output_key.unwrap();
-
-      for key in [Some(Some(&tx_key)), additional.as_ref().map(|additional| additional.get(o))] {
-        let key = if let Some(Some(key)) = key {
-          key
-        } else if let Some(None) = key {
+      // Only decompress the output key once an ECDH passes the view tag check, as the view tag
+      // will rule out most outputs which aren't ours
+      let mut output_key = None;
+
+      let additional_ecdh = || {
+        additional
+          .as_ref()
+          .map(|additional| additional.get(o).map(|key| self.pair.view.deref() * key))
+      };
+      for ecdh in
+        core::iter::once(Some(Some(tx_ecdh))).chain(core::iter::once_with(additional_ecdh))
+      {
+        let ecdh = if let Some(Some(ecdh)) = ecdh {
+          ecdh
+        } else if let Some(None) = ecdh {
           // This is non-standard. There were additional keys, yet not one for this output
           // https://github.com/monero-project/monero/
           //   blob/04a1e2875d6e35e27bb21497988a6c822d319c28/
@@ -365,18 +496,9 @@ impl Scanner {
         } else {
           break;
         };
-        let (view_tag, shared_key, payment_id_xor) = shared_key(
-          if self.burning_bug.is_none() { Some(uniqueness(&tx.prefix.inputs)) } else { None },
-          self.pair.view.deref() * key,
-          o,
-        );
-
-        let payment_id =
-          if let Some(PaymentId::Encrypted(id)) = payment_id.map(|id| id ^ payment_id_xor) {
-            id
-          } else {
-            payment_id_xor
-          };
+        let (view_tag, shared_key, payment_id_xor) = shared_key(uniqueness, ecdh, o);
+
+        let payment_id = payment_id.map(|id| id ^ payment_id_xor);
 
         if let Some(actual_view_tag) = output.view_tag {
           if actual_view_tag != view_tag {
@@ -384,9 +506,15 @@ impl Scanner {
           }
         }
 
+        if output_key.is_none() {
+          output_key = output.key.decompress();
+        }
+        let Some(output_key) = output_key else { break };
+
         // P - shared == spend
-        let subaddress =
-          self.subaddresses.get(&(output_key - (&shared_key * ED25519_BASEPOINT_TABLE)).compress());
+        let subaddress = self
+          .subaddresses
+          .get(&(output_key - (shared_key.deref() * ED25519_BASEPOINT_TABLE)).compress());
         if subaddress.is_none() {
           continue;
         }
@@ -398,7 +526,7 @@ impl Scanner {
         // If we did though, it'd enable bypassing the included burning bug protection
         assert!(output_key.is_torsion_free());
 
-        let mut key_offset = shared_key;
+        let mut key_offset = *shared_key;
         if let Some(subaddress) = subaddress {
           key_offset += self.pair.subaddress_derivation(subaddress);
         }
@@ -411,7 +539,7 @@ impl Scanner {
         // Regular transaction
         } else {
           let (mask, amount) = match tx.rct_signatures.base.encrypted_amounts.get(o) {
-            Some(amount) => amount_decryption(amount, shared_key),
+            Some(amount) => amount_decryption(amount, &shared_key),
             // This should never happen, yet it may be possible with miner transactions?
             // Using get just decreases the possibility of a panic and lets us move on in that case
             None => break,
@@ -427,17 +555,21 @@ impl Scanner {
         }
 
         if commitment.amount != 0 {
-          res.push(ReceivedOutput {
-            absolute: AbsoluteId { tx: tx.hash(), o: o.try_into().unwrap() },
-
-            data: OutputData { key: output_key, key_offset, commitment },
-
-            metadata: Metadata { subaddress, payment_id, arbitrary_data: extra.data() },
-          });
-
-          if let Some(burning_bug) = self.burning_bug.as_mut() {
-            burning_bug.insert(output.key);
-          }
+          res.push((
+            output.key,
+            ReceivedOutput {
+              absolute: AbsoluteId { tx: tx.hash(), o: o.try_into().unwrap() },
+
+              data: OutputData { key: output_key, key_offset, commitment },
+
+              metadata: Metadata {
+                subaddress,
+                payment_id,
+                arbitrary_data: extra.data(),
+                timelock: tx.prefix.timelock,
+              },
+            },
+          ));
         }
         // Break to prevent public keys from being included multiple times, triggering multiple
         // inclusions of the same output
@@ -445,7 +577,26 @@ impl Scanner {
       }
     }
 
-    Timelocked(tx.prefix.timelock, res)
+    Scanned(tx.prefix.timelock, res)
+  }
+
+  /// Scan the node's transaction pool for received outputs.
+  ///
+  /// These outputs are unconfirmed, and may never be confirmed, so they can't be spent and
+  /// shouldn't be considered final. Only transactions with received outputs are returned.
+  ///
+  /// This doesn't mark the outputs' keys as used, so the transactions can still be scanned
+  /// once they're confirmed.
+  pub async fn scan_pool<RPC: RpcConnection>(
+    &self,
+    rpc: &Rpc<RPC>,
+  ) -> Result<Vec<Timelocked<ReceivedOutput>>, RpcError> {
+    let txs = rpc.get_transaction_pool().await?;
+    // Scan with a copy, so the burning bug protection isn't updated for these outputs
+    let mut scanner = self.clone();
+    Ok(
+      scanner.scan_transactions(&txs).into_iter().filter(|outputs| !outputs.1.is_empty()).collect(),
+    )
   }
 
   /// Scan a block to obtain its spendable outputs. Its the presence in a block giving these
@@ -458,9 +609,29 @@ impl Scanner {
     rpc: &Rpc<RPC>,
     block: &Block,
   ) -> Result<Vec<Timelocked<SpendableOutput>>, RpcError> {
+    let txs = rpc.get_transactions(&block.txs).await?;
+    self.scan_with_transactions(rpc, block, txs).await
+  }
+
+  /// Scan a block, with its already fetched transactions, to obtain its spendable outputs.
+  ///
+  /// The transactions must be in the order they're listed in the block, not including the miner
+  /// transaction, as returned by `Rpc::get_blocks_range`.
+  pub async fn scan_with_transactions<RPC: RpcConnection>(
+    &mut self,
+    rpc: &Rpc<RPC>,
+    block: &Block,
+    transactions: Vec<Transaction>,
+  ) -> Result<Vec<Timelocked<SpendableOutput>>, RpcError> {
+    if (transactions.len() != block.txs.len()) ||
+      transactions.iter().zip(&block.txs).any(|(tx, hash)| tx.hash() != *hash)
+    {
+      Err(RpcError::InternalError("transactions weren't the block's transactions"))?;
+    }
+
     let mut index = rpc.get_o_indexes(block.miner_tx.hash()).await?[0];
     let mut txs = vec![block.miner_tx.clone()];
-    txs.extend(rpc.get_transactions(&block.txs).await?);
+    txs.extend(transactions);
 
     let map = |mut timelock: Timelocked<ReceivedOutput>, index| {
       if timelock.1.is_empty() {
@@ -481,8 +652,8 @@ impl Scanner {
     };
 
     let mut res = vec![];
-    for tx in txs {
-      if let Some(timelock) = map(self.scan_transaction(&tx), index) {
+    for (tx, scanned) in txs.iter().zip(self.scan_transactions(&txs)) {
+      if let Some(timelock) = map(scanned, index) {
         res.push(timelock);
       }
       index += u64::try_from(
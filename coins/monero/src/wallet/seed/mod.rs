@@ -4,8 +4,12 @@ use std_shims::string::String;
 use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 use rand_core::{RngCore, CryptoRng};
 
-pub(crate) mod classic;
-pub(crate) mod polyseed;
+use curve25519_dalek::scalar::Scalar;
+
+use crate::wallet::ViewPair;
+
+pub mod classic;
+pub mod polyseed;
 use classic::{CLASSIC_SEED_LENGTH, CLASSIC_SEED_LENGTH_WITH_CHECKSUM, ClassicSeed};
 use polyseed::{POLYSEED_LENGTH, Polyseed};
 
@@ -116,6 +120,18 @@ impl Seed {
     }
   }
 
+  /// Returns the private spend key derived from this seed.
+  pub fn spend_key(&self) -> Zeroizing<Scalar> {
+    Zeroizing::new(Scalar::from_bytes_mod_order(*self.key()))
+  }
+
+  /// Returns the ViewPair for this seed, with the view key derived from the spend key.
+  ///
+  /// This matches the reference wallet, making wallets restorable across implementations.
+  pub fn view_pair(&self) -> ViewPair {
+    ViewPair::from_spend_key(&self.spend_key())
+  }
+
   /// Returns the birthday of this seed.
   pub fn birthday(&self) -> u64 {
     match self {
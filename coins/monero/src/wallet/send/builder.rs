@@ -1,12 +1,14 @@
 use std::sync::{Arc, RwLock};
 
 use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use rand_core::{RngCore, CryptoRng};
 
 use crate::{
   Protocol,
+  rpc::{RpcConnection, Rpc},
   wallet::{
     address::MoneroAddress, Fee, SpendableOutput, Change, Decoys, SignableTransaction,
-    TransactionError, extra::MAX_ARBITRARY_DATA_SIZE,
+    TransactionError, InputSelection, extra::MAX_ARBITRARY_DATA_SIZE, send::dummy_address,
   },
 };
 
@@ -112,6 +114,43 @@ impl SignableTransactionBuilder {
     self.shallow_copy()
   }
 
+  /// Select inputs from the specified outputs, per the selection policy, and their decoys.
+  ///
+  /// The inputs are selected to cover the payments, data, and any inputs, already added to this
+  /// builder, so this should be called after all payments and data have been added. The selected
+  /// inputs are added in addition to any inputs already added.
+  pub async fn select_inputs<R: RngCore + CryptoRng, RPC: RpcConnection + Sync>(
+    &mut self,
+    rng: &mut R,
+    rpc: &Rpc<RPC>,
+    height: usize,
+    selection: InputSelection,
+    outputs: Vec<SpendableOutput>,
+  ) -> Result<Self, TransactionError> {
+    let inputs = {
+      let read = self.0.read().unwrap();
+      selection.select_with(
+        read.protocol,
+        read.fee_rate,
+        (
+          read.inputs.len(),
+          read.inputs.iter().map(|(input, _)| input.commitment().amount).sum::<u64>(),
+        ),
+        outputs,
+        &read.payments,
+        read.change_address.is_some(),
+        &read.data,
+      )?
+    };
+
+    let ring_len = self.0.read().unwrap().protocol.ring_len();
+    let decoys = Decoys::select(rng, rpc, ring_len, height, &inputs)
+      .await
+      .map_err(TransactionError::RpcError)?;
+    self.0.write().unwrap().add_inputs(&inputs.into_iter().zip(decoys).collect::<Vec<_>>());
+    Ok(self.shallow_copy())
+  }
+
   pub fn add_payment(&mut self, dest: MoneroAddress, amount: u64) -> Self {
     self.0.write().unwrap().add_payment(dest, amount);
     self.shallow_copy()
@@ -141,4 +180,66 @@ impl SignableTransactionBuilder {
       read.fee_rate,
     )
   }
+
+  /// Build a chain of transactions making every payment, selecting each transaction's inputs
+  /// from the specified outputs per the selection policy.
+  ///
+  /// The payments are split into as many transactions as needed to respect Monero's limit of 16
+  /// outputs per transaction, with each transaction having its own change output (if a change
+  /// address was set) and the arbitrary data. If a transaction would only have a single output, a
+  /// zero-amount output to a random address is added to it. Any inputs already added to this
+  /// builder are spent by the first transaction.
+  ///
+  /// The transactions don't spend each other's outputs, so they may be published simultaneously.
+  pub async fn build_chain<R: RngCore + CryptoRng, RPC: RpcConnection + Sync>(
+    self,
+    rng: &mut R,
+    rpc: &Rpc<RPC>,
+    height: usize,
+    selection: InputSelection,
+    mut outputs: Vec<SpendableOutput>,
+  ) -> Result<Vec<SignableTransaction>, TransactionError> {
+    let read = self.0.read().unwrap().clone();
+    let change = read.change_address.is_some();
+
+    let mut existing = read.inputs.clone();
+    let mut txs = vec![];
+    for mut payments in SignableTransaction::chunk_payments(&read.payments, change) {
+      if (payments.len() == 1) && (!change) {
+        payments.push((dummy_address(rng, payments[0].0.meta.network), 0));
+      }
+
+      let inputs = selection.select_with(
+        read.protocol,
+        read.fee_rate,
+        (existing.len(), existing.iter().map(|(input, _)| input.commitment().amount).sum::<u64>()),
+        outputs.clone(),
+        &payments,
+        change,
+        &read.data,
+      )?;
+      // Don't select these outputs again for any future transaction
+      outputs.retain(|output| !inputs.contains(output));
+
+      let decoys = Decoys::select(rng, rpc, read.protocol.ring_len(), height, &inputs)
+        .await
+        .map_err(TransactionError::RpcError)?;
+      existing.extend(inputs.into_iter().zip(decoys));
+
+      txs.push(SignableTransaction::new(
+        read.protocol,
+        read.r_seed.clone(),
+        core::mem::take(&mut existing),
+        payments,
+        read.change_address.clone(),
+        read.data.clone(),
+        read.fee_rate,
+      )?);
+    }
+
+    if txs.is_empty() {
+      Err(TransactionError::NoOutputs)?;
+    }
+    Ok(txs)
+  }
 }
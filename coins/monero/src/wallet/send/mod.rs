@@ -37,13 +37,16 @@ use crate::{
   transaction::{Input, Output, Timelock, TransactionPrefix, Transaction},
   rpc::RpcError,
   wallet::{
-    address::{Network, AddressSpec, MoneroAddress},
-    ViewPair, SpendableOutput, Decoys, PaymentId, ExtraField, Extra, key_image_sort, uniqueness,
-    shared_key, commitment_mask, amount_encryption,
-    extra::{ARBITRARY_DATA_MARKER, MAX_ARBITRARY_DATA_SIZE},
+    address::{Network, AddressType, SubaddressIndex, AddressSpec, AddressMeta, MoneroAddress},
+    ViewPair, SpendableOutput, Decoys, PaymentId, Extra, key_image_sort, uniqueness, shared_key,
+    commitment_mask, amount_encryption,
+    extra::MAX_ARBITRARY_DATA_SIZE,
   },
 };
 
+mod selection;
+pub use selection::InputSelection;
+
 #[cfg(feature = "std")]
 mod builder;
 #[cfg(feature = "std")]
@@ -83,9 +86,9 @@ impl SendOutput {
       SendOutput {
         R,
         view_tag,
-        dest: ((&shared_key * ED25519_BASEPOINT_TABLE) + output.0.spend),
-        commitment: Commitment::new(commitment_mask(shared_key), output.1),
-        amount: amount_encryption(output.1, shared_key),
+        dest: ((shared_key.deref() * ED25519_BASEPOINT_TABLE) + output.0.spend),
+        commitment: Commitment::new(commitment_mask(&shared_key), output.1),
+        amount: amount_encryption(output.1, &shared_key),
       },
       output
         .0
@@ -134,7 +137,7 @@ pub enum TransactionError {
   InvalidDecoyQuantity,
   #[cfg_attr(feature = "std", error("only one output and no change address"))]
   NoChange,
-  #[cfg_attr(feature = "std", error("too many outputs"))]
+  #[cfg_attr(feature = "std", error("too many outputs (a transaction may have up to 16)"))]
   TooManyOutputs,
   #[cfg_attr(feature = "std", error("too much data"))]
   TooMuchData,
@@ -147,6 +150,8 @@ pub enum TransactionError {
   NotEnoughFunds { inputs: u64, outputs: u64, fee: u64 },
   #[cfg_attr(feature = "std", error("wrong spend private key"))]
   WrongPrivateKey,
+  #[cfg_attr(feature = "std", error("an input is still timelocked"))]
+  LockedInput,
   #[cfg_attr(feature = "std", error("rpc error ({0})"))]
   RpcError(RpcError),
   #[cfg_attr(feature = "std", error("clsag error ({0})"))]
@@ -267,6 +272,10 @@ impl Fee {
 }
 
 /// Fee priority, determining how quickly a transaction is included in a block.
+///
+/// These correspond to wallet2's priorities of default, unimportant, normal, elevated, and
+/// priority, with the node's fee estimate for each scaling with how full recent blocks were.
+/// `Medium` is wallet2's normal priority.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[allow(non_camel_case_types)]
 pub enum FeePriority {
@@ -274,6 +283,7 @@ pub enum FeePriority {
   Low,
   Medium,
   High,
+  Highest,
   Custom { priority: u32 },
 }
 
@@ -284,6 +294,7 @@ impl FeePriority {
       FeePriority::Low => 1,
       FeePriority::Medium => 2,
       FeePriority::High => 3,
+      FeePriority::Highest => 4,
       FeePriority::Custom { priority, .. } => *priority,
     }
   }
@@ -295,6 +306,54 @@ pub(crate) enum InternalPayment {
   Change(Change, u64),
 }
 
+impl InternalPayment {
+  fn write<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    match self {
+      InternalPayment::Payment(payment) => {
+        w.write_all(&[0])?;
+        write_vec(write_byte, payment.0.to_string().as_bytes(), w)?;
+        w.write_all(&payment.1.to_le_bytes())
+      }
+      InternalPayment::Change(change, amount) => {
+        w.write_all(&[1])?;
+        write_vec(write_byte, change.address.to_string().as_bytes(), w)?;
+        if let Some(view) = change.view.as_ref() {
+          w.write_all(&[1])?;
+          write_scalar(view, w)?;
+        } else {
+          w.write_all(&[0])?;
+        }
+        w.write_all(&amount.to_le_bytes())
+      }
+    }
+  }
+
+  fn read<R: io::Read>(r: &mut R) -> io::Result<InternalPayment> {
+    fn read_address<R: io::Read>(r: &mut R) -> io::Result<MoneroAddress> {
+      String::from_utf8(read_vec(read_byte, r)?)
+        .ok()
+        .and_then(|str| MoneroAddress::from_str_raw(&str).ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid address"))
+    }
+
+    Ok(match read_byte(r)? {
+      0 => InternalPayment::Payment((read_address(r)?, read_u64(r)?)),
+      1 => InternalPayment::Change(
+        Change {
+          address: read_address(r)?,
+          view: match read_byte(r)? {
+            0 => None,
+            1 => Some(Zeroizing::new(read_scalar(r)?)),
+            _ => Err(io::Error::new(io::ErrorKind::Other, "invalid change payment"))?,
+          },
+        },
+        read_u64(r)?,
+      ),
+      _ => Err(io::Error::new(io::ErrorKind::Other, "invalid payment"))?,
+    })
+  }
+}
+
 /// The eventual output of a SignableTransaction.
 ///
 /// If the SignableTransaction has a Change with a view key, this will also have the view key.
@@ -349,6 +408,23 @@ impl Change {
     }
   }
 
+  /// Create a change output specification for a subaddress from a ViewPair.
+  ///
+  /// As with `new`, the view key is used so the change output doesn't require additional keys.
+  pub fn new_subaddress(view: &ViewPair, subaddress: SubaddressIndex, guaranteed: bool) -> Change {
+    Change {
+      address: view.address(
+        Network::Mainnet,
+        if !guaranteed {
+          AddressSpec::Subaddress(subaddress)
+        } else {
+          AddressSpec::Featured { subaddress: Some(subaddress), payment_id: None, guaranteed: true }
+        },
+      ),
+      view: Some(view.view.clone()),
+    }
+  }
+
   /// Create a fingerprintable change output specification which will harm privacy. Only use this
   /// if you know what you're doing.
   pub fn fingerprintable(address: MoneroAddress) -> Change {
@@ -356,6 +432,19 @@ impl Change {
   }
 }
 
+// Generate a random address to send a zero-amount output to, when a transaction would otherwise
+// only have one output
+pub(crate) fn dummy_address<R: RngCore + CryptoRng>(
+  rng: &mut R,
+  network: Network,
+) -> MoneroAddress {
+  MoneroAddress::new(
+    AddressMeta::new(network, AddressType::Standard),
+    &random_scalar(rng) * ED25519_BASEPOINT_TABLE,
+    &random_scalar(rng) * ED25519_BASEPOINT_TABLE,
+  )
+}
+
 fn need_additional(payments: &[InternalPayment]) -> (bool, bool) {
   let mut has_change_view = false;
   let subaddresses = payments
@@ -365,11 +454,12 @@ fn need_additional(payments: &[InternalPayment]) -> (bool, bool) {
       InternalPayment::Change(change, _) => {
         if change.view.is_some() {
           has_change_view = true;
-          // It should not be possible to construct a change specification to a subaddress with a
-          // view key
-          debug_assert!(!change.address.is_subaddress());
+          // With the view key, the change output can be derived from the transaction's key, even
+          // if it's to a subaddress
+          false
+        } else {
+          change.address.is_subaddress()
         }
-        change.address.is_subaddress()
       }
     })
     .count() !=
@@ -546,6 +636,102 @@ impl SignableTransaction {
     Ok(SignableTransaction { protocol, r_seed, inputs, payments, data, fee, fee_rate })
   }
 
+  /// Create a signable transaction sending the entirety of the inputs, minus the fee, to a single
+  /// address.
+  ///
+  /// No change output is created. As Monero requires two outputs, a zero-amount output to a
+  /// randomly generated address is included alongside the payment.
+  pub fn sweep<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    protocol: Protocol,
+    r_seed: Option<Zeroizing<[u8; 32]>>,
+    inputs: Vec<(SpendableOutput, Decoys)>,
+    address: MoneroAddress,
+    data: Vec<Vec<u8>>,
+    fee_rate: Fee,
+  ) -> Result<SignableTransaction, TransactionError> {
+    let dummy = dummy_address(rng, address.meta.network);
+
+    // The fee doesn't depend on the amounts sent, so calculate it with a zero-amount payment
+    let fee = Self::new(
+      protocol,
+      r_seed.clone(),
+      inputs.clone(),
+      vec![(address, 0), (dummy, 0)],
+      None,
+      data.clone(),
+      fee_rate,
+    )?
+    .fee;
+
+    let in_amount = inputs.iter().map(|(input, _)| input.commitment().amount).sum::<u64>();
+    let res = Self::new(
+      protocol,
+      r_seed,
+      inputs,
+      vec![(address, in_amount - fee), (dummy, 0)],
+      None,
+      data,
+      fee_rate,
+    )?;
+    debug_assert_eq!(res.fee, fee);
+    Ok(res)
+  }
+
+  /// Split payments into chunks which each fit within a single transaction.
+  ///
+  /// Each chunk has at most 16 outputs, including the change output if `change` is set. The
+  /// payments are kept in order.
+  pub fn chunk_payments(
+    payments: &[(MoneroAddress, u64)],
+    change: bool,
+  ) -> Vec<Vec<(MoneroAddress, u64)>> {
+    payments.chunks(MAX_OUTPUTS - usize::from(change)).map(<[_]>::to_vec).collect()
+  }
+
+  /// Estimate the fee for a transaction with the specified amount of inputs and outputs
+  /// (including the change output, if there is one), and the specified arbitrary data.
+  ///
+  /// This is an upper bound on the fee `new` will calculate, allowing selecting inputs before
+  /// selecting their decoys.
+  pub fn estimate_fee(
+    protocol: Protocol,
+    inputs: usize,
+    outputs: usize,
+    data: &[Vec<u8>],
+    fee_rate: Fee,
+  ) -> u64 {
+    Self::estimate_weight_and_fee(protocol, inputs, outputs, data, fee_rate).1
+  }
+
+  /// Estimate the weight and fee for a transaction with the specified amount of inputs and
+  /// outputs (including the change output, if there is one), and the specified arbitrary data.
+  ///
+  /// The ring size used is the protocol's, with `Protocol::Custom` allowing any ring size. These
+  /// are upper bounds on the weight and fee `new` will calculate.
+  pub fn estimate_weight_and_fee(
+    protocol: Protocol,
+    inputs: usize,
+    outputs: usize,
+    data: &[Vec<u8>],
+    fee_rate: Fee,
+  ) -> (usize, u64) {
+    // Assume the largest possible offsets and extra
+    let decoy_weights = vec![Decoys::fee_weight(&vec![u64::MAX; protocol.ring_len()]); inputs];
+    let extra = Extra::fee_weight(outputs, true, true, data);
+    calculate_weight_and_fee(protocol, &decoy_weights, outputs, extra, fee_rate)
+  }
+
+  /// Check every input's timelock has expired, as required for this transaction to be valid.
+  ///
+  /// `height` and `time` are as for `Timelock::is_unlocked`.
+  pub fn check_unlocked(&self, height: usize, time: u64) -> Result<(), TransactionError> {
+    if self.inputs.iter().any(|(input, _)| !input.timelock().is_unlocked(height, time)) {
+      Err(TransactionError::LockedInput)?;
+    }
+    Ok(())
+  }
+
   pub fn fee(&self) -> u64 {
     self.fee
   }
@@ -554,13 +740,76 @@ impl SignableTransaction {
     self.fee_rate
   }
 
+  /// The payments made by this transaction, including the change output if there is one.
+  pub fn payments(&self) -> Vec<(MoneroAddress, u64)> {
+    self
+      .payments
+      .iter()
+      .map(|payment| match payment {
+        InternalPayment::Payment(payment) => *payment,
+        InternalPayment::Change(change, amount) => (change.address, *amount),
+      })
+      .collect()
+  }
+
+  /// Write this transaction, before it's signed.
+  ///
+  /// This enables handing a transaction created by a watch-only wallet to an offline signer.
+  /// If there's a change output with a view key, the view key is included, so this must be
+  /// treated securely.
+  pub fn write<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+    self.protocol.write(w)?;
+    if let Some(r_seed) = self.r_seed.as_ref() {
+      w.write_all(&[1])?;
+      w.write_all(r_seed.as_ref())?;
+    } else {
+      w.write_all(&[0])?;
+    }
+    write_vec(
+      |(input, decoys), w| {
+        input.write(w)?;
+        decoys.write(w)
+      },
+      &self.inputs,
+      w,
+    )?;
+    write_vec(InternalPayment::write, &self.payments, w)?;
+    write_vec(|data, w| write_vec(write_byte, data, w), &self.data, w)?;
+    w.write_all(&self.fee.to_le_bytes())?;
+    w.write_all(&self.fee_rate.per_weight.to_le_bytes())?;
+    w.write_all(&self.fee_rate.mask.to_le_bytes())
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(1024);
+    self.write(&mut buf).unwrap();
+    buf
+  }
+
+  pub fn read<R: io::Read>(r: &mut R) -> io::Result<SignableTransaction> {
+    Ok(SignableTransaction {
+      protocol: Protocol::read(r)?,
+      r_seed: match read_byte(r)? {
+        0 => None,
+        1 => Some(Zeroizing::new(read_bytes(r)?)),
+        _ => Err(io::Error::new(io::ErrorKind::Other, "invalid r_seed flag"))?,
+      },
+      inputs: read_vec(|r| Ok((SpendableOutput::read(r)?, Decoys::read(r)?)), r)?,
+      payments: read_vec(InternalPayment::read, r)?,
+      data: read_vec(|r| read_vec(read_byte, r), r)?,
+      fee: read_u64(r)?,
+      fee_rate: Fee { per_weight: read_u64(r)?, mask: read_u64(r)? },
+    })
+  }
+
   #[allow(clippy::type_complexity)]
   fn prepare_payments(
     seed: &Zeroizing<[u8; 32]>,
     inputs: &[EdwardsPoint],
     payments: &mut Vec<InternalPayment>,
     uniqueness: [u8; 32],
-  ) -> (EdwardsPoint, Vec<Zeroizing<Scalar>>, Vec<SendOutput>, Option<[u8; 8]>) {
+  ) -> (Zeroizing<Scalar>, EdwardsPoint, Vec<Zeroizing<Scalar>>, Vec<SendOutput>, Option<[u8; 8]>)
+  {
     let mut rng = {
       // Hash the inputs into the seed so we don't re-use Rs
       // Doesn't re-use uniqueness as that's based on key images, which requires interactivity
@@ -610,11 +859,14 @@ impl SignableTransaction {
     for (o, mut payment) in payments.drain(..).enumerate() {
       // Downcast the change output to a payment output if it doesn't require special handling
       // regarding it's view key
-      payment = if !modified_change_ecdh {
-        if let InternalPayment::Change(change, amount) = &payment {
-          InternalPayment::Payment((change.address, *amount))
-        } else {
+      // A change output to a subaddress requires its view key unless it has an additional key
+      payment = if let InternalPayment::Change(change, amount) = &payment {
+        if modified_change_ecdh ||
+          ((!additional) && change.view.is_some() && change.address.is_subaddress())
+        {
           payment
+        } else {
+          InternalPayment::Payment((change.address, *amount))
         }
       } else {
         payment
@@ -663,7 +915,7 @@ impl SignableTransaction {
       id = id.or(Some(rand));
     }
 
-    (tx_public_key, additional_keys, outputs, id)
+    (tx_key, tx_public_key, additional_keys, outputs, id)
   }
 
   #[allow(non_snake_case)]
@@ -679,17 +931,14 @@ impl SignableTransaction {
     let mut extra = Extra::new(tx_key, if additional { Rs } else { vec![] });
 
     if let Some(id) = id {
-      let mut id_vec = Vec::with_capacity(1 + 8);
-      PaymentId::Encrypted(id).write(&mut id_vec).unwrap();
-      extra.push(ExtraField::Nonce(id_vec));
+      extra.push_payment_id(PaymentId::Encrypted(id));
     }
 
     // Include data if present
     let extra_len = Extra::fee_weight(Rs_len, additional, id.is_some(), data.as_ref());
     for part in data.drain(..) {
-      let mut arb = vec![ARBITRARY_DATA_MARKER];
-      arb.extend(part);
-      extra.push(ExtraField::Nonce(arb));
+      // The constructor checked the data's length
+      extra.push_data(&part).unwrap();
     }
 
     let mut serialized = Vec::with_capacity(extra_len);
@@ -705,7 +954,7 @@ impl SignableTransaction {
   /// if the transaction has already been signed and published.
   pub fn eventuality(&self) -> Option<Eventuality> {
     let inputs = self.inputs.iter().map(|(input, _)| input.key()).collect::<Vec<_>>();
-    let (tx_key, additional, outputs, id) = Self::prepare_payments(
+    let (_, tx_key, additional, outputs, id) = Self::prepare_payments(
       self.r_seed.as_ref()?,
       &inputs,
       &mut self.payments.clone(),
@@ -742,7 +991,7 @@ impl SignableTransaction {
       res
     });
 
-    let (tx_key, additional, outputs, id) = Self::prepare_payments(
+    let (_, tx_key, additional, outputs, id) = Self::prepare_payments(
       &r_seed,
       &self.inputs.iter().map(|(input, _)| input.key()).collect::<Vec<_>>(),
       &mut self.payments,
@@ -868,6 +1117,21 @@ impl Eventuality {
     &self.extra
   }
 
+  /// The private keys for this transaction, as needed to prove its payments via an OutProof.
+  ///
+  /// Returns the primary key and any additional keys, which will be distinct from the additional
+  /// keys present in the transaction if any payments weren't to subaddresses.
+  pub fn tx_keys(&self) -> (Zeroizing<Scalar>, Vec<Zeroizing<Scalar>>) {
+    let (tx_key, _, additional, _, _) = SignableTransaction::prepare_payments(
+      &self.r_seed,
+      &self.inputs,
+      &mut self.payments.clone(),
+      // As with eventuality, the uniqueness doesn't affect the ephemeral keys
+      [0; 32],
+    );
+    (tx_key, additional)
+  }
+
   #[must_use]
   pub fn matches(&self, tx: &Transaction) -> bool {
     if self.payments.len() != tx.prefix.outputs.len() {
@@ -888,7 +1152,7 @@ impl Eventuality {
     }
 
     // Generate the outputs. This is TX-specific due to uniqueness.
-    let (_, _, outputs, _) = SignableTransaction::prepare_payments(
+    let (_, _, _, outputs, _) = SignableTransaction::prepare_payments(
       &self.r_seed,
       &self.inputs,
       &mut self.payments.clone(),
@@ -929,27 +1193,7 @@ impl Eventuality {
     write_raw_vec(write_byte, self.r_seed.as_ref(), w)?;
     write_vec(write_point, &self.inputs, w)?;
 
-    fn write_payment<W: io::Write>(payment: &InternalPayment, w: &mut W) -> io::Result<()> {
-      match payment {
-        InternalPayment::Payment(payment) => {
-          w.write_all(&[0])?;
-          write_vec(write_byte, payment.0.to_string().as_bytes(), w)?;
-          w.write_all(&payment.1.to_le_bytes())
-        }
-        InternalPayment::Change(change, amount) => {
-          w.write_all(&[1])?;
-          write_vec(write_byte, change.address.to_string().as_bytes(), w)?;
-          if let Some(view) = change.view.as_ref() {
-            w.write_all(&[1])?;
-            write_scalar(view, w)?;
-          } else {
-            w.write_all(&[0])?;
-          }
-          w.write_all(&amount.to_le_bytes())
-        }
-      }
-    }
-    write_vec(write_payment, &self.payments, w)?;
+    write_vec(InternalPayment::write, &self.payments, w)?;
 
     write_vec(write_byte, &self.extra, w)
   }
@@ -961,36 +1205,11 @@ impl Eventuality {
   }
 
   pub fn read<R: io::Read>(r: &mut R) -> io::Result<Eventuality> {
-    fn read_address<R: io::Read>(r: &mut R) -> io::Result<MoneroAddress> {
-      String::from_utf8(read_vec(read_byte, r)?)
-        .ok()
-        .and_then(|str| MoneroAddress::from_str_raw(&str).ok())
-        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid address"))
-    }
-
-    fn read_payment<R: io::Read>(r: &mut R) -> io::Result<InternalPayment> {
-      Ok(match read_byte(r)? {
-        0 => InternalPayment::Payment((read_address(r)?, read_u64(r)?)),
-        1 => InternalPayment::Change(
-          Change {
-            address: read_address(r)?,
-            view: match read_byte(r)? {
-              0 => None,
-              1 => Some(Zeroizing::new(read_scalar(r)?)),
-              _ => Err(io::Error::new(io::ErrorKind::Other, "invalid change payment"))?,
-            },
-          },
-          read_u64(r)?,
-        ),
-        _ => Err(io::Error::new(io::ErrorKind::Other, "invalid payment"))?,
-      })
-    }
-
     Ok(Eventuality {
       protocol: Protocol::read(r)?,
       r_seed: Zeroizing::new(read_bytes::<_, 32>(r)?),
       inputs: read_vec(read_point, r)?,
-      payments: read_vec(read_payment, r)?,
+      payments: read_vec(InternalPayment::read, r)?,
       extra: read_vec(read_byte, r)?,
     })
   }
@@ -67,7 +67,9 @@ pub struct TransactionSignatureMachine {
 
 impl SignableTransaction {
   /// Create a FROST signing machine out of this signable transaction.
-  /// The height is the Monero blockchain height to synchronize around.
+  ///
+  /// Every signer must create their machine from the same signable transaction, with the same
+  /// transcript, as the inputs, rings, and payments are all bound to the transcript.
   pub fn multisig(
     self,
     keys: ThresholdKeys<Ed25519>,
@@ -0,0 +1,108 @@
+use std_shims::vec::Vec;
+
+use crate::{
+  Protocol,
+  wallet::{address::MoneroAddress, Fee, SpendableOutput, SignableTransaction, TransactionError},
+};
+
+/// A policy for selecting which outputs to spend as a transaction's inputs.
+///
+/// Every policy is fee-aware, selecting inputs until they cover the amount paid and the fee for a
+/// transaction with that many inputs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputSelection {
+  /// Spend the largest outputs first.
+  LargestFirst,
+  /// Spend the oldest outputs first, as ordered by their global index.
+  OldestFirst,
+  /// Spend as few outputs as possible, preferring smaller outputs to reduce the change.
+  MinimizeInputs,
+  /// Spend every output provided, as explicitly selected by the caller.
+  Explicit,
+}
+
+impl InputSelection {
+  /// Select the outputs to spend in order to make the specified payments.
+  ///
+  /// `change` is if the transaction will have a change output. `data` is the arbitrary data which
+  /// will be included in the transaction.
+  pub fn select(
+    &self,
+    protocol: Protocol,
+    fee_rate: Fee,
+    outputs: Vec<SpendableOutput>,
+    payments: &[(MoneroAddress, u64)],
+    change: bool,
+    data: &[Vec<u8>],
+  ) -> Result<Vec<SpendableOutput>, TransactionError> {
+    self.select_with(protocol, fee_rate, (0, 0), outputs, payments, change, data)
+  }
+
+  // Select outputs in addition to the specified amount of inputs, with the specified value,
+  // which have already been selected
+  #[allow(clippy::too_many_arguments)]
+  pub(crate) fn select_with(
+    &self,
+    protocol: Protocol,
+    fee_rate: Fee,
+    (existing, existing_value): (usize, u64),
+    mut outputs: Vec<SpendableOutput>,
+    payments: &[(MoneroAddress, u64)],
+    change: bool,
+    data: &[Vec<u8>],
+  ) -> Result<Vec<SpendableOutput>, TransactionError> {
+    let amount = payments.iter().map(|payment| payment.1).sum::<u64>();
+    let outputs_len = payments.len() + usize::from(change);
+    let fee = |inputs| {
+      SignableTransaction::estimate_fee(protocol, existing + inputs, outputs_len, data, fee_rate)
+    };
+    let covered = |inputs: &[SpendableOutput]| {
+      let value =
+        existing_value + inputs.iter().map(|input| input.commitment().amount).sum::<u64>();
+      ((existing + inputs.len()) != 0) && (value >= amount.saturating_add(fee(inputs.len())))
+    };
+    let not_enough_funds = |outputs: &[SpendableOutput]| TransactionError::NotEnoughFunds {
+      inputs: existing_value + outputs.iter().map(|output| output.commitment().amount).sum::<u64>(),
+      outputs: amount,
+      fee: fee(outputs.len()),
+    };
+
+    match self {
+      InputSelection::LargestFirst | InputSelection::MinimizeInputs => {
+        outputs.sort_by_key(|output| core::cmp::Reverse(output.commitment().amount))
+      }
+      InputSelection::OldestFirst => outputs.sort_by_key(|output| output.global_index),
+      InputSelection::Explicit => {
+        if !covered(&outputs) {
+          Err(not_enough_funds(&outputs))?;
+        }
+        return Ok(outputs);
+      }
+    }
+
+    // Take outputs, in order, until they cover the amount and the fee
+    let mut inputs = 0;
+    while !covered(&outputs[.. inputs]) {
+      if inputs == outputs.len() {
+        Err(not_enough_funds(&outputs))?;
+      }
+      inputs += 1;
+    }
+
+    if (*self == InputSelection::MinimizeInputs) && (inputs != 0) {
+      // The largest outputs were taken, so this is the fewest inputs possible
+      // Replace the last input with the smallest output which still covers the payments
+      let last = inputs - 1;
+      for i in (last .. outputs.len()).rev() {
+        outputs.swap(last, i);
+        if covered(&outputs[.. inputs]) {
+          break;
+        }
+        outputs.swap(last, i);
+      }
+    }
+
+    outputs.truncate(inputs);
+    Ok(outputs)
+  }
+}
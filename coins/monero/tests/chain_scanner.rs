@@ -0,0 +1,76 @@
+use std::{
+  sync::{Arc, Mutex},
+  collections::HashSet,
+};
+
+use monero_serai::wallet::{
+  address::{Network, AddressSpec},
+  Scanner, ScannerStore, ChainEvent, ChainScanner,
+};
+
+mod runner;
+
+// A store shared with the test, so it outlives each ChainScanner
+#[derive(Clone, Default)]
+struct MemoryStore(Arc<Mutex<Option<Vec<u8>>>>);
+impl ScannerStore for MemoryStore {
+  type Error = ();
+  fn load(&self) -> Result<Option<Vec<u8>>, ()> {
+    Ok(self.0.lock().unwrap().clone())
+  }
+  fn save(&mut self, state: &[u8]) -> Result<(), ()> {
+    *self.0.lock().unwrap() = Some(state.to_vec());
+    Ok(())
+  }
+}
+
+async_sequential!(
+  async fn chain_scanner_resumes() {
+    let rpc = runner::rpc().await;
+    let view = runner::random_address().1;
+    let addr = view.address(Network::Mainnet, AddressSpec::Standard).to_string();
+    let store = MemoryStore::default();
+
+    let start = rpc.get_height().await.unwrap();
+    rpc.generate_blocks(&addr, 3).await.unwrap();
+
+    let scan = |store| {
+      let view = view.clone();
+      let rpc = rpc.clone();
+      async move {
+        let mut scanner =
+          ChainScanner::new(Scanner::from_view(view, Some(HashSet::new())), store, start).unwrap();
+        let mut outputs = vec![];
+        let mut next_block = scanner.next_block();
+        while let Some(event) = scanner.next(&rpc).await.unwrap() {
+          let ChainEvent::Block { number, outputs: received, spent, .. } = event else {
+            panic!("reorg without any blocks being reorganized");
+          };
+          assert_eq!(number, next_block);
+          next_block += 1;
+          assert!(spent.is_empty());
+          for timelocked in received {
+            outputs.extend(timelocked.ignore_timelock());
+          }
+        }
+        assert_eq!(next_block, rpc.get_height().await.unwrap());
+        outputs
+      }
+    };
+
+    assert_eq!(scan(store.clone()).await.len(), 3);
+
+    // The prior scanner saved its state before returning it was at the tip, so this shouldn't
+    // rescan any blocks
+    assert!(scan(store.clone()).await.is_empty());
+
+    // Only the new block should be scanned
+    rpc.generate_blocks(&addr, 1).await.unwrap();
+    let outputs = scan(store.clone()).await;
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(
+      rpc.get_block_by_number(rpc.get_height().await.unwrap() - 1).await.unwrap().miner_tx.hash(),
+      outputs[0].output.absolute.tx
+    );
+  }
+);
@@ -0,0 +1,63 @@
+use std::collections::HashSet;
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use monero_serai::wallet::{
+  address::{Network, AddressSpec},
+  Scanner, Decoys, Change, FeePriority, SignableTransaction,
+};
+
+mod runner;
+
+async_sequential!(
+  async fn scan_pool() {
+    let rpc = runner::rpc().await;
+    let (spend, view, _) = runner::random_address();
+    let output = runner::get_miner_tx_output(&rpc, &view).await;
+
+    let protocol = rpc.get_protocol().await.unwrap();
+    let decoys = Decoys::select(
+      &mut OsRng,
+      &rpc,
+      protocol.ring_len(),
+      rpc.get_height().await.unwrap() - 1,
+      core::slice::from_ref(&output),
+    )
+    .await
+    .unwrap();
+
+    let recipient = runner::random_address().1;
+    let tx = SignableTransaction::new(
+      protocol,
+      None,
+      vec![(output, decoys[0].clone())],
+      vec![(recipient.address(Network::Mainnet, AddressSpec::Standard), 5)],
+      Some(Change::new(&view, false)),
+      vec![],
+      rpc.get_fee(protocol, FeePriority::Low).await.unwrap(),
+    )
+    .unwrap()
+    .sign(&mut OsRng, &Zeroizing::new(spend))
+    .unwrap();
+
+    let mut scanner = Scanner::from_view(recipient, Some(HashSet::new()));
+    assert!(scanner.scan_pool(&rpc).await.unwrap().is_empty());
+    assert!(!rpc.get_transaction_pool_hashes().await.unwrap().contains(&tx.hash()));
+
+    rpc.publish_transaction(&tx).await.unwrap();
+    assert!(rpc.get_transaction_pool_hashes().await.unwrap().contains(&tx.hash()));
+    assert!(rpc.get_transaction_pool().await.unwrap().contains(&tx));
+
+    let outputs = scanner.scan_pool(&rpc).await.unwrap().swap_remove(0).ignore_timelock();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].absolute.tx, tx.hash());
+    assert_eq!(outputs[0].commitment().amount, 5);
+
+    // Once confirmed, the transaction should leave the pool and still be scannable
+    runner::mine_until_unlocked(&rpc, &runner::random_address().2.to_string(), tx.hash()).await;
+    assert!(!rpc.get_transaction_pool_hashes().await.unwrap().contains(&tx.hash()));
+    assert!(scanner.scan_pool(&rpc).await.unwrap().is_empty());
+    assert_eq!(scanner.scan_transaction(&tx).ignore_timelock(), outputs);
+  }
+);
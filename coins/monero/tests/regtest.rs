@@ -0,0 +1,69 @@
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use monero_serai::wallet::{
+  address::{Network, AddressSpec},
+  Decoys, Change, FeePriority, SignableTransaction,
+};
+
+mod runner;
+
+async_sequential!(
+  async fn generate_and_pop_blocks() {
+    let rpc = runner::rpc().await;
+    let addr = runner::random_address().2.to_string();
+
+    let height = rpc.get_height().await.unwrap();
+    let blocks = rpc.generate_blocks(&addr, 3).await.unwrap();
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(rpc.get_height().await.unwrap(), height + 3);
+    for (i, block) in blocks.iter().enumerate() {
+      assert_eq!(rpc.get_block_hash(height + i).await.unwrap(), *block);
+    }
+
+    assert_eq!(rpc.pop_blocks(2).await.unwrap(), height + 1);
+    assert_eq!(rpc.get_height().await.unwrap(), height + 1);
+    assert_eq!(rpc.get_block_hash(height).await.unwrap(), blocks[0]);
+
+    // Mining after popping should produce distinct blocks
+    let replacement = rpc.generate_blocks(&addr, 1).await.unwrap().swap_remove(0);
+    assert_ne!(replacement, blocks[1]);
+    assert_eq!(rpc.get_block_hash(height + 1).await.unwrap(), replacement);
+  }
+
+  async fn flush_txpool() {
+    let rpc = runner::rpc().await;
+    let (spend, view, _) = runner::random_address();
+    let output = runner::get_miner_tx_output(&rpc, &view).await;
+
+    let protocol = rpc.get_protocol().await.unwrap();
+    let decoys = Decoys::select(
+      &mut OsRng,
+      &rpc,
+      protocol.ring_len(),
+      rpc.get_height().await.unwrap() - 1,
+      core::slice::from_ref(&output),
+    )
+    .await
+    .unwrap();
+
+    let tx = SignableTransaction::new(
+      protocol,
+      None,
+      vec![(output, decoys[0].clone())],
+      vec![(runner::random_address().1.address(Network::Mainnet, AddressSpec::Standard), 5)],
+      Some(Change::new(&view, false)),
+      vec![],
+      rpc.get_fee(protocol, FeePriority::Low).await.unwrap(),
+    )
+    .unwrap()
+    .sign(&mut OsRng, &Zeroizing::new(spend))
+    .unwrap();
+
+    rpc.publish_transaction(&tx).await.unwrap();
+    assert!(rpc.get_transaction_pool_hashes().await.unwrap().contains(&tx.hash()));
+
+    rpc.flush_txpool(&[tx.hash()]).await.unwrap();
+    assert!(!rpc.get_transaction_pool_hashes().await.unwrap().contains(&tx.hash()));
+  }
+);
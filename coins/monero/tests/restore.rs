@@ -0,0 +1,108 @@
+use std::{
+  sync::{Arc, Mutex},
+  collections::HashSet,
+};
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use monero_serai::{
+  ringct::generate_key_image,
+  wallet::{
+    address::{Network, AddressSpec},
+    Scanner, ScannerStore, WalletRestore, Decoys, Change, FeePriority, SignableTransaction,
+  },
+};
+
+mod runner;
+
+// A store shared with the test, so it outlives each WalletRestore
+#[derive(Clone, Default)]
+struct MemoryStore(Arc<Mutex<Option<Vec<u8>>>>);
+impl ScannerStore for MemoryStore {
+  type Error = ();
+  fn load(&self) -> Result<Option<Vec<u8>>, ()> {
+    Ok(self.0.lock().unwrap().clone())
+  }
+  fn save(&mut self, state: &[u8]) -> Result<(), ()> {
+    *self.0.lock().unwrap() = Some(state.to_vec());
+    Ok(())
+  }
+}
+
+async_sequential!(
+  async fn restore_from_height() {
+    let rpc = runner::rpc().await;
+    let (spend, view, _) = runner::random_address();
+
+    let start = rpc.get_height().await.unwrap();
+    let mut outputs = runner::fund(&rpc, &view, 2).await;
+
+    // Spend the first output, sending the change back to ourselves
+    let protocol = rpc.get_protocol().await.unwrap();
+    let spent = outputs.remove(0);
+    let decoys = Decoys::select(
+      &mut OsRng,
+      &rpc,
+      protocol.ring_len(),
+      rpc.get_height().await.unwrap() - 1,
+      core::slice::from_ref(&spent),
+    )
+    .await
+    .unwrap();
+    let tx = SignableTransaction::new(
+      protocol,
+      None,
+      vec![(spent.clone(), decoys[0].clone())],
+      vec![(runner::random_address().1.address(Network::Mainnet, AddressSpec::Standard), 5)],
+      Some(Change::new(&view, false)),
+      vec![],
+      rpc.get_fee(protocol, FeePriority::Low).await.unwrap(),
+    )
+    .unwrap()
+    .sign(&mut OsRng, &Zeroizing::new(spend))
+    .unwrap();
+    rpc.publish_transaction(&tx).await.unwrap();
+    runner::mine_until_unlocked(&rpc, &runner::random_address().2.to_string(), tx.hash()).await;
+
+    // The restore height found by timestamp should be before the wallet received anything
+    let timestamp = rpc.get_block_by_number(start).await.unwrap().header.timestamp;
+    assert!(
+      WalletRestore::<MemoryStore>::height_from_timestamp(&rpc, timestamp).await.unwrap() <= start
+    );
+
+    let restore = |store| {
+      WalletRestore::new(
+        Scanner::from_view(view.clone(), Some(HashSet::new())),
+        Zeroizing::new(spend),
+        store,
+        start,
+      )
+      .unwrap()
+    };
+
+    // Scan part of the chain, then resume from the saved state
+    let store = MemoryStore::default();
+    let mut wallet = restore(store.clone());
+    for _ in 0 .. 3 {
+      assert!(wallet.next(&rpc).await.unwrap());
+    }
+    assert_eq!(wallet.outputs().len(), 2);
+
+    let mut wallet = restore(store);
+    assert_eq!(wallet.next_block(), start + 3);
+    wallet.sync(&rpc).await.unwrap();
+    assert_eq!(wallet.next_block(), rpc.get_height().await.unwrap());
+
+    // The spent output should be replaced by its change
+    let image = generate_key_image(&Zeroizing::new(spend + spent.key_offset()));
+    assert_eq!(wallet.spent(), vec![image]);
+    let received = wallet.outputs();
+    assert_eq!(received.len(), 2);
+    assert!(received.contains(&outputs[0]));
+    assert_eq!(
+      wallet.balance(),
+      spent.commitment().amount + outputs[0].commitment().amount - 5 - tx.rct_signatures.base.fee
+    );
+  }
+);
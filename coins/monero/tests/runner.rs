@@ -57,20 +57,37 @@ pub async fn mine_until_unlocked(rpc: &Rpc<HttpRpc>, addr: &str, tx_hash: [u8; 3
   rpc.generate_blocks(addr, 9).await.unwrap();
 }
 
-// Mines 60 blocks and returns an unlocked miner TX output.
+// Miner TX outputs are locked for this many blocks
+const MINER_TX_LOCK: usize = 60;
+
+/// Fund a wallet with the miner TX outputs of `blocks` blocks, mining until they're unlocked.
 #[allow(dead_code)]
-pub async fn get_miner_tx_output(rpc: &Rpc<HttpRpc>, view: &ViewPair) -> SpendableOutput {
+pub async fn fund(rpc: &Rpc<HttpRpc>, view: &ViewPair, blocks: usize) -> Vec<SpendableOutput> {
   let mut scanner = Scanner::from_view(view.clone(), Some(HashSet::new()));
 
-  // Mine 60 blocks to unlock a miner TX
   let start = rpc.get_height().await.unwrap();
   rpc
-    .generate_blocks(&view.address(Network::Mainnet, AddressSpec::Standard).to_string(), 60)
+    .generate_blocks(&view.address(Network::Mainnet, AddressSpec::Standard).to_string(), blocks)
     .await
     .unwrap();
+  // Mine to another address so the only outputs received are from the above blocks
+  rpc.generate_blocks(&random_address().2.to_string(), MINER_TX_LOCK).await.unwrap();
+
+  let mut outputs = vec![];
+  for block in start .. (start + blocks) {
+    let block = rpc.get_block_by_number(block).await.unwrap();
+    for timelocked in scanner.scan(rpc, &block).await.unwrap() {
+      outputs.extend(timelocked.ignore_timelock());
+    }
+  }
+  assert_eq!(outputs.len(), blocks);
+  outputs
+}
 
-  let block = rpc.get_block_by_number(start).await.unwrap();
-  scanner.scan(rpc, &block).await.unwrap().swap_remove(0).ignore_timelock().swap_remove(0)
+// Mines 61 blocks and returns an unlocked miner TX output.
+#[allow(dead_code)]
+pub async fn get_miner_tx_output(rpc: &Rpc<HttpRpc>, view: &ViewPair) -> SpendableOutput {
+  fund(rpc, view, 1).await.swap_remove(0)
 }
 
 /// Make sure the weight and fee match the expected calculation.
@@ -85,8 +102,60 @@ pub fn check_weight_and_fee(tx: &Transaction, fee_rate: Fee) {
   assert_eq!(fee, expected_fee);
 }
 
+/// The URL of the regtest daemon to test against, configurable via `MONERO_RPC`.
+fn daemon_url() -> String {
+  std::env::var("MONERO_RPC").unwrap_or_else(|_| "http://127.0.0.1:18081".to_string())
+}
+
+// Start a regtest daemon if one isn't already reachable
+//
+// The daemon is detached, so later test binaries attach to it instead of starting their own
+async fn start_daemon(rpc: &Rpc<HttpRpc>) {
+  if rpc.get_height().await.is_ok() {
+    return;
+  }
+
+  let url = daemon_url();
+  let port = url
+    .rsplit(':')
+    .next()
+    .and_then(|port| port.trim_end_matches('/').parse::<u16>().ok())
+    .unwrap_or_else(|| panic!("MONERO_RPC ({url}) didn't specify a port"));
+  // monerod is searched for on the PATH unless MONEROD specifies its path
+  let monerod = std::env::var("MONEROD").unwrap_or_else(|_| "monerod".to_string());
+  let data_dir = std::env::temp_dir().join(format!("monero-serai-regtest-{port}"));
+
+  let status = std::process::Command::new(&monerod)
+    .args(["--regtest", "--offline", "--fixed-difficulty=1", "--non-interactive", "--no-zmq"])
+    .arg("--detach")
+    .arg(format!("--rpc-bind-port={port}"))
+    .arg(format!("--p2p-bind-port={}", port - 1))
+    .arg("--data-dir")
+    .arg(&data_dir)
+    .status()
+    .unwrap_or_else(|e| {
+      panic!(
+        "no Monero daemon was reachable at {url} and {monerod} couldn't be run ({e}). either run a \
+         regtest monerod, set MONERO_RPC to one, or set MONEROD to the path of monerod"
+      )
+    });
+  assert!(status.success(), "{monerod} exited with {status}");
+
+  for _ in 0 .. 60 {
+    if rpc.get_height().await.is_ok() {
+      return;
+    }
+    tokio::time::sleep(core::time::Duration::from_secs(1)).await;
+  }
+  panic!("started {monerod}, yet it never became reachable at {url}");
+}
+
 pub async fn rpc() -> Rpc<HttpRpc> {
-  let rpc = HttpRpc::new("http://127.0.0.1:18081".to_string()).unwrap();
+  let rpc = HttpRpc::new(daemon_url()).unwrap();
+
+  // Only start the daemon once per test binary
+  static DAEMON: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+  DAEMON.get_or_init(|| start_daemon(&rpc)).await;
 
   // Only run once
   if rpc.get_height().await.unwrap() != 1 {
@@ -4,7 +4,7 @@ use monero_serai::{
   transaction::Transaction,
   wallet::{
     extra::Extra, address::SubaddressIndex, ReceivedOutput, SpendableOutput, Decoys,
-    SignableTransactionBuilder,
+    SignableTransactionBuilder, InputSelection,
   },
   rpc::{Rpc, HttpRpc},
   Protocol,
@@ -82,6 +82,49 @@ test!(
   ),
 );
 
+test!(
+  select_inputs,
+  (
+    |_, mut builder: Builder, addr| async move {
+      builder.add_payment(addr, 1000000000000);
+      builder.add_payment(addr, 2000000000000);
+      (builder.build().unwrap(), ())
+    },
+    |_, tx: Transaction, mut scanner: Scanner, _| async move {
+      let outputs = scanner.scan_transaction(&tx).not_locked();
+      assert_eq!(outputs.len(), 2);
+      outputs
+    },
+  ),
+  (
+    |_, rpc: Rpc<_>, mut builder: Builder, addr, outputs: Vec<ReceivedOutput>| async move {
+      let mut spendable_outputs = Vec::with_capacity(outputs.len());
+      for output in outputs {
+        spendable_outputs.push(SpendableOutput::from(&rpc, output).await.unwrap());
+      }
+
+      builder.add_payment(addr, 6);
+      builder
+        .select_inputs(
+          &mut OsRng,
+          &rpc,
+          rpc.get_height().await.unwrap() - 1,
+          InputSelection::MinimizeInputs,
+          spendable_outputs,
+        )
+        .await
+        .unwrap();
+      (builder.build().unwrap(), ())
+    },
+    |_, tx: Transaction, mut scanner: Scanner, _| async move {
+      // Only the smaller output should've been spent
+      assert_eq!(tx.prefix.inputs.len(), 1);
+      let output = scanner.scan_transaction(&tx).not_locked().swap_remove(0);
+      assert_eq!(output.commitment().amount, 6);
+    },
+  ),
+);
+
 test!(
   // Ideally, this would be single_R, yet it isn't feasible to apply allow(non_snake_case) here
   single_r_subaddress_send,
@@ -150,6 +193,57 @@ test!(
   ),
 );
 
+test!(
+  change_to_subaddress,
+  (
+    |_, mut builder: Builder, addr| async move {
+      builder.add_payment(addr, 1000000000000);
+      (builder.build().unwrap(), ())
+    },
+    |_, tx: Transaction, mut scanner: Scanner, _| async move {
+      let mut outputs = scanner.scan_transaction(&tx).not_locked();
+      outputs.sort_by(|x, y| x.commitment().amount.cmp(&y.commitment().amount));
+      assert_eq!(outputs[0].commitment().amount, 1000000000000);
+      outputs
+    },
+  ),
+  (
+    |protocol, rpc: Rpc<_>, _, addr, outputs: Vec<ReceivedOutput>| async move {
+      use monero_serai::wallet::FeePriority;
+
+      let change_view = ViewPair::new(
+        &random_scalar(&mut OsRng) * ED25519_BASEPOINT_TABLE,
+        Zeroizing::new(random_scalar(&mut OsRng)),
+      );
+
+      let mut builder = SignableTransactionBuilder::new(
+        protocol,
+        rpc.get_fee(protocol, FeePriority::Low).await.unwrap(),
+        Some(Change::new_subaddress(&change_view, SubaddressIndex::new(0, 1).unwrap(), false)),
+      );
+      add_inputs(protocol, &rpc, vec![outputs.first().unwrap().clone()], &mut builder).await;
+      builder.add_payment(addr, 1);
+      (builder.build().unwrap(), change_view)
+    },
+    |_, tx: Transaction, _, change_view: ViewPair| async move {
+      // Make sure the change can be picked up by its subaddress
+      let mut change_scanner = Scanner::from_view(change_view, Some(HashSet::new()));
+      change_scanner.register_subaddress(SubaddressIndex::new(0, 1).unwrap());
+      let outputs = change_scanner.scan_transaction(&tx).not_locked();
+      assert!(outputs.len() == 1);
+      assert_eq!(outputs[0].metadata.subaddress, SubaddressIndex::new(0, 1));
+
+      // Make sure the change didn't require additional keys
+      assert!(Extra::read::<&[u8]>(&mut tx.prefix.extra.as_ref())
+        .unwrap()
+        .keys()
+        .unwrap()
+        .1
+        .is_none());
+    },
+  ),
+);
+
 test!(
   spend_one_input_to_one_output_plus_change,
   (
@@ -273,3 +367,215 @@ test!(
     },
   ),
 );
+
+test!(
+  spend_with_offline_decoys,
+  (
+    |_, mut builder: Builder, addr| async move {
+      builder.add_payment(addr, 2000000000000);
+      (builder.build().unwrap(), ())
+    },
+    |_, tx: Transaction, mut scanner: Scanner, _| async move {
+      let mut outputs = scanner.scan_transaction(&tx).not_locked();
+      outputs.sort_by(|x, y| x.commitment().amount.cmp(&y.commitment().amount));
+      assert_eq!(outputs[0].commitment().amount, 2000000000000);
+      outputs
+    },
+  ),
+  (
+    |protocol: Protocol, rpc, mut builder: Builder, addr, outputs: Vec<ReceivedOutput>| async move {
+      use monero_serai::wallet::DecoyCache;
+
+      let mut spendable_outputs = Vec::with_capacity(outputs.len());
+      for output in outputs {
+        spendable_outputs.push(SpendableOutput::from(&rpc, output).await.unwrap());
+      }
+
+      // Cache every output on the chain, which is feasible as the test chain is small
+      let height = rpc.get_height().await.unwrap() - 1;
+      let total = *rpc.get_output_distribution(0, height).await.unwrap().last().unwrap();
+      let cache = DecoyCache::fetch(&rpc, height, &(0 .. total).collect::<Vec<_>>()).await.unwrap();
+      // Simulate transferring the cache to an offline machine
+      let cache = DecoyCache::read::<&[u8]>(&mut cache.serialize().as_ref()).unwrap();
+
+      let decoys =
+        Decoys::select_offline(&mut OsRng, &cache, protocol.ring_len(), &spendable_outputs)
+          .await
+          .unwrap();
+      let inputs = spendable_outputs.into_iter().zip(decoys).collect::<Vec<_>>();
+      builder.add_inputs(&inputs);
+
+      builder.add_payment(addr, 2);
+      (builder.build().unwrap(), ())
+    },
+    |_, tx: Transaction, mut scanner: Scanner, _| async move {
+      let output = scanner.scan_transaction(&tx).not_locked().swap_remove(0);
+      assert_eq!(output.commitment().amount, 2);
+    },
+  ),
+);
+
+test!(
+  spend_excluding_coinbase_decoys,
+  (
+    |_, mut builder: Builder, addr| async move {
+      builder.add_payment(addr, 1000000000000);
+      (builder.build().unwrap(), ())
+    },
+    |_, tx: Transaction, mut scanner: Scanner, _| async move {
+      scanner.scan_transaction(&tx).not_locked()
+    },
+  ),
+  (
+    |protocol: Protocol, rpc, mut builder: Builder, addr, outputs: Vec<ReceivedOutput>| async move {
+      use monero_serai::wallet::CoinbaseDecoys;
+
+      let mut spendable_outputs = Vec::with_capacity(outputs.len());
+      for output in outputs {
+        spendable_outputs.push(SpendableOutput::from(&rpc, output).await.unwrap());
+      }
+
+      let decoys = Decoys::select_with_coinbase(
+        &mut OsRng,
+        &rpc,
+        protocol.ring_len(),
+        rpc.get_height().await.unwrap() - 1,
+        &spendable_outputs,
+        CoinbaseDecoys::Exclude,
+      )
+      .await
+      .unwrap();
+      builder.add_inputs(&spendable_outputs.into_iter().zip(decoys).collect::<Vec<_>>());
+
+      builder.add_payment(addr, 3);
+      (builder.build().unwrap(), ())
+    },
+    |rpc: Rpc<_>, tx: Transaction, _, _| async move {
+      use monero_serai::transaction::Input;
+
+      let Input::ToKey { key_offsets, .. } = &tx.prefix.inputs[0] else { panic!("not a spend") };
+      let mut ring = vec![];
+      for offset in key_offsets {
+        ring.push(ring.last().copied().unwrap_or(0) + offset);
+      }
+
+      // The real spend isn't a coinbase output, so no ring member should be
+      let members = rpc
+        .get_unlocked_outputs_with_coinbase(&ring, rpc.get_height().await.unwrap() - 1)
+        .await
+        .unwrap();
+      assert!(members.iter().all(|member| !member.unwrap().1));
+    },
+  ),
+);
+
+test!(
+  sweep_to_one_address,
+  (
+    |_, mut builder: Builder, addr| async move {
+      builder.add_payment(addr, 2000000000000);
+      (builder.build().unwrap(), ())
+    },
+    |_, tx: Transaction, mut scanner: Scanner, _| async move {
+      let mut outputs = scanner.scan_transaction(&tx).not_locked();
+      outputs.sort_by(|x, y| x.commitment().amount.cmp(&y.commitment().amount));
+      assert_eq!(outputs[0].commitment().amount, 2000000000000);
+      outputs
+    },
+  ),
+  (
+    |protocol: Protocol, rpc: Rpc<_>, _, addr, outputs: Vec<ReceivedOutput>| async move {
+      use monero_serai::wallet::FeePriority;
+
+      let mut spendable_outputs = Vec::with_capacity(outputs.len());
+      for output in outputs {
+        spendable_outputs.push(SpendableOutput::from(&rpc, output).await.unwrap());
+      }
+      let decoys = Decoys::select(
+        &mut OsRng,
+        &rpc,
+        protocol.ring_len(),
+        rpc.get_height().await.unwrap() - 1,
+        &spendable_outputs,
+      )
+      .await
+      .unwrap();
+      let inputs = spendable_outputs.into_iter().zip(decoys).collect::<Vec<_>>();
+      let in_amount = inputs.iter().map(|(input, _)| input.commitment().amount).sum::<u64>();
+
+      let tx = SignableTransaction::sweep(
+        &mut OsRng,
+        protocol,
+        None,
+        inputs,
+        addr,
+        vec![],
+        rpc.get_fee(protocol, FeePriority::Low).await.unwrap(),
+      )
+      .unwrap();
+      let expected = in_amount - tx.fee();
+      (tx, expected)
+    },
+    |_, tx: Transaction, mut scanner: Scanner, expected: u64| async move {
+      // The only output received is the entirety of the inputs, minus the fee
+      let outputs = scanner.scan_transaction(&tx).not_locked();
+      assert_eq!(outputs.len(), 1);
+      assert_eq!(outputs[0].commitment().amount, expected);
+      assert_eq!(tx.prefix.outputs.len(), 2);
+    },
+  ),
+);
+
+test!(
+  spend_with_cache,
+  (
+    |_, mut builder: Builder, addr| async move {
+      builder.add_payment(addr, 1000000000000);
+      builder.add_payment(addr, 2000000000000);
+      (builder.build().unwrap(), ())
+    },
+    |_, tx: Transaction, mut scanner: Scanner, _| async move {
+      let outputs = scanner.scan_transaction(&tx).not_locked();
+      assert_eq!(outputs.len(), 2);
+      outputs
+    },
+  ),
+  (
+    |protocol: Protocol, rpc, mut builder: Builder, addr, outputs: Vec<ReceivedOutput>| async move {
+      use monero_serai::wallet::{CoinbaseDecoys, OutputCache};
+
+      let mut cache = OutputCache::new();
+      let mut spendable_outputs = Vec::with_capacity(outputs.len());
+      for output in outputs {
+        let cached = SpendableOutput::from_cached(&rpc, &mut cache, output.clone()).await.unwrap();
+        assert_eq!(cached, SpendableOutput::from(&rpc, output).await.unwrap());
+        spendable_outputs.push(cached);
+      }
+
+      // Select decoys for each input separately, reusing the cached distribution
+      let height = rpc.get_height().await.unwrap() - 1;
+      for output in spendable_outputs {
+        let decoys = Decoys::select_cached(
+          &mut OsRng,
+          &rpc,
+          &mut cache,
+          protocol.ring_len(),
+          height,
+          core::slice::from_ref(&output),
+          CoinbaseDecoys::Include,
+        )
+        .await
+        .unwrap();
+        builder.add_input((output, decoys[0].clone()));
+      }
+
+      builder.add_payment(addr, 4);
+      (builder.build().unwrap(), ())
+    },
+    |_, tx: Transaction, mut scanner: Scanner, _| async move {
+      assert_eq!(tx.prefix.inputs.len(), 2);
+      let outputs = scanner.scan_transaction(&tx).not_locked();
+      assert!(outputs.iter().any(|output| output.commitment().amount == 4));
+    },
+  ),
+);
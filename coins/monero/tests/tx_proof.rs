@@ -0,0 +1,85 @@
+use rand::RngCore;
+
+use monero_serai::{
+  transaction::Transaction,
+  wallet::{
+    address::SubaddressIndex,
+    Eventuality,
+    proof::{TxProofError, out_proof, in_proof, check_tx_proof},
+  },
+};
+
+mod runner;
+
+test!(
+  tx_proof_standard_address,
+  (
+    |_, mut builder: Builder, _| async move {
+      let view = runner::random_address().1;
+      let mut r_seed = Zeroizing::new([0; 32]);
+      OsRng.fill_bytes(r_seed.as_mut());
+      builder.set_r_seed(r_seed);
+      builder.add_payment(view.address(Network::Mainnet, AddressSpec::Standard), 5);
+      let tx = builder.build().unwrap();
+      let eventuality = tx.eventuality().unwrap();
+      (tx, (view, eventuality))
+    },
+    |_, tx: Transaction, _, state: (ViewPair, Eventuality)| async move {
+      let (view, eventuality) = state;
+      let address = view.address(Network::Mainnet, AddressSpec::Standard);
+
+      let (tx_key, additional) = eventuality.tx_keys();
+      let proof = out_proof(&mut OsRng, &tx, &tx_key, &additional, &address, b"msg").unwrap();
+      assert!(proof.starts_with("OutProofV2"));
+      assert_eq!(check_tx_proof(&tx, &address, b"msg", &proof), Ok(5));
+      assert_eq!(check_tx_proof(&tx, &address, b"other", &proof), Err(TxProofError::InvalidProof));
+
+      let proof = in_proof(&mut OsRng, &tx, &view, None, b"msg").unwrap();
+      assert!(proof.starts_with("InProofV2"));
+      assert_eq!(check_tx_proof(&tx, &address, b"msg", &proof), Ok(5));
+
+      // A proof for another address shouldn't be valid for this address
+      let other = runner::random_address().1;
+      let proof = in_proof(&mut OsRng, &tx, &other, None, b"msg").unwrap();
+      assert_eq!(check_tx_proof(&tx, &address, b"msg", &proof), Err(TxProofError::InvalidProof));
+      // Yet it should be valid, with nothing received, for the other address
+      let other = other.address(Network::Mainnet, AddressSpec::Standard);
+      assert_eq!(check_tx_proof(&tx, &other, b"msg", &proof), Ok(0));
+    },
+  ),
+);
+
+test!(
+  tx_proof_subaddress,
+  (
+    |_, mut builder: Builder, _| async move {
+      let subaddress = SubaddressIndex::new(0, 1).unwrap();
+      let view = runner::random_address().1;
+      let mut r_seed = Zeroizing::new([0; 32]);
+      OsRng.fill_bytes(r_seed.as_mut());
+      builder.set_r_seed(r_seed);
+      builder.add_payment(view.address(Network::Mainnet, AddressSpec::Subaddress(subaddress)), 5);
+      let tx = builder.build().unwrap();
+      let eventuality = tx.eventuality().unwrap();
+      (tx, (view, subaddress, eventuality))
+    },
+    |_, tx: Transaction, _, state: (ViewPair, SubaddressIndex, Eventuality)| async move {
+      let (view, subaddress, eventuality) = state;
+      let address = view.address(Network::Mainnet, AddressSpec::Subaddress(subaddress));
+
+      let (tx_key, additional) = eventuality.tx_keys();
+      let proof = out_proof(&mut OsRng, &tx, &tx_key, &additional, &address, b"msg").unwrap();
+      assert_eq!(check_tx_proof(&tx, &address, b"msg", &proof), Ok(5));
+
+      let proof = in_proof(&mut OsRng, &tx, &view, Some(subaddress), b"msg").unwrap();
+      assert_eq!(check_tx_proof(&tx, &address, b"msg", &proof), Ok(5));
+
+      // The keys for a distinct transaction shouldn't produce a proof
+      let (wrong_key, _, _) = runner::random_address();
+      assert_eq!(
+        out_proof(&mut OsRng, &tx, &Zeroizing::new(wrong_key), &[], &address, b"msg"),
+        Err(TxProofError::WrongKeys)
+      );
+    },
+  ),
+);
@@ -0,0 +1,125 @@
+#[doc(hidden)]
+pub use scale as __scale;
+
+/// Create a key for an item within a database, domain-separated by the database and item names.
+pub fn serai_db_key(
+  db_dst: &'static [u8],
+  item_dst: &'static [u8],
+  key: impl AsRef<[u8]>,
+) -> Vec<u8> {
+  let db_len = u8::try_from(db_dst.len()).unwrap();
+  let dst_len = u8::try_from(item_dst.len()).unwrap();
+  [[db_len].as_ref(), db_dst, [dst_len].as_ref(), item_dst, key.as_ref()].concat()
+}
+
+/// Declare a database of typed items.
+///
+/// Each item becomes a unit struct with `key`, `get`, `set`, and `del` functions. The item's
+/// arguments are SCALE-encoded to form its key, and its value is SCALE-encoded when stored.
+///
+/// Items whose arguments can be decoded also have an `iter` function, which calls a function with
+/// the arguments and value of every instance of the item. This reads every entry in the database,
+/// so it's intended for migrations and maintenance, not regular usage.
+///
+/// ```
+/// use serai_db::{create_db, Db, DbTxn, MemDb};
+///
+/// create_db!(
+///   ExampleDb {
+///     LastBlock: (chain: u32) -> [u8; 32],
+///     Accounts: (chain: u32, block: u64) -> Vec<[u8; 32]>
+///   }
+/// );
+///
+/// let mut db = MemDb::new();
+/// let mut txn = db.txn();
+/// LastBlock::set(&mut txn, 0, &[0xff; 32]);
+/// Accounts::set(&mut txn, 0, 1, &vec![[1; 32], [2; 32]]);
+/// txn.commit();
+///
+/// assert_eq!(LastBlock::get(&db, 0), Some([0xff; 32]));
+/// assert_eq!(LastBlock::get(&db, 1), None);
+/// assert_eq!(Accounts::get(&db, 0, 1), Some(vec![[1; 32], [2; 32]]));
+///
+/// let mut blocks = vec![];
+/// LastBlock::iter(&db, |chain, block| {
+///   blocks.push((chain, block));
+///   Ok::<_, ()>(())
+/// })
+/// .unwrap();
+/// assert_eq!(blocks, vec![(0, [0xff; 32])]);
+///
+/// let mut txn = db.txn();
+/// Accounts::del(&mut txn, 0, 1);
+/// txn.commit();
+/// assert_eq!(Accounts::get(&db, 0, 1), None);
+/// ```
+#[macro_export]
+macro_rules! create_db {
+  ($db_name: ident {
+    $($field_name: ident: ($($arg: ident: $arg_type: ty),*) -> $field_type: ty),* $(,)?
+  }) => {
+    $(
+      #[derive(Clone, Copy, Debug)]
+      pub struct $field_name;
+      impl $field_name {
+        pub fn key($($arg: $arg_type),*) -> Vec<u8> {
+          $crate::serai_db_key(
+            stringify!($db_name).as_bytes(),
+            stringify!($field_name).as_bytes(),
+            $crate::__scale::Encode::encode(&($($arg),*)),
+          )
+        }
+
+        #[allow(dead_code)]
+        pub fn get(getter: &impl $crate::Get, $($arg: $arg_type),*) -> Option<$field_type> {
+          getter.get(Self::key($($arg),*)).map(|value| {
+            <$field_type as $crate::__scale::Decode>::decode(&mut value.as_slice()).unwrap()
+          })
+        }
+
+        #[allow(dead_code)]
+        pub fn set(txn: &mut impl $crate::DbTxn, $($arg: $arg_type,)* value: &$field_type) {
+          txn.put(Self::key($($arg),*), $crate::__scale::Encode::encode(value));
+        }
+
+        #[allow(dead_code)]
+        pub fn del(txn: &mut impl $crate::DbTxn, $($arg: $arg_type),*) {
+          txn.del(Self::key($($arg),*));
+        }
+
+        // The bound is higher-ranked so it isn't checked unless this is called, letting items
+        // whose arguments can't be decoded still be declared
+        #[allow(dead_code)]
+        pub fn iter<E>(
+          db: &impl $crate::IterableDb,
+          mut f: impl FnMut(($($arg_type),*), $field_type) -> Result<(), E>,
+        ) -> Result<(), E>
+        where
+          for<'a> ($($arg_type),*): $crate::__scale::Decode,
+        {
+          let prefix = $crate::serai_db_key(
+            stringify!($db_name).as_bytes(),
+            stringify!($field_name).as_bytes(),
+            [],
+          );
+          db.for_each_entry(|key, value| {
+            let Some(mut args) = key.strip_prefix(prefix.as_slice()) else { return Ok(()) };
+            // Skip any entry which happens to share this prefix yet isn't an instance of this item
+            let Ok(decoded) = <($($arg_type),*) as $crate::__scale::Decode>::decode(&mut args)
+            else {
+              return Ok(());
+            };
+            if !args.is_empty() {
+              return Ok(());
+            }
+            f(
+              decoded,
+              <$field_type as $crate::__scale::Decode>::decode(&mut &value[..]).unwrap(),
+            )
+          })
+        }
+      }
+    )*
+  };
+}
@@ -1,17 +1,31 @@
+mod create_db;
+pub use create_db::*;
+
 mod mem;
 pub use mem::*;
 
+mod snapshot;
+pub use snapshot::*;
+
+mod quiesce;
+pub use quiesce::*;
+
 #[cfg(feature = "rocksdb")]
 mod rocks;
 #[cfg(feature = "rocksdb")]
 pub use rocks::{RocksDB, new_rocksdb};
 
+#[cfg(test)]
+mod tests;
+
 /// An object implementing get.
 pub trait Get {
   fn get(&self, key: impl AsRef<[u8]>) -> Option<Vec<u8>>;
 }
 
 /// An atomic database operation.
+///
+/// If dropped without being committed, none of its writes are applied.
 #[must_use]
 pub trait DbTxn: Send + Get {
   fn put(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>);
@@ -23,9 +37,17 @@ pub trait DbTxn: Send + Get {
 pub trait Db: 'static + Send + Sync + Clone + Get {
   type Transaction<'a>: DbTxn;
   fn key(db_dst: &'static [u8], item_dst: &'static [u8], key: impl AsRef<[u8]>) -> Vec<u8> {
-    let db_len = u8::try_from(db_dst.len()).unwrap();
-    let dst_len = u8::try_from(item_dst.len()).unwrap();
-    [[db_len].as_ref(), db_dst, [dst_len].as_ref(), item_dst, key.as_ref()].concat()
+    serai_db_key(db_dst, item_dst, key)
   }
   fn txn(&mut self) -> Self::Transaction<'_>;
+
+  /// Perform a batch of writes as a single transaction.
+  ///
+  /// If `f` panics, none of its writes are applied.
+  fn batch<R>(&mut self, f: impl FnOnce(&mut Self::Transaction<'_>) -> R) -> R {
+    let mut txn = self.txn();
+    let res = f(&mut txn);
+    txn.commit();
+    res
+  }
 }
@@ -78,3 +78,12 @@ impl Db for MemDb {
     MemDbTxn(self, HashMap::new(), HashSet::new())
   }
 }
+impl IterableDb for MemDb {
+  fn for_each_entry<E>(&self, mut f: impl FnMut(&[u8], &[u8]) -> Result<(), E>) -> Result<(), E> {
+    // The read lock is held throughout, making this a consistent view
+    for (key, value) in self.0.read().unwrap().iter() {
+      f(key, value)?;
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,157 @@
+use core::time::Duration;
+use std::sync::{Arc, Mutex, Condvar};
+
+use crate::*;
+
+// Run a function which may block for an extended period of time
+#[cfg(feature = "tokio")]
+fn block_in_place<R>(f: impl FnOnce() -> R) -> R {
+  use tokio::runtime::{Handle, RuntimeFlavor};
+  // block_in_place panics on a current-thread runtime
+  match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+    Ok(RuntimeFlavor::MultiThread) => tokio::task::block_in_place(f),
+    _ => f(),
+  }
+}
+#[cfg(not(feature = "tokio"))]
+fn block_in_place<R>(f: impl FnOnce() -> R) -> R {
+  f()
+}
+
+#[derive(Default, Debug)]
+struct Status {
+  // If new transactions are currently prevented from being created
+  paused: bool,
+  // The amount of transactions which have yet to be committed or dropped
+  in_flight: usize,
+}
+
+#[derive(Default, Debug)]
+struct State {
+  status: Mutex<Status>,
+  changed: Condvar,
+}
+
+/// A database whose writes may be paused, so its contents are known not to be mid-update.
+///
+/// While quiesced, calls to `txn` block the calling thread until the database is resumed. With the
+/// `tokio` feature, calls from a multi-threaded tokio runtime's worker thread instead hand off the
+/// worker's other tasks first, so tasks holding transactions across awaits can still finish them.
+/// Without it, or on a current-thread runtime, transactions must only be created from threads
+/// which may block, such as with `spawn_blocking`.
+#[derive(Clone, Debug)]
+pub struct QuiescableDb<D: Db> {
+  db: D,
+  state: Arc<State>,
+}
+
+/// A transaction for a `QuiescableDb`, which holds off quiescing until it's committed or dropped.
+#[must_use]
+pub struct QuiescableTxn<'a, D: Db> {
+  txn: Option<D::Transaction<'a>>,
+  state: &'a State,
+}
+
+impl<'a, D: Db> Get for QuiescableTxn<'a, D> {
+  fn get(&self, key: impl AsRef<[u8]>) -> Option<Vec<u8>> {
+    self.txn.as_ref().unwrap().get(key)
+  }
+}
+impl<'a, D: Db> DbTxn for QuiescableTxn<'a, D> {
+  fn put(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) {
+    self.txn.as_mut().unwrap().put(key, value);
+  }
+  fn del(&mut self, key: impl AsRef<[u8]>) {
+    self.txn.as_mut().unwrap().del(key);
+  }
+  fn commit(mut self) {
+    self.txn.take().unwrap().commit();
+  }
+}
+impl<'a, D: Db> Drop for QuiescableTxn<'a, D> {
+  fn drop(&mut self) {
+    // Only considered finished after the commit, if there was one, so quiescing waits for it
+    self.state.status.lock().unwrap().in_flight -= 1;
+    self.state.changed.notify_all();
+  }
+}
+
+/// A guard for a quiesced database, which resumes it when dropped.
+pub struct Quiesced<D: Db> {
+  db: D,
+  state: Arc<State>,
+}
+
+impl<D: Db> Quiesced<D> {
+  /// The underlying database, which may still be written to by the holder of this guard.
+  pub fn db(&mut self) -> &mut D {
+    &mut self.db
+  }
+}
+
+impl<D: Db> Drop for Quiesced<D> {
+  fn drop(&mut self) {
+    self.state.status.lock().unwrap().paused = false;
+    self.state.changed.notify_all();
+  }
+}
+
+impl<D: Db> QuiescableDb<D> {
+  pub fn new(db: D) -> Self {
+    QuiescableDb { db, state: Arc::new(State::default()) }
+  }
+
+  /// Pause the creation of new transactions and wait for every existing transaction to finish.
+  ///
+  /// Returns None if the database is already quiesced, or if the existing transactions didn't
+  /// finish within the timeout, in which case the database is resumed. This blocks the current
+  /// thread.
+  pub fn quiesce(&self, timeout: Duration) -> Option<Quiesced<D>> {
+    let mut status = self.state.status.lock().unwrap();
+    if status.paused {
+      None?;
+    }
+    status.paused = true;
+
+    let (status, res) = self
+      .state
+      .changed
+      .wait_timeout_while(status, timeout, |status| status.in_flight != 0)
+      .unwrap();
+    drop(status);
+    // Resumes the database if this timed out
+    let quiesced = Quiesced { db: self.db.clone(), state: self.state.clone() };
+    if res.timed_out() {
+      None?;
+    }
+    Some(quiesced)
+  }
+}
+
+impl<D: Db> Get for QuiescableDb<D> {
+  fn get(&self, key: impl AsRef<[u8]>) -> Option<Vec<u8>> {
+    self.db.get(key)
+  }
+}
+impl<D: Db> Db for QuiescableDb<D> {
+  type Transaction<'a> = QuiescableTxn<'a, D>;
+  fn txn(&mut self) -> QuiescableTxn<'_, D> {
+    let begin = || {
+      let status = self.state.status.lock().unwrap();
+      let mut status = self.state.changed.wait_while(status, |status| status.paused).unwrap();
+      status.in_flight += 1;
+    };
+    if self.state.status.lock().unwrap().paused {
+      block_in_place(begin);
+    } else {
+      // This may have been paused since the above check, in which case this blocks as normal
+      begin();
+    }
+    QuiescableTxn { txn: Some(self.db.txn()), state: &self.state }
+  }
+}
+impl<D: IterableDb> IterableDb for QuiescableDb<D> {
+  fn for_each_entry<E>(&self, f: impl FnMut(&[u8], &[u8]) -> Result<(), E>) -> Result<(), E> {
+    self.db.for_each_entry(f)
+  }
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use rocksdb::{DBCompressionType, ThreadMode, SingleThreaded, Options, Transaction, TransactionDB};
+use rocksdb::{
+  DBCompressionType, ThreadMode, SingleThreaded, IteratorMode, Options, Transaction, TransactionDB,
+};
 
 use crate::*;
 
@@ -33,6 +35,17 @@ impl<T: ThreadMode + 'static> Db for Arc<TransactionDB<T>> {
   }
 }
 
+impl<T: ThreadMode + 'static> IterableDb for Arc<TransactionDB<T>> {
+  fn for_each_entry<E>(&self, mut f: impl FnMut(&[u8], &[u8]) -> Result<(), E>) -> Result<(), E> {
+    let snapshot = self.snapshot();
+    for entry in snapshot.iterator(IteratorMode::Start) {
+      let (key, value) = entry.expect("couldn't iterate over RocksDB snapshot");
+      f(&key, &value)?;
+    }
+    Ok(())
+  }
+}
+
 pub type RocksDB = Arc<TransactionDB<SingleThreaded>>;
 pub fn new_rocksdb(path: &str) -> RocksDB {
   let mut options = Options::default();
@@ -0,0 +1,115 @@
+use std::io::{self, Read, Write};
+
+use crate::*;
+
+const MAGIC: &[u8] = b"serai-db snapshot";
+const VERSION: u8 = 0;
+
+const ENTRY: u8 = 1;
+const END: u8 = 0;
+
+/// A database whose entire contents can be read.
+pub trait IterableDb: Db {
+  /// Call a function with every key-value pair in the database.
+  ///
+  /// Every pair is read from a single, consistent view of the database, even if it's written to
+  /// while this is running.
+  fn for_each_entry<E>(&self, f: impl FnMut(&[u8], &[u8]) -> Result<(), E>) -> Result<(), E>;
+
+  /// If the database has no entries.
+  fn is_empty(&self) -> bool {
+    self.for_each_entry(|_, _| Err(())).is_ok()
+  }
+}
+
+fn write_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
+  writer.write_all(
+    &u32::try_from(len)
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "entry was too large"))?
+      .to_le_bytes(),
+  )
+}
+
+fn read_vec<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+  let mut len = [0; 4];
+  reader.read_exact(&mut len)?;
+  let mut res = vec![];
+  // Use take to not allocate the claimed length until it's actually been read
+  let len = u64::from(u32::from_le_bytes(len));
+  if reader.take(len).read_to_end(&mut res)? != usize::try_from(len).unwrap() {
+    Err(io::Error::new(io::ErrorKind::UnexpectedEof, "snapshot ended mid-entry"))?;
+  }
+  Ok(res)
+}
+
+/// Write a snapshot of the database, returning the amount of entries written.
+pub fn export_snapshot<D: IterableDb, W: Write>(db: &D, writer: &mut W) -> io::Result<u64> {
+  writer.write_all(MAGIC)?;
+  writer.write_all(&[VERSION])?;
+
+  let mut entries = 0u64;
+  db.for_each_entry(|key, value| {
+    writer.write_all(&[ENTRY])?;
+    write_len(writer, key.len())?;
+    writer.write_all(key)?;
+    write_len(writer, value.len())?;
+    writer.write_all(value)?;
+    entries += 1;
+    Ok::<_, io::Error>(())
+  })?;
+
+  // Terminate with the amount of entries so a truncated snapshot is detected
+  writer.write_all(&[END])?;
+  writer.write_all(&entries.to_le_bytes())?;
+  writer.flush()?;
+  Ok(entries)
+}
+
+/// Import a snapshot into an empty database, returning the amount of entries imported.
+///
+/// The snapshot is imported atomically, with nothing written if it's invalid.
+pub fn import_snapshot<D: IterableDb, R: Read>(db: &mut D, reader: &mut R) -> io::Result<u64> {
+  let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+  let mut magic = [0; MAGIC.len()];
+  reader.read_exact(&mut magic)?;
+  if magic != MAGIC {
+    Err(invalid("file wasn't a snapshot"))?;
+  }
+  let mut version = [0; 1];
+  reader.read_exact(&mut version)?;
+  if version[0] != VERSION {
+    Err(invalid("snapshot had an unsupported version"))?;
+  }
+
+  // Importing into a database with existing entries would merge the two
+  if !db.is_empty() {
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "database to import into wasn't empty"))?;
+  }
+
+  let mut txn = db.txn();
+  let mut entries = 0u64;
+  loop {
+    let mut kind = [0; 1];
+    reader.read_exact(&mut kind)?;
+    match kind[0] {
+      ENTRY => {
+        let key = read_vec(reader)?;
+        let value = read_vec(reader)?;
+        txn.put(key, value);
+        entries += 1;
+      }
+      END => break,
+      _ => Err(invalid("snapshot had an invalid entry"))?,
+    }
+  }
+
+  let mut expected = [0; 8];
+  reader.read_exact(&mut expected)?;
+  if u64::from_le_bytes(expected) != entries {
+    Err(invalid("snapshot had a different amount of entries than expected"))?;
+  }
+
+  txn.commit();
+  Ok(entries)
+}
@@ -0,0 +1,2 @@
+mod snapshot;
+mod quiesce;
@@ -0,0 +1,127 @@
+use core::time::Duration;
+use std::{
+  sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+    mpsc,
+  },
+  thread,
+};
+
+use crate::*;
+
+#[test]
+fn quiesce_waits_for_transactions() {
+  let db = QuiescableDb::new(MemDb::new());
+
+  // A transaction in flight prevents quiescing
+  let mut holder = db.clone();
+  let txn = holder.txn();
+  assert!(db.quiesce(Duration::from_millis(100)).is_none());
+  // The failed attempt resumed the database
+  db.clone().txn().commit();
+
+  // Once it's committed, the database can be quiesced
+  let (send, recv) = mpsc::channel();
+  let releaser = {
+    let db = db.clone();
+    thread::spawn(move || {
+      let mut db = db;
+      let mut txn = db.txn();
+      send.send(()).unwrap();
+      thread::sleep(Duration::from_millis(100));
+      txn.put(b"key", b"value");
+      txn.commit();
+    })
+  };
+  drop(txn);
+  recv.recv().unwrap();
+  let mut quiesced = db.quiesce(Duration::from_secs(10)).unwrap();
+  releaser.join().unwrap();
+  // The transaction was committed before quiescing completed
+  assert_eq!(db.get(b"key"), Some(b"value".to_vec()));
+
+  // It can't be quiesced twice
+  assert!(db.quiesce(Duration::from_millis(100)).is_none());
+
+  // The holder of the guard can still write
+  let mut txn = quiesced.db().txn();
+  txn.put(b"guard", b"value");
+  txn.commit();
+  assert_eq!(db.get(b"guard"), Some(b"value".to_vec()));
+}
+
+#[test]
+fn quiesced_blocks_transactions() {
+  let mut db = QuiescableDb::new(MemDb::new());
+  let quiesced = db.quiesce(Duration::from_secs(10)).unwrap();
+
+  let committed = Arc::new(AtomicBool::new(false));
+  let writer = {
+    let db = db.clone();
+    let committed = committed.clone();
+    thread::spawn(move || {
+      let mut db = db;
+      let mut txn = db.txn();
+      txn.put(b"key", b"value");
+      txn.commit();
+      committed.store(true, Ordering::SeqCst);
+    })
+  };
+
+  thread::sleep(Duration::from_millis(100));
+  assert!(!committed.load(Ordering::SeqCst));
+  assert_eq!(db.get(b"key"), None);
+
+  // Resuming lets the transaction proceed
+  drop(quiesced);
+  writer.join().unwrap();
+  assert!(committed.load(Ordering::SeqCst));
+  assert_eq!(db.get(b"key"), Some(b"value".to_vec()));
+
+  // And the database can be quiesced again
+  assert!(db.quiesce(Duration::from_secs(10)).is_some());
+  drop(db.txn());
+}
+
+// A task waiting for the database to be resumed shouldn't stop tasks holding transactions across
+// awaits from finishing them
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn quiesced_doesnt_stall_runtime() {
+  let db = QuiescableDb::new(MemDb::new());
+
+  let (send, recv) = tokio::sync::oneshot::channel::<()>();
+  let holder = tokio::spawn({
+    let mut db = db.clone();
+    async move {
+      let mut txn = db.txn();
+      // This only resolves once the runtime's sole worker is blocked on the quiesced database
+      recv.await.unwrap();
+      txn.put(b"key", b"value");
+      txn.commit();
+    }
+  });
+  tokio::task::yield_now().await;
+
+  let quiescer = tokio::task::spawn_blocking({
+    let db = db.clone();
+    move || db.quiesce(Duration::from_secs(10)).is_some()
+  });
+  // Wait for the quiesce to pause the database
+  tokio::time::sleep(Duration::from_millis(100)).await;
+
+  let waiter = tokio::spawn({
+    let mut db = db.clone();
+    async move {
+      drop(db.txn());
+    }
+  });
+  tokio::time::sleep(Duration::from_millis(100)).await;
+  send.send(()).unwrap();
+
+  holder.await.unwrap();
+  assert!(quiescer.await.unwrap());
+  assert_eq!(db.get(b"key"), Some(b"value".to_vec()));
+  waiter.await.unwrap();
+}
@@ -0,0 +1,80 @@
+use std::io;
+
+use crate::*;
+
+fn populated() -> MemDb {
+  let mut db = MemDb::new();
+  let mut txn = db.txn();
+  txn.put(b"key", b"value");
+  txn.put(b"empty", []);
+  txn.put([], b"empty key");
+  txn.put([0xff; 1000], vec![0xaa; 100_000]);
+  txn.commit();
+  db
+}
+
+#[test]
+fn round_trip() {
+  let db = populated();
+  let mut snapshot = vec![];
+  assert_eq!(export_snapshot(&db, &mut snapshot).unwrap(), 4);
+
+  let mut imported = MemDb::new();
+  assert_eq!(import_snapshot(&mut imported, &mut snapshot.as_slice()).unwrap(), 4);
+  assert_eq!(imported, db);
+
+  // An empty database round-trips as well
+  let mut snapshot = vec![];
+  assert_eq!(export_snapshot(&MemDb::new(), &mut snapshot).unwrap(), 0);
+  let mut imported = MemDb::new();
+  assert_eq!(import_snapshot(&mut imported, &mut snapshot.as_slice()).unwrap(), 0);
+  assert!(imported.is_empty());
+}
+
+#[test]
+fn reject_non_empty() {
+  let db = populated();
+  let mut snapshot = vec![];
+  export_snapshot(&db, &mut snapshot).unwrap();
+
+  let mut existing = MemDb::new();
+  let mut txn = existing.txn();
+  txn.put(b"existing", b"value");
+  txn.commit();
+  assert_eq!(
+    import_snapshot(&mut existing, &mut snapshot.as_slice()).unwrap_err().kind(),
+    io::ErrorKind::AlreadyExists
+  );
+  assert_eq!(existing.get(b"key"), None);
+}
+
+#[test]
+fn reject_invalid() {
+  let db = populated();
+  let mut snapshot = vec![];
+  export_snapshot(&db, &mut snapshot).unwrap();
+
+  // Every truncation should be rejected, without writing anything
+  for len in 0 .. snapshot.len() {
+    let mut imported = MemDb::new();
+    assert!(import_snapshot(&mut imported, &mut &snapshot[.. len]).is_err());
+    assert!(imported.is_empty());
+  }
+
+  // As should a snapshot whose amount of entries doesn't match its terminator
+  let mut miscounted = snapshot.clone();
+  *miscounted.last_mut().unwrap() ^= 1;
+  let mut imported = MemDb::new();
+  assert_eq!(
+    import_snapshot(&mut imported, &mut miscounted.as_slice()).unwrap_err().kind(),
+    io::ErrorKind::InvalidData
+  );
+  assert!(imported.is_empty());
+
+  // And files which aren't snapshots
+  let mut imported = MemDb::new();
+  assert_eq!(
+    import_snapshot(&mut imported, &mut [0; 64].as_slice()).unwrap_err().kind(),
+    io::ErrorKind::InvalidData
+  );
+}
@@ -1,8 +1,148 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+use std::{sync::OnceLock, collections::HashMap};
+
+use zeroize::Zeroizing;
+
+// The variables defined by command-line flags, if they've been parsed.
+static FLAGS: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+fn flag(variable: &str) -> Option<String> {
+  FLAGS.get().and_then(|flags| flags.get(variable).cloned())
+}
+
+/// Parse variables from command-line flags, returning the remaining arguments.
+///
+/// `--db-path <value>` and `--db-path=<value>` both define `DB_PATH`, with `--config` defining
+/// `CONFIG_PATH`. Flags take precedence over both the environment and the config file.
+///
+/// This may only be called once, and must be called before any variable is read.
+pub fn parse_flags(args: impl IntoIterator<Item = String>) -> Result<Vec<String>, String> {
+  let mut flags = HashMap::new();
+  let mut rest = vec![];
+  let mut args = args.into_iter();
+  while let Some(arg) = args.next() {
+    let Some(flag) = arg.strip_prefix("--") else {
+      rest.push(arg);
+      continue;
+    };
+    let (flag, value) = match flag.split_once('=') {
+      Some((flag, value)) => (flag, value.to_string()),
+      None => (flag, args.next().ok_or_else(|| format!("--{flag} wasn't given a value"))?),
+    };
+    if flag.is_empty() {
+      Err("a flag without a name was specified".to_string())?;
+    }
+
+    let variable = if flag == "config" {
+      "CONFIG_PATH".to_string()
+    } else {
+      flag.replace('-', "_").to_uppercase()
+    };
+    if flags.insert(variable, value).is_some() {
+      Err(format!("--{flag} was specified multiple times"))?;
+    }
+  }
+  FLAGS.set(flags).map_err(|_| "flags were already parsed".to_string())?;
+  Ok(rest)
+}
+
+// The variables defined within the config file, if one was specified.
+fn config_file() -> &'static HashMap<String, String> {
+  static CONFIG: OnceLock<HashMap<String, String>> = OnceLock::new();
+  CONFIG.get_or_init(|| {
+    let Some(path) = flag("CONFIG_PATH").or_else(|| std::env::var("CONFIG_PATH").ok()) else {
+      return HashMap::new();
+    };
+    let contents = std::fs::read_to_string(&path)
+      .unwrap_or_else(|e| panic!("couldn't read config file {path}: {e}"));
+    parse_config(&contents).unwrap_or_else(|e| panic!("invalid config file {path}: {e}"))
+  })
+}
+
+fn flatten(
+  prefix: &str,
+  table: toml::Table,
+  res: &mut HashMap<String, String>,
+) -> Result<(), String> {
+  for (key, value) in table {
+    let key = if prefix.is_empty() { key } else { format!("{prefix}_{key}") }.to_uppercase();
+    let value = match value {
+      toml::Value::Table(table) => {
+        flatten(&key, table, res)?;
+        continue;
+      }
+      toml::Value::String(value) => value,
+      toml::Value::Integer(value) => value.to_string(),
+      toml::Value::Boolean(value) => value.to_string(),
+      // Arrays are represented as comma-separated lists, as they would be in an env variable
+      toml::Value::Array(values) => values
+        .into_iter()
+        .map(|value| match value {
+          toml::Value::String(value) => Ok(value),
+          toml::Value::Integer(value) => Ok(value.to_string()),
+          _ => Err(format!("{key} had an array with a non-string, non-integer value")),
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .join(","),
+      _ => Err(format!("{key} had an unsupported type"))?,
+    };
+    if res.insert(key.clone(), value).is_some() {
+      Err(format!("{key} was defined multiple times"))?;
+    }
+  }
+  Ok(())
+}
+
+/// Parse a TOML config into a map of variables.
+///
+/// Tables are flattened, with their keys prefixed by the table's name, so `[network.rpc]` with
+/// `port = 8332` defines `NETWORK_RPC_PORT`. Keys are case-insensitive.
+pub fn parse_config(config: &str) -> Result<HashMap<String, String>, String> {
+  let mut res = HashMap::new();
+  flatten("", config.parse::<toml::Table>().map_err(|e| e.to_string())?, &mut res)?;
+  Ok(res)
+}
+
 // Obtain a variable from the Serai environment/secret store.
+//
+// Command-line flags take precedence over environment variables, which take precedence over the
+// config file specified by `CONFIG_PATH`.
 pub fn var(variable: &str) -> Option<String> {
   // TODO: Move this to Kubernetes
-  std::env::var(variable).ok()
+  flag(variable)
+    .or_else(|| std::env::var(variable).ok())
+    .or_else(|| config_file().get(variable).cloned())
+}
+
+/// Obtain a variable, or its contents from the file specified by `{variable}_PATH`.
+///
+/// This allows key material to be provided via a file instead of the environment. The value is
+/// zeroized when dropped.
+pub fn secret(variable: &str) -> Option<Zeroizing<String>> {
+  if let Some(path) = var(&format!("{variable}_PATH")) {
+    let contents = Zeroizing::new(
+      std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("couldn't read {variable} from {path}: {e}")),
+    );
+    return Some(Zeroizing::new(contents.trim().to_string()));
+  }
+  var(variable).map(Zeroizing::new)
+}
+
+/// Obtain a variable, parsing it, and using the default if it wasn't specified.
+pub fn var_or<T: core::str::FromStr>(variable: &str, default: T) -> T {
+  var(variable).map_or(default, |value| {
+    value.parse().unwrap_or_else(|_| panic!("{variable} was specified yet invalid: {value}"))
+  })
+}
+
+/// Obtain a comma-separated list.
+pub fn list(variable: &str) -> Vec<String> {
+  var(variable)
+    .map(|list| {
+      list.split(',').map(str::trim).filter(|item| !item.is_empty()).map(String::from).collect()
+    })
+    .unwrap_or_default()
 }
@@ -0,0 +1,126 @@
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+use core::time::Duration;
+use std::{
+  sync::{Arc, Mutex},
+  time::Instant,
+  net::SocketAddr,
+};
+
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::{TcpListener, TcpStream},
+  time::timeout,
+};
+
+/// The default port to serve the health endpoints on.
+pub const DEFAULT_HEALTH_PORT: u16 = 9090;
+
+type Check = Box<dyn Send + Sync + Fn() -> bool>;
+
+/// A set of named checks which must all pass for a service to be ready.
+///
+/// `/live` responds with 200 while the process is responsive. `/ready` responds with 200 if every
+/// check passes, and 503 otherwise, with a line for each check's status.
+#[derive(Clone, Default)]
+pub struct Health(Arc<Mutex<Vec<(String, Check)>>>);
+
+/// A check which passes if it was beat within its timeout.
+///
+/// It fails until it's first beat.
+#[derive(Clone, Debug)]
+pub struct Heartbeat(Arc<Mutex<Option<Instant>>>);
+
+impl Heartbeat {
+  /// Mark the monitored task as having made progress.
+  pub fn beat(&self) {
+    *self.0.lock().unwrap() = Some(Instant::now());
+  }
+
+  /// The time since the last beat.
+  pub fn since_last(&self) -> Option<Duration> {
+    self.0.lock().unwrap().map(|last| last.elapsed())
+  }
+}
+
+impl Health {
+  pub fn new() -> Health {
+    Health::default()
+  }
+
+  /// Add a check.
+  pub fn check(&self, name: impl Into<String>, check: impl 'static + Send + Sync + Fn() -> bool) {
+    self.0.lock().unwrap().push((name.into(), Box::new(check)));
+  }
+
+  /// Remove all checks with the specified name.
+  pub fn remove(&self, name: &str) {
+    self.0.lock().unwrap().retain(|(check, _)| check != name);
+  }
+
+  /// Add a check which passes while it's beat at least once every `timeout`.
+  pub fn heartbeat(&self, name: impl Into<String>, timeout: Duration) -> Heartbeat {
+    let heartbeat = Heartbeat(Arc::new(Mutex::new(None)));
+    self.check(name, {
+      let heartbeat = heartbeat.clone();
+      move || heartbeat.since_last().is_some_and(|since_last| since_last < timeout)
+    });
+    heartbeat
+  }
+
+  /// Run every check, returning each check's name and if it passed.
+  pub fn status(&self) -> Vec<(String, bool)> {
+    self.0.lock().unwrap().iter().map(|(name, check)| (name.clone(), check())).collect()
+  }
+
+  async fn respond(&self, mut stream: TcpStream) -> std::io::Result<()> {
+    // Only the request line is needed, which should be within the first read
+    let mut buf = [0; 1024];
+    let len = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[.. len]);
+    let mut request = request.split_whitespace();
+
+    let (status, body) = match (request.next(), request.next()) {
+      (Some("GET"), Some("/live")) => ("200 OK", "live\n".to_string()),
+      (Some("GET"), Some("/ready")) => {
+        let status = self.status();
+        let mut body = String::new();
+        for (name, passed) in &status {
+          body += &format!("{name}: {}\n", if *passed { "ok" } else { "failing" });
+        }
+        if status.iter().all(|(_, passed)| *passed) {
+          ("200 OK", body)
+        } else {
+          ("503 Service Unavailable", body)
+        }
+      }
+      _ => ("404 Not Found", String::new()),
+    };
+
+    stream
+      .write_all(
+        format!(
+          "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\
+            Connection: close\r\n\r\n{body}",
+          body.len()
+        )
+        .as_bytes(),
+      )
+      .await?;
+    stream.shutdown().await
+  }
+
+  /// Serve the health endpoints on the specified address.
+  pub async fn serve(self, addr: SocketAddr) {
+    let listener = TcpListener::bind(addr).await.expect("couldn't bind the health endpoints");
+    loop {
+      let Ok((stream, _)) = listener.accept().await else { continue };
+      let health = self.clone();
+      tokio::spawn(async move {
+        // Don't let a slow client hold the connection open indefinitely
+        let _ = timeout(Duration::from_secs(5), health.respond(stream)).await;
+      });
+    }
+  }
+}
@@ -0,0 +1,8 @@
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+mod rng;
+pub use rng::*;
+
+mod time;
+pub use time::*;
@@ -0,0 +1,50 @@
+use std::sync::OnceLock;
+
+use rand_core::{RngCore, SeedableRng, OsRng};
+use rand_chacha::ChaCha20Rng;
+
+use blake2::{Digest, Blake2s256};
+
+/// The environment variable to replay a test run's randomness with.
+pub const SEED_VAR: &str = "SERAI_TEST_SEED";
+
+/// The seed for this test run.
+///
+/// This is read from `SERAI_TEST_SEED` (as hex) if set, and randomly generated otherwise. It's
+/// printed the first time it's requested so a failing run can be reproduced.
+pub fn seed() -> [u8; 32] {
+  static SEED: OnceLock<[u8; 32]> = OnceLock::new();
+  *SEED.get_or_init(|| {
+    let seed = match std::env::var(SEED_VAR) {
+      Ok(seed) => hex::decode(seed.trim())
+        .ok()
+        .and_then(|seed| <[u8; 32]>::try_from(seed).ok())
+        .unwrap_or_else(|| panic!("{SEED_VAR} wasn't 32 hex-encoded bytes")),
+      Err(_) => {
+        let mut seed = [0; 32];
+        OsRng.fill_bytes(&mut seed);
+        seed
+      }
+    };
+    eprintln!("{SEED_VAR}={}", hex::encode(seed));
+    seed
+  })
+}
+
+/// A RNG seeded from this test run's seed.
+///
+/// Every call returns a RNG with the same stream. Use `labeled_rng` for independent streams.
+pub fn test_rng() -> ChaCha20Rng {
+  ChaCha20Rng::from_seed(seed())
+}
+
+/// A RNG seeded from this test run's seed and a label.
+///
+/// Distinct labels yield independent streams, letting concurrent tasks each have a RNG without
+/// the order they're scheduled in affecting the values produced.
+pub fn labeled_rng(label: &[u8]) -> ChaCha20Rng {
+  let mut hasher = Blake2s256::new();
+  hasher.update(seed());
+  hasher.update(label);
+  ChaCha20Rng::from_seed(hasher.finalize().into())
+}
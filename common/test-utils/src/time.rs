@@ -0,0 +1,40 @@
+use core::{future::Future, time::Duration};
+
+use tokio::time::{Instant, sleep};
+
+/// How often `wait_until` polls its condition.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Pause tokio's clock, making sleeps resolve as soon as the runtime is otherwise idle.
+///
+/// This is equivalent to `#[tokio::test(start_paused = true)]` and has the same requirement of a
+/// current-thread runtime. Code which reads `std::time` is unaffected, so this should only be
+/// used with code which exclusively uses tokio's clock.
+pub fn pause() {
+  tokio::time::pause();
+}
+
+/// Advance tokio's paused clock, firing every timer which expires in the meantime.
+pub async fn advance(duration: Duration) {
+  tokio::time::advance(duration).await;
+}
+
+/// Wait until a condition holds, returning false if it didn't within the timeout.
+///
+/// This should be used in place of sleeping for however long an event is expected to take. Since
+/// it polls with tokio's clock, it costs no real time when tokio's clock is paused.
+pub async fn wait_until<F: Future<Output = bool>>(
+  timeout: Duration,
+  mut condition: impl FnMut() -> F,
+) -> bool {
+  let deadline = Instant::now() + timeout;
+  loop {
+    if condition().await {
+      return true;
+    }
+    if Instant::now() >= deadline {
+      return false;
+    }
+    sleep(POLL_INTERVAL).await;
+  }
+}
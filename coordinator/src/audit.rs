@@ -0,0 +1,155 @@
+use std::{
+  fs::File,
+  io::{Read, Write, BufWriter},
+};
+
+use scale::{Encode, Decode};
+
+use serai_client::{
+  primitives::NetworkId,
+  validator_sets::primitives::{ValidatorSet, KeyPair},
+  subxt::utils::Encoded,
+};
+
+use serai_db::{DbTxn, Db, RocksDB, MemDb};
+
+use processor_messages::CoordinatorMessage;
+
+use ::tributary::{TributaryReader, export_chain, import_chain};
+
+use serai_env as env;
+
+use crate::{
+  Config, LibP2p,
+  db::MainDb,
+  processors::{Message, Processors},
+  tributary::{TributarySpec, Transaction, TributaryDb, scanner::RecognizedIdType},
+};
+
+// The context from Substrate needed to replay a Tributary, which isn't on the Tributary itself
+#[derive(Encode, Decode)]
+struct Context {
+  key_pair: Option<KeyPair>,
+  // The plans for each Substrate block provided to the Tributary
+  plan_ids: Vec<(u64, Vec<[u8; 32]>)>,
+}
+
+// Processors which print every message sent to them, as a replay never receives messages
+#[derive(Clone)]
+struct PrintProcessors;
+#[async_trait::async_trait]
+impl Processors for PrintProcessors {
+  async fn send(&self, network: NetworkId, msg: CoordinatorMessage) {
+    println!("sent the {network:?} processor {msg:?}");
+  }
+  async fn recv(&mut self, _: NetworkId) -> Message {
+    unreachable!("replaying a Tributary received a message from a processor")
+  }
+  async fn ack(&mut self, _: Message) {
+    unreachable!("replaying a Tributary acknowledged a message from a processor")
+  }
+}
+
+/// Write the Tributary with the specified genesis, from the DB at `DB_PATH`, to the file at
+/// `path`.
+///
+/// This includes the Tributary's spec, its chain, and the context from Substrate needed to replay
+/// it. The coordinator must not be running while exporting.
+pub fn export(genesis: &str, path: &str) {
+  let genesis: [u8; 32] = hex::decode(genesis)
+    .ok()
+    .and_then(|genesis| genesis.try_into().ok())
+    .expect("genesis wasn't 32 hex-encoded bytes");
+
+  let db_path = env::var("DB_PATH").expect("path to DB wasn't specified");
+  let db = serai_db::new_rocksdb(&db_path);
+  let spec = MainDb::active_tributaries(&db)
+    .1
+    .into_iter()
+    .find(|spec| spec.genesis() == genesis)
+    .unwrap_or_else(|| panic!("no Tributary with genesis {}", hex::encode(genesis)));
+  let reader = TributaryReader::<_, Transaction>::new(db.clone(), genesis);
+
+  let mut plan_ids = vec![];
+  let mut last = genesis;
+  while let Some(hash) = reader.block_after(&last) {
+    for tx in reader.block(&hash).unwrap().transactions {
+      let ::tributary::Transaction::Application(Transaction::SubstrateBlock(block)) = tx else {
+        continue;
+      };
+      if let Some(plans) = TributaryDb::<RocksDB>::plan_ids(&db, genesis, block) {
+        plan_ids.push((block, plans));
+      }
+    }
+    last = hash;
+  }
+  let context = Context { key_pair: TributaryDb::<RocksDB>::key_pair(&db, spec.set()), plan_ids };
+
+  // Create the file exclusively to never overwrite a prior export
+  let file = File::options()
+    .write(true)
+    .create_new(true)
+    .open(path)
+    .unwrap_or_else(|e| panic!("couldn't create export file {path}: {e}"));
+  let mut writer = BufWriter::new(file);
+  spec.write(&mut writer).unwrap();
+  let blocks =
+    export_chain(&reader, &mut writer).unwrap_or_else(|e| panic!("couldn't export Tributary: {e}"));
+  writer.write_all(&context.encode()).unwrap();
+  writer.flush().unwrap();
+  println!("exported {blocks} blocks of Tributary {} to {path}", hex::encode(genesis));
+}
+
+/// Replay a Tributary exported to the file at `path`, as the validator with the configured key.
+///
+/// Every block is verified, then scanned, printing every message which would be sent to the
+/// processor, ID which would be recognized, and transaction which would be published to Serai.
+/// This lets the messages the validator's coordinator emitted be audited offline.
+pub async fn replay(path: &str) {
+  let config = Config::load();
+
+  let mut export = vec![];
+  File::open(path)
+    .and_then(|mut file| file.read_to_end(&mut export))
+    .unwrap_or_else(|e| panic!("couldn't read export file {path}: {e}"));
+  let mut export = export.as_slice();
+
+  let spec = TributarySpec::read(&mut export).expect("export had an invalid spec");
+  let genesis = spec.genesis();
+  let mut db = MemDb::new();
+  let blocks = import_chain::<_, Transaction, _>(
+    db.clone(),
+    genesis,
+    spec.validators(),
+    spec.consensus_params(),
+    &mut export,
+  )
+  .unwrap_or_else(|e| panic!("export had an invalid chain: {e}"));
+  let context = Context::decode(&mut export).expect("export had invalid context");
+  println!("imported {blocks} blocks of Tributary {} for {:?}", hex::encode(genesis), spec.set());
+
+  let mut txn = db.txn();
+  if let Some(key_pair) = &context.key_pair {
+    TributaryDb::<MemDb>::set_key_pair(&mut txn, spec.set(), key_pair);
+  }
+  for (block, plans) in &context.plan_ids {
+    TributaryDb::<MemDb>::set_plan_ids(&mut txn, genesis, *block, plans);
+  }
+  txn.commit();
+
+  let reader = TributaryReader::<_, Transaction>::new(db.clone(), genesis);
+  crate::tributary::scanner::handle_new_blocks::<_, _, _, _, _, _, LibP2p>(
+    &mut TributaryDb::new(db),
+    &config.key,
+    |network: NetworkId, _: [u8; 32], id_type: RecognizedIdType, id: [u8; 32], nonce: u32| async move {
+      println!("recognized {id_type:?} {} on {network:?}, with nonce {nonce}", hex::encode(id));
+    },
+    &PrintProcessors,
+    |set: ValidatorSet, tx: Encoded| async move {
+      println!("published a transaction for {set:?} to Serai: {}", hex::encode(tx.0));
+    },
+    &spec,
+    &reader,
+  )
+  .await;
+}
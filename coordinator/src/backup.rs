@@ -0,0 +1,39 @@
+use std::{
+  fs::File,
+  io::{BufReader, BufWriter},
+};
+
+use serai_db::{IterableDb, export_snapshot, import_snapshot};
+
+use serai_env as env;
+
+/// Write a snapshot of the DB at `DB_PATH` to the file at `path`.
+///
+/// The coordinator must not be running while exporting.
+pub fn export(path: &str) {
+  let db_path = env::var("DB_PATH").expect("path to DB wasn't specified");
+  let db = serai_db::new_rocksdb(&db_path);
+  // Create the file exclusively to never overwrite a prior snapshot
+  let file = File::options()
+    .write(true)
+    .create_new(true)
+    .open(path)
+    .unwrap_or_else(|e| panic!("couldn't create snapshot file {path}: {e}"));
+  let entries = export_snapshot(&db, &mut BufWriter::new(file))
+    .unwrap_or_else(|e| panic!("couldn't export snapshot: {e}"));
+  println!("exported {entries} entries to {path}");
+}
+
+/// Import the snapshot at `path` into the empty DB at `DB_PATH`.
+pub fn import(path: &str) {
+  let db_path = env::var("DB_PATH").expect("path to DB wasn't specified");
+  let mut db = serai_db::new_rocksdb(&db_path);
+  if !db.is_empty() {
+    panic!("DB at {db_path} already has entries. snapshots may only be imported into a new DB");
+  }
+  let file = File::open(path).unwrap_or_else(|e| panic!("couldn't open snapshot file {path}: {e}"));
+  let entries = import_snapshot(&mut db, &mut BufReader::new(file))
+    .unwrap_or_else(|e| panic!("couldn't import snapshot: {e}"));
+  crate::migration::mark_imported(&mut db);
+  println!("imported {entries} entries into {db_path}");
+}
@@ -0,0 +1,100 @@
+use core::ops::Deref;
+
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+
+use crate::{Config, backup, audit};
+
+fn usage(code: i32) -> ! {
+  eprintln!("usage: serai-coordinator [options] [command]");
+  eprintln!();
+  eprintln!("commands:");
+  eprintln!("  export <path>    write a snapshot of the DB at DB_PATH to the file at path");
+  eprintln!("  import <path>    import the snapshot at path into the empty DB at DB_PATH");
+  eprintln!("  export-tributary <genesis> <path>");
+  eprintln!("                   write the Tributary with the hex-encoded genesis, from the DB at");
+  eprintln!("                   DB_PATH, to the file at path");
+  eprintln!("  replay-tributary <path>");
+  eprintln!("                   verify and replay the Tributary exported to path, printing the");
+  eprintln!("                   messages it causes for the validator with the configured key");
+  eprintln!("  check-config     load and validate the configuration, then exit");
+  eprintln!("  help             print this message");
+  eprintln!();
+  eprintln!("with no command, the coordinator is run");
+  eprintln!();
+  eprintln!("the coordinator must not be running while exporting. once a snapshot is imported,");
+  eprintln!("the exported coordinator must never be run again, as running both would have them");
+  eprintln!("sign conflicting messages");
+  eprintln!();
+  eprintln!("options, which take precedence over the environment and the config file:");
+  eprintln!("  --config <path>               the TOML config file to read");
+  eprintln!("  --db-path <path>              the path to the DB");
+  eprintln!("  --serai-key-path <path>       the file containing the hex-encoded Serai key");
+  eprintln!("  --serai-hostname <hosts>      the comma-separated Serai nodes to fail over");
+  eprintln!("                                between, either as hostnames or ws:// URLs");
+  eprintln!("  --serai-rpc-port <port>       the RPC port of Serai nodes specified by hostname");
+  eprintln!("                                (default 9944)");
+  eprintln!("  --p2p-listen-address <ip>     the address to listen for P2P connections on");
+  eprintln!("                                (default 0.0.0.0)");
+  eprintln!("  --p2p-port <port>             the port to listen for P2P connections on");
+  eprintln!("                                (default 30563)");
+  eprintln!("  --p2p-bootnodes <addrs>       the comma-separated multiaddrs of peers to dial on");
+  eprintln!("                                boot");
+  eprintln!();
+  eprintln!("any other variable may be specified as a flag by lowercasing it and replacing each _");
+  eprintln!("with -, such as --health-port for HEALTH_PORT");
+  std::process::exit(code);
+}
+
+/// Parse the command-line flags, returning the command, and its arguments, if one was specified.
+///
+/// This must be called before any variable is read.
+pub fn parse() -> Vec<String> {
+  let args = std::env::args().skip(1).collect::<Vec<_>>();
+  if args.iter().any(|arg| (arg == "-h") || (arg == "--help")) {
+    usage(0);
+  }
+  serai_env::parse_flags(args).unwrap_or_else(|e| {
+    eprintln!("{e}");
+    eprintln!();
+    usage(1)
+  })
+}
+
+fn check_config() {
+  let config = Config::load();
+  println!("DB path: {}", config.db_path);
+  println!(
+    "validator key: {}",
+    hex::encode((Ristretto::generator() * config.key.deref()).to_bytes())
+  );
+  println!("P2P: listening on {}:{}", config.p2p.listen_address, config.p2p.port);
+  for bootnode in &config.p2p.bootnodes {
+    println!("P2P bootnode: {bootnode}");
+  }
+  for endpoint in &config.serai_endpoints {
+    println!("Serai node: {endpoint}");
+  }
+  println!("health port: {}", config.health_port);
+  if let Some(inspect_port) = config.inspect_port {
+    println!("inspection RPC port: {inspect_port}");
+  }
+  println!("migration path: {}", config.migration_path);
+  println!("config is valid");
+}
+
+/// Run the command specified, if there is one.
+///
+/// Returns false if no command was specified, in which case the coordinator should be run.
+pub async fn run_command(args: &[String]) -> bool {
+  let Some(command) = args.first() else { return false };
+  match (command.as_str(), &args[1 ..]) {
+    ("export", [path]) => backup::export(path),
+    ("import", [path]) => backup::import(path),
+    ("export-tributary", [genesis, path]) => audit::export(genesis, path),
+    ("replay-tributary", [path]) => audit::replay(path).await,
+    ("check-config", []) => check_config(),
+    ("help", []) => usage(0),
+    _ => usage(1),
+  }
+  true
+}
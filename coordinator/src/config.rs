@@ -0,0 +1,140 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use ciphersuite::{group::ff::PrimeField, Ciphersuite, Ristretto};
+
+use libp2p::Multiaddr;
+
+use serai_env as env;
+
+/// The default port for the coordinator's P2P network.
+pub const DEFAULT_P2P_PORT: u16 = 30563; // 5132 ^ (('c' << 8) | 'o')
+/// The default port for the Serai node's RPC.
+pub const DEFAULT_SERAI_RPC_PORT: u16 = 9944;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct P2pConfig {
+  /// The address to listen on.
+  pub listen_address: IpAddr,
+  /// The port to listen on, which is also the port expected of peers discovered via mDNS.
+  pub port: u16,
+  /// Peers to dial on boot.
+  pub bootnodes: Vec<Multiaddr>,
+}
+
+impl Default for P2pConfig {
+  fn default() -> Self {
+    P2pConfig {
+      listen_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+      port: DEFAULT_P2P_PORT,
+      bootnodes: vec![],
+    }
+  }
+}
+
+/// The configuration for the coordinator.
+///
+/// Each value is read from the command-line flags, then the environment, falling back to the
+/// config file specified by `CONFIG_PATH` (see `serai_env`).
+pub struct Config {
+  /// The path to the database (`DB_PATH`).
+  pub db_path: String,
+  /// The key used to participate on Serai (`SERAI_KEY`, or the file at `SERAI_KEY_PATH`).
+  pub key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+  /// The P2P configuration (`P2P_LISTEN_ADDRESS`, `P2P_PORT`, `P2P_BOOTNODES`).
+  pub p2p: P2pConfig,
+  /// The Serai node RPC endpoints to fail over between (`SERAI_HOSTNAME`, `SERAI_RPC_PORT`).
+  ///
+  /// Each may be specified as a hostname, using the RPC port, or as a complete `ws://`/`wss://`
+  /// URL.
+  pub serai_endpoints: Vec<String>,
+  /// The port to serve the health endpoints on (`HEALTH_PORT`).
+  pub health_port: u16,
+  /// The port to serve the read-only tributary inspection RPC on, if it should be served
+  /// (`INSPECT_PORT`).
+  pub inspect_port: Option<u16>,
+  /// The path to export a snapshot to upon the migration signal (`MIGRATION_PATH`, defaulting to
+  /// `DB_PATH` with `.migration` appended).
+  pub migration_path: String,
+}
+
+impl Config {
+  /// Load the configuration, panicking if it's missing values or invalid.
+  pub fn load() -> Config {
+    let db_path = env::var("DB_PATH").expect("path to DB wasn't specified");
+
+    let key = {
+      let key_hex = env::secret("SERAI_KEY").expect("Serai key wasn't provided");
+      let mut key_vec =
+        hex::decode(key_hex.as_str()).map_err(|_| ()).expect("Serai key wasn't hex-encoded");
+      if key_vec.len() != 32 {
+        key_vec.zeroize();
+        panic!("Serai key had an invalid length");
+      }
+      let mut key_bytes = [0; 32];
+      key_bytes.copy_from_slice(&key_vec);
+      key_vec.zeroize();
+      let key = Zeroizing::new(
+        Option::from(<Ristretto as Ciphersuite>::F::from_repr(key_bytes))
+          .expect("Serai key wasn't a valid scalar"),
+      );
+      key_bytes.zeroize();
+      key
+    };
+
+    let p2p = {
+      let default = P2pConfig::default();
+      P2pConfig {
+        listen_address: env::var_or("P2P_LISTEN_ADDRESS", default.listen_address),
+        port: env::var_or("P2P_PORT", default.port),
+        bootnodes: env::list("P2P_BOOTNODES")
+          .into_iter()
+          .map(|bootnode| {
+            bootnode.parse().unwrap_or_else(|_| panic!("invalid P2P bootnode {bootnode}"))
+          })
+          .collect(),
+      }
+    };
+
+    // Multiple Serai nodes may be specified, separated by commas, to fail over between
+    let serai_port = env::var_or("SERAI_RPC_PORT", DEFAULT_SERAI_RPC_PORT);
+    let serai_endpoints = env::list("SERAI_HOSTNAME")
+      .into_iter()
+      .map(|hostname| {
+        if hostname.contains("://") {
+          if !(hostname.starts_with("ws://") || hostname.starts_with("wss://")) {
+            panic!("Serai node URL {hostname} wasn't a websocket URL");
+          }
+          hostname
+        } else {
+          format!("ws://{hostname}:{serai_port}")
+        }
+      })
+      .collect::<Vec<_>>();
+    if serai_endpoints.is_empty() {
+      panic!("Serai hostname wasn't provided");
+    }
+
+    let health_port = env::var_or("HEALTH_PORT", serai_health::DEFAULT_HEALTH_PORT);
+
+    let inspect_port = env::var("INSPECT_PORT")
+      .map(|port| port.parse().unwrap_or_else(|_| panic!("invalid inspection RPC port {port}")));
+
+    // Every service listens on all interfaces, so each must have its own port
+    {
+      let mut ports = vec![("P2P", p2p.port), ("health", health_port)];
+      ports.extend(inspect_port.map(|port| ("inspection RPC", port)));
+      for (i, (service, port)) in ports.iter().enumerate() {
+        if let Some((other, _)) = ports[.. i].iter().find(|(_, other)| other == port) {
+          panic!("{service} and {other} were both configured to use port {port}");
+        }
+      }
+    }
+
+    let migration_path =
+      env::var("MIGRATION_PATH").unwrap_or_else(|| format!("{db_path}.migration"));
+
+    Config { db_path, key, p2p, serai_endpoints, health_port, inspect_port, migration_path }
+  }
+}
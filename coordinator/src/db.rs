@@ -1,8 +1,11 @@
 use core::marker::PhantomData;
+use std::collections::HashMap;
 
 use scale::{Encode, Decode};
 use serai_client::{primitives::NetworkId, in_instructions::primitives::SignedBatch};
 
+use message_queue::{Service, client::MessageQueue};
+
 pub use serai_db::*;
 
 use ::tributary::ReadWrite;
@@ -15,16 +18,6 @@ impl<D: Db> MainDb<D> {
     D::key(b"coordinator_main", dst, key)
   }
 
-  fn handled_message_key(network: NetworkId, id: u64) -> Vec<u8> {
-    Self::main_key(b"handled_message", (network, id).encode())
-  }
-  pub fn save_handled_message(txn: &mut D::Transaction<'_>, network: NetworkId, id: u64) {
-    txn.put(Self::handled_message_key(network, id), []);
-  }
-  pub fn handled_message<G: Get>(getter: &G, network: NetworkId, id: u64) -> bool {
-    getter.get(Self::handled_message_key(network, id)).is_some()
-  }
-
   fn acive_tributaries_key() -> Vec<u8> {
     Self::main_key(b"active_tributaries", [])
   }
@@ -51,6 +44,16 @@ impl<D: Db> MainDb<D> {
     spec.write(&mut existing_bytes).unwrap();
     txn.put(key, existing_bytes);
   }
+  pub fn remove_active_tributary(txn: &mut D::Transaction<'_>, spec: &TributarySpec) {
+    let (_, existing) = Self::active_tributaries(txn);
+    let mut bytes = vec![];
+    for tributary in existing {
+      if &tributary != spec {
+        tributary.write(&mut bytes).unwrap();
+      }
+    }
+    txn.put(Self::acive_tributaries_key(), bytes);
+  }
 
   fn signed_transaction_key(nonce: u32) -> Vec<u8> {
     Self::main_key(b"signed_transaction", nonce.to_le_bytes())
@@ -99,3 +102,39 @@ impl<D: Db> MainDb<D> {
       .map(|batch| SignedBatch::decode(&mut batch.as_ref()).unwrap())
   }
 }
+
+impl<D: IterableDb> MainDb<D> {
+  // Move the tracking of which messages were handled, as done prior to MessageQueue::handle, to
+  // MessageQueue
+  // Messages are handled in order, so only the latest message handled from each processor is kept
+  // This should be called on boot, before the DB is otherwise used, and is a no-op once done
+  pub fn migrate_legacy_handled_messages(db: &mut D) {
+    let prefix = Self::main_key(b"handled_message", []);
+    let mut legacy_keys = vec![];
+    let mut latest = HashMap::new();
+    db.for_each_entry(|key, _| {
+      let Some(mut suffix) = key.strip_prefix(prefix.as_slice()) else { return Ok(()) };
+      let Ok((network, id)) = <(NetworkId, u64)>::decode(&mut suffix) else { return Ok(()) };
+      legacy_keys.push(key.to_vec());
+      let latest = latest.entry(network).or_insert(id);
+      *latest = (*latest).max(id);
+      Ok::<_, ()>(())
+    })
+    .unwrap();
+    if legacy_keys.is_empty() {
+      return;
+    }
+
+    let mut txn = db.txn();
+    for (network, id) in latest {
+      let from = Service::Processor(network);
+      if !MessageQueue::handled(&txn, from, id) {
+        MessageQueue::handle(&mut txn, from, id);
+      }
+    }
+    for key in legacy_keys {
+      txn.del(key);
+    }
+    txn.commit();
+  }
+}
@@ -0,0 +1,269 @@
+use std::{sync::Arc, net::SocketAddr, collections::HashMap};
+
+use serde::Serialize;
+
+use tokio::sync::{RwLock, broadcast};
+
+use jsonrpsee::{RpcModule, server::ServerBuilder, core::Error};
+
+use ciphersuite::group::GroupEncoding;
+
+use serai_client::primitives::NetworkId;
+
+use tributary::Transaction as TributaryTransaction;
+
+use crate::{
+  Db, P2p, ActiveTributary, TributaryEvent,
+  tributary::{Transaction, TributaryDb},
+  substrate::{Cosign, SubstrateDb},
+  shutdown::Subtasks,
+};
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct InspectedTributary {
+  pub genesis: String,
+  pub network: String,
+  pub session: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct InspectedTip {
+  pub hash: String,
+  pub number: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct InspectedTransaction {
+  pub hash: String,
+  // The transaction's debug representation, as decoded
+  pub transaction: String,
+}
+
+impl InspectedTransaction {
+  fn new(tx: &TributaryTransaction<Transaction>) -> Self {
+    InspectedTransaction { hash: hex::encode(tx.hash()), transaction: format!("{tx:?}") }
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct InspectedBlock {
+  pub hash: String,
+  pub parent: String,
+  pub time: Option<u64>,
+  pub transactions: Vec<InspectedTransaction>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct InspectedEvidence {
+  pub validator: String,
+  // The evidence of the validator's equivocation, as published on-chain
+  pub evidence: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct InspectedCosign {
+  pub network: String,
+  pub session: u32,
+  pub block_number: u64,
+  pub block: String,
+  pub signature: String,
+}
+
+impl InspectedCosign {
+  fn new(cosign: Cosign) -> Self {
+    InspectedCosign {
+      network: format!("{:?}", cosign.set.network),
+      session: cosign.set.session.0,
+      block_number: cosign.block_number,
+      block: hex::encode(cosign.block),
+      signature: hex::encode(cosign.signature),
+    }
+  }
+}
+
+fn decode_hash(hash: &str) -> Option<[u8; 32]> {
+  hex::decode(hash).ok()?.try_into().ok()
+}
+
+/// Read-only views into the tributaries we're running, for debugging.
+///
+/// Retired tributaries remain inspectable until they're pruned.
+#[derive(Clone)]
+pub struct Inspector<D: Db, P: P2p>(D, Arc<RwLock<HashMap<[u8; 32], ActiveTributary<D, P>>>>);
+
+impl<D: Db, P: P2p> Inspector<D, P> {
+  pub fn new(db: D) -> Self {
+    Inspector(db, Arc::new(RwLock::new(HashMap::new())))
+  }
+
+  pub async fn add(&self, tributary: ActiveTributary<D, P>) {
+    self.1.write().await.insert(tributary.spec.genesis(), tributary);
+  }
+
+  async fn tributary(&self, genesis: &str) -> Option<ActiveTributary<D, P>> {
+    self.1.read().await.get(&decode_hash(genesis)?).cloned()
+  }
+
+  pub async fn tributaries(&self) -> Vec<InspectedTributary> {
+    let mut res = self
+      .1
+      .read()
+      .await
+      .values()
+      .map(|tributary| {
+        let set = tributary.spec.set();
+        InspectedTributary {
+          genesis: hex::encode(tributary.spec.genesis()),
+          network: format!("{:?}", set.network),
+          session: set.session.0,
+        }
+      })
+      .collect::<Vec<_>>();
+    res.sort_by(|a, b| (&a.network, a.session).cmp(&(&b.network, b.session)));
+    res
+  }
+
+  pub async fn tip(&self, genesis: &str) -> Option<InspectedTip> {
+    let tributary = self.tributary(genesis).await?.tributary;
+    Some(InspectedTip {
+      hash: hex::encode(tributary.tip().await),
+      number: tributary.block_number().await,
+    })
+  }
+
+  pub async fn block(&self, genesis: &str, hash: &str) -> Option<InspectedBlock> {
+    let reader = self.tributary(genesis).await?.tributary.reader();
+    let hash = decode_hash(hash)?;
+    let block = reader.block(&hash)?;
+    Some(InspectedBlock {
+      hash: hex::encode(hash),
+      parent: hex::encode(block.header.parent),
+      time: reader.time_of_block(&hash),
+      transactions: block.transactions.iter().map(InspectedTransaction::new).collect(),
+    })
+  }
+
+  pub async fn mempool(&self, genesis: &str) -> Option<Vec<InspectedTransaction>> {
+    let tributary = self.tributary(genesis).await?.tributary;
+    let mut res = tributary
+      .mempool_transactions()
+      .await
+      .iter()
+      .map(InspectedTransaction::new)
+      .collect::<Vec<_>>();
+    res.sort_by(|a, b| a.hash.cmp(&b.hash));
+    Some(res)
+  }
+
+  /// The evidence of equivocation scanned from a tributary, in the order of its validators.
+  pub async fn evidence(&self, genesis: &str) -> Option<Vec<InspectedEvidence>> {
+    let spec = self.tributary(genesis).await?.spec;
+    Some(
+      spec
+        .validators()
+        .into_iter()
+        .filter_map(|(validator, _)| {
+          let validator = validator.to_bytes();
+          TributaryDb::<D>::evidence(&self.0, spec.genesis(), validator).map(|evidence| {
+            InspectedEvidence { validator: hex::encode(validator), evidence: hex::encode(evidence) }
+          })
+        })
+        .collect(),
+    )
+  }
+
+  /// The cosigns we have for a Serai block, one per network.
+  pub fn cosigns(&self, block_number: u64) -> Vec<InspectedCosign> {
+    [NetworkId::Bitcoin, NetworkId::Ethereum, NetworkId::Monero]
+      .into_iter()
+      .filter_map(|network| SubstrateDb::<D>::cosign(&self.0, network, block_number))
+      .map(InspectedCosign::new)
+      .collect()
+  }
+
+  /// The latest cosign we have for each network.
+  pub fn latest_cosigns(&self) -> Vec<InspectedCosign> {
+    [NetworkId::Bitcoin, NetworkId::Ethereum, NetworkId::Monero]
+      .into_iter()
+      .filter_map(|network| SubstrateDb::<D>::latest_cosign(&self.0, network))
+      .map(InspectedCosign::new)
+      .collect()
+  }
+}
+
+/// Serve the inspection RPC on the specified address, adding tributaries as they're announced.
+///
+/// This offers the methods `tributaries`, `tip [genesis]`, `block [genesis, hash]`,
+/// `mempool [genesis]`, and `evidence [genesis]`, with all hashes hex-encoded. Unknown tributaries
+/// and blocks yield null.
+///
+/// It also offers `cosigns [block_number]` and `latest_cosigns`, serving the cosigns of Serai
+/// blocks we've produced so they may be relayed to whoever wants to verify Serai's finality.
+pub async fn serve<D: Db, P: P2p>(
+  addr: SocketAddr,
+  db: D,
+  mut new_tributary: broadcast::Receiver<TributaryEvent<D, P>>,
+) {
+  let inspector = Inspector::new(db);
+  let mut subtasks = Subtasks::new();
+  subtasks.spawn({
+    let inspector = inspector.clone();
+    async move {
+      loop {
+        match new_tributary.recv().await {
+          Ok(TributaryEvent::NewTributary(tributary)) => inspector.add(tributary).await,
+          Ok(TributaryEvent::TributaryRetired(_)) => {}
+          Err(broadcast::error::RecvError::Lagged(_)) => {
+            panic!("inspector lagged to handle new_tributary")
+          }
+          Err(broadcast::error::RecvError::Closed) => panic!("new_tributary sender closed"),
+        }
+      }
+    }
+  });
+
+  let mut module = RpcModule::new(inspector);
+  module
+    .register_async_method("tributaries", |_, inspector| async move {
+      Ok::<_, Error>(inspector.tributaries().await)
+    })
+    .unwrap();
+  module
+    .register_async_method("tip", |args, inspector| async move {
+      let (genesis,) = args.parse::<(String,)>()?;
+      Ok::<_, Error>(inspector.tip(&genesis).await)
+    })
+    .unwrap();
+  module
+    .register_async_method("block", |args, inspector| async move {
+      let (genesis, hash) = args.parse::<(String, String)>()?;
+      Ok::<_, Error>(inspector.block(&genesis, &hash).await)
+    })
+    .unwrap();
+  module
+    .register_async_method("mempool", |args, inspector| async move {
+      let (genesis,) = args.parse::<(String,)>()?;
+      Ok::<_, Error>(inspector.mempool(&genesis).await)
+    })
+    .unwrap();
+  module
+    .register_async_method("evidence", |args, inspector| async move {
+      let (genesis,) = args.parse::<(String,)>()?;
+      Ok::<_, Error>(inspector.evidence(&genesis).await)
+    })
+    .unwrap();
+  module
+    .register_async_method("cosigns", |args, inspector| async move {
+      let (block_number,) = args.parse::<(u64,)>()?;
+      Ok::<_, Error>(inspector.cosigns(block_number))
+    })
+    .unwrap();
+  module
+    .register_async_method("latest_cosigns", |_, inspector| async move {
+      Ok::<_, Error>(inspector.latest_cosigns())
+    })
+    .unwrap();
+
+  let server = ServerBuilder::new().build(addr).await.expect("couldn't bind the inspection RPC");
+  server.start(module).unwrap().stopped().await;
+}
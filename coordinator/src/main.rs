@@ -2,33 +2,37 @@ use core::{ops::Deref, future::Future};
 use std::{
   sync::Arc,
   time::{SystemTime, Duration},
-  collections::{VecDeque, HashMap},
+  net::{IpAddr, Ipv4Addr, SocketAddr},
+  collections::{VecDeque, HashSet, HashMap},
 };
 
-use zeroize::{Zeroize, Zeroizing};
+use zeroize::Zeroizing;
 use rand_core::OsRng;
 
 use ciphersuite::{
-  group::ff::{Field, PrimeField},
+  group::{ff::Field, GroupEncoding},
   Ciphersuite, Ristretto,
 };
 use schnorr::SchnorrSignature;
 use frost::Participant;
 
-use serai_db::{DbTxn, Db};
-use serai_env as env;
+use serai_db::{DbTxn, Db, QuiescableDb};
+use serai_health::{Health, Heartbeat};
 
-use serai_client::{primitives::NetworkId, Public, Serai};
+use serai_client::{primitives::NetworkId, validator_sets::primitives::ValidatorSet, Public, Serai};
 
 use message_queue::{Service, client::MessageQueue};
 
-use futures::stream::StreamExt;
 use tokio::{
   sync::{RwLock, mpsc, broadcast},
-  time::sleep,
+  time::{sleep, timeout},
 };
 
-use ::tributary::{ReadWrite, ProvidedError, TransactionKind, TransactionTrait, Block, Tributary};
+use tracing::Instrument;
+
+use ::tributary::{
+  ReadWrite, ProvidedError, TransactionKind, TransactionTrait, Block, ConsensusParams, Tributary,
+};
 
 mod tributary;
 use crate::tributary::{
@@ -38,6 +42,18 @@ use crate::tributary::{
 mod db;
 use db::MainDb;
 
+mod config;
+pub use config::*;
+
+mod cli;
+mod backup;
+mod audit;
+mod migration;
+mod shutdown;
+use shutdown::{Stage, Tasks, Subtasks};
+
+mod inspect;
+
 mod p2p;
 pub use p2p::*;
 
@@ -48,6 +64,12 @@ use processors::Processors;
 
 mod substrate;
 
+// How many blocks received out of order to hold while syncing a tributary
+const MAX_PENDING_SYNCED_BLOCKS: usize = 1000;
+
+// How long to keep a retired tributary's data, so peers may still sync it, in seconds
+const TRIBUTARY_RETIREMENT_GRACE_PERIOD: u64 = 7 * 24 * 60 * 60;
+
 #[cfg(test)]
 pub mod tests;
 
@@ -57,22 +79,69 @@ pub struct ActiveTributary<D: Db, P: P2p> {
   pub tributary: Arc<Tributary<D, Transaction, P>>,
 }
 
-// Adds a tributary into the specified HashMap
+#[derive(Clone)]
+pub enum TributaryEvent<D: Db, P: P2p> {
+  NewTributary(ActiveTributary<D, P>),
+  TributaryRetired(ValidatorSet),
+}
+
+// The name of the health check for a Tributary advancing
+fn tributary_check(set: ValidatorSet) -> String {
+  format!("tributary-{:?}-{}", set.network, set.session.0)
+}
+
+// The span all work for a specific Tributary occurs within
+fn tributary_span(spec: &TributarySpec) -> tracing::Span {
+  let set = spec.set();
+  tracing::info_span!(
+    "tributary",
+    genesis = %hex::encode(spec.genesis()),
+    network = ?set.network,
+    session = set.session.0,
+  )
+}
+
+// Deletes a retired tributary's data
+// This must be called before the tributary is started, and may be called again if interrupted
+fn prune_tributary<D: Db, P: P2p>(db: &mut D, spec: &TributarySpec) {
+  tracing::info!("pruning retired tributary {:?}", spec.set());
+  let genesis = spec.genesis();
+
+  // The TributaryDb is pruned first, as it finds what to prune by walking the tributary's blocks
+  TributaryDb::<D>::prune(db, spec);
+  Tributary::<D, Transaction, P>::prune(db, genesis, &spec.validators());
+
+  // Only now, once everything else has been pruned, stop tracking this tributary
+  let mut txn = db.txn();
+  MainDb::<D>::remove_active_tributary(&mut txn, spec);
+  TributaryDb::<D>::pruned(&mut txn, genesis);
+  txn.commit();
+}
+
+// Adds a tributary, informing all listeners of it
 async fn add_tributary<D: Db, Pro: Processors, P: P2p>(
   db: D,
   key: Zeroizing<<Ristretto as Ciphersuite>::F>,
   processors: &Pro,
   p2p: P,
-  tributaries: &broadcast::Sender<ActiveTributary<D, P>>,
+  tributaries: &broadcast::Sender<TributaryEvent<D, P>>,
   spec: TributarySpec,
 ) {
-  log::info!("adding tributary {:?}", spec.set());
+  tracing::info!("adding tributary {:?}", spec.set());
+
+  p2p
+    .subscribe(
+      spec.genesis(),
+      spec.validators().into_iter().map(|(validator, _)| validator).collect(),
+    )
+    .await;
 
   let tributary = Tributary::<_, Transaction, _>::new(
     // TODO2: Use a db on a distinct volume to protect against DoS attacks
     db,
     spec.genesis(),
     spec.start_time(),
+    spec.consensus_params(),
     key.clone(),
     spec.validators(),
     p2p,
@@ -104,69 +173,103 @@ async fn add_tributary<D: Db, Pro: Processors, P: P2p>(
     .await;
 
   tributaries
-    .send(ActiveTributary { spec, tributary: Arc::new(tributary) })
+    .send(TributaryEvent::NewTributary(ActiveTributary { spec, tributary: Arc::new(tributary) }))
     .map_err(|_| "all ActiveTributary recipients closed")
     .unwrap();
 }
 
+// Runs the tributaries for each set we're in, spawning them as the sets appear and retiring them
+// once the sets complete their lifecycle
+// Every task handling tributaries is informed of these via a TributaryEvent
+pub(crate) struct Tributaries<D: Db, Pro: Processors, P: P2p> {
+  db: D,
+  key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+  processors: Pro,
+  p2p: P,
+  events: broadcast::Sender<TributaryEvent<D, P>>,
+  // Every set we've added a tributary for, and if it's since been retired
+  sets: HashMap<ValidatorSet, bool>,
+}
+
+impl<D: Db, Pro: Processors, P: P2p> Tributaries<D, Pro, P> {
+  pub(crate) fn new(
+    db: D,
+    key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+    processors: Pro,
+    p2p: P,
+    events: broadcast::Sender<TributaryEvent<D, P>>,
+  ) -> Self {
+    Tributaries { db, key, processors, p2p, events, sets: HashMap::new() }
+  }
+
+  // Add a tributary, if it hasn't already been added
+  pub(crate) async fn add(&mut self, spec: TributarySpec) {
+    if self.sets.contains_key(&spec.set()) {
+      return;
+    }
+    self.sets.insert(spec.set(), false);
+
+    add_tributary(
+      self.db.clone(),
+      self.key.clone(),
+      &self.processors,
+      self.p2p.clone(),
+      &self.events,
+      spec.clone(),
+    )
+    .instrument(tributary_span(&spec))
+    .await;
+  }
+
+  // Retire a tributary, stopping the tasks handling it
+  // Its P2P messages are still handled so peers may sync it until it's pruned
+  pub(crate) fn retire(&mut self, set: ValidatorSet) {
+    let Some(retired) = self.sets.get_mut(&set) else { return };
+    if *retired {
+      return;
+    }
+    *retired = true;
+
+    tracing::info!("stopping retired tributary {set:?}");
+    self
+      .events
+      .send(TributaryEvent::TributaryRetired(set))
+      .map_err(|_| "all ActiveTributary recipients closed")
+      .unwrap();
+  }
+}
+
 pub async fn scan_substrate<D: Db, Pro: Processors>(
   db: D,
   key: Zeroizing<<Ristretto as Ciphersuite>::F>,
   processors: Pro,
   serai: Arc<Serai>,
   new_tributary_spec: mpsc::UnboundedSender<TributarySpec>,
+  retired_tributary: mpsc::UnboundedSender<ValidatorSet>,
+  synced: Heartbeat,
 ) {
-  log::info!("scanning substrate");
+  tracing::info!("scanning substrate");
 
   let mut db = substrate::SubstrateDb::new(db);
-  let mut next_substrate_block = db.next_block();
-
-  let new_substrate_block_notifier = {
-    let serai = &serai;
-    move || async move {
-      loop {
-        match serai.newly_finalized_block().await {
-          Ok(sub) => return sub,
-          Err(e) => {
-            log::error!("couldn't communicate with serai node: {e}");
-            sleep(Duration::from_secs(5)).await;
-          }
-        }
-      }
-    }
-  };
-  let mut substrate_block_notifier = new_substrate_block_notifier().await;
+  let mut finality = substrate::Finality::new(serai.clone());
 
   loop {
-    // await the next block, yet if our notifier had an error, re-create it
-    {
-      let Ok(next_block) =
-        tokio::time::timeout(Duration::from_secs(60), substrate_block_notifier.next()).await
-      else {
-        // Timed out, which may be because Serai isn't finalizing or may be some issue with the
-        // notifier
-        if serai.get_latest_block().await.map(|block| block.number()).ok() ==
-          Some(next_substrate_block.saturating_sub(1))
-        {
-          log::info!("serai hasn't finalized a block in the last 60s...");
-        } else {
-          substrate_block_notifier = new_substrate_block_notifier().await;
-        }
-        continue;
-      };
-
-      // next_block is a Option<Result>
-      if next_block.and_then(Result::ok).is_none() {
-        substrate_block_notifier = new_substrate_block_notifier().await;
-        continue;
-      }
+    // Wait for a new finalized block, or for enough time to pass we should check for one
+    // This also returns when the node reconnects, letting us catch up on every block finalized
+    // while disconnected
+    let latest = finality.wait().await;
+    if latest < db.next_block() {
+      tracing::debug!("no new finalized blocks");
+      // We're still synced, Serai simply isn't producing blocks
+      synced.beat();
+      continue;
     }
 
     match substrate::handle_new_blocks(
       &mut db,
       &key,
       |db: &mut D, spec: TributarySpec| {
-        log::info!("creating new tributary for {:?}", spec.set());
+        tracing::info!("creating new tributary for {:?}", spec.set());
 
         // Save it to the database
         let mut txn = db.txn();
@@ -177,15 +280,18 @@ pub async fn scan_substrate<D: Db, Pro: Processors>(
         // handled on reboot
         new_tributary_spec.send(spec).unwrap();
       },
+      // If we reboot before this is read, the tributary is retired on reboot
+      |set: ValidatorSet| retired_tributary.send(set).unwrap(),
       &processors,
       &serai,
-      &mut next_substrate_block,
+      &finality,
+      latest,
     )
     .await
     {
-      Ok(()) => {}
+      Ok(()) => synced.beat(),
       Err(e) => {
-        log::error!("couldn't communicate with serai node: {e}");
+        tracing::error!("couldn't communicate with serai node: {e}");
         sleep(Duration::from_secs(5)).await;
       }
     }
@@ -213,15 +319,23 @@ pub(crate) async fn scan_tributaries<
   recognized_id: RID,
   processors: Pro,
   serai: Arc<Serai>,
-  mut new_tributary: broadcast::Receiver<ActiveTributary<D, P>>,
+  health: Health,
+  mut new_tributary: broadcast::Receiver<TributaryEvent<D, P>>,
 ) {
-  log::info!("scanning tributaries");
+  tracing::info!("scanning tributaries");
 
+  let mut scanners = HashMap::new();
+  let mut subtasks = Subtasks::new();
   loop {
     match new_tributary.recv().await {
-      Ok(ActiveTributary { spec, tributary }) => {
+      Ok(TributaryEvent::NewTributary(ActiveTributary { spec, tributary })) => {
+        let set = spec.set();
+        let span = tributary_span(&spec);
+        // If a Tributary hasn't had a block in this long, it isn't advancing
+        let stalled = 20 * tributary.block_time();
+        let advancing = health.heartbeat(tributary_check(set), stalled);
         // For each Tributary, spawn a dedicated scanner task
-        tokio::spawn({
+        let scanner = subtasks.spawn({
           let raw_db = raw_db.clone();
           let key = key.clone();
           let recognized_id = recognized_id.clone();
@@ -247,7 +361,7 @@ pub(crate) async fn scan_tributaries<
                     loop {
                       match serai.publish(&tx).await {
                         Ok(_) => {
-                          log::info!("set key pair for {set:?}");
+                          tracing::info!("set key pair for {set:?}");
                           break;
                         }
                         // This is assumed to be some ephemeral error due to the assumed fault-free
@@ -256,11 +370,11 @@ pub(crate) async fn scan_tributaries<
                         Err(e) => {
                           // Check if this failed because the keys were already set by someone else
                           if matches!(serai.get_keys(spec.set()).await, Ok(Some(_))) {
-                            log::info!("another coordinator set key pair for {:?}", set);
+                            tracing::info!("another coordinator set key pair for {:?}", set);
                             break;
                           }
 
-                          log::error!(
+                          tracing::error!(
                             "couldn't connect to Serai node to publish set_keys TX: {:?}",
                             e
                           );
@@ -274,6 +388,7 @@ pub(crate) async fn scan_tributaries<
                 &reader,
               )
               .await;
+              advancing.beat();
 
               next_block_notification
                 .await
@@ -281,7 +396,16 @@ pub(crate) async fn scan_tributaries<
                 .expect("tributary dropped its notifications?");
             }
           }
+          .instrument(span)
         });
+        scanners.insert(set, scanner);
+      }
+      // Since the scanner is safe to kill at any point, simply abort it
+      Ok(TributaryEvent::TributaryRetired(set)) => {
+        if let Some(scanner) = scanners.remove(&set) {
+          scanner.abort();
+        }
+        health.remove(&tributary_check(set));
       }
       Err(broadcast::error::RecvError::Lagged(_)) => {
         panic!("scan_tributaries lagged to handle new_tributary")
@@ -293,16 +417,25 @@ pub(crate) async fn scan_tributaries<
 
 pub async fn heartbeat_tributaries<D: Db, P: P2p>(
   p2p: P,
-  mut new_tributary: broadcast::Receiver<ActiveTributary<D, P>>,
+  mut new_tributary: broadcast::Receiver<TributaryEvent<D, P>>,
 ) {
+  // This uses the default block time, as the check is against a minute of inactivity regardless
   let ten_blocks_of_time =
-    Duration::from_secs((10 * Tributary::<D, Transaction, P>::block_time()).into());
+    Duration::from_millis((10 * ConsensusParams::default().block_time()).into());
 
   let mut readers = vec![];
+  let handle_event = |readers: &mut Vec<_>, event| match event {
+    TributaryEvent::NewTributary(ActiveTributary { spec, tributary }) => {
+      readers.push((spec.set(), tributary.reader(), tributary.block_time()))
+    }
+    TributaryEvent::TributaryRetired(set) => {
+      readers.retain(|(reader_set, _, _)| *reader_set != set)
+    }
+  };
   loop {
-    while let Ok(ActiveTributary { spec: _, tributary }) = {
+    while let Ok(event) = {
       match new_tributary.try_recv() {
-        Ok(tributary) => Ok(tributary),
+        Ok(event) => Ok(event),
         Err(broadcast::error::TryRecvError::Empty) => Err(()),
         Err(broadcast::error::TryRecvError::Lagged(_)) => {
           panic!("heartbeat_tributaries lagged to handle new_tributary")
@@ -310,56 +443,75 @@ pub async fn heartbeat_tributaries<D: Db, P: P2p>(
         Err(broadcast::error::TryRecvError::Closed) => panic!("new_tributary sender closed"),
       }
     } {
-      readers.push(tributary.reader());
+      handle_event(&mut readers, event);
     }
 
-    for tributary in &readers {
+    for (_, tributary, tributary_block_time) in &readers {
       let tip = tributary.tip();
       let block_time =
         SystemTime::UNIX_EPOCH + Duration::from_secs(tributary.time_of_block(&tip).unwrap_or(0));
 
       // Only trigger syncing if the block is more than a minute behind
       if SystemTime::now() > (block_time + Duration::from_secs(60)) {
-        log::warn!("last known tributary block was over a minute ago");
+        tracing::warn!("last known tributary block was over a minute ago");
         let mut msg = tip.to_vec();
         // Also include the timestamp so LibP2p doesn't flag this as an old message re-circulating
         let timestamp = SystemTime::now()
           .duration_since(SystemTime::UNIX_EPOCH)
           .expect("system clock is wrong")
-          .as_secs();
+          .as_millis();
         // Divide by the block time so if multiple parties send a Heartbeat, they're more likely to
         // overlap
-        let time_unit = timestamp / u64::from(Tributary::<D, Transaction, P>::block_time());
+        let time_unit = u64::try_from(timestamp / tributary_block_time.as_millis()).unwrap();
         msg.extend(time_unit.to_le_bytes());
         P2p::broadcast(&p2p, P2pMessageKind::Heartbeat(tributary.genesis()), msg).await;
       }
     }
 
-    // Only check once every 10 blocks of time
-    sleep(ten_blocks_of_time).await;
+    // Only check once every 10 blocks of time, unless a new tributary is added
+    // This immediately checks new tributaries, letting a validator which starts late begin
+    // syncing without delay
+    match timeout(ten_blocks_of_time, new_tributary.recv()).await {
+      Ok(Ok(event)) => handle_event(&mut readers, event),
+      Ok(Err(broadcast::error::RecvError::Lagged(_))) => {
+        panic!("heartbeat_tributaries lagged to handle new_tributary")
+      }
+      Ok(Err(broadcast::error::RecvError::Closed)) => panic!("new_tributary sender closed"),
+      Err(_) => {}
+    }
   }
 }
 
 pub async fn handle_p2p<D: Db, P: P2p>(
   our_key: <Ristretto as Ciphersuite>::G,
   p2p: P,
-  mut new_tributary: broadcast::Receiver<ActiveTributary<D, P>>,
+  mut new_tributary: broadcast::Receiver<TributaryEvent<D, P>>,
 ) {
   let channels = Arc::new(RwLock::new(HashMap::new()));
-  tokio::spawn({
+  let mut subtasks = Subtasks::new();
+  subtasks.spawn({
     let p2p = p2p.clone();
     let channels = channels.clone();
     async move {
+      let mut subtasks = Subtasks::new();
       loop {
-        let tributary = new_tributary.recv().await.unwrap();
+        // Retired tributaries continue to be handled, so peers may sync them until they're pruned
+        let TributaryEvent::NewTributary(tributary) = new_tributary.recv().await.unwrap() else {
+          continue;
+        };
         let genesis = tributary.spec.genesis();
 
         let (send, mut recv) = mpsc::unbounded_channel();
         channels.write().await.insert(genesis, send);
 
-        tokio::spawn({
+        subtasks.spawn({
           let p2p = p2p.clone();
           async move {
+            // Blocks received while syncing which don't build on our tip, by their parent
+            // Since blocks may be received out of order, these are held until their parent is
+            // synced
+            let mut pending = HashMap::new();
+            let mut subtasks = Subtasks::new();
             loop {
               let mut msg: Message<P> = recv.recv().await.unwrap();
               match msg.kind {
@@ -367,7 +519,7 @@ pub async fn handle_p2p<D: Db, P: P2p>(
 
                 P2pMessageKind::Tributary(msg_genesis) => {
                   assert_eq!(msg_genesis, genesis);
-                  log::trace!("handling message for tributary {:?}", tributary.spec.set());
+                  tracing::trace!("handling message for tributary {:?}", tributary.spec.set());
                   if tributary.tributary.handle_message(&msg.msg).await {
                     P2p::broadcast(&p2p, msg.kind, msg.msg).await;
                   }
@@ -379,7 +531,7 @@ pub async fn handle_p2p<D: Db, P: P2p>(
                 P2pMessageKind::Heartbeat(msg_genesis) => {
                   assert_eq!(msg_genesis, genesis);
                   if msg.msg.len() != 40 {
-                    log::error!("validator sent invalid heartbeat");
+                    tracing::error!("validator sent invalid heartbeat");
                     continue;
                   }
 
@@ -388,7 +540,7 @@ pub async fn handle_p2p<D: Db, P: P2p>(
                   let reader = tributary.tributary.reader();
                   // Spawn a dedicated task as this may require loading large amounts of data from
                   // disk and take a notable amount of time
-                  tokio::spawn(async move {
+                  subtasks.spawn(async move {
                     /*
                     // Have sqrt(n) nodes reply with the blocks
                     let mut responders = (tributary.spec.n() as f32).sqrt().floor() as u64;
@@ -420,11 +572,11 @@ pub async fn handle_p2p<D: Db, P: P2p>(
                       }
                     }
                     if !selected {
-                      log::debug!("received heartbeat and not selected to respond");
+                      tracing::debug!("received heartbeat and not selected to respond");
                       return;
                     }
 
-                    log::debug!("received heartbeat and selected to respond");
+                    tracing::debug!("received heartbeat and selected to respond");
 
                     let mut latest = msg.msg[.. 32].try_into().unwrap();
                     while let Some(next) = reader.block_after(&latest) {
@@ -442,15 +594,47 @@ pub async fn handle_p2p<D: Db, P: P2p>(
                   assert_eq!(msg_genesis, genesis);
                   let mut msg_ref: &[u8] = msg.msg.as_ref();
                   let Ok(block) = Block::<Transaction>::read(&mut msg_ref) else {
-                    log::error!("received block message with an invalidly serialized block");
+                    tracing::error!("received block message with an invalidly serialized block");
                     continue;
                   };
                   // Get just the commit
                   msg.msg.drain(.. (msg.msg.len() - msg_ref.len()));
                   msg.msg.drain((msg.msg.len() - 8) ..);
 
+                  let reader = tributary.tributary.reader();
+                  let parent = block.header.parent;
+                  if parent != tributary.tributary.tip().await {
+                    // If we don't have this block's parent, hold it until we do
+                    // If we do have its parent, this block is either already synced or for a
+                    // fork, which can't occur as the commit has to be valid
+                    if reader.block(&parent).is_none() &&
+                      (pending.len() < MAX_PENDING_SYNCED_BLOCKS)
+                    {
+                      tracing::debug!("received block from {:?} out of order", msg.sender);
+                      pending.insert(parent, (block, msg.msg));
+                    }
+                    continue;
+                  }
+
                   let res = tributary.tributary.sync_block(block, msg.msg).await;
-                  log::debug!("received block from {:?}, sync_block returned {}", msg.sender, res);
+                  tracing::debug!(
+                    "received block from {:?}, sync_block returned {}",
+                    msg.sender,
+                    res
+                  );
+
+                  // Sync any blocks we received prior which build on this block
+                  if res {
+                    while let Some((block, commit)) =
+                      pending.remove(&tributary.tributary.tip().await)
+                    {
+                      if !tributary.tributary.sync_block(block, commit).await {
+                        break;
+                      }
+                    }
+                    // Drop any held blocks whose parents have been synced, as they're stale
+                    pending.retain(|parent, _| reader.block(parent).is_none());
+                  }
                 }
               }
             }
@@ -488,7 +672,7 @@ async fn publish_signed_transaction<D: Db, P: P2p>(
   tributary: &Tributary<D, Transaction, P>,
   tx: Transaction,
 ) {
-  log::debug!("publishing transaction {}", hex::encode(tx.hash()));
+  tracing::debug!("publishing transaction {}", hex::encode(tx.hash()));
 
   let mut txn = db.txn();
   let signer = if let TransactionKind::Signed(signed) = tx.kind() {
@@ -527,30 +711,34 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
   serai: Arc<Serai>,
   mut processors: Pro,
   network: NetworkId,
-  mut new_tributary: mpsc::UnboundedReceiver<ActiveTributary<D, P>>,
+  mut new_tributary: mpsc::UnboundedReceiver<TributaryEvent<D, P>>,
 ) {
   let mut db_clone = db.clone(); // Enables cloning the DB while we have a txn
   let pub_key = Ristretto::generator() * key.deref();
 
   let mut tributaries = HashMap::new();
+  let mut retired = HashSet::new();
 
   loop {
     match new_tributary.try_recv() {
-      Ok(tributary) => {
+      Ok(TributaryEvent::NewTributary(tributary)) => {
         tributaries.insert(tributary.spec.set().session, tributary);
       }
+      Ok(TributaryEvent::TributaryRetired(set)) => {
+        tributaries.remove(&set.session);
+        retired.insert(set.session);
+      }
       Err(mpsc::error::TryRecvError::Empty) => {}
       Err(mpsc::error::TryRecvError::Disconnected) => {
         panic!("handle_processor_messages new_tributary sender closed")
       }
     }
 
-    // TODO: Check this ID is sane (last handled ID or expected next ID)
     let msg = processors.recv(network).await;
 
     // TODO: We need to verify the Batches published to Substrate
 
-    if !MainDb::<D>::handled_message(&db, msg.network, msg.id) {
+    if !MessageQueue::handled(&db, Service::Processor(msg.network), msg.id) {
       let mut txn = db.txn();
 
       let relevant_tributary = match &msg.msg {
@@ -558,6 +746,9 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
           key_gen::ProcessorMessage::Commitments { id, .. } => Some(id.set.session),
           key_gen::ProcessorMessage::Shares { id, .. } => Some(id.set.session),
           key_gen::ProcessorMessage::GeneratedKeyPair { id, .. } => Some(id.set.session),
+          key_gen::ProcessorMessage::InvalidCommitments { id, .. } => Some(id.set.session),
+          key_gen::ProcessorMessage::InvalidShare { id, .. } => Some(id.set.session),
+          key_gen::ProcessorMessage::Blame { id, .. } => Some(id.set.session),
         },
         // TODO: Review replacing key with Session in messages?
         ProcessorMessage::Sign(inner_msg) => match inner_msg {
@@ -587,8 +778,8 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
               TributaryDb::<D>::set_plan_ids(&mut txn, tributary.spec.genesis(), *block, plans);
 
               let tx = Transaction::SubstrateBlock(*block);
-              log::trace!("processor message effected transaction {}", hex::encode(tx.hash()));
-              log::trace!("providing transaction {}", hex::encode(tx.hash()));
+              tracing::trace!("processor message effected transaction {}", hex::encode(tx.hash()));
+              tracing::trace!("providing transaction {}", hex::encode(tx.hash()));
               let res = tributary.tributary.provide_transaction(tx).await;
               if !(res.is_ok() || (res == Err(ProvidedError::AlreadyProvided))) {
                 panic!("provided an invalid transaction: {res:?}");
@@ -603,6 +794,30 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
           coordinator::ProcessorMessage::BatchShare { id, .. } => {
             Some(substrate::SubstrateDb::<D>::session_for_key(&txn, &id.key).unwrap())
           }
+          coordinator::ProcessorMessage::CosignPreprocess { id, .. } => {
+            Some(substrate::SubstrateDb::<D>::session_for_key(&txn, &id.key).unwrap())
+          }
+          coordinator::ProcessorMessage::CosignShare { id, .. } => {
+            Some(substrate::SubstrateDb::<D>::session_for_key(&txn, &id.key).unwrap())
+          }
+          // Save the cosign, so it may be served to whoever wants to verify Serai's finality
+          coordinator::ProcessorMessage::CosignedBlock { key, block_number, block, signature } => {
+            let session = substrate::SubstrateDb::<D>::session_for_key(&txn, key).unwrap();
+            let set = ValidatorSet { session, network: msg.network };
+            let cosign = substrate::Cosign {
+              set,
+              block_number: *block_number,
+              block: *block,
+              signature: signature.clone().try_into().unwrap(),
+            };
+            let key_pair = TributaryDb::<D>::key_pair(&txn, set).unwrap();
+            assert!(cosign.verify(&key_pair), "processor produced an invalid cosign");
+
+            tracing::info!("cosigned block {block_number} ({}) with {set:?}", hex::encode(block));
+            substrate::SubstrateDb::<D>::save_cosign(&mut txn, &cosign);
+
+            None
+          }
         },
         ProcessorMessage::Substrate(inner_msg) => match inner_msg {
           // If this is a new Batch, immediately publish it and don't do any further processing
@@ -613,7 +828,7 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
             );
             // TODO: Check this key's key pair's substrate key is authorized to publish batches
 
-            log::debug!("received batch {:?} {}", batch.batch.network, batch.batch.id);
+            tracing::debug!("received batch {:?} {}", batch.batch.network, batch.batch.id);
 
             // Save this batch to the disk
             MainDb::<D>::save_batch(&mut txn, batch.clone());
@@ -623,7 +838,7 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
               let mut first = true;
               loop {
                 if !first {
-                  log::error!(
+                  tracing::error!(
                     "{} {network:?}",
                     "couldn't connect to Serai node to get the next batch ID for",
                   );
@@ -658,7 +873,7 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
               }
 
               let tx = Serai::execute_batch(batch.clone());
-              log::debug!(
+              tracing::debug!(
                 "attempting to publish batch {:?} {}",
                 batch.batch.network,
                 batch.batch.id,
@@ -669,13 +884,13 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
               // this batch should execute
               let res = serai.publish(&tx).await;
               if res.is_ok() {
-                log::info!(
+                tracing::info!(
                   "published batch {network:?} {} (block {})",
                   batch.batch.id,
                   hex::encode(batch.batch.block),
                 );
               } else {
-                log::debug!(
+                tracing::debug!(
                   "couldn't publish batch {:?} {}: {:?}",
                   batch.batch.network,
                   batch.batch.id,
@@ -691,6 +906,15 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
         },
       };
 
+      // Messages for retired tributaries no longer have any effect
+      let relevant_tributary = relevant_tributary.filter(|session| {
+        let is_retired = retired.contains(session);
+        if is_retired {
+          tracing::warn!("dropping processor message for retired tributary {session:?}");
+        }
+        !is_retired
+      });
+
       // If there's a relevant Tributary...
       if let Some(relevant_tributary) = relevant_tributary {
         // Make sure we have it
@@ -763,6 +987,34 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
                 }
               }
             }
+            // Since the commitments are public, every validator will independently find them
+            // invalid and vote for the participant to be fatally slashed
+            // This is published instead of our DkgShares, using its nonce
+            key_gen::ProcessorMessage::InvalidCommitments { id, faulty } => {
+              tracing::warn!("participant {faulty:?} sent invalid DKG commitments for {id:?}");
+              Some(Transaction::InvalidDkgCommitments {
+                attempt: id.attempt,
+                faulty,
+                signed: Transaction::empty_signed(),
+              })
+            }
+            key_gen::ProcessorMessage::InvalidShare { id, faulty, blame } => {
+              Some(Transaction::InvalidDkgShare {
+                attempt: id.attempt,
+                faulty,
+                blame,
+                signed: Transaction::empty_signed(),
+              })
+            }
+            key_gen::ProcessorMessage::Blame { id, accuser, participant } => {
+              tracing::warn!("participant {participant:?} was found faulty in DKG {id:?}");
+              Some(Transaction::DkgBlame {
+                attempt: id.attempt,
+                accuser,
+                faulty: participant,
+                signed: Transaction::empty_signed(),
+              })
+            }
           },
           ProcessorMessage::Sign(msg) => match msg {
             sign::ProcessorMessage::Preprocess { id, preprocess } => {
@@ -808,7 +1060,7 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
           ProcessorMessage::Coordinator(inner_msg) => match inner_msg {
             coordinator::ProcessorMessage::SubstrateBlockAck { .. } => unreachable!(),
             coordinator::ProcessorMessage::BatchPreprocess { id, block, preprocess } => {
-              log::info!(
+              tracing::info!(
                 "informed of batch (sign ID {}, attempt {}) for block {}",
                 hex::encode(id.id),
                 id.attempt,
@@ -837,6 +1089,30 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
                 signed: Transaction::empty_signed(),
               }))
             }
+            coordinator::ProcessorMessage::CosignPreprocess { id, preprocess } => {
+              // As with batches, the first attempt waits until we synchronize around the block
+              if id.attempt == 0 {
+                MainDb::<D>::save_first_preprocess(&mut txn, spec.set().network, id.id, preprocess);
+
+                Some(Transaction::CosignSubstrateBlock(id.id))
+              } else {
+                Some(Transaction::CosignPreprocess(SignData {
+                  plan: id.id,
+                  attempt: id.attempt,
+                  data: preprocess,
+                  signed: Transaction::empty_signed(),
+                }))
+              }
+            }
+            coordinator::ProcessorMessage::CosignShare { id, share } => {
+              Some(Transaction::CosignShare(SignData {
+                plan: id.id,
+                attempt: id.attempt,
+                data: share.to_vec(),
+                signed: Transaction::empty_signed(),
+              }))
+            }
+            coordinator::ProcessorMessage::CosignedBlock { .. } => unreachable!(),
           },
           ProcessorMessage::Substrate(inner_msg) => match inner_msg {
             processor_messages::substrate::ProcessorMessage::Update { .. } => unreachable!(),
@@ -845,25 +1121,27 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
 
         // If this created a transaction, publish it
         if let Some(mut tx) = tx {
-          log::trace!("processor message effected transaction {}", hex::encode(tx.hash()));
+          tracing::trace!("processor message effected transaction {}", hex::encode(tx.hash()));
 
           match tx.kind() {
             TransactionKind::Provided(_) => {
-              log::trace!("providing transaction {}", hex::encode(tx.hash()));
+              tracing::trace!("providing transaction {}", hex::encode(tx.hash()));
               let res = tributary.provide_transaction(tx).await;
               if !(res.is_ok() || (res == Err(ProvidedError::AlreadyProvided))) {
                 panic!("provided an invalid transaction: {res:?}");
               }
             }
             TransactionKind::Unsigned => {
-              log::trace!("publishing unsigned transaction {}", hex::encode(tx.hash()));
+              tracing::trace!("publishing unsigned transaction {}", hex::encode(tx.hash()));
               // Ignores the result since we can't differentiate already in-mempool from
               // already on-chain from invalid
               // TODO: Don't ignore the result
               tributary.add_transaction(tx).await;
             }
             TransactionKind::Signed(_) => {
-              log::trace!("getting next nonce for Tributary TX in response to processor message");
+              tracing::trace!(
+                "getting next nonce for Tributary TX in response to processor message"
+              );
 
               let nonce = loop {
                 let Some(nonce) = NonceDecider::<D>::nonce(&txn, genesis, &tx)
@@ -873,7 +1151,7 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
                   // 1) We scanned the relevant transaction(s) in a Tributary block
                   // 2) The processor was sent a message and responded
                   // 3) The Tributary TXN has yet to be committed
-                  log::warn!("nonce has yet to be saved for processor-instigated transaction");
+                  tracing::warn!("nonce has yet to be saved for processor-instigated transaction");
                   sleep(Duration::from_millis(100)).await;
                   continue;
                 };
@@ -887,7 +1165,7 @@ async fn handle_processor_messages<D: Db, Pro: Processors, P: P2p>(
         }
       }
 
-      MainDb::<D>::save_handled_message(&mut txn, msg.network, msg.id);
+      MessageQueue::handle(&mut txn, Service::Processor(msg.network), msg.id);
       txn.commit();
     }
 
@@ -900,52 +1178,91 @@ pub async fn handle_processors<D: Db, Pro: Processors, P: P2p>(
   key: Zeroizing<<Ristretto as Ciphersuite>::F>,
   serai: Arc<Serai>,
   processors: Pro,
-  mut new_tributary: broadcast::Receiver<ActiveTributary<D, P>>,
+  tasks: &Tasks,
+  mut new_tributary: broadcast::Receiver<TributaryEvent<D, P>>,
 ) {
   let mut channels = HashMap::new();
   for network in [NetworkId::Bitcoin, NetworkId::Ethereum, NetworkId::Monero] {
     let (send, recv) = mpsc::unbounded_channel();
-    tokio::spawn(handle_processor_messages(
-      db.clone(),
-      key.clone(),
-      serai.clone(),
-      processors.clone(),
-      network,
-      recv,
-    ));
+    tasks.spawn(
+      Stage::Processors,
+      "processor messages",
+      handle_processor_messages(
+        db.clone(),
+        key.clone(),
+        serai.clone(),
+        processors.clone(),
+        network,
+        recv,
+      )
+      .instrument(tracing::info_span!("processor", ?network)),
+    );
     channels.insert(network, send);
   }
 
   // Listen to new tributary events
   loop {
-    let tributary = new_tributary.recv().await.unwrap();
-    channels[&tributary.spec.set().network].send(tributary).unwrap();
+    let event = new_tributary.recv().await.unwrap();
+    let network = match &event {
+      TributaryEvent::NewTributary(tributary) => tributary.spec.set().network,
+      TributaryEvent::TributaryRetired(set) => set.network,
+    };
+    channels[&network].send(event).unwrap();
   }
 }
 
 pub async fn run<D: Db, Pro: Processors, P: P2p>(
-  raw_db: D,
+  mut raw_db: D,
   key: Zeroizing<<Ristretto as Ciphersuite>::F>,
   p2p: P,
   processors: Pro,
   serai: Serai,
+  health: Health,
+  inspect: Option<SocketAddr>,
+  tasks: Tasks,
 ) {
   let serai = Arc::new(serai);
 
   let (new_tributary_spec_send, mut new_tributary_spec_recv) = mpsc::unbounded_channel();
-  // Reload active tributaries from the database
+  let (retired_tributary_send, mut retired_tributary_recv) = mpsc::unbounded_channel();
+  // Reload active tributaries from the database, pruning those retired for the grace period
+  let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
   for spec in MainDb::<D>::active_tributaries(&raw_db).1 {
+    let retired_at = TributaryDb::<D>::retired_at(&raw_db, spec.genesis());
+    if let Some(retired_at) = retired_at {
+      if now >= retired_at.saturating_add(TRIBUTARY_RETIREMENT_GRACE_PERIOD) {
+        prune_tributary::<D, P>(&mut raw_db, &spec);
+        continue;
+      }
+    }
+    // Tributaries within their grace period are still added, so peers may sync them, yet are
+    // immediately retired
+    let set = spec.set();
     new_tributary_spec_send.send(spec).unwrap();
+    if retired_at.is_some() {
+      retired_tributary_send.send(set).unwrap();
+    }
   }
 
-  // Handle new Substrate blocks
-  tokio::spawn(scan_substrate(
-    raw_db.clone(),
-    key.clone(),
-    processors.clone(),
-    serai.clone(),
-    new_tributary_spec_send,
-  ));
+  // Handle new Substrate blocks, restarting the scanner if it panics as it resumes from the DB
+  tasks.supervise(Stage::Substrate, "substrate scanner", {
+    let raw_db = raw_db.clone();
+    let key = key.clone();
+    let processors = processors.clone();
+    let serai = serai.clone();
+    let heartbeat = health.heartbeat("substrate", Duration::from_secs(180));
+    move || {
+      scan_substrate(
+        raw_db.clone(),
+        key.clone(),
+        processors.clone(),
+        serai.clone(),
+        new_tributary_spec_send.clone(),
+        retired_tributary_send.clone(),
+        heartbeat.clone(),
+      )
+    }
+  });
 
   // Handle the Tributaries
 
@@ -957,24 +1274,27 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
   let new_tributary_listener_4 = new_tributary.subscribe();
   let new_tributary_listener_5 = new_tributary.subscribe();
 
-  // Spawn a task to further add Tributaries as needed
-  tokio::spawn({
-    let raw_db = raw_db.clone();
-    let key = key.clone();
-    let processors = processors.clone();
-    let p2p = p2p.clone();
+  // Serve the inspection RPC, if configured to
+  if let Some(inspect) = inspect {
+    tasks.spawn(
+      Stage::P2p,
+      "inspect",
+      inspect::serve(inspect, raw_db.clone(), new_tributary.subscribe()),
+    );
+  }
+
+  // Spawn a task to further add and retire Tributaries as needed
+  tasks.spawn(Stage::Tributary, "tributaries", {
+    let mut tributaries =
+      Tributaries::new(raw_db.clone(), key.clone(), processors.clone(), p2p.clone(), new_tributary);
     async move {
       loop {
-        let spec = new_tributary_spec_recv.recv().await.unwrap();
-        add_tributary(
-          raw_db.clone(),
-          key.clone(),
-          &processors,
-          p2p.clone(),
-          &new_tributary,
-          spec.clone(),
-        )
-        .await;
+        tokio::select! {
+          // A set is only retired after it's been added, so handle additions first
+          biased;
+          spec = new_tributary_spec_recv.recv() => tributaries.add(spec.unwrap()).await,
+          set = retired_tributary_recv.recv() => tributaries.retire(set.unwrap()),
+        }
       }
     }
   });
@@ -985,13 +1305,16 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
     let key = key.clone();
 
     let tributaries = Arc::new(RwLock::new(HashMap::new()));
-    tokio::spawn({
+    tasks.spawn(Stage::Tributary, "recognized id", {
       let tributaries = tributaries.clone();
       async move {
         loop {
           match new_tributary_listener_1.recv().await {
-            Ok(tributary) => {
-              tributaries.write().await.insert(tributary.spec.genesis(), tributary.tributary);
+            Ok(TributaryEvent::NewTributary(tributary)) => {
+              tributaries.write().await.insert(tributary.spec.genesis(), tributary);
+            }
+            Ok(TributaryEvent::TributaryRetired(set)) => {
+              tributaries.write().await.retain(|_, tributary| tributary.spec.set() != set);
             }
             Err(broadcast::error::RecvError::Lagged(_)) => {
               panic!("recognized_id lagged to handle new_tributary")
@@ -1035,6 +1358,19 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
             data: get_preprocess(&raw_db, id).await,
             signed: Transaction::empty_signed(),
           }),
+
+          RecognizedIdType::Cosign => Transaction::CosignPreprocess(SignData {
+            plan: id,
+            attempt: 0,
+            data: get_preprocess(&raw_db, id).await,
+            signed: Transaction::empty_signed(),
+          }),
+
+          // Heartbeats have no data to wait on, with the ID solely encoding the epoch
+          RecognizedIdType::Heartbeat => Transaction::Heartbeat(
+            u32::from_le_bytes(id[.. 4].try_into().unwrap()),
+            Transaction::empty_signed(),
+          ),
         };
 
         tx.sign(&mut OsRng, genesis, &key, nonce);
@@ -1044,7 +1380,7 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
           // TODO: This may happen if the task above is simply slow
           panic!("tributary we don't have came to consensus on an Batch");
         };
-        publish_signed_transaction(&mut raw_db, tributary, tx).await;
+        publish_signed_transaction(&mut raw_db, &tributary.tributary, tx).await;
       }
     }
   };
@@ -1052,84 +1388,144 @@ pub async fn run<D: Db, Pro: Processors, P: P2p>(
   // Handle new blocks for each Tributary
   {
     let raw_db = raw_db.clone();
-    tokio::spawn(scan_tributaries(
-      raw_db,
-      key.clone(),
-      recognized_id,
-      processors.clone(),
-      serai.clone(),
-      new_tributary_listener_2,
-    ));
+    tasks.spawn(
+      Stage::Tributary,
+      "tributary scanner",
+      scan_tributaries(
+        raw_db,
+        key.clone(),
+        recognized_id,
+        processors.clone(),
+        serai.clone(),
+        health,
+        new_tributary_listener_2,
+      ),
+    );
   }
 
   // Spawn the heartbeat task, which will trigger syncing if there hasn't been a Tributary block
   // in a while (presumably because we're behind)
-  tokio::spawn(heartbeat_tributaries(p2p.clone(), new_tributary_listener_3));
+  tasks.spawn(
+    Stage::P2p,
+    "tributary heartbeat",
+    heartbeat_tributaries(p2p.clone(), new_tributary_listener_3),
+  );
 
   // Handle P2P messages
-  tokio::spawn(handle_p2p(Ristretto::generator() * key.deref(), p2p, new_tributary_listener_4));
+  tasks.spawn(
+    Stage::P2p,
+    "p2p",
+    handle_p2p(Ristretto::generator() * key.deref(), p2p, new_tributary_listener_4),
+  );
 
   // Handle all messages from processors
-  handle_processors(raw_db, key, serai, processors, new_tributary_listener_5).await;
+  handle_processors(raw_db, key, serai, processors, &tasks, new_tributary_listener_5).await;
 }
 
 #[tokio::main]
 async fn main() {
-  // Override the panic handler with one which will panic if any tokio task panics
+  // Override the panic handler with one which will exit if any tokio task panics, unless it's
+  // supervised and will be restarted
   {
     let existing = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic| {
       existing(panic);
+      if shutdown::supervised() {
+        return;
+      }
       const MSG: &str = "exiting the process due to a task panicking";
       println!("{MSG}");
-      log::error!("{MSG}");
+      tracing::error!("{MSG}");
       std::process::exit(1);
     }));
   }
 
+  // Parse the command line first, as flags override every other source of configuration
+  let args = cli::parse();
+
   if std::env::var("RUST_LOG").is_err() {
     std::env::set_var("RUST_LOG", serai_env::var("RUST_LOG").unwrap_or_else(|| "info".to_string()));
   }
-  env_logger::init();
+  // LOG_FORMAT selects between the default, compact, and JSON outputs, the latter allowing log
+  // aggregators to index events by their span fields
+  {
+    let subscriber =
+      tracing_subscriber::fmt().with_env_filter(tracing_subscriber::EnvFilter::from_default_env());
+    match serai_env::var("LOG_FORMAT").as_deref() {
+      None | Some("full") => subscriber.init(),
+      Some("compact") => subscriber.compact().init(),
+      Some("json") => subscriber.json().init(),
+      Some(format) => panic!("unrecognized LOG_FORMAT {format}"),
+    }
+  }
 
-  log::info!("starting coordinator service...");
+  // Handle any commands, such as those used to move the DB to another machine
+  if cli::run_command(&args).await {
+    return;
+  }
 
-  let db = serai_db::new_rocksdb(&env::var("DB_PATH").expect("path to DB wasn't specified"));
+  tracing::info!("starting coordinator service...");
 
-  let key = {
-    let mut key_hex = serai_env::var("SERAI_KEY").expect("Serai key wasn't provided");
-    let mut key_vec = hex::decode(&key_hex).map_err(|_| ()).expect("Serai key wasn't hex-encoded");
-    key_hex.zeroize();
-    if key_vec.len() != 32 {
-      key_vec.zeroize();
-      panic!("Serai key had an invalid length");
-    }
-    let mut key_bytes = [0; 32];
-    key_bytes.copy_from_slice(&key_vec);
-    key_vec.zeroize();
-    let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::from_repr(key_bytes).unwrap());
-    key_bytes.zeroize();
-    key
-  };
-  let p2p = LibP2p::new();
+  let config = Config::load();
+
+  // Allow pausing writes, so a snapshot can be taken while running
+  let mut db = QuiescableDb::new(serai_db::new_rocksdb(&config.db_path));
+  // Move any entries saved under prior key layouts before anything reads them
+  TributaryDb::migrate_legacy_keys(&mut db);
+  MainDb::migrate_legacy_handled_messages(&mut db);
+
+  // Stop every task and flush the DB on SIGTERM/SIGINT, so restarts don't interrupt any writes
+  let tasks = Tasks::new();
+  tokio::spawn(shutdown::handle_shutdown_signal(db.clone(), tasks.clone()));
+
+  let health = Health::new();
+  tasks.supervise(Stage::P2p, "health", {
+    let health = health.clone();
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), config.health_port);
+    move || health.clone().serve(addr)
+  });
+
+  let p2p = LibP2p::new(&config.p2p, &config.key, &tasks);
+  health.check("listening", {
+    let p2p = p2p.clone();
+    move || p2p.listening()
+  });
 
   let processors = Arc::new(MessageQueue::from_env(Service::Coordinator));
+  // The message-queue is polled every second, so it should always have been recently contacted
+  health.check("processors", {
+    let processors = processors.clone();
+    move || {
+      processors
+        .last_contact()
+        .is_some_and(|last_contact| last_contact.elapsed() < Duration::from_secs(60))
+    }
+  });
 
   let serai = || async {
+    let urls = config.serai_endpoints.iter().map(String::as_str).collect::<Vec<_>>();
     loop {
-      let Ok(serai) = Serai::new(&format!(
-        "ws://{}:9944",
-        serai_env::var("SERAI_HOSTNAME").expect("Serai hostname wasn't provided")
-      ))
-      .await
-      else {
-        log::error!("couldn't connect to the Serai node");
+      let Ok(serai) = Serai::new_with_endpoints(&urls).await else {
+        tracing::error!("couldn't connect to the Serai node");
         sleep(Duration::from_secs(5)).await;
         continue;
       };
-      log::info!("made initial connection to Serai node");
+      tracing::info!("made initial connection to Serai node");
       return serai;
     }
   };
-  run(db, key, p2p, processors, serai().await).await
+  let serai = serai().await;
+
+  // If this DB was migrated from another machine, verify it before resuming
+  migration::verify(&mut db, &config.key, &serai).await;
+  tokio::spawn(migration::handle_migration_signal(
+    db.clone(),
+    config.key.clone(),
+    Arc::new(serai.clone()),
+    config.migration_path,
+  ));
+
+  let inspect =
+    config.inspect_port.map(|port| SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port));
+  run(db, config.key, p2p, processors, serai, health, inspect, tasks).await
 }
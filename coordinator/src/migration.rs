@@ -0,0 +1,209 @@
+use core::ops::Deref;
+use std::{
+  sync::Arc,
+  time::Duration,
+  fs::File,
+  io::{Write, BufWriter},
+};
+
+use scale::{Encode, Decode};
+
+use zeroize::Zeroizing;
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+
+use serai_db::{create_db, Get, DbTxn, Db, IterableDb, QuiescableDb, export_snapshot};
+
+use serai_client::Serai;
+
+use tokio::signal::unix::{SignalKind, signal};
+
+use crate::substrate::SubstrateDb;
+
+// How long to wait for in-progress transactions to be committed before abandoning a migration
+const QUIESCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Migration {
+  // Our key, so the restored coordinator is confirmed to be the same validator
+  pub key: [u8; 32],
+  // The next Substrate block to handle, and the hash of the block prior, if there is one
+  pub next_block: u64,
+  pub prior_block: Option<[u8; 32]>,
+}
+
+create_db!(
+  MigrationDb {
+    // Set when the DB is exported for a migration
+    ExportedDb: () -> Migration,
+    // Set when a DB exported for a migration is imported, until it's verified
+    ImportedDb: () -> ()
+  }
+);
+
+fn public_key(key: &Zeroizing<<Ristretto as Ciphersuite>::F>) -> [u8; 32] {
+  (<Ristretto as Ciphersuite>::generator() * key.deref()).to_bytes()
+}
+
+// Get the hash of the finalized block before the specified block, returning None if it isn't
+// available
+async fn prior_block_hash(serai: &Serai, next_block: u64) -> Option<Option<[u8; 32]>> {
+  let Some(prior) = next_block.checked_sub(1) else { return Some(None) };
+  if serai.get_latest_block().await.ok()?.number() < prior {
+    None?;
+  }
+  serai.get_block_by_number(prior).await.ok()?.map(|block| Some(block.hash()))
+}
+
+fn export(
+  db: &QuiescableDb<serai_db::RocksDB>,
+  key: [u8; 32],
+  expected_next_block: u64,
+  prior_block: Option<[u8; 32]>,
+  path: &str,
+) -> Result<u64, String> {
+  let mut quiesced = db
+    .quiesce(QUIESCE_TIMEOUT)
+    .ok_or_else(|| "in-progress transactions didn't complete in time".to_string())?;
+
+  // The Substrate scanner may have handled another block since the prior block's hash was fetched
+  // Besides that, any point is fine to resume from as every Substrate and Tributary event is
+  // individually tracked as handled, within the transaction handling it
+  let next_block = SubstrateDb::new(quiesced.db().clone()).next_block();
+  if next_block != expected_next_block {
+    Err("handled another Substrate block while preparing the migration, try again".to_string())?;
+  }
+
+  // Mark this DB as exported, so it can never be run again and the restored DB can be verified
+  let mut txn = quiesced.db().txn();
+  ExportedDb::set(&mut txn, &Migration { key, next_block, prior_block });
+  txn.commit();
+
+  let file = File::options()
+    .write(true)
+    .create_new(true)
+    .open(path)
+    .map_err(|e| format!("couldn't create snapshot file: {e}"));
+  // If the file couldn't be created, there's nothing to remove, and what's present isn't ours
+  let created = file.is_ok();
+  let res = file.and_then(|file| {
+    let mut writer = BufWriter::new(file);
+    let entries = export_snapshot(quiesced.db(), &mut writer)
+      .map_err(|e| format!("couldn't export snapshot: {e}"))?;
+    writer
+      .into_inner()
+      .map_err(|e| e.into_error())
+      .and_then(|file| file.sync_all())
+      .map_err(|e| format!("couldn't flush snapshot: {e}"))?;
+    Ok(entries)
+  });
+
+  if res.is_err() {
+    // Resume as normal, without the mark, as nothing was exported
+    let mut txn = quiesced.db().txn();
+    ExportedDb::del(&mut txn);
+    txn.commit();
+    if created {
+      let _ = std::fs::remove_file(path);
+    }
+  } else {
+    // Don't resume, so nothing is written after the snapshot, and exit
+    std::mem::forget(quiesced);
+  }
+  res
+}
+
+/// Export a snapshot for migrating this coordinator when SIGUSR1 is received, then exit.
+///
+/// Writes are paused while the snapshot is taken and never resumed, so the exported DB has
+/// exactly the state of the snapshot and may never be run again.
+pub async fn handle_migration_signal(
+  db: QuiescableDb<serai_db::RocksDB>,
+  key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+  serai: Arc<Serai>,
+  path: String,
+) {
+  let key = public_key(&key);
+  let mut signals =
+    signal(SignalKind::user_defined1()).expect("couldn't listen for the migration signal");
+  loop {
+    signals.recv().await;
+    tracing::info!("received migration signal, exporting snapshot to {path}");
+
+    // Fetch this before quiescing, as the runtime may stall once writes are paused
+    let next_block = SubstrateDb::new(db.clone()).next_block();
+    let Some(prior_block) = prior_block_hash(&serai, next_block).await else {
+      tracing::error!("couldn't get the latest handled Substrate block, not migrating");
+      continue;
+    };
+
+    // This exits from the blocking thread as the runtime may not make progress once quiesced
+    let e = tokio::task::spawn_blocking({
+      let db = db.clone();
+      let path = path.clone();
+      move || match export(&db, key, next_block, prior_block, &path) {
+        Ok(entries) => {
+          tracing::info!("exported {entries} entries to {path} for migration, exiting");
+          let _ = std::io::stdout().flush();
+          std::process::exit(0);
+        }
+        Err(e) => e,
+      }
+    })
+    .await
+    .unwrap();
+    tracing::error!("couldn't export snapshot for migration: {e}");
+  }
+}
+
+/// Mark the DB as imported, if it was exported for a migration.
+pub fn mark_imported<D: Db>(db: &mut D) {
+  if ExportedDb::get(&*db).is_some() {
+    let mut txn = db.txn();
+    ImportedDb::set(&mut txn, &());
+    txn.commit();
+  }
+}
+
+/// Check the DB wasn't exported for a migration, and verify it against Serai if it was imported
+/// from a migration.
+///
+/// Panics if the DB must not be run.
+pub async fn verify<D: Db>(
+  db: &mut D,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  serai: &Serai,
+) {
+  let Some(migration) = ExportedDb::get(&*db) else { return };
+  if ImportedDb::get(&*db).is_none() {
+    panic!(
+      "this DB was exported for a migration and must never be run again, as running both it and \
+        the migrated coordinator would have them sign conflicting messages"
+    );
+  }
+
+  if migration.key != public_key(key) {
+    panic!("migrated DB was for a different key than the one configured");
+  }
+
+  // Ensure the Serai node is on the same chain, and has reached the block the snapshot was taken
+  // at, so we don't resume prior to where we were
+  loop {
+    match prior_block_hash(serai, migration.next_block).await {
+      Some(prior_block) if prior_block == migration.prior_block => break,
+      Some(_) => panic!("migrated DB was from a different chain than the Serai node's"),
+      None => {
+        tracing::warn!(
+          "Serai node doesn't have block {} from the migrated DB, waiting for it to sync",
+          migration.next_block.saturating_sub(1),
+        );
+        tokio::time::sleep(Duration::from_secs(5)).await;
+      }
+    }
+  }
+
+  let mut txn = db.txn();
+  ExportedDb::del(&mut txn);
+  ImportedDb::del(&mut txn);
+  txn.commit();
+  tracing::info!("verified migrated DB, resuming");
+}
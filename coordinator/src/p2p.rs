@@ -1,30 +1,151 @@
-use core::{time::Duration, fmt};
-use std::{sync::Arc, time::Instant, io::Read};
+use core::{ops::Deref, time::Duration, fmt};
+use std::{
+  sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+  },
+  time::Instant,
+  io::Read,
+  collections::{HashSet, HashMap},
+};
 
 use async_trait::async_trait;
 
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use transcript::{Transcript, RecommendedTranscript};
+use ciphersuite::{
+  group::{ff::Field, GroupEncoding},
+  Ciphersuite, Ristretto,
+};
+use schnorr::SchnorrSignature;
+
 use tokio::sync::{mpsc, Mutex};
 
 use libp2p::{
   futures::StreamExt,
   identity::Keypair,
-  PeerId, Transport,
+  PeerId, Multiaddr, Transport,
   core::upgrade,
+  multiaddr::Protocol,
   tcp::{Config, tokio as libp2p_tokio},
-  noise, yamux,
+  noise, yamux, identify,
+  core::ConnectedPoint,
   gossipsub::{
-    IdentTopic, FastMessageId, MessageId, MessageAuthenticity, ValidationMode, ConfigBuilder,
-    IdentityTransform, AllowAllSubscriptionFilter, Event as GsEvent, PublishError,
-    Behaviour as GsBehavior,
+    IdentTopic, TopicHash, FastMessageId, MessageId, MessageAuthenticity, MessageAcceptance,
+    ValidationMode, ConfigBuilder, IdentityTransform, AllowAllSubscriptionFilter, Event as GsEvent,
+    PublishError, Behaviour as GsBehavior,
+  },
+  swarm::{
+    NetworkBehaviour, SwarmBuilder, SwarmEvent, Swarm,
+    dial_opts::{DialOpts, PeerCondition},
   },
-  swarm::{NetworkBehaviour, SwarmBuilder, SwarmEvent, Swarm},
 };
 
 pub use tributary::P2p as TributaryP2p;
 
-// TODO: Use distinct topics
+use crate::{
+  P2pConfig,
+  shutdown::{Stage, Tasks},
+};
+
+// The topic all coordinators subscribe to, with each tributary having its own topic
 const LIBP2P_TOPIC: &str = "serai-coordinator";
 
+// How often to try reconnecting to peers we've lost our connections to
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(30);
+// How many times to try reconnecting to a peer before forgetting it
+const MAX_RECONNECT_ATTEMPTS: u8 = 10;
+// How long a peer has to prove it's a validator before it's disconnected
+const AUTHENTICATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn topic(genesis: Option<[u8; 32]>) -> IdentTopic {
+  match genesis {
+    Some(genesis) => IdentTopic::new(format!("{LIBP2P_TOPIC}-{}", hex::encode(genesis))),
+    None => IdentTopic::new(LIBP2P_TOPIC),
+  }
+}
+
+/// The version of the P2P protocol, advertised to peers when connecting.
+///
+/// This must be incremented whenever the encoding of a P2P message changes.
+///
+/// Version 2 requires peers to prove they're validators.
+pub const P2P_PROTOCOL_VERSION: u16 = 2;
+/// The oldest version of the P2P protocol peers may use.
+///
+/// Peers advertising an older version are disconnected. Peers advertising a newer version are
+/// expected to still speak this version, as they should only increment their minimum once the
+/// network has upgraded.
+///
+/// Version 1 peers don't prove they're validators, so they aren't accepted.
+pub const MIN_P2P_PROTOCOL_VERSION: u16 = 2;
+
+const PROTOCOL_VERSION_PREFIX: &str = "/serai/coordinator/";
+
+pub(crate) fn protocol_version() -> String {
+  format!("{PROTOCOL_VERSION_PREFIX}{P2P_PROTOCOL_VERSION}")
+}
+
+pub(crate) fn peer_protocol_version(protocol_version: &str) -> Option<u16> {
+  protocol_version.strip_prefix(PROTOCOL_VERSION_PREFIX)?.parse().ok()
+}
+
+const IDENTITY_PREFIX: &str = "serai-coordinator/";
+
+fn identity_challenge(
+  peer_id: &PeerId,
+  validator: <Ristretto as Ciphersuite>::G,
+  nonce: <Ristretto as Ciphersuite>::G,
+) -> <Ristretto as Ciphersuite>::F {
+  let mut transcript = RecommendedTranscript::new(b"Coordinator P2P Identity");
+  transcript.append_message(b"peer_id", peer_id.to_bytes());
+  transcript.append_message(b"validator", validator.to_bytes());
+  transcript.append_message(b"nonce", nonce.to_bytes());
+  Ristretto::hash_to_F(b"P2P identity signature", &transcript.challenge(b"challenge"))
+}
+
+/// Prove the validator with the specified key controls the specified peer ID.
+///
+/// This is advertised to peers as our agent version, and as the peer ID is authenticated by the
+/// Noise handshake, binds the connection to our validator key.
+pub fn prove_identity(key: &Zeroizing<<Ristretto as Ciphersuite>::F>, peer_id: &PeerId) -> String {
+  let validator = Ristretto::generator() * key.deref();
+  let nonce = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let challenge = identity_challenge(peer_id, validator, Ristretto::generator() * nonce.deref());
+  let signature = SchnorrSignature::<Ristretto>::sign(key, nonce, challenge);
+  format!(
+    "{IDENTITY_PREFIX}{}/{}",
+    hex::encode(validator.to_bytes()),
+    hex::encode(signature.serialize())
+  )
+}
+
+/// Verify a peer's proof it's a validator, returning the validator's key if valid.
+pub fn verify_identity(
+  peer_id: &PeerId,
+  agent_version: &str,
+) -> Option<<Ristretto as Ciphersuite>::G> {
+  let (validator, signature) = agent_version.strip_prefix(IDENTITY_PREFIX)?.split_once('/')?;
+  let validator = Ristretto::read_G::<&[u8]>(&mut hex::decode(validator).ok()?.as_ref()).ok()?;
+  let signature =
+    SchnorrSignature::<Ristretto>::read::<&[u8]>(&mut hex::decode(signature).ok()?.as_ref())
+      .ok()?;
+  signature
+    .verify(validator, identity_challenge(peer_id, validator, signature.R))
+    .then_some(validator)
+}
+
+// If this is a validator of any of the tributaries we're subscribed to
+fn is_tributary_validator(
+  tributary_validators: &HashMap<TopicHash, HashSet<[u8; 32]>>,
+  validator: &<Ristretto as Ciphersuite>::G,
+) -> bool {
+  let validator = validator.to_bytes();
+  tributary_validators.values().any(|validators| validators.contains(&validator))
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum P2pMessageKind {
   KeepAlive,
@@ -34,6 +155,16 @@ pub enum P2pMessageKind {
 }
 
 impl P2pMessageKind {
+  /// The genesis of the tributary this message is for, if it's for a tributary.
+  pub fn genesis(&self) -> Option<[u8; 32]> {
+    match self {
+      P2pMessageKind::KeepAlive => None,
+      P2pMessageKind::Tributary(genesis) |
+      P2pMessageKind::Heartbeat(genesis) |
+      P2pMessageKind::Block(genesis) => Some(*genesis),
+    }
+  }
+
   fn serialize(&self) -> Vec<u8> {
     match self {
       P2pMessageKind::KeepAlive => vec![0],
@@ -95,6 +226,12 @@ pub trait P2p: Send + Sync + Clone + fmt::Debug + TributaryP2p {
   async fn broadcast_raw(&self, msg: Vec<u8>);
   async fn receive_raw(&self) -> (Self::Id, Vec<u8>);
 
+  /// Start receiving messages for the tributary with the specified genesis.
+  ///
+  /// Messages for tributaries which haven't been subscribed to may not be received. Messages for
+  /// this tributary may only be received from its validators.
+  async fn subscribe(&self, genesis: [u8; 32], validators: Vec<<Ristretto as Ciphersuite>::G>);
+
   async fn send(&self, to: Self::Id, kind: P2pMessageKind, msg: Vec<u8>) {
     let mut actual_msg = kind.serialize();
     actual_msg.extend(msg);
@@ -103,7 +240,7 @@ pub trait P2p: Send + Sync + Clone + fmt::Debug + TributaryP2p {
   async fn broadcast(&self, kind: P2pMessageKind, msg: Vec<u8>) {
     let mut actual_msg = kind.serialize();
     actual_msg.extend(msg);
-    log::trace!(
+    tracing::trace!(
       "broadcasting p2p message (kind {})",
       match kind {
         P2pMessageKind::KeepAlive => "KeepAlive".to_string(),
@@ -118,18 +255,18 @@ pub trait P2p: Send + Sync + Clone + fmt::Debug + TributaryP2p {
     let (sender, kind, msg) = loop {
       let (sender, msg) = self.receive_raw().await;
       if msg.is_empty() {
-        log::error!("empty p2p message from {sender:?}");
+        tracing::error!("empty p2p message from {sender:?}");
         continue;
       }
 
       let mut msg_ref = msg.as_ref();
       let Some(kind) = P2pMessageKind::read::<&[u8]>(&mut msg_ref) else {
-        log::error!("invalid p2p message kind from {sender:?}");
+        tracing::error!("invalid p2p message kind from {sender:?}");
         continue;
       };
       break (sender, kind, msg_ref.to_vec());
     };
-    log::trace!(
+    tracing::trace!(
       "received p2p message (kind {})",
       match kind {
         P2pMessageKind::KeepAlive => "KeepAlive".to_string(),
@@ -147,6 +284,7 @@ struct Behavior {
   gossipsub: GsBehavior,
   //#[cfg(debug_assertions)]
   mdns: libp2p::mdns::tokio::Behaviour,
+  identify: identify::Behaviour,
 }
 
 #[allow(clippy::type_complexity)]
@@ -154,6 +292,8 @@ struct Behavior {
 pub struct LibP2p(
   Arc<Mutex<mpsc::UnboundedSender<Vec<u8>>>>,
   Arc<Mutex<mpsc::UnboundedReceiver<(PeerId, Vec<u8>)>>>,
+  Arc<AtomicBool>,
+  Arc<Mutex<mpsc::UnboundedSender<([u8; 32], Vec<<Ristretto as Ciphersuite>::G>)>>>,
 );
 impl fmt::Debug for LibP2p {
   fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -162,16 +302,20 @@ impl fmt::Debug for LibP2p {
 }
 
 impl LibP2p {
-  #[allow(clippy::new_without_default)]
-  pub fn new() -> Self {
-    log::info!("creating a libp2p instance");
+  pub fn new(
+    config: &P2pConfig,
+    key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+    tasks: &Tasks,
+  ) -> Self {
+    tracing::info!("creating a libp2p instance");
 
     let throwaway_key_pair = Keypair::generate_ed25519();
-    let throwaway_peer_id = PeerId::from(throwaway_key_pair.public());
+    let throwaway_public_key = throwaway_key_pair.public();
+    let throwaway_peer_id = PeerId::from(throwaway_public_key.clone());
 
-    // Uses noise for authentication, yamux for multiplexing
-    // TODO: Do we want to add a custom authentication protocol to only accept connections from
-    // fellow validators? Doing so would reduce the potential for spam
+    // Uses noise for authentication and encryption, yamux for multiplexing
+    // Noise authenticates the throwaway peer ID, which our validator key then signs over, letting
+    // peers only accept messages from fellow validators
     let transport = libp2p_tokio::Transport::new(Config::default().nodelay(true))
       .upgrade(upgrade::Version::V1)
       .authenticate(noise::Config::new(&throwaway_key_pair).unwrap())
@@ -196,6 +340,8 @@ impl LibP2p {
           // We send KeepAlive after 80s
           .idle_timeout(Duration::from_secs(85))
           .validation_mode(ValidationMode::Strict)
+          // Don't propagate messages until we've checked they were relayed by a validator
+          .validate_messages()
           // Uses a content based message ID to avoid duplicates as much as possible
           .message_id_fn(|msg| {
             MessageId::new(&Blake2s256::digest([msg.topic.as_str().as_bytes(), &msg.data].concat()))
@@ -215,10 +361,10 @@ impl LibP2p {
         )
         .unwrap();
 
-        // Uses a single topic to prevent being a BTC validator only connected to ETH validators,
-        // unable to communicate with other BTC validators
-        let topic = IdentTopic::new(LIBP2P_TOPIC);
-        gossipsub.subscribe(&topic).unwrap();
+        // All coordinators subscribe to a common topic, preventing being a BTC validator only
+        // connected to ETH validators, unable to communicate with other BTC validators
+        // Each tributary's messages are then only propagated to those subscribed to its topic
+        gossipsub.subscribe(&topic(None)).unwrap();
 
         gossipsub
       },
@@ -227,22 +373,52 @@ impl LibP2p {
       // TODO: We do tests on release binaries as of right now...
       //#[cfg(debug_assertions)]
       mdns: {
-        log::info!("creating mdns service");
+        tracing::info!("creating mdns service");
         libp2p::mdns::tokio::Behaviour::new(libp2p::mdns::Config::default(), throwaway_peer_id)
           .unwrap()
       },
+
+      // Exchange protocol versions and validator keys with peers upon connecting
+      identify: identify::Behaviour::new(
+        identify::Config::new(protocol_version(), throwaway_public_key)
+          .with_agent_version(prove_identity(key, &throwaway_peer_id)),
+      ),
     };
 
     let mut swarm =
       SwarmBuilder::with_tokio_executor(transport, behavior, throwaway_peer_id).build();
-    const PORT: u16 = 30563; // 5132 ^ (('c' << 8) | 'o')
-    swarm.listen_on(format!("/ip4/0.0.0.0/tcp/{PORT}").parse().unwrap()).unwrap();
+    let port = config.port;
+    swarm
+      .listen_on(Multiaddr::from(config.listen_address).with(Protocol::Tcp(port)))
+      .expect("couldn't listen on the configured P2P address");
+    let bootnodes = config.bootnodes.clone();
+    for bootnode in &bootnodes {
+      if let Err(e) = swarm.dial(bootnode.clone()) {
+        tracing::warn!("couldn't dial bootnode {bootnode}: {e}");
+      }
+    }
 
     let (broadcast_send, mut broadcast_recv) = mpsc::unbounded_channel();
     let (receive_send, receive_recv) = mpsc::unbounded_channel();
+    let (subscribe_send, mut subscribe_recv) =
+      mpsc::unbounded_channel::<([u8; 32], Vec<<Ristretto as Ciphersuite>::G>)>();
+    let listening = Arc::new(AtomicBool::new(false));
 
-    tokio::spawn({
+    tasks.spawn(Stage::P2p, "libp2p", {
       let mut time_of_last_p2p_message = Instant::now();
+      let listening = listening.clone();
+
+      // The addresses of the peers we've connected to, with how many times we've failed to
+      // reconnect to them since we lost our connection
+      let mut known_peers = HashMap::<PeerId, (Vec<Multiaddr>, u8)>::new();
+      let mut reconnect = tokio::time::interval(RECONNECT_INTERVAL);
+
+      // The validator keys of the peers which have proven them, and when we connected to the
+      // peers which have yet to
+      let mut authenticated = HashMap::<PeerId, <Ristretto as Ciphersuite>::G>::new();
+      let mut unauthenticated = HashMap::<PeerId, Instant>::new();
+      // The validators of each tributary we're subscribed to, by topic
+      let mut tributary_validators = HashMap::<TopicHash, HashSet<[u8; 32]>>::new();
 
       #[allow(clippy::needless_pass_by_ref_mut)] // False positive
       async fn broadcast_raw(
@@ -253,10 +429,13 @@ impl LibP2p {
         // Update the time of last message
         *time_of_last_p2p_message = Instant::now();
 
-        match p2p.behaviour_mut().gossipsub.publish(IdentTopic::new(LIBP2P_TOPIC), msg.clone()) {
+        // Publish to the topic for the tributary this message is for, if it's for one
+        let genesis =
+          P2pMessageKind::read::<&[u8]>(&mut msg.as_ref()).and_then(|kind| kind.genesis());
+        match p2p.behaviour_mut().gossipsub.publish(topic(genesis), msg.clone()) {
           Err(PublishError::SigningError(e)) => panic!("signing error when broadcasting: {e}"),
           Err(PublishError::InsufficientPeers) => {
-            log::warn!("failed to send p2p message due to insufficient peers")
+            tracing::warn!("failed to send p2p message due to insufficient peers")
           }
           Err(PublishError::MessageTooLarge) => {
             panic!("tried to send a too large message: {}", hex::encode(msg))
@@ -282,6 +461,20 @@ impl LibP2p {
               ).await;
             }
 
+            // Subscribe to the topics of new tributaries
+            subscription = subscribe_recv.recv() => {
+              let (genesis, validators) =
+                subscription.expect("subscribe_recv closed. are we shutting down?");
+              let topic = topic(Some(genesis));
+              tributary_validators.insert(
+                topic.hash(),
+                validators.iter().map(|validator| validator.to_bytes()).collect(),
+              );
+              if let Err(e) = swarm.behaviour_mut().gossipsub.subscribe(&topic) {
+                tracing::error!("couldn't subscribe to tributary {}: {e}", hex::encode(genesis));
+              }
+            }
+
             // Handle new incoming messages
             event = swarm.next() => {
               match event {
@@ -291,8 +484,8 @@ impl LibP2p {
                 ))) => {
                   for (peer, mut addr) in list {
                     // Check the port is as expected to prevent trying to peer with Substrate nodes
-                    if addr.pop() == Some(libp2p::multiaddr::Protocol::Tcp(PORT)) {
-                      log::info!("found peer via mdns");
+                    if addr.pop() == Some(Protocol::Tcp(port)) {
+                      tracing::info!("found peer via mdns");
                       swarm.behaviour_mut().gossipsub.add_explicit_peer(&peer);
                     }
                   }
@@ -302,22 +495,178 @@ impl LibP2p {
                   libp2p::mdns::Event::Expired(list),
                 ))) => {
                   for (peer, _) in list {
-                    log::info!("disconnecting peer due to mdns");
+                    tracing::info!("disconnecting peer due to mdns");
                     swarm.behaviour_mut().gossipsub.remove_explicit_peer(&peer);
                   }
                 }
 
+                Some(SwarmEvent::Behaviour(BehaviorEvent::Identify(
+                  identify::Event::Received { peer_id, info },
+                ))) => match peer_protocol_version(&info.protocol_version) {
+                  Some(version) if version >= MIN_P2P_PROTOCOL_VERSION => {
+                    let Some(validator) = verify_identity(&peer_id, &info.agent_version) else {
+                      tracing::warn!("disconnecting from peer {peer_id}, which isn't a validator");
+                      known_peers.remove(&peer_id);
+                      unauthenticated.remove(&peer_id);
+                      swarm.behaviour_mut().gossipsub.blacklist_peer(&peer_id);
+                      swarm.behaviour_mut().gossipsub.remove_explicit_peer(&peer_id);
+                      let _ = swarm.disconnect_peer_id(peer_id);
+                      continue;
+                    };
+                    // This isn't blacklisted as it may become a validator of one of our
+                    // tributaries once we've scanned further
+                    if !is_tributary_validator(&tributary_validators, &validator) {
+                      tracing::info!(
+                        "disconnecting from peer {peer_id}, which isn't a validator of any of our \
+                         tributaries",
+                      );
+                      known_peers.remove(&peer_id);
+                      unauthenticated.remove(&peer_id);
+                      let _ = swarm.disconnect_peer_id(peer_id);
+                      continue;
+                    }
+                    tracing::debug!(
+                      "peer {peer_id} authenticated as validator {}",
+                      hex::encode(validator.to_bytes()),
+                    );
+                    authenticated.insert(peer_id, validator);
+                    unauthenticated.remove(&peer_id);
+
+                    // Remember where this peer listens, so we can reconnect to it
+                    known_peers.insert(peer_id, (info.listen_addrs, 0));
+                    if version > P2P_PROTOCOL_VERSION {
+                      tracing::warn!(
+                        "peer {peer_id} uses p2p protocol version {version}, newer than our {}. \
+                         this coordinator should be upgraded",
+                        P2P_PROTOCOL_VERSION,
+                      );
+                    }
+                  }
+                  _ => {
+                    // Peers which predate the handshake don't run identify, and accordingly
+                    // are disconnected once they fail to authenticate in time
+                    tracing::error!(
+                      "disconnecting from peer {peer_id}, which uses the incompatible p2p \
+                       protocol {:?} (we support versions {} to {})",
+                      info.protocol_version,
+                      MIN_P2P_PROTOCOL_VERSION,
+                      P2P_PROTOCOL_VERSION,
+                    );
+                    // Peers regenerate their ID on restart, so this won't block the peer once
+                    // it's upgraded
+                    known_peers.remove(&peer_id);
+                    swarm.behaviour_mut().gossipsub.blacklist_peer(&peer_id);
+                    swarm.behaviour_mut().gossipsub.remove_explicit_peer(&peer_id);
+                    let _ = swarm.disconnect_peer_id(peer_id);
+                  }
+                },
+
                 Some(SwarmEvent::Behaviour(BehaviorEvent::Gossipsub(
-                  GsEvent::Message { propagation_source, message, .. },
+                  GsEvent::Message { propagation_source, message_id, message },
                 ))) => {
+                  // Only accept messages relayed by validators, and for a tributary, only by its
+                  // validators
+                  // As validators only relay messages they've accepted, this ensures every
+                  // message was sent by a validator
+                  let common = message.topic == topic(None).hash();
+                  let accepted = authenticated.get(&propagation_source).is_some_and(|validator| {
+                    match tributary_validators.get(&message.topic) {
+                      Some(validators) => validators.contains(&validator.to_bytes()),
+                      None => common && is_tributary_validator(&tributary_validators, validator),
+                    }
+                  });
+                  let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                    &message_id,
+                    &propagation_source,
+                    if accepted { MessageAcceptance::Accept } else { MessageAcceptance::Reject },
+                  );
+                  if !accepted {
+                    tracing::debug!("rejected p2p message relayed by {propagation_source}");
+                    continue;
+                  }
+
                   receive_send
                     .send((propagation_source, message.data))
                     .expect("receive_send closed. are we shutting down?");
                 }
+
+                Some(SwarmEvent::ConnectionEstablished { peer_id, .. }) => {
+                  if let Some((_, attempts)) = known_peers.get_mut(&peer_id) {
+                    *attempts = 0;
+                  }
+                  if !authenticated.contains_key(&peer_id) {
+                    unauthenticated.entry(peer_id).or_insert_with(Instant::now);
+                  }
+                }
+                Some(SwarmEvent::ConnectionClosed {
+                  peer_id, num_established: 0, endpoint, ..
+                }) => {
+                  tracing::debug!("lost connection to peer {peer_id}");
+                  authenticated.remove(&peer_id);
+                  unauthenticated.remove(&peer_id);
+                  // If this was a bootnode we don't otherwise know, remember its address
+                  if let ConnectedPoint::Dialer { address, .. } = endpoint {
+                    known_peers.entry(peer_id).or_insert_with(|| (vec![address], 0));
+                  }
+                }
+                Some(SwarmEvent::OutgoingConnectionError { peer_id: Some(peer_id), error, .. }) => {
+                  if let Some((_, attempts)) = known_peers.get_mut(&peer_id) {
+                    *attempts += 1;
+                    if *attempts >= MAX_RECONNECT_ATTEMPTS {
+                      tracing::info!("forgetting peer {peer_id}, unable to reconnect: {error}");
+                      known_peers.remove(&peer_id);
+                    }
+                  }
+                }
+
+                Some(SwarmEvent::NewListenAddr { address, .. }) => {
+                  tracing::info!("listening on {address}");
+                  listening.store(true, Ordering::Relaxed);
+                }
+                Some(SwarmEvent::ListenerClosed { reason, .. }) => {
+                  tracing::error!("p2p listener closed: {reason:?}");
+                  listening.store(false, Ordering::Relaxed);
+                }
                 _ => {}
               }
             }
 
+            // Reconnect to the peers we've lost our connections to
+            _ = reconnect.tick() => {
+              // Disconnect from peers which haven't proven they're validators in time
+              unauthenticated.retain(|peer, connected| {
+                if connected.elapsed() < AUTHENTICATION_TIMEOUT {
+                  return true;
+                }
+                tracing::info!("disconnecting from peer {peer}, which didn't authenticate");
+                let _ = swarm.disconnect_peer_id(*peer);
+                false
+              });
+
+              for (peer, (addresses, _)) in &known_peers {
+                if swarm.is_connected(peer) {
+                  continue;
+                }
+                let dial = DialOpts::peer_id(*peer)
+                  .addresses(addresses.clone())
+                  .condition(PeerCondition::Disconnected)
+                  .build();
+                if let Err(e) = swarm.dial(dial) {
+                  tracing::debug!("couldn't redial peer {peer}: {e}");
+                }
+              }
+
+              // If we have no peers at all, our known peers may have all changed their addresses
+              // Redial the bootnodes to rejoin the network
+              if swarm.connected_peers().next().is_none() {
+                for bootnode in &bootnodes {
+                  if let Err(e) = swarm.dial(bootnode.clone()) {
+                    tracing::warn!("couldn't redial bootnode {bootnode}: {e}");
+                  }
+                }
+              }
+            }
+
             // If it's been >80s since we've published a message, publish a KeepAlive since we're
             // still an active service
             // This is useful when we have no active tributaries and accordingly aren't sending
@@ -337,7 +686,17 @@ impl LibP2p {
       }
     });
 
-    LibP2p(Arc::new(Mutex::new(broadcast_send)), Arc::new(Mutex::new(receive_recv)))
+    LibP2p(
+      Arc::new(Mutex::new(broadcast_send)),
+      Arc::new(Mutex::new(receive_recv)),
+      listening,
+      Arc::new(Mutex::new(subscribe_send)),
+    )
+  }
+
+  /// If we're currently listening for P2P connections.
+  pub fn listening(&self) -> bool {
+    self.2.load(Ordering::Relaxed)
   }
 }
 
@@ -358,6 +717,15 @@ impl P2p for LibP2p {
   async fn receive_raw(&self) -> (Self::Id, Vec<u8>) {
     self.1.lock().await.recv().await.expect("receive_recv closed. are we shutting down?")
   }
+
+  async fn subscribe(&self, genesis: [u8; 32], validators: Vec<<Ristretto as Ciphersuite>::G>) {
+    self
+      .3
+      .lock()
+      .await
+      .send((genesis, validators))
+      .expect("subscribe_send closed. are we shutting down?");
+  }
 }
 
 #[async_trait]
@@ -12,6 +12,12 @@ pub struct Message {
   pub msg: ProcessorMessage,
 }
 
+/// The channel between the coordinator and the processors.
+///
+/// Messages are durably queued, in order, by the message-queue. A sent message is only delivered
+/// once per intent, so messages may safely be re-sent after a reboot. A received message will be
+/// received again until it's acknowledged, and should be acknowledged only after its effects were
+/// committed alongside `MessageQueue::handle`.
 #[async_trait::async_trait]
 pub trait Processors: 'static + Send + Sync + Clone {
   async fn send(&self, network: NetworkId, msg: CoordinatorMessage);
@@ -24,8 +30,7 @@ impl Processors for Arc<MessageQueue> {
   async fn send(&self, network: NetworkId, msg: CoordinatorMessage) {
     let metadata =
       Metadata { from: self.service, to: Service::Processor(network), intent: msg.intent() };
-    let msg = serde_json::to_string(&msg).unwrap();
-    self.queue(metadata, msg.into_bytes()).await;
+    self.queue(metadata, msg.serialize()).await;
   }
   async fn recv(&mut self, network: NetworkId) -> Message {
     let msg = self.next(Service::Processor(network)).await;
@@ -34,8 +39,9 @@ impl Processors for Arc<MessageQueue> {
     let id = msg.id;
 
     // Deserialize it into a ProcessorMessage
-    let msg: ProcessorMessage =
-      serde_json::from_slice(&msg.msg).expect("message wasn't a JSON-encoded ProcessorMessage");
+    let msg = ProcessorMessage::deserialize(&msg.msg).unwrap_or_else(|e| {
+      panic!("couldn't decode message {id} from the {network:?} processor: {e}")
+    });
 
     return Message { id, network, msg };
   }
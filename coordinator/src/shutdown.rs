@@ -0,0 +1,194 @@
+use core::{future::Future, panic::AssertUnwindSafe};
+use std::{
+  sync::{Arc, Mutex},
+  time::Duration,
+  io::Write,
+};
+
+use serai_db::QuiescableDb;
+
+use futures::FutureExt;
+use tokio::{
+  task::{JoinHandle, AbortHandle},
+  time::{Instant, sleep},
+  signal::unix::{SignalKind, signal},
+};
+
+// How long to wait for in-progress transactions to be committed before exiting regardless
+const QUIESCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// The delay before restarting a panicked task, doubled on every consecutive panic
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// How long a task must run before a panic is no longer considered consecutive with the last
+const BACKOFF_RESET: Duration = Duration::from_secs(5 * 60);
+
+tokio::task_local! {
+  static SUPERVISED: &'static str;
+}
+
+/// If the current task is supervised, and accordingly will be restarted if it panics.
+pub fn supervised() -> bool {
+  SUPERVISED.try_with(|_| ()).is_ok()
+}
+
+/// The stages the coordinator's tasks are stopped in, in order.
+///
+/// External input is stopped first, so nothing new is started while the tasks further down are
+/// stopped.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Stage {
+  /// Tasks handling the P2P network, and serving the health and inspection endpoints.
+  P2p,
+  /// Tasks adding, retiring, and scanning Tributaries.
+  Tributary,
+  /// Tasks scanning Substrate.
+  Substrate,
+  /// Tasks handling messages from the processors.
+  Processors,
+}
+
+/// The coordinator's long-running tasks, so they may be stopped in order.
+///
+/// Every event is tracked as handled within the same transaction handling it, and messages to the
+/// processors are queued in the DB until acknowledged, so stopping a task at any await point only
+/// discards uncommitted work which is redone once restarted.
+#[derive(Clone)]
+pub struct Tasks(Arc<Mutex<Option<Vec<(Stage, &'static str, JoinHandle<()>)>>>>);
+
+impl Default for Tasks {
+  fn default() -> Self {
+    Tasks(Arc::new(Mutex::new(Some(vec![]))))
+  }
+}
+
+impl Tasks {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Spawn a task which will be stopped on shutdown.
+  ///
+  /// If the task panics, the process exits. Tasks which can be recreated should use `supervise`
+  /// instead. If shutdown has already started, the task isn't spawned.
+  pub fn spawn(
+    &self,
+    stage: Stage,
+    name: &'static str,
+    task: impl 'static + Send + Future<Output = ()>,
+  ) {
+    let mut tasks = self.0.lock().unwrap();
+    let Some(tasks) = tasks.as_mut() else { return };
+    // Drop the handles of tasks which have already completed
+    tasks.retain(|(_, _, handle)| !handle.is_finished());
+    tasks.push((stage, name, tokio::spawn(task)));
+  }
+
+  /// Spawn a task which will be restarted, with an exponential backoff, if it panics.
+  ///
+  /// The task is recreated by calling `task` again, which should create it from fresh clones of
+  /// its arguments.
+  pub fn supervise<F: 'static + Send + Future<Output = ()>>(
+    &self,
+    stage: Stage,
+    name: &'static str,
+    mut task: impl 'static + Send + FnMut() -> F,
+  ) {
+    self.spawn(stage, name, async move {
+      let mut backoff = INITIAL_BACKOFF;
+      loop {
+        let start = Instant::now();
+        if SUPERVISED.scope(name, AssertUnwindSafe(task()).catch_unwind()).await.is_ok() {
+          return;
+        }
+
+        if start.elapsed() >= BACKOFF_RESET {
+          backoff = INITIAL_BACKOFF;
+        }
+        tracing::error!("task {name} panicked, restarting it in {}s", backoff.as_secs());
+        sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+      }
+    });
+  }
+
+  /// Stop every task, stage by stage.
+  ///
+  /// Once called, no further tasks will be spawned.
+  pub async fn shutdown(&self) {
+    let Some(mut tasks) = self.0.lock().unwrap().take() else { return };
+    // This sort is stable, so tasks within a stage are stopped in the order they were spawned
+    tasks.sort_by_key(|(stage, _, _)| *stage);
+    for (stage, name, handle) in tasks {
+      handle.abort();
+      // This will be a cancellation error, unless the task already panicked
+      let _ = handle.await;
+      tracing::debug!("stopped task {name} ({stage:?})");
+    }
+  }
+}
+
+/// Tasks spawned by another task, which are stopped when it's dropped.
+///
+/// A task stopped by `Tasks` is dropped, so anything it spawned via its `Subtasks` is stopped with
+/// it.
+#[derive(Default)]
+pub struct Subtasks(Vec<JoinHandle<()>>);
+
+impl Subtasks {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Spawn a task which will be stopped when this is dropped.
+  ///
+  /// The returned handle may be used to stop the task sooner.
+  pub fn spawn(&mut self, task: impl 'static + Send + Future<Output = ()>) -> AbortHandle {
+    // Drop the handles of tasks which have already completed
+    self.0.retain(|handle| !handle.is_finished());
+    let handle = tokio::spawn(task);
+    let abort = handle.abort_handle();
+    self.0.push(handle);
+    abort
+  }
+}
+
+impl Drop for Subtasks {
+  fn drop(&mut self) {
+    for handle in &self.0 {
+      handle.abort();
+    }
+  }
+}
+
+/// Stop the coordinator's tasks when SIGTERM or SIGINT is received, then exit.
+///
+/// After the tasks are stopped, writes are paused until any in-progress transactions are
+/// committed, and never resumed, so the DB is left as of the last completed event.
+pub async fn handle_shutdown_signal(db: QuiescableDb<serai_db::RocksDB>, tasks: Tasks) {
+  let mut terminate =
+    signal(SignalKind::terminate()).expect("couldn't listen for the terminate signal");
+  let mut interrupt =
+    signal(SignalKind::interrupt()).expect("couldn't listen for the interrupt signal");
+  tokio::select! {
+    _ = terminate.recv() => {},
+    _ = interrupt.recv() => {},
+  }
+
+  tracing::info!("received shutdown signal, stopping tasks");
+  tasks.shutdown().await;
+
+  // This exits from the blocking thread as the runtime may not make progress once quiesced
+  tokio::task::spawn_blocking(move || {
+    if let Some(quiesced) = db.quiesce(QUIESCE_TIMEOUT) {
+      std::mem::forget(quiesced);
+      tracing::info!("stopped all tasks and committed all transactions, exiting");
+    } else {
+      tracing::warn!("in-progress transactions didn't complete in time, exiting regardless");
+    }
+    let _ = std::io::stdout().flush();
+    std::process::exit(0);
+  })
+  .await
+  .unwrap();
+}
@@ -1,8 +1,36 @@
 use scale::{Encode, Decode};
 
+use sp_application_crypto::{RuntimePublic, sr25519};
+
 pub use serai_db::*;
 
-use serai_client::validator_sets::primitives::{Session, KeyPair};
+use serai_client::{
+  primitives::NetworkId,
+  validator_sets::primitives::{Session, ValidatorSet, KeyPair},
+};
+
+use processor_messages::coordinator::cosign_block_msg;
+
+/// A validator set's signature over a finalized Serai block.
+///
+/// This is a Schnorrkel signature by the set's Substrate key, over `cosign_block_msg`, letting
+/// anyone who knows the set's key verify Serai's finality without running a node.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Cosign {
+  pub set: ValidatorSet,
+  pub block_number: u64,
+  pub block: [u8; 32],
+  pub signature: [u8; 64],
+}
+
+impl Cosign {
+  pub fn verify(&self, key: &KeyPair) -> bool {
+    sr25519::Public::from_raw(key.0 .0).verify(
+      &cosign_block_msg(self.block_number, self.block),
+      &sr25519::Signature::from_raw(self.signature),
+    )
+  }
+}
 
 #[derive(Debug)]
 pub struct SubstrateDb<D: Db>(pub D);
@@ -18,14 +46,27 @@ impl<D: Db> SubstrateDb<D> {
   fn block_key() -> Vec<u8> {
     Self::substrate_key(b"block", [])
   }
-  pub fn set_next_block(&mut self, block: u64) {
+  fn block_hash_key() -> Vec<u8> {
+    Self::substrate_key(b"block_hash", [])
+  }
+  // The next block and the hash of the block before it are set atomically, so the next block's
+  // parent is always known
+  pub fn set_handled_block(&mut self, number: u64, hash: [u8; 32]) {
     let mut txn = self.0.txn();
-    txn.put(Self::block_key(), block.to_le_bytes());
+    txn.put(Self::block_key(), (number + 1).to_le_bytes());
+    txn.put(Self::block_hash_key(), hash);
     txn.commit();
   }
   pub fn next_block(&self) -> u64 {
     u64::from_le_bytes(self.0.get(Self::block_key()).unwrap_or(vec![0; 8]).try_into().unwrap())
   }
+  /// The hash of the last handled block.
+  ///
+  /// This is None if no block has been handled, or if the last block was handled by a version
+  /// which didn't track it.
+  pub fn last_block_hash(&self) -> Option<[u8; 32]> {
+    self.0.get(Self::block_hash_key()).map(|hash| hash.try_into().unwrap())
+  }
 
   fn event_key(id: &[u8], index: u32) -> Vec<u8> {
     Self::substrate_key(b"event", [id, index.to_le_bytes().as_ref()].concat())
@@ -55,4 +96,30 @@ impl<D: Db> SubstrateDb<D> {
     txn.put(key_0, session.clone());
     txn.put(Self::session_key(&key_pair.1), session);
   }
+
+  fn cosign_key(network: NetworkId, block_number: u64) -> Vec<u8> {
+    Self::substrate_key(b"cosign", (network, block_number).encode())
+  }
+  fn latest_cosign_key(network: NetworkId) -> Vec<u8> {
+    Self::substrate_key(b"latest_cosign", network.encode())
+  }
+  pub fn save_cosign(txn: &mut D::Transaction<'_>, cosign: &Cosign) {
+    let network = cosign.set.network;
+    txn.put(Self::cosign_key(network, cosign.block_number), cosign.encode());
+    // Cosigns may be completed out of order, so only replace the latest with a later one
+    if Self::latest_cosign(txn, network).map(|latest| latest.block_number) <
+      Some(cosign.block_number)
+    {
+      txn.put(Self::latest_cosign_key(network), cosign.block_number.to_le_bytes());
+    }
+  }
+  pub fn cosign<G: Get>(getter: &G, network: NetworkId, block_number: u64) -> Option<Cosign> {
+    getter
+      .get(Self::cosign_key(network, block_number))
+      .map(|bytes| Cosign::decode(&mut bytes.as_ref()).unwrap())
+  }
+  pub fn latest_cosign<G: Get>(getter: &G, network: NetworkId) -> Option<Cosign> {
+    let block_number = getter.get(Self::latest_cosign_key(network))?;
+    Self::cosign(getter, network, u64::from_le_bytes(block_number.try_into().unwrap()))
+  }
 }
@@ -0,0 +1,98 @@
+use core::{pin::Pin, time::Duration};
+use std::sync::Arc;
+
+use futures::stream::{Stream, StreamExt};
+use tokio::time::{sleep, timeout};
+
+use serai_client::{SeraiError, Block, Serai};
+
+// How long to wait for a finality notification before polling the node for the latest finalized
+// block
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(60);
+// The delay before retrying after failing to communicate with the Serai node
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+type Notifier = Pin<Box<dyn Send + Stream<Item = Result<(), SeraiError>>>>;
+
+/// A follower of Serai's finalized chain.
+///
+/// Notifications of newly finalized blocks are only used as a hint. Whenever one is received, or
+/// none has been received for a while, the node is asked for its latest finalized block, so blocks
+/// finalized while disconnected are caught up on once reconnected. Each block fetched is checked
+/// to build on the block handled before it, so a node on a distinct chain is never followed.
+pub struct Finality {
+  serai: Arc<Serai>,
+  notifier: Option<Notifier>,
+}
+
+impl Finality {
+  pub fn new(serai: Arc<Serai>) -> Self {
+    Finality { serai, notifier: None }
+  }
+
+  /// Wait for a block to be finalized, returning the number of the latest finalized block.
+  ///
+  /// This will return even if no block was finalized, if no notification was received for a
+  /// while. Failures to communicate with the node are retried, re-subscribing as needed, until it
+  /// succeeds.
+  pub async fn wait(&mut self) -> u64 {
+    loop {
+      if self.notifier.is_none() {
+        match self.serai.newly_finalized_block().await {
+          Ok(notifier) => self.notifier = Some(Box::pin(notifier)),
+          Err(e) => {
+            tracing::error!("couldn't subscribe to finalized blocks: {e}");
+            sleep(RETRY_DELAY).await;
+            continue;
+          }
+        }
+      }
+
+      match timeout(NOTIFICATION_TIMEOUT, self.notifier.as_mut().unwrap().next()).await {
+        Ok(Some(Ok(()))) => {}
+        // The subscription failed, so re-create it on the next call
+        // Any blocks finalized in the meantime will be caught up on as we poll below
+        Ok(Some(Err(e))) => {
+          tracing::warn!("finalized block subscription failed: {e}");
+          self.notifier = None;
+        }
+        Ok(None) => {
+          tracing::warn!("finalized block subscription closed");
+          self.notifier = None;
+        }
+        // Timed out, which may be because Serai isn't finalizing or because the subscription
+        // silently stalled
+        Err(_) => tracing::debug!("no finalized block notification in the last 60s"),
+      }
+
+      match self.serai.get_latest_block().await {
+        Ok(latest) => return latest.number(),
+        Err(e) => {
+          tracing::error!("couldn't get the latest finalized block: {e}");
+          sleep(RETRY_DELAY).await;
+        }
+      }
+    }
+  }
+
+  /// Get a finalized block by its number, which must build on the block with the specified hash.
+  ///
+  /// If the block doesn't exist, such as when we've failed over to a node which is behind, or
+  /// doesn't build on the specified block, an error is returned.
+  pub async fn block(&self, number: u64, parent: Option<[u8; 32]>) -> Result<Block, SeraiError> {
+    let Some(block) = self.serai.get_block_by_number(number).await? else {
+      tracing::warn!("node didn't have finalized block {number}");
+      Err(SeraiError::InvalidNode)?
+    };
+    if let Some(parent) = parent {
+      if block.header().parent_hash.0 != parent {
+        tracing::error!(
+          "finalized block {number} didn't build on the block handled before it ({})",
+          hex::encode(parent),
+        );
+        Err(SeraiError::InvalidNode)?;
+      }
+    }
+    Ok(block)
+  }
+}
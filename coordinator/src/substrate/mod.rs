@@ -9,7 +9,7 @@ use serai_client::{
   SeraiError, Block, Serai,
   primitives::{BlockHash, NetworkId},
   validator_sets::{
-    primitives::{ValidatorSet, KeyPair},
+    primitives::{Session, ValidatorSet, KeyPair},
     ValidatorSetsEvent,
   },
   in_instructions::InInstructionsEvent,
@@ -18,12 +18,13 @@ use serai_client::{
 
 use serai_db::DbTxn;
 
-use processor_messages::{SubstrateContext, CoordinatorMessage};
+use processor_messages::{SubstrateContext, CoordinatorMessage, coordinator, sign::SignId};
 
 use tokio::time::sleep;
 
 use crate::{
   Db,
+  db::MainDb,
   processors::Processors,
   tributary::{TributarySpec, TributaryDb},
 };
@@ -31,6 +32,14 @@ use crate::{
 mod db;
 pub use db::*;
 
+mod finality;
+pub use finality::Finality;
+
+// How often Serai blocks are cosigned, in blocks (10 minutes with 6 second blocks)
+// Blocks which set a validator set's keys are also cosigned, so a light client following the
+// cosigns can learn each set's keys from the set before it
+pub const COSIGN_DISTANCE: u64 = 100;
+
 async fn in_set(
   key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
   serai: &Serai,
@@ -43,6 +52,29 @@ async fn in_set(
   Ok(Some(data.participants.iter().any(|(participant, _)| participant.0 == key)))
 }
 
+// The validator sets declared at genesis
+//
+// These are read from the genesis state, instead of the genesis block's events, so sets declared
+// by any pallet's genesis build (such as the next session's sets, declared when the session pallet
+// builds its genesis) aren't missed
+async fn genesis_sets(serai: &Serai, genesis: [u8; 32]) -> Result<Vec<ValidatorSet>, SeraiError> {
+  const EXTERNAL_NETWORKS: [NetworkId; 3] =
+    [NetworkId::Bitcoin, NetworkId::Ethereum, NetworkId::Monero];
+
+  let latest = serai.get_latest_session(genesis).await?.unwrap_or(Session(0));
+  let mut sets = vec![];
+  for session in 0 ..= latest.0 {
+    for network in EXTERNAL_NETWORKS {
+      let set = ValidatorSet { session: Session(session), network };
+      if serai.get_validator_set(set).await?.is_some() {
+        sets.push(set);
+      }
+    }
+  }
+  Ok(sets)
+}
+
+#[tracing::instrument(skip_all, fields(network = ?set.network, session = set.session.0))]
 async fn handle_new_set<D: Db, CNT: Clone + Fn(&mut D, TributarySpec)>(
   db: &mut D,
   key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
@@ -52,7 +84,16 @@ async fn handle_new_set<D: Db, CNT: Clone + Fn(&mut D, TributarySpec)>(
   set: ValidatorSet,
 ) -> Result<(), SeraiError> {
   if in_set(key, serai, set).await?.expect("NewSet for set which doesn't exist") {
-    log::info!("present in set {:?}", set);
+    tracing::info!("present in set {:?}", set);
+
+    // The sets for a session are declared when the prior session starts, giving them until the
+    // next session rotation to complete their DKG
+    if set.session.0 != 0 {
+      let prior = Session(set.session.0 - 1);
+      if let Some(schedule) = serai.get_session_schedule(block.hash(), prior).await? {
+        tracing::info!("set {:?} is expected to rotate in at block {:?}", set, schedule.next_start);
+      }
+    }
 
     let set_data = serai.get_validator_set(set).await?.expect("NewSet for set which doesn't exist");
 
@@ -80,12 +121,13 @@ async fn handle_new_set<D: Db, CNT: Clone + Fn(&mut D, TributarySpec)>(
     let spec = TributarySpec::new(block.hash(), time, set, set_data);
     create_new_tributary(db, spec.clone());
   } else {
-    log::info!("not present in set {:?}", set);
+    tracing::info!("not present in set {:?}", set);
   }
 
   Ok(())
 }
 
+#[tracing::instrument(skip_all, fields(network = ?set.network, session = set.session.0))]
 async fn handle_key_gen<D: Db, Pro: Processors>(
   db: &mut D,
   processors: &Pro,
@@ -209,13 +251,43 @@ async fn handle_batch_and_burns<Pro: Processors>(
   Ok(())
 }
 
+// Have every set we're in, which has keys and hasn't been retired, cosign this block
+// The processor only cosigns with the set whose keys it's currently using for Substrate, ignoring
+// requests for any other set
+async fn handle_cosign<D: Db, Pro: Processors>(db: &D, processors: &Pro, block: &Block) {
+  for spec in MainDb::<D>::active_tributaries(db).1 {
+    if TributaryDb::<D>::retired_at(db, spec.genesis()).is_some() {
+      continue;
+    }
+    let Some(key_pair) = TributaryDb::<D>::key_pair(db, spec.set()) else { continue };
+
+    tracing::debug!("requesting {:?} cosign block {}", spec.set(), block.number());
+    processors
+      .send(
+        spec.set().network,
+        CoordinatorMessage::Coordinator(coordinator::CoordinatorMessage::CosignSubstrateBlock {
+          id: SignId { key: key_pair.0 .0.to_vec(), id: block.hash(), attempt: 0 },
+          block_number: block.number(),
+        }),
+      )
+      .await;
+  }
+}
+
 // Handle a specific Substrate block, returning an error when it fails to get data
 // (not blocking / holding)
 #[allow(clippy::needless_pass_by_ref_mut)] // False positive?
-async fn handle_block<D: Db, CNT: Clone + Fn(&mut D, TributarySpec), Pro: Processors>(
+#[tracing::instrument(skip_all, fields(block = block.number(), hash = %hex::encode(block.hash())))]
+async fn handle_block<
+  D: Db,
+  CNT: Clone + Fn(&mut D, TributarySpec),
+  RT: Clone + Fn(ValidatorSet),
+  Pro: Processors,
+>(
   db: &mut SubstrateDb<D>,
   key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
   create_new_tributary: CNT,
+  retire_tributary: RT,
   processors: &Pro,
   serai: &Serai,
   block: Block,
@@ -226,21 +298,32 @@ async fn handle_block<D: Db, CNT: Clone + Fn(&mut D, TributarySpec), Pro: Proces
   let mut event_id = 0;
 
   // If a new validator set was activated, create tributary/inform processor to do a DKG
-  for new_set in serai.get_new_set_events(hash).await? {
+  let new_sets = if block.number() == 0 {
+    genesis_sets(serai, hash).await?
+  } else {
+    serai
+      .get_new_set_events(hash)
+      .await?
+      .into_iter()
+      .map(|new_set| {
+        let ValidatorSetsEvent::NewSet { set } = new_set else {
+          panic!("NewSet event wasn't NewSet: {new_set:?}");
+        };
+        set
+      })
+      .collect()
+  };
+  for set in new_sets {
     // Individually mark each event as handled so on reboot, we minimize duplicates
     // Additionally, if the Serai connection also fails 1/100 times, this means a block with 1000
     // events will successfully be incrementally handled (though the Serai connection should be
     // stable)
-    let ValidatorSetsEvent::NewSet { set } = new_set else {
-      panic!("NewSet event wasn't NewSet: {new_set:?}");
-    };
-
     if set.network == NetworkId::Serai {
       continue;
     }
 
     if !SubstrateDb::<D>::handled_event(&db.0, hash, event_id) {
-      log::info!("found fresh new set event {:?}", new_set);
+      tracing::info!("found fresh new set {:?}", set);
       handle_new_set(&mut db.0, key, create_new_tributary.clone(), serai, &block, set).await?;
       let mut txn = db.0.txn();
       SubstrateDb::<D>::handle_event(&mut txn, hash, event_id);
@@ -250,15 +333,30 @@ async fn handle_block<D: Db, CNT: Clone + Fn(&mut D, TributarySpec), Pro: Proces
   }
 
   // If a key pair was confirmed, inform the processor
-  for key_gen in serai.get_key_gen_events(hash).await? {
+  let key_gen_events = serai.get_key_gen_events(hash).await?;
+  let set_keys = !key_gen_events.is_empty();
+  for key_gen in key_gen_events {
     if !SubstrateDb::<D>::handled_event(&db.0, hash, event_id) {
-      log::info!("found fresh key gen event {:?}", key_gen);
+      tracing::info!("found fresh key gen event {:?}", key_gen);
       if let ValidatorSetsEvent::KeyGen { set, key_pair } = key_gen {
         // Immediately ensure this key pair is accessible to the tributary, before we fire any
         // events off of it
         let mut txn = db.0.txn();
         TributaryDb::<D>::set_key_pair(&mut txn, set, &key_pair);
+        // Once this set has keys, the set before it has been active for an entire session. Any
+        // sets before that have completed their lifecycle and can be retired
+        let mut retired = vec![];
+        for spec in MainDb::<D>::active_tributaries(&txn).1 {
+          if (spec.set().network == set.network) && ((spec.set().session.0 + 2) <= set.session.0) {
+            tracing::info!("retiring tributary {:?}", spec.set());
+            TributaryDb::<D>::retire(&mut txn, spec.genesis(), block.time().unwrap() / 1000);
+            retired.push(spec.set());
+          }
+        }
         txn.commit();
+        for set in retired {
+          retire_tributary(set);
+        }
 
         handle_key_gen(&mut db.0, processors, serai, &block, set, key_pair).await?;
       } else {
@@ -278,51 +376,61 @@ async fn handle_block<D: Db, CNT: Clone + Fn(&mut D, TributarySpec), Pro: Proces
   // (network, (hash, event_id)) remains valid as a unique ID for an event
   if !SubstrateDb::<D>::handled_event(&db.0, hash, event_id) {
     handle_batch_and_burns(processors, serai, &block).await?;
+    let mut txn = db.0.txn();
+    SubstrateDb::<D>::handle_event(&mut txn, hash, event_id);
+    txn.commit();
+  }
+  event_id += 1;
+
+  // Cosign this block, if it's due to be cosigned
+  if ((block.number() % COSIGN_DISTANCE) == 0) || set_keys {
+    if !SubstrateDb::<D>::handled_event(&db.0, hash, event_id) {
+      handle_cosign(&db.0, processors, &block).await;
+      let mut txn = db.0.txn();
+      SubstrateDb::<D>::handle_event(&mut txn, hash, event_id);
+      txn.commit();
+    }
   }
-  let mut txn = db.0.txn();
-  SubstrateDb::<D>::handle_event(&mut txn, hash, event_id);
-  txn.commit();
 
   Ok(())
 }
 
-pub async fn handle_new_blocks<D: Db, CNT: Clone + Fn(&mut D, TributarySpec), Pro: Processors>(
+/// Handle every finalized block from the next block to handle through the specified block.
+///
+/// Progress is saved after every block, and every event within a block is individually marked as
+/// handled, so if this errors, or the coordinator reboots, calling this again resumes from the
+/// last handled event without handling any event twice.
+pub async fn handle_new_blocks<
+  D: Db,
+  CNT: Clone + Fn(&mut D, TributarySpec),
+  RT: Clone + Fn(ValidatorSet),
+  Pro: Processors,
+>(
   db: &mut SubstrateDb<D>,
   key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
   create_new_tributary: CNT,
+  retire_tributary: RT,
   processors: &Pro,
   serai: &Serai,
-  next_block: &mut u64,
+  finality: &Finality,
+  latest: u64,
 ) -> Result<(), SeraiError> {
-  // Check if there's been a new Substrate block
-  let latest = serai.get_latest_block().await?;
-  let latest_number = latest.number();
-  if latest_number < *next_block {
-    return Ok(());
-  }
-  let mut latest = Some(latest);
-
-  for b in *next_block ..= latest_number {
-    log::info!("found substrate block {b}");
+  for b in db.next_block() ..= latest {
+    tracing::info!("found substrate block {b}");
+    let block = finality.block(b, db.last_block_hash()).await?;
+    let hash = block.hash();
     handle_block(
       db,
       key,
       create_new_tributary.clone(),
+      retire_tributary.clone(),
       processors,
       serai,
-      if b == latest_number {
-        latest.take().unwrap()
-      } else {
-        serai
-          .get_block_by_number(b)
-          .await?
-          .expect("couldn't get block before the latest finalized block")
-      },
+      block,
     )
     .await?;
-    *next_block += 1;
-    db.set_next_block(*next_block);
-    log::info!("handled substrate block {b}");
+    db.set_handled_block(b, hash);
+    tracing::info!("handled substrate block {b}");
   }
 
   Ok(())
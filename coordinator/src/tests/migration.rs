@@ -0,0 +1,38 @@
+use serai_db::{Get, DbTxn, Db, MemDb, export_snapshot, import_snapshot};
+
+use crate::migration::{Migration, ExportedDb, ImportedDb, mark_imported};
+
+#[test]
+fn export_restore() {
+  let mut db = MemDb::new();
+  let mut txn = db.txn();
+  txn.put(b"state", b"value");
+  // Exporting marks the DB before taking the snapshot, so the mark is included
+  let migration = Migration { key: [0xff; 32], next_block: 5, prior_block: Some([4; 32]) };
+  ExportedDb::set(&mut txn, &migration);
+  txn.commit();
+
+  let mut snapshot = vec![];
+  export_snapshot(&db, &mut snapshot).unwrap();
+
+  let mut restored = MemDb::new();
+  import_snapshot(&mut restored, &mut snapshot.as_slice()).unwrap();
+  mark_imported(&mut restored);
+
+  // The restored DB has the exported state, and is marked as imported so it may be run once
+  // verified
+  assert_eq!(restored.get(b"state"), Some(b"value".to_vec()));
+  assert_eq!(ExportedDb::get(&restored), Some(migration));
+  assert_eq!(ImportedDb::get(&restored), Some(()));
+
+  // The exported DB isn't, so it'll refuse to run again
+  assert_eq!(ImportedDb::get(&db), None);
+
+  // Importing a snapshot of a DB which wasn't exported for a migration doesn't mark it
+  let mut snapshot = vec![];
+  export_snapshot(&MemDb::new(), &mut snapshot).unwrap();
+  let mut restored = MemDb::new();
+  import_snapshot(&mut restored, &mut snapshot.as_slice()).unwrap();
+  mark_imported(&mut restored);
+  assert_eq!(ImportedDb::get(&restored), None);
+}
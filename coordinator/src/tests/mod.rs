@@ -4,6 +4,8 @@ use std::{
   collections::{VecDeque, HashMap},
 };
 
+use ciphersuite::{Ciphersuite, Ristretto};
+
 use serai_client::primitives::NetworkId;
 
 use processor_messages::CoordinatorMessage;
@@ -17,8 +19,14 @@ use crate::{
   TributaryP2p, P2pMessageKind, P2p,
 };
 
+pub mod sim;
+
 pub mod tributary;
 
+mod p2p;
+mod shutdown;
+mod migration;
+
 #[derive(Clone)]
 pub struct MemProcessors(pub Arc<RwLock<HashMap<NetworkId, VecDeque<CoordinatorMessage>>>>);
 impl MemProcessors {
@@ -84,6 +92,9 @@ impl P2p for LocalP2p {
       tokio::time::sleep(std::time::Duration::from_millis(100)).await;
     }
   }
+
+  // Every message is delivered to every validator, so there's nothing to subscribe to
+  async fn subscribe(&self, _: [u8; 32], _: Vec<<Ristretto as Ciphersuite>::G>) {}
 }
 
 #[async_trait]
@@ -0,0 +1,62 @@
+use core::ops::Deref;
+
+use zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use ciphersuite::{
+  group::{ff::Field, GroupEncoding},
+  Ciphersuite, Ristretto,
+};
+
+use libp2p::{identity::Keypair, PeerId};
+
+use crate::{
+  prove_identity, verify_identity, P2P_PROTOCOL_VERSION, MIN_P2P_PROTOCOL_VERSION,
+  p2p::{protocol_version, peer_protocol_version},
+};
+
+#[test]
+fn identity_proof() {
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let validator = Ristretto::generator() * key.deref();
+  let peer_id = PeerId::from(Keypair::generate_ed25519().public());
+
+  let proof = prove_identity(&key, &peer_id);
+  assert_eq!(verify_identity(&peer_id, &proof), Some(validator));
+
+  // The proof is bound to the peer ID it was made for
+  let other_peer_id = PeerId::from(Keypair::generate_ed25519().public());
+  assert_eq!(verify_identity(&other_peer_id, &proof), None);
+
+  // Claiming another validator's key with our signature fails
+  let other_validator = Ristretto::generator() * <Ristretto as Ciphersuite>::F::random(&mut OsRng);
+  let signature = proof.rsplit_once('/').unwrap().1;
+  let forged = format!("serai-coordinator/{}/{signature}", hex::encode(other_validator.to_bytes()));
+  assert_eq!(verify_identity(&peer_id, &forged), None);
+
+  // Malformed proofs, such as agent versions from other software, are rejected
+  assert_eq!(verify_identity(&peer_id, ""), None);
+  assert_eq!(verify_identity(&peer_id, "rust-libp2p/0.43.0"), None);
+  assert_eq!(verify_identity(&peer_id, &proof[.. proof.len() - 2]), None);
+}
+
+#[test]
+fn handshake_protocol_version() {
+  // Our own protocol version is understood
+  assert_eq!(peer_protocol_version(&protocol_version()), Some(P2P_PROTOCOL_VERSION));
+
+  // As are the versions advertised by prior and future releases
+  assert_eq!(peer_protocol_version("/serai/coordinator/1"), Some(1));
+  assert_eq!(peer_protocol_version("/serai/coordinator/2"), Some(2));
+  assert_eq!(peer_protocol_version("/serai/coordinator/65535"), Some(u16::MAX));
+
+  // Version 1 peers don't prove they're validators, and accordingly aren't supported
+  assert!(peer_protocol_version("/serai/coordinator/1").unwrap() < MIN_P2P_PROTOCOL_VERSION);
+
+  // Other protocols, including those of other libp2p software, aren't
+  assert_eq!(peer_protocol_version(""), None);
+  assert_eq!(peer_protocol_version("/ipfs/0.1.0"), None);
+  assert_eq!(peer_protocol_version("/serai/coordinator/"), None);
+  assert_eq!(peer_protocol_version("/serai/coordinator/v2"), None);
+  assert_eq!(peer_protocol_version("/serai/coordinator/65536"), None);
+}
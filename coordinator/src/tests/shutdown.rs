@@ -0,0 +1,87 @@
+use std::{
+  sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  },
+  time::Duration,
+};
+
+use tokio::time::sleep;
+
+use serai_test_utils::pause;
+
+use crate::shutdown::{Stage, Tasks};
+
+#[tokio::test]
+async fn supervisor_restarts_panicked_tasks() {
+  // Sleeps only cost virtual time, as nothing here reads the system's clock
+  pause();
+  let tasks = Tasks::new();
+
+  let runs = Arc::new(AtomicUsize::new(0));
+  tasks.supervise(Stage::Substrate, "panics once", {
+    let runs = runs.clone();
+    move || {
+      let runs = runs.clone();
+      async move {
+        if runs.fetch_add(1, Ordering::SeqCst) == 0 {
+          panic!("first run panicked");
+        }
+        // Run until stopped
+        loop {
+          sleep(Duration::from_secs(1)).await;
+        }
+      }
+    }
+  });
+
+  // The task should be restarted after the initial backoff
+  sleep(Duration::from_secs(2)).await;
+  assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+  tasks.shutdown().await;
+  sleep(Duration::from_secs(2)).await;
+  assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn shutdown_stops_in_order() {
+  pause();
+  let tasks = Tasks::new();
+
+  // Each task records when it was stopped, by when it was dropped
+  struct OnDrop(Arc<std::sync::Mutex<Vec<Stage>>>, Stage);
+  impl Drop for OnDrop {
+    fn drop(&mut self) {
+      self.0.lock().unwrap().push(self.1);
+    }
+  }
+
+  let stopped = Arc::new(std::sync::Mutex::new(vec![]));
+  for stage in [Stage::Processors, Stage::Substrate, Stage::P2p, Stage::Tributary] {
+    let on_drop = OnDrop(stopped.clone(), stage);
+    tasks.spawn(stage, "task", async move {
+      let _on_drop = on_drop;
+      loop {
+        sleep(Duration::from_secs(1)).await;
+      }
+    });
+  }
+
+  tasks.shutdown().await;
+  assert_eq!(
+    *stopped.lock().unwrap(),
+    vec![Stage::P2p, Stage::Tributary, Stage::Substrate, Stage::Processors]
+  );
+
+  // Tasks spawned after shutdown are never run
+  let ran = Arc::new(AtomicUsize::new(0));
+  tasks.spawn(Stage::P2p, "late", {
+    let ran = ran.clone();
+    async move {
+      ran.fetch_add(1, Ordering::SeqCst);
+    }
+  });
+  sleep(Duration::from_millis(100)).await;
+  assert_eq!(ran.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,329 @@
+use core::{cmp::Reverse, time::Duration};
+use std::{
+  sync::{Arc, Mutex},
+  time::Instant,
+  collections::{BinaryHeap, HashSet, HashMap, VecDeque},
+};
+
+use rand_core::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use async_trait::async_trait;
+
+use tokio::{task::JoinHandle, time::sleep};
+
+use crate::{TributaryP2p, P2pMessageKind, P2p};
+
+// How often the clock is advanced when following real time, and how often nodes check for
+// delivered messages
+const TICK: Duration = Duration::from_millis(10);
+
+/// The conditions of a simulated network.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Conditions {
+  /// The latency of every message.
+  pub latency: Duration,
+  /// The maximum additional latency of a message, chosen uniformly per message.
+  pub jitter: Duration,
+  /// The probability a message is dropped, in parts per million.
+  pub drop_rate: u32,
+}
+
+// A message in flight, ordered by when it'll be delivered and then by when it was sent
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct Envelope {
+  deliver_at: Duration,
+  sent: u64,
+  // The sender and recipient, with the incarnations they had when this was sent
+  from: (usize, usize),
+  to: (usize, usize),
+  msg: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Network {
+  // The time of the mock clock, since the network was created
+  now: Duration,
+  // A seeded RNG for drops and jitter, so a network is deterministic given the messages sent
+  rng: ChaCha20Rng,
+  conditions: Conditions,
+  // The incarnation of each node, incremented whenever it's restarted
+  incarnations: Vec<usize>,
+  killed: HashSet<usize>,
+  // The partition each node is in, if the network is partitioned
+  partitions: Option<HashMap<usize, usize>>,
+  sent: u64,
+  in_flight: BinaryHeap<Reverse<Envelope>>,
+  inboxes: Vec<VecDeque<(usize, Vec<u8>)>>,
+}
+
+impl Network {
+  fn connected(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+    let partition = |node| self.partitions.as_ref().map(|partitions| partitions[&node]);
+    (self.incarnations[from.0] == from.1) &&
+      (self.incarnations[to.0] == to.1) &&
+      (!self.killed.contains(&from.0)) &&
+      (!self.killed.contains(&to.0)) &&
+      (partition(from.0) == partition(to.0))
+  }
+}
+
+/// A simulated network between nodes, with controllable latency, drops, and partitions.
+///
+/// Messages are delivered according to a mock clock, which may be advanced manually or set to
+/// follow real time. Drops and latency are decided by a seeded RNG, and messages delivered at the
+/// same time are delivered in the order they were sent, so the network behaves identically
+/// whenever the same messages are sent in the same order.
+#[derive(Clone, Debug)]
+pub struct SimNetwork(Arc<Mutex<Network>>);
+
+impl SimNetwork {
+  pub fn new(nodes: usize, seed: u64) -> SimNetwork {
+    SimNetwork(Arc::new(Mutex::new(Network {
+      now: Duration::ZERO,
+      rng: ChaCha20Rng::seed_from_u64(seed),
+      conditions: Conditions::default(),
+      incarnations: vec![0; nodes],
+      killed: HashSet::new(),
+      partitions: None,
+      sent: 0,
+      in_flight: BinaryHeap::new(),
+      inboxes: vec![VecDeque::new(); nodes],
+    })))
+  }
+
+  /// The handle for the current incarnation of the specified node.
+  pub fn node(&self, i: usize) -> SimP2p {
+    let incarnation = self.0.lock().unwrap().incarnations[i];
+    SimP2p { network: self.clone(), i, incarnation }
+  }
+
+  /// The current time of the mock clock.
+  pub fn now(&self) -> Duration {
+    self.0.lock().unwrap().now
+  }
+
+  /// Advance the mock clock, delivering every message due by the new time.
+  pub fn advance(&self, by: Duration) {
+    let mut network = self.0.lock().unwrap();
+    network.now += by;
+    while network.in_flight.peek().is_some_and(|Reverse(msg)| msg.deliver_at <= network.now) {
+      let Reverse(Envelope { from, to, msg, .. }) = network.in_flight.pop().unwrap();
+      // Drop messages whose route was cut while they were in flight
+      if network.connected(from, to) {
+        network.inboxes[to.0].push_back((from.0, msg));
+      }
+    }
+  }
+
+  /// Advance the mock clock along with real time, as needed to run Tributaries, until the
+  /// returned task is aborted.
+  pub fn follow_real_time(&self) -> JoinHandle<()> {
+    let network = self.clone();
+    tokio::spawn(async move {
+      let mut last = Instant::now();
+      loop {
+        sleep(TICK).await;
+        let now = Instant::now();
+        network.advance(now.duration_since(last));
+        last = now;
+      }
+    })
+  }
+
+  pub fn set_conditions(&self, conditions: Conditions) {
+    self.0.lock().unwrap().conditions = conditions;
+  }
+
+  pub fn partition(&self, partitions: &[&[usize]]) {
+    let mut network = self.0.lock().unwrap();
+    let mut map = HashMap::new();
+    for (p, nodes) in partitions.iter().enumerate() {
+      for node in *nodes {
+        assert!(map.insert(*node, p).is_none(), "node was in multiple partitions");
+      }
+    }
+    assert_eq!(map.len(), network.incarnations.len(), "not every node was in a partition");
+    network.partitions = Some(map);
+  }
+
+  /// Remove any partitions and restore perfect network conditions.
+  pub fn heal(&self) {
+    let mut network = self.0.lock().unwrap();
+    network.partitions = None;
+    network.conditions = Conditions::default();
+  }
+
+  /// Cut a node off from the network.
+  pub fn kill(&self, i: usize) {
+    assert!(self.0.lock().unwrap().killed.insert(i), "killing a node which was already killed");
+  }
+
+  /// Reconnect a killed node, returning the handle for its new incarnation.
+  ///
+  /// Messages sent by, or to, its prior incarnation are dropped.
+  pub fn restart(&self, i: usize) -> SimP2p {
+    {
+      let mut network = self.0.lock().unwrap();
+      assert!(network.killed.remove(&i), "restarting a node which wasn't killed");
+      network.incarnations[i] += 1;
+      network.inboxes[i].clear();
+    }
+    self.node(i)
+  }
+
+  /// The amount of messages sent yet not delivered, including those which will be dropped.
+  pub fn in_flight(&self) -> usize {
+    self.0.lock().unwrap().in_flight.len()
+  }
+
+  fn send(&self, from: usize, incarnation: usize, to: usize, msg: Vec<u8>) {
+    let mut network = self.0.lock().unwrap();
+    let network = &mut *network;
+    let (from, to) = ((from, incarnation), (to, network.incarnations[to]));
+    if !network.connected(from, to) {
+      return;
+    }
+
+    // Always sample the RNG the same amount, so these conditions don't affect later messages
+    let dropped = (network.rng.next_u32() % 1_000_000) < network.conditions.drop_rate;
+    let jitter = u64::try_from(network.conditions.jitter.as_micros()).unwrap();
+    let jitter = Duration::from_micros(network.rng.next_u64() % (jitter + 1));
+    if dropped {
+      return;
+    }
+
+    let deliver_at = network.now + network.conditions.latency + jitter;
+    let sent = network.sent;
+    network.sent += 1;
+    network.in_flight.push(Reverse(Envelope { deliver_at, sent, from, to, msg }));
+  }
+
+  fn receive(&self, i: usize, incarnation: usize) -> Option<(usize, Vec<u8>)> {
+    let mut network = self.0.lock().unwrap();
+    if network.incarnations[i] != incarnation {
+      None?;
+    }
+    network.inboxes[i].pop_front()
+  }
+}
+
+/// A node's connection to a simulated network.
+#[derive(Clone, Debug)]
+pub struct SimP2p {
+  network: SimNetwork,
+  i: usize,
+  incarnation: usize,
+}
+
+#[async_trait]
+impl P2p for SimP2p {
+  type Id = usize;
+
+  async fn send_raw(&self, to: Self::Id, msg: Vec<u8>) {
+    self.network.send(self.i, self.incarnation, to, msg);
+  }
+
+  async fn broadcast_raw(&self, msg: Vec<u8>) {
+    let nodes = self.network.0.lock().unwrap().incarnations.len();
+    for to in (0 .. nodes).filter(|to| *to != self.i) {
+      self.network.send(self.i, self.incarnation, to, msg.clone());
+    }
+  }
+
+  async fn receive_raw(&self) -> (Self::Id, Vec<u8>) {
+    loop {
+      if let Some(res) = self.network.receive(self.i, self.incarnation) {
+        return res;
+      }
+      sleep(TICK).await;
+    }
+  }
+
+  // Every message is delivered to every node, so there's nothing to subscribe to
+  async fn subscribe(&self, _: [u8; 32], _: Vec<<Ristretto as Ciphersuite>::G>) {}
+}
+
+#[async_trait]
+impl TributaryP2p for SimP2p {
+  async fn broadcast(&self, genesis: [u8; 32], msg: Vec<u8>) {
+    <Self as P2p>::broadcast(self, P2pMessageKind::Tributary(genesis), msg).await
+  }
+}
+
+// Send a fixed series of messages over a network with the specified seed and conditions,
+// returning what each node received and when
+fn simulate(seed: u64, conditions: Conditions) -> Vec<Vec<(Duration, usize, Vec<u8>)>> {
+  let network = SimNetwork::new(4, seed);
+  network.set_conditions(conditions);
+
+  let mut received = vec![vec![]; 4];
+  for step in 0 .. 100u8 {
+    let from = usize::from(step) % 4;
+    for to in (0 .. 4).filter(|to| *to != from) {
+      network.send(from, 0, to, vec![step]);
+    }
+    network.advance(Duration::from_millis(10));
+    for (i, received) in received.iter_mut().enumerate() {
+      while let Some((from, msg)) = network.receive(i, 0) {
+        received.push((network.now(), from, msg));
+      }
+    }
+  }
+  received
+}
+
+#[test]
+fn sim_network_is_deterministic() {
+  let conditions = Conditions {
+    latency: Duration::from_millis(50),
+    jitter: Duration::from_millis(100),
+    drop_rate: 100_000,
+  };
+  let received = simulate(0, conditions);
+  assert_eq!(received, simulate(0, conditions));
+  // Different seeds should drop and delay different messages
+  assert!(received != simulate(1, conditions));
+
+  // Around 10% of messages should've been dropped, with some still in flight
+  let delivered = received.iter().map(Vec::len).sum::<usize>();
+  assert!((200 .. 290).contains(&delivered));
+
+  // Nothing should be delivered before its latency, and the jitter should reorder messages
+  for received in &received {
+    assert!(received.iter().all(|(time, _, msg)| {
+      *time >= (Duration::from_millis(10) * u32::from(msg[0])) + Duration::from_millis(50)
+    }));
+  }
+  assert!(received.iter().any(|received| received.windows(2).any(|msgs| msgs[0].2 > msgs[1].2)));
+}
+
+#[test]
+fn sim_network_partitions() {
+  let network = SimNetwork::new(4, 0);
+  network.partition(&[&[0, 1, 2], &[3]]);
+  for to in 1 .. 4 {
+    network.send(0, 0, to, vec![to.try_into().unwrap()]);
+  }
+  // Messages in flight when a node is killed are dropped, even if the node is restarted
+  network.set_conditions(Conditions { latency: Duration::from_secs(1), ..Default::default() });
+  network.send(0, 0, 2, vec![]);
+  network.kill(2);
+  network.advance(Duration::ZERO);
+  assert_eq!(network.receive(1, 0), Some((0, vec![1])));
+  assert_eq!(network.receive(2, 0), None);
+  assert_eq!(network.receive(3, 0), None);
+
+  network.restart(2);
+  network.advance(Duration::from_secs(1));
+  assert_eq!(network.receive(2, 1), None);
+  assert_eq!(network.in_flight(), 0);
+
+  // Once healed, the partitioned node should receive messages
+  network.heal();
+  network.send(0, 0, 3, vec![3]);
+  network.advance(Duration::ZERO);
+  assert_eq!(network.receive(3, 0), Some((0, vec![3])));
+}
@@ -0,0 +1,228 @@
+// Benchmarks for the Tributary's throughput, latency, and the time to complete a DKG
+//
+// These are ignored by default due to how long they take. Run them with
+// `cargo test --release -p serai-coordinator bench -- --ignored --nocapture --test-threads 1`
+
+use core::{future::Future, time::Duration};
+use std::{time::Instant, collections::HashMap};
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use tokio::time::sleep;
+
+use tributary::TransactionTrait;
+
+use crate::{
+  tributary::{Transaction, TributarySpec, SignData, dkg_confirmation_nonces},
+  tests::tributary::{new_validator_keys, new_spec, chaos::ChaosCluster},
+};
+
+// How often to check for newly included transactions, bounding the precision of latencies
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// The amount of transactions each validator publishes, kept under the Tributary's limit on how
+// many transactions an account may have in the mempool
+const TXS_PER_VALIDATOR: u32 = 40;
+
+// Run each configuration on its own runtime so the prior configuration's Tributaries are stopped
+fn run<F: Future>(future: F) -> F::Output {
+  let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+  let res = runtime.block_on(future);
+  runtime.shutdown_background();
+  res
+}
+
+async fn new_cluster(
+  validators: usize,
+  delay: Duration,
+) -> (Vec<Zeroizing<<Ristretto as Ciphersuite>::F>>, TributarySpec, ChaosCluster) {
+  let keys = new_validator_keys(&mut OsRng, validators);
+  let spec = new_spec(&mut OsRng, &keys);
+  let cluster = ChaosCluster::new(&keys, &spec).await;
+  cluster.delay(delay).await;
+  (keys, spec, cluster)
+}
+
+struct Inclusion {
+  blocks: usize,
+  elapsed: Duration,
+  latencies: Vec<Duration>,
+}
+
+impl Inclusion {
+  fn mean_latency(&self) -> Duration {
+    self.latencies.iter().sum::<Duration>() / u32::try_from(self.latencies.len()).unwrap()
+  }
+  fn max_latency(&self) -> Duration {
+    self.latencies.iter().max().copied().unwrap_or_default()
+  }
+}
+
+// Publish transactions, each via the specified node, and wait until the first node has included
+// all of them
+async fn include(
+  cluster: &ChaosCluster,
+  txs: Vec<(usize, Transaction)>,
+  timeout: Duration,
+) -> Inclusion {
+  let reader = cluster.tributary(0).reader();
+  let mut last = cluster.tributary(0).tip().await;
+
+  let start = Instant::now();
+  let mut pending = HashMap::new();
+  for (i, tx) in txs {
+    pending.insert(tx.hash(), Instant::now());
+    assert!(cluster.tributary(i).add_transaction(tx).await);
+  }
+
+  let mut blocks = 0;
+  let mut latencies = vec![];
+  while !pending.is_empty() {
+    assert!(start.elapsed() < timeout, "transactions weren't included in time");
+    sleep(POLL_INTERVAL).await;
+    while let Some(next) = reader.block_after(&last) {
+      blocks += 1;
+      for tx in reader.block(&next).unwrap().transactions {
+        if let Some(published) = pending.remove(&tx.hash()) {
+          latencies.push(published.elapsed());
+        }
+      }
+      last = next;
+    }
+  }
+  Inclusion { blocks, elapsed: start.elapsed(), latencies }
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+  let mut res = vec![0; len];
+  OsRng.fill_bytes(&mut res);
+  res
+}
+
+#[test]
+#[ignore]
+fn bench_throughput() {
+  let configs = [
+    (4, Duration::ZERO, 64),
+    (4, Duration::ZERO, 4096),
+    (4, Duration::ZERO, 32768),
+    (7, Duration::ZERO, 4096),
+    (10, Duration::ZERO, 4096),
+    (4, Duration::from_millis(200), 4096),
+    (4, Duration::from_millis(1000), 4096),
+  ];
+
+  println!("validators | delay (ms) | payload | blocks/s | txs/s | mean latency | max latency");
+  for (validators, delay, payload) in configs {
+    let inclusion = run(async move {
+      let (keys, spec, cluster) = new_cluster(validators, delay).await;
+
+      let mut txs = vec![];
+      for (i, key) in keys.iter().enumerate() {
+        for nonce in 0 .. TXS_PER_VALIDATOR {
+          let mut plan = [0; 32];
+          OsRng.fill_bytes(&mut plan);
+          let data = SignData {
+            plan,
+            attempt: 0,
+            data: random_bytes(payload),
+            signed: Transaction::empty_signed(),
+          };
+          let mut tx = Transaction::SignPreprocess(data);
+          tx.sign(&mut OsRng, spec.genesis(), key, nonce);
+          txs.push((i, tx));
+        }
+      }
+
+      include(&cluster, txs, Duration::from_secs(600)).await
+    });
+
+    let secs = inclusion.elapsed.as_secs_f64();
+    println!(
+      "{validators} | {} | {payload} | {:.3} | {:.3} | {:?} | {:?}",
+      delay.as_millis(),
+      (inclusion.blocks as f64) / secs,
+      (inclusion.latencies.len() as f64) / secs,
+      inclusion.mean_latency(),
+      inclusion.max_latency(),
+    );
+  }
+}
+
+#[test]
+#[ignore]
+fn bench_dkg() {
+  let configs = [
+    (4, Duration::ZERO),
+    (7, Duration::ZERO),
+    (10, Duration::ZERO),
+    (4, Duration::from_millis(500)),
+    (10, Duration::from_millis(500)),
+  ];
+
+  println!("validators | delay (ms) | commitments | shares | confirmations | total");
+  for (validators, delay) in configs {
+    let rounds = run(async move {
+      let (keys, spec, cluster) = new_cluster(validators, delay).await;
+      let timeout = Duration::from_secs(300);
+
+      // Each round is only started once the prior round has been included, as the processor
+      // requires the prior round's data to create the next round's
+      let commitments = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+          let mut tx =
+            Transaction::DkgCommitments(0, random_bytes(256), Transaction::empty_signed());
+          tx.sign(&mut OsRng, spec.genesis(), key, 0);
+          (i, tx)
+        })
+        .collect();
+      let commitments = include(&cluster, commitments, timeout).await;
+
+      let shares = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+          let mut tx = Transaction::DkgShares {
+            attempt: 0,
+            shares: (1 .. validators).map(|_| random_bytes(256)).collect(),
+            confirmation_nonces: dkg_confirmation_nonces(key, &spec, 0),
+            signed: Transaction::empty_signed(),
+          };
+          tx.sign(&mut OsRng, spec.genesis(), key, 1);
+          (i, tx)
+        })
+        .collect();
+      let shares = include(&cluster, shares, timeout).await;
+
+      // The Tributary doesn't validate the confirmation shares, so random ones suffice
+      let confirmations = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+          let mut share = [0; 32];
+          OsRng.fill_bytes(&mut share);
+          let mut tx = Transaction::DkgConfirmed(0, share, Transaction::empty_signed());
+          tx.sign(&mut OsRng, spec.genesis(), key, 2);
+          (i, tx)
+        })
+        .collect();
+      let confirmations = include(&cluster, confirmations, timeout).await;
+
+      [commitments.elapsed, shares.elapsed, confirmations.elapsed]
+    });
+
+    println!(
+      "{validators} | {} | {:?} | {:?} | {:?} | {:?}",
+      delay.as_millis(),
+      rounds[0],
+      rounds[1],
+      rounds[2],
+      rounds.iter().sum::<Duration>(),
+    );
+  }
+}
@@ -31,16 +31,23 @@ use crate::{
   tests::LocalP2p,
 };
 
-pub fn new_keys<R: RngCore + CryptoRng>(
+pub fn new_validator_keys<R: RngCore + CryptoRng>(
   rng: &mut R,
+  validators: usize,
 ) -> Vec<Zeroizing<<Ristretto as Ciphersuite>::F>> {
   let mut keys = vec![];
-  for _ in 0 .. 5 {
+  for _ in 0 .. validators {
     keys.push(Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut *rng)));
   }
   keys
 }
 
+pub fn new_keys<R: RngCore + CryptoRng>(
+  rng: &mut R,
+) -> Vec<Zeroizing<<Ristretto as Ciphersuite>::F>> {
+  new_validator_keys(rng, 5)
+}
+
 pub fn new_spec<R: RngCore + CryptoRng>(
   rng: &mut R,
   keys: &[Zeroizing<<Ristretto as Ciphersuite>::F>],
@@ -83,6 +90,7 @@ pub async fn new_tributaries(
         MemDb::new(),
         spec.genesis(),
         spec.start_time(),
+        spec.consensus_params(),
         key.clone(),
         spec.validators(),
         p2p[i].clone(),
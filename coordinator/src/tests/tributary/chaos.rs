@@ -0,0 +1,421 @@
+use core::time::Duration;
+use std::{
+  sync::Arc,
+  time::SystemTime,
+  collections::{HashSet, HashMap},
+};
+
+use zeroize::Zeroizing;
+use rand_core::{RngCore, OsRng};
+
+use ciphersuite::{Ciphersuite, Ristretto};
+use frost::Participant;
+
+use tokio::{sync::broadcast, task::JoinHandle, time::sleep};
+
+use serai_db::MemDb;
+
+use processor_messages::{
+  key_gen::{self, KeyGenId},
+  CoordinatorMessage,
+};
+
+use tributary::{TransactionTrait, ConsensusParams, Tributary};
+
+use crate::{
+  tributary::{TributaryDb, Transaction, TributarySpec, SignData, scanner::handle_new_blocks},
+  ActiveTributary, TributaryEvent, handle_p2p, heartbeat_tributaries,
+  tests::{
+    MemProcessors,
+    sim::{Conditions, SimNetwork, SimP2p},
+    tributary::{new_keys, new_spec},
+  },
+};
+
+struct Node {
+  key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+  db: MemDb,
+  tributary: Arc<Tributary<MemDb, Transaction, SimP2p>>,
+  // Held so the tasks listening for new Tributaries don't see their channel close
+  _new_tributary: broadcast::Sender<TributaryEvent<MemDb, SimP2p>>,
+  tasks: Vec<JoinHandle<()>>,
+}
+
+// A cluster of coordinators, each running a Tributary, the P2P handler, and the heartbeat
+// protocol, over a simulated network which can have faults injected into it
+pub struct ChaosCluster {
+  spec: TributarySpec,
+  network: SimNetwork,
+  // The task advancing the network's clock with real time, as the Tributaries run in real time
+  clock: JoinHandle<()>,
+  nodes: Vec<Node>,
+  // Killed nodes are retained as dropping them would close channels their tasks still listen on
+  killed: Vec<Node>,
+}
+
+impl Drop for ChaosCluster {
+  fn drop(&mut self) {
+    self.clock.abort();
+  }
+}
+
+impl ChaosCluster {
+  pub async fn new(
+    keys: &[Zeroizing<<Ristretto as Ciphersuite>::F>],
+    spec: &TributarySpec,
+    seed: u64,
+  ) -> ChaosCluster {
+    let network = SimNetwork::new(keys.len(), seed);
+    let mut cluster = ChaosCluster {
+      spec: spec.clone(),
+      clock: network.follow_real_time(),
+      network,
+      nodes: vec![],
+      killed: vec![],
+    };
+    for (i, key) in keys.iter().enumerate() {
+      let node = cluster.start(key.clone(), MemDb::new(), cluster.network.node(i)).await;
+      cluster.nodes.push(node);
+    }
+    cluster
+  }
+
+  async fn start(
+    &self,
+    key: Zeroizing<<Ristretto as Ciphersuite>::F>,
+    db: MemDb,
+    p2p: SimP2p,
+  ) -> Node {
+    let tributary = Arc::new(
+      Tributary::<_, Transaction, _>::new(
+        db.clone(),
+        self.spec.genesis(),
+        self.spec.start_time(),
+        self.spec.consensus_params(),
+        key.clone(),
+        self.spec.validators(),
+        p2p.clone(),
+      )
+      .await
+      .unwrap(),
+    );
+
+    let (new_tributary, p2p_recv) = broadcast::channel(1);
+    let heartbeat_recv = new_tributary.subscribe();
+    let tasks = vec![
+      tokio::spawn(handle_p2p(Ristretto::generator() * *key, p2p.clone(), p2p_recv)),
+      tokio::spawn(heartbeat_tributaries(p2p, heartbeat_recv)),
+    ];
+    new_tributary
+      .send(TributaryEvent::NewTributary(ActiveTributary {
+        spec: self.spec.clone(),
+        tributary: tributary.clone(),
+      }))
+      .map_err(|_| "failed to send ActiveTributary")
+      .unwrap();
+
+    Node { key, db, tributary, _new_tributary: new_tributary, tasks }
+  }
+
+  pub fn network(&self) -> &SimNetwork {
+    &self.network
+  }
+
+  pub fn tributary(&self, i: usize) -> &Tributary<MemDb, Transaction, ChaosP2p> {
+    &self.nodes[i].tributary
+  }
+
+  // Kill a node, cutting it off from the network and stopping its tasks
+  // Its Tendermint machine will continue running, yet will never be able to communicate
+  pub fn kill(&mut self, i: usize) {
+    self.network.kill(i);
+    for task in &self.nodes[i].tasks {
+      task.abort();
+    }
+  }
+
+  // Restart a killed node from its database
+  pub async fn restart(&mut self, i: usize) {
+    let p2p = self.network.restart(i);
+    let node = self.start(self.nodes[i].key.clone(), self.nodes[i].db.clone(), p2p).await;
+    let killed = core::mem::replace(&mut self.nodes[i], node);
+    self.killed.push(killed);
+  }
+
+  pub fn partition(&self, partitions: &[&[usize]]) {
+    self.network.partition(partitions);
+  }
+
+  pub fn delay(&self, latency: Duration) {
+    self.network.set_conditions(Conditions { latency, ..Default::default() });
+  }
+
+  // Remove any partitions and delays
+  pub fn heal(&self) {
+    self.network.heal();
+  }
+
+  // Wait until the specified node has all of the specified transactions on-chain
+  pub async fn wait_for_txs(&self, i: usize, txs: &[Transaction], timeout: Duration) {
+    let hashes = txs.iter().map(Transaction::hash).collect::<HashSet<_>>();
+    let reader = self.nodes[i].tributary.reader();
+    let timeout = SystemTime::now() + timeout;
+
+    let mut found = HashSet::new();
+    let mut last = self.spec.genesis();
+    loop {
+      while let Some(next) = reader.block_after(&last) {
+        for tx in reader.block(&next).unwrap().transactions {
+          found.insert(tx.hash());
+        }
+        last = next;
+      }
+
+      if hashes.is_subset(&found) {
+        return;
+      }
+      assert!(SystemTime::now() < timeout, "node {i} didn't include the transactions in time");
+      sleep(Duration::from_secs(1)).await;
+    }
+  }
+}
+
+fn block_time() -> Duration {
+  Duration::from_millis(ConsensusParams::default().block_time().into())
+}
+
+#[tokio::test]
+async fn chaos_dkg() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  // The following kills/partitions assume exactly one node may be faulty
+  assert_eq!(spec.n() - spec.t(), 1);
+
+  let mut cluster = ChaosCluster::new(&keys, &spec, 0).await;
+
+  let txs = keys
+    .iter()
+    .map(|key| {
+      let mut commitments = vec![0; 256];
+      OsRng.fill_bytes(&mut commitments);
+
+      let mut tx = Transaction::DkgCommitments(0, commitments, Transaction::empty_signed());
+      tx.sign(&mut OsRng, spec.genesis(), key, 0);
+      tx
+    })
+    .collect::<Vec<_>>();
+
+  // Kill the last node before it publishes its commitments
+  let last = keys.len() - 1;
+  cluster.kill(last);
+
+  // Publish the first two commitments while the rest of the network is healthy
+  for (i, tx) in txs.iter().enumerate().take(2) {
+    assert!(cluster.tributary(i).add_transaction(tx.clone()).await);
+  }
+
+  // Partition the network such that neither side has t nodes online, then publish the next
+  // commitments within the second partition
+  cluster.partition(&[&[0, 1], &[2, 3, last]]);
+  for (i, tx) in txs.iter().enumerate().take(last).skip(2) {
+    assert!(cluster.tributary(i).add_transaction(tx.clone()).await);
+  }
+
+  // Allow any messages sent prior to the partition to be handled, then verify the Tributary halted
+  sleep(block_time()).await;
+  let tip = cluster.tributary(0).tip().await;
+  sleep(3 * block_time()).await;
+  assert_eq!(cluster.tributary(0).tip().await, tip);
+
+  // Heal the partition yet add latency, and restart the killed node so it can publish its
+  // commitments
+  cluster.heal();
+  cluster.delay(Duration::from_millis(500));
+  cluster.restart(last).await;
+  assert!(cluster.tributary(last).add_transaction(txs[last].clone()).await);
+
+  // Every node which remained online should include every commitment
+  for i in 0 .. last {
+    cluster.wait_for_txs(i, &txs, 20 * block_time()).await;
+  }
+  // The restarted node should sync, via the heartbeat protocol, and include them as well
+  cluster.wait_for_txs(last, &txs, 30 * block_time()).await;
+
+  // Verify every node's scanner completes the DKG's commitments round
+  let commitments: HashMap<_, _> = txs
+    .iter()
+    .enumerate()
+    .map(|(i, tx)| {
+      let Transaction::DkgCommitments(_, commitments, _) = tx else {
+        panic!("txs had non-commitments")
+      };
+      (Participant::new((i + 1).try_into().unwrap()).unwrap(), commitments.clone())
+    })
+    .collect();
+
+  for (i, key) in keys.iter().enumerate() {
+    let processors = MemProcessors::new();
+    handle_new_blocks::<_, _, _, _, _, _, SimP2p>(
+      &mut TributaryDb(MemDb::new()),
+      key,
+      |_, _, _, _, _| async { panic!("provided TX caused recognized_id to be called") },
+      &processors,
+      |_, _| async { panic!("test tried to publish a new Serai TX") },
+      &spec,
+      &cluster.tributary(i).reader(),
+    )
+    .await;
+
+    let mut msgs = processors.0.write().await;
+    let msgs = msgs.get_mut(&spec.set().network).unwrap();
+    let mut commitments = commitments.clone();
+    commitments.remove(&Participant::new((i + 1).try_into().unwrap()).unwrap());
+    assert_eq!(
+      msgs.pop_front().unwrap(),
+      CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::Commitments {
+        id: KeyGenId { set: spec.set(), attempt: 0 },
+        commitments
+      })
+    );
+    assert!(msgs.is_empty());
+  }
+}
+
+#[tokio::test]
+async fn chaos_signing() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  assert_eq!(spec.n() - spec.t(), 1);
+
+  let mut cluster = ChaosCluster::new(&keys, &spec, 0).await;
+
+  let mut plan = [0; 32];
+  OsRng.fill_bytes(&mut plan);
+  let sign_txs = |share: bool| {
+    keys
+      .iter()
+      .map(|key| {
+        let mut data = vec![0; 64];
+        OsRng.fill_bytes(&mut data);
+        let data = SignData { plan, attempt: 0, data, signed: Transaction::empty_signed() };
+
+        let mut tx =
+          if share { Transaction::SignShare(data) } else { Transaction::SignPreprocess(data) };
+        tx.sign(&mut OsRng, spec.genesis(), key, u32::from(share));
+        tx
+      })
+      .collect::<Vec<_>>()
+  };
+  let preprocesses = sign_txs(false);
+  let shares = sign_txs(true);
+
+  // Publish every preprocess, killing the second node immediately after it publishes its own
+  for (i, tx) in preprocesses.iter().enumerate() {
+    assert!(cluster.tributary(i).add_transaction(tx.clone()).await);
+    if i == 1 {
+      cluster.kill(i);
+    }
+  }
+
+  // Add latency, which should slow, yet not halt, the Tributary
+  cluster.delay(Duration::from_secs(1));
+  for i in (0 .. keys.len()).filter(|i| *i != 1) {
+    cluster.wait_for_txs(i, &preprocesses, 20 * block_time()).await;
+  }
+
+  // Publish the shares of every online node
+  for (i, tx) in shares.iter().enumerate().filter(|(i, _)| *i != 1) {
+    assert!(cluster.tributary(i).add_transaction(tx.clone()).await);
+  }
+
+  // Restart the killed node and, once it has synced, have it publish its share
+  cluster.heal();
+  cluster.restart(1).await;
+  cluster.wait_for_txs(1, &preprocesses, 30 * block_time()).await;
+  assert!(cluster.tributary(1).add_transaction(shares[1].clone()).await);
+
+  for i in 0 .. keys.len() {
+    cluster.wait_for_txs(i, &shares, 30 * block_time()).await;
+  }
+}
+
+#[tokio::test]
+async fn chaos_dkg_shares_partition() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  assert_eq!(spec.n() - spec.t(), 1);
+
+  let mut cluster = ChaosCluster::new(&keys, &spec, 1).await;
+
+  let commitments = keys
+    .iter()
+    .map(|key| {
+      let mut commitments = vec![0; 256];
+      OsRng.fill_bytes(&mut commitments);
+
+      let mut tx = Transaction::DkgCommitments(0, commitments, Transaction::empty_signed());
+      tx.sign(&mut OsRng, spec.genesis(), key, 0);
+      tx
+    })
+    .collect::<Vec<_>>();
+  for (i, tx) in commitments.iter().enumerate() {
+    assert!(cluster.tributary(i).add_transaction(tx.clone()).await);
+  }
+  for i in 0 .. keys.len() {
+    cluster.wait_for_txs(i, &commitments, 20 * block_time()).await;
+  }
+
+  let shares = keys
+    .iter()
+    .enumerate()
+    .map(|(k, key)| {
+      let mut shares = vec![];
+      for i in 0 .. keys.len() {
+        if i != k {
+          let mut share = vec![0; 256];
+          OsRng.fill_bytes(&mut share);
+          shares.push(share);
+        }
+      }
+
+      let mut tx = Transaction::DkgShares {
+        attempt: 0,
+        shares,
+        confirmation_nonces: crate::tributary::dkg_confirmation_nonces(key, &spec, 0),
+        signed: Transaction::empty_signed(),
+      };
+      tx.sign(&mut OsRng, spec.genesis(), key, 1);
+      tx
+    })
+    .collect::<Vec<_>>();
+
+  // Partition validator 3 off during the share phase, with the rest of the network lossy and
+  // jittery
+  let partitioned = 3;
+  cluster.partition(&[&[0, 1, 2], &[partitioned]]);
+  cluster.network().set_conditions(Conditions {
+    latency: Duration::from_millis(100),
+    jitter: Duration::from_millis(400),
+    drop_rate: 50_000,
+  });
+  let tip = cluster.tributary(partitioned).tip().await;
+  for (i, tx) in shares.iter().enumerate() {
+    assert!(cluster.tributary(i).add_transaction(tx.clone()).await);
+  }
+
+  // The majority should continue, including every share but the partitioned validator's
+  let majority_shares =
+    shares.iter().enumerate().filter(|(i, _)| *i != partitioned).map(|(_, tx)| tx.clone());
+  let majority_shares = majority_shares.collect::<Vec<_>>();
+  for i in (0 .. keys.len()).filter(|i| *i != partitioned) {
+    cluster.wait_for_txs(i, &majority_shares, 30 * block_time()).await;
+  }
+  // The partitioned validator shouldn't have been able to make progress on its own
+  assert_eq!(cluster.tributary(partitioned).tip().await, tip);
+
+  // Once healed, the partitioned validator should sync and its share should be included
+  cluster.heal();
+  for i in 0 .. keys.len() {
+    cluster.wait_for_txs(i, &shares, 30 * block_time()).await;
+  }
+}
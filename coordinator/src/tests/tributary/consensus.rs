@@ -0,0 +1,50 @@
+use rand_core::OsRng;
+
+use tokio::time::sleep;
+
+use tributary::ConsensusParams;
+
+use crate::{
+  tributary::TributarySpec,
+  tests::tributary::{new_keys, new_spec, new_tributaries, run_tributaries},
+};
+
+#[tokio::test]
+async fn consensus_params_test() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  assert_eq!(spec.consensus_params(), ConsensusParams::default());
+  // Specs with the default parameters don't encode them, remaining compatible with prior specs
+  assert_eq!(spec.serialize().len(), 32 + 8 + 4 + 1 + 4 + (keys.len() * (32 + 8)));
+  assert_eq!(TributarySpec::read::<&[u8]>(&mut spec.serialize().as_ref()).unwrap(), spec);
+
+  // Block times must be a whole number of seconds
+  assert!(ConsensusParams::new(100, 100).is_none());
+  assert!(ConsensusParams::new(0, 0).is_none());
+  assert!(ConsensusParams::new(u32::MAX, u32::MAX).is_none());
+
+  // One-second blocks, the fastest possible
+  let params = ConsensusParams::new(250, 250).unwrap();
+  let fast_spec = spec.clone().with_consensus_params(params);
+  assert_eq!(fast_spec.consensus_params(), params);
+  assert_eq!(TributarySpec::read::<&[u8]>(&mut fast_spec.serialize().as_ref()).unwrap(), fast_spec);
+  // Invalid parameters should be rejected when reading a spec
+  let mut invalid = fast_spec.serialize();
+  let latency_time = invalid.len() - 4;
+  invalid[latency_time ..].copy_from_slice(&100u32.to_le_bytes());
+  assert!(TributarySpec::read::<&[u8]>(&mut invalid.as_ref()).is_err());
+  // The parameters are committed to by the genesis
+  assert!(fast_spec.genesis() != spec.genesis());
+
+  let tributaries = new_tributaries(&keys, &fast_spec).await;
+  let block_time = tributaries[0].1.block_time();
+  assert_eq!(block_time.as_millis(), 1000);
+
+  // Run the tributaries in the background
+  tokio::spawn(run_tributaries(tributaries.clone()));
+
+  // Twenty blocks of time is less than four blocks at the default parameters, yet we should have
+  // several blocks by now
+  sleep(20 * block_time).await;
+  assert!(tributaries[0].1.block_number().await >= 5);
+}
@@ -0,0 +1,173 @@
+use core::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+use zeroize::Zeroizing;
+use rand_core::RngCore;
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use serai_test_utils::test_rng;
+
+use serai_client::{
+  primitives::NetworkId,
+  validator_sets::primitives::{Session, ValidatorSet},
+  Public,
+};
+
+use serai_db::{DbTxn, Db, MemDb};
+
+use processor_messages::{
+  coordinator::{self, cosign_block_msg},
+  sign::SignId,
+  CoordinatorMessage,
+};
+
+use crate::{
+  substrate::{Cosign, SubstrateDb},
+  tributary::{
+    SignData, Transaction, TributarySpec, TributaryDb, handle_application_tx,
+    scanner::RecognizedIdType,
+  },
+  tests::{
+    MemProcessors,
+    tributary::{new_keys, new_spec},
+  },
+};
+
+// Handle a transaction, returning the messages it caused to be sent to the processor and the IDs
+// recognized_id was called with
+async fn handle(
+  db: &mut MemDb,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  spec: &TributarySpec,
+  tx: Transaction,
+) -> (Vec<CoordinatorMessage>, Vec<(RecognizedIdType, [u8; 32])>) {
+  let processors = MemProcessors::new();
+  let recognized = Arc::new(Mutex::new(vec![]));
+  let mut txn = db.txn();
+  handle_application_tx::<MemDb, _, _, _, _, _>(
+    tx,
+    spec,
+    &processors,
+    |_, _| async { panic!("cosigning caused a Serai TX to be published") },
+    key,
+    |_, _, id_type, id, _| {
+      let recognized = recognized.clone();
+      async move { recognized.lock().unwrap().push((id_type, id)) }
+    },
+    &mut txn,
+  )
+  .await;
+  txn.commit();
+  let mut msgs = processors.0.write().await;
+  let recognized = recognized.lock().unwrap().clone();
+  (msgs.remove(&spec.set().network).map(Vec::from).unwrap_or(vec![]), recognized)
+}
+
+fn cosign_data(
+  spec: &TributarySpec,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  block: [u8; 32],
+  data: Vec<u8>,
+  share: bool,
+) -> Transaction {
+  let data = SignData { plan: block, attempt: 0, data, signed: Transaction::empty_signed() };
+  let mut tx =
+    if share { Transaction::CosignShare(data) } else { Transaction::CosignPreprocess(data) };
+  tx.sign(&mut test_rng(), spec.genesis(), key, 0);
+  tx
+}
+
+#[tokio::test]
+async fn cosign_test() {
+  let mut rng = test_rng();
+  let keys = new_keys(&mut rng);
+  let spec = new_spec(&mut rng, &keys);
+  let t = usize::from(spec.t());
+
+  let mut block = [0; 32];
+  rng.fill_bytes(&mut block);
+  let key_pair = (Public([0xff; 32]), vec![0xff; 33].try_into().unwrap());
+
+  let mut db = MemDb::new();
+  {
+    let mut txn = db.txn();
+    TributaryDb::<MemDb>::set_key_pair(&mut txn, spec.set(), &key_pair);
+    txn.commit();
+  }
+  // Cosigns are signed by the set's Substrate key
+  let id = SignId { key: key_pair.0 .0.to_vec(), id: block, attempt: 0 };
+
+  // Once the block to cosign is provided, we should be told to publish our preprocess
+  let (msgs, recognized) =
+    handle(&mut db, &keys[0], &spec, Transaction::CosignSubstrateBlock(block)).await;
+  assert!(msgs.is_empty());
+  assert_eq!(recognized, vec![(RecognizedIdType::Cosign, block)]);
+
+  let participant = |key: &Zeroizing<<Ristretto as Ciphersuite>::F>| {
+    spec.i(Ristretto::generator() * key.deref()).unwrap()
+  };
+  let data = |key: &Zeroizing<<Ristretto as Ciphersuite>::F>, label: u8| {
+    let mut data = vec![label; 32];
+    data[0] = u8::try_from(u16::from(participant(key))).unwrap();
+    data
+  };
+
+  for key in &keys[.. (t - 1)] {
+    let tx = cosign_data(&spec, key, block, data(key, 0), false);
+    assert!(handle(&mut db, &keys[0], &spec, tx).await.0.is_empty());
+  }
+  let tx = cosign_data(&spec, &keys[t - 1], block, data(&keys[t - 1], 0), false);
+  assert_eq!(
+    handle(&mut db, &keys[0], &spec, tx).await.0,
+    vec![CoordinatorMessage::Coordinator(coordinator::CoordinatorMessage::CosignPreprocesses {
+      id: id.clone(),
+      preprocesses: keys[1 .. t].iter().map(|key| (participant(key), data(key, 0))).collect(),
+    })]
+  );
+
+  // Someone who isn't in the signing set shouldn't have their share used
+  let late = &keys[t];
+  let tx = cosign_data(&spec, late, block, data(late, 1), true);
+  assert!(handle(&mut db, &keys[0], &spec, tx).await.0.is_empty());
+
+  for key in &keys[.. (t - 1)] {
+    let tx = cosign_data(&spec, key, block, data(key, 1), true);
+    assert!(handle(&mut db, &keys[0], &spec, tx).await.0.is_empty());
+  }
+  let tx = cosign_data(&spec, &keys[t - 1], block, data(&keys[t - 1], 1), true);
+  assert_eq!(
+    handle(&mut db, &keys[0], &spec, tx).await.0,
+    vec![CoordinatorMessage::Coordinator(coordinator::CoordinatorMessage::CosignShares {
+      id,
+      shares: keys[1 .. t]
+        .iter()
+        .map(|key| (participant(key), data(key, 1).try_into().unwrap()))
+        .collect(),
+    })]
+  );
+}
+
+#[test]
+fn cosign_db() {
+  let mut db = MemDb::new();
+  let set = ValidatorSet { session: Session(0), network: NetworkId::Bitcoin };
+  let cosign = |block_number| Cosign { set, block_number, block: [0xaa; 32], signature: [0; 64] };
+
+  assert!(SubstrateDb::<MemDb>::latest_cosign(&db, NetworkId::Bitcoin).is_none());
+
+  // Cosigns completed out of order shouldn't replace a later cosign as the latest
+  let mut txn = db.txn();
+  SubstrateDb::<MemDb>::save_cosign(&mut txn, &cosign(200));
+  SubstrateDb::<MemDb>::save_cosign(&mut txn, &cosign(100));
+  txn.commit();
+  assert_eq!(SubstrateDb::<MemDb>::latest_cosign(&db, NetworkId::Bitcoin), Some(cosign(200)));
+  assert_eq!(SubstrateDb::<MemDb>::cosign(&db, NetworkId::Bitcoin, 100), Some(cosign(100)));
+  assert!(SubstrateDb::<MemDb>::latest_cosign(&db, NetworkId::Monero).is_none());
+
+  // An invalid signature shouldn't verify
+  assert!(!cosign(100).verify(&(Public([0xff; 32]), vec![0xff; 33].try_into().unwrap())));
+  // The message is bound to both the block number and hash
+  assert!(cosign_block_msg(100, [0xaa; 32]) != cosign_block_msg(101, [0xaa; 32]));
+  assert!(cosign_block_msg(100, [0xaa; 32]) != cosign_block_msg(100, [0xbb; 32]));
+}
@@ -1,8 +1,7 @@
-use core::time::Duration;
 use std::collections::HashMap;
 
 use zeroize::Zeroizing;
-use rand_core::{RngCore, OsRng};
+use rand_core::RngCore;
 
 use scale::Decode;
 
@@ -11,7 +10,7 @@ use frost::Participant;
 
 use sp_runtime::traits::Verify;
 
-use tokio::time::sleep;
+use serai_test_utils::{test_rng, wait_until};
 
 use serai_db::{DbTxn, Db, MemDb};
 
@@ -30,10 +29,25 @@ use crate::{
   },
 };
 
+// Wait until every Tributary has a block, so scanners over any of them will see it
+async fn wait_for_block_sync(
+  tributaries: &[(LocalP2p, Tributary<MemDb, Transaction, LocalP2p>)],
+  block: [u8; 32],
+) {
+  let timeout = tributaries[0].1.block_time() * 10;
+  assert!(
+    wait_until(timeout, || async {
+      tributaries.iter().all(|(_, tributary)| tributary.reader().block(&block).is_some())
+    })
+    .await
+  );
+}
+
 #[tokio::test]
 async fn dkg_test() {
-  let keys = new_keys(&mut OsRng);
-  let spec = new_spec(&mut OsRng, &keys);
+  let mut rng = test_rng();
+  let keys = new_keys(&mut rng);
+  let spec = new_spec(&mut rng, &keys);
 
   let tributaries = new_tributaries(&keys, &spec).await;
 
@@ -45,10 +59,10 @@ async fn dkg_test() {
   for key in &keys {
     let attempt = 0;
     let mut commitments = vec![0; 256];
-    OsRng.fill_bytes(&mut commitments);
+    rng.fill_bytes(&mut commitments);
 
     let mut tx = Transaction::DkgCommitments(attempt, commitments, Transaction::empty_signed());
-    tx.sign(&mut OsRng, spec.genesis(), key, 0);
+    tx.sign(&mut rng, spec.genesis(), key, 0);
     txs.push(tx);
   }
 
@@ -105,8 +119,8 @@ async fn dkg_test() {
   // Publish the last commitment
   let block_before_tx = tributaries[0].1.tip().await;
   assert!(tributaries[0].1.add_transaction(txs[0].clone()).await);
-  wait_for_tx_inclusion(&tributaries[0].1, block_before_tx, txs[0].hash()).await;
-  sleep(Duration::from_secs(Tributary::<MemDb, Transaction, LocalP2p>::block_time().into())).await;
+  let block = wait_for_tx_inclusion(&tributaries[0].1, block_before_tx, txs[0].hash()).await;
+  wait_for_block_sync(&tributaries, block).await;
 
   // Verify the scanner emits a KeyGen::Commitments message
   handle_new_blocks::<_, _, _, _, _, _, LocalP2p>(
@@ -164,7 +178,7 @@ async fn dkg_test() {
     for i in 0 .. keys.len() {
       if i != k {
         let mut share = vec![0; 256];
-        OsRng.fill_bytes(&mut share);
+        rng.fill_bytes(&mut share);
         shares.push(share);
       }
     }
@@ -175,7 +189,7 @@ async fn dkg_test() {
       confirmation_nonces: crate::tributary::dkg_confirmation_nonces(key, &spec, 0),
       signed: Transaction::empty_signed(),
     };
-    tx.sign(&mut OsRng, spec.genesis(), key, 1);
+    tx.sign(&mut rng, spec.genesis(), key, 1);
     txs.push(tx);
   }
 
@@ -206,8 +220,8 @@ async fn dkg_test() {
   // Publish the final set of shares
   let block_before_tx = tributaries[0].1.tip().await;
   assert!(tributaries[0].1.add_transaction(txs[0].clone()).await);
-  wait_for_tx_inclusion(&tributaries[0].1, block_before_tx, txs[0].hash()).await;
-  sleep(Duration::from_secs(Tributary::<MemDb, Transaction, LocalP2p>::block_time().into())).await;
+  let block = wait_for_tx_inclusion(&tributaries[0].1, block_before_tx, txs[0].hash()).await;
+  wait_for_block_sync(&tributaries, block).await;
 
   // Each scanner should emit a distinct shares message
   let shares_for = |i: usize| {
@@ -275,9 +289,9 @@ async fn dkg_test() {
 
   // Send DkgConfirmed
   let mut substrate_key = [0; 32];
-  OsRng.fill_bytes(&mut substrate_key);
-  let mut network_key = vec![0; usize::try_from((OsRng.next_u64() % 32) + 32).unwrap()];
-  OsRng.fill_bytes(&mut network_key);
+  rng.fill_bytes(&mut substrate_key);
+  let mut network_key = vec![0; usize::try_from((rng.next_u64() % 32) + 32).unwrap()];
+  rng.fill_bytes(&mut network_key);
   let key_pair = (serai_client::Public(substrate_key), network_key.try_into().unwrap());
 
   let mut txs = vec![];
@@ -291,7 +305,7 @@ async fn dkg_test() {
     txn.commit();
 
     let mut tx = Transaction::DkgConfirmed(attempt, share, Transaction::empty_signed());
-    tx.sign(&mut OsRng, spec.genesis(), key, 2);
+    tx.sign(&mut rng, spec.genesis(), key, 2);
     txs.push(tx);
   }
   let block_before_tx = tributaries[0].1.tip().await;
@@ -0,0 +1,142 @@
+use rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use proptest::{prelude::*, collection::vec, option};
+
+use frost::Participant;
+
+use tributary::{ReadWrite, Signed, tests::random_signed};
+
+use crate::tributary::{SignData, Transaction};
+
+// Lengths at the boundaries of what the encoding supports, along with a range of typical lengths
+fn u16_len() -> impl Strategy<Value = usize> {
+  prop_oneof![Just(0), Just(1), Just(usize::from(u16::MAX)), 0 .. 1024usize]
+}
+
+fn signed() -> impl Strategy<Value = Signed> {
+  any::<[u8; 32]>().prop_map(|seed| random_signed(&mut ChaCha20Rng::from_seed(seed)))
+}
+
+fn sign_data() -> impl Strategy<Value = SignData> {
+  (any::<[u8; 32]>(), any::<u32>(), u16_len().prop_flat_map(|len| vec(any::<u8>(), len)), signed())
+    .prop_map(|(plan, attempt, data, signed)| SignData { plan, attempt, data, signed })
+}
+
+fn shares() -> impl Strategy<Value = Vec<Vec<u8>>> {
+  // Shares must all be of the same length
+  // Bound the total size by only allowing many shares when each is small
+  prop_oneof![(0 ..= 4usize, u16_len()), (Just(usize::from(u16::MAX)), 0 ..= 1usize)]
+    .prop_flat_map(|(quantity, len)| vec(vec(any::<u8>(), len), quantity))
+}
+
+fn transaction() -> impl Strategy<Value = Transaction> {
+  prop_oneof![
+    (any::<u32>(), u16_len().prop_flat_map(|len| vec(any::<u8>(), len)), signed()).prop_map(
+      |(attempt, commitments, signed)| Transaction::DkgCommitments(attempt, commitments, signed)
+    ),
+    (any::<u32>(), shares(), any::<[u8; 32]>(), any::<[u8; 32]>(), signed()).prop_map(
+      |(attempt, shares, nonces_a, nonces_b, signed)| {
+        let mut confirmation_nonces = [0; 64];
+        confirmation_nonces[.. 32].copy_from_slice(&nonces_a);
+        confirmation_nonces[32 ..].copy_from_slice(&nonces_b);
+        Transaction::DkgShares { attempt, shares, confirmation_nonces, signed }
+      }
+    ),
+    (any::<u32>(), any::<[u8; 32]>(), signed())
+      .prop_map(|(attempt, share, signed)| Transaction::DkgConfirmed(attempt, share, signed)),
+    (
+      any::<u32>(),
+      1 ..= u16::MAX,
+      option::of(u16_len().prop_flat_map(|len| vec(any::<u8>(), len))),
+      signed()
+    )
+      .prop_map(|(attempt, faulty, blame, signed)| Transaction::InvalidDkgShare {
+        attempt,
+        faulty: Participant::new(faulty).unwrap(),
+        blame,
+        signed,
+      }),
+    (any::<u32>(), 1 ..= u16::MAX, signed()).prop_map(|(attempt, faulty, signed)| {
+      Transaction::InvalidDkgCommitments {
+        attempt,
+        faulty: Participant::new(faulty).unwrap(),
+        signed,
+      }
+    }),
+    (any::<u32>(), 1 ..= u16::MAX, 1 ..= u16::MAX, signed()).prop_map(
+      |(attempt, accuser, faulty, signed)| Transaction::DkgBlame {
+        attempt,
+        accuser: Participant::new(accuser).unwrap(),
+        faulty: Participant::new(faulty).unwrap(),
+        signed,
+      }
+    ),
+    (any::<[u8; 32]>(), any::<[u8; 32]>())
+      .prop_map(|(block, batch)| Transaction::Batch(block, batch)),
+    any::<u64>().prop_map(Transaction::SubstrateBlock),
+    sign_data().prop_map(Transaction::BatchPreprocess),
+    sign_data().prop_map(Transaction::BatchShare),
+    sign_data().prop_map(Transaction::SignPreprocess),
+    sign_data().prop_map(Transaction::SignShare),
+    (any::<[u8; 32]>(), vec(any::<u8>(), 0 ..= 255), signed(), signed()).prop_map(
+      |(plan, tx_hash, first_signer, signature)| Transaction::SignCompleted {
+        plan,
+        tx_hash,
+        first_signer: first_signer.signer,
+        signature: signature.signature,
+      }
+    ),
+    (any::<u32>(), signed()).prop_map(|(epoch, signed)| Transaction::Heartbeat(epoch, signed)),
+    any::<[u8; 32]>().prop_map(Transaction::CosignSubstrateBlock),
+    sign_data().prop_map(Transaction::CosignPreprocess),
+    sign_data().prop_map(Transaction::CosignShare),
+  ]
+}
+
+// If an encoding is successfully read, it must be the canonical encoding of what was read
+fn assert_canonical(encoding: &[u8]) {
+  let mut reader = encoding;
+  if let Ok(tx) = Transaction::read(&mut reader) {
+    let read = encoding.len() - reader.len();
+    assert_eq!(tx.serialize(), &encoding[.. read]);
+  }
+}
+
+proptest! {
+  #![proptest_config(ProptestConfig::with_cases(64))]
+
+  #[test]
+  fn transaction_round_trip(tx in transaction()) {
+    let encoding = tx.serialize();
+    let mut reader = encoding.as_slice();
+    prop_assert_eq!(Transaction::read(&mut reader).unwrap(), tx);
+    // The entire encoding should've been read
+    prop_assert!(reader.is_empty());
+  }
+
+  #[test]
+  fn truncated_transaction(tx in transaction(), cut in any::<prop::sample::Index>()) {
+    let encoding = tx.serialize();
+    let cut = cut.index(encoding.len());
+    prop_assert!(Transaction::read::<&[u8]>(&mut &encoding[.. cut]).is_err());
+  }
+
+  #[test]
+  fn mutated_transaction(
+    tx in transaction(),
+    mutations in vec((any::<prop::sample::Index>(), any::<u8>()), 1 ..= 4),
+  ) {
+    let mut encoding = tx.serialize();
+    for (i, byte) in mutations {
+      let i = i.index(encoding.len());
+      encoding[i] = byte;
+    }
+    assert_canonical(&encoding);
+  }
+
+  #[test]
+  fn arbitrary_transaction(encoding in vec(any::<u8>(), 0 .. 2048)) {
+    assert_canonical(&encoding);
+  }
+}
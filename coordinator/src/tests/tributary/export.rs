@@ -0,0 +1,80 @@
+use rand_core::{RngCore, OsRng};
+
+use tokio::time::sleep;
+
+use serai_db::MemDb;
+
+use tributary::{
+  transaction::Transaction as TransactionTrait, TributaryReader, export_chain, import_chain,
+};
+
+use crate::{
+  tributary::Transaction,
+  tests::tributary::{new_keys, new_spec, new_tributaries, run_tributaries, wait_for_tx_inclusion},
+};
+
+#[tokio::test]
+async fn export_import_test() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+
+  let tributaries = new_tributaries(&keys, &spec).await;
+  tokio::spawn(run_tributaries(tributaries.clone()));
+
+  // Include a signed transaction, and a provided transaction, so both are exported
+  let block_before_tx = tributaries[0].1.tip().await;
+  let mut commitments = vec![0; 256];
+  OsRng.fill_bytes(&mut commitments);
+  let mut tx = Transaction::DkgCommitments(0, commitments, Transaction::empty_signed());
+  tx.sign(&mut OsRng, spec.genesis(), &keys[0], 0);
+  assert!(tributaries[0].1.add_transaction(tx.clone()).await);
+  wait_for_tx_inclusion(&tributaries[0].1, block_before_tx, tx.hash()).await;
+
+  let block_before_tx = tributaries[0].1.tip().await;
+  let provided = Transaction::SubstrateBlock(0);
+  for (_, tributary) in &tributaries {
+    tributary.provide_transaction(provided.clone()).await.unwrap();
+  }
+  wait_for_tx_inclusion(&tributaries[0].1, block_before_tx, provided.hash()).await;
+  sleep(tributaries[0].1.block_time()).await;
+
+  let reader = tributaries[0].1.reader();
+  let mut export = vec![];
+  let blocks = export_chain(&reader, &mut export).unwrap();
+  assert!(blocks >= 2);
+
+  let import = |export: &[u8]| {
+    let db = MemDb::new();
+    let res = import_chain::<_, Transaction, _>(
+      db.clone(),
+      spec.genesis(),
+      spec.validators(),
+      spec.consensus_params(),
+      &mut &export[..],
+    );
+    (db, res)
+  };
+
+  // The imported chain should be identical to the exported chain
+  let (db, res) = import(&export);
+  assert_eq!(res.unwrap(), blocks);
+  let imported = TributaryReader::<_, Transaction>::new(db, spec.genesis());
+  let mut last = spec.genesis();
+  for _ in 0 .. blocks {
+    let hash = reader.block_after(&last).unwrap();
+    assert_eq!(imported.block_after(&last), Some(hash));
+    assert_eq!(imported.block(&hash), reader.block(&hash));
+    assert_eq!(imported.commit(&hash), reader.commit(&hash));
+    last = hash;
+  }
+  assert_eq!(imported.block_after(&last), None);
+
+  // Truncated exports shouldn't be imported
+  assert!(import(&export[.. (export.len() - 1)]).1.is_err());
+
+  // Nor should exports whose last commit was tampered with
+  let mut tampered = export.clone();
+  let last_byte = tampered.len() - 2;
+  tampered[last_byte] ^= 1;
+  assert!(import(&tampered).1.is_err());
+}
@@ -7,17 +7,9 @@ use ciphersuite::{Ciphersuite, Ristretto};
 
 use tokio::{sync::broadcast, time::sleep};
 
-use serai_db::MemDb;
-
-use tributary::Tributary;
-
 use crate::{
-  tributary::Transaction,
   ActiveTributary, handle_p2p,
-  tests::{
-    LocalP2p,
-    tributary::{new_keys, new_spec, new_tributaries},
-  },
+  tests::tributary::{new_keys, new_spec, new_tributaries},
 };
 
 #[tokio::test]
@@ -44,8 +36,7 @@ async fn handle_p2p_test() {
 
   // After two blocks of time, we should have a new block
   // We don't wait one block of time as we may have missed the chance for this block
-  sleep(Duration::from_secs((2 * Tributary::<MemDb, Transaction, LocalP2p>::block_time()).into()))
-    .await;
+  sleep(2 * tributaries[0].block_time()).await;
   let tip = tributaries[0].tip().await;
   assert!(tip != spec.genesis());
 
@@ -57,7 +48,7 @@ async fn handle_p2p_test() {
   }
 
   // Then after another block of time, we should have yet another new block
-  sleep(Duration::from_secs(Tributary::<MemDb, Transaction, LocalP2p>::block_time().into())).await;
+  sleep(tributaries[0].block_time()).await;
   let new_tip = tributaries[0].tip().await;
   assert!(new_tip != tip);
   sleep(Duration::from_secs(1)).await;
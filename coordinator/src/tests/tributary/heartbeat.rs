@@ -0,0 +1,168 @@
+use core::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+use zeroize::Zeroizing;
+
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+use frost::dkg::ThresholdParams;
+
+use serai_test_utils::test_rng;
+
+use serai_db::{DbTxn, Db, MemDb};
+
+use processor_messages::{
+  key_gen::{self, KeyGenId},
+  CoordinatorMessage,
+};
+
+use crate::{
+  tributary::{
+    Transaction, TributarySpec, TributaryDb, Topic, HEARTBEAT_EPOCH_BLOCKS,
+    OFFLINE_MISSED_HEARTBEATS, handle_heartbeat_epoch, handle_dkg_timeout, handle_application_tx,
+    scanner::RecognizedIdType,
+  },
+  tests::{
+    MemProcessors,
+    tributary::{new_keys, new_spec},
+  },
+};
+
+// The heartbeats recognized_id was called to publish, as (epoch, nonce)
+type Published = Arc<Mutex<Vec<(u32, u32)>>>;
+
+// Handle a block with no transactions, as the scanner would
+async fn block(
+  db: &mut MemDb,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  spec: &TributarySpec,
+  published: &Published,
+  processors: &MemProcessors,
+  dkg: bool,
+) {
+  let mut txn = db.txn();
+  handle_heartbeat_epoch::<MemDb, _, _>(&mut txn, spec, |_, genesis, id_type, id, nonce| {
+    let published = published.clone();
+    let expected = spec.genesis();
+    async move {
+      assert_eq!(genesis, expected);
+      assert_eq!(id_type, RecognizedIdType::Heartbeat);
+      published.lock().unwrap().push((u32::from_le_bytes(id[.. 4].try_into().unwrap()), nonce));
+    }
+  })
+  .await;
+  if dkg {
+    handle_dkg_timeout::<MemDb, _>(&mut txn, spec, key, processors).await;
+  }
+  txn.commit();
+}
+
+async fn heartbeat(
+  db: &mut MemDb,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  spec: &TributarySpec,
+  epoch: u32,
+) {
+  let mut tx = Transaction::Heartbeat(epoch, Transaction::empty_signed());
+  tx.sign(&mut test_rng(), spec.genesis(), key, 0);
+
+  let mut txn = db.txn();
+  handle_application_tx::<MemDb, _, _, _, _, _>(
+    tx,
+    spec,
+    &MemProcessors::new(),
+    |_, _| async { panic!("heartbeat caused a Serai TX to be published") },
+    key,
+    |_, _, _, _, _| async { panic!("heartbeat caused recognized_id to be called") },
+    &mut txn,
+  )
+  .await;
+  txn.commit();
+}
+
+fn validator(key: &Zeroizing<<Ristretto as Ciphersuite>::F>) -> [u8; 32] {
+  (Ristretto::generator() * key.deref()).to_bytes()
+}
+
+#[tokio::test]
+async fn heartbeat_test() {
+  let mut rng = test_rng();
+  let keys = new_keys(&mut rng);
+  let spec = new_spec(&mut rng, &keys);
+  let genesis = spec.genesis();
+  let (online, offline) = keys.split_at(keys.len() - 1);
+  let offline = &offline[0];
+
+  let mut db = MemDb::new();
+  let published = Published::default();
+  let processors = MemProcessors::new();
+
+  // Epoch 0 has no heartbeats, so nothing should be published until it ends
+  for _ in 0 .. (HEARTBEAT_EPOCH_BLOCKS - 1) {
+    block(&mut db, &keys[0], &spec, &published, &processors, false).await;
+  }
+  assert!(published.lock().unwrap().is_empty());
+
+  // Run through the epochs until the last validator is considered offline, with everyone else
+  // heartbeating every epoch
+  for epoch in 1 ..= OFFLINE_MISSED_HEARTBEATS {
+    block(&mut db, &keys[0], &spec, &published, &processors, false).await;
+    // Nonce 0 is reserved for the first DKG attempt, and nothing else was allocated a nonce
+    assert_eq!(published.lock().unwrap().last(), Some(&(epoch, epoch)));
+
+    for key in online {
+      heartbeat(&mut db, key, &spec, epoch).await;
+    }
+    // A heartbeat for a prior epoch shouldn't count
+    if epoch != 1 {
+      heartbeat(&mut db, offline, &spec, epoch - 1).await;
+    }
+
+    for _ in 0 .. (HEARTBEAT_EPOCH_BLOCKS - 1) {
+      block(&mut db, &keys[0], &spec, &published, &processors, false).await;
+    }
+    assert_eq!(
+      TributaryDb::<MemDb>::missed_heartbeats(&db, genesis, validator(offline)),
+      epoch - 1
+    );
+  }
+
+  // Ending the last epoch should flag the last validator as offline, penalizing them
+  block(&mut db, &keys[0], &spec, &published, &processors, false).await;
+  assert!(TributaryDb::<MemDb>::offline(&db, genesis, validator(offline)));
+  assert_eq!(TributaryDb::<MemDb>::slash_points(&db, genesis, validator(offline)), 1);
+  for key in online {
+    assert!(!TributaryDb::<MemDb>::offline(&db, genesis, validator(key)));
+    assert_eq!(TributaryDb::<MemDb>::slash_points(&db, genesis, validator(key)), 0);
+  }
+
+  // Since the DKG requires the offline validator, it shouldn't be re-attempted after timing out
+  let epoch = OFFLINE_MISSED_HEARTBEATS + 1;
+  for key in online {
+    heartbeat(&mut db, key, &spec, epoch).await;
+  }
+  for _ in 0 .. (HEARTBEAT_EPOCH_BLOCKS * 2) {
+    block(&mut db, &keys[0], &spec, &published, &processors, true).await;
+  }
+  assert_eq!(TributaryDb::<MemDb>::attempt(&db, genesis, Topic::Dkg), Some(0));
+  assert!(processors.0.read().await.is_empty());
+
+  // Once they're back online, the DKG should be re-attempted
+  // Their heartbeat for the current epoch is all which is needed to be considered online
+  let epoch = TributaryDb::<MemDb>::heartbeat_blocks(&db, genesis) / HEARTBEAT_EPOCH_BLOCKS;
+  heartbeat(&mut db, offline, &spec, epoch).await;
+  assert!(!TributaryDb::<MemDb>::offline(&db, genesis, validator(offline)));
+  block(&mut db, &keys[0], &spec, &published, &processors, true).await;
+  assert_eq!(TributaryDb::<MemDb>::attempt(&db, genesis, Topic::Dkg), Some(1));
+  assert_eq!(
+    processors.0.write().await.get_mut(&spec.set().network).unwrap().pop_front().unwrap(),
+    CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::GenerateKey {
+      id: KeyGenId { set: spec.set(), attempt: 1 },
+      params: ThresholdParams::new(
+        spec.t(),
+        spec.n(),
+        spec.i(Ristretto::generator() * keys[0].deref()).unwrap()
+      )
+      .unwrap(),
+    })
+  );
+}
@@ -0,0 +1,89 @@
+use core::ops::Deref;
+use std::sync::Arc;
+
+use rand_core::{RngCore, OsRng};
+
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+
+use serai_db::{DbTxn, Db, MemDb};
+
+use crate::{
+  ActiveTributary,
+  inspect::{InspectedTributary, InspectedEvidence, Inspector},
+  tributary::{Transaction, TributaryDb},
+  tests::tributary::{new_keys, new_spec, new_tributaries, run_tributaries, wait_for_tx_inclusion},
+};
+
+#[tokio::test]
+async fn inspect_test() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  let tributaries = new_tributaries(&keys, &spec).await;
+  let tributary = &tributaries[0].1;
+
+  let mut db = MemDb::new();
+  let inspector = Inspector::new(db.clone());
+  inspector
+    .add(ActiveTributary { spec: spec.clone(), tributary: Arc::new(tributary.clone()) })
+    .await;
+
+  let genesis = hex::encode(spec.genesis());
+  assert_eq!(
+    inspector.tributaries().await,
+    vec![InspectedTributary {
+      genesis: genesis.clone(),
+      network: "Bitcoin".to_string(),
+      session: 0
+    }]
+  );
+
+  // Unknown tributaries, and invalid hashes, should return None
+  assert!(inspector.tip(&hex::encode([0xff; 32])).await.is_none());
+  assert!(inspector.tip("invalid").await.is_none());
+  assert!(inspector.mempool(&hex::encode([0xff; 32])).await.is_none());
+  assert!(inspector.evidence(&hex::encode([0xff; 32])).await.is_none());
+  assert!(inspector.block(&genesis, &hex::encode([0xff; 32])).await.is_none());
+
+  assert_eq!(inspector.tip(&genesis).await.unwrap().hash, genesis);
+  assert!(inspector.mempool(&genesis).await.unwrap().is_empty());
+  assert!(inspector.evidence(&genesis).await.unwrap().is_empty());
+
+  // Only the first evidence against a validator should be saved
+  let equivocator = (Ristretto::generator() * keys[1].deref()).to_bytes();
+  {
+    let mut txn = db.txn();
+    assert!(TributaryDb::<MemDb>::save_evidence(&mut txn, spec.genesis(), equivocator, &[1]));
+    assert!(!TributaryDb::<MemDb>::save_evidence(&mut txn, spec.genesis(), equivocator, &[2]));
+    txn.commit();
+  }
+  assert_eq!(
+    inspector.evidence(&genesis).await.unwrap(),
+    vec![InspectedEvidence { validator: hex::encode(equivocator), evidence: "01".to_string() }]
+  );
+
+  // Since the tributaries aren't yet communicating, a transaction should remain in the mempool
+  let mut commitments = vec![0; 256];
+  OsRng.fill_bytes(&mut commitments);
+  let mut tx = Transaction::DkgCommitments(0, commitments, Transaction::empty_signed());
+  tx.sign(&mut OsRng, spec.genesis(), &keys[0], 0);
+  assert!(tributary.add_transaction(tx.clone()).await);
+
+  let mempool = inspector.mempool(&genesis).await.unwrap();
+  assert_eq!(mempool.len(), 1);
+  assert_eq!(mempool[0].hash, hex::encode(tx.hash()));
+  assert!(mempool[0].transaction.contains("DkgCommitments"));
+
+  // Once included, it should be in the block and no longer in the mempool
+  let block_before_tx = tributary.tip().await;
+  tokio::spawn(run_tributaries(tributaries.clone()));
+  let block = wait_for_tx_inclusion(tributary, block_before_tx, tx.hash()).await;
+
+  let inspected = inspector.block(&genesis, &hex::encode(block)).await.unwrap();
+  assert_eq!(inspected.hash, hex::encode(block));
+  assert_eq!(inspected.parent, hex::encode(tributary.reader().block(&block).unwrap().parent()));
+  assert!(inspected.time.is_some());
+  assert!(inspected.transactions.iter().any(|included| included.hash == hex::encode(tx.hash())));
+
+  assert!(inspector.tip(&genesis).await.unwrap().number > 0);
+  assert!(inspector.mempool(&genesis).await.unwrap().is_empty());
+}
@@ -0,0 +1,69 @@
+use rand_core::{RngCore, OsRng};
+
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+
+use serai_db::{Get, DbTxn, Db, MemDb};
+
+use crate::tributary::{
+  Topic, DataSpecification, FatallySlashed, TributaryDb, DKG_COMMITMENTS, BATCH_SHARE,
+};
+
+fn legacy_key(item: &'static [u8], key: &[u8]) -> Vec<u8> {
+  MemDb::key(b"coordinator_tributary", item, key)
+}
+
+// The key for a DataSpecification, as it was prior to create_db
+fn legacy_data_spec(genesis: [u8; 32], label: &[u8], id: &[u8], data_label: &str) -> Vec<u8> {
+  let mut res = genesis.to_vec();
+  res.push(u8::try_from(label.len()).unwrap());
+  res.extend(label);
+  res.push(u8::try_from(id.len()).unwrap());
+  res.extend(id);
+  res.push(u8::try_from(data_label.len()).unwrap());
+  res.extend(data_label.as_bytes());
+  res.extend(1u32.to_le_bytes());
+  res
+}
+
+#[test]
+fn migrate_legacy_keys() {
+  let mut genesis = [0; 32];
+  OsRng.fill_bytes(&mut genesis);
+  let mut batch = [0; 32];
+  OsRng.fill_bytes(&mut batch);
+  let signer = <Ristretto as Ciphersuite>::generator() *
+    <Ristretto as Ciphersuite>::random_nonzero_F(&mut OsRng);
+
+  let mut db = MemDb::new();
+  let mut txn = db.txn();
+  txn.put(legacy_key(b"block", &genesis), [0xff; 32]);
+  txn.put(legacy_key(b"fatal_slash", &genesis), [[1; 32], [2; 32]].concat());
+  txn.put(legacy_key(b"plan_ids", &[genesis.as_slice(), &5u64.to_le_bytes()].concat()), [3; 32]);
+  let batch_topic = [genesis.as_slice(), &[5], b"batch", &[32], &batch].concat();
+  txn.put(legacy_key(b"attempt", &batch_topic), 2u32.to_le_bytes());
+  let dkg_data = legacy_data_spec(genesis, b"dkg", &[], DKG_COMMITMENTS);
+  txn.put(legacy_key(b"data_received", &dkg_data), 1u16.to_le_bytes());
+  let batch_data = legacy_data_spec(genesis, b"batch", &batch, BATCH_SHARE);
+  txn.put(legacy_key(b"data", &[batch_data, signer.to_bytes().to_vec()].concat()), [4; 16]);
+  // An entry not from the legacy layout should be left as-is
+  txn.put(b"unrelated", b"value");
+  txn.commit();
+
+  TributaryDb::migrate_legacy_keys(&mut db);
+
+  // Running the migration again should be a no-op
+  TributaryDb::migrate_legacy_keys(&mut db);
+
+  assert_eq!(TributaryDb::new(db.clone()).last_block(genesis), [0xff; 32]);
+  assert_eq!(FatallySlashed::get(&db, genesis), Some(vec![[1; 32], [2; 32]]));
+  assert_eq!(TributaryDb::<MemDb>::plan_ids(&db, genesis, 5), Some(vec![[3; 32]]));
+  assert_eq!(TributaryDb::<MemDb>::attempt(&db, genesis, Topic::Batch(batch)), Some(2));
+  let dkg_spec = DataSpecification { topic: Topic::Dkg, label: DKG_COMMITMENTS, attempt: 1 };
+  assert_eq!(TributaryDb::<MemDb>::data_received(&db, genesis, &dkg_spec), 1);
+  let batch_spec = DataSpecification { topic: Topic::Batch(batch), label: BATCH_SHARE, attempt: 1 };
+  assert_eq!(TributaryDb::<MemDb>::data(&db, genesis, &batch_spec, signer), Some(vec![4; 16]));
+
+  // The legacy entries should've been removed
+  assert!(db.get(legacy_key(b"block", &genesis)).is_none());
+  assert_eq!(db.get(b"unrelated"), Some(b"value".to_vec()));
+}
@@ -10,12 +10,24 @@ mod chain;
 pub use chain::*;
 
 mod tx;
+mod encoding;
+mod consensus;
 
 mod dkg;
+mod replay;
+mod heartbeat;
+mod sign;
+mod cosign;
+mod export;
 // TODO: Test the other transactions
 
 mod handle_p2p;
 mod sync;
+mod chaos;
+mod bench;
+mod tributaries;
+mod inspect;
+mod legacy_keys;
 
 fn random_u32<R: RngCore>(rng: &mut R) -> u32 {
   u32::try_from(rng.next_u64() >> 32).unwrap()
@@ -121,4 +133,14 @@ fn serialize_transaction() {
       signature: random_signed(&mut OsRng).signature,
     });
   }
+
+  test_read_write(Transaction::Heartbeat(random_u32(&mut OsRng), random_signed(&mut OsRng)));
+
+  {
+    let mut block = [0; 32];
+    OsRng.fill_bytes(&mut block);
+    test_read_write(Transaction::CosignSubstrateBlock(block));
+  }
+  test_read_write(Transaction::CosignPreprocess(random_sign_data(&mut OsRng)));
+  test_read_write(Transaction::CosignShare(random_sign_data(&mut OsRng)));
 }
@@ -0,0 +1,175 @@
+use core::time::Duration;
+use std::sync::Arc;
+
+use zeroize::Zeroizing;
+use rand_core::RngCore;
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use tokio::{sync::RwLock, time::sleep};
+
+use serai_test_utils::test_rng;
+
+use serai_client::primitives::NetworkId;
+use serai_db::MemDb;
+
+use processor_messages::CoordinatorMessage;
+
+use tributary::{TransactionTrait, Tributary};
+
+use crate::{
+  processors::{Message, Processors},
+  tributary::{TributaryDb, Transaction, TributarySpec, scanner::handle_new_blocks},
+  tests::{
+    MemProcessors, LocalP2p,
+    tributary::{new_keys, new_spec, new_tributaries, run_tributaries, wait_for_tx_inclusion},
+  },
+};
+
+// Processors which never returns from the kill_at-th send, simulating the coordinator being killed
+// while sending it
+#[derive(Clone)]
+struct KilledProcessors {
+  sent: Arc<RwLock<usize>>,
+  kill_at: usize,
+  inner: MemProcessors,
+}
+
+#[async_trait::async_trait]
+impl Processors for KilledProcessors {
+  async fn send(&self, network: NetworkId, msg: CoordinatorMessage) {
+    let mut sent = self.sent.write().await;
+    if *sent == self.kill_at {
+      drop(sent);
+      core::future::pending::<()>().await;
+    }
+    *sent += 1;
+    self.inner.send(network, msg).await;
+  }
+  async fn recv(&mut self, _: NetworkId) -> Message {
+    todo!()
+  }
+  async fn ack(&mut self, _: Message) {
+    todo!()
+  }
+}
+
+async fn scan<Pro: Processors>(
+  scanner_db: &mut TributaryDb<MemDb>,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  spec: &TributarySpec,
+  tributary: &Tributary<MemDb, Transaction, LocalP2p>,
+  processors: &Pro,
+) {
+  handle_new_blocks::<_, _, _, _, _, _, LocalP2p>(
+    scanner_db,
+    key,
+    |_, _, _, _, _| async {
+      panic!("provided TX caused recognized_id to be called in replay test")
+    },
+    processors,
+    |_, _| async { panic!("test tried to publish a new Serai TX in replay test") },
+    spec,
+    &tributary.reader(),
+  )
+  .await;
+}
+
+fn delivered(processors: &MemProcessors, network: NetworkId) -> Vec<CoordinatorMessage> {
+  processors
+    .0
+    .try_read()
+    .unwrap()
+    .get(&network)
+    .map(|msgs| msgs.iter().cloned().collect())
+    .unwrap_or(vec![])
+}
+
+#[tokio::test]
+async fn replay_test() {
+  let mut rng = test_rng();
+  let keys = new_keys(&mut rng);
+  let spec = new_spec(&mut rng, &keys);
+
+  let tributaries = new_tributaries(&keys, &spec).await;
+  tokio::spawn(run_tributaries(tributaries.clone()));
+
+  // Publish commitments and shares for every key, so the scanner has multiple messages to send
+  let mut txs = vec![];
+  for key in &keys {
+    let mut commitments = vec![0; 256];
+    rng.fill_bytes(&mut commitments);
+    let mut tx = Transaction::DkgCommitments(0, commitments, Transaction::empty_signed());
+    tx.sign(&mut rng, spec.genesis(), key, 0);
+    txs.push(tx);
+  }
+  for (k, key) in keys.iter().enumerate() {
+    let mut shares = vec![];
+    for i in 0 .. keys.len() {
+      if i != k {
+        let mut share = vec![0; 256];
+        rng.fill_bytes(&mut share);
+        shares.push(share);
+      }
+    }
+    let mut tx = Transaction::DkgShares {
+      attempt: 0,
+      shares,
+      confirmation_nonces: crate::tributary::dkg_confirmation_nonces(key, &spec, 0),
+      signed: Transaction::empty_signed(),
+    };
+    tx.sign(&mut rng, spec.genesis(), key, 1);
+    txs.push(tx);
+  }
+
+  let (commitments, shares) = txs.split_at(keys.len());
+  for txs in [commitments, shares] {
+    let block_before_tx = tributaries[0].1.tip().await;
+    for (i, tx) in txs.iter().enumerate() {
+      assert!(tributaries[i].1.add_transaction(tx.clone()).await);
+    }
+    for tx in txs {
+      wait_for_tx_inclusion(&tributaries[0].1, block_before_tx, tx.hash()).await;
+    }
+  }
+  sleep(tributaries[0].1.block_time()).await;
+
+  let network = spec.set().network;
+  let tributary = &tributaries[0].1;
+
+  // Obtain the messages an uninterrupted scanner sends
+  let reference = {
+    let processors = MemProcessors::new();
+    scan(&mut TributaryDb(MemDb::new()), &keys[0], &spec, tributary, &processors).await;
+    delivered(&processors, network)
+  };
+  assert_eq!(reference.len(), 2);
+
+  // Kill the scanner while sending each message, then reboot it on the same DB
+  for kill_at in 0 .. reference.len() {
+    let mut scanner_db = TributaryDb(MemDb::new());
+
+    let killed =
+      KilledProcessors { sent: Arc::new(RwLock::new(0)), kill_at, inner: MemProcessors::new() };
+    assert!(tokio::time::timeout(
+      Duration::from_secs(5),
+      scan(&mut scanner_db, &keys[0], &spec, tributary, &killed),
+    )
+    .await
+    .is_err());
+
+    let rebooted = MemProcessors::new();
+    scan(&mut scanner_db, &keys[0], &spec, tributary, &rebooted).await;
+
+    // Every message should've been sent exactly once, in order, across both runs
+    let mut all = delivered(&killed.inner, network);
+    assert_eq!(all.len(), kill_at);
+    all.extend(delivered(&rebooted, network));
+    assert_eq!(all, reference);
+
+    // Scanning again shouldn't send anything further
+    let idle = MemProcessors::new();
+    scan(&mut scanner_db, &keys[0], &spec, tributary, &idle).await;
+    assert!(delivered(&idle, network).is_empty());
+  }
+}
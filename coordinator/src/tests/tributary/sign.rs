@@ -0,0 +1,130 @@
+use core::ops::Deref;
+
+use zeroize::Zeroizing;
+use rand_core::RngCore;
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use serai_test_utils::test_rng;
+
+use serai_client::Public;
+
+use serai_db::{DbTxn, Db, MemDb};
+
+use processor_messages::{
+  sign::{self, SignId},
+  CoordinatorMessage,
+};
+
+use crate::{
+  tributary::{SignData, Transaction, TributarySpec, TributaryDb, Topic, handle_application_tx},
+  tests::{
+    MemProcessors,
+    tributary::{new_keys, new_spec},
+  },
+};
+
+// Handle a transaction, returning the messages it caused to be sent to the processor
+async fn handle(
+  db: &mut MemDb,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  spec: &TributarySpec,
+  tx: Transaction,
+) -> Vec<CoordinatorMessage> {
+  let processors = MemProcessors::new();
+  let mut txn = db.txn();
+  handle_application_tx::<MemDb, _, _, _, _, _>(
+    tx,
+    spec,
+    &processors,
+    |_, _| async { panic!("signing caused a Serai TX to be published") },
+    key,
+    |_, _, _, _, _| async { panic!("signing caused recognized_id to be called") },
+    &mut txn,
+  )
+  .await;
+  txn.commit();
+  let mut msgs = processors.0.write().await;
+  msgs.remove(&spec.set().network).map(Vec::from).unwrap_or(vec![])
+}
+
+fn sign_data(
+  spec: &TributarySpec,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  plan: [u8; 32],
+  data: Vec<u8>,
+  share: bool,
+) -> Transaction {
+  let data = SignData { plan, attempt: 0, data, signed: Transaction::empty_signed() };
+  let mut tx = if share { Transaction::SignShare(data) } else { Transaction::SignPreprocess(data) };
+  tx.sign(&mut test_rng(), spec.genesis(), key, 0);
+  tx
+}
+
+#[tokio::test]
+async fn sign_test() {
+  let mut rng = test_rng();
+  let keys = new_keys(&mut rng);
+  let spec = new_spec(&mut rng, &keys);
+  let t = usize::from(spec.t());
+  // Every validator but the last will be part of the signing set
+  assert!(t < keys.len());
+
+  let mut plan = [0; 32];
+  rng.fill_bytes(&mut plan);
+  let key_pair = (Public([0xff; 32]), vec![0xff; 33].try_into().unwrap());
+
+  let mut db = MemDb::new();
+  {
+    let mut txn = db.txn();
+    TributaryDb::<MemDb>::recognize_topic(&mut txn, spec.genesis(), Topic::Sign(plan));
+    TributaryDb::<MemDb>::set_key_pair(&mut txn, spec.set(), &key_pair);
+    txn.commit();
+  }
+  let id = SignId { key: key_pair.1.to_vec(), id: plan, attempt: 0 };
+
+  let participant = |key: &Zeroizing<<Ristretto as Ciphersuite>::F>| {
+    spec.i(Ristretto::generator() * key.deref()).unwrap()
+  };
+  let data = |key: &Zeroizing<<Ristretto as Ciphersuite>::F>, label: u8| {
+    vec![label, u8::try_from(u16::from(participant(key))).unwrap()]
+  };
+
+  // Nothing should be sent to the processor until the threshold of preprocesses are published
+  for key in &keys[.. (t - 1)] {
+    let tx = sign_data(&spec, key, plan, data(key, 0), false);
+    assert!(handle(&mut db, &keys[0], &spec, tx).await.is_empty());
+  }
+  let tx = sign_data(&spec, &keys[t - 1], plan, data(&keys[t - 1], 0), false);
+  assert_eq!(
+    handle(&mut db, &keys[0], &spec, tx).await,
+    vec![CoordinatorMessage::Sign(sign::CoordinatorMessage::Preprocesses {
+      id: id.clone(),
+      // Our own preprocess isn't included
+      preprocesses: keys[1 .. t].iter().map(|key| (participant(key), data(key, 0))).collect(),
+    })]
+  );
+
+  // Preprocesses after the threshold was reached are late, and shouldn't be used
+  let late = &keys[t];
+  let tx = sign_data(&spec, late, plan, data(late, 0), false);
+  assert!(handle(&mut db, &keys[0], &spec, tx).await.is_empty());
+
+  // Someone who isn't in the signing set shouldn't have their share used
+  let tx = sign_data(&spec, late, plan, data(late, 1), true);
+  assert!(handle(&mut db, &keys[0], &spec, tx).await.is_empty());
+
+  // Once the signing set publishes their shares, they should be sent to the processor
+  for key in &keys[.. (t - 1)] {
+    let tx = sign_data(&spec, key, plan, data(key, 1), true);
+    assert!(handle(&mut db, &keys[0], &spec, tx).await.is_empty());
+  }
+  let tx = sign_data(&spec, &keys[t - 1], plan, data(&keys[t - 1], 1), true);
+  assert_eq!(
+    handle(&mut db, &keys[0], &spec, tx).await,
+    vec![CoordinatorMessage::Sign(sign::CoordinatorMessage::Shares {
+      id,
+      shares: keys[1 .. t].iter().map(|key| (participant(key), data(key, 1))).collect(),
+    })]
+  );
+}
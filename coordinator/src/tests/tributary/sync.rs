@@ -7,17 +7,9 @@ use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
 
 use tokio::{sync::broadcast, time::sleep};
 
-use serai_db::MemDb;
-
-use tributary::Tributary;
-
 use crate::{
-  tributary::Transaction,
-  ActiveTributary, handle_p2p, heartbeat_tributaries,
-  tests::{
-    LocalP2p,
-    tributary::{new_keys, new_spec, new_tributaries},
-  },
+  ActiveTributary, TributaryEvent, P2pMessageKind, P2p, handle_p2p, heartbeat_tributaries,
+  tests::tributary::{new_keys, new_spec, new_tributaries},
 };
 
 #[tokio::test]
@@ -44,7 +36,7 @@ async fn sync_test() {
     let thread =
       tokio::spawn(handle_p2p(Ristretto::generator() * *keys[i], p2p, new_tributary_recv));
     new_tributary_send
-      .send(ActiveTributary { spec: spec.clone(), tributary })
+      .send(TributaryEvent::NewTributary(ActiveTributary { spec: spec.clone(), tributary }))
       .map_err(|_| "failed to send ActiveTributary")
       .unwrap();
     tributary_senders.push(new_tributary_send);
@@ -56,8 +48,8 @@ async fn sync_test() {
   // We don't wait one block of time as we may have missed the chance for the first block
   // We don't wait two blocks because we may have missed the chance, and then had a failure to
   // propose by our 'offline' validator
-  let block_time = u64::from(Tributary::<MemDb, Transaction, LocalP2p>::block_time());
-  sleep(Duration::from_secs(3 * block_time)).await;
+  let block_time = tributaries[0].block_time();
+  sleep(3 * block_time).await;
   let tip = tributaries[0].tip().await;
   assert!(tip != spec.genesis());
 
@@ -78,7 +70,10 @@ async fn sync_test() {
   let (syncer_tributary_send, syncer_tributary_recv) = broadcast::channel(5);
   tokio::spawn(handle_p2p(syncer_key, syncer_p2p.clone(), syncer_tributary_recv));
   syncer_tributary_send
-    .send(ActiveTributary { spec: spec.clone(), tributary: syncer_tributary.clone() })
+    .send(TributaryEvent::NewTributary(ActiveTributary {
+      spec: spec.clone(),
+      tributary: syncer_tributary.clone(),
+    }))
     .map_err(|_| "failed to send ActiveTributary to syncer")
     .unwrap();
 
@@ -86,7 +81,7 @@ async fn sync_test() {
   // Sanity check this
   let tip = tributaries[0].tip().await;
   // Wait until a new block occurs
-  sleep(Duration::from_secs(3 * block_time)).await;
+  sleep(3 * block_time).await;
   // Make sure a new block actually occurred
   assert!(tributaries[0].tip().await != tip);
   // Make sure the new block alone didn't trigger catching up
@@ -96,12 +91,15 @@ async fn sync_test() {
   let (syncer_heartbeat_tributary_send, syncer_heartbeat_tributary_recv) = broadcast::channel(5);
   tokio::spawn(heartbeat_tributaries(syncer_p2p, syncer_heartbeat_tributary_recv));
   syncer_heartbeat_tributary_send
-    .send(ActiveTributary { spec: spec.clone(), tributary: syncer_tributary.clone() })
+    .send(TributaryEvent::NewTributary(ActiveTributary {
+      spec: spec.clone(),
+      tributary: syncer_tributary.clone(),
+    }))
     .map_err(|_| "failed to send ActiveTributary to heartbeat")
     .unwrap();
 
   // The heartbeat is once every 10 blocks
-  sleep(Duration::from_secs(10 * block_time)).await;
+  sleep(10 * block_time).await;
   assert!(syncer_tributary.tip().await != spec.genesis());
 
   // Verify it synced to the tip
@@ -117,7 +115,7 @@ async fn sync_test() {
     syncer_tip
   };
 
-  sleep(Duration::from_secs(block_time)).await;
+  sleep(block_time).await;
 
   // Verify it's now keeping up
   assert!(syncer_tributary.tip().await != syncer_tip);
@@ -132,7 +130,7 @@ async fn sync_test() {
   }
 
   // wait for a block
-  sleep(Duration::from_secs(block_time)).await;
+  sleep(block_time).await;
 
   if syncer_tributary
     .reader()
@@ -147,3 +145,77 @@ async fn sync_test() {
 
   panic!("synced tributary didn't start participating in consensus");
 }
+
+#[tokio::test]
+async fn sync_out_of_order_test() {
+  let mut keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  assert!(spec.n() > spec.t());
+
+  let mut tributaries = new_tributaries(&keys, &spec).await;
+
+  // Keep a Tributary back, effectively having it offline
+  let syncer_key = keys.pop().unwrap();
+  let (syncer_p2p, syncer_tributary) = tributaries.pop().unwrap();
+
+  // Have the rest form a P2P net
+  let mut tributary_senders = vec![];
+  let mut tributary_arcs = vec![];
+  let mut p2ps = vec![];
+  for (i, (p2p, tributary)) in tributaries.drain(..).enumerate() {
+    let tributary = Arc::new(tributary);
+    tributary_arcs.push(tributary.clone());
+    p2ps.push(p2p.clone());
+    let (new_tributary_send, new_tributary_recv) = broadcast::channel(5);
+    tokio::spawn(handle_p2p(Ristretto::generator() * *keys[i], p2p, new_tributary_recv));
+    new_tributary_send
+      .send(TributaryEvent::NewTributary(ActiveTributary { spec: spec.clone(), tributary }))
+      .map_err(|_| "failed to send ActiveTributary")
+      .unwrap();
+    tributary_senders.push(new_tributary_send);
+  }
+  let tributaries = tributary_arcs;
+
+  // Wait for a few blocks
+  let block_time = tributaries[0].block_time();
+  sleep(5 * block_time).await;
+
+  let reader = tributaries[0].reader();
+  let mut blocks = vec![];
+  let mut latest = spec.genesis();
+  while let Some(next) = reader.block_after(&latest) {
+    blocks.push(next);
+    latest = next;
+  }
+  assert!(blocks.len() >= 2);
+
+  // Drop the syncer's pending P2P messages and have it join the net
+  syncer_p2p.1.write().await.last_mut().unwrap().clear();
+  let syncer_tributary = Arc::new(syncer_tributary);
+  let (syncer_tributary_send, syncer_tributary_recv) = broadcast::channel(5);
+  tokio::spawn(handle_p2p(
+    Ristretto::generator() * *syncer_key,
+    syncer_p2p.clone(),
+    syncer_tributary_recv,
+  ));
+  syncer_tributary_send
+    .send(TributaryEvent::NewTributary(ActiveTributary {
+      spec: spec.clone(),
+      tributary: syncer_tributary.clone(),
+    }))
+    .map_err(|_| "failed to send ActiveTributary to syncer")
+    .unwrap();
+
+  // Send it the blocks in reverse order, as if they were received out of order
+  for block in blocks.iter().rev() {
+    let mut msg = reader.block(block).unwrap().serialize();
+    msg.extend(reader.commit(block).unwrap());
+    // The timestamp included within the Heartbeat
+    msg.extend([0; 8]);
+    p2ps[0].send(syncer_p2p.0, P2pMessageKind::Block(spec.genesis()), msg).await;
+  }
+
+  // It should've held the blocks until it received their parents, then synced all of them
+  sleep(Duration::from_secs(1)).await;
+  assert!(syncer_tributary.reader().block(blocks.last().unwrap()).is_some());
+}
@@ -0,0 +1,75 @@
+use rand_core::OsRng;
+
+use tokio::sync::broadcast;
+
+use serai_db::MemDb;
+
+use processor_messages::{
+  key_gen::{self, KeyGenId},
+  CoordinatorMessage,
+};
+
+use crate::{
+  Tributaries, TributaryEvent,
+  tests::{
+    MemProcessors, LocalP2p,
+    tributary::{new_keys, new_spec},
+  },
+};
+
+#[tokio::test]
+async fn tributaries_test() {
+  let keys = new_keys(&mut OsRng);
+  let spec = new_spec(&mut OsRng, &keys);
+  let set = spec.set();
+
+  let processors = MemProcessors::new();
+  let (events_send, mut events) = broadcast::channel(5);
+  let mut tributaries = Tributaries::new(
+    MemDb::new(),
+    keys[0].clone(),
+    processors.clone(),
+    LocalP2p::new(keys.len()).swap_remove(0),
+    events_send,
+  );
+
+  // Retiring a tributary which was never added should do nothing
+  tributaries.retire(set);
+  assert!(events.try_recv().is_err());
+
+  // Adding a tributary should announce it and trigger its DKG
+  tributaries.add(spec.clone()).await;
+  let Ok(TributaryEvent::NewTributary(tributary)) = events.try_recv() else {
+    panic!("adding a tributary didn't announce it");
+  };
+  assert_eq!(tributary.spec, spec);
+  {
+    let msgs = processors.0.read().await;
+    let msgs = &msgs[&set.network];
+    assert_eq!(msgs.len(), 1);
+    assert!(matches!(
+      &msgs[0],
+      CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::GenerateKey {
+        id: KeyGenId { set: id_set, attempt: 0 },
+        ..
+      }) if *id_set == set
+    ));
+  }
+
+  // Adding it again should do nothing
+  tributaries.add(spec.clone()).await;
+  assert!(events.try_recv().is_err());
+  assert_eq!(processors.0.read().await[&set.network].len(), 1);
+
+  // Retiring it should announce its retirement
+  tributaries.retire(set);
+  assert!(
+    matches!(events.try_recv(), Ok(TributaryEvent::TributaryRetired(retired)) if retired == set)
+  );
+
+  // A retired tributary should neither be retired again nor re-added
+  tributaries.retire(set);
+  tributaries.add(spec).await;
+  assert!(events.try_recv().is_err());
+  assert_eq!(processors.0.read().await[&set.network].len(), 1);
+}
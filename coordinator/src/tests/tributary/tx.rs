@@ -1,21 +1,12 @@
-use core::time::Duration;
-
 use rand_core::{RngCore, OsRng};
 
 use tokio::time::sleep;
 
-use serai_db::MemDb;
-
-use tributary::{
-  transaction::Transaction as TransactionTrait, Transaction as TributaryTransaction, Tributary,
-};
+use tributary::{transaction::Transaction as TransactionTrait, Transaction as TributaryTransaction};
 
 use crate::{
   tributary::Transaction,
-  tests::{
-    LocalP2p,
-    tributary::{new_keys, new_spec, new_tributaries, run_tributaries, wait_for_tx_inclusion},
-  },
+  tests::tributary::{new_keys, new_spec, new_tributaries, run_tributaries, wait_for_tx_inclusion},
 };
 
 #[tokio::test]
@@ -46,7 +37,7 @@ async fn tx_test() {
   assert!(tributaries[sender].1.add_transaction(tx.clone()).await);
   let included_in = wait_for_tx_inclusion(&tributaries[sender].1, block_before_tx, tx.hash()).await;
   // Also sleep for the block time to ensure the block is synced around before we run checks on it
-  sleep(Duration::from_secs(Tributary::<MemDb, Transaction, LocalP2p>::block_time().into())).await;
+  sleep(tributaries[0].1.block_time()).await;
 
   // All tributaries should have acknowledged this transaction in a block
   for (_, tributary) in tributaries {
@@ -1,54 +1,121 @@
-use std::io::Read;
-
 use scale::{Encode, Decode};
 
 use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
+use frost::Participant;
+
+use serai_client::{
+  primitives::NetworkId,
+  validator_sets::primitives::{ValidatorSet, KeyPair},
+};
+
+use processor_messages::CoordinatorMessage;
 
-use serai_client::validator_sets::primitives::{ValidatorSet, KeyPair};
+use tributary::{Transaction as TributaryTransaction, TributaryReader};
 
 pub use serai_db::*;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+use crate::tributary::{
+  TributarySpec, Transaction, NonceDecider,
+  handle::{
+    DKG_COMMITMENTS, DKG_SHARES, DKG_CONFIRMATION_NONCES, DKG_CONFIRMATION_SHARES,
+    BATCH_PREPROCESS, BATCH_SHARE, SIGN_PREPROCESS, SIGN_SHARE, COSIGN_PREPROCESS, COSIGN_SHARE,
+    HEARTBEAT_EPOCH_BLOCKS, OFFLINE_MISSED_HEARTBEATS,
+  },
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode)]
 pub enum Topic {
   Dkg,
   Batch([u8; 32]),
   Sign([u8; 32]),
+  Cosign([u8; 32]),
+}
+
+// A fault committed by a validator, as deterministically observed from the Tributary.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode)]
+pub enum Fault {
+  // Didn't publish a transaction before the protocol it was for timed out
+  MissedTransaction,
+  // Published an invalid signature share
+  InvalidSignature,
+  // Published conflicting transactions
+  Equivocation,
+  // Missed enough consecutive heartbeats to be considered offline
+  Offline,
 }
 
-impl Topic {
-  fn as_key(&self, genesis: [u8; 32]) -> Vec<u8> {
-    let mut res = genesis.to_vec();
-    let (label, id) = match self {
-      Topic::Dkg => (b"dkg".as_slice(), [].as_slice()),
-      Topic::Batch(id) => (b"batch".as_slice(), id.as_slice()),
-      Topic::Sign(id) => (b"sign".as_slice(), id.as_slice()),
-    };
-    res.push(u8::try_from(label.len()).unwrap());
-    res.extend(label);
-    res.push(u8::try_from(id.len()).unwrap());
-    res.extend(id);
-    res
+impl Fault {
+  // How many slash points this fault incurs
+  // Only missing transactions may be benign, so the others are weighted far more heavily
+  pub fn slash_points(self) -> u32 {
+    match self {
+      Fault::MissedTransaction => 1,
+      Fault::InvalidSignature => 100,
+      Fault::Equivocation => 100,
+      Fault::Offline => 1,
+    }
   }
 }
 
 // A struct to refer to a piece of data all validators will presumably provide a value for.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode)]
 pub struct DataSpecification {
   pub topic: Topic,
   pub label: &'static str,
   pub attempt: u32,
 }
 
-impl DataSpecification {
-  fn as_key(&self, genesis: [u8; 32]) -> Vec<u8> {
-    let mut res = self.topic.as_key(genesis);
-    let label_bytes = self.label.bytes();
-    res.push(u8::try_from(label_bytes.len()).unwrap());
-    res.extend(label_bytes);
-    res.extend(self.attempt.to_le_bytes());
-    res
+create_db!(
+  TributaryDb {
+    // Last block scanned
+    LastBlock: (genesis: [u8; 32]) -> [u8; 32],
+    // The validators which have been fatally slashed
+    FatallySlashed: (genesis: [u8; 32]) -> Vec<[u8; 32]>,
+    // The evidence of a validator's equivocation on the Tributary's consensus, as first published
+    Evidence: (genesis: [u8; 32], validator: [u8; 32]) -> Vec<u8>,
+    // The amount of times a validator has committed a fault
+    FaultCount: (genesis: [u8; 32], validator: [u8; 32], fault: Fault) -> u32,
+    // The slash points accumulated by a validator
+    SlashPoints: (genesis: [u8; 32], validator: [u8; 32]) -> u32,
+    // The plan IDs associated with a Substrate block
+    PlanIds: (genesis: [u8; 32], block: u64) -> Vec<[u8; 32]>,
+    // The key pair which we're actively working on completing
+    CurrentlyCompletingKeyPair: (genesis: [u8; 32]) -> KeyPair,
+    // The key pair confirmed for this Tributary
+    KeyPairDb: (set: ValidatorSet) -> KeyPair,
+    // The current attempt to resolve a topic
+    AttemptDb: (genesis: [u8; 32], topic: Topic) -> u32,
+    // The amount of blocks the current DKG attempt has been active for
+    DkgAttemptBlocks: (genesis: [u8; 32]) -> u32,
+    // If the DKG was completed, producing a signature for its key pair
+    DkgCompleted: (genesis: [u8; 32]) -> (),
+    // The amount of instances of data received thus far
+    DataReceived: (genesis: [u8; 32], data_spec: DataSpecification) -> u16,
+    // An instance of data from a specific validator
+    DataDb: (genesis: [u8; 32], data_spec: DataSpecification, signer: [u8; 32]) -> Vec<u8>,
+    // Every DKG share sent, kept so blame for an invalid share can be verified
+    DkgShare: (genesis: [u8; 32], attempt: u32, from: u16, to: u16) -> Vec<u8>,
+    // Who an accuser accused of sending them an invalid share
+    DkgAccusation: (genesis: [u8; 32], attempt: u32, accuser: u16) -> u16,
+    // If a validator voted for a DKG participant to be fatally slashed
+    DkgFaultVote: (genesis: [u8; 32], attempt: u32, faulty: u16, voter: [u8; 32]) -> (),
+    // The amount of validators who voted for a DKG participant to be fatally slashed
+    DkgFaultVotes: (genesis: [u8; 32], attempt: u32, faulty: u16) -> u16,
+    // If an event has been handled
+    EventDb: (id: [u8; 32], index: u32) -> (),
+    // When this Tributary was retired, as a timestamp in seconds
+    RetiredAt: (genesis: [u8; 32]) -> u64,
+    // The amount of blocks this Tributary has had, as used to define heartbeat epochs
+    HeartbeatBlocks: (genesis: [u8; 32]) -> u32,
+    // If a validator published their heartbeat for an epoch
+    Heartbeat: (genesis: [u8; 32], epoch: u32, validator: [u8; 32]) -> (),
+    // The amount of consecutive heartbeat epochs a validator has missed
+    MissedHeartbeats: (genesis: [u8; 32], validator: [u8; 32]) -> u32,
+    // Messages for the processors, saved alongside the handling of the event which created them,
+    // which have yet to be sent
+    QueuedProcessorMessages: (genesis: [u8; 32]) -> Vec<(NetworkId, Vec<u8>)>
   }
-}
+);
 
 #[derive(Debug)]
 pub struct TributaryDb<D: Db>(pub D);
@@ -57,127 +124,185 @@ impl<D: Db> TributaryDb<D> {
     Self(db)
   }
 
-  fn tributary_key(dst: &'static [u8], key: impl AsRef<[u8]>) -> Vec<u8> {
-    D::key(b"coordinator_tributary", dst, key)
-  }
-
-  // Last block scanned
-  fn last_block_key(genesis: [u8; 32]) -> Vec<u8> {
-    Self::tributary_key(b"block", genesis)
-  }
-  pub fn set_last_block(&mut self, genesis: [u8; 32], block: [u8; 32]) {
-    let mut txn = self.0.txn();
-    txn.put(Self::last_block_key(genesis), block);
-    txn.commit();
+  // This should be called within the same transaction as the block's final event, so a crash can't
+  // leave a block partially handled yet marked as handled, or vice versa
+  pub fn set_last_block(txn: &mut D::Transaction<'_>, genesis: [u8; 32], block: [u8; 32]) {
+    LastBlock::set(txn, genesis, &block);
   }
   pub fn last_block(&self, genesis: [u8; 32]) -> [u8; 32] {
-    self
-      .0
-      .get(Self::last_block_key(genesis))
-      .map(|last| last.try_into().unwrap())
-      .unwrap_or(genesis)
+    LastBlock::get(&self.0, genesis).unwrap_or(genesis)
   }
 
-  // If a validator has been fatally slashed
-  fn fatal_slash_key(genesis: [u8; 32]) -> Vec<u8> {
-    Self::tributary_key(b"fatal_slash", genesis)
-  }
   pub fn set_fatally_slashed(txn: &mut D::Transaction<'_>, genesis: [u8; 32], id: [u8; 32]) {
-    let key = Self::fatal_slash_key(genesis);
-    let mut existing = txn.get(&key).unwrap_or(vec![]);
+    let mut existing = FatallySlashed::get(txn, genesis).unwrap_or(vec![]);
 
     // Don't append if we already have it
-    if existing.chunks(32).any(|ex_id| ex_id == id) {
+    if existing.contains(&id) {
       return;
     }
 
-    existing.extend(id);
-    txn.put(key, existing);
+    existing.push(id);
+    FatallySlashed::set(txn, genesis, &existing);
+  }
+
+  // Returns false if evidence was already saved for this validator, in which case this isn't saved
+  pub fn save_evidence(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    validator: [u8; 32],
+    evidence: &[u8],
+  ) -> bool {
+    if Evidence::get(txn, genesis, validator).is_some() {
+      return false;
+    }
+    Evidence::set(txn, genesis, validator, &evidence.to_vec());
+    true
+  }
+  pub fn evidence<G: Get>(getter: &G, genesis: [u8; 32], validator: [u8; 32]) -> Option<Vec<u8>> {
+    Evidence::get(getter, genesis, validator)
   }
 
-  // The plan IDs associated with a Substrate block
-  fn plan_ids_key(genesis: &[u8], block: u64) -> Vec<u8> {
-    Self::tributary_key(b"plan_ids", [genesis, block.to_le_bytes().as_ref()].concat())
+  pub fn record_fault(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    validator: [u8; 32],
+    fault: Fault,
+  ) {
+    let count = FaultCount::get(txn, genesis, validator, fault).unwrap_or(0);
+    FaultCount::set(txn, genesis, validator, fault, &(count + 1));
+    let points = SlashPoints::get(txn, genesis, validator).unwrap_or(0);
+    SlashPoints::set(txn, genesis, validator, &points.saturating_add(fault.slash_points()));
+  }
+  pub fn slash_points<G: Get>(getter: &G, genesis: [u8; 32], validator: [u8; 32]) -> u32 {
+    SlashPoints::get(getter, genesis, validator).unwrap_or(0)
+  }
+  // The slash points of every validator, in the order of the spec's validators
+  // As faults are only recorded from on-chain data, this is identical across honest nodes
+  pub fn slash_report<G: Get>(
+    getter: &G,
+    spec: &TributarySpec,
+  ) -> Vec<(<Ristretto as Ciphersuite>::G, u32)> {
+    spec
+      .validators()
+      .into_iter()
+      .map(|(validator, _)| {
+        (validator, Self::slash_points(getter, spec.genesis(), validator.to_bytes()))
+      })
+      .collect()
   }
+
   pub fn set_plan_ids(
     txn: &mut D::Transaction<'_>,
     genesis: [u8; 32],
     block: u64,
     plans: &[[u8; 32]],
   ) {
-    txn.put(Self::plan_ids_key(&genesis, block), plans.concat());
+    PlanIds::set(txn, genesis, block, &plans.to_vec());
   }
   pub fn plan_ids<G: Get>(getter: &G, genesis: [u8; 32], block: u64) -> Option<Vec<[u8; 32]>> {
-    getter.get(Self::plan_ids_key(&genesis, block)).map(|bytes| {
-      let mut res = vec![];
-      let mut bytes_ref: &[u8] = bytes.as_ref();
-      while !bytes_ref.is_empty() {
-        let mut id = [0; 32];
-        bytes_ref.read_exact(&mut id).unwrap();
-        res.push(id);
-      }
-      res
-    })
+    PlanIds::get(getter, genesis, block)
   }
 
-  // The key pair which we're actively working on completing
-  fn currently_completing_key_pair_key(genesis: [u8; 32]) -> Vec<u8> {
-    Self::tributary_key(b"currently_completing_key_pair", genesis)
-  }
   pub fn save_currently_completing_key_pair(
     txn: &mut D::Transaction<'_>,
     genesis: [u8; 32],
     key_pair: &KeyPair,
   ) {
-    txn.put(Self::currently_completing_key_pair_key(genesis), key_pair.encode())
+    CurrentlyCompletingKeyPair::set(txn, genesis, key_pair)
   }
   pub fn currently_completing_key_pair<G: Get>(getter: &G, genesis: [u8; 32]) -> Option<KeyPair> {
-    getter
-      .get(Self::currently_completing_key_pair_key(genesis))
-      .map(|bytes| KeyPair::decode(&mut bytes.as_slice()).unwrap())
+    CurrentlyCompletingKeyPair::get(getter, genesis)
   }
 
-  // The key pair confirmed for this Tributary
-  pub fn key_pair_key(set: ValidatorSet) -> Vec<u8> {
-    Self::tributary_key(b"key_pair", set.encode())
-  }
   pub fn set_key_pair(txn: &mut D::Transaction<'_>, set: ValidatorSet, key_pair: &KeyPair) {
-    txn.put(Self::key_pair_key(set), key_pair.encode());
+    KeyPairDb::set(txn, set, key_pair);
   }
   pub fn key_pair<G: Get>(getter: &G, set: ValidatorSet) -> Option<KeyPair> {
-    Some(KeyPair::decode(&mut getter.get(Self::key_pair_key(set))?.as_slice()).unwrap())
+    KeyPairDb::get(getter, set)
   }
 
-  // The current attempt to resolve a topic
-  fn attempt_key(genesis: [u8; 32], topic: Topic) -> Vec<u8> {
-    Self::tributary_key(b"attempt", topic.as_key(genesis))
-  }
   pub fn recognize_topic(txn: &mut D::Transaction<'_>, genesis: [u8; 32], topic: Topic) {
-    txn.put(Self::attempt_key(genesis, topic), 0u32.to_le_bytes())
+    AttemptDb::set(txn, genesis, topic, &0)
   }
   pub fn attempt<G: Get>(getter: &G, genesis: [u8; 32], topic: Topic) -> Option<u32> {
-    let attempt_bytes = getter.get(Self::attempt_key(genesis, topic));
+    let attempt = AttemptDb::get(getter, genesis, topic);
     // DKGs start when the chain starts
-    if attempt_bytes.is_none() && (topic == Topic::Dkg) {
+    if attempt.is_none() && (topic == Topic::Dkg) {
       return Some(0);
     }
-    Some(u32::from_le_bytes(attempt_bytes?.try_into().unwrap()))
+    attempt
+  }
+
+  // Increments the amount of blocks the current DKG attempt has been active for, returning the new
+  // amount
+  pub fn increment_dkg_attempt_blocks(txn: &mut D::Transaction<'_>, genesis: [u8; 32]) -> u32 {
+    let blocks = DkgAttemptBlocks::get(txn, genesis).unwrap_or(0) + 1;
+    DkgAttemptBlocks::set(txn, genesis, &blocks);
+    blocks
+  }
+  // Starts a new DKG attempt, returning the new attempt
+  pub fn reattempt_dkg(txn: &mut D::Transaction<'_>, genesis: [u8; 32]) -> u32 {
+    let attempt = Self::attempt(txn, genesis, Topic::Dkg).unwrap() + 1;
+    AttemptDb::set(txn, genesis, Topic::Dkg, &attempt);
+    DkgAttemptBlocks::set(txn, genesis, &0);
+    attempt
   }
 
-  // Key for the amount of instances received thus far
-  fn data_received_key(genesis: [u8; 32], data_spec: &DataSpecification) -> Vec<u8> {
-    Self::tributary_key(b"data_received", data_spec.as_key(genesis))
+  pub fn complete_dkg(txn: &mut D::Transaction<'_>, genesis: [u8; 32]) {
+    DkgCompleted::set(txn, genesis, &());
+  }
+  pub fn dkg_completed<G: Get>(getter: &G, genesis: [u8; 32]) -> bool {
+    DkgCompleted::get(getter, genesis).is_some()
+  }
+
+  // Increments the amount of blocks this Tributary has had, returning the new amount
+  pub fn increment_heartbeat_blocks(txn: &mut D::Transaction<'_>, genesis: [u8; 32]) -> u32 {
+    let blocks = HeartbeatBlocks::get(txn, genesis).unwrap_or(0) + 1;
+    HeartbeatBlocks::set(txn, genesis, &blocks);
+    blocks
+  }
+  pub fn heartbeat_blocks<G: Get>(getter: &G, genesis: [u8; 32]) -> u32 {
+    HeartbeatBlocks::get(getter, genesis).unwrap_or(0)
   }
-  // Key for an instance of data from a specific validator
-  fn data_key(
+
+  pub fn set_heartbeat(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    epoch: u32,
+    validator: [u8; 32],
+  ) {
+    Heartbeat::set(txn, genesis, epoch, validator, &());
+    // Having heartbeated, they're no longer missing any heartbeats
+    MissedHeartbeats::del(txn, genesis, validator);
+  }
+  pub fn heartbeat<G: Get>(getter: &G, genesis: [u8; 32], epoch: u32, validator: [u8; 32]) -> bool {
+    Heartbeat::get(getter, genesis, epoch, validator).is_some()
+  }
+  // Increments the amount of consecutive heartbeats a validator has missed, returning the new
+  // amount
+  pub fn miss_heartbeat(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    validator: [u8; 32],
+  ) -> u32 {
+    let missed = Self::missed_heartbeats(txn, genesis, validator) + 1;
+    MissedHeartbeats::set(txn, genesis, validator, &missed);
+    missed
+  }
+  pub fn missed_heartbeats<G: Get>(getter: &G, genesis: [u8; 32], validator: [u8; 32]) -> u32 {
+    MissedHeartbeats::get(getter, genesis, validator).unwrap_or(0)
+  }
+  // If a validator has missed enough consecutive heartbeats to be considered offline
+  pub fn offline<G: Get>(getter: &G, genesis: [u8; 32], validator: [u8; 32]) -> bool {
+    Self::missed_heartbeats(getter, genesis, validator) >= OFFLINE_MISSED_HEARTBEATS
+  }
+
+  pub fn data_received<G: Get>(
+    getter: &G,
     genesis: [u8; 32],
     data_spec: &DataSpecification,
-    signer: <Ristretto as Ciphersuite>::G,
-  ) -> Vec<u8> {
-    Self::tributary_key(
-      b"data",
-      [data_spec.as_key(genesis).as_slice(), signer.to_bytes().as_ref()].concat(),
-    )
+  ) -> u16 {
+    DataReceived::get(getter, genesis, *data_spec).unwrap_or(0)
   }
   pub fn data<G: Get>(
     getter: &G,
@@ -185,7 +310,7 @@ impl<D: Db> TributaryDb<D> {
     data_spec: &DataSpecification,
     signer: <Ristretto as Ciphersuite>::G,
   ) -> Option<Vec<u8>> {
-    getter.get(Self::data_key(genesis, data_spec, signer))
+    DataDb::get(getter, genesis, *data_spec, signer.to_bytes())
   }
   pub fn set_data(
     txn: &mut D::Transaction<'_>,
@@ -194,25 +319,375 @@ impl<D: Db> TributaryDb<D> {
     signer: <Ristretto as Ciphersuite>::G,
     data: &[u8],
   ) -> u16 {
-    let received_key = Self::data_received_key(genesis, data_spec);
-    let mut received =
-      u16::from_le_bytes(txn.get(&received_key).unwrap_or(vec![0; 2]).try_into().unwrap());
-    received += 1;
+    let received = DataReceived::get(txn, genesis, *data_spec).unwrap_or(0) + 1;
+    DataReceived::set(txn, genesis, *data_spec, &received);
+    DataDb::set(txn, genesis, *data_spec, signer.to_bytes(), &data.to_vec());
+    received
+  }
 
-    txn.put(received_key, received.to_le_bytes());
-    txn.put(Self::data_key(genesis, data_spec, signer), data);
+  pub fn save_dkg_share(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    attempt: u32,
+    from: Participant,
+    to: Participant,
+    share: &[u8],
+  ) {
+    DkgShare::set(txn, genesis, attempt, u16::from(from), u16::from(to), &share.to_vec());
+  }
+  pub fn dkg_share<G: Get>(
+    getter: &G,
+    genesis: [u8; 32],
+    attempt: u32,
+    from: Participant,
+    to: Participant,
+  ) -> Option<Vec<u8>> {
+    DkgShare::get(getter, genesis, attempt, u16::from(from), u16::from(to))
+  }
 
-    received
+  pub fn save_dkg_accusation(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    attempt: u32,
+    accuser: Participant,
+    accused: Participant,
+  ) {
+    DkgAccusation::set(txn, genesis, attempt, u16::from(accuser), &u16::from(accused));
+  }
+  pub fn dkg_accusation<G: Get>(
+    getter: &G,
+    genesis: [u8; 32],
+    attempt: u32,
+    accuser: Participant,
+  ) -> Option<Participant> {
+    DkgAccusation::get(getter, genesis, attempt, u16::from(accuser))
+      .map(|accused| Participant::new(accused).unwrap())
   }
 
-  fn event_key(id: &[u8], index: u32) -> Vec<u8> {
-    Self::tributary_key(b"event", [id, index.to_le_bytes().as_ref()].concat())
+  // Returns the amount of validators who have voted for this participant to be fatally slashed,
+  // only counting each validator's first vote
+  pub fn vote_dkg_fault(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    attempt: u32,
+    faulty: Participant,
+    voter: [u8; 32],
+  ) -> u16 {
+    let faulty = u16::from(faulty);
+    let votes = DkgFaultVotes::get(txn, genesis, attempt, faulty).unwrap_or(0);
+    if DkgFaultVote::get(txn, genesis, attempt, faulty, voter).is_some() {
+      return votes;
+    }
+    DkgFaultVote::set(txn, genesis, attempt, faulty, voter, &());
+    DkgFaultVotes::set(txn, genesis, attempt, faulty, &(votes + 1));
+    votes + 1
   }
+
   pub fn handled_event<G: Get>(getter: &G, id: [u8; 32], index: u32) -> bool {
-    getter.get(Self::event_key(&id, index)).is_some()
+    EventDb::get(getter, id, index).is_some()
   }
   pub fn handle_event(txn: &mut D::Transaction<'_>, id: [u8; 32], index: u32) {
     assert!(!Self::handled_event(txn, id, index));
-    txn.put(Self::event_key(&id, index), []);
+    EventDb::set(txn, id, index, &());
+  }
+
+  pub fn queue_processor_messages(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    msgs: Vec<(NetworkId, CoordinatorMessage)>,
+  ) {
+    if msgs.is_empty() {
+      return;
+    }
+    let mut queued = QueuedProcessorMessages::get(txn, genesis).unwrap_or(vec![]);
+    queued.extend(msgs.into_iter().map(|(network, msg)| (network, msg.serialize())));
+    QueuedProcessorMessages::set(txn, genesis, &queued);
+  }
+  pub fn next_queued_processor_message<G: Get>(
+    getter: &G,
+    genesis: [u8; 32],
+  ) -> Option<(NetworkId, CoordinatorMessage)> {
+    let (network, msg) = QueuedProcessorMessages::get(getter, genesis)?.into_iter().next()?;
+    Some((network, CoordinatorMessage::deserialize(&msg).unwrap()))
+  }
+  pub fn pop_queued_processor_message(txn: &mut D::Transaction<'_>, genesis: [u8; 32]) {
+    let mut queued = QueuedProcessorMessages::get(txn, genesis).unwrap();
+    queued.remove(0);
+    if queued.is_empty() {
+      QueuedProcessorMessages::del(txn, genesis);
+    } else {
+      QueuedProcessorMessages::set(txn, genesis, &queued);
+    }
+  }
+
+  // Mark a Tributary as retired, once its validator set has completed its lifecycle
+  pub fn retire(txn: &mut D::Transaction<'_>, genesis: [u8; 32], time: u64) {
+    if RetiredAt::get(txn, genesis).is_none() {
+      RetiredAt::set(txn, genesis, &time);
+    }
+  }
+  pub fn retired_at<G: Get>(getter: &G, genesis: [u8; 32]) -> Option<u64> {
+    RetiredAt::get(getter, genesis)
+  }
+  // Called once the Tributary has been fully pruned, as RetiredAt is what causes the pruning
+  pub fn pruned(txn: &mut D::Transaction<'_>, genesis: [u8; 32]) {
+    RetiredAt::del(txn, genesis);
+  }
+
+  // Delete all data for a topic, across every attempt
+  fn prune_topic(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    validators: &[[u8; 32]],
+    topic: Topic,
+    labels: &[&'static str],
+  ) {
+    for attempt in 0 ..= Self::attempt(txn, genesis, topic).unwrap_or(0) {
+      for label in labels {
+        let data_spec = DataSpecification { topic, label: *label, attempt };
+        DataReceived::del(txn, genesis, data_spec);
+        for validator in validators {
+          DataDb::del(txn, genesis, data_spec, *validator);
+        }
+      }
+    }
+    AttemptDb::del(txn, genesis, topic);
+  }
+
+  // Delete all of a retired Tributary's data, except for when it was retired
+  // The data to delete is found by walking the Tributary's blocks, so this must be called before
+  // the Tributary itself is pruned. If interrupted, this may be safely called again.
+  pub fn prune(db: &mut D, spec: &TributarySpec) {
+    let genesis = spec.genesis();
+    let validators =
+      spec.validators().into_iter().map(|(validator, _)| validator.to_bytes()).collect::<Vec<_>>();
+
+    // Prune each block's data in its own DB transaction, to bound memory usage
+    let reader = TributaryReader::<D, Transaction>::new(db.clone(), genesis);
+    let mut last = genesis;
+    while let Some(block) = reader.block_after(&last) {
+      last = block;
+      let transactions = reader.block(&block).unwrap().transactions;
+
+      db.batch(|txn| {
+        // Every block has an event per transaction, and a final event for the DKG timeout and
+        // heartbeat epoch
+        for index in 0 ..= u32::try_from(transactions.len()).unwrap() {
+          EventDb::del(txn, block, index);
+        }
+        for tx in transactions {
+          match tx {
+            TributaryTransaction::Application(Transaction::Batch(_, batch)) => {
+              let labels = [BATCH_PREPROCESS, BATCH_SHARE];
+              Self::prune_topic(txn, genesis, &validators, Topic::Batch(batch), &labels);
+              NonceDecider::<D>::prune_batch(txn, genesis, batch);
+            }
+            TributaryTransaction::Application(Transaction::SubstrateBlock(substrate_block)) => {
+              for plan in PlanIds::get(txn, genesis, substrate_block).unwrap_or(vec![]) {
+                let labels = [SIGN_PREPROCESS, SIGN_SHARE];
+                Self::prune_topic(txn, genesis, &validators, Topic::Sign(plan), &labels);
+                NonceDecider::<D>::prune_plan(txn, genesis, plan);
+              }
+              PlanIds::del(txn, genesis, substrate_block);
+            }
+            TributaryTransaction::Application(Transaction::CosignSubstrateBlock(block)) => {
+              let labels = [COSIGN_PREPROCESS, COSIGN_SHARE];
+              Self::prune_topic(txn, genesis, &validators, Topic::Cosign(block), &labels);
+              NonceDecider::<D>::prune_cosign(txn, genesis, block);
+            }
+            _ => {}
+          }
+        }
+      });
+    }
+
+    let mut txn = db.txn();
+
+    let dkg_attempt = Self::attempt(&txn, genesis, Topic::Dkg).unwrap();
+    let labels = [DKG_COMMITMENTS, DKG_SHARES, DKG_CONFIRMATION_NONCES, DKG_CONFIRMATION_SHARES];
+    Self::prune_topic(&mut txn, genesis, &validators, Topic::Dkg, &labels);
+    for attempt in 0 ..= dkg_attempt {
+      for from in 1 ..= spec.n() {
+        for to in 1 ..= spec.n() {
+          DkgShare::del(&mut txn, genesis, attempt, from, to);
+        }
+      }
+      for i in 1 ..= spec.n() {
+        DkgAccusation::del(&mut txn, genesis, attempt, i);
+        for validator in &validators {
+          DkgFaultVote::del(&mut txn, genesis, attempt, i, *validator);
+        }
+        DkgFaultVotes::del(&mut txn, genesis, attempt, i);
+      }
+    }
+    NonceDecider::<D>::prune_dkg(&mut txn, genesis, dkg_attempt, spec.n());
+    DkgAttemptBlocks::del(&mut txn, genesis);
+    DkgCompleted::del(&mut txn, genesis);
+    CurrentlyCompletingKeyPair::del(&mut txn, genesis);
+
+    let heartbeat_epoch = Self::heartbeat_blocks(&txn, genesis) / HEARTBEAT_EPOCH_BLOCKS;
+    for validator in &validators {
+      for epoch in 1 ..= heartbeat_epoch {
+        Heartbeat::del(&mut txn, genesis, epoch, *validator);
+      }
+      MissedHeartbeats::del(&mut txn, genesis, *validator);
+    }
+    NonceDecider::<D>::prune_heartbeats(&mut txn, genesis, heartbeat_epoch);
+    HeartbeatBlocks::del(&mut txn, genesis);
+
+    for validator in &validators {
+      for fault in
+        [Fault::MissedTransaction, Fault::InvalidSignature, Fault::Equivocation, Fault::Offline]
+      {
+        FaultCount::del(&mut txn, genesis, *validator, fault);
+      }
+      SlashPoints::del(&mut txn, genesis, *validator);
+      Evidence::del(&mut txn, genesis, *validator);
+    }
+    FatallySlashed::del(&mut txn, genesis);
+    QueuedProcessorMessages::del(&mut txn, genesis);
+    LastBlock::del(&mut txn, genesis);
+
+    txn.commit();
+  }
+}
+
+// The labels data may have been saved under, as needed to recover the static label from a key
+const DATA_LABELS: [&str; 10] = [
+  DKG_COMMITMENTS,
+  DKG_SHARES,
+  DKG_CONFIRMATION_NONCES,
+  DKG_CONFIRMATION_SHARES,
+  BATCH_PREPROCESS,
+  BATCH_SHARE,
+  SIGN_PREPROCESS,
+  SIGN_SHARE,
+  COSIGN_PREPROCESS,
+  COSIGN_SHARE,
+];
+
+// Read a fixed amount of bytes from a legacy key
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+  if bytes.len() < len {
+    None?;
+  }
+  let (res, rest) = bytes.split_at(len);
+  *bytes = rest;
+  Some(res)
+}
+fn take_32(bytes: &mut &[u8]) -> Option<[u8; 32]> {
+  take(bytes, 32)?.try_into().ok()
+}
+// Read a length-prefixed field from a legacy key
+fn take_labeled<'a>(bytes: &mut &'a [u8]) -> Option<&'a [u8]> {
+  let len = take(bytes, 1)?[0];
+  take(bytes, usize::from(len))
+}
+
+// Read a Topic as it was keyed prior to create_db, prefixed by the genesis
+fn legacy_topic(bytes: &mut &[u8]) -> Option<([u8; 32], Topic)> {
+  let genesis = take_32(bytes)?;
+  let label = take_labeled(bytes)?;
+  let id = take_labeled(bytes)?;
+  let topic = match label {
+    b"dkg" if id.is_empty() => Topic::Dkg,
+    b"batch" => Topic::Batch(id.try_into().ok()?),
+    b"sign" => Topic::Sign(id.try_into().ok()?),
+    _ => None?,
+  };
+  Some((genesis, topic))
+}
+
+// Read a DataSpecification as it was keyed prior to create_db, prefixed by the genesis
+fn legacy_data_spec(bytes: &mut &[u8]) -> Option<([u8; 32], DataSpecification)> {
+  let (genesis, topic) = legacy_topic(bytes)?;
+  let label = take_labeled(bytes)?;
+  let label = DATA_LABELS.into_iter().find(|known| known.as_bytes() == label)?;
+  let attempt = u32::from_le_bytes(take(bytes, 4)?.try_into().unwrap());
+  Some((genesis, DataSpecification { topic, label, attempt }))
+}
+
+// Convert an entry saved under the key layout used prior to create_db to its current key and
+// value, if it was such an entry
+fn migrate_legacy_entry(key: &[u8], value: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+  const LEGACY_DB: &[u8] = b"coordinator_tributary";
+  let mut key = key
+    .strip_prefix([u8::try_from(LEGACY_DB.len()).unwrap()].as_slice())?
+    .strip_prefix(LEGACY_DB)?;
+  let item = take_labeled(&mut key)?;
+
+  // IDs were concatenated, whereas they're now SCALE-encoded as a Vec
+  let ids = || -> Option<Vec<u8>> {
+    if (value.len() % 32) != 0 {
+      None?;
+    }
+    Some(value.chunks(32).map(|id| <[u8; 32]>::try_from(id).unwrap()).collect::<Vec<_>>().encode())
+  };
+
+  let res = match item {
+    b"block" => (LastBlock::key(take_32(&mut key)?), value.to_vec()),
+    b"fatal_slash" => (FatallySlashed::key(take_32(&mut key)?), ids()?),
+    b"plan_ids" => {
+      let genesis = take_32(&mut key)?;
+      let block = u64::from_le_bytes(take(&mut key, 8)?.try_into().unwrap());
+      (PlanIds::key(genesis, block), ids()?)
+    }
+    b"currently_completing_key_pair" => {
+      (CurrentlyCompletingKeyPair::key(take_32(&mut key)?), value.to_vec())
+    }
+    b"key_pair" => {
+      let set = ValidatorSet::decode(&mut key).ok()?;
+      (KeyPairDb::key(set), value.to_vec())
+    }
+    b"attempt" => {
+      let (genesis, topic) = legacy_topic(&mut key)?;
+      (AttemptDb::key(genesis, topic), value.to_vec())
+    }
+    b"data_received" => {
+      let (genesis, data_spec) = legacy_data_spec(&mut key)?;
+      (DataReceived::key(genesis, data_spec), value.to_vec())
+    }
+    // Data was saved raw, whereas it's now SCALE-encoded as a Vec
+    b"data" => {
+      let (genesis, data_spec) = legacy_data_spec(&mut key)?;
+      (DataDb::key(genesis, data_spec, take_32(&mut key)?), value.to_vec().encode())
+    }
+    b"event" => {
+      let id = take_32(&mut key)?;
+      let index = u32::from_le_bytes(take(&mut key, 4)?.try_into().unwrap());
+      (EventDb::key(id, index), value.to_vec())
+    }
+    _ => None?,
+  };
+  // The entire key should've been read
+  if !key.is_empty() {
+    None?;
+  }
+  Some(res)
+}
+
+impl<D: IterableDb> TributaryDb<D> {
+  // Move every item saved under the key layout used prior to create_db to its current key
+  // This should be called on boot, before the DB is otherwise used, and is a no-op once done
+  pub fn migrate_legacy_keys(db: &mut D) {
+    let mut migrations = vec![];
+    db.for_each_entry(|key, value| {
+      if let Some(migrated) = migrate_legacy_entry(key, value) {
+        migrations.push((key.to_vec(), migrated));
+      }
+      Ok::<_, ()>(())
+    })
+    .unwrap();
+    if migrations.is_empty() {
+      return;
+    }
+
+    tracing::info!("migrating {} TributaryDb entries to their current keys", migrations.len());
+    let mut txn = db.txn();
+    for (legacy_key, (key, value)) in migrations {
+      txn.del(legacy_key);
+      txn.put(key, value);
+    }
+    txn.commit();
   }
 }
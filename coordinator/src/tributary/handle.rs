@@ -7,10 +7,10 @@ use rand_core::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 
 use transcript::{Transcript, RecommendedTranscript};
-use ciphersuite::{Ciphersuite, Ristretto};
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
 use frost::{
   FrostError,
-  dkg::{Participant, musig::musig},
+  dkg::{Participant, ThresholdParams, musig::musig},
   sign::*,
 };
 use frost_schnorrkel::Schnorrkel;
@@ -35,23 +35,26 @@ use serai_db::{Get, Db};
 use crate::{
   processors::Processors,
   tributary::{
-    Transaction, TributarySpec, Topic, DataSpecification, TributaryDb, nonce_decider::NonceDecider,
-    scanner::RecognizedIdType,
+    Transaction, TributarySpec, Topic, DataSpecification, Fault, TributaryDb,
+    nonce_decider::NonceDecider, scanner::RecognizedIdType,
   },
 };
 
-const DKG_COMMITMENTS: &str = "commitments";
-const DKG_SHARES: &str = "shares";
-const DKG_CONFIRMATION_NONCES: &str = "confirmation_nonces";
-const DKG_CONFIRMATION_SHARES: &str = "confirmation_shares";
+pub(crate) const DKG_COMMITMENTS: &str = "commitments";
+pub(crate) const DKG_SHARES: &str = "shares";
+pub(crate) const DKG_CONFIRMATION_NONCES: &str = "confirmation_nonces";
+pub(crate) const DKG_CONFIRMATION_SHARES: &str = "confirmation_shares";
 
 // These s/b prefixes between Batch and Sign should be unnecessary, as Batch/Share entries
 // themselves should already be domain separated
-const BATCH_PREPROCESS: &str = "b_preprocess";
-const BATCH_SHARE: &str = "b_share";
+pub(crate) const BATCH_PREPROCESS: &str = "b_preprocess";
+pub(crate) const BATCH_SHARE: &str = "b_share";
 
-const SIGN_PREPROCESS: &str = "s_preprocess";
-const SIGN_SHARE: &str = "s_share";
+pub(crate) const SIGN_PREPROCESS: &str = "s_preprocess";
+pub(crate) const SIGN_SHARE: &str = "s_share";
+
+pub(crate) const COSIGN_PREPROCESS: &str = "c_preprocess";
+pub(crate) const COSIGN_SHARE: &str = "c_share";
 
 // Instead of maintaing state, this simply re-creates the machine(s) in-full on every call (which
 // should only be once per tributary).
@@ -234,6 +237,166 @@ pub fn generated_key_pair<D: Db>(
   DkgConfirmer::share(spec, key, attempt, preprocesses, key_pair)
 }
 
+// Record a vote for a DKG participant to be fatally slashed, doing so once enough validators agree
+// Since fatal slashes are only applied once a threshold of validators vote for them, at least one
+// honest validator's processor must have found the participant faulty
+fn vote_dkg_fault<D: Db>(
+  txn: &mut D::Transaction<'_>,
+  spec: &TributarySpec,
+  attempt: u32,
+  faulty: Participant,
+  signed: &Signed,
+) {
+  let genesis = spec.genesis();
+  let Some(faulty_key) = spec.validator(faulty) else {
+    // TODO: Full slash
+    return;
+  };
+
+  let votes =
+    TributaryDb::<D>::vote_dkg_fault(txn, genesis, attempt, faulty, signed.signer.to_bytes());
+  if votes == spec.t() {
+    tracing::warn!("participant {faulty:?} was voted faulty in DKG attempt {attempt}");
+    TributaryDb::<D>::set_fatally_slashed(txn, genesis, faulty_key.to_bytes());
+  }
+}
+
+// The amount of blocks a DKG attempt may take before it's re-attempted
+// This has to cover three rounds of transactions, along with the processors' computations
+const DKG_TIMEOUT_BLOCKS: u32 = 50;
+
+// The amount of blocks in a heartbeat epoch, during which every validator should publish a
+// heartbeat
+// Epoch 0 has no heartbeats, as the DKG commitments published on start serve the same purpose
+pub(crate) const HEARTBEAT_EPOCH_BLOCKS: u32 = 50;
+// The amount of consecutive heartbeats a validator may miss before they're considered offline
+pub(crate) const OFFLINE_MISSED_HEARTBEATS: u32 = 3;
+
+// Start a new heartbeat epoch if this block ends the current one, flagging whoever missed the
+// prior epoch's heartbeat and publishing our own heartbeat for the new epoch
+// This must be called once per block, after its transactions have been handled
+pub(crate) async fn handle_heartbeat_epoch<
+  D: Db,
+  FRid: Future<Output = ()>,
+  RID: crate::RIDTrait<FRid>,
+>(
+  txn: &mut D::Transaction<'_>,
+  spec: &TributarySpec,
+  recognized_id: RID,
+) {
+  let genesis = spec.genesis();
+  let blocks = TributaryDb::<D>::increment_heartbeat_blocks(txn, genesis);
+  if (blocks % HEARTBEAT_EPOCH_BLOCKS) != 0 {
+    return;
+  }
+  let epoch = blocks / HEARTBEAT_EPOCH_BLOCKS;
+
+  // Check everyone heartbeated during the epoch which just ended
+  let ended = epoch - 1;
+  if ended != 0 {
+    for (validator, _) in spec.validators() {
+      let validator = validator.to_bytes();
+      if TributaryDb::<D>::heartbeat(txn, genesis, ended, validator) {
+        continue;
+      }
+
+      let missed = TributaryDb::<D>::miss_heartbeat(txn, genesis, validator);
+      // Penalize validators once they're considered offline, and again for every further
+      // OFFLINE_MISSED_HEARTBEATS they miss
+      if (missed % OFFLINE_MISSED_HEARTBEATS) == 0 {
+        tracing::warn!(
+          "{} missed {missed} consecutive heartbeats on {:?}, considering them offline",
+          hex::encode(validator),
+          spec.set(),
+        );
+        TributaryDb::<D>::record_fault(txn, genesis, validator, Fault::Offline);
+      }
+    }
+  }
+
+  let nonce = NonceDecider::<D>::handle_heartbeat_epoch(txn, genesis, epoch);
+  let mut id = [0; 32];
+  id[.. 4].copy_from_slice(&epoch.to_le_bytes());
+  recognized_id(spec.set().network, genesis, RecognizedIdType::Heartbeat, id, nonce).await;
+}
+
+// Re-attempt the DKG if the current attempt has timed out
+// This must be called once per block, after its transactions have been handled
+pub(crate) async fn handle_dkg_timeout<D: Db, Pro: Processors>(
+  txn: &mut D::Transaction<'_>,
+  spec: &TributarySpec,
+  key: &Zeroizing<<Ristretto as Ciphersuite>::F>,
+  processors: &Pro,
+) {
+  let genesis = spec.genesis();
+  if TributaryDb::<D>::dkg_completed(txn, genesis) {
+    return;
+  }
+  let attempt = TributaryDb::<D>::attempt(txn, genesis, Topic::Dkg).unwrap();
+
+  if TributaryDb::<D>::increment_dkg_attempt_blocks(txn, genesis) < DKG_TIMEOUT_BLOCKS {
+    return;
+  }
+
+  // Find whoever failed to publish their transaction for the round which stalled
+  // The confirmation round isn't evaluated as validators who accused another of sending an invalid
+  // share won't publish a confirmation, despite not being faulty
+  let mut missing = vec![];
+  for label in [DKG_COMMITMENTS, DKG_SHARES] {
+    let data_spec = DataSpecification { topic: Topic::Dkg, label, attempt };
+    if TributaryDb::<D>::data_received(txn, genesis, &data_spec) == spec.n() {
+      continue;
+    }
+    for (validator, _) in spec.validators() {
+      if TributaryDb::<D>::data(txn, genesis, &data_spec, validator).is_none() {
+        missing.push(validator.to_bytes());
+      }
+    }
+    break;
+  }
+
+  // The DKG requires every validator, so a new attempt would stall on an offline validator just
+  // the same. Instead, keep the current attempt open until they're back online, when they'll
+  // presumably publish their transaction for it
+  if missing.iter().any(|validator| TributaryDb::<D>::offline(txn, genesis, *validator)) {
+    return;
+  }
+
+  // Penalize whoever stalled this attempt
+  for validator in missing {
+    TributaryDb::<D>::record_fault(txn, genesis, validator, Fault::MissedTransaction);
+  }
+
+  // Any further transactions for the prior attempt will be ignored as stale
+  let attempt = TributaryDb::<D>::reattempt_dkg(txn, genesis);
+  tracing::warn!("DKG for {:?} timed out, starting attempt {attempt}", spec.set());
+  tracing::debug!(
+    "slash points for {:?}: {:?}",
+    spec.set(),
+    TributaryDb::<D>::slash_report(txn, spec)
+      .into_iter()
+      .map(|(validator, points)| (hex::encode(validator.to_bytes()), points))
+      .collect::<Vec<_>>(),
+  );
+  NonceDecider::<D>::handle_dkg_reattempt(txn, genesis, attempt);
+  processors
+    .send(
+      spec.set().network,
+      CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::GenerateKey {
+        id: KeyGenId { set: spec.set(), attempt },
+        params: ThresholdParams::new(
+          spec.t(),
+          spec.n(),
+          spec
+            .i(Ristretto::generator() * key.deref())
+            .expect("handling a DKG timeout for a Tributary we aren't part of"),
+        )
+        .unwrap(),
+      }),
+    )
+    .await;
+}
+
 pub(crate) async fn handle_application_tx<
   D: Db,
   Pro: Processors,
@@ -261,8 +424,10 @@ pub(crate) async fn handle_application_tx<
     // If they've already published a TX for this attempt, slash
     if let Some(data) = TributaryDb::<D>::data(txn, genesis, data_spec, signed.signer) {
       if data != bytes {
-        // TODO: Full slash
-        todo!();
+        let signer = signed.signer.to_bytes();
+        TributaryDb::<D>::record_fault(txn, genesis, signer, Fault::Equivocation);
+        TributaryDb::<D>::set_fatally_slashed(txn, genesis, signer);
+        return None;
       }
 
       // TODO: Slash
@@ -279,17 +444,37 @@ pub(crate) async fn handle_application_tx<
       todo!();
     }
 
-    // TODO: We can also full slash if shares before all commitments, or share before the
-    // necessary preprocesses
+    // TODO: We can also full slash if shares before all commitments
+
+    // TODO: This needs to be coded by weight, not by validator count
+    let needed = if data_spec.topic == Topic::Dkg { spec.n() } else { spec.t() };
+
+    // If we already have all the needed data, this is late
+    // For preprocesses, this means the signer won't be part of the signing set
+    if TributaryDb::<D>::data_received(txn, genesis, data_spec) == needed {
+      // TODO: Slash for being late
+      return None;
+    }
 
-    // TODO: If this is shares, we need to check they are part of the selected signing set
+    // Shares may only be published by the signing set, which is whoever's preprocess was used
+    let preprocess_label = match data_spec.label {
+      BATCH_SHARE => Some(BATCH_PREPROCESS),
+      SIGN_SHARE => Some(SIGN_PREPROCESS),
+      COSIGN_SHARE => Some(COSIGN_PREPROCESS),
+      _ => None,
+    };
+    if let Some(label) = preprocess_label {
+      let preprocess_spec = DataSpecification { label, ..*data_spec };
+      if TributaryDb::<D>::data(txn, genesis, &preprocess_spec, signed.signer).is_none() {
+        // TODO: Full slash
+        return None;
+      }
+    }
 
     // Store this data
     let received = TributaryDb::<D>::set_data(txn, genesis, data_spec, signed.signer, &bytes);
 
     // If we have all the needed commitments/preprocesses/shares, tell the processor
-    // TODO: This needs to be coded by weight, not by validator count
-    let needed = if data_spec.topic == Topic::Dkg { spec.n() } else { spec.t() };
     if received == needed {
       return Some(read_known_to_exist_data::<D, _>(txn, spec, key, data_spec, needed));
     }
@@ -305,7 +490,8 @@ pub(crate) async fn handle_application_tx<
         &signed,
       ) {
         Some(Some(commitments)) => {
-          log::info!("got all DkgCommitments for {}", hex::encode(genesis));
+          tracing::info!("got all DkgCommitments for {}", hex::encode(genesis));
+          NonceDecider::<D>::handle_dkg_commitments(txn, genesis, attempt);
           processors
             .send(
               spec.set().network,
@@ -321,7 +507,7 @@ pub(crate) async fn handle_application_tx<
       }
     }
 
-    Transaction::DkgShares { attempt, mut shares, confirmation_nonces, signed } => {
+    Transaction::DkgShares { attempt, shares, confirmation_nonces, signed } => {
       if shares.len() != (usize::from(spec.n()) - 1) {
         // TODO: Full slash
         todo!();
@@ -331,7 +517,7 @@ pub(crate) async fn handle_application_tx<
         .i(signed.signer)
         .expect("transaction added to tributary by signer who isn't a participant");
 
-      // Only save our share's bytes
+      // Only pass along our share's bytes
       let our_i = spec
         .i(Ristretto::generator() * key.deref())
         .expect("in a tributary we're not a validator for");
@@ -343,9 +529,11 @@ pub(crate) async fn handle_application_tx<
         let relative_i = usize::from(u16::from(our_i) - 1) -
           (if u16::from(our_i) > u16::from(sender_i) { 1 } else { 0 });
         // Safe since we length-checked shares
-        shares.swap_remove(relative_i)
+        shares[relative_i].clone()
       };
-      drop(shares);
+
+      let shares_spec = DataSpecification { topic: Topic::Dkg, label: DKG_SHARES, attempt };
+      let first = TributaryDb::<D>::data(txn, genesis, &shares_spec, signed.signer).is_none();
 
       let confirmation_nonces = handle(
         txn,
@@ -353,15 +541,29 @@ pub(crate) async fn handle_application_tx<
         confirmation_nonces.to_vec(),
         &signed,
       );
-      match handle(
-        txn,
-        &DataSpecification { topic: Topic::Dkg, label: DKG_SHARES, attempt },
-        bytes,
-        &signed,
-      ) {
+      let handled = handle(txn, &shares_spec, bytes, &signed);
+
+      // Save every share, so if any recipient claims their share is invalid, it can be verified
+      // This is only done if these shares were accepted as the signer's first for this attempt, so
+      // a later (or rejected) transaction can't replace the shares recipients actually received
+      if first && TributaryDb::<D>::data(txn, genesis, &shares_spec, signed.signer).is_some() {
+        for (relative_i, share) in shares.iter().enumerate() {
+          // 0-indexed to 1-indexed, skipping over the sender
+          let mut to = u16::try_from(relative_i).unwrap() + 1;
+          if to >= u16::from(sender_i) {
+            to += 1;
+          }
+          let to = Participant::new(to).unwrap();
+          TributaryDb::<D>::save_dkg_share(txn, genesis, attempt, sender_i, to, share);
+        }
+      }
+      drop(shares);
+
+      match handled {
         Some(Some(shares)) => {
-          log::info!("got all DkgShares for {}", hex::encode(genesis));
+          tracing::info!("got all DkgShares for {}", hex::encode(genesis));
           assert!(confirmation_nonces.is_some());
+          NonceDecider::<D>::handle_dkg_shares(txn, genesis, attempt);
           processors
             .send(
               spec.set().network,
@@ -385,7 +587,7 @@ pub(crate) async fn handle_application_tx<
         &signed,
       ) {
         Some(Some(shares)) => {
-          log::info!("got all DkgConfirmed for {}", hex::encode(genesis));
+          tracing::info!("got all DkgConfirmed for {}", hex::encode(genesis));
 
           let Some(preprocesses) = read_known_to_exist_data::<D, _>(
             txn,
@@ -404,12 +606,19 @@ pub(crate) async fn handle_application_tx<
                 "(including us) fires DkgConfirmed, yet no confirming key pair"
               )
             });
-          let Ok(sig) = DkgConfirmer::complete(spec, key, attempt, preprocesses, &key_pair, shares)
-          else {
-            // TODO: Full slash
-            todo!();
-          };
-
+          let sig =
+            match DkgConfirmer::complete(spec, key, attempt, preprocesses, &key_pair, shares) {
+              Ok(sig) => sig,
+              // This attempt won't be able to complete, and will eventually be re-attempted
+              Err(p) => {
+                let faulty = spec.validator(p).unwrap().to_bytes();
+                TributaryDb::<D>::record_fault(txn, genesis, faulty, Fault::InvalidSignature);
+                TributaryDb::<D>::set_fatally_slashed(txn, genesis, faulty);
+                return;
+              }
+            };
+
+          TributaryDb::<D>::complete_dkg(txn, genesis);
           publish_serai_tx(
             spec.set(),
             Serai::set_validator_set_keys(spec.set().network, key_pair, Signature(sig)),
@@ -421,6 +630,64 @@ pub(crate) async fn handle_application_tx<
       }
     }
 
+    Transaction::InvalidDkgShare { attempt, faulty, blame, signed } => {
+      // Ignore accusations from prior attempts, which have already been moved past
+      if attempt < TributaryDb::<D>::attempt(txn, genesis, Topic::Dkg).unwrap() {
+        return;
+      }
+
+      let accuser = spec
+        .i(signed.signer)
+        .expect("transaction added to tributary by signer who isn't a participant");
+
+      // If the accused never sent the accuser a share, the accusation is baseless
+      let share = if faulty == accuser {
+        None
+      } else {
+        TributaryDb::<D>::dkg_share(txn, genesis, attempt, faulty, accuser)
+      };
+      let Some(share) = share else {
+        tracing::warn!(
+          "{} accused {:?}, which didn't send them a share",
+          hex::encode(signed.signer.to_bytes()),
+          faulty,
+        );
+        TributaryDb::<D>::set_fatally_slashed(txn, genesis, signed.signer.to_bytes());
+        return;
+      };
+
+      // Have the processor determine who's at fault, which it'll report back via Blame
+      // Every validator responds with a DkgBlame, so allocate a nonce for ours
+      TributaryDb::<D>::save_dkg_accusation(txn, genesis, attempt, accuser, faulty);
+      NonceDecider::<D>::handle_dkg_accusation(txn, genesis, attempt, accuser);
+      processors
+        .send(
+          spec.set().network,
+          CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::VerifyBlame {
+            id: KeyGenId { set: spec.set(), attempt },
+            accusation: key_gen::Accusation { accuser, accused: faulty, share, blame },
+          }),
+        )
+        .await;
+    }
+
+    Transaction::InvalidDkgCommitments { attempt, faulty, signed } => {
+      vote_dkg_fault::<D>(txn, spec, attempt, faulty, &signed);
+    }
+
+    Transaction::DkgBlame { attempt, accuser, faulty, signed } => {
+      // Votes may only be cast for a party to an accusation which was actually made
+      let Some(accused) = TributaryDb::<D>::dkg_accusation(txn, genesis, attempt, accuser) else {
+        // TODO: Full slash
+        return;
+      };
+      if (faulty != accuser) && (faulty != accused) {
+        // TODO: Full slash
+        return;
+      }
+      vote_dkg_fault::<D>(txn, spec, attempt, faulty, &signed);
+    }
+
     Transaction::Batch(_, batch) => {
       // Because this Batch has achieved synchrony, its batch ID should be authorized
       TributaryDb::<D>::recognize_topic(txn, genesis, Topic::Batch(batch));
@@ -569,8 +836,19 @@ pub(crate) async fn handle_application_tx<
         None => {}
       }
     }
+    Transaction::Heartbeat(epoch, signed) => {
+      // Only heartbeats for the current epoch count, as a late heartbeat doesn't prove the signer
+      // was online during the epoch it was for
+      if epoch != (TributaryDb::<D>::heartbeat_blocks(txn, genesis) / HEARTBEAT_EPOCH_BLOCKS) {
+        // TODO: Slash for being late
+        return;
+      }
+      // TODO: Slash if they already published a heartbeat for this epoch
+      TributaryDb::<D>::set_heartbeat(txn, genesis, epoch, signed.signer.to_bytes());
+    }
+
     Transaction::SignCompleted { plan, tx_hash, .. } => {
-      log::info!(
+      tracing::info!(
         "on-chain SignCompleted claims {} completes {}",
         hex::encode(&tx_hash),
         hex::encode(plan)
@@ -589,5 +867,72 @@ pub(crate) async fn handle_application_tx<
         )
         .await;
     }
+
+    Transaction::CosignSubstrateBlock(block) => {
+      // Because this block has achieved synchrony, its cosign should be authorized
+      TributaryDb::<D>::recognize_topic(txn, genesis, Topic::Cosign(block));
+      let nonce = NonceDecider::<D>::handle_cosign(txn, genesis, block);
+      recognized_id(spec.set().network, genesis, RecognizedIdType::Cosign, block, nonce).await;
+    }
+    Transaction::CosignPreprocess(data) => {
+      match handle(
+        txn,
+        &DataSpecification {
+          topic: Topic::Cosign(data.plan),
+          label: COSIGN_PREPROCESS,
+          attempt: data.attempt,
+        },
+        data.data,
+        &data.signed,
+      ) {
+        Some(Some(preprocesses)) => {
+          NonceDecider::<D>::selected_for_cosigning(txn, genesis, data.plan);
+          let key = TributaryDb::<D>::key_pair(txn, spec.set()).unwrap().0 .0.to_vec();
+          processors
+            .send(
+              spec.set().network,
+              CoordinatorMessage::Coordinator(
+                coordinator::CoordinatorMessage::CosignPreprocesses {
+                  id: SignId { key, id: data.plan, attempt: data.attempt },
+                  preprocesses,
+                },
+              ),
+            )
+            .await;
+        }
+        Some(None) => {}
+        None => {}
+      }
+    }
+    Transaction::CosignShare(data) => {
+      match handle(
+        txn,
+        &DataSpecification {
+          topic: Topic::Cosign(data.plan),
+          label: COSIGN_SHARE,
+          attempt: data.attempt,
+        },
+        data.data,
+        &data.signed,
+      ) {
+        Some(Some(shares)) => {
+          let key = TributaryDb::<D>::key_pair(txn, spec.set()).unwrap().0 .0.to_vec();
+          processors
+            .send(
+              spec.set().network,
+              CoordinatorMessage::Coordinator(coordinator::CoordinatorMessage::CosignShares {
+                id: SignId { key, id: data.plan, attempt: data.attempt },
+                shares: shares
+                  .into_iter()
+                  .map(|(validator, share)| (validator, share.try_into().unwrap()))
+                  .collect(),
+              }),
+            )
+            .await;
+        }
+        Some(None) => {}
+        None => {}
+      }
+    }
   }
 }
@@ -23,7 +23,7 @@ use serai_client::{
 
 #[rustfmt::skip]
 use tributary::{
-  ReadWrite,
+  ReadWrite, ConsensusParams,
   transaction::{Signed, TransactionError, TransactionKind, Transaction as TransactionTrait}
 };
 
@@ -38,12 +38,18 @@ pub use handle::*;
 
 pub mod scanner;
 
+// Set in the encoded validators length when the spec has non-default consensus parameters, which
+// are then appended. Specs with the default parameters are encoded as they were before the
+// parameters were configurable, so existing databases and exports remain readable.
+const CONSENSUS_PARAMS_FLAG: u32 = 1 << 31;
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct TributarySpec {
   serai_block: [u8; 32],
   start_time: u64,
   set: ValidatorSet,
   validators: Vec<(<Ristretto as Ciphersuite>::G, u64)>,
+  consensus_params: ConsensusParams,
 }
 
 impl TributarySpec {
@@ -63,7 +69,16 @@ impl TributarySpec {
       validators.push((participant, amount.0 / set_data.bond.0));
     }
 
-    Self { serai_block, start_time, set, validators }
+    Self { serai_block, start_time, set, validators, consensus_params: ConsensusParams::default() }
+  }
+
+  /// Use the specified consensus parameters, instead of the defaults.
+  ///
+  /// These are committed to by the genesis, so every validator must use the same parameters.
+  #[cfg(test)]
+  pub fn with_consensus_params(mut self, consensus_params: ConsensusParams) -> Self {
+    self.consensus_params = consensus_params;
+    self
   }
 
   pub fn set(&self) -> ValidatorSet {
@@ -77,6 +92,15 @@ impl TributarySpec {
     genesis.append_message(b"serai_block", self.serai_block);
     genesis.append_message(b"session", self.set.session.0.to_le_bytes());
     genesis.append_message(b"network", self.set.network.encode());
+    // This locks it to specific consensus parameters
+    // The defaults aren't included so the genesis of existing Tributaries is unchanged
+    if self.consensus_params != ConsensusParams::default() {
+      genesis.append_message(
+        b"block_processing_time",
+        self.consensus_params.block_processing_time().to_le_bytes(),
+      );
+      genesis.append_message(b"latency_time", self.consensus_params.latency_time().to_le_bytes());
+    }
     let genesis = genesis.challenge(b"genesis");
     let genesis_ref: &[u8] = genesis.as_ref();
     genesis_ref[.. 32].try_into().unwrap()
@@ -86,6 +110,10 @@ impl TributarySpec {
     self.start_time
   }
 
+  pub fn consensus_params(&self) -> ConsensusParams {
+    self.consensus_params
+  }
+
   pub fn n(&self) -> u16 {
     // TODO: Support multiple key shares
     // self.validators.iter().map(|(_, weight)| u16::try_from(weight).unwrap()).sum()
@@ -110,6 +138,11 @@ impl TributarySpec {
     None
   }
 
+  pub fn validator(&self, i: Participant) -> Option<<Ristretto as Ciphersuite>::G> {
+    // TODO: Support multiple key shares
+    self.validators.get(usize::from(u16::from(i)) - 1).map(|(validator, _weight)| *validator)
+  }
+
   pub fn validators(&self) -> Vec<(<Ristretto as Ciphersuite>::G, u64)> {
     self.validators.clone()
   }
@@ -121,11 +154,21 @@ impl TributarySpec {
     let network_encoded = self.set.network.encode();
     assert_eq!(network_encoded.len(), 1);
     writer.write_all(&network_encoded)?;
-    writer.write_all(&u32::try_from(self.validators.len()).unwrap().to_le_bytes())?;
+    let consensus_params = self.consensus_params != ConsensusParams::default();
+    let mut validators_len = u32::try_from(self.validators.len()).unwrap();
+    assert_eq!(validators_len & CONSENSUS_PARAMS_FLAG, 0);
+    if consensus_params {
+      validators_len |= CONSENSUS_PARAMS_FLAG;
+    }
+    writer.write_all(&validators_len.to_le_bytes())?;
     for validator in &self.validators {
       writer.write_all(&validator.0.to_bytes())?;
       writer.write_all(&validator.1.to_le_bytes())?;
     }
+    if consensus_params {
+      writer.write_all(&self.consensus_params.block_processing_time().to_le_bytes())?;
+      writer.write_all(&self.consensus_params.latency_time().to_le_bytes())?;
+    }
     Ok(())
   }
 
@@ -154,7 +197,9 @@ impl TributarySpec {
 
     let mut validators_len = [0; 4];
     reader.read_exact(&mut validators_len)?;
-    let validators_len = usize::try_from(u32::from_le_bytes(validators_len)).unwrap();
+    let validators_len = u32::from_le_bytes(validators_len);
+    let consensus_params = (validators_len & CONSENSUS_PARAMS_FLAG) != 0;
+    let validators_len = usize::try_from(validators_len & !CONSENSUS_PARAMS_FLAG).unwrap();
 
     let mut validators = Vec::with_capacity(validators_len);
     for _ in 0 .. validators_len {
@@ -164,7 +209,27 @@ impl TributarySpec {
       validators.push((key, u64::from_le_bytes(bond)));
     }
 
-    Ok(Self { serai_block, start_time, set: ValidatorSet { session, network }, validators })
+    let consensus_params = if consensus_params {
+      let mut block_processing_time = [0; 4];
+      reader.read_exact(&mut block_processing_time)?;
+      let mut latency_time = [0; 4];
+      reader.read_exact(&mut latency_time)?;
+      ConsensusParams::new(
+        u32::from_le_bytes(block_processing_time),
+        u32::from_le_bytes(latency_time),
+      )
+      .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid consensus parameters"))?
+    } else {
+      ConsensusParams::default()
+    };
+
+    Ok(Self {
+      serai_block,
+      start_time,
+      set: ValidatorSet { session, network },
+      validators,
+      consensus_params,
+    })
   }
 }
 
@@ -230,6 +295,29 @@ pub enum Transaction {
     signed: Signed,
   },
   DkgConfirmed(u32, [u8; 32], Signed),
+  // Published instead of DkgConfirmed when the share sent to the signer was invalid
+  // The blame is the proof the share was invalid, prefixed by which curve it's for, if there is one
+  InvalidDkgShare {
+    attempt: u32,
+    faulty: Participant,
+    blame: Option<Vec<u8>>,
+    signed: Signed,
+  },
+  // Published instead of DkgShares when a participant's commitments were invalid, voting for them
+  // to be fatally slashed
+  InvalidDkgCommitments {
+    attempt: u32,
+    faulty: Participant,
+    signed: Signed,
+  },
+  // Published in response to an InvalidDkgShare, voting for whoever was found at fault (either the
+  // accuser or the accused) to be fatally slashed
+  DkgBlame {
+    attempt: u32,
+    accuser: Participant,
+    faulty: Participant,
+    signed: Signed,
+  },
 
   // When we have synchrony on a batch, we can allow signing it
   // TODO (never?): This is less efficient compared to an ExternalBlock provided transaction,
@@ -258,6 +346,22 @@ pub enum Transaction {
     first_signer: <Ristretto as Ciphersuite>::G,
     signature: SchnorrSignature<Ristretto>,
   },
+
+  // Periodically published by every validator, proving they're online
+  // The u32 is the heartbeat epoch this was published for
+  Heartbeat(u32, Signed),
+
+  // When we have synchrony on a Serai block to cosign, we can allow cosigning it
+  CosignSubstrateBlock([u8; 32]),
+  CosignPreprocess(SignData),
+  CosignShare(SignData),
+}
+
+fn read_participant<R: io::Read>(reader: &mut R) -> io::Result<Participant> {
+  let mut participant = [0; 2];
+  reader.read_exact(&mut participant)?;
+  Participant::new(u16::from_le_bytes(participant))
+    .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid participant"))
 }
 
 impl ReadWrite for Transaction {
@@ -362,6 +466,75 @@ impl ReadWrite for Transaction {
         Ok(Transaction::SignCompleted { plan, tx_hash, first_signer, signature })
       }
 
+      10 => {
+        let mut attempt = [0; 4];
+        reader.read_exact(&mut attempt)?;
+        let attempt = u32::from_le_bytes(attempt);
+
+        let faulty = read_participant(reader)?;
+
+        let mut has_blame = [0];
+        reader.read_exact(&mut has_blame)?;
+        let blame = match has_blame[0] {
+          0 => None,
+          1 => {
+            let mut blame_len = [0; 2];
+            reader.read_exact(&mut blame_len)?;
+            let mut blame = vec![0; usize::from(u16::from_le_bytes(blame_len))];
+            reader.read_exact(&mut blame)?;
+            Some(blame)
+          }
+          _ => Err(io::Error::new(io::ErrorKind::Other, "invalid blame flag"))?,
+        };
+
+        let signed = Signed::read(reader)?;
+
+        Ok(Transaction::InvalidDkgShare { attempt, faulty, blame, signed })
+      }
+
+      11 => {
+        let mut epoch = [0; 4];
+        reader.read_exact(&mut epoch)?;
+        let epoch = u32::from_le_bytes(epoch);
+
+        let signed = Signed::read(reader)?;
+
+        Ok(Transaction::Heartbeat(epoch, signed))
+      }
+
+      12 => {
+        let mut block = [0; 32];
+        reader.read_exact(&mut block)?;
+        Ok(Transaction::CosignSubstrateBlock(block))
+      }
+      13 => SignData::read(reader).map(Transaction::CosignPreprocess),
+      14 => SignData::read(reader).map(Transaction::CosignShare),
+
+      15 => {
+        let mut attempt = [0; 4];
+        reader.read_exact(&mut attempt)?;
+        let attempt = u32::from_le_bytes(attempt);
+
+        let faulty = read_participant(reader)?;
+
+        let signed = Signed::read(reader)?;
+
+        Ok(Transaction::InvalidDkgCommitments { attempt, faulty, signed })
+      }
+
+      16 => {
+        let mut attempt = [0; 4];
+        reader.read_exact(&mut attempt)?;
+        let attempt = u32::from_le_bytes(attempt);
+
+        let accuser = read_participant(reader)?;
+        let faulty = read_participant(reader)?;
+
+        let signed = Signed::read(reader)?;
+
+        Ok(Transaction::DkgBlame { attempt, accuser, faulty, signed })
+      }
+
       _ => Err(io::Error::new(io::ErrorKind::Other, "invalid transaction type")),
     }
   }
@@ -415,6 +588,25 @@ impl ReadWrite for Transaction {
         signed.write(writer)
       }
 
+      Transaction::InvalidDkgShare { attempt, faulty, blame, signed } => {
+        writer.write_all(&[10])?;
+        writer.write_all(&attempt.to_le_bytes())?;
+        writer.write_all(&u16::from(*faulty).to_le_bytes())?;
+        match blame {
+          None => writer.write_all(&[0])?,
+          Some(blame) => {
+            writer.write_all(&[1])?;
+            if blame.len() > u16::MAX.into() {
+              // A curve tag, a point, and a DLEq proof, which will never exceed a u16
+              Err(io::Error::new(io::ErrorKind::Other, "dkg blame exceeded 65535 bytes"))?;
+            }
+            writer.write_all(&u16::try_from(blame.len()).unwrap().to_le_bytes())?;
+            writer.write_all(blame)?;
+          }
+        }
+        signed.write(writer)
+      }
+
       Transaction::Batch(block, batch) => {
         writer.write_all(&[3])?;
         writer.write_all(block)?;
@@ -452,6 +644,40 @@ impl ReadWrite for Transaction {
         writer.write_all(&first_signer.to_bytes())?;
         signature.write(writer)
       }
+
+      Transaction::Heartbeat(epoch, signed) => {
+        writer.write_all(&[11])?;
+        writer.write_all(&epoch.to_le_bytes())?;
+        signed.write(writer)
+      }
+
+      Transaction::CosignSubstrateBlock(block) => {
+        writer.write_all(&[12])?;
+        writer.write_all(block)
+      }
+      Transaction::CosignPreprocess(data) => {
+        writer.write_all(&[13])?;
+        data.write(writer)
+      }
+      Transaction::CosignShare(data) => {
+        writer.write_all(&[14])?;
+        data.write(writer)
+      }
+
+      Transaction::InvalidDkgCommitments { attempt, faulty, signed } => {
+        writer.write_all(&[15])?;
+        writer.write_all(&attempt.to_le_bytes())?;
+        writer.write_all(&u16::from(*faulty).to_le_bytes())?;
+        signed.write(writer)
+      }
+
+      Transaction::DkgBlame { attempt, accuser, faulty, signed } => {
+        writer.write_all(&[16])?;
+        writer.write_all(&attempt.to_le_bytes())?;
+        writer.write_all(&u16::from(*accuser).to_le_bytes())?;
+        writer.write_all(&u16::from(*faulty).to_le_bytes())?;
+        signed.write(writer)
+      }
     }
   }
 }
@@ -462,6 +688,9 @@ impl TransactionTrait for Transaction {
       Transaction::DkgCommitments(_, _, signed) => TransactionKind::Signed(signed),
       Transaction::DkgShares { signed, .. } => TransactionKind::Signed(signed),
       Transaction::DkgConfirmed(_, _, signed) => TransactionKind::Signed(signed),
+      Transaction::InvalidDkgShare { signed, .. } => TransactionKind::Signed(signed),
+      Transaction::InvalidDkgCommitments { signed, .. } => TransactionKind::Signed(signed),
+      Transaction::DkgBlame { signed, .. } => TransactionKind::Signed(signed),
 
       Transaction::Batch(_, _) => TransactionKind::Provided("batch"),
       Transaction::SubstrateBlock(_) => TransactionKind::Provided("serai"),
@@ -472,6 +701,12 @@ impl TransactionTrait for Transaction {
       Transaction::SignPreprocess(data) => TransactionKind::Signed(&data.signed),
       Transaction::SignShare(data) => TransactionKind::Signed(&data.signed),
       Transaction::SignCompleted { .. } => TransactionKind::Unsigned,
+
+      Transaction::Heartbeat(_, signed) => TransactionKind::Signed(signed),
+
+      Transaction::CosignSubstrateBlock(_) => TransactionKind::Provided("cosign"),
+      Transaction::CosignPreprocess(data) => TransactionKind::Signed(&data.signed),
+      Transaction::CosignShare(data) => TransactionKind::Signed(&data.signed),
     }
   }
 
@@ -485,7 +720,7 @@ impl TransactionTrait for Transaction {
   }
 
   fn verify(&self) -> Result<(), TransactionError> {
-    if let Transaction::BatchShare(data) = self {
+    if let Transaction::BatchShare(data) | Transaction::CosignShare(data) = self {
       if data.data.len() != 32 {
         Err(TransactionError::InvalidContent)?;
       }
@@ -527,6 +762,9 @@ impl Transaction {
         Transaction::DkgCommitments(_, _, ref mut signed) => signed,
         Transaction::DkgShares { ref mut signed, .. } => signed,
         Transaction::DkgConfirmed(_, _, ref mut signed) => signed,
+        Transaction::InvalidDkgShare { ref mut signed, .. } => signed,
+        Transaction::InvalidDkgCommitments { ref mut signed, .. } => signed,
+        Transaction::DkgBlame { ref mut signed, .. } => signed,
 
         Transaction::Batch(_, _) => panic!("signing Batch"),
         Transaction::SubstrateBlock(_) => panic!("signing SubstrateBlock"),
@@ -537,6 +775,12 @@ impl Transaction {
         Transaction::SignPreprocess(ref mut data) => &mut data.signed,
         Transaction::SignShare(ref mut data) => &mut data.signed,
         Transaction::SignCompleted { .. } => panic!("signing SignCompleted"),
+
+        Transaction::Heartbeat(_, ref mut signed) => signed,
+
+        Transaction::CosignSubstrateBlock(_) => panic!("signing CosignSubstrateBlock"),
+        Transaction::CosignPreprocess(ref mut data) => &mut data.signed,
+        Transaction::CosignShare(ref mut data) => &mut data.signed,
       }
     }
 
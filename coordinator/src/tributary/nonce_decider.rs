@@ -2,6 +2,8 @@ use core::marker::PhantomData;
 
 use serai_db::{Get, DbTxn, Db};
 
+use frost::Participant;
+
 use crate::tributary::Transaction;
 
 /// Decides the nonce which should be used for a transaction on a Tributary.
@@ -14,6 +16,27 @@ const BATCH_CODE: u8 = 0;
 const BATCH_SIGNING_CODE: u8 = 1;
 const PLAN_CODE: u8 = 2;
 const PLAN_SIGNING_CODE: u8 = 3;
+const DKG_COMMITMENTS_CODE: u8 = 4;
+const DKG_SHARES_CODE: u8 = 5;
+const DKG_CONFIRMATION_CODE: u8 = 6;
+const HEARTBEAT_CODE: u8 = 7;
+const COSIGN_CODE: u8 = 8;
+const COSIGN_SIGNING_CODE: u8 = 9;
+const DKG_BLAME_CODE: u8 = 10;
+
+// DKG nonces are keyed by their attempt, and heartbeat nonces by their epoch
+fn attempt_id(attempt: u32) -> [u8; 32] {
+  let mut id = [0; 32];
+  id[.. 4].copy_from_slice(&attempt.to_le_bytes());
+  id
+}
+
+// DKG blame nonces are keyed by their attempt and the accuser whose accusation they respond to
+fn blame_id(attempt: u32, accuser: Participant) -> [u8; 32] {
+  let mut id = attempt_id(attempt);
+  id[4 .. 6].copy_from_slice(&u16::from(accuser).to_le_bytes());
+  id
+}
 
 impl<D: Db> NonceDecider<D> {
   fn next_nonce_key(genesis: [u8; 32]) -> Vec<u8> {
@@ -21,8 +44,9 @@ impl<D: Db> NonceDecider<D> {
   }
   fn allocate_nonce(txn: &mut D::Transaction<'_>, genesis: [u8; 32]) -> u32 {
     let key = Self::next_nonce_key(genesis);
+    // 0 is used by the commitments for the first DKG attempt, which are published on start
     let next =
-      txn.get(&key).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap())).unwrap_or(3);
+      txn.get(&key).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap())).unwrap_or(1);
     txn.put(key, (next + 1).to_le_bytes());
     next
   }
@@ -49,6 +73,31 @@ impl<D: Db> NonceDecider<D> {
       .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
   }
 
+  // DKG nonces are allocated as each round completes, instead of in advance per attempt, so an
+  // attempt which times out doesn't leave behind nonces which will never be used
+  pub fn handle_dkg_reattempt(txn: &mut D::Transaction<'_>, genesis: [u8; 32], attempt: u32) {
+    let nonce_for = Self::allocate_nonce(txn, genesis);
+    Self::set_nonce(txn, genesis, DKG_COMMITMENTS_CODE, attempt_id(attempt), nonce_for);
+  }
+  pub fn handle_dkg_commitments(txn: &mut D::Transaction<'_>, genesis: [u8; 32], attempt: u32) {
+    let nonce_for = Self::allocate_nonce(txn, genesis);
+    Self::set_nonce(txn, genesis, DKG_SHARES_CODE, attempt_id(attempt), nonce_for);
+  }
+  pub fn handle_dkg_shares(txn: &mut D::Transaction<'_>, genesis: [u8; 32], attempt: u32) {
+    let nonce_for = Self::allocate_nonce(txn, genesis);
+    Self::set_nonce(txn, genesis, DKG_CONFIRMATION_CODE, attempt_id(attempt), nonce_for);
+  }
+  // Every validator is asked to verify an accusation, and responds with their DkgBlame
+  pub fn handle_dkg_accusation(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    attempt: u32,
+    accuser: Participant,
+  ) {
+    let nonce_for = Self::allocate_nonce(txn, genesis);
+    Self::set_nonce(txn, genesis, DKG_BLAME_CODE, blame_id(attempt, accuser), nonce_for);
+  }
+
   pub fn handle_batch(txn: &mut D::Transaction<'_>, genesis: [u8; 32], batch: [u8; 32]) -> u32 {
     let nonce_for = Self::allocate_nonce(txn, genesis);
     Self::set_nonce(txn, genesis, BATCH_CODE, batch, nonce_for);
@@ -85,19 +134,44 @@ impl<D: Db> NonceDecider<D> {
     Self::set_nonce(txn, genesis, PLAN_SIGNING_CODE, plan, nonce_for);
   }
 
+  pub fn handle_heartbeat_epoch(
+    txn: &mut D::Transaction<'_>,
+    genesis: [u8; 32],
+    epoch: u32,
+  ) -> u32 {
+    let nonce_for = Self::allocate_nonce(txn, genesis);
+    Self::set_nonce(txn, genesis, HEARTBEAT_CODE, attempt_id(epoch), nonce_for);
+    nonce_for
+  }
+
+  pub fn handle_cosign(txn: &mut D::Transaction<'_>, genesis: [u8; 32], block: [u8; 32]) -> u32 {
+    let nonce_for = Self::allocate_nonce(txn, genesis);
+    Self::set_nonce(txn, genesis, COSIGN_CODE, block, nonce_for);
+    nonce_for
+  }
+  pub fn selected_for_cosigning(txn: &mut D::Transaction<'_>, genesis: [u8; 32], block: [u8; 32]) {
+    let nonce_for = Self::allocate_nonce(txn, genesis);
+    Self::set_nonce(txn, genesis, COSIGN_SIGNING_CODE, block, nonce_for);
+  }
+
   pub fn nonce<G: Get>(getter: &G, genesis: [u8; 32], tx: &Transaction) -> Option<Option<u32>> {
     match tx {
       Transaction::DkgCommitments(attempt, _, _) => {
-        assert_eq!(*attempt, 0);
-        Some(Some(0))
+        if *attempt == 0 {
+          return Some(Some(0));
+        }
+        Some(Self::db_nonce(getter, genesis, DKG_COMMITMENTS_CODE, attempt_id(*attempt)))
+      }
+      // InvalidDkgCommitments is published instead of DkgShares, so it takes the same nonce
+      Transaction::DkgShares { attempt, .. } | Transaction::InvalidDkgCommitments { attempt, .. } => {
+        Some(Self::db_nonce(getter, genesis, DKG_SHARES_CODE, attempt_id(*attempt)))
       }
-      Transaction::DkgShares { attempt, .. } => {
-        assert_eq!(*attempt, 0);
-        Some(Some(1))
+      // InvalidDkgShare is only published when a DkgConfirmed won't be, so it takes the same nonce
+      Transaction::DkgConfirmed(attempt, _, _) | Transaction::InvalidDkgShare { attempt, .. } => {
+        Some(Self::db_nonce(getter, genesis, DKG_CONFIRMATION_CODE, attempt_id(*attempt)))
       }
-      Transaction::DkgConfirmed(attempt, _, _) => {
-        assert_eq!(*attempt, 0);
-        Some(Some(2))
+      Transaction::DkgBlame { attempt, accuser, .. } => {
+        Some(Self::db_nonce(getter, genesis, DKG_BLAME_CODE, blame_id(*attempt, *accuser)))
       }
 
       Transaction::Batch(_, _) => None,
@@ -122,6 +196,52 @@ impl<D: Db> NonceDecider<D> {
       }
 
       Transaction::SignCompleted { .. } => None,
+
+      Transaction::Heartbeat(epoch, _) => {
+        Some(Self::db_nonce(getter, genesis, HEARTBEAT_CODE, attempt_id(*epoch)))
+      }
+
+      Transaction::CosignSubstrateBlock(_) => None,
+      Transaction::CosignPreprocess(data) => {
+        assert_eq!(data.attempt, 0);
+        Some(Self::db_nonce(getter, genesis, COSIGN_CODE, data.plan))
+      }
+      Transaction::CosignShare(data) => {
+        assert_eq!(data.attempt, 0);
+        Some(Self::db_nonce(getter, genesis, COSIGN_SIGNING_CODE, data.plan))
+      }
+    }
+  }
+
+  // Delete the nonces for a retired Tributary, given the amount of DKG attempts made (and the
+  // amount of participants in them), the batches/plans/cosigns it recognized, and the heartbeat
+  // epochs it reached
+  pub fn prune_dkg(txn: &mut D::Transaction<'_>, genesis: [u8; 32], attempt: u32, n: u16) {
+    for attempt in 0 ..= attempt {
+      for code in [DKG_COMMITMENTS_CODE, DKG_SHARES_CODE, DKG_CONFIRMATION_CODE] {
+        txn.del(Self::item_nonce_key(genesis, code, attempt_id(attempt)));
+      }
+      for accuser in (1 ..= n).map(|i| Participant::new(i).unwrap()) {
+        txn.del(Self::item_nonce_key(genesis, DKG_BLAME_CODE, blame_id(attempt, accuser)));
+      }
+    }
+    txn.del(Self::next_nonce_key(genesis));
+  }
+  pub fn prune_batch(txn: &mut D::Transaction<'_>, genesis: [u8; 32], batch: [u8; 32]) {
+    txn.del(Self::item_nonce_key(genesis, BATCH_CODE, batch));
+    txn.del(Self::item_nonce_key(genesis, BATCH_SIGNING_CODE, batch));
+  }
+  pub fn prune_plan(txn: &mut D::Transaction<'_>, genesis: [u8; 32], plan: [u8; 32]) {
+    txn.del(Self::item_nonce_key(genesis, PLAN_CODE, plan));
+    txn.del(Self::item_nonce_key(genesis, PLAN_SIGNING_CODE, plan));
+  }
+  pub fn prune_cosign(txn: &mut D::Transaction<'_>, genesis: [u8; 32], block: [u8; 32]) {
+    txn.del(Self::item_nonce_key(genesis, COSIGN_CODE, block));
+    txn.del(Self::item_nonce_key(genesis, COSIGN_SIGNING_CODE, block));
+  }
+  pub fn prune_heartbeats(txn: &mut D::Transaction<'_>, genesis: [u8; 32], epoch: u32) {
+    for epoch in 1 ..= epoch {
+      txn.del(Self::item_nonce_key(genesis, HEARTBEAT_CODE, attempt_id(epoch)));
     }
   }
 }
@@ -1,10 +1,15 @@
 use core::future::Future;
+use std::sync::{Arc, Mutex};
 
 use zeroize::Zeroizing;
 
 use ciphersuite::{Ciphersuite, Ristretto};
 
-use serai_client::{validator_sets::primitives::ValidatorSet, subxt::utils::Encoded};
+use serai_client::{
+  primitives::NetworkId, validator_sets::primitives::ValidatorSet, subxt::utils::Encoded,
+};
+
+use processor_messages::CoordinatorMessage;
 
 use tributary::{
   Transaction as TributaryTransaction, Block, TributaryReader,
@@ -18,9 +23,9 @@ use serai_db::DbTxn;
 
 use crate::{
   Db,
-  tributary::handle::handle_application_tx,
-  processors::Processors,
-  tributary::{TributaryDb, TributarySpec, Transaction},
+  tributary::handle::{handle_application_tx, handle_dkg_timeout, handle_heartbeat_epoch},
+  processors::{Message, Processors},
+  tributary::{Fault, TributaryDb, TributarySpec, Transaction},
   P2p,
 };
 
@@ -28,10 +33,52 @@ use crate::{
 pub enum RecognizedIdType {
   Batch,
   Plan,
+  Heartbeat,
+  Cosign,
+}
+
+// Processors which buffers the messages sent to it
+// This lets the messages be saved to the DB atomically with the handling of the event which
+// created them, so they're neither lost nor re-created if we're killed mid-block
+#[derive(Clone, Default)]
+struct BufferedProcessors(Arc<Mutex<Vec<(NetworkId, CoordinatorMessage)>>>);
+impl BufferedProcessors {
+  fn take(&self) -> Vec<(NetworkId, CoordinatorMessage)> {
+    core::mem::take(&mut *self.0.lock().unwrap())
+  }
+}
+
+#[async_trait::async_trait]
+impl Processors for BufferedProcessors {
+  async fn send(&self, network: NetworkId, msg: CoordinatorMessage) {
+    self.0.lock().unwrap().push((network, msg));
+  }
+  async fn recv(&mut self, _: NetworkId) -> Message {
+    unreachable!("BufferedProcessors only sends messages")
+  }
+  async fn ack(&mut self, _: Message) {
+    unreachable!("BufferedProcessors only sends messages")
+  }
+}
+
+// Send the messages queued for the processors, in order
+// Each message is removed from the queue once sent. If we're killed after sending a message, yet
+// before removing it, it'll be sent again on reboot. This is safe as the message-queue only
+// delivers one message per intent
+async fn send_queued_processor_messages<D: Db, Pro: Processors>(
+  db: &mut TributaryDb<D>,
+  genesis: [u8; 32],
+  processors: &Pro,
+) {
+  while let Some((network, msg)) = TributaryDb::<D>::next_queued_processor_message(&db.0, genesis) {
+    processors.send(network, msg).await;
+    db.0.batch(|txn| TributaryDb::<D>::pop_queued_processor_message(txn, genesis));
+  }
 }
 
 // Handle a specific Tributary block
 #[allow(clippy::needless_pass_by_ref_mut)] // False positive?
+#[tracing::instrument(skip_all, fields(block = %hex::encode(block.hash())))]
 async fn handle_block<
   D: Db,
   Pro: Processors,
@@ -49,7 +96,7 @@ async fn handle_block<
   spec: &TributarySpec,
   block: Block<Transaction>,
 ) {
-  log::info!("found block for Tributary {:?}", spec.set());
+  tracing::info!("found block for Tributary {:?}", spec.set());
 
   let genesis = spec.genesis();
   let hash = block.hash();
@@ -63,16 +110,30 @@ async fn handle_block<
     }
 
     let mut txn = db.0.txn();
+    let buffered = BufferedProcessors::default();
 
     match tx {
       TributaryTransaction::Tendermint(TendermintTx::SlashEvidence(ev)) => {
         // Since the evidence is on the chain, it should already have been validated
         // We can just punish the signer
         let msgs = decode_evidence::<TendermintNetwork<D, Transaction, P>>(&ev).unwrap();
-
-        // Since anything with evidence is fundamentally faulty behavior, not just temporal errors,
-        // mark the node as fatally slashed
-        TributaryDb::<D>::set_fatally_slashed(&mut txn, genesis, msgs.0.msg.sender);
+        let sender = msgs.0.msg.sender;
+
+        // Multiple pieces of evidence may be published against a validator, yet they're only
+        // punished once
+        if TributaryDb::<D>::save_evidence(&mut txn, genesis, sender, &ev) {
+          tracing::warn!(
+            "validator {} equivocated on tributary {:?}",
+            hex::encode(sender),
+            spec.set()
+          );
+
+          // Since anything with evidence is fundamentally faulty behavior, not just temporal
+          // errors, mark the node as fatally slashed
+          // This is included in the slash report eventually published to Serai
+          TributaryDb::<D>::record_fault(&mut txn, genesis, sender, Fault::Equivocation);
+          TributaryDb::<D>::set_fatally_slashed(&mut txn, genesis, sender);
+        }
 
         // TODO2: disconnect the node from network/ban from further participation in Tributary
       }
@@ -80,7 +141,7 @@ async fn handle_block<
         handle_application_tx::<D, _, _, _, _, _>(
           tx,
           spec,
-          processors,
+          &buffered,
           publish_serai_tx.clone(),
           key,
           recognized_id.clone(),
@@ -90,13 +151,34 @@ async fn handle_block<
       }
     }
 
+    TributaryDb::<D>::queue_processor_messages(&mut txn, genesis, buffered.take());
     TributaryDb::<D>::handle_event(&mut txn, hash, event_id);
     txn.commit();
+    send_queued_processor_messages(db, genesis, processors).await;
 
     event_id += 1;
   }
 
-  // TODO2: Trigger any necessary re-attempts
+  // Advance the heartbeat epoch and check if the DKG timed out with this block, which is handled
+  // as the block's final event
+  // The heartbeat epoch is advanced first so the DKG timeout sees who's currently offline
+  // The block is marked as handled within the same transaction, so either all of the final event's
+  // writes are applied alongside the block being marked as handled, or none are
+  let mut txn = db.0.txn();
+  // This event will only have been handled without the block being marked as handled if handled
+  // by a prior version which didn't write them atomically
+  if !TributaryDb::<D>::handled_event(&txn, hash, event_id) {
+    let buffered = BufferedProcessors::default();
+    handle_heartbeat_epoch::<D, _, _>(&mut txn, spec, recognized_id.clone()).await;
+    handle_dkg_timeout::<D, _>(&mut txn, spec, key, &buffered).await;
+    TributaryDb::<D>::queue_processor_messages(&mut txn, genesis, buffered.take());
+    TributaryDb::<D>::handle_event(&mut txn, hash, event_id);
+  }
+  TributaryDb::<D>::set_last_block(&mut txn, genesis, hash);
+  txn.commit();
+  send_queued_processor_messages(db, genesis, processors).await;
+
+  // TODO2: Trigger any necessary re-attempts for batches and plans
 }
 
 pub(crate) async fn handle_new_blocks<
@@ -117,6 +199,10 @@ pub(crate) async fn handle_new_blocks<
   tributary: &TributaryReader<D, Transaction>,
 ) {
   let genesis = tributary.genesis();
+
+  // Send any messages left queued by a prior run which was killed
+  send_queued_processor_messages(db, genesis, processors).await;
+
   let mut last_block = db.last_block(genesis);
   while let Some(next) = tributary.block_after(&last_block) {
     let block = tributary.block(&next).unwrap();
@@ -131,6 +217,5 @@ pub(crate) async fn handle_new_blocks<
     )
     .await;
     last_block = next;
-    db.set_last_block(genesis, next);
   }
 }
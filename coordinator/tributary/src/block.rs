@@ -7,15 +7,20 @@ use thiserror::Error;
 
 use blake2::{Digest, Blake2s256};
 
+use rand::rngs::OsRng;
+
 use ciphersuite::{Ciphersuite, Ristretto};
+use schnorr::SchnorrSignature;
+use multiexp::BatchVerifier;
 
 use tendermint::ext::{Network, Commit};
 
 use crate::{
   transaction::{
-    TransactionError, Signed, TransactionKind, Transaction as TransactionTrait, verify_transaction,
+    TransactionError, Signed, TransactionKind, Transaction as TransactionTrait,
+    verify_transaction_stateless, verify_nonce,
   },
-  BLOCK_SIZE_LIMIT, ReadWrite, merkle, Transaction,
+  BLOCK_SIZE_LIMIT, ConsensusParams, ReadWrite, merkle, Transaction,
   tendermint::tx::verify_tendermint_tx,
 };
 
@@ -47,6 +52,10 @@ pub enum BlockError {
   TransactionError(TransactionError),
 }
 
+// A signed transaction's signer, signature, and challenge
+type SignatureStatement =
+  (<Ristretto as Ciphersuite>::G, SchnorrSignature<Ristretto>, <Ristretto as Ciphersuite>::F);
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct BlockHeader {
   pub parent: [u8; 32],
@@ -108,7 +117,8 @@ impl<T: TransactionTrait> ReadWrite for Block<T> {
 impl<T: TransactionTrait> Block<T> {
   /// Create a new block.
   ///
-  /// mempool is expected to only have valid, non-conflicting transactions, sorted by nonce.
+  /// mempool is expected to only have valid, non-conflicting transactions, with each signer's
+  /// transactions sorted by nonce.
   pub(crate) fn new(parent: [u8; 32], provided: Vec<T>, mempool: Vec<Transaction<T>>) -> Self {
     let mut txs = vec![];
     for tx in provided {
@@ -130,21 +140,14 @@ impl<T: TransactionTrait> Block<T> {
     // then signed
     txs.extend(signed);
 
-    // Check TXs are sorted by nonce.
-    let nonce = |tx: &Transaction<T>| {
-      if let TransactionKind::Signed(Signed { nonce, .. }) = tx.kind() {
-        *nonce
-      } else {
-        0
-      }
-    };
-    let mut last = 0;
+    // Check each signer's TXs are sorted by nonce.
+    let mut last = HashMap::new();
     for tx in &txs {
-      let nonce = nonce(tx);
-      if nonce < last {
-        panic!("TXs in mempool weren't ordered by nonce");
+      if let TransactionKind::Signed(Signed { signer, nonce, .. }) = tx.kind() {
+        if last.insert(*signer, *nonce).is_some_and(|last| *nonce < last) {
+          panic!("TXs in mempool weren't ordered by nonce");
+        }
       }
-      last = nonce;
     }
 
     let mut res =
@@ -165,10 +168,16 @@ impl<T: TransactionTrait> Block<T> {
     self.header.hash()
   }
 
+  /// Verify this block is valid to build off the last block.
+  ///
+  /// Checks dependent on the chain's state, such as ordering and nonces, are performed for every
+  /// transaction before any transaction's own checks. Signatures are verified last. If a block has
+  /// multiple invalid transactions, the error returned may not be for the first of them.
   #[allow(clippy::too_many_arguments)]
   pub(crate) fn verify<N: Network>(
     &self,
     genesis: [u8; 32],
+    params: ConsensusParams,
     last_block: [u8; 32],
     mut locally_provided: HashMap<&'static str, VecDeque<T>>,
     mut next_nonces: HashMap<<Ristretto as Ciphersuite>::G, u32>,
@@ -200,9 +209,15 @@ impl<T: TransactionTrait> Block<T> {
       Err(BlockError::InvalidParent)?;
     }
 
+    // Verification is split into two stages
+    // The first stage is serial, checking everything dependent on the chain's state (such as
+    // ordering and nonces). Since it only involves cheap checks, it quickly rejects invalid blocks
+    // The second stage performs the checks which are independent per transaction, in parallel if
+    // the parallel feature is enabled, before batch verifying every signature in the block
     let mut last_tx_order = Order::Provided;
     let mut included_in_block = HashSet::new();
     let mut txs = Vec::with_capacity(self.transactions.len());
+    let mut application = vec![];
     for tx in self.transactions.iter() {
       let tx_hash = tx.hash();
       txs.push(tx_hash);
@@ -242,8 +257,8 @@ impl<T: TransactionTrait> Block<T> {
       last_tx_order = current_tx_order;
 
       if current_tx_order == Order::Provided {
-        // We don't need to call verify_transaction since we did when we locally provided this
-        // transaction. Since it's identical, it must be valid
+        // We don't need to verify this transaction since we did when we locally provided it.
+        // Since it's identical, it must be valid
         continue;
       }
 
@@ -251,15 +266,18 @@ impl<T: TransactionTrait> Block<T> {
       // use this pattern of verifying tendermint Txs and app txs differently?
       match tx {
         Transaction::Tendermint(tx) => {
-          match verify_tendermint_tx::<N>(tx, schema.clone(), &commit) {
+          match verify_tendermint_tx::<N>(tx, params, schema.clone(), &commit) {
             Ok(()) => {}
             Err(e) => Err(BlockError::TransactionError(e))?,
           }
         }
-        Transaction::Application(tx) => match verify_transaction(tx, genesis, &mut next_nonces) {
-          Ok(()) => {}
-          Err(e) => Err(BlockError::TransactionError(e))?,
-        },
+        Transaction::Application(tx) => {
+          if let TransactionKind::Signed(signed) = tx.kind() {
+            verify_nonce(signed, &next_nonces).map_err(BlockError::TransactionError)?;
+            next_nonces.insert(signed.signer, signed.nonce + 1);
+          }
+          application.push(tx);
+        }
       }
     }
 
@@ -267,6 +285,49 @@ impl<T: TransactionTrait> Block<T> {
       Err(BlockError::InvalidTransactions)?;
     }
 
+    // Returns the statement for signed transactions, to be batch verified
+    let verify = |tx: &T| -> Result<Option<SignatureStatement>, TransactionError> {
+      verify_transaction_stateless(tx)?;
+      Ok(match tx.kind() {
+        TransactionKind::Signed(Signed { signer, signature, .. }) => {
+          Some((*signer, *signature, tx.sig_hash(genesis)))
+        }
+        _ => None,
+      })
+    };
+
+    #[cfg(feature = "parallel")]
+    let verified = {
+      use rayon::prelude::*;
+      application.par_iter().map(|tx| verify(tx)).collect::<Vec<_>>()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let verified = application.iter().map(|tx| verify(tx)).collect::<Vec<_>>();
+
+    let mut batch = BatchVerifier::new(verified.len());
+    let mut statements = Vec::with_capacity(verified.len());
+    for (i, verified) in verified.into_iter().enumerate() {
+      if let Some(statement) = verified.map_err(BlockError::TransactionError)? {
+        let (signer, signature, challenge) = statement;
+        signature.batch_verify(&mut OsRng, &mut batch, i, signer, challenge);
+        statements.push((i, statement));
+      }
+    }
+    if !batch.verify_vartime() {
+      // Verify each signature individually to identify the transaction with an invalid signature
+      for (i, (signer, signature, challenge)) in statements {
+        if !signature.verify(signer, challenge) {
+          tracing::warn!(
+            "transaction {} in block {} had an invalid signature",
+            hex::encode(application[i].hash()),
+            hex::encode(self.hash()),
+          );
+          break;
+        }
+      }
+      Err(BlockError::TransactionError(TransactionError::InvalidSignature))?;
+    }
+
     Ok(())
   }
 }
@@ -9,7 +9,8 @@ use scale::Decode;
 use tendermint::ext::{Network, Commit};
 
 use crate::{
-  ReadWrite, ProvidedError, ProvidedTransactions, BlockError, Block, Mempool, Transaction,
+  ReadWrite, ProvidedError, ProvidedTransactions, BlockError, Block, MempoolLimits, Mempool,
+  ConsensusParams, Transaction,
   transaction::{Signed, TransactionKind, Transaction as TransactionTrait},
 };
 
@@ -17,10 +18,12 @@ use crate::{
 pub(crate) struct Blockchain<D: Db, T: TransactionTrait> {
   db: Option<D>,
   genesis: [u8; 32],
+  params: ConsensusParams,
 
   block_number: u32,
   tip: [u8; 32],
   next_nonces: HashMap<<Ristretto as Ciphersuite>::G, u32>,
+  weights: HashMap<<Ristretto as Ciphersuite>::G, u64>,
 
   provided: ProvidedTransactions<D, T>,
   mempool: Mempool<D, T>,
@@ -32,8 +35,8 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
   fn tip_key(genesis: [u8; 32]) -> Vec<u8> {
     D::key(b"tributary_blockchain", b"tip", genesis)
   }
-  fn block_number_key(&self) -> Vec<u8> {
-    D::key(b"tributary_blockchain", b"block_number", self.genesis)
+  fn block_number_key(genesis: [u8; 32]) -> Vec<u8> {
+    D::key(b"tributary_blockchain", b"block_number", genesis)
   }
   fn block_key(genesis: &[u8], hash: &[u8; 32]) -> Vec<u8> {
     D::key(b"tributary_blockchain", b"block", [genesis, hash].concat())
@@ -50,48 +53,54 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
   fn unsigned_included_key(genesis: &[u8], hash: &[u8; 32]) -> Vec<u8> {
     D::key(b"tributary_blockchain", b"unsigned_included", [genesis, hash].concat())
   }
-  fn next_nonce_key(&self, signer: &<Ristretto as Ciphersuite>::G) -> Vec<u8> {
+  fn next_nonce_key(genesis: [u8; 32], signer: &<Ristretto as Ciphersuite>::G) -> Vec<u8> {
     D::key(
       b"tributary_blockchain",
       b"next_nonce",
-      [self.genesis.as_ref(), signer.to_bytes().as_ref()].concat(),
+      [genesis.as_ref(), signer.to_bytes().as_ref()].concat(),
     )
   }
 
   pub(crate) fn new(
     db: D,
     genesis: [u8; 32],
-    participants: &[<Ristretto as Ciphersuite>::G],
+    participants: &[(<Ristretto as Ciphersuite>::G, u64)],
+    params: ConsensusParams,
   ) -> Self {
     let mut next_nonces = HashMap::new();
-    for participant in participants {
+    for (participant, _) in participants {
       next_nonces.insert(*participant, 0);
     }
 
     let mut res = Self {
       db: Some(db.clone()),
       genesis,
+      params,
 
       block_number: 0,
       tip: genesis,
       next_nonces,
+      weights: participants.iter().cloned().collect(),
 
       provided: ProvidedTransactions::new(db.clone(), genesis),
-      mempool: Mempool::new(db, genesis),
+      mempool: Mempool::new(db, genesis, params),
 
       next_block_notifications: VecDeque::new(),
     };
 
     if let Some((block_number, tip)) = {
       let db = res.db.as_ref().unwrap();
-      db.get(res.block_number_key()).map(|number| (number, db.get(Self::tip_key(genesis)).unwrap()))
+      db.get(Self::block_number_key(genesis))
+        .map(|number| (number, db.get(Self::tip_key(genesis)).unwrap()))
     } {
       res.block_number = u32::from_le_bytes(block_number.try_into().unwrap());
       res.tip.copy_from_slice(&tip);
     }
 
-    for participant in participants {
-      if let Some(next_nonce) = res.db.as_ref().unwrap().get(res.next_nonce_key(participant)) {
+    for (participant, _) in participants {
+      if let Some(next_nonce) =
+        res.db.as_ref().unwrap().get(Self::next_nonce_key(genesis, participant))
+      {
         res.next_nonces.insert(*participant, u32::from_le_bytes(next_nonce.try_into().unwrap()));
       }
     }
@@ -140,6 +149,54 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     db.get(Self::tip_key(genesis)).map(|bytes| bytes.try_into().unwrap()).unwrap_or(genesis)
   }
 
+  /// Delete all of a blockchain's data from the database.
+  ///
+  /// This must not be called while a Blockchain with this genesis is instantiated.
+  pub(crate) fn prune(
+    db: &mut D,
+    genesis: [u8; 32],
+    participants: &[<Ristretto as Ciphersuite>::G],
+  ) {
+    let block_number = db
+      .get(Self::block_number_key(genesis))
+      .map(|number| u32::from_le_bytes(number.try_into().unwrap()))
+      .unwrap_or(0);
+
+    // Delete each block in its own DB transaction to bound memory usage
+    // Since the block number is deleted last, this can be safely re-run if interrupted
+    for number in 1 ..= block_number {
+      let Some(hash) = Self::block_hash_from_db(db, genesis, number) else { continue };
+      let block = Self::block_from_db(db, genesis, &hash);
+      let mut txn = db.txn();
+      if let Some(block) = block {
+        for tx in &block.transactions {
+          match tx.kind() {
+            TransactionKind::Provided(_) => {
+              ProvidedTransactions::<D, T>::prune_completed(&mut txn, genesis, tx.hash())
+            }
+            TransactionKind::Unsigned => txn.del(Self::unsigned_included_key(&genesis, &tx.hash())),
+            TransactionKind::Signed(_) => {}
+          }
+        }
+        txn.del(Self::block_after_key(&genesis, &block.parent()));
+      }
+      txn.del(Self::block_key(&genesis, &hash));
+      txn.del(Self::commit_key(&genesis, &hash));
+      txn.del(Self::block_hash_key(&genesis, number));
+      txn.commit();
+    }
+
+    let mut txn = db.txn();
+    for participant in participants {
+      txn.del(Self::next_nonce_key(genesis, participant));
+    }
+    Mempool::<D, T>::prune(&mut txn, genesis);
+    ProvidedTransactions::<D, T>::prune(&mut txn, genesis);
+    txn.del(Self::tip_key(genesis));
+    txn.del(Self::block_number_key(genesis));
+    txn.commit();
+  }
+
   pub(crate) fn add_transaction<N: Network>(
     &mut self,
     internal: bool,
@@ -166,6 +223,28 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     self.provided.provide(tx)
   }
 
+  pub(crate) fn set_mempool_limits(&mut self, limits: MempoolLimits) {
+    self.mempool.set_limits(limits);
+  }
+
+  pub(crate) fn mempool_transaction_size_limit(&self) -> usize {
+    self.mempool.transaction_size_limit()
+  }
+
+  pub(crate) fn mempool_rejections(&self, signer: <Ristretto as Ciphersuite>::G) -> u32 {
+    self.mempool.rejections(&signer)
+  }
+
+  pub(crate) fn mempool_transactions(&self) -> Vec<Transaction<T>> {
+    self.mempool.txs().values().cloned().collect()
+  }
+
+  /// If a transaction is in the mempool, including if it's buffered awaiting prior nonces.
+  pub(crate) fn in_mempool(&self, hash: &[u8; 32]) -> bool {
+    self.mempool.txs().contains_key(hash) ||
+      self.mempool.buffered().values().flat_map(|txs| txs.values()).any(|tx| tx.hash() == *hash)
+  }
+
   /// Returns the next nonce for signing, or None if they aren't a participant.
   pub(crate) fn next_nonce(&self, key: <Ristretto as Ciphersuite>::G) -> Option<u32> {
     Some(self.next_nonces.get(&key).cloned()?.max(self.mempool.next_nonce(&key).unwrap_or(0)))
@@ -179,7 +258,7 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     let block = Block::new(
       self.tip,
       self.provided.transactions.values().flatten().cloned().collect(),
-      self.mempool.block(&self.next_nonces, unsigned_in_chain),
+      self.mempool.block(&self.next_nonces, &self.weights, unsigned_in_chain),
     );
     // build_block should not return invalid blocks
     self.verify_block::<N>(&block, schema).unwrap();
@@ -201,6 +280,7 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     };
     block.verify::<N>(
       self.genesis,
+      self.params,
       self.tip,
       self.provided.transactions.clone(),
       self.next_nonces.clone(),
@@ -219,7 +299,7 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
   ) -> Result<(), BlockError> {
     self.verify_block::<N>(block, schema)?;
 
-    log::info!(
+    tracing::info!(
       "adding block {} to tributary {} with {} TXs",
       hex::encode(block.hash()),
       hex::encode(self.genesis),
@@ -237,7 +317,7 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
     txn.put(Self::tip_key(self.genesis), self.tip);
 
     self.block_number += 1;
-    txn.put(self.block_number_key(), self.block_number.to_le_bytes());
+    txn.put(Self::block_number_key(self.genesis), self.block_number.to_le_bytes());
 
     txn.put(Self::block_hash_key(&self.genesis, self.block_number), self.tip);
 
@@ -268,7 +348,7 @@ impl<D: Db, T: TransactionTrait> Blockchain<D, T> {
             panic!("verified block had an invalid nonce");
           }
 
-          txn.put(self.next_nonce_key(signer), next_nonce.to_le_bytes());
+          txn.put(Self::next_nonce_key(self.genesis, signer), next_nonce.to_le_bytes());
 
           self.mempool.remove(&tx.hash());
         }
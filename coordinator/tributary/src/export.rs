@@ -0,0 +1,156 @@
+use std::{
+  sync::Arc,
+  io::{self, Read, Write},
+  collections::HashSet,
+};
+
+use async_trait::async_trait;
+
+use ciphersuite::{Ciphersuite, Ristretto};
+
+use scale::Decode;
+use ::tendermint::{
+  ext::{Commit, SignatureScheme, Weights},
+  commit_msg,
+};
+
+use serai_db::Db;
+
+use crate::{
+  BLOCK_SIZE_LIMIT, ReadWrite, TransactionKind, TransactionTrait, Transaction, Block, Blockchain,
+  ConsensusParams, P2p, TributaryReader,
+  tendermint::{Validators, TendermintNetwork},
+};
+
+// Importing never produces messages, yet adding a block requires a network type
+#[derive(Clone, Debug)]
+struct NoP2p;
+#[async_trait]
+impl P2p for NoP2p {
+  async fn broadcast(&self, _: [u8; 32], _: Vec<u8>) {}
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+  io::Error::new(io::ErrorKind::Other, msg.into())
+}
+
+// This mirrors Network::verify_commit, which requires a running network
+fn verify_commit(
+  validators: &Arc<Validators>,
+  id: [u8; 32],
+  commit: &Commit<Arc<Validators>>,
+) -> bool {
+  if commit.validators.iter().collect::<HashSet<_>>().len() != commit.validators.len() {
+    return false;
+  }
+
+  if !validators.verify_aggregate(
+    &commit.validators,
+    &commit_msg(commit.end_time, id.as_ref()),
+    &commit.signature,
+  ) {
+    return false;
+  }
+
+  commit.validators.iter().map(|v| validators.weight(*v)).sum::<u64>() >= validators.threshold()
+}
+
+/// Write a Tributary's genesis, then every block and its commit, in order.
+///
+/// Returns the amount of blocks written.
+pub fn export_chain<D: Db, T: TransactionTrait, W: Write>(
+  tributary: &TributaryReader<D, T>,
+  writer: &mut W,
+) -> io::Result<u32> {
+  let genesis = tributary.genesis();
+  writer.write_all(&genesis)?;
+
+  let mut blocks = 0;
+  let mut last = genesis;
+  while let Some(hash) = tributary.block_after(&last) {
+    writer.write_all(&[1])?;
+    tributary.block(&hash).unwrap().write(writer)?;
+    let commit = tributary.commit(&hash).unwrap();
+    writer.write_all(&u32::try_from(commit.len()).unwrap().to_le_bytes())?;
+    writer.write_all(&commit)?;
+    blocks += 1;
+    last = hash;
+  }
+  // Mark the end, so a truncated export isn't mistaken for a shorter chain
+  writer.write_all(&[0])?;
+
+  Ok(blocks)
+}
+
+/// Import a chain written by `export_chain` into a DB which doesn't have any blocks for this
+/// Tributary.
+///
+/// Every block must be valid and have a valid commit from the specified validators. Since a commit
+/// proves the validators agreed to its block, the block's provided transactions are accepted
+/// without having been locally provided.
+///
+/// Returns the amount of blocks imported.
+pub fn import_chain<D: Db, T: TransactionTrait, R: Read>(
+  db: D,
+  genesis: [u8; 32],
+  validators: Vec<(<Ristretto as Ciphersuite>::G, u64)>,
+  params: ConsensusParams,
+  reader: &mut R,
+) -> io::Result<u32> {
+  let mut exported_genesis = [0; 32];
+  reader.read_exact(&mut exported_genesis)?;
+  if exported_genesis != genesis {
+    Err(invalid("chain was exported from a different Tributary"))?;
+  }
+
+  let mut blockchain = Blockchain::<D, T>::new(db, genesis, &validators, params);
+  if blockchain.block_number() != 0 {
+    Err(invalid("DB already has blocks for this Tributary"))?;
+  }
+  let validators =
+    Arc::new(Validators::new(genesis, validators).ok_or_else(|| invalid("invalid validators"))?);
+
+  let mut blocks = 0;
+  loop {
+    let mut flag = [0];
+    reader.read_exact(&mut flag)?;
+    match flag[0] {
+      0 => break,
+      1 => {}
+      _ => Err(invalid("invalid block flag"))?,
+    }
+
+    let block = Block::<T>::read(reader)?;
+    let mut commit_len = [0; 4];
+    reader.read_exact(&mut commit_len)?;
+    let commit_len = usize::try_from(u32::from_le_bytes(commit_len)).unwrap();
+    if commit_len > BLOCK_SIZE_LIMIT {
+      Err(invalid("commit exceeded the block size limit"))?;
+    }
+    let mut commit = vec![0; commit_len];
+    reader.read_exact(&mut commit)?;
+
+    let mut commit_ref = commit.as_ref();
+    let parsed = Commit::<Arc<Validators>>::decode(&mut commit_ref)
+      .map_err(|_| invalid("invalidly serialized commit"))?;
+    if (!commit_ref.is_empty()) || (!verify_commit(&validators, block.hash(), &parsed)) {
+      Err(invalid(format!("invalid commit for block {blocks}")))?;
+    }
+
+    for tx in &block.transactions {
+      if let Transaction::Application(tx) = tx {
+        if let TransactionKind::Provided(_) = tx.kind() {
+          blockchain
+            .provide_transaction(tx.clone())
+            .map_err(|e| invalid(format!("invalid provided transaction: {e}")))?;
+        }
+      }
+    }
+    blockchain
+      .add_block::<TendermintNetwork<D, T, NoP2p>>(&block, commit, validators.clone())
+      .map_err(|e| invalid(format!("invalid block {blocks}: {e}")))?;
+    blocks += 1;
+  }
+
+  Ok(blocks)
+}
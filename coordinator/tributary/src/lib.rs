@@ -1,5 +1,9 @@
-use core::{marker::PhantomData, fmt::Debug};
-use std::{sync::Arc, io};
+use core::{marker::PhantomData, ops::Deref, fmt::Debug};
+use std::{
+  sync::Arc,
+  io,
+  time::{Instant, Duration},
+};
 
 use async_trait::async_trait;
 
@@ -17,7 +21,7 @@ use ::tendermint::{
 
 use serai_db::Db;
 
-use tokio::sync::RwLock;
+use tokio::{sync::RwLock, time::sleep};
 
 mod merkle;
 pub(crate) use merkle::*;
@@ -40,6 +44,13 @@ pub(crate) use blockchain::*;
 mod mempool;
 pub(crate) use mempool::*;
 
+mod rebroadcast;
+pub(crate) use rebroadcast::*;
+pub use rebroadcast::{REBROADCAST_INITIAL_BLOCKS, REBROADCAST_MAX_BLOCKS};
+
+mod export;
+pub use export::{export_chain, import_chain};
+
 pub mod tendermint;
 pub(crate) use crate::tendermint::*;
 
@@ -50,11 +61,91 @@ pub mod tests;
 pub const TRANSACTION_SIZE_LIMIT: usize = 50_000;
 /// Amount of transactions a single account may have in the mempool.
 pub const ACCOUNT_MEMPOOL_LIMIT: u32 = 50;
+/// Amount of transactions the mempool may have in total.
+pub const MEMPOOL_LIMIT: usize = 5_000;
+/// Amount of transactions a single account may have buffered, awaiting prior nonces.
+pub const ACCOUNT_BUFFER_LIMIT: usize = 16;
 /// Block size limit.
 // This targets a growth limit of roughly 5 GB a day, under load, in order to prevent a malicious
 // participant from flooding disks and causing out of space errors in order processes.
 pub const BLOCK_SIZE_LIMIT: usize = 350_000;
 
+/// Limits on which transactions the mempool will accept.
+///
+/// These are local policy, not consensus rules, and may differ between validators. Transactions we
+/// add ourselves are exempt from the total and per-account limits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MempoolLimits {
+  /// Amount of transactions the mempool may have in total.
+  pub transactions: usize,
+  /// Amount of transactions a single account may have in the mempool.
+  pub account_transactions: u32,
+  /// Amount of transactions a single account may have buffered, awaiting prior nonces.
+  pub account_buffered: usize,
+  /// Size limit for an individual transaction. Values above TRANSACTION_SIZE_LIMIT are ignored.
+  pub transaction_size: usize,
+}
+
+impl Default for MempoolLimits {
+  fn default() -> Self {
+    MempoolLimits {
+      transactions: MEMPOOL_LIMIT,
+      account_transactions: ACCOUNT_MEMPOOL_LIMIT,
+      account_buffered: ACCOUNT_BUFFER_LIMIT,
+      transaction_size: TRANSACTION_SIZE_LIMIT,
+    }
+  }
+}
+
+/// The timing parameters of a Tributary's consensus.
+///
+/// Unlike MempoolLimits, these are consensus rules. All validators must use the same parameters for
+/// a Tributary, so they should be committed to by whatever defines its genesis. As block end times
+/// are in seconds, the resulting block time must be a whole number of seconds.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ConsensusParams {
+  block_processing_time: u32,
+  latency_time: u32,
+}
+
+impl Default for ConsensusParams {
+  fn default() -> Self {
+    ConsensusParams {
+      block_processing_time: crate::tendermint::BLOCK_PROCESSING_TIME,
+      latency_time: crate::tendermint::LATENCY_TIME,
+    }
+  }
+}
+
+impl ConsensusParams {
+  /// Create consensus parameters from the block processing time and the network latency time, in
+  /// milliseconds.
+  ///
+  /// Returns None if the resulting block time isn't a non-zero whole number of seconds.
+  pub fn new(block_processing_time: u32, latency_time: u32) -> Option<Self> {
+    let block_time = latency_time.checked_mul(3)?.checked_add(block_processing_time)?;
+    if (block_time == 0) || ((block_time % 1000) != 0) {
+      None?;
+    }
+    Some(ConsensusParams { block_processing_time, latency_time })
+  }
+
+  /// Maximum block processing time, in milliseconds.
+  pub fn block_processing_time(&self) -> u32 {
+    self.block_processing_time
+  }
+
+  /// Network latency time, in milliseconds.
+  pub fn latency_time(&self) -> u32 {
+    self.latency_time
+  }
+
+  /// The block time, in milliseconds.
+  pub fn block_time(&self) -> u32 {
+    self.block_processing_time + (3 * self.latency_time)
+  }
+}
+
 pub(crate) const TENDERMINT_MESSAGE: u8 = 0;
 pub(crate) const BLOCK_MESSAGE: u8 = 1;
 pub(crate) const TRANSACTION_MESSAGE: u8 = 2;
@@ -147,25 +238,31 @@ pub struct Tributary<D: Db, T: TransactionTrait, P: P2p> {
   synced_block: Arc<RwLock<SyncedBlockSender<TendermintNetwork<D, T, P>>>>,
   synced_block_result: Arc<RwLock<SyncedBlockResultReceiver>>,
   messages: Arc<RwLock<MessageSender<TendermintNetwork<D, T, P>>>>,
+
+  rebroadcaster: Arc<RwLock<Rebroadcaster>>,
 }
 
 impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
+  /// Create a new Tributary.
+  ///
+  /// `start_time` is the genesis time, in seconds since the Unix epoch.
   pub async fn new(
     db: D,
     genesis: [u8; 32],
     start_time: u64,
+    params: ConsensusParams,
     key: Zeroizing<<Ristretto as Ciphersuite>::F>,
     validators: Vec<(<Ristretto as Ciphersuite>::G, u64)>,
     p2p: P,
   ) -> Option<Self> {
-    log::info!("new Tributary with genesis {}", hex::encode(genesis));
+    tracing::info!("new Tributary with genesis {}", hex::encode(genesis));
 
-    let validators_vec = validators.iter().map(|validator| validator.0).collect::<Vec<_>>();
+    let our_key = Ristretto::generator() * key.deref();
 
     let signer = Arc::new(Signer::new(genesis, key));
+    let mut blockchain = Blockchain::new(db.clone(), genesis, &validators, params);
     let validators = Arc::new(Validators::new(genesis, validators)?);
 
-    let mut blockchain = Blockchain::new(db.clone(), genesis, &validators_vec);
     let block_number = BlockNumber(blockchain.block_number().into());
 
     let start_time = if let Some(commit) = blockchain.commit(&blockchain.tip()) {
@@ -178,12 +275,23 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     );
     let blockchain = Arc::new(RwLock::new(blockchain));
 
-    let network = TendermintNetwork { genesis, signer, validators, blockchain, p2p };
+    let network = TendermintNetwork { genesis, params, signer, validators, blockchain, p2p };
 
     let TendermintHandle { synced_block, synced_block_result, messages, machine } =
       TendermintMachine::new(network.clone(), block_number, start_time, proposal).await;
     tokio::task::spawn(machine.run());
 
+    // Resume rebroadcasting the transactions we signed which are still in the mempool from
+    // before we rebooted
+    let mut rebroadcaster = Rebroadcaster::new(Duration::from_millis(params.block_time().into()));
+    for tx in network.blockchain.read().await.mempool_transactions() {
+      if matches!(tx.kind(), TransactionKind::Signed(signed) if signed.signer == our_key) {
+        rebroadcaster.track(Instant::now(), tx.hash(), Self::transaction_message(&tx));
+      }
+    }
+    let rebroadcaster = Arc::new(RwLock::new(rebroadcaster));
+    tokio::task::spawn(Self::rebroadcast(network.clone(), rebroadcaster.clone()));
+
     Some(Self {
       db,
       genesis,
@@ -191,11 +299,49 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
       synced_block: Arc::new(RwLock::new(synced_block)),
       synced_block_result: Arc::new(RwLock::new(synced_block_result)),
       messages: Arc::new(RwLock::new(messages)),
+      rebroadcaster,
     })
   }
 
-  pub fn block_time() -> u32 {
-    TendermintNetwork::<D, T, P>::block_time()
+  fn transaction_message(tx: &Transaction<T>) -> Vec<u8> {
+    let mut msg = vec![TRANSACTION_MESSAGE];
+    tx.write(&mut msg).unwrap();
+    msg
+  }
+
+  // Rebroadcast locally-added transactions until they leave the mempool
+  async fn rebroadcast(
+    network: TendermintNetwork<D, T, P>,
+    rebroadcaster: Arc<RwLock<Rebroadcaster>>,
+  ) {
+    loop {
+      sleep(Duration::from_millis(network.params.block_time().into())).await;
+      let due = {
+        let blockchain = network.blockchain.read().await;
+        rebroadcaster.write().await.due(Instant::now(), |hash| blockchain.in_mempool(hash))
+      };
+      for msg in due {
+        network.p2p.broadcast(network.genesis, msg).await;
+      }
+    }
+  }
+
+  /// Delete all of a Tributary's data from the database.
+  ///
+  /// This must not be called while a Tributary with this genesis is running. It may be safely
+  /// called again if it was interrupted.
+  pub fn prune(db: &mut D, genesis: [u8; 32], validators: &[(<Ristretto as Ciphersuite>::G, u64)]) {
+    tracing::info!("pruning Tributary with genesis {}", hex::encode(genesis));
+    let validators = validators.iter().map(|validator| validator.0).collect::<Vec<_>>();
+    Blockchain::<D, T>::prune(db, genesis, &validators);
+  }
+
+  pub fn consensus_params(&self) -> ConsensusParams {
+    self.network.params
+  }
+
+  pub fn block_time(&self) -> Duration {
+    Duration::from_millis(self.network.params.block_time().into())
   }
 
   pub fn genesis(&self) -> [u8; 32] {
@@ -221,24 +367,55 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     self.network.blockchain.read().await.next_nonce(signer)
   }
 
+  /// Set the limits the mempool applies to transactions received from the network.
+  ///
+  /// Transactions already in the mempool are kept, even if they exceed the new limits.
+  pub async fn set_mempool_limits(&self, limits: MempoolLimits) {
+    self.network.blockchain.write().await.set_mempool_limits(limits)
+  }
+
+  /// The amount of transactions from this signer rejected for exceeding the mempool's limits.
+  ///
+  /// This is solely based on what this node has received, and isn't agreed upon by the validator
+  /// set. It may be used to penalize peers locally, yet not as a basis for slashing.
+  pub async fn mempool_rejections(&self, signer: <Ristretto as Ciphersuite>::G) -> u32 {
+    self.network.blockchain.read().await.mempool_rejections(signer)
+  }
+
+  /// The transactions currently in the mempool, in no particular order.
+  ///
+  /// Transactions buffered while awaiting prior nonces aren't included.
+  pub async fn mempool_transactions(&self) -> Vec<Transaction<T>> {
+    self.network.blockchain.read().await.mempool_transactions()
+  }
+
   // Returns if the transaction was new and valid.
   // Safe to be &self since the only meaningful usage of self is self.network.blockchain which
   // successfully acquires its own write lock
+  //
+  // Transactions added are rebroadcast, with an exponential backoff, until they're included in a
+  // block or otherwise leave the mempool.
   pub async fn add_transaction(&self, tx: T) -> bool {
     let tx = Transaction::Application(tx);
-    let mut to_broadcast = vec![TRANSACTION_MESSAGE];
-    tx.write(&mut to_broadcast).unwrap();
+    let hash = tx.hash();
+    let to_broadcast = Self::transaction_message(&tx);
     let res = self.network.blockchain.write().await.add_transaction::<TendermintNetwork<D, T, P>>(
       true,
       tx,
       self.network.signature_scheme(),
     );
     if res {
+      self.rebroadcaster.write().await.track(Instant::now(), hash, to_broadcast.clone());
       self.network.p2p.broadcast(self.genesis, to_broadcast).await;
     }
     res
   }
 
+  /// The amount of locally-added transactions being rebroadcast until they're included.
+  pub async fn pending_rebroadcasts(&self) -> usize {
+    self.rebroadcaster.read().await.len()
+  }
+
   async fn sync_block_internal(
     &self,
     block: Block<T>,
@@ -251,24 +428,24 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     };
 
     if block.header.parent != tip {
-      log::debug!("told to sync a block whose parent wasn't our tip");
+      tracing::debug!("told to sync a block whose parent wasn't our tip");
       return false;
     }
 
     let block = TendermintBlock(block.serialize());
     let mut commit_ref = commit.as_ref();
     let Ok(commit) = Commit::<Arc<Validators>>::decode(&mut commit_ref) else {
-      log::error!("sent an invalidly serialized commit");
+      tracing::error!("sent an invalidly serialized commit");
       return false;
     };
     // Storage DoS vector. We *could* truncate to solely the relevant portion, trying to save this,
     // yet then we'd have to test the truncation was performed correctly.
     if !commit_ref.is_empty() {
-      log::error!("sent an commit with additional data after it");
+      tracing::error!("sent an commit with additional data after it");
       return false;
     }
     if !self.network.verify_commit(block.id(), &commit) {
-      log::error!("sent an invalid commit");
+      tracing::error!("sent an invalid commit");
       return false;
     }
 
@@ -292,7 +469,7 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
     match msg.first() {
       Some(&TRANSACTION_MESSAGE) => {
         let Ok(tx) = Transaction::read::<&[u8]>(&mut &msg[1 ..]) else {
-          log::error!("received invalid transaction message");
+          tracing::error!("received invalid transaction message");
           return false;
         };
 
@@ -304,7 +481,7 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
             tx,
             self.network.signature_scheme(),
           );
-        log::debug!("received transaction message. valid new transaction: {res}");
+        tracing::debug!("received transaction message. valid new transaction: {res}");
         res
       }
 
@@ -312,7 +489,7 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
         let Ok(msg) =
           SignedMessageFor::<TendermintNetwork<D, T, P>>::decode::<&[u8]>(&mut &msg[1 ..])
         else {
-          log::error!("received invalid tendermint message");
+          tracing::error!("received invalid tendermint message");
           return false;
         };
 
@@ -323,12 +500,12 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
       Some(&BLOCK_MESSAGE) => {
         let mut msg_ref = &msg[1 ..];
         let Ok(block) = Block::<T>::read(&mut msg_ref) else {
-          log::error!("received invalid block message");
+          tracing::error!("received invalid block message");
           return false;
         };
         let commit = msg[(msg.len() - msg_ref.len()) ..].to_vec();
         if self.sync_block_internal(block, commit, &mut sync_block).await {
-          log::debug!("synced block over p2p net instead of building the commit ourselves");
+          tracing::debug!("synced block over p2p net instead of building the commit ourselves");
         }
         false
       }
@@ -350,6 +527,11 @@ impl<D: Db, T: TransactionTrait, P: P2p> Tributary<D, T, P> {
 #[derive(Clone)]
 pub struct TributaryReader<D: Db, T: TransactionTrait>(D, [u8; 32], PhantomData<T>);
 impl<D: Db, T: TransactionTrait> TributaryReader<D, T> {
+  /// Create a reader for a Tributary, which doesn't have to be running.
+  pub fn new(db: D, genesis: [u8; 32]) -> Self {
+    TributaryReader(db, genesis, PhantomData)
+  }
+
   pub fn genesis(&self) -> [u8; 32] {
     self.1
   }
@@ -368,10 +550,9 @@ impl<D: Db, T: TransactionTrait> TributaryReader<D, T> {
   pub fn block_after(&self, hash: &[u8; 32]) -> Option<[u8; 32]> {
     Blockchain::<D, T>::block_after(&self.0, self.1, hash)
   }
+  /// The end time of a block, in seconds since the Unix epoch.
   pub fn time_of_block(&self, hash: &[u8; 32]) -> Option<u64> {
-    self
-      .commit(hash)
-      .map(|commit| Commit::<Validators>::decode(&mut commit.as_ref()).unwrap().end_time)
+    self.commit(hash).map(|commit| Commit::<Validators>::decode(&mut commit.as_ref()).unwrap().end_time)
   }
 
   // This isn't static, yet can be read with only minor discrepancy risks
@@ -1,13 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{VecDeque, HashMap, BTreeMap};
 
-use ciphersuite::{Ciphersuite, Ristretto};
+use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
 
-use serai_db::{DbTxn, Db};
+use serai_db::{Get, DbTxn, Db};
 
 use tendermint::ext::{Network, Commit};
 
 use crate::{
-  ACCOUNT_MEMPOOL_LIMIT, ReadWrite,
+  TRANSACTION_SIZE_LIMIT, MempoolLimits, ConsensusParams, ReadWrite,
   transaction::{Signed, TransactionKind, Transaction as TransactionTrait, verify_transaction},
   tendermint::tx::verify_tendermint_tx,
   Transaction,
@@ -17,24 +17,32 @@ use crate::{
 pub(crate) struct Mempool<D: Db, T: TransactionTrait> {
   db: D,
   genesis: [u8; 32],
+  params: ConsensusParams,
+
+  limits: MempoolLimits,
 
   txs: HashMap<[u8; 32], Transaction<T>>,
   next_nonces: HashMap<<Ristretto as Ciphersuite>::G, u32>,
+  // Amount of transactions rejected per signer for exceeding the limits
+  rejections: HashMap<<Ristretto as Ciphersuite>::G, u32>,
+  // Signed transactions whose nonces are ahead of the next nonce, held until the gap is filled
+  // These aren't saved to the DB as they'll be regossiped if lost
+  buffered: HashMap<<Ristretto as Ciphersuite>::G, BTreeMap<u32, T>>,
 }
 
 impl<D: Db, T: TransactionTrait> Mempool<D, T> {
-  fn transaction_key(&self, hash: &[u8]) -> Vec<u8> {
-    D::key(b"tributary_mempool", b"transaction", [self.genesis.as_ref(), hash].concat())
+  fn transaction_key(genesis: [u8; 32], hash: &[u8]) -> Vec<u8> {
+    D::key(b"tributary_mempool", b"transaction", [genesis.as_ref(), hash].concat())
   }
-  fn current_mempool_key(&self) -> Vec<u8> {
-    D::key(b"tributary_mempool", b"current", self.genesis)
+  fn current_mempool_key(genesis: [u8; 32]) -> Vec<u8> {
+    D::key(b"tributary_mempool", b"current", genesis)
   }
 
   // save given tx to the mempool db
   fn save_tx(&mut self, tx: Transaction<T>) {
     let tx_hash = tx.hash();
-    let transaction_key = self.transaction_key(&tx_hash);
-    let current_mempool_key = self.current_mempool_key();
+    let transaction_key = Self::transaction_key(self.genesis, &tx_hash);
+    let current_mempool_key = Self::current_mempool_key(self.genesis);
     #[allow(clippy::unwrap_or_default)]
     let mut current_mempool = self.db.get(&current_mempool_key).unwrap_or(vec![]);
 
@@ -55,16 +63,26 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
     unsigned_in_chain(hash) || self.txs.contains_key(&hash)
   }
 
-  pub(crate) fn new(db: D, genesis: [u8; 32]) -> Self {
-    let mut res = Mempool { db, genesis, txs: HashMap::new(), next_nonces: HashMap::new() };
+  pub(crate) fn new(db: D, genesis: [u8; 32], params: ConsensusParams) -> Self {
+    let mut res = Mempool {
+      db,
+      genesis,
+      params,
+      limits: MempoolLimits::default(),
+      txs: HashMap::new(),
+      next_nonces: HashMap::new(),
+      rejections: HashMap::new(),
+      buffered: HashMap::new(),
+    };
 
-    let current_mempool = res.db.get(res.current_mempool_key()).unwrap_or(vec![]);
+    let current_mempool = res.db.get(Self::current_mempool_key(genesis)).unwrap_or(vec![]);
 
     for hash in current_mempool.chunks(32) {
       let hash: [u8; 32] = hash.try_into().unwrap();
-      let tx: Transaction<T> =
-        Transaction::read::<&[u8]>(&mut res.db.get(res.transaction_key(&hash)).unwrap().as_ref())
-          .unwrap();
+      let tx: Transaction<T> = Transaction::read::<&[u8]>(
+        &mut res.db.get(Self::transaction_key(genesis, &hash)).unwrap().as_ref(),
+      )
+      .unwrap();
       debug_assert_eq!(tx.hash(), hash);
 
       match tx {
@@ -92,6 +110,75 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
     res
   }
 
+  // Note a transaction was rejected for exceeding our limits
+  fn note_rejection(
+    &mut self,
+    blockchain_next_nonces: &HashMap<<Ristretto as Ciphersuite>::G, u32>,
+    tx: &Transaction<T>,
+  ) {
+    let Transaction::Application(tx) = tx else { return };
+    let TransactionKind::Signed(Signed { signer, signature, .. }) = tx.kind() else { return };
+    // Only track participants, so arbitrary keys can't grow the rejections map or make us verify
+    // signatures for them
+    if !blockchain_next_nonces.contains_key(signer) {
+      return;
+    }
+    // Only attribute the rejection if the signer actually signed this, so no one can be framed
+    if signature.verify(*signer, tx.sig_hash(self.genesis)) {
+      *self.rejections.entry(*signer).or_insert(0) += 1;
+    }
+  }
+
+  // Buffer a signed transaction whose nonce is ahead of the next nonce
+  fn buffer(&mut self, tx: T) {
+    let TransactionKind::Signed(Signed { signer, nonce, signature }) = tx.kind() else {
+      panic!("buffering a transaction which wasn't signed")
+    };
+    let (signer, nonce) = (*signer, *nonce);
+
+    // Verify everything which doesn't depend on the prior nonces, so junk can't fill the buffer
+    if tx.verify().is_err() || (!signature.verify(signer, tx.sig_hash(self.genesis))) {
+      return;
+    }
+
+    let buffered = self.buffered.entry(signer).or_default();
+    if buffered.contains_key(&nonce) {
+      return;
+    }
+    if buffered.len() >= self.limits.account_buffered {
+      // Prefer lower nonces, as they're the ones able to be released sooner
+      let Some(highest) = buffered.keys().next_back().cloned() else { return };
+      if highest < nonce {
+        return;
+      }
+      buffered.remove(&highest);
+    }
+    buffered.insert(nonce, tx);
+  }
+
+  // Move any buffered transactions which are now next into the mempool
+  fn release(&mut self, signer: <Ristretto as Ciphersuite>::G) {
+    loop {
+      let Some(next_nonce) = self.next_nonces.get(&signer).cloned() else { return };
+      let full = self.txs.len() >= self.limits.transactions;
+      let Some(buffered) = self.buffered.get_mut(&signer) else { return };
+      // Drop anything which went stale
+      buffered.retain(|nonce, _| *nonce >= next_nonce);
+      // Released transactions are subject to the same limit as any other transaction from the
+      // network, so if the mempool is full, leave them buffered until there's room
+      let tx = if full { None } else { buffered.remove(&next_nonce) };
+      if buffered.is_empty() {
+        self.buffered.remove(&signer);
+      }
+
+      let Some(tx) = tx else { return };
+      if verify_transaction(&tx, self.genesis, &mut self.next_nonces).is_err() {
+        continue;
+      }
+      self.save_tx(Transaction::Application(tx));
+    }
+  }
+
   /// Returns true if this is a valid, new transaction.
   pub(crate) fn add<N: Network>(
     &mut self,
@@ -102,6 +189,16 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
     unsigned_in_chain: impl Fn([u8; 32]) -> bool,
     commit: impl Fn(u32) -> Option<Commit<N::SignatureScheme>>,
   ) -> bool {
+    if tx.serialize().len() > self.transaction_size_limit() {
+      self.note_rejection(blockchain_next_nonces, &tx);
+      return false;
+    }
+    // Don't let other validators fill our mempool
+    // This isn't noted as a rejection as the signer of this transaction may not be at fault
+    if !internal && (self.txs.len() >= self.limits.transactions) {
+      return false;
+    }
+
     match &tx {
       Transaction::Tendermint(tendermint_tx) => {
         // All Tendermint transactions should be unsigned
@@ -113,7 +210,7 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
         }
 
         // verify the tx
-        if verify_tendermint_tx::<N>(tendermint_tx, schema, commit).is_err() {
+        if verify_tendermint_tx::<N>(tendermint_tx, self.params, schema, commit).is_err() {
           return false;
         }
       }
@@ -139,7 +236,17 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
 
             // If we have too many transactions from this sender, don't add this yet UNLESS we are
             // this sender
-            if !internal && (nonce >= &(blockchain_next_nonce + ACCOUNT_MEMPOOL_LIMIT)) {
+            if !internal &&
+              (nonce >= &blockchain_next_nonce.saturating_add(self.limits.account_transactions))
+            {
+              self.note_rejection(blockchain_next_nonces, &tx);
+              return false;
+            }
+
+            // If this is ahead of the next nonce, hold it until the transactions before it arrive
+            // Since it isn't yet valid, this still returns false
+            if *nonce > self.next_nonces[signer] {
+              self.buffer(app_tx.clone());
               return false;
             }
 
@@ -164,23 +271,71 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
     }
 
     // Save the TX to the pool
+    let signer = if let TransactionKind::Signed(Signed { signer, .. }) = tx.kind() {
+      Some(*signer)
+    } else {
+      None
+    };
     self.save_tx(tx);
+    // If this filled a gap, release the transactions buffered behind it
+    if let Some(signer) = signer {
+      self.release(signer);
+    }
     true
   }
 
+  /// Delete all transactions in the mempool from the database.
+  pub(crate) fn prune(txn: &mut D::Transaction<'_>, genesis: [u8; 32]) {
+    let current_mempool_key = Self::current_mempool_key(genesis);
+    if let Some(current_mempool) = txn.get(&current_mempool_key) {
+      for hash in current_mempool.chunks(32) {
+        txn.del(Self::transaction_key(genesis, hash));
+      }
+    }
+    txn.del(current_mempool_key);
+  }
+
+  pub(crate) fn set_limits(&mut self, limits: MempoolLimits) {
+    self.limits = limits;
+  }
+
+  /// The size limit for an individual transaction, as configured.
+  pub(crate) fn transaction_size_limit(&self) -> usize {
+    self.limits.transaction_size.min(TRANSACTION_SIZE_LIMIT)
+  }
+
+  pub(crate) fn rejections(&self, signer: &<Ristretto as Ciphersuite>::G) -> u32 {
+    self.rejections.get(signer).cloned().unwrap_or(0)
+  }
+
   // Returns None if the mempool doesn't have a nonce tracked.
   pub(crate) fn next_nonce(&self, signer: &<Ristretto as Ciphersuite>::G) -> Option<u32> {
     self.next_nonces.get(signer).cloned()
   }
 
   /// Get transactions to include in a block.
+  ///
+  /// Signed transactions are ordered so each signer receives space in the block proportional to
+  /// their weight. As blocks are truncated from the end when too large, this caps each signer to
+  /// their share of a congested block, with any share left unused going to the other signers.
   pub(crate) fn block(
     &mut self,
     blockchain_next_nonces: &HashMap<<Ristretto as Ciphersuite>::G, u32>,
+    weights: &HashMap<<Ristretto as Ciphersuite>::G, u64>,
     unsigned_in_chain: impl Fn([u8; 32]) -> bool,
   ) -> Vec<Transaction<T>> {
+    // The blockchain may have filled the gap before buffered transactions
+    for signer in self.buffered.keys().cloned().collect::<Vec<_>>() {
+      let Some(blockchain_next_nonce) = blockchain_next_nonces.get(&signer).cloned() else {
+        continue;
+      };
+      let next_nonce = self.next_nonces.entry(signer).or_insert(blockchain_next_nonce);
+      *next_nonce = (*next_nonce).max(blockchain_next_nonce);
+      self.release(signer);
+    }
+
     let mut unsigned = vec![];
-    let mut signed = vec![];
+    let mut signed = HashMap::<_, Vec<_>>::new();
     for hash in self.txs.keys().cloned().collect::<Vec<_>>() {
       let tx = &self.txs[&hash];
 
@@ -193,7 +348,7 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
           }
 
           // Since this TX isn't stale, include it
-          signed.push(tx.clone());
+          signed.entry(*signer).or_default().push(tx.clone());
         }
         TransactionKind::Unsigned => {
           if unsigned_in_chain(hash) {
@@ -207,7 +362,7 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
       }
     }
 
-    // Sort signed by nonce
+    // Sort each signer's transactions by nonce
     let nonce = |tx: &Transaction<T>| {
       if let TransactionKind::Signed(Signed { nonce, .. }) = tx.kind() {
         *nonce
@@ -215,17 +370,46 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
         unreachable!()
       }
     };
-    signed.sort_by(|a, b| nonce(a).partial_cmp(&nonce(b)).unwrap());
+    // Sort the signers by their keys, so ties are broken identically by every proposer
+    let mut signers = signed
+      .into_iter()
+      .map(|(signer, mut txs)| {
+        txs.sort_by_key(nonce);
+        let txs = txs
+          .into_iter()
+          .map(|tx| (u128::try_from(tx.serialize().len()).unwrap(), tx))
+          .collect::<VecDeque<_>>();
+        (signer, u128::from(weights[&signer]), txs, 0)
+      })
+      .collect::<Vec<_>>();
+    signers.sort_by_key(|(signer, ..)| signer.to_bytes());
+
+    // Repeatedly include the next transaction from whichever signer will have used the least space
+    // relative to their weight, once it's included
+    let mut ordered = vec![];
+    // min_by returns the first of equal elements, which is the signer with the lowest key
+    while let Some((i, _, _)) = signers
+      .iter()
+      .enumerate()
+      .filter_map(|(i, (_, weight, txs, used))| Some((i, used + txs.front()?.0, *weight)))
+      .min_by(|(_, used_a, weight_a), (_, used_b, weight_b)| {
+        (used_a * weight_b).cmp(&(used_b * weight_a))
+      })
+    {
+      let (len, tx) = signers[i].2.pop_front().unwrap();
+      signers[i].3 += len;
+      ordered.push(tx);
+    }
 
     // unsigned first, then signed.
-    unsigned.append(&mut signed);
+    unsigned.append(&mut ordered);
     unsigned
   }
 
   /// Remove a transaction from the mempool.
   pub(crate) fn remove(&mut self, tx: &[u8; 32]) {
-    let transaction_key = self.transaction_key(tx);
-    let current_mempool_key = self.current_mempool_key();
+    let transaction_key = Self::transaction_key(self.genesis, tx);
+    let current_mempool_key = Self::current_mempool_key(self.genesis);
     #[allow(clippy::unwrap_or_default)]
     let current_mempool = self.db.get(&current_mempool_key).unwrap_or(vec![]);
 
@@ -249,7 +433,10 @@ impl<D: Db, T: TransactionTrait> Mempool<D, T> {
     self.txs.remove(tx);
   }
 
-  #[cfg(test)]
+  pub(crate) fn buffered(&self) -> &HashMap<<Ristretto as Ciphersuite>::G, BTreeMap<u32, T>> {
+    &self.buffered
+  }
+
   pub(crate) fn txs(&self) -> &HashMap<[u8; 32], Transaction<T>> {
     &self.txs
   }
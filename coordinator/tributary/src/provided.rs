@@ -28,21 +28,25 @@ pub struct ProvidedTransactions<D: Db, T: Transaction> {
 }
 
 impl<D: Db, T: Transaction> ProvidedTransactions<D, T> {
-  fn transaction_key(&self, hash: &[u8]) -> Vec<u8> {
-    D::key(b"tributary_provided", b"transaction", [self.genesis.as_ref(), hash].concat())
+  fn transaction_key(genesis: [u8; 32], hash: &[u8]) -> Vec<u8> {
+    D::key(b"tributary_provided", b"transaction", [genesis.as_ref(), hash].concat())
   }
-  fn current_provided_key(&self) -> Vec<u8> {
-    D::key(b"tributary_provided", b"current", self.genesis)
+  fn current_provided_key(genesis: [u8; 32]) -> Vec<u8> {
+    D::key(b"tributary_provided", b"current", genesis)
   }
 
   pub(crate) fn new(db: D, genesis: [u8; 32]) -> Self {
     let mut res = ProvidedTransactions { db, genesis, transactions: HashMap::new() };
 
-    let currently_provided = res.db.get(res.current_provided_key()).unwrap_or(vec![]);
+    let currently_provided = res.db.get(Self::current_provided_key(genesis)).unwrap_or(vec![]);
     let mut i = 0;
     while i < currently_provided.len() {
       let tx = T::read::<&[u8]>(
-        &mut res.db.get(res.transaction_key(&currently_provided[i .. (i + 32)])).unwrap().as_ref(),
+        &mut res
+          .db
+          .get(Self::transaction_key(genesis, &currently_provided[i .. (i + 32)]))
+          .unwrap()
+          .as_ref(),
       )
       .unwrap();
 
@@ -71,12 +75,12 @@ impl<D: Db, T: Transaction> ProvidedTransactions<D, T> {
     }
 
     let tx_hash = tx.hash();
-    let provided_key = self.transaction_key(&tx_hash);
+    let provided_key = Self::transaction_key(self.genesis, &tx_hash);
     if self.db.get(&provided_key).is_some() {
       Err(ProvidedError::AlreadyProvided)?;
     }
 
-    let current_provided_key = self.current_provided_key();
+    let current_provided_key = Self::current_provided_key(self.genesis);
     #[allow(clippy::unwrap_or_default)]
     let mut currently_provided = self.db.get(&current_provided_key).unwrap_or(vec![]);
 
@@ -102,7 +106,7 @@ impl<D: Db, T: Transaction> ProvidedTransactions<D, T> {
   ) {
     assert_eq!(self.transactions.get_mut(order).unwrap().pop_front().unwrap().hash(), tx);
 
-    let current_provided_key = self.current_provided_key();
+    let current_provided_key = Self::current_provided_key(self.genesis);
     let mut currently_provided = txn.get(&current_provided_key).unwrap();
 
     // Find this TX's hash
@@ -121,4 +125,20 @@ impl<D: Db, T: Transaction> ProvidedTransactions<D, T> {
 
     txn.put(current_provided_key, currently_provided);
   }
+
+  /// Delete a completed provided transaction from the database.
+  pub(crate) fn prune_completed(txn: &mut D::Transaction<'_>, genesis: [u8; 32], tx: [u8; 32]) {
+    txn.del(Self::transaction_key(genesis, &tx));
+  }
+
+  /// Delete all provided transactions which have yet to be completed from the database.
+  pub(crate) fn prune(txn: &mut D::Transaction<'_>, genesis: [u8; 32]) {
+    let current_provided_key = Self::current_provided_key(genesis);
+    if let Some(currently_provided) = txn.get(&current_provided_key) {
+      for tx in currently_provided.chunks(32) {
+        txn.del(Self::transaction_key(genesis, tx));
+      }
+    }
+    txn.del(current_provided_key);
+  }
 }
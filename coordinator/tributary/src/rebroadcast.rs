@@ -0,0 +1,64 @@
+use std::{
+  time::{Instant, Duration},
+  collections::HashMap,
+};
+
+/// How many block times to wait before first rebroadcasting a transaction.
+pub const REBROADCAST_INITIAL_BLOCKS: u32 = 2;
+/// The most block times to wait between rebroadcasts of a transaction.
+pub const REBROADCAST_MAX_BLOCKS: u32 = 64;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Pending {
+  msg: Vec<u8>,
+  next: Instant,
+  backoff: Duration,
+}
+
+/// Locally-added transactions which have yet to be included in a block.
+///
+/// Each is rebroadcast, with an exponential backoff, until it leaves the mempool. A transaction
+/// added while we're disconnected would otherwise never be seen by the validator set.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Rebroadcaster {
+  block_time: Duration,
+  pending: HashMap<[u8; 32], Pending>,
+}
+
+impl Rebroadcaster {
+  pub(crate) fn new(block_time: Duration) -> Self {
+    Rebroadcaster { block_time, pending: HashMap::new() }
+  }
+
+  /// Track a transaction, by its hash and the message broadcasting it.
+  pub(crate) fn track(&mut self, now: Instant, hash: [u8; 32], msg: Vec<u8>) {
+    let backoff = self.block_time * REBROADCAST_INITIAL_BLOCKS;
+    self.pending.entry(hash).or_insert(Pending { msg, next: now + backoff, backoff });
+  }
+
+  /// The amount of transactions being tracked.
+  pub(crate) fn len(&self) -> usize {
+    self.pending.len()
+  }
+
+  /// Return the messages to rebroadcast, no longer tracking transactions which have left the
+  /// mempool, whether due to being included or being dropped.
+  pub(crate) fn due(
+    &mut self,
+    now: Instant,
+    in_mempool: impl Fn(&[u8; 32]) -> bool,
+  ) -> Vec<Vec<u8>> {
+    self.pending.retain(|hash, _| in_mempool(hash));
+
+    let max_backoff = self.block_time * REBROADCAST_MAX_BLOCKS;
+    let mut res = vec![];
+    for pending in self.pending.values_mut() {
+      if pending.next <= now {
+        res.push(pending.msg.clone());
+        pending.backoff = (pending.backoff * 2).min(max_backoff);
+        pending.next = now + pending.backoff;
+      }
+    }
+    res
+  }
+}
@@ -43,7 +43,7 @@ use tokio::{
 use crate::{
   TENDERMINT_MESSAGE, TRANSACTION_MESSAGE, BLOCK_MESSAGE, ReadWrite,
   transaction::Transaction as TransactionTrait, Transaction, BlockHeader, Block, BlockError,
-  Blockchain, P2p,
+  Blockchain, ConsensusParams, P2p,
 };
 
 pub mod tx;
@@ -266,6 +266,7 @@ impl BlockTrait for TendermintBlock {
 #[derive(Clone, Debug)]
 pub struct TendermintNetwork<D: Db, T: TransactionTrait, P: P2p> {
   pub(crate) genesis: [u8; 32],
+  pub(crate) params: ConsensusParams,
 
   pub(crate) signer: Arc<Signer>,
   pub(crate) validators: Arc<Validators>,
@@ -274,6 +275,7 @@ pub struct TendermintNetwork<D: Db, T: TransactionTrait, P: P2p> {
   pub(crate) p2p: P,
 }
 
+// These are the defaults for ConsensusParams
 pub const BLOCK_PROCESSING_TIME: u32 = 999;
 pub const LATENCY_TIME: u32 = 1667;
 // TODO: Add test asserting this
@@ -286,12 +288,16 @@ impl<D: Db, T: TransactionTrait, P: P2p> Network for TendermintNetwork<D, T, P>
   type Weights = Arc<Validators>;
   type Block = TendermintBlock;
 
-  // These are in milliseconds and create a six-second block time.
+  // These are in milliseconds and by default create a six-second block time.
   // The block time is the latency on message delivery (where a message is some piece of data
   // embedded in a transaction) times three plus the block processing time, hence why it should be
   // kept low.
-  const BLOCK_PROCESSING_TIME: u32 = BLOCK_PROCESSING_TIME;
-  const LATENCY_TIME: u32 = LATENCY_TIME;
+  fn block_processing_time(&self) -> u32 {
+    self.params.block_processing_time
+  }
+  fn latency_time(&self) -> u32 {
+    self.params.latency_time
+  }
 
   fn signer(&self) -> Arc<Signer> {
     self.signer.clone()
@@ -310,7 +316,7 @@ impl<D: Db, T: TransactionTrait, P: P2p> Network for TendermintNetwork<D, T, P>
   }
 
   async fn slash(&mut self, validator: Self::ValidatorId, slash_event: SlashEvent<Self>) {
-    log::error!(
+    tracing::error!(
       "validator {} triggered a slash event on tributary {} (with evidence: {})",
       hex::encode(validator),
       hex::encode(self.genesis),
@@ -321,7 +327,20 @@ impl<D: Db, T: TransactionTrait, P: P2p> Network for TendermintNetwork<D, T, P>
     let Some(tx) = (match slash_event {
       SlashEvent::WithEvidence(m1, m2) => {
         // create an unsigned evidence tx
-        Some(TendermintTx::SlashEvidence((m1, m2).encode()))
+        let tx = TendermintTx::SlashEvidence((m1, m2).encode());
+        // Evidence of conflicting proposals contains both blocks, which may exceed the size limit
+        // This uses the mempool's configured limit, as the mempool would otherwise reject it
+        let limit = self.blockchain.read().await.mempool_transaction_size_limit();
+        if tx.serialize().len() > limit {
+          tracing::warn!(
+            "evidence against {} on tributary {} was too large to publish",
+            hex::encode(validator),
+            hex::encode(self.genesis),
+          );
+          None
+        } else {
+          Some(tx)
+        }
       }
       SlashEvent::Id(_reason, _block, _round) => {
         // TODO: Increase locally observed slash points
@@ -394,14 +413,14 @@ impl<D: Db, T: TransactionTrait, P: P2p> Network for TendermintNetwork<D, T, P>
           break;
         }
         Err(BlockError::NonLocalProvided(hash)) => {
-          log::error!(
+          tracing::error!(
             "missing provided transaction {} which other validators on tributary {} had",
             hex::encode(hash),
             hex::encode(self.genesis)
           );
           // TODO: Use a notification system for when we have a new provided, in order to minimize
           // latency
-          sleep(Duration::from_secs(Self::block_time().into())).await;
+          sleep(Duration::from_millis(self.block_time().into())).await;
         }
         _ => return invalid_block(),
       }
@@ -8,7 +8,7 @@ use ciphersuite::{Ciphersuite, Ristretto};
 
 use crate::{
   transaction::{Transaction, TransactionKind, TransactionError},
-  ReadWrite,
+  ReadWrite, ConsensusParams,
 };
 
 use tendermint::{
@@ -107,6 +107,7 @@ pub fn decode_evidence<N: Network>(
 // re-implements an entire foreign library's checks for malicious behavior).
 pub(crate) fn verify_tendermint_tx<N: Network>(
   tx: &TendermintTx,
+  params: ConsensusParams,
   schema: N::SignatureScheme,
   commit: impl Fn(u32) -> Option<Commit<N::SignatureScheme>>,
 ) -> Result<(), TransactionError> {
@@ -194,7 +195,13 @@ pub(crate) fn verify_tendermint_tx<N: Network>(
           // calculate the end time till the msg round
           let mut last_end_time = CanonicalInstant::new(prior_commit.end_time);
           for r in 0 ..= first.round.0 {
-            last_end_time = RoundData::<N>::new(RoundNumber(r), last_end_time).end_time();
+            last_end_time = RoundData::<N>::new(
+              params.block_processing_time,
+              params.latency_time,
+              RoundNumber(r),
+              last_end_time,
+            )
+            .end_time();
           }
 
           // verify that the commit was actually invalid
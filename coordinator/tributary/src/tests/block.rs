@@ -1,5 +1,8 @@
 use std::{sync::Arc, io, collections::HashMap, fmt::Debug};
 
+use zeroize::Zeroizing;
+use rand::rngs::OsRng;
+
 use blake2::{Digest, Blake2s256};
 use ciphersuite::{
   group::{ff::Field, Group},
@@ -11,8 +14,8 @@ use serai_db::MemDb;
 use tendermint::ext::Commit;
 
 use crate::{
-  ReadWrite, BlockError, Block, Transaction,
-  tests::p2p::DummyP2p,
+  ReadWrite, BlockError, Block, ConsensusParams, Transaction,
+  tests::{p2p::DummyP2p, new_genesis, signed_transaction},
   transaction::{TransactionError, Signed, TransactionKind, Transaction as TransactionTrait},
   tendermint::{TendermintNetwork, Validators},
 };
@@ -85,6 +88,7 @@ fn empty_block() {
   Block::<NonceTransaction>::new(LAST, vec![], vec![])
     .verify::<N>(
       GENESIS,
+      ConsensusParams::default(),
       LAST,
       HashMap::new(),
       HashMap::new(),
@@ -117,6 +121,7 @@ fn duplicate_nonces() {
 
     let res = Block::new(LAST, vec![], mempool).verify::<N>(
       GENESIS,
+      ConsensusParams::default(),
       LAST,
       HashMap::new(),
       HashMap::from([(<Ristretto as Ciphersuite>::G::identity(), 0)]),
@@ -131,3 +136,50 @@ fn duplicate_nonces() {
     }
   }
 }
+
+#[test]
+fn invalid_signature() {
+  const LAST: [u8; 32] = [0x01; 32];
+  let genesis = new_genesis();
+
+  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let keys = (0 .. 3)
+    .map(|_| Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng)))
+    .collect::<Vec<_>>();
+
+  // Run once with every signature valid, and then once for each transaction with its signature
+  // invalidated, confirming any single invalid signature in the batch is caught
+  for invalid in [None, Some(0), Some(1), Some(2)] {
+    let mut mempool = vec![];
+    let mut next_nonces = HashMap::new();
+    for (i, key) in keys.iter().enumerate() {
+      let mut tx = signed_transaction(&mut OsRng, genesis, key, 0);
+      if invalid == Some(i) {
+        tx.1.signature.s += <Ristretto as Ciphersuite>::F::ONE;
+      }
+      next_nonces.insert(tx.1.signer, 0);
+      mempool.push(Transaction::Application(tx));
+    }
+
+    let commit = |_: u32| -> Option<Commit<Arc<Validators>>> {
+      Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
+    };
+    let unsigned_in_chain = |_: [u8; 32]| false;
+
+    let res = Block::new(LAST, vec![], mempool).verify::<N>(
+      genesis,
+      ConsensusParams::default(),
+      LAST,
+      HashMap::new(),
+      next_nonces,
+      validators.clone(),
+      commit,
+      unsigned_in_chain,
+    );
+    if invalid.is_none() {
+      res.unwrap();
+    } else {
+      assert_eq!(res, Err(BlockError::TransactionError(TransactionError::InvalidSignature)));
+    }
+  }
+}
@@ -12,13 +12,13 @@ use blake2::{Digest, Blake2s256};
 
 use ciphersuite::{group::ff::Field, Ciphersuite, Ristretto};
 
-use serai_db::{DbTxn, Db, MemDb};
+use serai_db::{DbTxn, Db, IterableDb, MemDb};
 
 use crate::{
   ReadWrite, TransactionKind,
   transaction::Transaction as TransactionTrait,
   TransactionError, Transaction, ProvidedError, ProvidedTransactions, merkle, BlockError, Block,
-  Blockchain,
+  Blockchain, ConsensusParams,
   tendermint::{TendermintNetwork, Validators, Signer, TendermintBlock},
   tests::{
     ProvidedTransaction, SignedTransaction, random_provided_transaction, p2p::DummyP2p,
@@ -33,7 +33,8 @@ fn new_blockchain<T: TransactionTrait>(
   participants: &[<Ristretto as Ciphersuite>::G],
 ) -> (MemDb, Blockchain<MemDb, T>) {
   let db = MemDb::new();
-  let blockchain = Blockchain::new(db.clone(), genesis, participants);
+  let participants = participants.iter().map(|participant| (*participant, 1)).collect::<Vec<_>>();
+  let blockchain = Blockchain::new(db.clone(), genesis, &participants, ConsensusParams::default());
   assert_eq!(blockchain.tip(), genesis);
   assert_eq!(blockchain.block_number(), 0);
   (db, blockchain)
@@ -314,6 +315,42 @@ async fn tendermint_evidence_tx() {
   test(&mut blockchain, mempool, validators);
 }
 
+#[tokio::test]
+async fn prune() {
+  let genesis = new_genesis();
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let signer = Ristretto::generator() * key.deref();
+  let validators = Arc::new(Validators::new(genesis, vec![(signer, 1)]).unwrap());
+
+  let (mut db, mut blockchain) = new_blockchain::<SignedTransaction>(genesis, &[signer]);
+
+  // Add a block with a signed transaction and an unsigned transaction
+  for tx in [
+    Transaction::Application(crate::tests::signed_transaction(&mut OsRng, genesis, &key, 0)),
+    Transaction::Tendermint(
+      random_evidence_tx::<N>(Signer::new(genesis, key.clone()).into(), TendermintBlock(vec![]))
+        .await,
+    ),
+  ] {
+    assert!(blockchain.add_transaction::<N>(true, tx, validators.clone()));
+  }
+  let block = blockchain.build_block::<N>(validators.clone());
+  assert_eq!(block.transactions.len(), 2);
+  assert!(blockchain.add_block::<N>(&block, vec![], validators.clone()).is_ok());
+
+  // Leave a transaction in the mempool
+  assert!(blockchain.add_transaction::<N>(
+    true,
+    Transaction::Application(crate::tests::signed_transaction(&mut OsRng, genesis, &key, 1)),
+    validators,
+  ));
+
+  assert!(!db.is_empty());
+  drop(blockchain);
+  Blockchain::<MemDb, SignedTransaction>::prune(&mut db, genesis, &[signer]);
+  assert!(db.is_empty());
+}
+
 #[tokio::test]
 async fn block_tx_ordering() {
   #[derive(Debug, PartialEq, Eq, Clone)]
@@ -10,9 +10,9 @@ use tendermint::ext::Commit;
 use serai_db::MemDb;
 
 use crate::{
-  transaction::Transaction as TransactionTrait,
+  transaction::{Signed, TransactionKind, Transaction as TransactionTrait},
   tendermint::{TendermintBlock, Validators, Signer, TendermintNetwork},
-  ACCOUNT_MEMPOOL_LIMIT, Transaction, Mempool,
+  ACCOUNT_MEMPOOL_LIMIT, MempoolLimits, ConsensusParams, Transaction, Mempool,
   tests::{SignedTransaction, signed_transaction, p2p::DummyP2p, random_evidence_tx},
 };
 
@@ -22,7 +22,14 @@ fn new_mempool<T: TransactionTrait>() -> ([u8; 32], MemDb, Mempool<MemDb, T>) {
   let mut genesis = [0; 32];
   OsRng.fill_bytes(&mut genesis);
   let db = MemDb::new();
-  (genesis, db.clone(), Mempool::new(db, genesis))
+  (genesis, db.clone(), Mempool::new(db, genesis, ConsensusParams::default()))
+}
+
+// Give every participant a single key
+fn weights(
+  blockchain_next_nonces: &HashMap<<Ristretto as Ciphersuite>::G, u32>,
+) -> HashMap<<Ristretto as Ciphersuite>::G, u64> {
+  blockchain_next_nonces.keys().map(|participant| (*participant, 1)).collect()
 }
 
 #[tokio::test]
@@ -67,7 +74,7 @@ async fn mempool_addition() {
   ));
 
   // Test reloading works
-  assert_eq!(mempool, Mempool::new(db, genesis));
+  assert_eq!(mempool, Mempool::new(db, genesis, ConsensusParams::default()));
 
   // Adding it again should fail
   assert!(!mempool.add::<N>(
@@ -125,11 +132,17 @@ async fn mempool_addition() {
   assert_eq!(mempool.next_nonce(&second_signer), Some(3));
 
   // Getting a block should work
-  assert_eq!(mempool.block(&blockchain_next_nonces, unsigned_in_chain).len(), 4);
+  assert_eq!(
+    mempool
+      .block(&blockchain_next_nonces, &weights(&blockchain_next_nonces), unsigned_in_chain)
+      .len(),
+    4
+  );
 
   // If the blockchain says an account had its nonce updated, it should cause a prune
   blockchain_next_nonces.insert(signer, 1);
-  let mut block = mempool.block(&blockchain_next_nonces, unsigned_in_chain);
+  let mut block =
+    mempool.block(&blockchain_next_nonces, &weights(&blockchain_next_nonces), unsigned_in_chain);
   assert_eq!(block.len(), 3);
   assert!(!block.iter().any(|tx| tx.hash() == first_tx.hash()));
   assert_eq!(mempool.txs(), &block.drain(..).map(|tx| (tx.hash(), tx)).collect::<HashMap<_, _>>());
@@ -177,4 +190,254 @@ fn too_many_mempool() {
     unsigned_in_chain,
     commit,
   ));
+  assert_eq!(mempool.rejections(&signer), 1);
+}
+
+#[test]
+fn mempool_limits() {
+  let (genesis, _, mut mempool) = new_mempool::<SignedTransaction>();
+  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let commit = |_: u32| -> Option<Commit<Arc<Validators>>> {
+    Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
+  };
+  let unsigned_in_chain = |_: [u8; 32]| false;
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let second_key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let tx = signed_transaction(&mut OsRng, genesis, &key, 0);
+  let second_tx = signed_transaction(&mut OsRng, genesis, &second_key, 0);
+  let signer = tx.1.signer;
+  let second_signer = second_tx.1.signer;
+  let blockchain_next_nonces = HashMap::from([(signer, 0), (second_signer, 0)]);
+
+  // Transactions exceeding the size limit should be rejected, even if they're our own
+  mempool.set_limits(MempoolLimits { transaction_size: 100, ..Default::default() });
+  assert!(!mempool.add::<N>(
+    &blockchain_next_nonces,
+    true,
+    Transaction::Application(tx.clone()),
+    validators.clone(),
+    unsigned_in_chain,
+    commit,
+  ));
+  assert_eq!(mempool.rejections(&signer), 1);
+
+  // Rejections shouldn't be tracked for signers who aren't participants
+  let outsider_key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let outsider_tx = signed_transaction(&mut OsRng, genesis, &outsider_key, 0);
+  let outsider = outsider_tx.1.signer;
+  assert!(!mempool.add::<N>(
+    &blockchain_next_nonces,
+    false,
+    Transaction::Application(outsider_tx),
+    validators.clone(),
+    unsigned_in_chain,
+    commit,
+  ));
+  assert_eq!(mempool.rejections(&outsider), 0);
+
+  // Once the mempool is full, transactions from the network should be rejected
+  mempool.set_limits(MempoolLimits { transactions: 1, ..Default::default() });
+  assert!(mempool.add::<N>(
+    &blockchain_next_nonces,
+    false,
+    Transaction::Application(tx),
+    validators.clone(),
+    unsigned_in_chain,
+    commit,
+  ));
+  assert!(!mempool.add::<N>(
+    &blockchain_next_nonces,
+    false,
+    Transaction::Application(second_tx.clone()),
+    validators.clone(),
+    unsigned_in_chain,
+    commit,
+  ));
+  // Since this isn't the second signer's fault, it shouldn't be noted as a rejection
+  assert_eq!(mempool.rejections(&second_signer), 0);
+
+  // Our own transactions should still be added
+  assert!(mempool.add::<N>(
+    &blockchain_next_nonces,
+    true,
+    Transaction::Application(second_tx),
+    validators,
+    unsigned_in_chain,
+    commit,
+  ));
+  assert_eq!(mempool.txs().len(), 2);
+}
+
+#[test]
+fn buffered_nonces() {
+  let (genesis, _, mut mempool) = new_mempool::<SignedTransaction>();
+  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let commit = |_: u32| -> Option<Commit<Arc<Validators>>> {
+    Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
+  };
+  let unsigned_in_chain = |_: [u8; 32]| false;
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let txs = (0 .. 3).map(|i| signed_transaction(&mut OsRng, genesis, &key, i)).collect::<Vec<_>>();
+  let signer = txs[0].1.signer;
+  let mut blockchain_next_nonces = HashMap::from([(signer, 0)]);
+
+  // Transactions with future nonces should be buffered, not added
+  for tx in [&txs[2], &txs[1]] {
+    assert!(!mempool.add::<N>(
+      &blockchain_next_nonces,
+      false,
+      Transaction::Application(tx.clone()),
+      validators.clone(),
+      unsigned_in_chain,
+      commit,
+    ));
+  }
+  assert!(mempool.txs().is_empty());
+  assert_eq!(mempool.buffered()[&signer].len(), 2);
+
+  // Transactions with invalid signatures shouldn't be buffered
+  let mut invalid = signed_transaction(&mut OsRng, genesis, &key, 3);
+  invalid.0[0] ^= 1;
+  assert!(!mempool.add::<N>(
+    &blockchain_next_nonces,
+    false,
+    Transaction::Application(invalid),
+    validators.clone(),
+    unsigned_in_chain,
+    commit,
+  ));
+  assert_eq!(mempool.buffered()[&signer].len(), 2);
+
+  // Filling the gap should release the buffered transactions
+  assert!(mempool.add::<N>(
+    &blockchain_next_nonces,
+    false,
+    Transaction::Application(txs[0].clone()),
+    validators.clone(),
+    unsigned_in_chain,
+    commit,
+  ));
+  assert_eq!(mempool.next_nonce(&signer), Some(3));
+  assert!(mempool.buffered().is_empty());
+  assert_eq!(
+    mempool
+      .block(&blockchain_next_nonces, &weights(&blockchain_next_nonces), unsigned_in_chain)
+      .len(),
+    3
+  );
+
+  // If the blockchain fills the gap, the buffered transactions should be released when a block is
+  // built
+  let txs = (3 .. 6).map(|i| signed_transaction(&mut OsRng, genesis, &key, i)).collect::<Vec<_>>();
+  assert!(!mempool.add::<N>(
+    &blockchain_next_nonces,
+    false,
+    Transaction::Application(txs[2].clone()),
+    validators,
+    unsigned_in_chain,
+    commit,
+  ));
+  blockchain_next_nonces.insert(signer, 5);
+  let block =
+    mempool.block(&blockchain_next_nonces, &weights(&blockchain_next_nonces), unsigned_in_chain);
+  assert_eq!(block, vec![Transaction::Application(txs[2].clone())]);
+  assert_eq!(mempool.next_nonce(&signer), Some(6));
+}
+
+#[test]
+fn buffered_nonces_limit() {
+  let (genesis, _, mut mempool) = new_mempool::<SignedTransaction>();
+  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let commit = |_: u32| -> Option<Commit<Arc<Validators>>> {
+    Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
+  };
+  let unsigned_in_chain = |_: [u8; 32]| false;
+  let key = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
+  let txs = (0 .. 3).map(|i| signed_transaction(&mut OsRng, genesis, &key, i)).collect::<Vec<_>>();
+  let signer = txs[0].1.signer;
+  let blockchain_next_nonces = HashMap::from([(signer, 0)]);
+
+  mempool.set_limits(MempoolLimits { transactions: 2, ..Default::default() });
+  for tx in [&txs[2], &txs[1]] {
+    assert!(!mempool.add::<N>(
+      &blockchain_next_nonces,
+      false,
+      Transaction::Application(tx.clone()),
+      validators.clone(),
+      unsigned_in_chain,
+      commit,
+    ));
+  }
+
+  // Filling the gap should only release as many transactions as the mempool has room for
+  assert!(mempool.add::<N>(
+    &blockchain_next_nonces,
+    false,
+    Transaction::Application(txs[0].clone()),
+    validators,
+    unsigned_in_chain,
+    commit,
+  ));
+  assert_eq!(mempool.txs().len(), 2);
+  assert_eq!(mempool.next_nonce(&signer), Some(2));
+  assert_eq!(mempool.buffered()[&signer].len(), 1);
+
+  // Once there's room, the rest should be released
+  mempool.remove(&txs[0].hash());
+  mempool.remove(&txs[1].hash());
+  let blockchain_next_nonces = HashMap::from([(signer, 2)]);
+  let block =
+    mempool.block(&blockchain_next_nonces, &weights(&blockchain_next_nonces), unsigned_in_chain);
+  assert_eq!(block, vec![Transaction::Application(txs[2].clone())]);
+  assert!(mempool.buffered().is_empty());
+}
+
+#[test]
+fn weighted_block() {
+  let (genesis, _, mut mempool) = new_mempool::<SignedTransaction>();
+  let validators = Arc::new(Validators::new(genesis, vec![]).unwrap());
+  let commit = |_: u32| -> Option<Commit<Arc<Validators>>> {
+    Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
+  };
+  let unsigned_in_chain = |_: [u8; 32]| false;
+
+  let keys = (0 .. 2)
+    .map(|_| Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng)))
+    .collect::<Vec<_>>();
+  let signers = keys.iter().map(|key| Ristretto::generator() * **key).collect::<Vec<_>>();
+  let blockchain_next_nonces = signers.iter().map(|signer| (*signer, 0)).collect::<HashMap<_, _>>();
+  let weights = HashMap::from([(signers[0], 3), (signers[1], 1)]);
+
+  for key in &keys {
+    for nonce in 0 .. 8 {
+      assert!(mempool.add::<N>(
+        &blockchain_next_nonces,
+        false,
+        Transaction::Application(signed_transaction(&mut OsRng, genesis, key, nonce)),
+        validators.clone(),
+        unsigned_in_chain,
+        commit,
+      ));
+    }
+  }
+
+  let block = mempool.block(&blockchain_next_nonces, &weights, unsigned_in_chain);
+  assert_eq!(block.len(), 16);
+  // The order should be deterministic, despite the mempool being unordered
+  assert_eq!(block, mempool.block(&blockchain_next_nonces, &weights, unsigned_in_chain));
+
+  let signed = |tx: &Transaction<SignedTransaction>| {
+    let TransactionKind::Signed(Signed { signer, nonce, .. }) = tx.kind() else { panic!() };
+    (*signer, *nonce)
+  };
+  // Since every transaction is the same size, any prefix should be split 3:1
+  assert_eq!(block[.. 8].iter().map(signed).filter(|(signer, _)| *signer == signers[0]).count(), 6);
+  // Once the first signer's transactions are exhausted, the second signer should have the rest
+  assert!(block[12 ..].iter().map(signed).all(|(signer, _)| signer == signers[1]));
+
+  // Each signer's transactions should remain ordered by nonce
+  for signer in &signers {
+    let nonces = block.iter().map(signed).filter(|(tx_signer, _)| tx_signer == signer);
+    assert!(nonces.map(|(_, nonce)| nonce).eq(0 .. 8));
+  }
 }
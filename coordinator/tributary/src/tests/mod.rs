@@ -12,3 +12,5 @@ mod blockchain;
 mod mempool;
 #[cfg(test)]
 mod p2p;
+#[cfg(test)]
+mod rebroadcast;
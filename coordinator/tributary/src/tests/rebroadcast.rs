@@ -0,0 +1,40 @@
+use std::time::{Instant, Duration};
+
+use crate::{Rebroadcaster, REBROADCAST_INITIAL_BLOCKS, REBROADCAST_MAX_BLOCKS};
+
+#[test]
+fn rebroadcast_backoff() {
+  let block_time = Duration::from_secs(1);
+  let mut rebroadcaster = Rebroadcaster::new(block_time);
+
+  let start = Instant::now();
+  rebroadcaster.track(start, [0; 32], vec![0]);
+  // Tracking the same transaction again shouldn't reset its backoff
+  rebroadcaster.track(start + block_time, [0; 32], vec![0]);
+  assert_eq!(rebroadcaster.len(), 1);
+
+  // Nothing should be rebroadcast until the initial backoff elapses
+  assert!(rebroadcaster.due(start, |_| true).is_empty());
+  let mut now = start + (block_time * REBROADCAST_INITIAL_BLOCKS);
+  assert_eq!(rebroadcaster.due(now, |_| true), vec![vec![0]]);
+  assert!(rebroadcaster.due(now, |_| true).is_empty());
+
+  // The backoff should double until it reaches the maximum
+  let mut backoff = block_time * REBROADCAST_INITIAL_BLOCKS;
+  while backoff < (block_time * REBROADCAST_MAX_BLOCKS) {
+    backoff *= 2;
+    assert!(rebroadcaster.due(now + backoff - Duration::from_millis(1), |_| true).is_empty());
+    now += backoff;
+    assert_eq!(rebroadcaster.due(now, |_| true), vec![vec![0]]);
+  }
+  now += block_time * REBROADCAST_MAX_BLOCKS;
+  assert_eq!(rebroadcaster.due(now, |_| true), vec![vec![0]]);
+
+  // Once the transaction leaves the mempool, it should no longer be tracked
+  rebroadcaster.track(now, [1; 32], vec![1]);
+  now += block_time * REBROADCAST_MAX_BLOCKS;
+  assert_eq!(rebroadcaster.due(now, |hash| *hash == [1; 32]), vec![vec![1]]);
+  assert_eq!(rebroadcaster.len(), 1);
+  assert!(rebroadcaster.due(now + (block_time * REBROADCAST_MAX_BLOCKS), |_| false).is_empty());
+  assert_eq!(rebroadcaster.len(), 0);
+}
@@ -17,7 +17,7 @@ use tendermint::{
 use serai_db::MemDb;
 
 use crate::{
-  ReadWrite,
+  ReadWrite, ConsensusParams,
   tendermint::{
     tx::{TendermintTx, verify_tendermint_tx},
     TendermintBlock, Signer, Validators, TendermintNetwork,
@@ -40,6 +40,7 @@ async fn serialize_tendermint() {
 
 #[tokio::test]
 async fn invalid_valid_round() {
+  let params = ConsensusParams::default();
   // signer
   let (_, signer, signer_id, validators) = tendermint_meta().await;
   let commit = |_: u32| -> Option<Commit<Arc<Validators>>> {
@@ -57,13 +58,13 @@ async fn invalid_valid_round() {
 
   // This should be invalid evidence if a valid valid round is specified
   let (_, tx) = valid_round_tx(None).await;
-  assert!(verify_tendermint_tx::<N>(&tx, validators.clone(), commit).is_err());
+  assert!(verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).is_err());
 
   // If an invalid valid round is specified (>= current), this should be invalid evidence
   let (mut signed, tx) = valid_round_tx(Some(RoundNumber(0))).await;
 
   // should pass
-  verify_tendermint_tx::<N>(&tx, validators.clone(), commit).unwrap();
+  verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).unwrap();
 
   // change the signature
   let mut random_sig = [0u8; 64];
@@ -72,11 +73,12 @@ async fn invalid_valid_round() {
   let tx = TendermintTx::SlashEvidence((signed.clone(), None::<SignedMessageFor<N>>).encode());
 
   // should fail
-  assert!(verify_tendermint_tx::<N>(&tx, validators, commit).is_err());
+  assert!(verify_tendermint_tx::<N>(&tx, params, validators, commit).is_err());
 }
 
 #[tokio::test]
 async fn invalid_precommit_signature() {
+  let params = ConsensusParams::default();
   let (_, signer, signer_id, validators) = tendermint_meta().await;
   let commit = |i: u32| -> Option<Commit<Arc<Validators>>> {
     assert_eq!(i, 0);
@@ -94,17 +96,23 @@ async fn invalid_precommit_signature() {
   };
 
   // Empty Precommit should fail.
-  assert!(verify_tendermint_tx::<N>(&precommit(None).await.1, validators.clone(), commit).is_err());
+  assert!(verify_tendermint_tx::<N>(&precommit(None).await.1, params, validators.clone(), commit)
+    .is_err());
 
   // valid precommit signature should fail.
   let block_id = [0x22u8; 32];
-  let last_end_time =
-    RoundData::<N>::new(RoundNumber(0), CanonicalInstant::new(commit(0).unwrap().end_time))
-      .end_time();
+  let last_end_time = RoundData::<N>::new(
+    params.block_processing_time,
+    params.latency_time,
+    RoundNumber(0),
+    CanonicalInstant::new(commit(0).unwrap().end_time),
+  )
+  .end_time();
   let commit_msg = commit_msg(last_end_time.canonical(), block_id.as_ref());
 
   assert!(verify_tendermint_tx::<N>(
     &precommit(Some((block_id, signer.clone().sign(&commit_msg).await))).await.1,
+    params,
     validators.clone(),
     commit
   )
@@ -113,19 +121,20 @@ async fn invalid_precommit_signature() {
   // any other signature can be used as evidence.
   {
     let (mut signed, tx) = precommit(Some((block_id, signer.sign(&[]).await))).await;
-    verify_tendermint_tx::<N>(&tx, validators.clone(), commit).unwrap();
+    verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).unwrap();
 
     // So long as we can authenticate where it came from
     let mut random_sig = [0u8; 64];
     OsRng.fill_bytes(&mut random_sig);
     signed.sig = random_sig;
     let tx = TendermintTx::SlashEvidence((signed.clone(), None::<SignedMessageFor<N>>).encode());
-    assert!(verify_tendermint_tx::<N>(&tx, validators, commit).is_err());
+    assert!(verify_tendermint_tx::<N>(&tx, params, validators, commit).is_err());
   }
 }
 
 #[tokio::test]
 async fn evidence_with_prevote() {
+  let params = ConsensusParams::default();
   let (_, signer, signer_id, validators) = tendermint_meta().await;
   let commit = |_: u32| -> Option<Commit<Arc<Validators>>> {
     Some(Commit::<Arc<Validators>> { end_time: 0, validators: vec![], signature: vec![] })
@@ -147,12 +156,13 @@ async fn evidence_with_prevote() {
 
   // No prevote message should be valid as slash evidence at this time
   for prevote in [prevote(None).await, prevote(Some([0x22u8; 32])).await] {
-    assert!(verify_tendermint_tx::<N>(&prevote, validators.clone(), commit).is_err());
+    assert!(verify_tendermint_tx::<N>(&prevote, params, validators.clone(), commit).is_err());
   }
 }
 
 #[tokio::test]
 async fn conflicting_msgs_evidence_tx() {
+  let params = ConsensusParams::default();
   let (genesis, signer, signer_id, validators) = tendermint_meta().await;
   let commit = |i: u32| -> Option<Commit<Arc<Validators>>> {
     assert_eq!(i, 0);
@@ -170,23 +180,23 @@ async fn conflicting_msgs_evidence_tx() {
     // non-conflicting data should fail
     let signed_1 = signed_for_b_r(0, 0, Data::Proposal(None, TendermintBlock(vec![0x11]))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(&signed_1)).encode());
-    assert!(verify_tendermint_tx::<N>(&tx, validators.clone(), commit).is_err());
+    assert!(verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).is_err());
 
     // conflicting data should pass
     let signed_2 = signed_for_b_r(0, 0, Data::Proposal(None, TendermintBlock(vec![0x22]))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(signed_2)).encode());
-    verify_tendermint_tx::<N>(&tx, validators.clone(), commit).unwrap();
+    verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).unwrap();
 
     // Except if it has a distinct round number, as we don't check cross-round conflicts
     // (except for Precommit)
     let signed_2 = signed_for_b_r(0, 1, Data::Proposal(None, TendermintBlock(vec![0x22]))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(signed_2)).encode());
-    verify_tendermint_tx::<N>(&tx, validators.clone(), commit).unwrap_err();
+    verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).unwrap_err();
 
     // Proposals for different block numbers should also fail as evidence
     let signed_2 = signed_for_b_r(1, 0, Data::Proposal(None, TendermintBlock(vec![0x22]))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(signed_2)).encode());
-    verify_tendermint_tx::<N>(&tx, validators.clone(), commit).unwrap_err();
+    verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).unwrap_err();
   }
 
   // Prevote
@@ -194,23 +204,23 @@ async fn conflicting_msgs_evidence_tx() {
     // non-conflicting data should fail
     let signed_1 = signed_for_b_r(0, 0, Data::Prevote(Some([0x11; 32]))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(&signed_1)).encode());
-    assert!(verify_tendermint_tx::<N>(&tx, validators.clone(), commit).is_err());
+    assert!(verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).is_err());
 
     // conflicting data should pass
     let signed_2 = signed_for_b_r(0, 0, Data::Prevote(Some([0x22; 32]))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(signed_2)).encode());
-    verify_tendermint_tx::<N>(&tx, validators.clone(), commit).unwrap();
+    verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).unwrap();
 
     // Except if it has a distinct round number, as we don't check cross-round conflicts
     // (except for Precommit)
     let signed_2 = signed_for_b_r(0, 1, Data::Prevote(Some([0x22; 32]))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(signed_2)).encode());
-    verify_tendermint_tx::<N>(&tx, validators.clone(), commit).unwrap_err();
+    verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).unwrap_err();
 
     // Proposals for different block numbers should also fail as evidence
     let signed_2 = signed_for_b_r(1, 0, Data::Prevote(Some([0x22; 32]))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(signed_2)).encode());
-    verify_tendermint_tx::<N>(&tx, validators.clone(), commit).unwrap_err();
+    verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).unwrap_err();
   }
 
   // Precommit
@@ -219,17 +229,17 @@ async fn conflicting_msgs_evidence_tx() {
 
     let signed_1 = signed_for_b_r(0, 0, Data::Precommit(Some(([0x11; 32], sig)))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(&signed_1)).encode());
-    assert!(verify_tendermint_tx::<N>(&tx, validators.clone(), commit).is_err());
+    assert!(verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).is_err());
 
     // For precommit, the round number is ignored
     let signed_2 = signed_for_b_r(0, 1, Data::Precommit(Some(([0x22; 32], sig)))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(signed_2)).encode());
-    verify_tendermint_tx::<N>(&tx, validators.clone(), commit).unwrap();
+    verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).unwrap();
 
     // Yet the block number isn't
     let signed_2 = signed_for_b_r(1, 0, Data::Precommit(Some(([0x22; 32], sig)))).await;
     let tx = TendermintTx::SlashEvidence((&signed_1, Some(signed_2)).encode());
-    assert!(verify_tendermint_tx::<N>(&tx, validators.clone(), commit).is_err());
+    assert!(verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).is_err());
   }
 
   // msgs from different senders should fail
@@ -258,7 +268,7 @@ async fn conflicting_msgs_evidence_tx() {
     let validators =
       Arc::new(Validators::new(genesis, vec![(signer_pub, 1), (signer_pub_2, 1)]).unwrap());
 
-    assert!(verify_tendermint_tx::<N>(&tx, validators, commit).is_err());
+    assert!(verify_tendermint_tx::<N>(&tx, params, validators, commit).is_err());
   }
 
   // msgs with different steps should fail
@@ -266,6 +276,6 @@ async fn conflicting_msgs_evidence_tx() {
     let signed_1 = signed_for_b_r(0, 0, Data::Proposal(None, TendermintBlock(vec![]))).await;
     let signed_2 = signed_for_b_r(0, 0, Data::Prevote(None)).await;
     let tx = TendermintTx::SlashEvidence((signed_1, Some(signed_2)).encode());
-    assert!(verify_tendermint_tx::<N>(&tx, validators.clone(), commit).is_err());
+    assert!(verify_tendermint_tx::<N>(&tx, params, validators.clone(), commit).is_err());
   }
 }
@@ -142,37 +142,54 @@ pub trait Transaction: 'static + Send + Sync + Clone + Eq + Debug + ReadWrite {
   }
 }
 
+// Verify everything about a transaction which doesn't depend on the chain's state or its
+// signature
+// As this is independent per transaction, it may be done for many transactions in parallel
+pub(crate) fn verify_transaction_stateless<T: Transaction>(tx: &T) -> Result<(), TransactionError> {
+  if tx.serialize().len() > TRANSACTION_SIZE_LIMIT {
+    Err(TransactionError::TooLargeTransaction)?;
+  }
+
+  tx.verify()
+}
+
+// Check a signed transaction's signer is a participant and its nonce is the signer's next nonce
+pub(crate) fn verify_nonce(
+  signed: &Signed,
+  next_nonces: &HashMap<<Ristretto as Ciphersuite>::G, u32>,
+) -> Result<(), TransactionError> {
+  if let Some(next_nonce) = next_nonces.get(&signed.signer) {
+    if signed.nonce != *next_nonce {
+      Err(TransactionError::InvalidNonce)?;
+    }
+  } else {
+    // Not a participant
+    Err(TransactionError::InvalidSigner)?;
+  }
+  Ok(())
+}
+
 // This will only cause mutations when the transaction is valid
 pub(crate) fn verify_transaction<T: Transaction>(
   tx: &T,
   genesis: [u8; 32],
   next_nonces: &mut HashMap<<Ristretto as Ciphersuite>::G, u32>,
 ) -> Result<(), TransactionError> {
-  if tx.serialize().len() > TRANSACTION_SIZE_LIMIT {
-    Err(TransactionError::TooLargeTransaction)?;
-  }
-
-  tx.verify()?;
+  verify_transaction_stateless(tx)?;
 
   match tx.kind() {
     TransactionKind::Provided(_) => {}
     TransactionKind::Unsigned => {}
-    TransactionKind::Signed(Signed { signer, nonce, signature }) => {
-      if let Some(next_nonce) = next_nonces.get(signer) {
-        if nonce != next_nonce {
-          Err(TransactionError::InvalidNonce)?;
-        }
-      } else {
-        // Not a participant
-        Err(TransactionError::InvalidSigner)?;
-      }
+    TransactionKind::Signed(signed) => {
+      verify_nonce(signed, next_nonces)?;
 
-      // TODO: Use Schnorr half-aggregation and a batch verification here
-      if !signature.verify(*signer, tx.sig_hash(genesis)) {
+      // Blocks batch verify their signatures, yet transactions are individually verified when
+      // added to the mempool
+      if !signed.signature.verify(signed.signer, tx.sig_hash(genesis)) {
         Err(TransactionError::InvalidSignature)?;
       }
 
-      next_nonces.insert(*signer, nonce + 1);
+      next_nonces.insert(signed.signer, signed.nonce + 1);
     }
   }
 
@@ -12,6 +12,9 @@ use crate::{
 };
 
 pub(crate) struct BlockData<N: Network> {
+  block_processing_time: u32,
+  latency_time: u32,
+
   pub(crate) number: BlockNumber,
   pub(crate) validator_id: Option<N::ValidatorId>,
   pub(crate) proposal: Option<N::Block>,
@@ -32,12 +35,16 @@ pub(crate) struct BlockData<N: Network> {
 
 impl<N: Network> BlockData<N> {
   pub(crate) fn new(
+    network: &N,
     weights: Arc<N::Weights>,
     number: BlockNumber,
     validator_id: Option<N::ValidatorId>,
     proposal: Option<N::Block>,
   ) -> BlockData<N> {
     BlockData {
+      block_processing_time: network.block_processing_time(),
+      latency_time: network.latency_time(),
+
       number,
       validator_id,
       proposal,
@@ -71,7 +78,13 @@ impl<N: Network> BlockData<N> {
     for r in (self.round().number.0 + 1) ..= round.0 {
       self.end_time.insert(
         RoundNumber(r),
-        RoundData::<N>::new(RoundNumber(r), self.end_time[&RoundNumber(r - 1)]).end_time(),
+        RoundData::<N>::new(
+          self.block_processing_time,
+          self.latency_time,
+          RoundNumber(r),
+          self.end_time[&RoundNumber(r - 1)],
+        )
+        .end_time(),
       );
     }
   }
@@ -99,6 +112,8 @@ impl<N: Network> BlockData<N> {
 
     // 11-13
     self.round = Some(RoundData::<N>::new(
+      self.block_processing_time,
+      self.latency_time,
       round,
       time.unwrap_or_else(|| self.end_time[&RoundNumber(round.0 - 1)]),
     ));
@@ -225,19 +225,21 @@ pub trait Network: Sized + Send + Sync {
   ///
   /// This should include both the time to download the block and the actual processing time.
   ///
-  /// BLOCK_PROCESSING_TIME + (3 * LATENCY_TIME) must be divisible by 1000.
-  const BLOCK_PROCESSING_TIME: u32;
+  /// This must be constant for the lifetime of the machine, and agreed upon by all validators.
+  /// block_processing_time + (3 * latency_time) must be divisible by 1000.
+  fn block_processing_time(&self) -> u32;
   /// Network latency time in milliseconds.
   ///
-  /// BLOCK_PROCESSING_TIME + (3 * LATENCY_TIME) must be divisible by 1000.
-  const LATENCY_TIME: u32;
-
-  /// The block time, in seconds. Defined as the processing time plus three times the latency.
-  fn block_time() -> u32 {
-    let raw = Self::BLOCK_PROCESSING_TIME + (3 * Self::LATENCY_TIME);
-    let res = raw / 1000;
-    assert_eq!(res * 1000, raw);
-    res
+  /// This must be constant for the lifetime of the machine, and agreed upon by all validators.
+  /// block_processing_time + (3 * latency_time) must be divisible by 1000.
+  fn latency_time(&self) -> u32;
+
+  /// The block time, in milliseconds. Defined as the processing time plus three times the latency.
+  ///
+  /// As the end times of blocks are in seconds, implementors must ensure this is a whole number of
+  /// seconds, such as by validating the times when they're configured.
+  fn block_time(&self) -> u32 {
+    self.block_processing_time() + (3 * self.latency_time())
   }
 
   /// Return a handle on the signer in use, usable for the entire lifetime of the machine.
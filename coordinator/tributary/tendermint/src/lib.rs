@@ -233,6 +233,7 @@ impl<N: Network + 'static> TendermintMachine<N> {
 
     // Create the new block
     self.block = BlockData::new(
+      &self.network,
       self.weights.clone(),
       BlockNumber(self.block.number.0 + 1),
       self.signer.validator_id().await,
@@ -279,6 +280,8 @@ impl<N: Network + 'static> TendermintMachine<N> {
   /// Create a new Tendermint machine, from the specified point, with the specified block as the
   /// one to propose next. This will return a channel to send messages from the gossip layer and
   /// the machine itself. The machine should have `run` called from an asynchronous task.
+  ///
+  /// `last_time` is the end time of the last block, in seconds since the Unix epoch.
   #[allow(clippy::new_ret_no_self)]
   pub async fn new(
     network: N,
@@ -318,24 +321,26 @@ impl<N: Network + 'static> TendermintMachine<N> {
         let validators = network.signature_scheme();
         let weights = Arc::new(network.weights());
         let validator_id = signer.validator_id().await;
+        let block = BlockData::new(
+          &network,
+          weights.clone(),
+          BlockNumber(last_block.0 + 1),
+          validator_id,
+          Some(proposal),
+        );
         // 01-10
         let mut machine = TendermintMachine {
           network,
           signer,
           validators,
-          weights: weights.clone(),
+          weights,
 
           queue: VecDeque::new(),
           msg_recv,
           synced_block_recv,
           synced_block_result_send,
 
-          block: BlockData::new(
-            weights,
-            BlockNumber(last_block.0 + 1),
-            validator_id,
-            Some(proposal),
-          ),
+          block,
         };
 
         // The end time of the last block is the start time for this one
@@ -344,7 +349,8 @@ impl<N: Network + 'static> TendermintMachine<N> {
         // Using the genesis time in place will cause this block to be created immediately
         // after it, without the standard amount of separation (so their times will be
         // equivalent or minimally offset)
-        // For callers wishing to avoid this, they should pass (0, GENESIS + N::block_time())
+        // For callers wishing to avoid this, they should pass
+        // (0, GENESIS + network.block_time())
         machine.round(RoundNumber(0), Some(CanonicalInstant::new(last_time)));
         machine
       },
@@ -506,9 +512,11 @@ impl<N: Network + 'static> TendermintMachine<N> {
             let current_msg = SignedMessage { msg: msg.clone(), sig: sig.clone() };
 
             let slash = if let Some(old_msg) = evidence_msg {
-              // if the malicious message contains a block, only vote to slash
-              // TODO: Should this decision be made at a higher level?
-              if let Data::Proposal(_, _) = &current_msg.msg.data {
+              // if the malicious message contains a block, only vote to slash, unless it
+              // conflicts with a prior proposal
+              // Equivocation is always reported with its evidence, leaving it to the network to
+              // decide if the evidence can be published
+              if matches!(&current_msg.msg.data, Data::Proposal(_, _)) && (old_msg == current_msg) {
                 SlashEvent::Id(
                   SlashReason::InvalidBlock,
                   self.block.number.0,
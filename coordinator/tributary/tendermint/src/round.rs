@@ -15,6 +15,8 @@ use crate::{
 
 pub struct RoundData<N: Network> {
   _network: PhantomData<N>,
+  block_processing_time: u32,
+  latency_time: u32,
   pub number: RoundNumber,
   pub start_time: CanonicalInstant,
   pub step: Step,
@@ -22,9 +24,17 @@ pub struct RoundData<N: Network> {
 }
 
 impl<N: Network> RoundData<N> {
-  pub fn new(number: RoundNumber, start_time: CanonicalInstant) -> Self {
+  /// Create the data for a round, given the network's block processing and latency times.
+  pub fn new(
+    block_processing_time: u32,
+    latency_time: u32,
+    number: RoundNumber,
+    start_time: CanonicalInstant,
+  ) -> Self {
     RoundData {
       _network: PhantomData,
+      block_processing_time,
+      latency_time,
       number,
       start_time,
       step: Step::Propose,
@@ -33,8 +43,8 @@ impl<N: Network> RoundData<N> {
   }
 
   fn timeout(&self, step: Step) -> CanonicalInstant {
-    let adjusted_block = N::BLOCK_PROCESSING_TIME * (self.number.0 + 1);
-    let adjusted_latency = N::LATENCY_TIME * (self.number.0 + 1);
+    let adjusted_block = self.block_processing_time * (self.number.0 + 1);
+    let adjusted_latency = self.latency_time * (self.number.0 + 1);
     let offset = Duration::from_millis(
       (match step {
         Step::Propose => adjusted_block + adjusted_latency,
@@ -116,8 +116,13 @@ impl Network for TestNetwork {
   type Weights = TestWeights;
   type Block = TestBlock;
 
-  const BLOCK_PROCESSING_TIME: u32 = 2000;
-  const LATENCY_TIME: u32 = 1000;
+  fn block_processing_time(&self) -> u32 {
+    2000
+  }
+
+  fn latency_time(&self) -> u32 {
+    1000
+  }
 
   fn signer(&self) -> TestSigner {
     TestSigner(self.0)
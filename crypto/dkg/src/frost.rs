@@ -133,7 +133,7 @@ impl<C: Ciphersuite> KeyGenMachine<C> {
     );
 
     // Additionally create an encryption mechanism to protect the secret shares
-    let encryption = Encryption::new(self.context.clone(), self.params.i, rng);
+    let encryption = Encryption::new(self.context.clone(), Some(self.params.i), rng);
 
     // Step 4: Broadcast
     let msg =
@@ -151,7 +151,7 @@ impl<C: Ciphersuite> KeyGenMachine<C> {
   }
 }
 
-fn polynomial<F: PrimeField + Zeroize>(
+pub(crate) fn polynomial<F: PrimeField + Zeroize>(
   coefficients: &[Zeroizing<F>],
   l: Participant,
 ) -> Zeroizing<F> {
@@ -177,7 +177,7 @@ fn polynomial<F: PrimeField + Zeroize>(
 // The encryption system also explicitly uses Zeroizing<M> so it can ensure anything being
 // encrypted is within Zeroizing. Accordingly, internally having Zeroizing would be redundant.
 #[derive(Clone, PartialEq, Eq)]
-pub struct SecretShare<F: PrimeField>(F::Repr);
+pub struct SecretShare<F: PrimeField>(pub(crate) F::Repr);
 impl<F: PrimeField> AsRef<[u8]> for SecretShare<F> {
   fn as_ref(&self) -> &[u8] {
     self.0.as_ref()
@@ -363,7 +363,7 @@ impl<C: Ciphersuite> Zeroize for KeyMachine<C> {
 // Calculate the exponent for a given participant and apply it to a series of commitments
 // Initially used with the actual commitments to verify the secret share, later used with
 // stripes to generate the verification shares
-fn exponential<C: Ciphersuite>(i: Participant, values: &[C::G]) -> Vec<(C::F, C::G)> {
+pub(crate) fn exponential<C: Ciphersuite>(i: Participant, values: &[C::G]) -> Vec<(C::F, C::G)> {
   let i = C::F::from(u16::from(i).into());
   let mut res = Vec::with_capacity(values.len());
   (0 .. values.len()).fold(C::F::ONE, |exp, l| {
@@ -373,7 +373,7 @@ fn exponential<C: Ciphersuite>(i: Participant, values: &[C::G]) -> Vec<(C::F, C:
   res
 }
 
-fn share_verification_statements<C: Ciphersuite>(
+pub(crate) fn share_verification_statements<C: Ciphersuite>(
   target: Participant,
   commitments: &[C::G],
   mut share: Zeroizing<C::F>,
@@ -470,12 +470,12 @@ impl<C: Ciphersuite> KeyMachine<C> {
     Ok(BlameMachine {
       commitments,
       encryption,
-      result: ThresholdCore {
+      result: Some(ThresholdCore {
         params,
         secret_share: secret,
         group_key: stripes[0],
         verification_shares,
-      },
+      }),
     })
   }
 }
@@ -484,7 +484,8 @@ impl<C: Ciphersuite> KeyMachine<C> {
 pub struct BlameMachine<C: Ciphersuite> {
   commitments: HashMap<Participant, Vec<C::G>>,
   encryption: Encryption<C>,
-  result: ThresholdCore<C>,
+  // None if this was created solely to verify blame
+  result: Option<ThresholdCore<C>>,
 }
 
 impl<C: Ciphersuite> fmt::Debug for BlameMachine<C> {
@@ -518,7 +519,7 @@ impl<C: Ciphersuite> BlameMachine<C> {
   /// tooling to do so. This function is solely intended to force users to acknowledge they're
   /// completing the protocol, not processing any blame.
   pub fn complete(self) -> ThresholdCore<C> {
-    self.result
+    self.result.unwrap()
   }
 
   fn blame_internal(
@@ -585,6 +586,30 @@ impl<C: Ciphersuite> BlameMachine<C> {
 #[derive(Debug, Zeroize)]
 pub struct AdditionalBlameMachine<C: Ciphersuite>(BlameMachine<C>);
 impl<C: Ciphersuite> AdditionalBlameMachine<C> {
+  /// Create an AdditionalBlameMachine capable of evaluating blame, regardless of if the caller was
+  /// a participant in the key generation protocol.
+  ///
+  /// The commitments must be every participant's, as broadcast and agreed upon. Since this
+  /// doesn't verify their proofs of knowledge, they must have been verified by a participant
+  /// which went on to send its secret shares, as is implied by the existence of blame.
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    context: String,
+    n: u16,
+    mut commitment_msgs: HashMap<Participant, EncryptionKeyMessage<C, Commitments<C>>>,
+  ) -> Result<Self, FrostError<C>> {
+    let mut commitments = HashMap::new();
+    let mut encryption = Encryption::new(context, None, rng);
+    for i in (1 ..= n).map(Participant) {
+      let Some(msg) = commitment_msgs.remove(&i) else { Err(DkgError::MissingParticipant(i))? };
+      commitments.insert(i, encryption.register(i, msg).commitments);
+    }
+    if let Some(i) = commitment_msgs.keys().next() {
+      Err(DkgError::InvalidParticipant(n, *i))?;
+    }
+    Ok(AdditionalBlameMachine(BlameMachine { commitments, encryption, result: None }))
+  }
+
   /// Given an accusation of fault, determine the faulty party (either the sender, who sent an
   /// invalid secret share, or the receiver, who claimed a valid secret share was invalid).
   ///
@@ -3,11 +3,14 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use core::fmt::{self, Debug};
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
 
 #[cfg(feature = "std")]
 use thiserror::Error;
 
-use zeroize::Zeroize;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// MuSig-style key aggregation.
 pub mod musig;
@@ -25,6 +28,18 @@ pub mod frost;
 #[cfg(feature = "std")]
 pub mod promote;
 
+/// Reshare keys to a new set of participants, without changing the group key.
+#[cfg(feature = "std")]
+pub mod resharing;
+
+/// Change the threshold of keys, without changing the group key.
+#[cfg(feature = "std")]
+pub mod threshold;
+
+/// Repair the share of a participant who lost it, without reconstructing the key.
+#[cfg(feature = "std")]
+pub mod repair;
+
 /// Tests for application-provided curves and algorithms.
 #[cfg(any(test, feature = "tests"))]
 pub mod tests;
@@ -95,17 +110,19 @@ pub enum DkgError<B: Clone + PartialEq + Eq + Debug> {
   /// An invalid proof of knowledge was provided.
   #[cfg_attr(feature = "std", error("invalid proof of knowledge (participant {0})"))]
   InvalidProofOfKnowledge(Participant),
+  /// Commitments which didn't commit to the expected value were provided.
+  #[cfg_attr(feature = "std", error("invalid commitments (participant {0})"))]
+  InvalidCommitments(Participant),
   /// An invalid DKG share was provided.
   #[cfg_attr(feature = "std", error("invalid share (participant {participant}, blame {blame})"))]
   InvalidShare { participant: Participant, blame: Option<B> },
 }
 
-#[cfg(feature = "std")]
 mod lib {
   pub use super::*;
 
   use core::ops::Deref;
-  use std::{io, sync::Arc, collections::HashMap};
+  use std_shims::{vec::Vec, io, sync::Arc, collections::HashMap};
 
   use zeroize::Zeroizing;
 
@@ -403,6 +420,12 @@ mod lib {
       }
     }
   }
+  impl<C: Ciphersuite> Drop for ThresholdView<C> {
+    fn drop(&mut self) {
+      self.zeroize();
+    }
+  }
+  impl<C: Ciphersuite> ZeroizeOnDrop for ThresholdView<C> {}
 
   impl<C: Ciphersuite> ThresholdKeys<C> {
     /// Create a new set of ThresholdKeys from a ThresholdCore.
@@ -449,11 +472,23 @@ mod lib {
       self.core.verification_shares()
     }
 
+    /// Write these keys to a type satisfying std::io::Write.
+    ///
+    /// Any offset is ephemeral and will not be written.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+      self.core.write(writer)
+    }
+
     /// Serialize these keys to a `Vec<u8>`.
     pub fn serialize(&self) -> Zeroizing<Vec<u8>> {
       self.core.serialize()
     }
 
+    /// Read keys from a type satisfying std::io::Read.
+    pub fn read<R: io::Read>(reader: &mut R) -> io::Result<ThresholdKeys<C>> {
+      ThresholdCore::read(reader).map(ThresholdKeys::new)
+    }
+
     /// Obtain a view of these keys, with any offset applied, interpolated for the specified signing
     /// set.
     pub fn view(&self, mut included: Vec<Participant>) -> Result<ThresholdView<C>, DkgError<()>> {
@@ -529,5 +564,4 @@ mod lib {
     }
   }
 }
-#[cfg(feature = "std")]
 pub use lib::*;
@@ -1,24 +1,19 @@
-#[cfg(feature = "std")]
 use core::ops::Deref;
-use std_shims::collections::HashSet;
-#[cfg(feature = "std")]
-use std_shims::collections::HashMap;
+use std_shims::{
+  vec::Vec,
+  collections::{HashSet, HashMap},
+};
 
-#[cfg(feature = "std")]
 use zeroize::Zeroizing;
 
 use transcript::{Transcript, RecommendedTranscript};
 
-#[cfg(feature = "std")]
-use ciphersuite::group::ff::Field;
 use ciphersuite::{
-  group::{Group, GroupEncoding},
+  group::{ff::Field, Group, GroupEncoding},
   Ciphersuite,
 };
 
-use crate::DkgError;
-#[cfg(feature = "std")]
-use crate::{Participant, ThresholdParams, ThresholdCore, lagrange};
+use crate::{DkgError, Participant, ThresholdParams, ThresholdCore, lagrange};
 
 fn check_keys<C: Ciphersuite>(keys: &[C::G]) -> Result<u16, DkgError<()>> {
   if keys.is_empty() {
@@ -72,7 +67,6 @@ pub fn musig_key<C: Ciphersuite>(context: &[u8], keys: &[C::G]) -> Result<C::G,
 /// A n-of-n non-interactive DKG which does not guarantee the usability of the resulting key.
 ///
 /// Creating an aggregate key with a list containing duplicated public keys returns an error.
-#[cfg(feature = "std")]
 pub fn musig<C: Ciphersuite>(
   context: &[u8],
   private_key: &Zeroizing<C::F>,
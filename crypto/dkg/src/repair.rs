@@ -0,0 +1,459 @@
+use core::{ops::Deref, fmt};
+use std::{
+  io::{self, Read, Write},
+  collections::HashMap,
+};
+
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use ciphersuite::{
+  group::{
+    ff::{Field, PrimeField},
+    GroupEncoding,
+  },
+  Ciphersuite,
+};
+use multiexp::BatchVerifier;
+
+use crate::{
+  Participant, DkgError, ThresholdParams, ThresholdCore, ThresholdKeys,
+  encryption::{ReadWrite, EncryptionKeyMessage, EncryptedMessage, Encryption, EncryptionKeyProof},
+  frost::SecretShare,
+};
+
+type RepairError<C> = DkgError<EncryptionKeyProof<C>>;
+
+// The lagrange coefficient for `i`, within `included`, when interpolating the polynomial at
+// `target` (instead of at 0, as `lagrange` does)
+fn lagrange_at<F: PrimeField>(i: Participant, included: &[Participant], target: Participant) -> F {
+  let i_f = F::from(u64::from(u16::from(i)));
+  let target = F::from(u64::from(u16::from(target)));
+
+  let mut num = F::ONE;
+  let mut denom = F::ONE;
+  for l in included {
+    if i == *l {
+      continue;
+    }
+
+    let share = F::from(u64::from(u16::from(*l)));
+    num *= target - share;
+    denom *= i_f - share;
+  }
+
+  // Safe as this will only be 0 if we're part of the above loop
+  num * denom.invert().unwrap()
+}
+
+// Validate a map of values has a value for every participant in the specified set, besides
+// ourselves (if we're in the set), and only them
+fn validate_set_map<T, B: Clone + PartialEq + Eq + fmt::Debug>(
+  map: &HashMap<Participant, T>,
+  set: &[Participant],
+  ours: Option<Participant>,
+) -> Result<(), DkgError<B>> {
+  let expected = set.iter().filter(|l| Some(**l) != ours).collect::<Vec<_>>();
+  if map.len() != expected.len() {
+    Err(DkgError::InvalidParticipantQuantity(expected.len(), map.len()))?;
+  }
+  for participant in expected {
+    if !map.contains_key(participant) {
+      Err(DkgError::MissingParticipant(*participant))?;
+    }
+  }
+  Ok(())
+}
+
+#[derive(Clone, Copy, Hash, Debug, Zeroize)]
+enum BatchId {
+  Decryption(Participant),
+  Share(Participant),
+}
+
+/// The message the participant whose share is being repaired sends to every helper, registering
+/// the key the repaired share should be encrypted to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Zeroize)]
+pub struct RepairRequest;
+
+impl ReadWrite for RepairRequest {
+  fn read<R: Read>(_: &mut R, _: ThresholdParams) -> io::Result<Self> {
+    Ok(RepairRequest)
+  }
+
+  fn write<W: Write>(&self, _: &mut W) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+/// A helper's commitments to how it split its contribution to the repaired share, intended to be
+/// broadcast to every other helper and the participant whose share is being repaired.
+///
+/// There's one commitment per helper, in the order of their participant indexes.
+///
+/// Every helper should only provide one set of commitments to all parties. If any helper sends
+/// multiple sets of commitments, they are faulty and should be presumed malicious.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
+pub struct RepairCommitments<C: Ciphersuite>(Vec<C::G>);
+
+impl<C: Ciphersuite> ReadWrite for RepairCommitments<C> {
+  fn read<R: Read>(reader: &mut R, params: ThresholdParams) -> io::Result<Self> {
+    let mut commitments = Vec::with_capacity(params.t().into());
+    for _ in 0 .. params.t() {
+      commitments.push(C::read_G(reader)?);
+    }
+    Ok(RepairCommitments(commitments))
+  }
+
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    for commitment in &self.0 {
+      writer.write_all(commitment.to_bytes().as_ref())?;
+    }
+    Ok(())
+  }
+}
+
+/// State machine for a helper, contributing to the repair of a lost participant's share.
+///
+/// This implements the repairable threshold scheme from
+/// [Laing and Stinson](https://eprint.iacr.org/2017/1155). The helpers are exactly `t`
+/// participants. Each helper splits its share of the lost share into random summands, one per
+/// helper, which each helper then sums and sends to the lost participant. No party learns anything
+/// beyond what they already know, besides the lost participant learning their share.
+pub struct RepairMachine<C: Ciphersuite> {
+  params: ThresholdParams,
+  helpers: Vec<Participant>,
+  lost: Participant,
+  verification_shares: HashMap<Participant, C::G>,
+  summands: Vec<Zeroizing<C::F>>,
+  our_commitments: Vec<C::G>,
+  encryption: Encryption<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for RepairMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("RepairMachine")
+      .field("params", &self.params)
+      .field("helpers", &self.helpers)
+      .field("lost", &self.lost)
+      .field("our_commitments", &self.our_commitments)
+      .field("encryption", &self.encryption)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> RepairMachine<C> {
+  /// Start helping repair the share of the `lost` participant.
+  ///
+  /// `helpers` must be exactly `t` participants, including these keys' participant yet not the
+  /// lost participant. Every helper must use the same set. The context string should be unique
+  /// among repairs and key generations. These keys must not be offset.
+  ///
+  /// Returns commitments to be sent to every other helper and the lost participant over an
+  /// authenticated channel.
+  #[allow(clippy::type_complexity)]
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    keys: &ThresholdKeys<C>,
+    mut helpers: Vec<Participant>,
+    lost: Participant,
+    context: String,
+  ) -> Result<(RepairMachine<C>, EncryptionKeyMessage<C, RepairCommitments<C>>), DkgError<()>> {
+    assert!(keys.current_offset().is_none(), "repairing with keys which were offset");
+    let params = keys.params();
+
+    helpers.sort();
+    helpers.dedup();
+    if (helpers.len() != usize::from(params.t())) ||
+      (!helpers.contains(&params.i())) ||
+      helpers.contains(&lost)
+    {
+      Err(DkgError::InvalidSigningSet)?;
+    }
+    for participant in helpers.iter().chain([lost].iter()) {
+      if u16::from(*participant) > params.n() {
+        Err(DkgError::InvalidParticipant(params.n(), *participant))?;
+      }
+    }
+
+    // Our contribution to the lost share is our share, weighted to interpolate the lost share
+    let contribution =
+      Zeroizing::new(lagrange_at::<C::F>(params.i(), &helpers, lost) * keys.secret_share().deref());
+
+    // Split it into random summands, one per helper
+    let mut summands = Vec::with_capacity(helpers.len());
+    let mut remaining = contribution;
+    for _ in 1 .. helpers.len() {
+      let summand = Zeroizing::new(C::F::random(&mut *rng));
+      *remaining -= summand.deref();
+      summands.push(summand);
+    }
+    summands.push(remaining);
+
+    let our_commitments =
+      summands.iter().map(|summand| C::generator() * summand.deref()).collect::<Vec<_>>();
+
+    let encryption = Encryption::new(context, Some(params.i()), rng);
+    let msg = encryption.registration(RepairCommitments(our_commitments.clone()));
+    Ok((
+      RepairMachine {
+        params,
+        helpers,
+        lost,
+        verification_shares: keys.verification_shares(),
+        summands,
+        our_commitments,
+        encryption,
+      },
+      msg,
+    ))
+  }
+
+  /// Continue helping repair the share.
+  ///
+  /// Takes in every other helper's commitments, and the lost participant's registration. Returns
+  /// a HashMap of encrypted summands to be sent over authenticated channels to the other helpers.
+  #[allow(clippy::type_complexity)]
+  pub fn generate_summands<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    mut commitments: HashMap<Participant, EncryptionKeyMessage<C, RepairCommitments<C>>>,
+    registration: EncryptionKeyMessage<C, RepairRequest>,
+  ) -> Result<
+    (RepairSummandMachine<C>, HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>),
+    DkgError<()>,
+  > {
+    validate_set_map(&commitments, &self.helpers, Some(self.params.i()))?;
+    self.encryption.register(self.lost, registration);
+
+    let mut all_commitments = HashMap::new();
+    for (l, msg) in commitments.drain() {
+      let RepairCommitments(these_commitments) = self.encryption.register(l, msg);
+      // Each helper's summands must sum to their actual contribution to the lost share
+      if (these_commitments.len() != self.helpers.len()) ||
+        (these_commitments.iter().copied().sum::<C::G>() !=
+          (self.verification_shares[&l] * lagrange_at::<C::F>(l, &self.helpers, self.lost)))
+      {
+        Err(DkgError::InvalidCommitments(l))?;
+      }
+      all_commitments.insert(l, these_commitments);
+    }
+    all_commitments.insert(self.params.i(), self.our_commitments.drain(..).collect());
+
+    let mut res = HashMap::new();
+    let mut our_summand = None;
+    for (l, summand) in self.helpers.iter().zip(self.summands.drain(..)) {
+      let summand_bytes = Zeroizing::new(SecretShare::<C::F>(summand.to_repr()));
+      if *l == self.params.i() {
+        our_summand = Some(summand);
+        continue;
+      }
+      res.insert(*l, self.encryption.encrypt(rng, *l, summand_bytes));
+    }
+
+    Ok((
+      RepairSummandMachine {
+        params: self.params,
+        helpers: self.helpers,
+        lost: self.lost,
+        sum: our_summand.unwrap(),
+        commitments: all_commitments,
+        encryption: self.encryption,
+      },
+      res,
+    ))
+  }
+}
+
+/// Advancement of the repair state machine.
+pub struct RepairSummandMachine<C: Ciphersuite> {
+  params: ThresholdParams,
+  helpers: Vec<Participant>,
+  lost: Participant,
+  sum: Zeroizing<C::F>,
+  commitments: HashMap<Participant, Vec<C::G>>,
+  encryption: Encryption<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for RepairSummandMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("RepairSummandMachine")
+      .field("params", &self.params)
+      .field("helpers", &self.helpers)
+      .field("lost", &self.lost)
+      .field("commitments", &self.commitments)
+      .field("encryption", &self.encryption)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> RepairSummandMachine<C> {
+  /// Complete helping repair the share.
+  ///
+  /// Takes in the summands sent to us by every other helper. Returns the encrypted sum, to be sent
+  /// over an authenticated channel to the lost participant.
+  ///
+  /// This will error on, and return a blame proof for, the first-observed case of faulty behavior.
+  pub fn sum<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    mut summands: HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>,
+  ) -> Result<EncryptedMessage<C, SecretShare<C::F>>, RepairError<C>> {
+    validate_set_map(&summands, &self.helpers, Some(self.params.i()))?;
+
+    let our_index = self.helpers.iter().position(|l| *l == self.params.i()).unwrap();
+
+    let mut batch = BatchVerifier::new(summands.len());
+    let mut blames = HashMap::new();
+    for (l, summand_bytes) in summands.drain() {
+      let (mut summand_bytes, blame) =
+        self.encryption.decrypt(rng, &mut batch, BatchId::Decryption(l), l, summand_bytes);
+      let summand = Zeroizing::new(
+        Option::<C::F>::from(C::F::from_repr(summand_bytes.0))
+          .ok_or_else(|| DkgError::InvalidShare { participant: l, blame: Some(blame.clone()) })?,
+      );
+      summand_bytes.zeroize();
+      *self.sum += summand.deref();
+
+      blames.insert(l, blame);
+      batch.queue(
+        rng,
+        BatchId::Share(l),
+        [(C::F::ONE, self.commitments[&l][our_index]), (-*summand, C::generator())],
+      );
+    }
+    batch.verify_with_vartime_blame().map_err(|id| {
+      let (l, blame) = match id {
+        BatchId::Decryption(l) => (l, None),
+        BatchId::Share(l) => (l, Some(blames.remove(&l).unwrap())),
+      };
+      DkgError::InvalidShare { participant: l, blame }
+    })?;
+
+    let sum_bytes = Zeroizing::new(SecretShare::<C::F>(self.sum.to_repr()));
+    Ok(self.encryption.encrypt(rng, self.lost, sum_bytes))
+  }
+}
+
+/// State machine for a participant who lost their share, having it repaired by a set of helpers.
+pub struct RepairedMachine<C: Ciphersuite> {
+  params: ThresholdParams,
+  verification_shares: HashMap<Participant, C::G>,
+  encryption: Encryption<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for RepairedMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("RepairedMachine")
+      .field("params", &self.params)
+      .field("verification_shares", &self.verification_shares)
+      .field("encryption", &self.encryption)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> RepairedMachine<C> {
+  /// Request the repair of our share.
+  ///
+  /// `verification_shares` must be every participant's verification share, as agreed upon when
+  /// the keys were generated. They're used to verify the repaired share.
+  ///
+  /// Returns a registration message to be sent to every helper over an authenticated channel.
+  #[allow(clippy::type_complexity)]
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    params: ThresholdParams,
+    context: String,
+    verification_shares: HashMap<Participant, C::G>,
+  ) -> Result<(RepairedMachine<C>, EncryptionKeyMessage<C, RepairRequest>), DkgError<()>> {
+    for i in (1 ..= params.n()).map(Participant) {
+      if !verification_shares.contains_key(&i) {
+        Err(DkgError::MissingParticipant(i))?;
+      }
+    }
+    if verification_shares.len() != usize::from(params.n()) {
+      Err(DkgError::InvalidParticipantQuantity(params.n().into(), verification_shares.len()))?;
+    }
+
+    let encryption = Encryption::new(context, Some(params.i()), rng);
+    let msg = encryption.registration(RepairRequest);
+    Ok((RepairedMachine { params, verification_shares, encryption }, msg))
+  }
+
+  /// Recover our share, given every helper's commitments and the sums they sent to us.
+  ///
+  /// This will error on, and return a blame proof for, the first-observed case of faulty behavior.
+  pub fn complete<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    commitments: HashMap<Participant, EncryptionKeyMessage<C, RepairCommitments<C>>>,
+    mut sums: HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>,
+  ) -> Result<ThresholdCore<C>, RepairError<C>> {
+    let mut helpers = commitments.keys().copied().collect::<Vec<_>>();
+    helpers.sort();
+    if (helpers.len() != usize::from(self.params.t())) || helpers.contains(&self.params.i()) {
+      Err(DkgError::InvalidSigningSet)?;
+    }
+    for helper in &helpers {
+      if u16::from(*helper) > self.params.n() {
+        Err(DkgError::InvalidParticipant(self.params.n(), *helper))?;
+      }
+    }
+    validate_set_map(&sums, &helpers, None)?;
+
+    let mut all_commitments = HashMap::new();
+    for (l, msg) in commitments {
+      let RepairCommitments(these_commitments) = self.encryption.register(l, msg);
+      // Each helper's summands must sum to their actual contribution to our share
+      if (these_commitments.len() != helpers.len()) ||
+        (these_commitments.iter().copied().sum::<C::G>() !=
+          (self.verification_shares[&l] * lagrange_at::<C::F>(l, &helpers, self.params.i())))
+      {
+        Err(DkgError::InvalidCommitments(l))?;
+      }
+      all_commitments.insert(l, these_commitments);
+    }
+
+    let mut secret = Zeroizing::new(C::F::ZERO);
+    let mut batch = BatchVerifier::new(sums.len());
+    let mut blames = HashMap::new();
+    for (l, sum_bytes) in sums.drain() {
+      let (mut sum_bytes, blame) =
+        self.encryption.decrypt(rng, &mut batch, BatchId::Decryption(l), l, sum_bytes);
+      let sum = Zeroizing::new(
+        Option::<C::F>::from(C::F::from_repr(sum_bytes.0))
+          .ok_or_else(|| DkgError::InvalidShare { participant: l, blame: Some(blame.clone()) })?,
+      );
+      sum_bytes.zeroize();
+      *secret += sum.deref();
+
+      // The sum from helper l should be the sum of every helper's summand for l
+      let index = helpers.iter().position(|helper| *helper == l).unwrap();
+      let mut statements = all_commitments
+        .values()
+        .map(|commitments| (C::F::ONE, commitments[index]))
+        .collect::<Vec<_>>();
+      statements.push((-*sum, C::generator()));
+
+      blames.insert(l, blame);
+      batch.queue(rng, BatchId::Share(l), statements);
+    }
+    batch.verify_with_vartime_blame().map_err(|id| {
+      let (l, blame) = match id {
+        BatchId::Decryption(l) => (l, None),
+        BatchId::Share(l) => (l, Some(blames.remove(&l).unwrap())),
+      };
+      DkgError::InvalidShare { participant: l, blame }
+    })?;
+
+    // Since every sum was verified against the helpers' commitments, and every helper's
+    // commitments were verified to sum to their contribution, this is guaranteed
+    debug_assert_eq!(C::generator() * secret.deref(), self.verification_shares[&self.params.i()]);
+
+    Ok(ThresholdCore::new(self.params, secret, self.verification_shares))
+  }
+}
@@ -0,0 +1,348 @@
+use core::{ops::Deref, fmt};
+use std::{
+  io::{self, Read, Write},
+  collections::HashMap,
+};
+
+use rand_core::{RngCore, CryptoRng};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use ciphersuite::{
+  group::{
+    ff::{Field, PrimeField},
+    GroupEncoding,
+  },
+  Ciphersuite,
+};
+use multiexp::{multiexp_vartime, BatchVerifier};
+
+use crate::{
+  Participant, DkgError, ThresholdParams, ThresholdCore, ThresholdKeys, lagrange,
+  encryption::{ReadWrite, EncryptionKeyMessage, EncryptedMessage, Encryption, EncryptionKeyProof},
+  frost::{SecretShare, polynomial, exponential, share_verification_statements},
+};
+
+type ResharingError<C> = DkgError<EncryptionKeyProof<C>>;
+
+// Validate a map of values has a value for every participant in the specified set, and only them
+fn validate_set_map<T, B: Clone + PartialEq + Eq + fmt::Debug>(
+  map: &HashMap<Participant, T>,
+  set: &[Participant],
+) -> Result<(), DkgError<B>> {
+  if map.len() != set.len() {
+    Err(DkgError::InvalidParticipantQuantity(set.len(), map.len()))?;
+  }
+  for participant in set {
+    if !map.contains_key(participant) {
+      Err(DkgError::MissingParticipant(*participant))?;
+    }
+  }
+  Ok(())
+}
+
+/// The message a participant of the new set broadcasts to every resharer, registering the key
+/// their secret shares should be encrypted to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Zeroize)]
+pub struct ReshareRequest;
+
+impl ReadWrite for ReshareRequest {
+  fn read<R: Read>(_: &mut R, _: ThresholdParams) -> io::Result<Self> {
+    Ok(ReshareRequest)
+  }
+
+  fn write<W: Write>(&self, _: &mut W) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+/// The commitments to a resharer's polynomial, intended to be broadcast to every participant of
+/// the new set.
+///
+/// Every resharer should only provide one set of commitments to all parties. If any resharer
+/// sends multiple sets of commitments, they are faulty and should be presumed malicious.
+#[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
+pub struct ResharingCommitments<C: Ciphersuite>(Vec<C::G>);
+
+impl<C: Ciphersuite> ResharingCommitments<C> {
+  /// Read a set of commitments, where `params` are the parameters of the new set.
+  pub fn read<R: Read>(reader: &mut R, params: ThresholdParams) -> io::Result<Self> {
+    let mut commitments = Vec::with_capacity(params.t().into());
+    for _ in 0 .. params.t() {
+      commitments.push(C::read_G(reader)?);
+    }
+    Ok(ResharingCommitments(commitments))
+  }
+
+  pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    for commitment in &self.0 {
+      writer.write_all(commitment.to_bytes().as_ref())?;
+    }
+    Ok(())
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    let mut buf = vec![];
+    self.write(&mut buf).unwrap();
+    buf
+  }
+}
+
+/// State machine for a participant of the existing set, resharing their share of the key to a new
+/// set of participants.
+///
+/// The resharers are a signing set of the existing set. Each reshares their interpolated share
+/// using the FROST key generation's secret sharing, so the resharers' polynomials sum to one whose
+/// constant term is the existing key.
+pub struct ResharingMachine<C: Ciphersuite> {
+  params: ThresholdParams,
+  context: String,
+  keys: ThresholdKeys<C>,
+  resharers: Vec<Participant>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for ResharingMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("ResharingMachine")
+      .field("params", &self.params)
+      .field("context", &self.context)
+      .field("resharers", &self.resharers)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> ResharingMachine<C> {
+  /// Create a new machine to reshare these keys to a new set of `n` participants, `t` of whom will
+  /// be needed to sign.
+  ///
+  /// `resharers` is the signing set of existing participants performing the resharing, which must
+  /// include these keys' participant. Every resharer must use the same set.
+  ///
+  /// The context string should be unique among resharings and key generations. These keys must
+  /// not be offset.
+  pub fn new(
+    keys: ThresholdKeys<C>,
+    mut resharers: Vec<Participant>,
+    t: u16,
+    n: u16,
+    context: String,
+  ) -> Result<ResharingMachine<C>, DkgError<()>> {
+    assert!(keys.current_offset().is_none(), "resharing keys which were offset");
+
+    // The new set's parameters are validated here, with the participant index being ours as a
+    // resharer (only used to identify us when encrypting)
+    let params = ThresholdParams::new(t, n, Participant(1))?;
+    let params = ThresholdParams { i: keys.params().i(), ..params };
+
+    resharers.sort();
+    for pair in resharers.windows(2) {
+      if pair[0] == pair[1] {
+        Err(DkgError::DuplicatedParticipant(pair[0]))?;
+      }
+    }
+    for resharer in &resharers {
+      if u16::from(*resharer) > keys.params().n() {
+        Err(DkgError::InvalidParticipant(keys.params().n(), *resharer))?;
+      }
+    }
+    if (resharers.len() < usize::from(keys.params().t())) ||
+      (!resharers.contains(&keys.params().i()))
+    {
+      Err(DkgError::InvalidSigningSet)?;
+    }
+
+    Ok(ResharingMachine { params, context, keys, resharers })
+  }
+
+  /// Reshare our share of the key.
+  ///
+  /// Takes in the registration of every participant of the new set. Returns the commitments to
+  /// broadcast to every participant of the new set, and a HashMap of encrypted secret shares to be
+  /// sent over authenticated channels to their relevant participants.
+  #[allow(clippy::type_complexity)]
+  pub fn generate_secret_shares<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+    mut registrations: HashMap<Participant, EncryptionKeyMessage<C, ReshareRequest>>,
+  ) -> Result<
+    (ResharingCommitments<C>, HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>),
+    DkgError<()>,
+  > {
+    let participants = (1 ..= self.params.n()).map(Participant).collect::<Vec<_>>();
+    validate_set_map(&registrations, &participants)?;
+
+    let mut encryption = Encryption::new(self.context, Some(self.params.i()), rng);
+    for (l, msg) in registrations.drain() {
+      encryption.register(l, msg);
+    }
+
+    // The constant term is our interpolated share of the key, so the sum of every resharer's
+    // constant term is the key itself
+    let t = usize::from(self.params.t());
+    let mut coefficients = Vec::with_capacity(t);
+    coefficients.push(Zeroizing::new(
+      lagrange::<C::F>(self.params.i(), &self.resharers) * self.keys.secret_share().deref(),
+    ));
+    for _ in 1 .. t {
+      coefficients.push(Zeroizing::new(C::random_nonzero_F(&mut *rng)));
+    }
+    let commitments = ResharingCommitments(
+      coefficients.iter().map(|coefficient| C::generator() * coefficient.deref()).collect(),
+    );
+
+    let mut shares = HashMap::new();
+    for l in participants {
+      let mut share = polynomial(&coefficients, l);
+      let share_bytes = Zeroizing::new(SecretShare::<C::F>(share.to_repr()));
+      share.zeroize();
+      shares.insert(l, encryption.encrypt(rng, l, share_bytes));
+    }
+    coefficients.zeroize();
+
+    Ok((commitments, shares))
+  }
+}
+
+#[derive(Clone, Copy, Hash, Debug, Zeroize)]
+enum BatchId {
+  Decryption(Participant),
+  Share(Participant),
+}
+
+/// State machine for a participant of the new set, receiving a share of an existing key.
+pub struct ResharedMachine<C: Ciphersuite> {
+  params: ThresholdParams,
+  group_key: C::G,
+  resharers: Vec<Participant>,
+  // The resharers' verification shares, interpolated for the resharers
+  interpolated_shares: HashMap<Participant, C::G>,
+  encryption: Encryption<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for ResharedMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt
+      .debug_struct("ResharedMachine")
+      .field("params", &self.params)
+      .field("group_key", &self.group_key)
+      .field("resharers", &self.resharers)
+      .field("encryption", &self.encryption)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<C: Ciphersuite> ResharedMachine<C> {
+  /// Create a new machine to receive a share of an existing key.
+  ///
+  /// `verification_shares` must be the existing verification shares of the resharers, and only
+  /// the resharers. They're used to verify each resharer reshared their actual share of the key.
+  ///
+  /// Returns a registration message to be sent to every resharer over an authenticated channel.
+  #[allow(clippy::type_complexity)]
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    params: ThresholdParams,
+    context: String,
+    group_key: C::G,
+    verification_shares: HashMap<Participant, C::G>,
+  ) -> Result<(ResharedMachine<C>, EncryptionKeyMessage<C, ReshareRequest>), DkgError<()>> {
+    let mut resharers = verification_shares.keys().copied().collect::<Vec<_>>();
+    resharers.sort();
+
+    // If the resharers' interpolated verification shares don't sum to the group key, the resharers
+    // aren't a valid signing set for it
+    let interpolated_shares = verification_shares
+      .into_iter()
+      .map(|(l, share)| (l, share * lagrange::<C::F>(l, &resharers)))
+      .collect::<HashMap<_, _>>();
+    if interpolated_shares.values().copied().sum::<C::G>() != group_key {
+      Err(DkgError::InvalidSigningSet)?;
+    }
+
+    let encryption = Encryption::new(context, Some(params.i()), rng);
+    let msg = encryption.registration(ReshareRequest);
+    Ok((ResharedMachine { params, group_key, resharers, interpolated_shares, encryption }, msg))
+  }
+
+  /// Calculate our share of the key, given every resharer's commitments and the secret shares they
+  /// sent to us.
+  ///
+  /// This will error on, and return a blame proof for, the first-observed case of faulty behavior.
+  ///
+  /// The returned keys should only be used after having confirmed, with all participants of the
+  /// new set, successful completion. Until then, the existing keys must be retained.
+  pub fn calculate_share<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+    commitments: HashMap<Participant, ResharingCommitments<C>>,
+    mut shares: HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>,
+  ) -> Result<ThresholdCore<C>, ResharingError<C>> {
+    validate_set_map(&commitments, &self.resharers)?;
+    validate_set_map(&shares, &self.resharers)?;
+
+    // Each resharer must have committed to their actual interpolated share as their constant term
+    for (l, commitments) in &commitments {
+      if (commitments.0.len() != usize::from(self.params.t())) ||
+        (commitments.0[0] != self.interpolated_shares[l])
+      {
+        Err(DkgError::InvalidCommitments(*l))?;
+      }
+    }
+
+    let mut secret = Zeroizing::new(C::F::ZERO);
+    let mut batch = BatchVerifier::new(shares.len());
+    let mut blames = HashMap::new();
+    for (l, share_bytes) in shares.drain() {
+      let (mut share_bytes, blame) =
+        self.encryption.decrypt(rng, &mut batch, BatchId::Decryption(l), l, share_bytes);
+      let share = Zeroizing::new(
+        Option::<C::F>::from(C::F::from_repr(share_bytes.0))
+          .ok_or_else(|| DkgError::InvalidShare { participant: l, blame: Some(blame.clone()) })?,
+      );
+      share_bytes.zeroize();
+      *secret += share.deref();
+
+      blames.insert(l, blame);
+      batch.queue(
+        rng,
+        BatchId::Share(l),
+        share_verification_statements::<C>(self.params.i(), &commitments[&l].0, share),
+      );
+    }
+    batch.verify_with_vartime_blame().map_err(|id| {
+      let (l, blame) = match id {
+        BatchId::Decryption(l) => (l, None),
+        BatchId::Share(l) => (l, Some(blames.remove(&l).unwrap())),
+      };
+      DkgError::InvalidShare { participant: l, blame }
+    })?;
+
+    let mut stripes = Vec::with_capacity(usize::from(self.params.t()));
+    for t in 0 .. usize::from(self.params.t()) {
+      stripes.push(commitments.values().map(|commitments| commitments.0[t]).sum::<C::G>());
+    }
+    // Since every constant term was checked to be the resharer's interpolated share, this is
+    // guaranteed
+    debug_assert_eq!(stripes[0], self.group_key);
+
+    let mut verification_shares = HashMap::new();
+    for i in (1 ..= self.params.n()).map(Participant) {
+      verification_shares.insert(
+        i,
+        if i == self.params.i() {
+          C::generator() * secret.deref()
+        } else {
+          multiexp_vartime(&exponential::<C>(i, &stripes))
+        },
+      );
+    }
+
+    Ok(ThresholdCore {
+      params: self.params,
+      secret_share: secret,
+      group_key: self.group_key,
+      verification_shares,
+    })
+  }
+}
@@ -19,6 +19,18 @@ use frost::frost_gen;
 mod promote;
 use promote::test_generator_promotion;
 
+// Resharing test.
+mod resharing;
+use resharing::test_resharing;
+
+// Threshold change test.
+mod threshold;
+use threshold::test_threshold_change;
+
+// Repair test.
+mod repair;
+use repair::test_repair;
+
 /// Constant amount of participants to use when testing.
 pub const PARTICIPANTS: u16 = 5;
 /// Constant threshold of participants to use when testing.
@@ -60,7 +72,12 @@ pub fn key_gen<R: RngCore + CryptoRng, C: Ciphersuite>(
         &ThresholdCore::<C>::read::<&[u8]>(&mut core.serialize().as_ref()).unwrap(),
         &core
       );
-      (i, ThresholdKeys::new(core))
+      let keys = ThresholdKeys::new(core);
+      assert_eq!(
+        ThresholdKeys::<C>::read::<&[u8]>(&mut keys.serialize().as_ref()).unwrap().serialize(),
+        keys.serialize()
+      );
+      (i, keys)
     })
     .collect();
   assert_eq!(C::generator() * recover_key(&res), res[&Participant(1)].group_key());
@@ -93,6 +110,9 @@ pub fn musig_key_gen<R: RngCore + CryptoRng, C: Ciphersuite>(
 pub fn test_ciphersuite<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) {
   key_gen::<_, C>(rng);
   test_generator_promotion::<_, C>(rng);
+  test_resharing::<_, C>(rng);
+  test_threshold_change::<_, C>(rng);
+  test_repair::<_, C>(rng);
 }
 
 #[test]
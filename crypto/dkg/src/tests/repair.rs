@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use ciphersuite::Ciphersuite;
+
+use crate::{
+  Participant, DkgError,
+  encryption::{EncryptionKeyMessage, EncryptedMessage},
+  repair::{RepairMachine, RepairedMachine},
+  tests::{THRESHOLD, clone_without, key_gen},
+};
+
+const CONTEXT: &str = "DKG Test Repair";
+
+// Test repairing a participant's share
+pub(crate) fn test_repair<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) {
+  let keys = key_gen::<_, C>(&mut *rng);
+
+  let lost = Participant(1);
+  let helpers = (2 ..= (THRESHOLD + 1)).map(Participant).collect::<Vec<_>>();
+  let params = keys[&lost].params();
+
+  // The lost participant can't help repair their own share
+  assert_eq!(
+    RepairMachine::new(
+      &mut *rng,
+      &keys[&helpers[0]],
+      [vec![lost], helpers[1 ..].to_vec()].concat(),
+      lost,
+      CONTEXT.to_string()
+    )
+    .map(|_| ()),
+    Err(DkgError::InvalidSigningSet)
+  );
+
+  let (repaired, registration) = RepairedMachine::<C>::new(
+    &mut *rng,
+    params,
+    CONTEXT.to_string(),
+    keys[&helpers[0]].verification_shares(),
+  )
+  .unwrap();
+  let registration =
+    EncryptionKeyMessage::read::<&[u8]>(&mut registration.serialize().as_ref(), params).unwrap();
+
+  let mut machines = HashMap::new();
+  let mut commitments = HashMap::new();
+  for l in &helpers {
+    let (machine, these_commitments) =
+      RepairMachine::new(&mut *rng, &keys[l], helpers.clone(), lost, CONTEXT.to_string()).unwrap();
+    machines.insert(*l, machine);
+    commitments.insert(
+      *l,
+      EncryptionKeyMessage::read::<&[u8]>(&mut these_commitments.serialize().as_ref(), params)
+        .unwrap(),
+    );
+  }
+
+  let mut summands = HashMap::new();
+  let machines = machines
+    .drain()
+    .map(|(l, machine)| {
+      let (machine, mut these_summands) = machine
+        .generate_summands(&mut *rng, clone_without(&commitments, &l), registration.clone())
+        .unwrap();
+      let these_summands = these_summands
+        .drain()
+        .map(|(i, summand)| {
+          (i, EncryptedMessage::read::<&[u8]>(&mut summand.serialize().as_ref(), params).unwrap())
+        })
+        .collect::<HashMap<_, _>>();
+      summands.insert(l, these_summands);
+      (l, machine)
+    })
+    .collect::<HashMap<_, _>>();
+
+  let mut sums = HashMap::new();
+  for (l, machine) in machines {
+    let our_summands = summands
+      .iter()
+      .filter(|(i, _)| **i != l)
+      .map(|(i, summands)| (*i, summands[&l].clone()))
+      .collect();
+    let sum = machine.sum(&mut *rng, our_summands).unwrap();
+    sums.insert(l, EncryptedMessage::read::<&[u8]>(&mut sum.serialize().as_ref(), params).unwrap());
+  }
+
+  // The repaired keys should be identical to the lost keys
+  let repaired = repaired.complete(&mut *rng, commitments, sums).unwrap();
+  assert_eq!(&repaired, keys[&lost].core.as_ref());
+}
@@ -0,0 +1,120 @@
+use core::ops::Deref;
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use ciphersuite::Ciphersuite;
+
+use crate::{
+  Participant, DkgError, ThresholdParams, ThresholdKeys,
+  encryption::{EncryptionKeyMessage, EncryptedMessage},
+  resharing::{ResharingCommitments, ResharingMachine, ResharedMachine},
+  tests::{THRESHOLD, PARTICIPANTS, key_gen, recover_key},
+};
+
+const CONTEXT: &str = "DKG Test Resharing";
+
+// Reshare to a larger set, with a distinct threshold
+const NEW_PARTICIPANTS: u16 = PARTICIPANTS + 2;
+const NEW_THRESHOLD: u16 = THRESHOLD + 1;
+
+// Test resharing threshold keys to a new set of participants
+pub(crate) fn test_resharing<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) {
+  let keys = key_gen::<_, C>(&mut *rng);
+  let group_key = keys[&Participant(1)].group_key();
+
+  // Reshare with a signing set which doesn't include the first participant
+  let resharers = (2 ..= (THRESHOLD + 1)).map(Participant).collect::<Vec<_>>();
+  let all_verification_shares = keys[&Participant(1)].verification_shares();
+  let verification_shares =
+    resharers.iter().map(|l| (*l, all_verification_shares[l])).collect::<HashMap<_, _>>();
+
+  // An insufficient set of resharers should be rejected
+  {
+    let mut insufficient = verification_shares.clone();
+    insufficient.remove(&resharers[0]);
+    let params = ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, Participant(1)).unwrap();
+    assert_eq!(
+      ResharedMachine::<C>::new(&mut *rng, params, CONTEXT.to_string(), group_key, insufficient)
+        .map(|_| ()),
+      Err(DkgError::InvalidSigningSet)
+    );
+  }
+
+  let mut reshared = HashMap::new();
+  let mut registrations = HashMap::new();
+  for i in (1 ..= NEW_PARTICIPANTS).map(Participant) {
+    let params = ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, i).unwrap();
+    let (machine, registration) = ResharedMachine::<C>::new(
+      &mut *rng,
+      params,
+      CONTEXT.to_string(),
+      group_key,
+      verification_shares.clone(),
+    )
+    .unwrap();
+    reshared.insert(i, machine);
+    registrations.insert(
+      i,
+      EncryptionKeyMessage::read::<&[u8]>(&mut registration.serialize().as_ref(), params).unwrap(),
+    );
+  }
+
+  // Only t/n actually matters when reading, so use 1 for i
+  let new_params = ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, Participant(1)).unwrap();
+  let mut commitments = HashMap::new();
+  let mut shares = HashMap::new();
+  for l in &resharers {
+    let machine = ResharingMachine::new(
+      keys[l].clone(),
+      resharers.clone(),
+      NEW_THRESHOLD,
+      NEW_PARTICIPANTS,
+      CONTEXT.to_string(),
+    )
+    .unwrap();
+    let (these_commitments, mut these_shares) =
+      machine.generate_secret_shares(&mut *rng, registrations.clone()).unwrap();
+    commitments.insert(
+      *l,
+      ResharingCommitments::read::<&[u8]>(&mut these_commitments.serialize().as_ref(), new_params)
+        .unwrap(),
+    );
+    shares.insert(
+      *l,
+      these_shares
+        .drain()
+        .map(|(i, share)| {
+          (i, EncryptedMessage::read::<&[u8]>(&mut share.serialize().as_ref(), new_params).unwrap())
+        })
+        .collect::<HashMap<_, _>>(),
+    );
+  }
+
+  let mut new_keys = HashMap::new();
+  for (i, machine) in reshared {
+    let our_shares = shares.iter().map(|(l, shares)| (*l, shares[&i].clone())).collect();
+    let core = machine.calculate_share(&mut *rng, commitments.clone(), our_shares).unwrap();
+    assert_eq!(core.params(), ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, i).unwrap());
+    // The group key should be unchanged
+    assert_eq!(core.group_key(), group_key);
+    new_keys.insert(i, ThresholdKeys::new(core));
+  }
+
+  // Every participant should agree on the verification shares, which should match the shares
+  let verification_shares = new_keys[&Participant(1)].verification_shares();
+  for (i, keys) in &new_keys {
+    assert_eq!(keys.verification_shares(), verification_shares);
+    assert_eq!(C::generator() * keys.secret_share().deref(), verification_shares[i]);
+  }
+
+  // Any new threshold of participants should be able to recover the key
+  assert_eq!(C::generator() * recover_key(&new_keys), group_key);
+  let signing_set = new_keys
+    .iter()
+    .filter(|(i, _)| u16::from(**i) > (NEW_PARTICIPANTS - NEW_THRESHOLD))
+    .map(|(i, keys)| (*i, keys.clone()))
+    .collect::<HashMap<_, _>>();
+  assert_eq!(signing_set.len(), usize::from(NEW_THRESHOLD));
+  assert_eq!(C::generator() * recover_key(&signing_set), group_key);
+}
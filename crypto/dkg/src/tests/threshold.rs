@@ -0,0 +1,125 @@
+use core::ops::Deref;
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use ciphersuite::{group::ff::Field, Ciphersuite};
+
+use crate::{
+  Participant, DkgError, ThresholdParams, ThresholdKeys, lagrange,
+  encryption::{EncryptionKeyMessage, EncryptedMessage},
+  resharing::ResharingCommitments,
+  threshold::{ThresholdChangeMachine, ThresholdChangedMachine},
+  tests::{THRESHOLD, PARTICIPANTS, key_gen, recover_key},
+};
+
+const CONTEXT: &str = "DKG Test Threshold Change";
+
+// Grow the set by one participant, increasing the threshold accordingly
+const NEW_PARTICIPANTS: u16 = PARTICIPANTS + 1;
+const NEW_THRESHOLD: u16 = THRESHOLD + 1;
+
+// Test changing the threshold of keys
+pub(crate) fn test_threshold_change<R: RngCore + CryptoRng, C: Ciphersuite>(rng: &mut R) {
+  let keys = key_gen::<_, C>(&mut *rng);
+  let group_key = keys[&Participant(1)].group_key();
+
+  let resharers = (1 ..= THRESHOLD).map(Participant).collect::<Vec<_>>();
+  let all_verification_shares = keys[&Participant(1)].verification_shares();
+  let verification_shares =
+    resharers.iter().map(|l| (*l, all_verification_shares[l])).collect::<HashMap<_, _>>();
+
+  // Fewer verification shares than the existing threshold should be rejected
+  {
+    let mut insufficient = verification_shares.clone();
+    insufficient.remove(&resharers[0]);
+    let params = ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, Participant(1)).unwrap();
+    assert_eq!(
+      ThresholdChangedMachine::<C>::new(
+        &mut *rng,
+        params,
+        CONTEXT.to_string(),
+        THRESHOLD,
+        group_key,
+        insufficient
+      )
+      .map(|_| ()),
+      Err(DkgError::InvalidSigningSet)
+    );
+  }
+
+  let mut changed = HashMap::new();
+  let mut registrations = HashMap::new();
+  for i in (1 ..= NEW_PARTICIPANTS).map(Participant) {
+    let params = ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, i).unwrap();
+    let (machine, registration) = ThresholdChangedMachine::<C>::new(
+      &mut *rng,
+      params,
+      CONTEXT.to_string(),
+      THRESHOLD,
+      group_key,
+      verification_shares.clone(),
+    )
+    .unwrap();
+    changed.insert(i, machine);
+    registrations.insert(
+      i,
+      EncryptionKeyMessage::read::<&[u8]>(&mut registration.serialize().as_ref(), params).unwrap(),
+    );
+  }
+
+  let new_params = ThresholdParams::new(NEW_THRESHOLD, NEW_PARTICIPANTS, Participant(1)).unwrap();
+  let mut commitments = HashMap::new();
+  let mut shares = HashMap::new();
+  for l in &resharers {
+    let machine = ThresholdChangeMachine::new(
+      keys[l].clone(),
+      resharers.clone(),
+      NEW_THRESHOLD,
+      NEW_PARTICIPANTS,
+      CONTEXT.to_string(),
+    )
+    .unwrap();
+    let (these_commitments, mut these_shares) =
+      machine.generate_secret_shares(&mut *rng, registrations.clone()).unwrap();
+    commitments.insert(
+      *l,
+      ResharingCommitments::read::<&[u8]>(&mut these_commitments.serialize().as_ref(), new_params)
+        .unwrap(),
+    );
+    shares.insert(
+      *l,
+      these_shares
+        .drain()
+        .map(|(i, share)| {
+          (i, EncryptedMessage::read::<&[u8]>(&mut share.serialize().as_ref(), new_params).unwrap())
+        })
+        .collect::<HashMap<_, _>>(),
+    );
+  }
+
+  let mut new_keys = HashMap::new();
+  for (i, machine) in changed {
+    let our_shares = shares.iter().map(|(l, shares)| (*l, shares[&i].clone())).collect();
+    let core = machine.calculate_share(&mut *rng, commitments.clone(), our_shares).unwrap();
+    assert_eq!(core.params().t(), NEW_THRESHOLD);
+    // The group key should be unchanged
+    assert_eq!(core.group_key(), group_key);
+    new_keys.insert(i, ThresholdKeys::new(core));
+  }
+
+  // Exactly the new threshold of participants should be able to recover the key
+  let signing_set = new_keys
+    .iter()
+    .filter(|(i, _)| u16::from(**i) <= NEW_THRESHOLD)
+    .map(|(i, keys)| (*i, keys.clone()))
+    .collect::<HashMap<_, _>>();
+  assert_eq!(C::generator() * recover_key(&signing_set), group_key);
+
+  // While the prior threshold of participants no longer should be
+  let included = (1 ..= THRESHOLD).map(Participant).collect::<Vec<_>>();
+  let insufficient = included.iter().fold(C::F::ZERO, |accum, i| {
+    accum + (lagrange::<C::F>(*i, &included) * new_keys[i].secret_share().deref())
+  });
+  assert!(C::generator() * insufficient != group_key);
+}
@@ -0,0 +1,130 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use rand_core::{RngCore, CryptoRng};
+
+use ciphersuite::Ciphersuite;
+
+use crate::{
+  Participant, DkgError, ThresholdParams, ThresholdCore, ThresholdKeys,
+  encryption::{EncryptionKeyMessage, EncryptedMessage, EncryptionKeyProof},
+  frost::SecretShare,
+  resharing::{ReshareRequest, ResharingCommitments, ResharingMachine, ResharedMachine},
+};
+
+type ThresholdChangeError<C> = DkgError<EncryptionKeyProof<C>>;
+
+// The context used by the underlying resharing, domain separated from any other protocol and
+// bound to the threshold change being performed
+fn threshold_change_context(context: &str, existing_t: u16, t: u16, n: u16) -> String {
+  format!("DKG Threshold Change v0.1 ({existing_t} to {t}-of-{n}): {context}")
+}
+
+/// State machine for a participant of the existing set, changing the threshold of the key.
+///
+/// This reshares the key with the new threshold, as `ResharingMachine` does, yet under its own
+/// context so a threshold change can't be confused with any other resharing.
+pub struct ThresholdChangeMachine<C: Ciphersuite> {
+  machine: ResharingMachine<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for ThresholdChangeMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt.debug_struct("ThresholdChangeMachine").field("machine", &self.machine).finish()
+  }
+}
+
+impl<C: Ciphersuite> ThresholdChangeMachine<C> {
+  /// Create a new machine to change the threshold of these keys to `t`, among a set of `n`
+  /// participants.
+  ///
+  /// `resharers` is the signing set of existing participants performing the change, which must
+  /// include these keys' participant. Every resharer must use the same set.
+  ///
+  /// The context string should be unique among threshold changes, resharings, and key generations.
+  /// These keys must not be offset.
+  pub fn new(
+    keys: ThresholdKeys<C>,
+    resharers: Vec<Participant>,
+    t: u16,
+    n: u16,
+    context: String,
+  ) -> Result<ThresholdChangeMachine<C>, DkgError<()>> {
+    let context = threshold_change_context(&context, keys.params().t(), t, n);
+    Ok(ThresholdChangeMachine { machine: ResharingMachine::new(keys, resharers, t, n, context)? })
+  }
+
+  /// Reshare our share of the key under the new threshold.
+  ///
+  /// Takes in the registration of every participant of the new set. Returns the commitments to
+  /// broadcast to every participant of the new set, and a HashMap of encrypted secret shares to be
+  /// sent over authenticated channels to their relevant participants.
+  #[allow(clippy::type_complexity)]
+  pub fn generate_secret_shares<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+    registrations: HashMap<Participant, EncryptionKeyMessage<C, ReshareRequest>>,
+  ) -> Result<
+    (ResharingCommitments<C>, HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>),
+    DkgError<()>,
+  > {
+    self.machine.generate_secret_shares(rng, registrations)
+  }
+}
+
+/// State machine for a participant of the new set, receiving a share of the key under its new
+/// threshold.
+pub struct ThresholdChangedMachine<C: Ciphersuite> {
+  machine: ResharedMachine<C>,
+}
+
+impl<C: Ciphersuite> fmt::Debug for ThresholdChangedMachine<C> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt.debug_struct("ThresholdChangedMachine").field("machine", &self.machine).finish()
+  }
+}
+
+impl<C: Ciphersuite> ThresholdChangedMachine<C> {
+  /// Create a new machine to receive a share of an existing key, whose threshold is currently
+  /// `existing_t`, under the threshold specified by `params`.
+  ///
+  /// `verification_shares` must be the existing verification shares of the resharers, and only
+  /// the resharers. They're used to verify each resharer reshared their actual share of the key.
+  ///
+  /// Returns a registration message to be sent to every resharer over an authenticated channel.
+  #[allow(clippy::type_complexity)]
+  pub fn new<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    params: ThresholdParams,
+    context: String,
+    existing_t: u16,
+    group_key: C::G,
+    verification_shares: HashMap<Participant, C::G>,
+  ) -> Result<(ThresholdChangedMachine<C>, EncryptionKeyMessage<C, ReshareRequest>), DkgError<()>>
+  {
+    if verification_shares.len() < usize::from(existing_t) {
+      Err(DkgError::InvalidSigningSet)?;
+    }
+
+    let context = threshold_change_context(&context, existing_t, params.t(), params.n());
+    let (machine, msg) =
+      ResharedMachine::new(rng, params, context, group_key, verification_shares)?;
+    Ok((ThresholdChangedMachine { machine }, msg))
+  }
+
+  /// Calculate our share of the key, given every resharer's commitments and the secret shares they
+  /// sent to us.
+  ///
+  /// This will error on, and return a blame proof for, the first-observed case of faulty behavior.
+  ///
+  /// The returned keys should only be used after having confirmed, with all participants of the
+  /// new set, successful completion. Until then, the existing keys must be retained.
+  pub fn calculate_share<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+    commitments: HashMap<Participant, ResharingCommitments<C>>,
+    shares: HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>,
+  ) -> Result<ThresholdCore<C>, ThresholdChangeError<C>> {
+    self.machine.calculate_share(rng, commitments, shares)
+  }
+}
@@ -33,6 +33,9 @@ pub(crate) enum Re<G0: PrimeGroup, G1: PrimeGroup> {
   // while maintaining the bandwidth savings, yet also while adding 252 hashes for
   // Secp256k1/Ed25519
   e(G0::Scalar),
+  // Borromean ring signatures share a single challenge across every ring, which is stored once
+  // within the proof instead of within each ring
+  Shared,
 }
 
 impl<G0: PrimeGroup, G1: PrimeGroup> Re<G0, G1> {
@@ -131,6 +134,7 @@ where
             *R1_0 = R.1
           }
           Re::e(ref mut e_0) => *e_0 = e.0,
+          Re::Shared => {}
         }
       }
 
@@ -204,11 +208,98 @@ where
           Err(DLEqError::InvalidProof)?;
         }
       }
+
+      // Rings with a shared challenge must be verified with borromean_verify
+      Re::Shared => Err(DLEqError::InvalidProof)?,
     }
 
     Ok(())
   }
 
+  // Borromean ring signatures need every ring's final R before the shared challenge can be
+  // calculated. Accordingly, rings are proven in two halves, the first of which is from after the
+  // actual index through the end of the ring. This returns the nonce for the actual index and the
+  // final R.
+  #[allow(non_snake_case, clippy::type_complexity)]
+  pub(crate) fn borromean_start<R: RngCore + CryptoRng, T: Clone + Transcript>(
+    rng: &mut R,
+    transcript: T,
+    generators: (Generators<G0>, Generators<G1>),
+    ring: &[(G0, G1)],
+    actual: usize,
+  ) -> (Self, (G0::Scalar, G1::Scalar), (G0, G1)) {
+    debug_assert!((RING_LEN == 2) || (RING_LEN == 4));
+    debug_assert_eq!(RING_LEN, ring.len());
+
+    let mut s = [(G0::Scalar::ZERO, G1::Scalar::ZERO); RING_LEN];
+
+    let r = (G0::Scalar::random(&mut *rng), G1::Scalar::random(&mut *rng));
+    let mut R = (generators.0.alt * r.0, generators.1.alt * r.1);
+    for i in (actual + 1) .. RING_LEN {
+      let e = Self::nonces(transcript.clone(), R);
+      s[i] = (G0::Scalar::random(&mut *rng), G1::Scalar::random(&mut *rng));
+      R = Self::R(generators, s[i], ring[i], e);
+    }
+
+    (Aos { Re_0: Re::Shared, s }, r, R)
+  }
+
+  // Complete a ring started with borromean_start, from its start through the actual index, now
+  // that the shared challenge is known
+  #[allow(clippy::too_many_arguments)]
+  pub(crate) fn borromean_finish<R: RngCore + CryptoRng, T: Clone + Transcript>(
+    &mut self,
+    rng: &mut R,
+    transcript: T,
+    generators: (Generators<G0>, Generators<G1>),
+    ring: &[(G0, G1)],
+    mut actual: usize,
+    r: &mut (G0::Scalar, G1::Scalar),
+    blinding_key: &mut (G0::Scalar, G1::Scalar),
+    e_0: (G0::Scalar, G1::Scalar),
+  ) {
+    debug_assert_eq!(RING_LEN, ring.len());
+
+    let mut e = e_0;
+    #[allow(clippy::needless_range_loop)]
+    for i in 0 .. actual {
+      self.s[i] = (G0::Scalar::random(&mut *rng), G1::Scalar::random(&mut *rng));
+      e = Self::R_nonces(transcript.clone(), generators, self.s[i], ring[i], e);
+    }
+
+    self.s[actual] = (r.0 + (e.0 * blinding_key.0), r.1 + (e.1 * blinding_key.1));
+    debug_assert_eq!(
+      Self::R(generators, self.s[actual], ring[actual], e),
+      (generators.0.alt * r.0, generators.1.alt * r.1)
+    );
+    actual.zeroize();
+    blinding_key.0.zeroize();
+    blinding_key.1.zeroize();
+    r.0.zeroize();
+    r.1.zeroize();
+  }
+
+  // Verify a ring with a shared challenge, returning its final R to be checked against the shared
+  // challenge
+  // Assumes the ring has already been transcripted in some form. Critically insecure if it hasn't
+  #[allow(non_snake_case)]
+  pub(crate) fn borromean_verify<T: Clone + Transcript>(
+    &self,
+    transcript: T,
+    generators: (Generators<G0>, Generators<G1>),
+    ring: &[(G0, G1)],
+    e_0: (G0::Scalar, G1::Scalar),
+  ) -> (G0, G1) {
+    debug_assert_eq!(RING_LEN, ring.len());
+
+    let mut e = e_0;
+    #[allow(clippy::needless_range_loop)]
+    for i in 0 .. (RING_LEN - 1) {
+      e = Self::R_nonces(transcript.clone(), generators, self.s[i], ring[i], e);
+    }
+    Self::R(generators, self.s[RING_LEN - 1], ring[RING_LEN - 1], e)
+  }
+
   #[cfg(feature = "serialize")]
   pub(crate) fn write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
     #[allow(non_snake_case)]
@@ -218,6 +309,7 @@ where
         w.write_all(R1.to_bytes().as_ref())?;
       }
       Re::e(e) => w.write_all(e.to_repr().as_ref())?,
+      Re::Shared => {}
     }
 
     for i in 0 .. RING_LEN {
@@ -237,6 +329,7 @@ where
         *R1 = read_point(r)?
       }
       Re::e(ref mut e) => *e = read_scalar(r)?,
+      Re::Shared => {}
     }
 
     let mut s = [(G0::Scalar::ZERO, G1::Scalar::ZERO); RING_LEN];
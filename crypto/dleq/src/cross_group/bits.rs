@@ -1,6 +1,6 @@
 use rand_core::{RngCore, CryptoRng};
 
-use zeroize::Zeroize;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use transcript::Transcript;
 
@@ -23,6 +23,7 @@ pub(crate) enum BitSignature {
   ConciseLinear,
   EfficientLinear,
   CompromiseLinear,
+  CompactLinear,
 }
 
 impl BitSignature {
@@ -32,6 +33,7 @@ impl BitSignature {
       BitSignature::ConciseLinear => 1,
       BitSignature::EfficientLinear => 2,
       BitSignature::CompromiseLinear => 3,
+      BitSignature::CompactLinear => 4,
     }
   }
 
@@ -41,6 +43,7 @@ impl BitSignature {
       1 => BitSignature::ConciseLinear,
       2 => BitSignature::EfficientLinear,
       3 => BitSignature::CompromiseLinear,
+      4 => BitSignature::CompactLinear,
       _ => panic!("Unknown algorithm"),
     }
   }
@@ -51,6 +54,7 @@ impl BitSignature {
       BitSignature::ConciseLinear => 2,
       BitSignature::EfficientLinear => 1,
       BitSignature::CompromiseLinear => 2,
+      BitSignature::CompactLinear => 2,
     }
   }
 
@@ -64,6 +68,7 @@ impl BitSignature {
       BitSignature::ConciseLinear => Re::e_default(),
       BitSignature::EfficientLinear => Re::R_default(),
       BitSignature::CompromiseLinear => Re::R_default(),
+      BitSignature::CompactLinear => Re::Shared,
     }
   }
 }
@@ -104,6 +109,19 @@ where
     res
   }
 
+  fn commitments(
+    generators: (Generators<G0>, Generators<G1>),
+    pow_2: (G0, G1),
+    bits: u8,
+    blinding_key: &(G0::Scalar, G1::Scalar),
+  ) -> (G0, G1) {
+    let mut commitments =
+      ((generators.0.alt * blinding_key.0), (generators.1.alt * blinding_key.1));
+    commitments.0 += pow_2.0 * G0::Scalar::from(bits.into());
+    commitments.1 += pow_2.1 * G1::Scalar::from(bits.into());
+    commitments
+  }
+
   fn shift(pow_2: &mut (G0, G1)) {
     for _ in 0 .. BitSignature::from(SIGNATURE).bits() {
       pow_2.0 = pow_2.0.double();
@@ -120,11 +138,7 @@ where
     mut bits: u8,
     blinding_key: &mut (G0::Scalar, G1::Scalar),
   ) -> Self {
-    let mut commitments =
-      ((generators.0.alt * blinding_key.0), (generators.1.alt * blinding_key.1));
-    commitments.0 += pow_2.0 * G0::Scalar::from(bits.into());
-    commitments.1 += pow_2.1 * G1::Scalar::from(bits.into());
-
+    let commitments = Self::commitments(generators, *pow_2, bits, blinding_key);
     Self::transcript(transcript, i, commitments);
 
     let signature = Aos::prove(
@@ -165,6 +179,67 @@ where
     Ok(())
   }
 
+  // Start proving for these bits with a Borromean ring signature, which can only be completed once
+  // the challenge shared by every ring is known
+  pub(crate) fn prove_borromean<R: RngCore + CryptoRng, T: Clone + Transcript>(
+    rng: &mut R,
+    transcript: &mut T,
+    generators: (Generators<G0>, Generators<G1>),
+    i: usize,
+    pow_2: &mut (G0, G1),
+    mut bits: u8,
+    blinding_key: &mut (G0::Scalar, G1::Scalar),
+  ) -> PendingBits<G0, G1, T, SIGNATURE, RING_LEN> {
+    let commitments = Self::commitments(generators, *pow_2, bits, blinding_key);
+    Self::transcript(transcript, i, commitments);
+
+    let ring = Self::ring(*pow_2, commitments);
+    let actual = usize::from(bits);
+    bits.zeroize();
+    #[allow(non_snake_case)]
+    let (signature, r, R) =
+      Aos::borromean_start(rng, transcript.clone(), generators, &ring, actual);
+
+    let pending = PendingBits {
+      bits: Bits { commitments, signature },
+      transcript: transcript.clone(),
+      ring,
+      actual,
+      r,
+      blinding_key: *blinding_key,
+      R,
+    };
+    blinding_key.0.zeroize();
+    blinding_key.1.zeroize();
+
+    Self::shift(pow_2);
+    pending
+  }
+
+  // Verify these bits' Borromean ring signature, returning the final R of the ring so the shared
+  // challenge can be recalculated
+  pub(crate) fn verify_borromean<T: Clone + Transcript>(
+    &self,
+    transcript: &mut T,
+    generators: (Generators<G0>, Generators<G1>),
+    i: usize,
+    pow_2: &mut (G0, G1),
+    e_0: (G0::Scalar, G1::Scalar),
+  ) -> (G0, G1) {
+    Self::transcript(transcript, i, self.commitments);
+
+    #[allow(non_snake_case)]
+    let R = self.signature.borromean_verify(
+      transcript.clone(),
+      generators,
+      &Self::ring(*pow_2, self.commitments),
+      e_0,
+    );
+
+    Self::shift(pow_2);
+    R
+  }
+
   #[cfg(feature = "serialize")]
   pub(crate) fn write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
     w.write_all(self.commitments.0.to_bytes().as_ref())?;
@@ -180,3 +255,104 @@ where
     })
   }
 }
+
+// Bits whose Borromean ring signature is pending the challenge shared by every ring
+// This holds the ring's secret nonce and blinding key, so it's zeroized on drop
+#[allow(non_snake_case)]
+pub(crate) struct PendingBits<
+  G0: PrimeGroup + Zeroize,
+  G1: PrimeGroup + Zeroize,
+  T: Clone + Transcript,
+  const SIGNATURE: u8,
+  const RING_LEN: usize,
+> where
+  G0::Scalar: Zeroize,
+  G1::Scalar: Zeroize,
+{
+  bits: Bits<G0, G1, SIGNATURE, RING_LEN>,
+  transcript: T,
+  ring: Vec<(G0, G1)>,
+  actual: usize,
+  r: (G0::Scalar, G1::Scalar),
+  blinding_key: (G0::Scalar, G1::Scalar),
+  pub(crate) R: (G0, G1),
+}
+
+impl<
+    G0: PrimeGroup + Zeroize,
+    G1: PrimeGroup + Zeroize,
+    T: Clone + Transcript,
+    const SIGNATURE: u8,
+    const RING_LEN: usize,
+  > Zeroize for PendingBits<G0, G1, T, SIGNATURE, RING_LEN>
+where
+  G0::Scalar: Zeroize,
+  G1::Scalar: Zeroize,
+{
+  fn zeroize(&mut self) {
+    self.actual.zeroize();
+    self.r.0.zeroize();
+    self.r.1.zeroize();
+    self.blinding_key.0.zeroize();
+    self.blinding_key.1.zeroize();
+  }
+}
+impl<
+    G0: PrimeGroup + Zeroize,
+    G1: PrimeGroup + Zeroize,
+    T: Clone + Transcript,
+    const SIGNATURE: u8,
+    const RING_LEN: usize,
+  > Drop for PendingBits<G0, G1, T, SIGNATURE, RING_LEN>
+where
+  G0::Scalar: Zeroize,
+  G1::Scalar: Zeroize,
+{
+  fn drop(&mut self) {
+    self.zeroize();
+  }
+}
+impl<
+    G0: PrimeGroup + Zeroize,
+    G1: PrimeGroup + Zeroize,
+    T: Clone + Transcript,
+    const SIGNATURE: u8,
+    const RING_LEN: usize,
+  > ZeroizeOnDrop for PendingBits<G0, G1, T, SIGNATURE, RING_LEN>
+where
+  G0::Scalar: Zeroize,
+  G1::Scalar: Zeroize,
+{
+}
+
+impl<
+    G0: PrimeGroup + Zeroize,
+    G1: PrimeGroup + Zeroize,
+    T: Clone + Transcript,
+    const SIGNATURE: u8,
+    const RING_LEN: usize,
+  > PendingBits<G0, G1, T, SIGNATURE, RING_LEN>
+where
+  G0::Scalar: PrimeFieldBits + Zeroize,
+  G1::Scalar: PrimeFieldBits + Zeroize,
+{
+  pub(crate) fn finish<R: RngCore + CryptoRng>(
+    mut self,
+    rng: &mut R,
+    generators: (Generators<G0>, Generators<G1>),
+    e_0: (G0::Scalar, G1::Scalar),
+  ) -> Bits<G0, G1, SIGNATURE, RING_LEN> {
+    self.bits.signature.borromean_finish(
+      rng,
+      self.transcript.clone(),
+      generators,
+      &self.ring,
+      self.actual,
+      &mut self.r,
+      &mut self.blinding_key,
+      e_0,
+    );
+    // As this is zeroized on drop, the bits can't be moved out of it
+    self.bits.clone()
+  }
+}
@@ -1,6 +1,8 @@
 use core::ops::{Deref, DerefMut};
 #[cfg(feature = "serialize")]
 use std::io::{self, Read, Write};
+#[cfg(feature = "serialize")]
+use crate::read_scalar;
 
 use thiserror::Error;
 
@@ -28,7 +30,7 @@ use self::schnorr::SchnorrPoK;
 pub(crate) mod aos;
 
 mod bits;
-use bits::{BitSignature, Bits};
+use bits::{BitSignature, Bits, PendingBits};
 
 // Use black_box when possible
 #[rustversion::since(1.66)]
@@ -127,6 +129,8 @@ pub struct __DLEqProof<
 {
   bits: Vec<Bits<G0, G1, SIGNATURE, RING_LEN>>,
   remainder: Option<Bits<G0, G1, SIGNATURE, REMAINDER_RING_LEN>>,
+  // The challenge shared by every ring signature, present when they're Borromean
+  e_0: Option<G0::Scalar>,
   poks: (SchnorrPoK<G0>, SchnorrPoK<G1>),
 }
 
@@ -203,6 +207,22 @@ dleq!(
   true,
 );
 
+// Proves for 2-bits at a time, as ConciseLinear does, yet with Borromean ring signatures which
+// share a single challenge across every ring. This saves an element from every ring, adding just
+// one for the proof as a whole
+dleq!(
+  "
+    A DLEq proof modified from MRL-0010, proving for two bits at a time with Borromean ring
+    signatures to minimize space usage.
+
+    This is the most compact of the provided proofs, yet can't take advantage of batch
+    verification.
+  ",
+  CompactLinearDLEq,
+  BitSignature::CompactLinear,
+  true,
+);
+
 impl<
     G0: PrimeGroup + Zeroize,
     G1: PrimeGroup + Zeroize,
@@ -237,6 +257,19 @@ where
     blinding_key
   }
 
+  #[allow(non_snake_case)]
+  fn borromean_challenge<T: Transcript>(
+    transcript: &mut T,
+    Rs: impl Iterator<Item = (G0, G1)>,
+  ) -> (G0::Scalar, G1::Scalar) {
+    transcript.domain_separate(b"borromean");
+    for R in Rs {
+      transcript.append_message(b"nonce_0", R.0.to_bytes());
+      transcript.append_message(b"nonce_1", R.1.to_bytes());
+    }
+    mutual_scalar_from_bytes(transcript.challenge(b"challenge").as_ref())
+  }
+
   fn reconstruct_keys(&self) -> (G0, G1) {
     let mut res = (
       self.bits.iter().map(|bit| bit.commitments.0).sum::<G0>(),
@@ -284,11 +317,13 @@ where
 
     let capacity = usize::try_from(G0::Scalar::CAPACITY.min(G1::Scalar::CAPACITY)).unwrap();
     let bits_per_group = BitSignature::from(SIGNATURE).bits();
+    let borromean = matches!(BitSignature::from(SIGNATURE), BitSignature::CompactLinear);
 
     let mut pow_2 = (generators.0.primary, generators.1.primary);
 
     let mut raw_bits = f.0.to_le_bits();
     let mut bits = Vec::with_capacity(capacity);
+    let mut pending = vec![];
     let mut these_bits: u8 = 0;
     // Needed to zero out the bits
     #[allow(unused_assignments)]
@@ -305,37 +340,74 @@ where
       if (i % bits_per_group) == (bits_per_group - 1) {
         let last = i == (capacity - 1);
         let mut blinding_key = blinding_key(&mut *rng, last);
-        bits.push(Bits::prove(
+        if borromean {
+          pending.push(Bits::prove_borromean(
+            &mut *rng,
+            transcript,
+            generators,
+            i / bits_per_group,
+            &mut pow_2,
+            these_bits,
+            &mut blinding_key,
+          ));
+        } else {
+          bits.push(Bits::prove(
+            &mut *rng,
+            transcript,
+            generators,
+            i / bits_per_group,
+            &mut pow_2,
+            these_bits,
+            &mut blinding_key,
+          ));
+        }
+        these_bits.zeroize();
+      }
+    }
+    debug_assert_eq!(bits.len() + pending.len(), capacity / bits_per_group);
+
+    let mut remainder = None;
+    let mut pending_remainder: Option<PendingBits<_, _, _, SIGNATURE, REMAINDER_RING_LEN>> = None;
+    if capacity != ((capacity / bits_per_group) * bits_per_group) {
+      let mut blinding_key = blinding_key(&mut *rng, true);
+      if borromean {
+        pending_remainder = Some(Bits::prove_borromean(
           &mut *rng,
           transcript,
           generators,
-          i / bits_per_group,
+          capacity / bits_per_group,
+          &mut pow_2,
+          these_bits,
+          &mut blinding_key,
+        ));
+      } else {
+        remainder = Some(Bits::prove(
+          &mut *rng,
+          transcript,
+          generators,
+          capacity / bits_per_group,
           &mut pow_2,
           these_bits,
           &mut blinding_key,
         ));
-        these_bits.zeroize();
       }
     }
-    debug_assert_eq!(bits.len(), capacity / bits_per_group);
 
-    let mut remainder = None;
-    if capacity != ((capacity / bits_per_group) * bits_per_group) {
-      let mut blinding_key = blinding_key(&mut *rng, true);
-      remainder = Some(Bits::prove(
-        &mut *rng,
+    these_bits.zeroize();
+
+    // With every ring started, the shared challenge can be calculated and the rings completed
+    let mut e_0 = None;
+    if borromean {
+      let e = Self::borromean_challenge(
         transcript,
-        generators,
-        capacity / bits_per_group,
-        &mut pow_2,
-        these_bits,
-        &mut blinding_key,
-      ));
+        pending.iter().map(|bits| bits.R).chain(pending_remainder.iter().map(|bits| bits.R)),
+      );
+      bits = pending.drain(..).map(|bits| bits.finish(&mut *rng, generators, e)).collect();
+      remainder = pending_remainder.map(|bits| bits.finish(&mut *rng, generators, e));
+      e_0 = Some(e.0);
     }
 
-    these_bits.zeroize();
-
-    let proof = __DLEqProof { bits, remainder, poks };
+    let proof = __DLEqProof { bits, remainder, e_0, poks };
     debug_assert_eq!(
       proof.reconstruct_keys(),
       (generators.0.primary * f.0.deref(), generators.1.primary * f.1.deref())
@@ -398,12 +470,14 @@ where
     let capacity = usize::try_from(G0::Scalar::CAPACITY.min(G1::Scalar::CAPACITY)).unwrap();
     let bits_per_group = BitSignature::from(SIGNATURE).bits();
     let has_remainder = (capacity % bits_per_group) != 0;
+    let borromean = matches!(BitSignature::from(SIGNATURE), BitSignature::CompactLinear);
 
     // These shouldn't be possible, as locally created and deserialized proofs should be properly
     // formed in these regards, yet it doesn't hurt to check and would be problematic if true
     if (self.bits.len() != (capacity / bits_per_group)) ||
       ((self.remainder.is_none() && has_remainder) ||
-        (self.remainder.is_some() && !has_remainder))
+        (self.remainder.is_some() && !has_remainder)) ||
+      (self.e_0.is_some() != borromean)
     {
       return Err(DLEqError::InvalidProofLength);
     }
@@ -416,6 +490,7 @@ where
       BitSignature::ConciseLinear => 3,
       BitSignature::EfficientLinear => (self.bits.len() + 1) * 3,
       BitSignature::CompromiseLinear => (self.bits.len() + 1) * 3,
+      BitSignature::CompactLinear => 3,
     };
     let mut batch = (BatchVerifier::new(batch_capacity), BatchVerifier::new(batch_capacity));
 
@@ -423,11 +498,28 @@ where
     self.poks.1.verify(&mut *rng, transcript, generators.1.primary, keys.1, &mut batch.1);
 
     let mut pow_2 = (generators.0.primary, generators.1.primary);
-    for (i, bits) in self.bits.iter().enumerate() {
-      bits.verify(&mut *rng, transcript, generators, &mut batch, i, &mut pow_2)?;
-    }
-    if let Some(bit) = &self.remainder {
-      bit.verify(&mut *rng, transcript, generators, &mut batch, self.bits.len(), &mut pow_2)?;
+    if let Some(e_0) = self.e_0 {
+      let e_0 = (e_0, scalar_convert(e_0).ok_or(DLEqError::InvalidChallenge)?);
+
+      #[allow(non_snake_case)]
+      let mut Rs = Vec::with_capacity(self.bits.len() + 1);
+      for (i, bits) in self.bits.iter().enumerate() {
+        Rs.push(bits.verify_borromean(transcript, generators, i, &mut pow_2, e_0));
+      }
+      if let Some(bit) = &self.remainder {
+        Rs.push(bit.verify_borromean(transcript, generators, self.bits.len(), &mut pow_2, e_0));
+      }
+
+      if Self::borromean_challenge(transcript, Rs.into_iter()).0 != e_0.0 {
+        Err(DLEqError::InvalidProof)?;
+      }
+    } else {
+      for (i, bits) in self.bits.iter().enumerate() {
+        bits.verify(&mut *rng, transcript, generators, &mut batch, i, &mut pow_2)?;
+      }
+      if let Some(bit) = &self.remainder {
+        bit.verify(&mut *rng, transcript, generators, &mut batch, self.bits.len(), &mut pow_2)?;
+      }
     }
 
     if (!batch.0.verify_vartime()) || (!batch.1.verify_vartime()) {
@@ -440,6 +532,9 @@ where
   /// Write a Cross-Group Discrete Log Equality proof to a type satisfying std::io::Write.
   #[cfg(feature = "serialize")]
   pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    if let Some(e_0) = self.e_0 {
+      w.write_all(e_0.to_repr().as_ref())?;
+    }
     for bit in &self.bits {
       bit.write(w)?;
     }
@@ -456,6 +551,11 @@ where
     let capacity = usize::try_from(G0::Scalar::CAPACITY.min(G1::Scalar::CAPACITY)).unwrap();
     let bits_per_group = BitSignature::from(SIGNATURE).bits();
 
+    let mut e_0 = None;
+    if matches!(BitSignature::from(SIGNATURE), BitSignature::CompactLinear) {
+      e_0 = Some(read_scalar(r)?);
+    }
+
     let mut bits = Vec::with_capacity(capacity / bits_per_group);
     for _ in 0 .. (capacity / bits_per_group) {
       bits.push(Bits::read(r)?);
@@ -466,6 +566,6 @@ where
       remainder = Some(Bits::read(r)?);
     }
 
-    Ok(__DLEqProof { bits, remainder, poks: (SchnorrPoK::read(r)?, SchnorrPoK::read(r)?) })
+    Ok(__DLEqProof { bits, remainder, e_0, poks: (SchnorrPoK::read(r)?, SchnorrPoK::read(r)?) })
   }
 }
@@ -3,6 +3,10 @@
 #![doc = include_str!("../README.md")]
 
 use core::ops::Deref;
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+use std_shims::vec::Vec;
 
 use rand_core::{RngCore, CryptoRng};
 
@@ -14,7 +18,7 @@ use ff::{Field, PrimeField};
 use group::prime::PrimeGroup;
 
 #[cfg(feature = "serialize")]
-use std::io::{self, ErrorKind, Error, Read, Write};
+use std_shims::io::{self, ErrorKind, Error, Read, Write};
 
 /// A cross-group DLEq proof capable of proving that two public keys, across two different curves,
 /// share a private key.
@@ -211,7 +215,6 @@ where
 ///
 /// This is effectively n distinct DLEq proofs, one for each discrete logarithm and its points
 /// across some generators, yet with a smaller overall proof size.
-#[cfg(feature = "std")]
 #[derive(Clone, PartialEq, Eq, Debug, Zeroize)]
 pub struct MultiDLEqProof<G: PrimeGroup>
 where
@@ -221,7 +224,6 @@ where
   s: Vec<G::Scalar>,
 }
 
-#[cfg(feature = "std")]
 #[allow(non_snake_case)]
 impl<G: PrimeGroup> MultiDLEqProof<G>
 where
@@ -18,7 +18,7 @@ use transcript::{Transcript, RecommendedTranscript};
 use crate::{
   cross_group::{
     scalar::mutual_scalar_from_bytes, Generators, ClassicLinearDLEq, EfficientLinearDLEq,
-    ConciseLinearDLEq, CompromiseLinearDLEq,
+    ConciseLinearDLEq, CompromiseLinearDLEq, CompactLinearDLEq,
   },
 };
 
@@ -151,6 +151,7 @@ test_dleq!(
   test_compromise_linear,
   CompromiseLinearDLEq
 );
+test_dleq!("CompactLinear", benchmark_compact_linear, test_compact_linear, CompactLinearDLEq);
 
 #[test]
 fn test_rejection_sampling() {
@@ -1,5 +1,8 @@
 use core::{marker::PhantomData, fmt::Debug};
-use std::io::{self, Read, Write};
+use std_shims::{
+  vec::Vec,
+  io::{self, Read, Write},
+};
 
 use zeroize::Zeroizing;
 use rand_core::{RngCore, CryptoRng};
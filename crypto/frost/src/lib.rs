@@ -1,9 +1,14 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 use core::fmt::Debug;
-use std::collections::HashMap;
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+use std_shims::collections::HashMap;
 
+#[cfg(feature = "std")]
 use thiserror::Error;
 
 /// Distributed key generation protocol.
@@ -24,25 +29,29 @@ pub mod sign;
 pub mod tests;
 
 /// Various errors possible during signing.
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Error)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Error))]
 pub enum FrostError {
-  #[error("internal error: {0}")]
+  #[cfg_attr(feature = "std", error("internal error: {0}"))]
   InternalError(&'static str),
 
-  #[error("invalid participant (0 < participant <= {0}, yet participant is {1})")]
+  #[cfg_attr(
+    feature = "std",
+    error("invalid participant (0 < participant <= {0}, yet participant is {1})")
+  )]
   InvalidParticipant(u16, Participant),
-  #[error("invalid signing set ({0})")]
+  #[cfg_attr(feature = "std", error("invalid signing set ({0})"))]
   InvalidSigningSet(&'static str),
-  #[error("invalid participant quantity (expected {0}, got {1})")]
+  #[cfg_attr(feature = "std", error("invalid participant quantity (expected {0}, got {1})"))]
   InvalidParticipantQuantity(usize, usize),
-  #[error("duplicated participant ({0})")]
+  #[cfg_attr(feature = "std", error("duplicated participant ({0})"))]
   DuplicatedParticipant(Participant),
-  #[error("missing participant {0}")]
+  #[cfg_attr(feature = "std", error("missing participant {0}"))]
   MissingParticipant(Participant),
 
-  #[error("invalid preprocess (participant {0})")]
+  #[cfg_attr(feature = "std", error("invalid preprocess (participant {0})"))]
   InvalidPreprocess(Participant),
-  #[error("invalid share (participant {0})")]
+  #[cfg_attr(feature = "std", error("invalid share (participant {0})"))]
   InvalidShare(Participant),
 }
 
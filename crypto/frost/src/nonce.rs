@@ -10,14 +10,15 @@
 // confirm their integrity
 
 use core::ops::Deref;
-use std::{
+use std_shims::{
+  vec::Vec,
   io::{self, Read, Write},
   collections::HashMap,
 };
 
 use rand_core::{RngCore, CryptoRng};
 
-use zeroize::{Zeroize, Zeroizing};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use transcript::Transcript;
 
@@ -54,6 +55,9 @@ fn dleq_transcript<T: Transcript>(context: &[u8]) -> T {
 // This is considered a single nonce as r = d + be
 #[derive(Clone, Zeroize)]
 pub(crate) struct Nonce<C: Curve>(pub(crate) [Zeroizing<C::F>; 2]);
+// Both nonces are within Zeroizing, so this doesn't need its own Drop implementation (which would
+// prevent destructuring it)
+impl<C: Curve> ZeroizeOnDrop for Nonce<C> {}
 
 // Commitments to a specific generator for this binomial nonce
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -158,10 +162,10 @@ impl<C: Curve> Commitments<C> {
 
       if generators.len() > 1 {
         dleq_generators.push(generators.clone());
-        dleq_nonces.push(Zeroizing::new(
-          (these_commitments.aggregation_factor::<T>(context) * nonce.0[1].deref()) +
-            nonce.0[0].deref(),
-        ));
+        let mut dleq_nonce = Zeroizing::new(these_commitments.aggregation_factor::<T>(context));
+        *dleq_nonce *= nonce.0[1].deref();
+        *dleq_nonce += nonce.0[0].deref();
+        dleq_nonces.push(dleq_nonce);
       }
 
       nonces.push(nonce);
@@ -1,5 +1,6 @@
 use core::{ops::Deref, fmt::Debug};
-use std::{
+use std_shims::{
+  vec::Vec,
   io::{self, Read, Write},
   collections::HashMap,
 };
@@ -7,15 +8,15 @@ use std::{
 use rand_core::{RngCore, CryptoRng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 
-use zeroize::{Zeroize, Zeroizing};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use transcript::Transcript;
 
 use ciphersuite::group::{
   ff::{Field, PrimeField},
-  GroupEncoding,
+  Group, GroupEncoding,
 };
-use multiexp::BatchVerifier;
+use multiexp::{multiexp_vartime, BatchVerifier};
 
 use crate::{
   curve::Curve,
@@ -153,6 +154,31 @@ impl<C: Curve, A: Algorithm<C>> AlgorithmMachine<C, A> {
     )
   }
 
+  /// Perform the preprocessing round for several future signing sessions at once.
+  ///
+  /// Each returned preprocess may be broadcast immediately, leaving only the exchange of signature
+  /// shares once the message to sign is known. A signing session is resumed by passing its cached
+  /// preprocess to `AlgorithmSignMachine::from_cache`, with an algorithm in the same state as the
+  /// one used here.
+  ///
+  /// Each cached preprocess MUST only be used once, and MUST be handled with the same security as
+  /// your private key share. See `CachedPreprocess` for more details.
+  pub fn preprocess_batch<R: RngCore + CryptoRng>(
+    self,
+    rng: &mut R,
+    sessions: usize,
+  ) -> Vec<(CachedPreprocess, Preprocess<C, A::Addendum>)> {
+    (0 .. sessions)
+      .map(|_| {
+        let mut seed = CachedPreprocess(Zeroizing::new([0; 32]));
+        rng.fill_bytes(seed.0.as_mut());
+        let (machine, preprocess) =
+          AlgorithmMachine { params: self.params.clone() }.seeded_preprocess(seed);
+        (machine.cache(), preprocess)
+      })
+      .collect()
+  }
+
   #[cfg(any(test, feature = "tests"))]
   pub(crate) fn unsafe_override_preprocess(
     mut self,
@@ -262,6 +288,13 @@ pub struct AlgorithmSignMachine<C: Curve, A: Algorithm<C>> {
   pub(crate) blame_entropy: [u8; 32],
 }
 
+impl<C: Curve, A: Algorithm<C>> Drop for AlgorithmSignMachine<C, A> {
+  fn drop(&mut self) {
+    self.zeroize();
+  }
+}
+impl<C: Curve, A: Algorithm<C>> ZeroizeOnDrop for AlgorithmSignMachine<C, A> {}
+
 impl<C: Curve, A: Algorithm<C>> SignMachine<A::Signature> for AlgorithmSignMachine<C, A> {
   type Params = A;
   type Keys = ThresholdKeys<C>;
@@ -270,7 +303,7 @@ impl<C: Curve, A: Algorithm<C>> SignMachine<A::Signature> for AlgorithmSignMachi
   type SignatureMachine = AlgorithmSignatureMachine<C, A>;
 
   fn cache(self) -> CachedPreprocess {
-    self.seed
+    CachedPreprocess(self.seed.0.clone())
   }
 
   fn from_cache(
@@ -417,6 +450,9 @@ impl<C: Curve, A: Algorithm<C>> SignMachine<A::Signature> for AlgorithmSignMachi
 
     let share = self.params.algorithm.sign_share(&view, &Rs, nonces, msg);
 
+    // Bind any statements of blame to this signing session, without advancing the transcript
+    let context = self.params.algorithm.transcript().clone().challenge(b"blame").as_ref().to_vec();
+
     Ok((
       AlgorithmSignatureMachine {
         params: self.params.clone(),
@@ -424,6 +460,7 @@ impl<C: Curve, A: Algorithm<C>> SignMachine<A::Signature> for AlgorithmSignMachi
         B,
         Rs,
         share,
+        context,
         blame_entropy: self.blame_entropy,
       },
       SignatureShare(share),
@@ -454,22 +491,110 @@ pub struct AlgorithmSignatureMachine<C: Curve, A: Algorithm<C>> {
   B: BindingFactor<C>,
   Rs: Vec<Vec<C::G>>,
   share: C::F,
+  context: Vec<u8>,
   blame_entropy: [u8; 32],
 }
 
-impl<C: Curve, A: Algorithm<C>> SignatureMachine<A::Signature> for AlgorithmSignatureMachine<C, A> {
-  type SignatureShare = SignatureShare<C>;
+// The share itself is broadcast, yet it's zeroized in case this machine is dropped before then
+impl<C: Curve, A: Algorithm<C>> Zeroize for AlgorithmSignatureMachine<C, A> {
+  fn zeroize(&mut self) {
+    self.params.zeroize();
+    self.view.zeroize();
+    self.share.zeroize();
+    self.blame_entropy.zeroize();
+  }
+}
+impl<C: Curve, A: Algorithm<C>> Drop for AlgorithmSignatureMachine<C, A> {
+  fn drop(&mut self) {
+    self.zeroize();
+  }
+}
+impl<C: Curve, A: Algorithm<C>> ZeroizeOnDrop for AlgorithmSignatureMachine<C, A> {}
 
-  fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<SignatureShare<C>> {
-    Ok(SignatureShare(C::read_F(reader)?))
+/// Statement blaming a participant for producing an invalid signature share.
+///
+/// This contains the offending share and is bound to the signing session it was produced in.
+///
+/// Signature shares aren't signed, so this doesn't prove the blamed participant produced the share.
+/// It's only meaningful when shares are sent over an authenticated broadcast channel, where every
+/// participant receives the same share from the blamed participant. Another participant in the
+/// session can then verify it with `AlgorithmSignatureMachine::verify_blame`, which compares it to
+/// the share they received. Without such a channel, a faulty participant could blame anyone with a
+/// share of their own creation.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ShareBlame<C: Curve> {
+  participant: Participant,
+  context: Vec<u8>,
+  share: SignatureShare<C>,
+}
+
+impl<C: Curve> ShareBlame<C> {
+  /// The participant blamed.
+  pub fn participant(&self) -> Participant {
+    self.participant
   }
+}
 
-  fn complete(
+impl<C: Curve> Writable for ShareBlame<C> {
+  fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&self.participant.to_bytes())?;
+    writer.write_all(&self.context)?;
+    self.share.write(writer)
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> AlgorithmSignatureMachine<C, A> {
+  // Returns None if the share was rejected outright, without producing statements to verify
+  fn share_statements(&self, l: Participant, share: C::F) -> Option<Vec<(C::F, C::G)>> {
+    self
+      .params
+      .algorithm
+      .verify_share(self.view.verification_share(l), &self.B.bound(l), share)
+      .ok()
+  }
+
+  /// Read a statement of blame produced within this signing session.
+  pub fn read_blame<R: Read>(&self, reader: &mut R) -> io::Result<ShareBlame<C>> {
+    let mut participant = [0; 2];
+    reader.read_exact(&mut participant)?;
+    let participant = Participant::new(u16::from_le_bytes(participant))
+      .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid participant"))?;
+
+    let mut context = vec![0; self.context.len()];
+    reader.read_exact(&mut context)?;
+
+    Ok(ShareBlame { participant, context, share: self.read_share(reader)? })
+  }
+
+  /// Verify a statement of blame, returning true if the blamed participant is faulty.
+  ///
+  /// `received` is the share we received from the blamed participant, over the authenticated
+  /// broadcast channel shares are sent over. This will return false if the blamed share differs
+  /// from it, or for statements produced within other signing sessions.
+  pub fn verify_blame(&self, blame: &ShareBlame<C>, received: &SignatureShare<C>) -> bool {
+    if (blame.context != self.context) ||
+      (!self.view.included().contains(&blame.participant)) ||
+      (&blame.share != received)
+    {
+      return false;
+    }
+    self
+      .share_statements(blame.participant, blame.share.0)
+      .map(|statements| !bool::from(multiexp_vartime(&statements).is_identity()))
+      .unwrap_or(true)
+  }
+
+  /// Complete signing.
+  ///
+  /// If a participant's signature share was invalid, this will additionally return a statement of
+  /// blame against them. See `ShareBlame` for when it can be relied upon.
+  #[allow(clippy::type_complexity)]
+  pub fn complete_with_blame(
     self,
     mut shares: HashMap<Participant, SignatureShare<C>>,
-  ) -> Result<A::Signature, FrostError> {
+  ) -> Result<A::Signature, (FrostError, Option<ShareBlame<C>>)> {
     let params = self.params.multisig_params();
-    validate_map(&shares, self.view.included(), params.i())?;
+    validate_map(&shares, self.view.included(), params.i()).map_err(|e| (e, None))?;
 
     let mut responses = HashMap::new();
     responses.insert(params.i(), self.share);
@@ -486,6 +611,17 @@ impl<C: Curve, A: Algorithm<C>> SignatureMachine<A::Signature> for AlgorithmSign
       return Ok(sig);
     }
 
+    let blame = |l: Participant| {
+      (
+        FrostError::InvalidShare(l),
+        Some(ShareBlame {
+          participant: l,
+          context: self.context.clone(),
+          share: SignatureShare(responses[&l]),
+        }),
+      )
+    };
+
     // We could remove blame_entropy by taking in an RNG here
     // Considering we don't need any RNG for a valid signature, and we only use the RNG here for
     // performance reasons, it doesn't feel worthwhile to include as an argument to every
@@ -493,24 +629,38 @@ impl<C: Curve, A: Algorithm<C>> SignatureMachine<A::Signature> for AlgorithmSign
     let mut rng = ChaCha20Rng::from_seed(self.blame_entropy);
     let mut batch = BatchVerifier::new(self.view.included().len());
     for l in self.view.included() {
-      if let Ok(statements) = self.params.algorithm.verify_share(
-        self.view.verification_share(*l),
-        &self.B.bound(*l),
-        responses[l],
-      ) {
+      if let Some(statements) = self.share_statements(*l, responses[l]) {
         batch.queue(&mut rng, *l, statements);
       } else {
-        Err(FrostError::InvalidShare(*l))?;
+        Err(blame(*l))?;
       }
     }
 
     if let Err(l) = batch.verify_vartime_with_vartime_blame() {
-      Err(FrostError::InvalidShare(l))?;
+      Err(blame(l))?;
     }
 
     // If everyone has a valid share, and there were enough participants, this should've worked
     // The only known way to cause this, for valid parameters/algorithms, is to deserialize a
     // semantically invalid FrostKeys
-    Err(FrostError::InternalError("everyone had a valid share yet the signature was still invalid"))
+    Err((
+      FrostError::InternalError("everyone had a valid share yet the signature was still invalid"),
+      None,
+    ))
+  }
+}
+
+impl<C: Curve, A: Algorithm<C>> SignatureMachine<A::Signature> for AlgorithmSignatureMachine<C, A> {
+  type SignatureShare = SignatureShare<C>;
+
+  fn read_share<R: Read>(&self, reader: &mut R) -> io::Result<SignatureShare<C>> {
+    Ok(SignatureShare(C::read_F(reader)?))
+  }
+
+  fn complete(
+    self,
+    shares: HashMap<Participant, SignatureShare<C>>,
+  ) -> Result<A::Signature, FrostError> {
+    self.complete_with_blame(shares).map_err(|(e, _)| e)
   }
 }
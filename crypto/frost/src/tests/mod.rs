@@ -2,12 +2,17 @@ use std::collections::HashMap;
 
 use rand_core::{RngCore, CryptoRng};
 
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
 pub use dkg::tests::{key_gen, musig_key_gen, recover_key};
 
 use crate::{
-  Curve, Participant, ThresholdKeys, FrostError,
+  Curve, Participant, ThresholdKeys, ThresholdView, FrostError,
   algorithm::{Algorithm, Hram, IetfSchnorr},
-  sign::{Writable, PreprocessMachine, SignMachine, SignatureMachine, AlgorithmMachine},
+  sign::{
+    Writable, PreprocessMachine, SignMachine, SignatureMachine, AlgorithmMachine,
+    AlgorithmSignMachine, AlgorithmSignatureMachine,
+  },
 };
 
 /// Tests for the nonce handling code.
@@ -246,6 +251,10 @@ pub fn test_schnorr_blame<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mu
 
   let (mut machines, shares) = preprocess_and_shares(&mut *rng, machines, |_, _| {}, MSG);
 
+  // Set aside a participant to verify the blame produced by everyone else
+  let verifier = *machines.keys().next().unwrap();
+  let verifier = machines.remove(&verifier).unwrap();
+
   for (i, machine) in machines.drain() {
     let mut shares = clone_without(&shares, &i);
 
@@ -253,10 +262,86 @@ pub fn test_schnorr_blame<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mu
     let participants = shares.keys().collect::<Vec<_>>();
     let faulty = *participants
       [usize::try_from(rng.next_u64() % u64::try_from(participants.len()).unwrap()).unwrap()];
+    let valid = shares[&faulty].clone();
     shares.get_mut(&faulty).unwrap().invalidate();
+    let invalid = shares[&faulty].clone();
+
+    let (err, blame) = machine.complete_with_blame(shares).unwrap_err();
+    assert_eq!(err, FrostError::InvalidShare(faulty));
+    let blame = blame.unwrap();
+    assert_eq!(blame.participant(), faulty);
+
+    let mut blame = blame.serialize();
+    let read_blame = |blame: &[u8]| verifier.read_blame::<&[u8]>(&mut blame.as_ref()).unwrap();
+    // If the faulty participant broadcast the invalid share, the blame should verify
+    assert!(verifier.verify_blame(&read_blame(&blame), &invalid));
+
+    // If the faulty participant broadcast a valid share, the share in the blame was forged
+    assert!(!verifier.verify_blame(&read_blame(&blame), &valid));
+
+    // Blame for a valid share shouldn't verify
+    let valid_bytes = valid.serialize();
+    let start = blame.len() - valid_bytes.len();
+    blame[start ..].copy_from_slice(&valid_bytes);
+    assert!(!verifier.verify_blame(&read_blame(&blame), &valid));
+  }
+}
+
+/// Test signing several messages with preprocesses generated ahead of time.
+pub fn test_schnorr_preprocess_batch<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mut R) {
+  const SESSIONS: usize = 3;
+
+  let keys = key_gen(&mut *rng);
+  let group_key = keys[&Participant::new(1).unwrap()].group_key();
 
-    assert_eq!(machine.complete(shares).err(), Some(FrostError::InvalidShare(faulty)));
+  let mut caches = HashMap::new();
+  let mut preprocesses = HashMap::new();
+  for (i, machine) in algorithm_machines(&mut *rng, IetfSchnorr::<C, H>::ietf(), &keys) {
+    let (these_caches, these_preprocesses): (Vec<_>, Vec<_>) =
+      machine.preprocess_batch(&mut *rng, SESSIONS).into_iter().unzip();
+    caches.insert(i, these_caches);
+    preprocesses.insert(i, these_preprocesses.iter().map(Writable::serialize).collect::<Vec<_>>());
   }
+
+  for session in (0 .. SESSIONS).rev() {
+    let msg = [u8::try_from(session).unwrap(); 32];
+
+    // Resume each signing session from its cache and sign in a single round
+    let mut machines = HashMap::new();
+    let mut shares = HashMap::new();
+    for (i, caches) in &mut caches {
+      let machine = AlgorithmSignMachine::from_cache(
+        IetfSchnorr::<C, H>::ietf(),
+        keys[i].clone(),
+        caches.pop().unwrap(),
+      )
+      .unwrap();
+      let these_preprocesses = preprocesses
+        .iter()
+        .filter(|(l, _)| *l != i)
+        .map(|(l, preprocesses)| {
+          (*l, machine.read_preprocess::<&[u8]>(&mut preprocesses[session].as_ref()).unwrap())
+        })
+        .collect();
+      let (machine, share) = machine.sign(these_preprocesses, &msg).unwrap();
+      machines.insert(*i, machine);
+      shares.insert(*i, share);
+    }
+
+    for (i, machine) in machines {
+      let sig = machine.complete(clone_without(&shares, &i)).unwrap();
+      assert!(sig.verify(group_key, H::hram(&sig.R, &group_key, &msg)));
+    }
+  }
+}
+
+/// Test the types holding secrets during signing zeroize them when dropped.
+pub fn test_zeroize<C: Curve, H: Hram<C>>() {
+  fn zeroize_on_drop<Z: Zeroize + ZeroizeOnDrop>() {}
+  zeroize_on_drop::<crate::nonce::Nonce<C>>();
+  zeroize_on_drop::<ThresholdView<C>>();
+  zeroize_on_drop::<AlgorithmSignMachine<C, IetfSchnorr<C, H>>>();
+  zeroize_on_drop::<AlgorithmSignatureMachine<C, IetfSchnorr<C, H>>>();
 }
 
 /// Run a variety of tests against a ciphersuite.
@@ -265,6 +350,8 @@ pub fn test_ciphersuite<R: RngCore + CryptoRng, C: Curve, H: Hram<C>>(rng: &mut
   test_musig_schnorr::<R, C, H>(rng);
   test_offset_schnorr::<R, C, H>(rng);
   test_schnorr_blame::<R, C, H>(rng);
+  test_schnorr_preprocess_batch::<R, C, H>(rng);
+  test_zeroize::<C, H>();
 
   test_multi_nonce::<R, C>(rng);
   test_invalid_commitment::<R, C>(rng);
@@ -3,6 +3,8 @@ use std_shims::{
   io::{self, Read, Write},
 };
 
+use rand_core::{RngCore, CryptoRng};
+
 use zeroize::Zeroize;
 
 use transcript::{Transcript, SecureDigest, DigestTranscript};
@@ -14,7 +16,7 @@ use ciphersuite::{
   },
   Ciphersuite,
 };
-use multiexp::multiexp_vartime;
+use multiexp::{multiexp_vartime, BatchVerifier};
 
 use crate::SchnorrSignature;
 
@@ -116,18 +118,15 @@ impl<C: Ciphersuite> SchnorrAggregate<C> {
     self.Rs.as_slice()
   }
 
-  /// Perform signature verification.
-  ///
-  /// Challenges must be properly crafted, which means being binding to the public key, nonce, and
-  /// any message. Failure to do so will let a malicious adversary to forge signatures for
-  /// different keys/messages.
-  ///
-  /// The DST used here must prevent a collision with whatever hash function produced the
-  /// challenges.
-  #[must_use]
-  pub fn verify(&self, dst: &'static [u8], keys_and_challenges: &[(C::G, C::F)]) -> bool {
+  // Return the series of pairs whose products sum to zero for a valid aggregate, or None if the
+  // amount of keys and challenges doesn't match the amount of aggregated signatures
+  fn batch_statements(
+    &self,
+    dst: &'static [u8],
+    keys_and_challenges: &[(C::G, C::F)],
+  ) -> Option<Vec<(C::F, C::G)>> {
     if self.Rs.len() != keys_and_challenges.len() {
-      return false;
+      None?;
     }
 
     let mut digest = DigestTranscript::<C::H>::new(dst);
@@ -143,8 +142,48 @@ impl<C: Ciphersuite> SchnorrAggregate<C> {
       pairs.push((z * challenge, *key));
     }
     pairs.push((-self.s, C::generator()));
+    Some(pairs)
+  }
+
+  /// Perform signature verification.
+  ///
+  /// Challenges must be properly crafted, which means being binding to the public key, nonce, and
+  /// any message. Failure to do so will let a malicious adversary to forge signatures for
+  /// different keys/messages.
+  ///
+  /// The DST used here must prevent a collision with whatever hash function produced the
+  /// challenges.
+  #[must_use]
+  pub fn verify(&self, dst: &'static [u8], keys_and_challenges: &[(C::G, C::F)]) -> bool {
+    let Some(pairs) = self.batch_statements(dst, keys_and_challenges) else { return false };
     multiexp_vartime(&pairs).is_identity().into()
   }
+
+  /// Queue an aggregate for batch verification, enabling verifying several aggregates (and
+  /// signatures) with a single multiexp.
+  ///
+  /// Returns false, without queueing anything, if the amount of keys and challenges doesn't match
+  /// the amount of aggregated signatures.
+  ///
+  /// Challenges must be properly crafted, which means being binding to the public key, nonce, and
+  /// any message. Failure to do so will let a malicious adversary to forge signatures for
+  /// different keys/messages.
+  ///
+  /// The DST used here must prevent a collision with whatever hash function produced the
+  /// challenges.
+  #[must_use]
+  pub fn batch_verify<R: RngCore + CryptoRng, I: Copy + Zeroize>(
+    &self,
+    rng: &mut R,
+    batch: &mut BatchVerifier<I, C::G>,
+    id: I,
+    dst: &'static [u8],
+    keys_and_challenges: &[(C::G, C::F)],
+  ) -> bool {
+    let Some(pairs) = self.batch_statements(dst, keys_and_challenges) else { return false };
+    batch.queue(rng, id, pairs);
+    true
+  }
 }
 
 /// A signature aggregator capable of consuming signatures in order to produce an aggregate.
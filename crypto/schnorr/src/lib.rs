@@ -74,11 +74,13 @@ impl<C: Ciphersuite> SchnorrSignature<C> {
     nonce: Zeroizing<C::F>,
     challenge: C::F,
   ) -> SchnorrSignature<C> {
-    SchnorrSignature {
-      // Uses deref instead of * as * returns C::F yet deref returns &C::F, preventing a copy
-      R: C::generator() * nonce.deref(),
-      s: (challenge * private_key.deref()) + nonce.deref(),
-    }
+    // Uses deref instead of * as * returns C::F yet deref returns &C::F, preventing a copy
+    // Also calculates s within Zeroizing so the private key multiplied by the challenge, which
+    // would reveal the private key, isn't left in memory
+    let mut s = Zeroizing::new(challenge);
+    *s *= private_key.deref();
+    *s += nonce.deref();
+    SchnorrSignature { R: C::generator() * nonce.deref(), s: *s }
   }
 
   /// Return the series of pairs whose products sum to zero for a valid signature.
@@ -77,38 +77,87 @@ pub(crate) fn batch_verify<C: Ciphersuite>() {
   }
 }
 
-pub(crate) fn aggregate<C: Ciphersuite>() {
-  const DST: &[u8] = b"Schnorr Aggregator Test";
+const AGGREGATE_DST: &[u8] = b"Schnorr Aggregator Test";
 
-  // Create 5 signatures
-  let mut keys = vec![];
-  let mut challenges = vec![];
-  let mut aggregator = SchnorrAggregator::<C>::new(DST);
-  for i in 0 .. 5 {
-    keys.push(Zeroizing::new(C::random_nonzero_F(&mut OsRng)));
+// Create an aggregate of 5 signatures, returning it with the keys and challenges it's for
+#[allow(clippy::type_complexity)]
+fn aggregate_signatures<C: Ciphersuite>() -> (SchnorrAggregate<C>, Vec<(C::G, C::F)>) {
+  let mut keys_and_challenges = vec![];
+  let mut aggregator = SchnorrAggregator::<C>::new(AGGREGATE_DST);
+  for _ in 0 .. 5 {
+    let key = Zeroizing::new(C::random_nonzero_F(&mut OsRng));
     // In practice, this MUST be a secure challenge binding to the nonce, key, and any message
-    challenges.push(C::random_nonzero_F(&mut OsRng));
+    let challenge = C::random_nonzero_F(&mut OsRng);
     aggregator.aggregate(
-      challenges[i],
-      SchnorrSignature::<C>::sign(
-        &keys[i],
-        Zeroizing::new(C::random_nonzero_F(&mut OsRng)),
-        challenges[i],
-      ),
+      challenge,
+      SchnorrSignature::<C>::sign(&key, Zeroizing::new(C::random_nonzero_F(&mut OsRng)), challenge),
     );
+    keys_and_challenges.push((C::generator() * key.deref(), challenge));
   }
 
   let aggregate = aggregator.complete().unwrap();
   let aggregate =
     SchnorrAggregate::<C>::read::<&[u8]>(&mut aggregate.serialize().as_ref()).unwrap();
-  assert!(aggregate.verify(
-    DST,
-    keys
-      .iter()
-      .map(|key| C::generator() * key.deref())
-      .zip(challenges.iter().copied())
-      .collect::<Vec<_>>()
-      .as_ref(),
+  (aggregate, keys_and_challenges)
+}
+
+pub(crate) fn aggregate<C: Ciphersuite>() {
+  let (aggregate, keys_and_challenges) = aggregate_signatures::<C>();
+  assert!(aggregate.verify(AGGREGATE_DST, &keys_and_challenges));
+
+  // The aggregate shouldn't verify for a subset of the signatures
+  assert!(!aggregate.verify(AGGREGATE_DST, &keys_and_challenges[1 ..]));
+
+  // Nor with the challenges reordered
+  let mut reordered = keys_and_challenges.clone();
+  let first_challenge = reordered[0].1;
+  reordered[0].1 = reordered[1].1;
+  reordered[1].1 = first_challenge;
+  assert!(!aggregate.verify(AGGREGATE_DST, &reordered));
+}
+
+pub(crate) fn batch_verify_aggregates<C: Ciphersuite>() {
+  let aggregates = (0 .. 3).map(|_| aggregate_signatures::<C>()).collect::<Vec<_>>();
+
+  // Batch verify the aggregates
+  {
+    let mut batch = BatchVerifier::new(3);
+    for (i, (aggregate, keys_and_challenges)) in aggregates.iter().enumerate() {
+      assert!(aggregate.batch_verify(
+        &mut OsRng,
+        &mut batch,
+        i,
+        AGGREGATE_DST,
+        keys_and_challenges
+      ));
+    }
+    batch.verify_vartime_with_vartime_blame().unwrap();
+  }
+
+  // An aggregate verified against the wrong signatures should be blamed
+  {
+    let mut batch = BatchVerifier::new(3);
+    for (i, (aggregate, _)) in aggregates.iter().enumerate() {
+      let keys_and_challenges = &aggregates[if i == 1 { 2 } else { i }].1;
+      assert!(aggregate.batch_verify(
+        &mut OsRng,
+        &mut batch,
+        i,
+        AGGREGATE_DST,
+        keys_and_challenges
+      ));
+    }
+    assert_eq!(batch.verify_vartime_with_vartime_blame(), Err(1));
+  }
+
+  // A mismatched amount of signatures shouldn't be queued
+  let mut batch = BatchVerifier::new(1);
+  assert!(!aggregates[0].0.batch_verify(
+    &mut OsRng,
+    &mut batch,
+    0,
+    AGGREGATE_DST,
+    &aggregates[0].1[1 ..]
   ));
 }
 
@@ -118,4 +167,5 @@ fn test() {
   verify::<Ed25519>();
   batch_verify::<Ed25519>();
   aggregate::<Ed25519>();
+  batch_verify_aggregates::<Ed25519>();
 }
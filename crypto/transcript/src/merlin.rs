@@ -4,7 +4,8 @@ use crate::Transcript;
 
 /// A wrapper around a Merlin transcript which satisfiees the Transcript API.
 ///
-/// Challenges are fixed to 64 bytes, despite Merlin supporting variable length challenges.
+/// Challenges produced via the Transcript API are fixed to 64 bytes. Variable length challenges,
+/// as supported by Merlin, are available via `MerlinTranscript::challenge_bytes`.
 ///
 /// This implementation is intended to remain in the spirit of Merlin more than it's intended to be
 /// in the spirit of the provided DigestTranscript. While DigestTranscript uses flags for each of
@@ -21,6 +22,28 @@ impl Debug for MerlinTranscript {
   }
 }
 
+impl From<merlin::Transcript> for MerlinTranscript {
+  fn from(transcript: merlin::Transcript) -> MerlinTranscript {
+    MerlinTranscript(transcript)
+  }
+}
+
+impl From<MerlinTranscript> for merlin::Transcript {
+  fn from(transcript: MerlinTranscript) -> merlin::Transcript {
+    transcript.0
+  }
+}
+
+impl MerlinTranscript {
+  /// Fill the destination buffer with a challenge of its length.
+  ///
+  /// As Merlin binds the length of a challenge to it, challenges of distinct lengths are distinct,
+  /// not prefixes of each other. `challenge` is equivalent to calling this with a 64-byte buffer.
+  pub fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+    self.0.challenge_bytes(label, dest);
+  }
+}
+
 impl Transcript for MerlinTranscript {
   // Uses a challenge length of 64 bytes to support wide reduction on commonly used EC scalars
   // From a security level standpoint (Merlin targets 128-bits), this should just be 32 bytes
@@ -46,7 +69,7 @@ impl Transcript for MerlinTranscript {
 
   fn challenge(&mut self, label: &'static [u8]) -> Self::Challenge {
     let mut challenge = [0; 64];
-    self.0.challenge_bytes(label, &mut challenge);
+    self.challenge_bytes(label, &mut challenge);
     challenge
   }
 
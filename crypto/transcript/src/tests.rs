@@ -40,6 +40,21 @@ where
     assert!(c(t1) != c(t2));
   }
 
+  // Ensure domain separators can't be confused with messages
+  {
+    let mut t1 = t();
+    let mut t2 = t();
+    t1.domain_separate(b"d");
+    t2.append_message(b"d", b"");
+    assert!(c(t1) != c(t2));
+
+    let mut t1 = t();
+    let mut t2 = t();
+    t1.domain_separate(b"d");
+    t2.append_message(b"domain", b"d");
+    assert!(c(t1) != c(t2));
+  }
+
   // Ensure distinct messages create distinct challenges
   {
     // By label
@@ -101,3 +116,37 @@ fn test_recommended() {
 fn test_merlin() {
   test_transcript::<crate::MerlinTranscript>();
 }
+
+#[cfg(feature = "merlin")]
+#[test]
+fn test_merlin_interop() {
+  // A MerlinTranscript should produce the same challenges as the Merlin transcript it wraps
+  let mut t = crate::MerlinTranscript::new(b"name");
+  let mut raw = merlin::Transcript::new(b"name");
+
+  t.domain_separate(b"domain");
+  raw.append_message(b"dom-sep", b"domain");
+  t.append_message(b"label", b"message");
+  raw.append_message(b"label", b"message");
+
+  let mut challenge = [0; 64];
+  raw.clone().challenge_bytes(b"challenge", &mut challenge);
+  assert_eq!(t.clone().challenge(b"challenge"), challenge);
+
+  // Including after converting between the two
+  let mut challenge = [0; 64];
+  merlin::Transcript::from(t.clone()).challenge_bytes(b"challenge", &mut challenge);
+  assert_eq!(crate::MerlinTranscript::from(raw).challenge(b"challenge"), challenge);
+
+  // Challenges of distinct lengths shouldn't be prefixes of each other
+  let mut short = [0; 32];
+  t.clone().challenge_bytes(b"challenge", &mut short);
+  assert!(short[..] != t.challenge(b"challenge")[.. 32]);
+}
+
+#[cfg(feature = "merlin")]
+#[test]
+#[should_panic]
+fn test_merlin_reserved_label() {
+  crate::MerlinTranscript::new(b"name").append_message(b"dom-sep", b"message");
+}
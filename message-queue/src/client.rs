@@ -1,4 +1,5 @@
-use core::ops::Deref;
+use core::{ops::Deref, time::Duration};
+use std::{sync::Mutex, time::Instant, collections::HashMap};
 
 use zeroize::{Zeroize, Zeroizing};
 use rand_core::OsRng;
@@ -13,16 +14,32 @@ use serde::{Serialize, Deserialize};
 
 use reqwest::Client;
 
+use serai_db::{Get, DbTxn, create_db};
 use serai_env as env;
 
 use crate::{Service, Metadata, QueuedMessage, message_challenge, ack_challenge};
 
+// The longest to wait between attempts to contact the message-queue
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// The amount of consecutive failures after which to discard any pooled connections
+const RECONNECT_AFTER: u32 = 3;
+
+create_db!(
+  MessageQueueClientDb {
+    // The ID of the next message to handle from a service
+    NextToHandleDb: (from: Service) -> u64
+  }
+);
+
 pub struct MessageQueue {
   pub service: Service,
   priv_key: Zeroizing<<Ristretto as Ciphersuite>::F>,
   pub_key: <Ristretto as Ciphersuite>::G,
-  client: Client,
+  client: Mutex<Client>,
   url: String,
+  last_contact: Mutex<Option<Instant>>,
+  // The ID of the next message expected from each service, known once a message was acknowledged
+  next_ids: Mutex<HashMap<Service, u64>>,
 }
 
 impl MessageQueue {
@@ -45,8 +62,10 @@ impl MessageQueue {
       service,
       pub_key: Ristretto::generator() * priv_key.deref(),
       priv_key,
-      client: Client::new(),
+      client: Mutex::new(Client::new()),
       url,
+      last_contact: Mutex::new(None),
+      next_ids: Mutex::new(HashMap::new()),
     }
   }
 
@@ -54,10 +73,9 @@ impl MessageQueue {
     let url = env::var("MESSAGE_QUEUE_RPC").expect("message-queue RPC wasn't specified");
 
     let priv_key: Zeroizing<<Ristretto as Ciphersuite>::F> = {
-      let key_str =
-        Zeroizing::new(env::var("MESSAGE_QUEUE_KEY").expect("message-queue key wasn't specified"));
+      let key_str = env::secret("MESSAGE_QUEUE_KEY").expect("message-queue key wasn't specified");
       let key_bytes = Zeroizing::new(
-        hex::decode(&key_str).expect("invalid message-queue key specified (wasn't hex)"),
+        hex::decode(key_str.as_str()).expect("invalid message-queue key specified (wasn't hex)"),
       );
       let mut bytes = <<Ristretto as Ciphersuite>::F as PrimeField>::Repr::default();
       bytes.copy_from_slice(&key_bytes);
@@ -81,10 +99,11 @@ impl MessageQueue {
       id: u64,
     }
 
+    let mut failures = 0;
     let res = loop {
       // Make the request
-      match self
-        .client
+      let client = self.client.lock().unwrap().clone();
+      match client
         .post(&self.url)
         .json(&JsonRpcRequest { jsonrpc: "2.0", method, params: params.clone(), id: 0 })
         .send()
@@ -93,19 +112,25 @@ impl MessageQueue {
         Ok(req) => {
           // Get the response
           match req.text().await {
-            Ok(res) => break res,
-            Err(e) => {
-              dbg!(e);
+            Ok(res) => {
+              *self.last_contact.lock().unwrap() = Some(Instant::now());
+              break res;
             }
+            Err(e) => log::warn!("couldn't read the message-queue's response to {method}: {e}"),
           }
         }
-        Err(e) => {
-          dbg!(e);
-        }
+        Err(e) => log::warn!("couldn't call {method} on the message-queue: {e}"),
+      }
+
+      // If we've repeatedly failed, the pooled connections may be stale, so create a new client
+      failures += 1;
+      if (failures % RECONNECT_AFTER) == 0 {
+        log::warn!("reconnecting to the message-queue after {failures} failed attempts");
+        *self.client.lock().unwrap() = Client::new();
       }
 
-      // Sleep for a second before trying again
-      tokio::time::sleep(core::time::Duration::from_secs(1)).await;
+      // Back off exponentially before trying again
+      tokio::time::sleep(Duration::from_secs(1 << (failures - 1).min(5)).min(MAX_BACKOFF)).await;
     };
 
     let json =
@@ -116,6 +141,11 @@ impl MessageQueue {
     json
   }
 
+  /// When the message-queue last responded to us, if it ever has.
+  pub fn last_contact(&self) -> Option<Instant> {
+    *self.last_contact.lock().unwrap()
+  }
+
   pub async fn queue(&self, metadata: Metadata, msg: Vec<u8>) {
     // TODO: Should this use OsRng? Deterministic or deterministic + random may be better.
     let nonce = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
@@ -175,10 +205,36 @@ impl MessageQueue {
       }
       // TODO: Verify the sender's signature
 
+      // The oldest unacknowledged message is returned, so after a message is acknowledged, the
+      // next message must have the next ID
+      if let Some(expected) = self.next_ids.lock().unwrap().get(&from) {
+        assert_eq!(
+          msg.id, *expected,
+          "message-queue returned message {} from {from:?} when {expected} was expected",
+          msg.id,
+        );
+      }
+
       return msg;
     }
   }
 
+  /// Get a message by its ID, regardless of if it's been acknowledged.
+  ///
+  /// This allows replaying the queue from an arbitrary offset.
+  pub async fn get(&self, from: Service, id: u64) -> Option<QueuedMessage> {
+    let json = self.json_call("get", serde_json::json!([from, self.service, id])).await;
+    let msg: Option<QueuedMessage> = serde_json::from_value(
+      json.get("result").expect("successful JSON RPC call didn't have result").clone(),
+    )
+    .expect("get didn't return an Option<QueuedMessage>");
+    if let Some(msg) = msg.as_ref() {
+      assert_eq!(msg.from, from, "message-queue returned a message from another service");
+      assert_eq!(msg.id, id, "message-queue returned a message with a distinct ID");
+    }
+    msg
+  }
+
   pub async fn ack(&self, from: Service, id: u64) {
     // TODO: Should this use OsRng? Deterministic or deterministic + random may be better.
     let nonce = Zeroizing::new(<Ristretto as Ciphersuite>::F::random(&mut OsRng));
@@ -194,5 +250,27 @@ impl MessageQueue {
     if json.get("result") != Some(&serde_json::Value::Bool(true)) {
       panic!("failed to ack message {id}: {json}");
     }
+    // Re-acknowledging a prior message is a no-op, so don't regress the expected ID
+    let mut next_ids = self.next_ids.lock().unwrap();
+    let next = next_ids.entry(from).or_insert(0);
+    *next = (*next).max(id + 1);
+  }
+
+  /// If a message from a service has already been handled.
+  ///
+  /// Messages should be handled by applying their effects in a transaction which also calls
+  /// `handle`, committing it, and only then acknowledging them. If the service halts after
+  /// committing yet before acknowledging, the message will be received again, and this will
+  /// return true so its effects aren't applied twice.
+  pub fn handled(getter: &impl Get, from: Service, id: u64) -> bool {
+    NextToHandleDb::get(getter, from).is_some_and(|next| id < next)
+  }
+
+  /// Mark a message from a service as handled, within the transaction applying its effects.
+  pub fn handle(txn: &mut impl DbTxn, from: Service, id: u64) {
+    if let Some(next) = NextToHandleDb::get(&*txn, from) {
+      assert_eq!(id, next, "handling message {id} from {from:?} when {next} was next");
+    }
+    NextToHandleDb::set(txn, from, &(id + 1));
   }
 }
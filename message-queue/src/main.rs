@@ -124,27 +124,56 @@ mod binaries {
     queue.get_message(next)
   }
 
+  // get RPC method
+  /*
+    Gets a message by its ID, regardless of if it's been acknowledged.
+
+    This allows a service to replay its queue from an arbitrary offset, such as when recovering
+    its state. Like `next`, this is not authenticated.
+  */
+  pub(crate) fn get_message_by_id(from: Service, to: Service, id: u64) -> Option<QueuedMessage> {
+    (*QUEUES).read().unwrap()[&(from, to)].read().unwrap().get_message(id)
+  }
+
   // ack RPC method
   /*
     Acknowledges a message as received and handled, meaning it'll no longer be returned as the next
     message.
+
+    Messages must be acknowledged in order. Acknowledging an already acknowledged message is a
+    no-op, allowing services to safely retry acknowledgements. Acknowledging a message out of order
+    is rejected, returning false.
   */
-  pub(crate) fn ack_message(from: Service, to: Service, id: u64, sig: SchnorrSignature<Ristretto>) {
+  pub(crate) fn ack_message(
+    from: Service,
+    to: Service,
+    id: u64,
+    sig: SchnorrSignature<Ristretto>,
+  ) -> bool {
     {
       let to_key = (*KEYS).read().unwrap()[&to];
       assert!(sig.verify(to_key, ack_challenge(to, to_key, from, id, sig.R)));
     }
 
-    // Is it:
-    // The acknowledged message should be > last acknowledged OR
-    // The acknowledged message should be >=
-    // It's the first if we save messages as acknowledged before acknowledging them
-    // It's the second if we acknowledge messages before saving them as acknowledged
-    // TODO: Check only a proper message is being acked
-
     log::info!("Acknowledging From: {:?} To: {:?} ID: {}", from, to, id);
 
-    (*QUEUES).read().unwrap()[&(from, to)].write().unwrap().ack_message(id)
+    match (*QUEUES).read().unwrap()[&(from, to)].write().unwrap().ack_message(id) {
+      Ok(true) => {}
+      Ok(false) => log::warn!(
+        "Prior acknowledged message acknowledged again. From: {:?} To: {:?} ID: {id}",
+        from,
+        to
+      ),
+      Err(next) => {
+        log::warn!(
+          "Message acknowledged out of order. From: {:?} To: {:?} ID: {id} Next: {next}",
+          from,
+          to
+        );
+        return false;
+      }
+    }
+    true
   }
 }
 
@@ -250,16 +279,21 @@ async fn main() {
       Ok(get_next_message(from, to))
     })
     .unwrap();
+  module
+    .register_method("get", |args, _| {
+      let (from, to, id) = args.parse::<(Service, Service, u64)>().unwrap();
+      Ok(get_message_by_id(from, to, id))
+    })
+    .unwrap();
   module
     .register_method("ack", |args, _| {
       let args = args.parse::<(Service, Service, u64, Vec<u8>)>().unwrap();
-      ack_message(
+      Ok(ack_message(
         args.0,
         args.1,
         args.2,
         SchnorrSignature::<Ristretto>::read(&mut args.3.as_slice()).unwrap(),
-      );
-      Ok(true)
+      ))
     })
     .unwrap();
 
@@ -1,11 +1,12 @@
 use transcript::{Transcript, RecommendedTranscript};
 use ciphersuite::{group::GroupEncoding, Ciphersuite, Ristretto};
 
+use scale::{Encode, Decode};
 use serde::{Serialize, Deserialize};
 
 use serai_primitives::NetworkId;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Encode, Decode, Serialize, Deserialize)]
 pub enum Service {
   Processor(NetworkId),
   Coordinator,
@@ -1,4 +1,4 @@
-use serai_db::{DbTxn, Db};
+use serai_db::{Get, DbTxn, Db};
 
 use crate::messages::*;
 
@@ -12,9 +12,8 @@ impl<D: Db> Queue<D> {
   fn message_count_key(&self) -> Vec<u8> {
     Self::key(b"message_count", bincode::serialize(&(self.1, self.2)).unwrap())
   }
-  pub(crate) fn message_count(&self) -> u64 {
-    self
-      .0
+  pub(crate) fn message_count<G: Get>(&self, getter: &G) -> u64 {
+    getter
       .get(self.message_count_key())
       .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
       .unwrap_or(0)
@@ -33,14 +32,12 @@ impl<D: Db> Queue<D> {
   fn message_key(&self, id: u64) -> Vec<u8> {
     Self::key(b"message", bincode::serialize(&(self.1, self.2, id)).unwrap())
   }
-  // TODO: This is fine as-used, yet gets from the DB while having a txn. It should get from the
-  // txn
   pub(crate) fn queue_message(
     &mut self,
     txn: &mut D::Transaction<'_>,
     mut msg: QueuedMessage,
   ) -> u64 {
-    let id = self.message_count();
+    let id = self.message_count(txn);
     msg.id = id;
     let msg_key = self.message_key(id);
     let msg_count_key = self.message_count_key();
@@ -60,10 +57,24 @@ impl<D: Db> Queue<D> {
     msg
   }
 
-  pub(crate) fn ack_message(&mut self, id: u64) {
+  /// Acknowledge a message, returning false if it was already acknowledged.
+  ///
+  /// Messages must be acknowledged in order. If the specified message isn't the next message in
+  /// the queue, nothing is acknowledged and the ID of the next message is returned as an error.
+  pub(crate) fn ack_message(&mut self, id: u64) -> Result<bool, u64> {
+    let next = self.last_acknowledged().map(|i| i + 1).unwrap_or(0);
+    if id < next {
+      return Ok(false);
+    }
+    // This is either out of order or for a message which wasn't queued
+    if (id != next) || (id >= self.message_count(&self.0)) {
+      Err(next)?;
+    }
+
     let ack_key = self.last_acknowledged_key();
     let mut txn = self.0.txn();
     txn.put(ack_key, id.to_le_bytes());
     txn.commit();
+    Ok(true)
   }
 }
@@ -148,3 +148,100 @@ impl Processor {
     self.handle.join().unwrap()
   }
 }
+
+// The amount of confirmations an external block needs before it's batched.
+// The modeled network only ever reorganizes its tip, making two sufficient.
+const CONFIRMATIONS: usize = 2;
+
+#[derive(Debug)]
+pub struct Network {
+  handle: JoinHandle<()>,
+  // The hashes of the blocks on the current best chain
+  pub blocks: Arc<RwLock<Vec<u64>>>,
+  pub finished: Arc<RwLock<bool>>,
+}
+
+impl Network {
+  // Mine the specified amount of blocks, replacing the block mined at the specified height with a
+  // competing block (a reorganization of depth one)
+  pub fn new(blocks: u64, reorg: Option<u64>) -> Network {
+    let chain = Arc::new(RwLock::new(vec![]));
+    let finished = Arc::new(RwLock::new(false));
+
+    let handle = thread::spawn({
+      let chain = chain.clone();
+      let finished = finished.clone();
+
+      move || {
+        // Hashes are unique across every block mined, including orphaned ones
+        let mut next_hash = 0;
+        for b in 0 .. blocks {
+          chain.write().unwrap().push(next_hash);
+          next_hash += 1;
+
+          if reorg == Some(b) {
+            *chain.write().unwrap().last_mut().unwrap() = next_hash;
+            next_hash += 1;
+          }
+        }
+        *finished.write().unwrap() = true;
+      }
+    });
+
+    Network { handle, blocks: chain, finished }
+  }
+
+  pub fn join(self) -> Vec<u64> {
+    self.handle.join().unwrap();
+    self.blocks.read().unwrap().clone()
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ExternalBatch {
+  pub block: u64,
+  pub hash: u64,
+}
+
+#[derive(Debug)]
+pub struct Scanner {
+  handle: JoinHandle<(Network, Vec<ExternalBatch>)>,
+}
+
+impl Scanner {
+  pub fn new(network: Network) -> Scanner {
+    Scanner::with_confirmations(network, CONFIRMATIONS)
+  }
+
+  // Batch every block once it has the specified amount of confirmations
+  pub fn with_confirmations(network: Network, confirmations: usize) -> Scanner {
+    let handle = thread::spawn(move || {
+      let mut batches = vec![];
+      loop {
+        // Once the network has stopped mining, no further reorganizations can occur and every
+        // block is final
+        let finished = *network.finished.read().unwrap();
+        {
+          let chain = network.blocks.read().unwrap();
+          let confirmed =
+            if finished { chain.len() } else { (chain.len() + 1).saturating_sub(confirmations) };
+          while batches.len() < confirmed {
+            let block = batches.len();
+            batches.push(ExternalBatch { block: block.try_into().unwrap(), hash: chain[block] });
+          }
+        }
+
+        if finished {
+          break;
+        }
+        thread::yield_now();
+      }
+      (network, batches)
+    });
+    Scanner { handle }
+  }
+
+  pub fn join(self) -> (Network, Vec<ExternalBatch>) {
+    self.handle.join().unwrap()
+  }
+}
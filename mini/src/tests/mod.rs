@@ -1 +1,2 @@
 mod activation_race;
+mod reorg_race;
@@ -0,0 +1,60 @@
+use std::{
+  collections::HashSet,
+  sync::{Arc as StdArc, RwLock as StdRwLock},
+};
+
+use crate::*;
+
+// Run the model, returning every distinct (final chain, batches) result
+fn model(confirmations: usize) -> HashSet<(Vec<u64>, Vec<ExternalBatch>)> {
+  let results = StdArc::new(StdRwLock::new(HashSet::new()));
+
+  loom::model({
+    let results = results.clone();
+    move || {
+      let network = Network::new(3, Some(1));
+      let scanner = Scanner::with_confirmations(network, confirmations);
+      let (network, batches) = scanner.join();
+      let chain = network.join();
+
+      results.write().unwrap().insert((chain, batches));
+    }
+  });
+
+  let results = results.read().unwrap().clone();
+  results
+}
+
+#[test]
+fn reorg_race() {
+  let results = model(1);
+
+  // Batching the tip immediately allows batching a block which is then reorganized out
+  let mut orphaned = false;
+  for (chain, batches) in results {
+    assert_eq!(chain, vec![0, 2, 3]);
+    assert_eq!(batches.len(), chain.len());
+    for (b, batch) in batches.into_iter().enumerate() {
+      assert_eq!(batch.block, u64::try_from(b).unwrap());
+      orphaned |= batch.hash != chain[b];
+    }
+  }
+  assert!(orphaned);
+}
+
+#[test]
+fn confirmations_solve_reorg_race() {
+  let results = model(CONFIRMATIONS);
+
+  for (chain, batches) in results {
+    assert_eq!(chain, vec![0, 2, 3]);
+    assert_eq!(
+      batches,
+      chain
+        .iter()
+        .enumerate()
+        .map(|(b, hash)| ExternalBatch { block: b.try_into().unwrap(), hash: *hash })
+        .collect::<Vec<_>>()
+    );
+  }
+}
@@ -1,9 +1,10 @@
+use core::fmt;
 use std::collections::HashMap;
 
 use zeroize::Zeroize;
 
 use scale::{Encode, Decode};
-use serde::{Serialize, Deserialize};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
 
 use dkg::{Participant, ThresholdParams};
 
@@ -29,6 +30,17 @@ pub mod key_gen {
     pub attempt: u32,
   }
 
+  // An accusation of a participant having sent an invalid share, as published on the Tributary.
+  #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+  pub struct Accusation {
+    pub accuser: Participant,
+    pub accused: Participant,
+    // The encrypted share the accused sent the accuser.
+    pub share: Vec<u8>,
+    // The proof the share was invalid, prefixed by which curve it's for, if there is one.
+    pub blame: Option<Vec<u8>>,
+  }
+
   #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
   pub enum CoordinatorMessage {
     // Instructs the Processor to begin the key generation process.
@@ -38,12 +50,23 @@ pub mod key_gen {
     Commitments { id: KeyGenId, commitments: HashMap<Participant, Vec<u8>> },
     // Received shares for the specified key generation protocol.
     Shares { id: KeyGenId, shares: HashMap<Participant, Vec<u8>> },
+    // Verify the blame of an accused participant, as published by the accuser.
+    VerifyBlame { id: KeyGenId, accusation: Accusation },
   }
 
   impl CoordinatorMessage {
     pub fn required_block(&self) -> Option<BlockHash> {
       None
     }
+
+    pub fn id(&self) -> KeyGenId {
+      match self {
+        CoordinatorMessage::GenerateKey { id, .. } => *id,
+        CoordinatorMessage::Commitments { id, .. } => *id,
+        CoordinatorMessage::Shares { id, .. } => *id,
+        CoordinatorMessage::VerifyBlame { id, .. } => *id,
+      }
+    }
   }
 
   #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -54,6 +77,13 @@ pub mod key_gen {
     Shares { id: KeyGenId, shares: HashMap<Participant, Vec<u8>> },
     // Resulting keys from the specified key generation protocol.
     GeneratedKeyPair { id: KeyGenId, substrate_key: [u8; 32], network_key: Vec<u8> },
+    // The commitments published by the specified participant were invalid.
+    // As the commitments are public, every participant will independently identify this fault.
+    InvalidCommitments { id: KeyGenId, faulty: Participant },
+    // The share sent to us by the specified participant was invalid, with a proof to blame them.
+    InvalidShare { id: KeyGenId, faulty: Participant, blame: Option<Vec<u8>> },
+    // The participant found to be faulty when verifying the accuser's blame.
+    Blame { id: KeyGenId, accuser: Participant, participant: Participant },
   }
 }
 
@@ -115,6 +145,10 @@ pub mod coordinator {
     BatchShares { id: SignId, shares: HashMap<Participant, [u8; 32]> },
     // Re-attempt a batch signing protocol.
     BatchReattempt { id: SignId },
+    // Cosign the specified Serai block, whose hash is the ID.
+    CosignSubstrateBlock { id: SignId, block_number: u64 },
+    CosignPreprocesses { id: SignId, preprocesses: HashMap<Participant, Vec<u8>> },
+    CosignShares { id: SignId, shares: HashMap<Participant, [u8; 32]> },
   }
 
   impl CoordinatorMessage {
@@ -127,6 +161,10 @@ pub mod coordinator {
         CoordinatorMessage::BatchPreprocesses { .. } => None,
         CoordinatorMessage::BatchShares { .. } => None,
         CoordinatorMessage::BatchReattempt { .. } => None,
+        // Serai blocks are only cosigned once finalized, so the processor needn't wait for them
+        CoordinatorMessage::CosignSubstrateBlock { .. } => None,
+        CoordinatorMessage::CosignPreprocesses { .. } => None,
+        CoordinatorMessage::CosignShares { .. } => None,
       }
     }
 
@@ -135,6 +173,9 @@ pub mod coordinator {
         CoordinatorMessage::BatchPreprocesses { id, .. } => &id.key,
         CoordinatorMessage::BatchShares { id, .. } => &id.key,
         CoordinatorMessage::BatchReattempt { id } => &id.key,
+        CoordinatorMessage::CosignSubstrateBlock { id, .. } => &id.key,
+        CoordinatorMessage::CosignPreprocesses { id, .. } => &id.key,
+        CoordinatorMessage::CosignShares { id, .. } => &id.key,
       }
     }
   }
@@ -144,6 +185,20 @@ pub mod coordinator {
     SubstrateBlockAck { network: NetworkId, block: u64, plans: Vec<[u8; 32]> },
     BatchPreprocess { id: SignId, block: BlockHash, preprocess: Vec<u8> },
     BatchShare { id: SignId, share: [u8; 32] },
+    CosignPreprocess { id: SignId, preprocess: Vec<u8> },
+    CosignShare { id: SignId, share: [u8; 32] },
+    // Uses Vec<u8> instead of [u8; 64] since serde Deserialize isn't implemented for [u8; 64]
+    CosignedBlock { key: Vec<u8>, block_number: u64, block: [u8; 32], signature: Vec<u8> },
+  }
+
+  // The message signed when cosigning a Serai block
+  pub fn cosign_block_msg(block_number: u64, block: [u8; 32]) -> Vec<u8> {
+    const DST: &[u8] = b"Cosign";
+    let mut res = vec![u8::try_from(DST.len()).unwrap()];
+    res.extend(DST);
+    res.extend(block_number.to_le_bytes());
+    res.extend(block);
+    res
   }
 }
 
@@ -236,6 +291,98 @@ impl_from!(sign, ProcessorMessage, Sign);
 impl_from!(coordinator, ProcessorMessage, Coordinator);
 impl_from!(substrate, ProcessorMessage, Substrate);
 
+// Versioning code
+
+/// The version of the protocol messages between the coordinator and processor are encoded with.
+///
+/// This must be incremented whenever a message's encoding changes.
+///
+/// Version 2 added the messages for cosigning Serai blocks.
+pub const PROTOCOL_VERSION: u16 = 2;
+/// The oldest version of the protocol which can still be decoded.
+///
+/// Version 0 messages were the bare JSON encoding of the message, without any version.
+pub const MIN_PROTOCOL_VERSION: u16 = 0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecodeError {
+  /// The message was encoded with a version of the protocol this doesn't support.
+  UnsupportedVersion(u16),
+  /// The message wasn't a valid encoding of a message.
+  InvalidMessage,
+}
+
+impl fmt::Display for DecodeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      DecodeError::UnsupportedVersion(version) => write!(
+        f,
+        "message used protocol version {version}, yet only versions {MIN_PROTOCOL_VERSION} to \
+         {PROTOCOL_VERSION} are supported. are the coordinator and processor on compatible \
+         releases?"
+      ),
+      DecodeError::InvalidMessage => write!(f, "message wasn't a valid encoding of a message"),
+    }
+  }
+}
+impl std::error::Error for DecodeError {}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Versioned<M> {
+  version: u16,
+  msg: M,
+}
+
+fn serialize<M: Serialize>(msg: &M) -> Vec<u8> {
+  serde_json::to_vec(&Versioned { version: PROTOCOL_VERSION, msg }).unwrap()
+}
+
+fn deserialize<M: DeserializeOwned>(bytes: &[u8]) -> Result<M, DecodeError> {
+  let value =
+    serde_json::from_slice::<serde_json::Value>(bytes).map_err(|_| DecodeError::InvalidMessage)?;
+
+  // Messages are enums, which serialize as objects keyed by their variant's name, so an object
+  // with a version field is never a version 0 message
+  let (version, msg) = if value.get("version").is_some() {
+    let Versioned { version, msg } = serde_json::from_value::<Versioned<serde_json::Value>>(value)
+      .map_err(|_| DecodeError::InvalidMessage)?;
+    (version, msg)
+  } else {
+    (0, value)
+  };
+  if !(MIN_PROTOCOL_VERSION ..= PROTOCOL_VERSION).contains(&version) {
+    Err(DecodeError::UnsupportedVersion(version))?;
+  }
+
+  // Every version so far has used the same encoding for the message itself
+  serde_json::from_value(msg).map_err(|_| DecodeError::InvalidMessage)
+}
+
+impl CoordinatorMessage {
+  /// Serialize this message, tagged with the current protocol version.
+  pub fn serialize(&self) -> Vec<u8> {
+    serialize(self)
+  }
+
+  /// Deserialize a message encoded with any supported protocol version.
+  pub fn deserialize(bytes: &[u8]) -> Result<CoordinatorMessage, DecodeError> {
+    deserialize(bytes)
+  }
+}
+
+impl ProcessorMessage {
+  /// Serialize this message, tagged with the current protocol version.
+  pub fn serialize(&self) -> Vec<u8> {
+    serialize(self)
+  }
+
+  /// Deserialize a message encoded with any supported protocol version.
+  pub fn deserialize(bytes: &[u8]) -> Result<ProcessorMessage, DecodeError> {
+    deserialize(bytes)
+  }
+}
+
 // Intent generation code
 
 const COORDINATOR_UID: u8 = 0;
@@ -258,13 +405,17 @@ impl CoordinatorMessage {
       CoordinatorMessage::KeyGen(msg) => {
         // Unique since key gen ID embeds the validator set and attempt
         let (sub, id) = match msg {
-          key_gen::CoordinatorMessage::GenerateKey { id, .. } => (0, id),
-          key_gen::CoordinatorMessage::Commitments { id, .. } => (1, id),
-          key_gen::CoordinatorMessage::Shares { id, .. } => (2, id),
+          key_gen::CoordinatorMessage::GenerateKey { id, .. } => (0, id.encode()),
+          key_gen::CoordinatorMessage::Commitments { id, .. } => (1, id.encode()),
+          key_gen::CoordinatorMessage::Shares { id, .. } => (2, id.encode()),
+          // Unique since an accuser only accuses a participant once per attempt
+          key_gen::CoordinatorMessage::VerifyBlame { id, accusation } => {
+            (3, (id, accusation.accuser.to_bytes(), accusation.accused.to_bytes()).encode())
+          }
         };
 
         let mut res = vec![COORDINATOR_UID, TYPE_KEY_GEN_UID, sub];
-        res.extend(&id.encode());
+        res.extend(&id);
         res
       }
       CoordinatorMessage::Sign(msg) => {
@@ -290,6 +441,10 @@ impl CoordinatorMessage {
           coordinator::CoordinatorMessage::BatchPreprocesses { id, .. } => (0, id.encode()),
           coordinator::CoordinatorMessage::BatchShares { id, .. } => (1, id.encode()),
           coordinator::CoordinatorMessage::BatchReattempt { id, .. } => (2, id.encode()),
+          // Unique since this embeds the block hash and the key of the set cosigning it
+          coordinator::CoordinatorMessage::CosignSubstrateBlock { id, .. } => (3, id.encode()),
+          coordinator::CoordinatorMessage::CosignPreprocesses { id, .. } => (4, id.encode()),
+          coordinator::CoordinatorMessage::CosignShares { id, .. } => (5, id.encode()),
         };
 
         let mut res = vec![COORDINATOR_UID, TYPE_COORDINATOR_UID, sub];
@@ -325,13 +480,24 @@ impl ProcessorMessage {
       ProcessorMessage::KeyGen(msg) => {
         let (sub, id) = match msg {
           // Unique since KeyGenId
-          key_gen::ProcessorMessage::Commitments { id, .. } => (0, id),
-          key_gen::ProcessorMessage::Shares { id, .. } => (1, id),
-          key_gen::ProcessorMessage::GeneratedKeyPair { id, .. } => (2, id),
+          key_gen::ProcessorMessage::Commitments { id, .. } => (0, id.encode()),
+          key_gen::ProcessorMessage::Shares { id, .. } => (1, id.encode()),
+          key_gen::ProcessorMessage::GeneratedKeyPair { id, .. } => (2, id.encode()),
+          // Unique since a participant is only found faulty once per attempt
+          key_gen::ProcessorMessage::InvalidCommitments { id, faulty } => {
+            (3, (id, faulty.to_bytes()).encode())
+          }
+          key_gen::ProcessorMessage::InvalidShare { id, faulty, .. } => {
+            (4, (id, faulty.to_bytes()).encode())
+          }
+          // Unique since an accuser only accuses a participant once per attempt
+          key_gen::ProcessorMessage::Blame { id, accuser, .. } => {
+            (5, (id, accuser.to_bytes()).encode())
+          }
         };
 
         let mut res = vec![PROCESSSOR_UID, TYPE_KEY_GEN_UID, sub];
-        res.extend(&id.encode());
+        res.extend(&id);
         res
       }
       ProcessorMessage::Sign(msg) => {
@@ -355,6 +521,13 @@ impl ProcessorMessage {
           // Unique since SignId
           coordinator::ProcessorMessage::BatchPreprocess { id, .. } => (1, id.encode()),
           coordinator::ProcessorMessage::BatchShare { id, .. } => (2, id.encode()),
+          coordinator::ProcessorMessage::CosignPreprocess { id, .. } => (3, id.encode()),
+          coordinator::ProcessorMessage::CosignShare { id, .. } => (4, id.encode()),
+          // Unique since a processor will only cosign a block once with a key
+          // Multiple sets may cosign the same block, so this includes the key
+          coordinator::ProcessorMessage::CosignedBlock { key, block, .. } => {
+            (5, (key, block).encode())
+          }
         };
 
         let mut res = vec![PROCESSSOR_UID, TYPE_COORDINATOR_UID, sub];
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use dkg::Participant;
+
+use serai_primitives::{NetworkId, Session, ValidatorSet};
+
+use serai_processor_messages::{
+  key_gen::{self, KeyGenId},
+  CoordinatorMessage, ProcessorMessage, DecodeError, PROTOCOL_VERSION,
+};
+
+fn id() -> KeyGenId {
+  KeyGenId { set: ValidatorSet { session: Session(1), network: NetworkId::Monero }, attempt: 3 }
+}
+
+fn coordinator_message() -> CoordinatorMessage {
+  CoordinatorMessage::KeyGen(key_gen::CoordinatorMessage::Commitments {
+    id: id(),
+    commitments: HashMap::from([(Participant::new(2).unwrap(), vec![1, 2, 3])]),
+  })
+}
+
+fn processor_message() -> ProcessorMessage {
+  ProcessorMessage::KeyGen(key_gen::ProcessorMessage::Commitments {
+    id: id(),
+    commitments: vec![4, 5, 6],
+  })
+}
+
+#[test]
+fn round_trip() {
+  let msg = coordinator_message();
+  let serialized = msg.serialize();
+  // Messages are tagged with the current version
+  let value = serde_json::from_slice::<serde_json::Value>(&serialized).unwrap();
+  assert_eq!(value["version"], serde_json::json!(PROTOCOL_VERSION));
+  assert_eq!(CoordinatorMessage::deserialize(&serialized), Ok(msg));
+
+  let msg = processor_message();
+  assert_eq!(ProcessorMessage::deserialize(&msg.serialize()), Ok(msg));
+}
+
+#[test]
+fn decode_v0() {
+  // Version 0 messages were the bare JSON encoding of the message
+  let msg = coordinator_message();
+  let v0 = serde_json::to_vec(&msg).unwrap();
+  assert_eq!(CoordinatorMessage::deserialize(&v0), Ok(msg));
+
+  let msg = processor_message();
+  let v0 = serde_json::to_vec(&msg).unwrap();
+  assert_eq!(ProcessorMessage::deserialize(&v0), Ok(msg));
+}
+
+#[test]
+fn decode_v1() {
+  let msg = coordinator_message();
+  let v1 = serde_json::to_vec(&serde_json::json!({ "version": 1, "msg": msg })).unwrap();
+  assert_eq!(CoordinatorMessage::deserialize(&v1), Ok(msg));
+
+  let msg = processor_message();
+  let v1 = serde_json::to_vec(&serde_json::json!({ "version": 1, "msg": msg })).unwrap();
+  assert_eq!(ProcessorMessage::deserialize(&v1), Ok(msg));
+}
+
+#[test]
+fn reject_invalid() {
+  let msg = coordinator_message();
+
+  // Messages from a future version are rejected, not misinterpreted
+  let future = PROTOCOL_VERSION + 1;
+  let v_future = serde_json::to_vec(&serde_json::json!({ "version": future, "msg": msg })).unwrap();
+  assert_eq!(
+    CoordinatorMessage::deserialize(&v_future),
+    Err(DecodeError::UnsupportedVersion(future))
+  );
+
+  // As are unknown fields alongside the version
+  let extra =
+    serde_json::to_vec(&serde_json::json!({ "version": 1, "msg": msg, "extra": 0 })).unwrap();
+  assert_eq!(CoordinatorMessage::deserialize(&extra), Err(DecodeError::InvalidMessage));
+
+  // And a ProcessorMessage isn't a valid CoordinatorMessage
+  assert_eq!(
+    CoordinatorMessage::deserialize(&processor_message().serialize()),
+    Err(DecodeError::InvalidMessage)
+  );
+
+  assert_eq!(CoordinatorMessage::deserialize(b"not json"), Err(DecodeError::InvalidMessage));
+  assert_eq!(CoordinatorMessage::deserialize(b""), Err(DecodeError::InvalidMessage));
+}
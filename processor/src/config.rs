@@ -0,0 +1,76 @@
+use zeroize::{Zeroize, Zeroizing};
+
+use serai_client::primitives::NetworkId;
+
+use serai_env as env;
+
+/// The default RPC port for a network's node.
+pub fn default_rpc_port(network: NetworkId) -> u16 {
+  match network {
+    NetworkId::Serai => 9944,
+    NetworkId::Bitcoin => 8332,
+    NetworkId::Ethereum => 8545,
+    NetworkId::Monero => 18081,
+  }
+}
+
+/// The configuration for the processor.
+///
+/// Each value is read from the environment, falling back to the config file specified by
+/// `CONFIG_PATH` (see `serai_env`).
+pub struct Config {
+  /// The path to the database (`DB_PATH`).
+  pub db_path: String,
+  /// The network this processor is for (`NETWORK`).
+  pub network: NetworkId,
+  /// The URL of the network's node (`NETWORK_RPC_LOGIN`, `NETWORK_RPC_HOSTNAME`,
+  /// `NETWORK_RPC_PORT`).
+  pub network_rpc: String,
+  /// The entropy all keys are derived from (`ENTROPY`, or the file at `ENTROPY_PATH`).
+  pub entropy: Zeroizing<[u8; 32]>,
+  /// The port to serve the health endpoints on (`HEALTH_PORT`).
+  pub health_port: u16,
+}
+
+impl Config {
+  /// Load the configuration, panicking if it's missing values or invalid.
+  pub fn load() -> Config {
+    let db_path = env::var("DB_PATH").expect("path to DB wasn't specified");
+
+    let network = match env::var("NETWORK").expect("network wasn't specified").as_str() {
+      "bitcoin" => NetworkId::Bitcoin,
+      "monero" => NetworkId::Monero,
+      network => panic!("unrecognized network {network}"),
+    };
+
+    let network_rpc = {
+      let login = env::var("NETWORK_RPC_LOGIN").map(|login| login + "@").unwrap_or_default();
+      let hostname =
+        env::var("NETWORK_RPC_HOSTNAME").expect("network RPC hostname wasn't specified");
+      let port = env::var_or("NETWORK_RPC_PORT", default_rpc_port(network));
+      format!("http://{login}{hostname}:{port}")
+    };
+
+    let entropy = {
+      let entropy = env::secret("ENTROPY").expect("entropy wasn't specified");
+      if entropy.len() != 64 {
+        panic!("entropy isn't the right length");
+      }
+      let mut bytes = Zeroizing::new(
+        hex::decode(entropy.as_str()).map_err(|_| ()).expect("entropy wasn't hex-formatted"),
+      );
+      if bytes.len() != 32 {
+        bytes.zeroize();
+        panic!("entropy wasn't 32 bytes");
+      }
+      let mut entropy = Zeroizing::new([0; 32]);
+      let entropy_mut: &mut [u8] = entropy.as_mut();
+      entropy_mut.copy_from_slice(bytes.as_ref());
+      entropy
+    };
+
+    let health_port = env::var_or("HEALTH_PORT", serai_health::DEFAULT_HEALTH_PORT);
+
+    Config { db_path, network, network_rpc, entropy, health_port }
+  }
+}
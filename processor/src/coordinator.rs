@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use messages::{ProcessorMessage, CoordinatorMessage};
 
 use message_queue::{Service, Metadata, client::MessageQueue};
@@ -8,6 +10,12 @@ pub struct Message {
   pub msg: CoordinatorMessage,
 }
 
+/// The channel between the processor and the coordinator.
+///
+/// Messages are durably queued, in order, by the message-queue. A sent message is only delivered
+/// once per intent, so messages may safely be re-sent after a reboot. A received message will be
+/// received again until it's acknowledged, and should be acknowledged only after its effects were
+/// committed alongside `MessageQueue::handle`.
 #[async_trait::async_trait]
 pub trait Coordinator {
   async fn send(&mut self, msg: ProcessorMessage);
@@ -16,12 +24,10 @@ pub trait Coordinator {
 }
 
 #[async_trait::async_trait]
-impl Coordinator for MessageQueue {
+impl Coordinator for Arc<MessageQueue> {
   async fn send(&mut self, msg: ProcessorMessage) {
     let metadata = Metadata { from: self.service, to: Service::Coordinator, intent: msg.intent() };
-    let msg = serde_json::to_string(&msg).unwrap();
-
-    self.queue(metadata, msg.into_bytes()).await;
+    self.queue(metadata, msg.serialize()).await;
   }
 
   async fn recv(&mut self) -> Message {
@@ -30,8 +36,8 @@ impl Coordinator for MessageQueue {
     let id = msg.id;
 
     // Deserialize it into a CoordinatorMessage
-    let msg: CoordinatorMessage =
-      serde_json::from_slice(&msg.msg).expect("message wasn't a JSON-encoded CoordinatorMessage");
+    let msg = CoordinatorMessage::deserialize(&msg.msg)
+      .unwrap_or_else(|e| panic!("couldn't decode message {id} from the coordinator: {e}"));
 
     return Message { id, msg };
   }
@@ -6,27 +6,44 @@ use serai_client::validator_sets::primitives::{ValidatorSet, KeyPair};
 
 pub use serai_db::*;
 
+use message_queue::{Service, client::MessageQueue};
+
 use crate::networks::{Block, Network};
 
-#[derive(Debug)]
-pub struct MainDb<N: Network, D: Db>(D, PhantomData<N>);
-impl<N: Network, D: Db> MainDb<N, D> {
-  pub fn new(db: D) -> Self {
-    Self(db, PhantomData)
+create_db!(
+  MainDb {
+    // Messages from the coordinator which were handled, as tracked prior to MessageQueue::handle
+    HandledMessageDb: (id: u64) -> ()
   }
+);
 
-  fn main_key(dst: &'static [u8], key: impl AsRef<[u8]>) -> Vec<u8> {
-    D::key(b"MAIN", dst, key)
-  }
+// Move the tracking of which messages were handled to MessageQueue
+// Messages are handled in order, so only the latest message handled is kept
+// This should be called on boot, before the DB is otherwise used, and is a no-op once done
+pub fn migrate_legacy_handled_messages<D: IterableDb>(db: &mut D) {
+  let mut handled = vec![];
+  HandledMessageDb::iter(db, |id, ()| {
+    handled.push(id);
+    Ok::<_, ()>(())
+  })
+  .unwrap();
+  let Some(latest) = handled.iter().copied().max() else { return };
 
-  fn handled_key(id: u64) -> Vec<u8> {
-    Self::main_key(b"handled", id.to_le_bytes())
+  let mut txn = db.txn();
+  if !MessageQueue::handled(&txn, Service::Coordinator, latest) {
+    MessageQueue::handle(&mut txn, Service::Coordinator, latest);
   }
-  pub fn handled_message(&self, id: u64) -> bool {
-    self.0.get(Self::handled_key(id)).is_some()
+  for id in handled {
+    HandledMessageDb::del(&mut txn, id);
   }
-  pub fn handle_message(txn: &mut D::Transaction<'_>, id: u64) {
-    txn.put(Self::handled_key(id), [])
+  txn.commit();
+}
+
+#[derive(Debug)]
+pub struct MainDb<N: Network, D: Db>(PhantomData<(N, D)>);
+impl<N: Network, D: Db> MainDb<N, D> {
+  fn main_key(dst: &'static [u8], key: impl AsRef<[u8]>) -> Vec<u8> {
+    D::key(b"MAIN", dst, key)
   }
 
   fn pending_activation_key() -> Vec<u8> {
@@ -10,10 +10,12 @@ use transcript::{Transcript, RecommendedTranscript};
 use ciphersuite::group::GroupEncoding;
 use frost::{
   curve::{Ciphersuite, Ristretto},
-  dkg::{Participant, ThresholdParams, ThresholdCore, ThresholdKeys, encryption::*, frost::*},
+  dkg::{
+    DkgError, Participant, ThresholdParams, ThresholdCore, ThresholdKeys, encryption::*, frost::*,
+  },
 };
 
-use log::info;
+use tracing::info;
 
 use scale::Encode;
 use serai_client::validator_sets::primitives::{ValidatorSet, KeyPair};
@@ -94,8 +96,8 @@ impl<N: Network, D: Db> KeyGenDb<N, D> {
   ) -> Option<(Vec<u8>, (ThresholdKeys<Ristretto>, ThresholdKeys<N::Curve>))> {
     let keys_vec = getter.get(key)?;
     let mut keys_ref: &[u8] = keys_vec.as_ref();
-    let substrate_keys = ThresholdKeys::new(ThresholdCore::read(&mut keys_ref).unwrap());
-    let mut network_keys = ThresholdKeys::new(ThresholdCore::read(&mut keys_ref).unwrap());
+    let substrate_keys = ThresholdKeys::read(&mut keys_ref).unwrap();
+    let mut network_keys = ThresholdKeys::read(&mut keys_ref).unwrap();
     N::tweak_keys(&mut network_keys);
     Some((keys_vec, (substrate_keys, network_keys)))
   }
@@ -168,6 +170,14 @@ impl<N: Network, D: Db> KeyGen<N, D> {
     KeyGenDb::<N, D>::keys(&self.db, key)
   }
 
+  #[tracing::instrument(
+    skip_all,
+    fields(
+      network = ?msg.id().set.network,
+      session = msg.id().set.session.0,
+      attempt = msg.id().attempt,
+    ),
+  )]
   pub async fn handle(
     &mut self,
     txn: &mut D::Transaction<'_>,
@@ -190,6 +200,7 @@ impl<N: Network, D: Db> KeyGen<N, D> {
     let coefficients_rng = |id| rng(b"Key Gen Coefficients", id);
     let secret_shares_rng = |id| rng(b"Key Gen Secret Shares", id);
     let share_rng = |id| rng(b"Key Gen Share", id);
+    let blame_rng = |id| rng(b"Key Gen Blame", id);
 
     let key_gen_machines = |id, params| {
       let mut rng = coefficients_rng(id);
@@ -243,40 +254,50 @@ impl<N: Network, D: Db> KeyGen<N, D> {
         let mut commitments_ref: HashMap<Participant, &[u8]> =
           commitments.iter().map(|(i, commitments)| (*i, commitments.as_ref())).collect();
 
+        // Returns the participant whose commitments were invalid on error
         #[allow(clippy::type_complexity)]
         fn handle_machine<C: Ciphersuite>(
           rng: &mut ChaCha20Rng,
           params: ThresholdParams,
           machine: SecretShareMachine<C>,
           commitments_ref: &mut HashMap<Participant, &[u8]>,
-        ) -> (KeyMachine<C>, HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>) {
+        ) -> Result<
+          (KeyMachine<C>, HashMap<Participant, EncryptedMessage<C, SecretShare<C::F>>>),
+          Participant,
+        > {
           // Parse the commitments
-          let parsed = match commitments_ref
-            .iter_mut()
-            .map(|(i, commitments)| {
+          let mut parsed = HashMap::new();
+          for (i, commitments) in commitments_ref.iter_mut() {
+            let Ok(commitments) =
               EncryptionKeyMessage::<C, Commitments<C>>::read(commitments, params)
-                .map(|commitments| (*i, commitments))
-            })
-            .collect()
-          {
-            Ok(commitments) => commitments,
-            Err(e) => todo!("malicious signer: {:?}", e),
-          };
+            else {
+              Err(*i)?
+            };
+            parsed.insert(*i, commitments);
+          }
 
           match machine.generate_secret_shares(rng, parsed) {
-            Ok(res) => res,
-            Err(e) => todo!("malicious signer: {:?}", e),
+            Ok(res) => Ok(res),
+            Err(DkgError::InvalidProofOfKnowledge(i)) => Err(i),
+            // Any other error is due to the coordinator providing an invalid set of commitments
+            Err(e) => panic!("coordinator provided an invalid set of commitments: {e:?}"),
           }
         }
 
         let (substrate_machine, mut substrate_shares) =
-          handle_machine::<Ristretto>(&mut rng, params, machines.0, &mut commitments_ref);
+          match handle_machine::<Ristretto>(&mut rng, params, machines.0, &mut commitments_ref) {
+            Ok(res) => res,
+            Err(faulty) => return ProcessorMessage::InvalidCommitments { id, faulty },
+          };
         let (network_machine, network_shares) =
-          handle_machine(&mut rng, params, machines.1, &mut commitments_ref);
+          match handle_machine(&mut rng, params, machines.1, &mut commitments_ref) {
+            Ok(res) => res,
+            Err(faulty) => return ProcessorMessage::InvalidCommitments { id, faulty },
+          };
 
-        for (_, commitments) in commitments_ref {
+        for (i, commitments) in commitments_ref {
           if !commitments.is_empty() {
-            todo!("malicious signer: extra bytes");
+            return ProcessorMessage::InvalidCommitments { id, faulty: i };
           }
         }
 
@@ -338,38 +359,52 @@ impl<N: Network, D: Db> KeyGen<N, D> {
         let mut shares_ref: HashMap<Participant, &[u8]> =
           shares.iter().map(|(i, shares)| (*i, shares.as_ref())).collect();
 
+        // Returns the participant whose share was invalid, with the blame proof if there is one,
+        // on error
         fn handle_machine<C: Ciphersuite>(
           rng: &mut ChaCha20Rng,
           params: ThresholdParams,
           machine: KeyMachine<C>,
           shares_ref: &mut HashMap<Participant, &[u8]>,
-        ) -> ThresholdCore<C> {
+        ) -> Result<ThresholdCore<C>, (Participant, Option<Vec<u8>>)> {
           // Parse the shares
-          let shares = match shares_ref
-            .iter_mut()
-            .map(|(i, share)| {
-              EncryptedMessage::<C, SecretShare<C::F>>::read(share, params).map(|share| (*i, share))
-            })
-            .collect()
-          {
-            Ok(shares) => shares,
-            Err(e) => todo!("malicious signer: {:?}", e),
-          };
+          let mut shares = HashMap::new();
+          for (i, share) in shares_ref.iter_mut() {
+            let Ok(share) = EncryptedMessage::<C, SecretShare<C::F>>::read(share, params) else {
+              Err((*i, None))?
+            };
+            shares.insert(*i, share);
+          }
 
-          // TODO2: Handle the blame machine properly
-          (match machine.calculate_share(rng, shares) {
-            Ok(res) => res,
-            Err(e) => todo!("malicious signer: {:?}", e),
-          })
-          .complete()
+          match machine.calculate_share(rng, shares) {
+            Ok(res) => Ok(res.complete()),
+            Err(DkgError::InvalidShare { participant, blame }) => {
+              Err((participant, blame.map(|blame| blame.serialize())))
+            }
+            // Any other error is due to the coordinator providing an invalid set of shares
+            Err(e) => panic!("coordinator provided an invalid set of shares: {e:?}"),
+          }
         }
 
-        let substrate_keys = handle_machine(&mut rng, params, machines.0, &mut shares_ref);
-        let network_keys = handle_machine(&mut rng, params, machines.1, &mut shares_ref);
+        // The blame is prefixed with which curve it's for, so it can be verified by others
+        let substrate_keys = match handle_machine(&mut rng, params, machines.0, &mut shares_ref) {
+          Ok(keys) => keys,
+          Err((faulty, blame)) => {
+            let blame = blame.map(|blame| [[0].as_ref(), &blame].concat());
+            return ProcessorMessage::InvalidShare { id, faulty, blame };
+          }
+        };
+        let network_keys = match handle_machine(&mut rng, params, machines.1, &mut shares_ref) {
+          Ok(keys) => keys,
+          Err((faulty, blame)) => {
+            let blame = blame.map(|blame| [[1].as_ref(), &blame].concat());
+            return ProcessorMessage::InvalidShare { id, faulty, blame };
+          }
+        };
 
-        for (_, shares) in shares_ref {
+        for (i, shares) in shares_ref {
           if !shares.is_empty() {
-            todo!("malicious signer: extra bytes");
+            return ProcessorMessage::InvalidShare { id, faulty: i, blame: None };
           }
         }
 
@@ -384,6 +419,91 @@ impl<N: Network, D: Db> KeyGen<N, D> {
           network_key: network_keys.group_key().to_bytes().as_ref().to_vec(),
         }
       }
+
+      CoordinatorMessage::VerifyBlame { id, accusation } => {
+        let Accusation { accuser, accused, share, blame } = accusation;
+        info!("Verifying blame of {:?} by {:?} for {:?}", accused, accuser, id);
+
+        let params = KeyGenDb::<N, D>::params(txn, &id.set).unwrap();
+
+        // If the share published by the accused is malformed, they're at fault
+        let mut share_ref = share.as_slice();
+        let Ok(substrate_share) =
+          EncryptedMessage::<Ristretto, SecretShare<_>>::read(&mut share_ref, params)
+        else {
+          return ProcessorMessage::Blame { id, accuser, participant: accused };
+        };
+        let Ok(network_share) =
+          EncryptedMessage::<N::Curve, SecretShare<_>>::read(&mut share_ref, params)
+        else {
+          return ProcessorMessage::Blame { id, accuser, participant: accused };
+        };
+        if !share_ref.is_empty() {
+          return ProcessorMessage::Blame { id, accuser, participant: accused };
+        }
+
+        // Blame can only be raised once shares are sent, which is after every participant's
+        // commitments were verified and saved
+        // Our own commitments weren't saved, yet can be deterministically regenerated
+        let commitments = KeyGenDb::<N, D>::commitments(txn, &id);
+        let mut substrate_commitments = HashMap::new();
+        let mut network_commitments = HashMap::new();
+        for (i, commitments) in &commitments {
+          let mut commitments = commitments.as_slice();
+          substrate_commitments.insert(
+            *i,
+            EncryptionKeyMessage::<Ristretto, Commitments<_>>::read(&mut commitments, params)
+              .unwrap(),
+          );
+          network_commitments.insert(
+            *i,
+            EncryptionKeyMessage::<N::Curve, Commitments<_>>::read(&mut commitments, params)
+              .unwrap(),
+          );
+        }
+        let our_commitments = key_gen_machines(id, params).1;
+        substrate_commitments.insert(params.i(), our_commitments.0);
+        network_commitments.insert(params.i(), our_commitments.1);
+
+        let mut rng = blame_rng(id);
+        let substrate_blame =
+          AdditionalBlameMachine::new(&mut rng, context(&id), params.n(), substrate_commitments)
+            .unwrap();
+        let network_blame =
+          AdditionalBlameMachine::new(&mut rng, context(&id), params.n(), network_commitments)
+            .unwrap();
+
+        fn read_proof<C: Ciphersuite>(mut proof: &[u8]) -> Option<EncryptionKeyProof<C>> {
+          let res = EncryptionKeyProof::read(&mut proof).ok()?;
+          Some(res).filter(|_| proof.is_empty())
+        }
+
+        let participant = match blame.as_deref() {
+          // Without a proof, the accuser is claiming the accused's message had an invalid
+          // signature, which the accused is at fault for if it's true for either curve
+          None => {
+            if (substrate_blame.blame(accused, accuser, substrate_share, None) == accused) ||
+              (network_blame.blame(accused, accuser, network_share, None) == accused)
+            {
+              accused
+            } else {
+              accuser
+            }
+          }
+          Some([0, proof @ ..]) => match read_proof(proof) {
+            Some(proof) => substrate_blame.blame(accused, accuser, substrate_share, Some(proof)),
+            None => accuser,
+          },
+          Some([1, proof @ ..]) => match read_proof(proof) {
+            Some(proof) => network_blame.blame(accused, accuser, network_share, Some(proof)),
+            None => accuser,
+          },
+          // The accuser provided an invalid blame proof
+          Some(_) => accuser,
+        };
+
+        ProcessorMessage::Blame { id, accuser, participant }
+      }
     }
   }
 
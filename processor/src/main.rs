@@ -1,11 +1,16 @@
-use std::{sync::RwLock, time::Duration, collections::HashMap};
+use std::{
+  sync::{Arc, RwLock},
+  time::Duration,
+  net::{IpAddr, Ipv4Addr, SocketAddr},
+  collections::HashMap,
+};
 
 use zeroize::{Zeroize, Zeroizing};
 
 use transcript::{Transcript, RecommendedTranscript};
 use ciphersuite::{group::GroupEncoding, Ciphersuite};
 
-use log::{info, warn};
+use tracing::{info, warn};
 use tokio::time::sleep;
 
 use serai_client::{
@@ -15,10 +20,10 @@ use serai_client::{
 
 use messages::{CoordinatorMessage, ProcessorMessage};
 
-use serai_env as env;
-
 use message_queue::{Service, client::MessageQueue};
 
+use serai_health::Health;
+
 mod plan;
 pub use plan::*;
 
@@ -29,6 +34,9 @@ use networks::Bitcoin;
 #[cfg(feature = "monero")]
 use networks::Monero;
 
+mod config;
+pub use config::*;
+
 mod additional_key;
 pub use additional_key::additional_key;
 
@@ -115,6 +123,7 @@ struct TributaryMutable<N: Network, D: Db> {
 
 type SubstrateMutable<N, D> = MultisigManager<D, N>;
 
+#[tracing::instrument(skip_all, fields(network = ?N::NETWORK, msg = msg.id))]
 async fn handle_coordinator_msg<D: Db, N: Network, Co: Coordinator>(
   txn: &mut D::Transaction<'_>,
   network: &N,
@@ -154,7 +163,7 @@ async fn handle_coordinator_msg<D: Db, N: Network, Co: Coordinator>(
       // Check that we've synced this block and can actually operate on it ourselves
       let latest = scanner.latest_scanned(key);
       if usize::try_from(context.network_latest_finalized_block).unwrap() < latest {
-        log::warn!(
+        tracing::warn!(
           "external network node disconnected/desynced from rest of the network. \
           our block: {latest:?}, network's acknowledged: {}",
           context.network_latest_finalized_block,
@@ -221,7 +230,8 @@ async fn handle_coordinator_msg<D: Db, N: Network, Co: Coordinator>(
         .substrate_signer
         .as_mut()
         .expect(
-          "coordinator told us to sign a batch when we don't have a Substrate signer at this time",
+          "coordinator told us to sign a batch or cosign a block when we don't have a Substrate \
+          signer at this time",
         )
         .handle(txn, msg)
         .await;
@@ -375,22 +385,9 @@ async fn handle_coordinator_msg<D: Db, N: Network, Co: Coordinator>(
 async fn boot<N: Network, D: Db>(
   raw_db: &mut D,
   network: &N,
-) -> (MainDb<N, D>, TributaryMutable<N, D>, SubstrateMutable<N, D>) {
+  entropy: Zeroizing<[u8; 32]>,
+) -> (TributaryMutable<N, D>, SubstrateMutable<N, D>) {
   let mut entropy_transcript = {
-    let entropy = Zeroizing::new(env::var("ENTROPY").expect("entropy wasn't specified"));
-    if entropy.len() != 64 {
-      panic!("entropy isn't the right length");
-    }
-    let mut bytes =
-      Zeroizing::new(hex::decode(entropy).map_err(|_| ()).expect("entropy wasn't hex-formatted"));
-    if bytes.len() != 32 {
-      bytes.zeroize();
-      panic!("entropy wasn't 32 bytes");
-    }
-    let mut entropy = Zeroizing::new([0; 32]);
-    let entropy_mut: &mut [u8] = entropy.as_mut();
-    entropy_mut.copy_from_slice(bytes.as_ref());
-
     let mut transcript = RecommendedTranscript::new(b"Serai Processor Entropy");
     transcript.append_message(b"entropy", entropy);
     transcript
@@ -418,8 +415,6 @@ async fn boot<N: Network, D: Db>(
   let mut substrate_signer = None;
   let mut signers = HashMap::new();
 
-  let main_db = MainDb::<N, _>::new(raw_db.clone());
-
   for (i, key) in current_keys.iter().enumerate() {
     let Some((substrate_keys, network_keys)) = key_gen.keys(key) else { continue };
     let network_key = network_keys.group_key();
@@ -457,22 +452,23 @@ async fn boot<N: Network, D: Db>(
     signers.insert(key.as_ref().to_vec(), signer);
   }
 
-  (main_db, TributaryMutable { key_gen, substrate_signer, signers }, multisig_manager)
+  (TributaryMutable { key_gen, substrate_signer, signers }, multisig_manager)
 }
 
 #[allow(clippy::await_holding_lock)] // Needed for txn, unfortunately can't be down-scoped
-async fn run<N: Network, D: Db, Co: Coordinator>(mut raw_db: D, network: N, mut coordinator: Co) {
+async fn run<N: Network, D: Db, Co: Coordinator>(
+  mut raw_db: D,
+  network: N,
+  entropy: Zeroizing<[u8; 32]>,
+  mut coordinator: Co,
+) {
   // We currently expect a contextless bidirectional mapping between these two values
   // (which is that any value of A can be interpreted as B and vice versa)
   // While we can write a contextual mapping, we have yet to do so
   // This check ensures no network which doesn't have a bidirectional mapping is defined
   assert_eq!(<N::Block as Block<N>>::Id::default().as_ref().len(), BlockHash([0u8; 32]).0.len());
 
-  let (main_db, mut tributary_mutable, mut substrate_mutable) = boot(&mut raw_db, &network).await;
-
-  // We can't load this from the DB as we can't guarantee atomic increments with the ack function
-  // TODO: Load with a slight tolerance
-  let mut last_coordinator_msg = None;
+  let (mut tributary_mutable, mut substrate_mutable) = boot(&mut raw_db, &network, entropy).await;
 
   loop {
     // The following select uses this txn in both branches, hence why needing a RwLock to pass it
@@ -491,12 +487,9 @@ async fn run<N: Network, D: Db, Co: Coordinator>(mut raw_db: D, network: N, mut
         let mut txn = txn.write().unwrap();
         let txn = &mut txn;
 
-        assert_eq!(msg.id, (last_coordinator_msg.unwrap_or(msg.id - 1) + 1));
-        last_coordinator_msg = Some(msg.id);
-
         // Only handle this if we haven't already
-        if !main_db.handled_message(msg.id) {
-          MainDb::<N, D>::handle_message(txn, msg.id);
+        if !MessageQueue::handled(&**txn, Service::Coordinator, msg.id) {
+          MessageQueue::handle(&mut **txn, Service::Coordinator, msg.id);
 
           // This is isolated to better think about how its ordered, or rather, about how the other
           // cases aren't ordered
@@ -611,7 +604,7 @@ async fn main() {
       existing(panic);
       const MSG: &str = "exiting the process due to a task panicking";
       println!("{MSG}");
-      log::error!("{MSG}");
+      tracing::error!("{MSG}");
       std::process::exit(1);
     }));
   }
@@ -619,30 +612,46 @@ async fn main() {
   if std::env::var("RUST_LOG").is_err() {
     std::env::set_var("RUST_LOG", serai_env::var("RUST_LOG").unwrap_or_else(|| "info".to_string()));
   }
-  env_logger::init();
+  // LOG_FORMAT selects between the default, compact, and JSON outputs, the latter allowing log
+  // aggregators to index events by their span fields
+  {
+    let subscriber =
+      tracing_subscriber::fmt().with_env_filter(tracing_subscriber::EnvFilter::from_default_env());
+    match serai_env::var("LOG_FORMAT").as_deref() {
+      None | Some("full") => subscriber.init(),
+      Some("compact") => subscriber.compact().init(),
+      Some("json") => subscriber.json().init(),
+      Some(format) => panic!("unrecognized LOG_FORMAT {format}"),
+    }
+  }
 
-  let db = serai_db::new_rocksdb(&env::var("DB_PATH").expect("path to DB wasn't specified"));
+  let config = Config::load();
 
-  // Network configuration
-  let url = {
-    let login = env::var("NETWORK_RPC_LOGIN").expect("network RPC login wasn't specified");
-    let hostname = env::var("NETWORK_RPC_HOSTNAME").expect("network RPC hostname wasn't specified");
-    let port = env::var("NETWORK_RPC_PORT").expect("network port domain wasn't specified");
-    "http://".to_string() + &login + "@" + &hostname + ":" + &port
-  };
-  let network_id = match env::var("NETWORK").expect("network wasn't specified").as_str() {
-    "bitcoin" => NetworkId::Bitcoin,
-    "monero" => NetworkId::Monero,
-    _ => panic!("unrecognized network"),
-  };
+  let mut db = serai_db::new_rocksdb(&config.db_path);
+  db::migrate_legacy_handled_messages(&mut db);
+
+  let health = Health::new();
+  tokio::spawn(
+    health.clone().serve(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), config.health_port)),
+  );
 
-  let coordinator = MessageQueue::from_env(Service::Processor(network_id));
+  let coordinator = Arc::new(MessageQueue::from_env(Service::Processor(config.network)));
+  // The message-queue is polled every second, so it should always have been recently contacted
+  health.check("coordinator", {
+    let coordinator = coordinator.clone();
+    move || {
+      coordinator
+        .last_contact()
+        .is_some_and(|last_contact| last_contact.elapsed() < Duration::from_secs(60))
+    }
+  });
 
-  match network_id {
+  let url = config.network_rpc;
+  match config.network {
     #[cfg(feature = "bitcoin")]
-    NetworkId::Bitcoin => run(db, Bitcoin::new(url).await, coordinator).await,
+    NetworkId::Bitcoin => run(db, Bitcoin::new(url).await, config.entropy, coordinator).await,
     #[cfg(feature = "monero")]
-    NetworkId::Monero => run(db, Monero::new(url), coordinator).await,
+    NetworkId::Monero => run(db, Monero::new(url), config.entropy, coordinator).await,
     _ => panic!("spawning a processor for an unsupported network"),
   }
 }
@@ -125,7 +125,7 @@ impl<N: Network, D: Db> MultisigsDb<N, D> {
     }
 
     if !found {
-      log::warn!("told to finish signing {} yet wasn't actively signing it", hex::encode(plan));
+      tracing::warn!("told to finish signing {} yet wasn't actively signing it", hex::encode(plan));
     }
 
     txn.put(Self::signing_key(key), signing);
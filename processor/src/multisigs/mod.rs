@@ -14,7 +14,7 @@ use serai_client::{
   tokens::primitives::{OutInstruction, OutInstructionWithBalance},
 };
 
-use log::{info, error};
+use tracing::{info, error};
 
 use tokio::time::sleep;
 
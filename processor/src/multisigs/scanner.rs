@@ -9,7 +9,7 @@ use std::{
 use ciphersuite::group::GroupEncoding;
 use frost::curve::Ciphersuite;
 
-use log::{info, debug, warn};
+use tracing::{info, debug, warn};
 use tokio::{
   sync::{RwLockReadGuard, RwLockWriteGuard, RwLock, mpsc},
   time::sleep,
@@ -320,7 +320,7 @@ impl<N: Network, D: Db> ScannerHandle<N, D> {
     // Get the number for this block
     let number = ScannerDb::<N, D>::block_number(txn, &id)
       .expect("main loop trying to operate on data we haven't scanned");
-    log::trace!("block {} was {number}", hex::encode(&id));
+    tracing::trace!("block {} was {number}", hex::encode(&id));
 
     let outputs = ScannerDb::<N, D>::save_scanned_block(txn, number);
     // This has a race condition if we try to ack a block we scanned on a prior boot, and we have
@@ -224,7 +224,7 @@ impl<N: Network> Scheduler<N> {
     mut utxos: Vec<N::Output>,
     key_for_any_change: <N::Curve as Ciphersuite>::G,
   ) -> Vec<Plan<N>> {
-    log::info!("adding {} outputs", utxos.len());
+    tracing::info!("adding {} outputs", utxos.len());
 
     let mut txs = vec![];
 
@@ -251,7 +251,7 @@ impl<N: Network> Scheduler<N> {
       self.utxos.push(utxo);
     }
 
-    log::info!("{} planned TXs have had their required inputs confirmed", txs.len());
+    tracing::info!("{} planned TXs have had their required inputs confirmed", txs.len());
     txs
   }
 
@@ -283,16 +283,16 @@ impl<N: Network> Scheduler<N> {
 
     let mut plans = self.add_outputs(utxos, key_for_any_change);
 
-    log::info!("scheduling {} new payments", payments.len());
+    tracing::info!("scheduling {} new payments", payments.len());
 
     // Add all new payments to the list of pending payments
     self.payments.extend(payments);
     let payments_at_start = self.payments.len();
-    log::info!("{} payments are now scheduled", payments_at_start);
+    tracing::info!("{} payments are now scheduled", payments_at_start);
 
     // If we don't have UTXOs available, don't try to continue
     if self.utxos.is_empty() {
-      log::info!("no utxos currently avilable");
+      tracing::info!("no utxos currently avilable");
       return plans;
     }
 
@@ -324,7 +324,7 @@ impl<N: Network> Scheduler<N> {
     for chunk in utxo_chunks.drain(..) {
       // TODO: While payments have their TXs' fees deducted from themselves, that doesn't hold here
       // We need the documented, but not yet implemented, virtual amount scheme to solve this
-      log::debug!("aggregating a chunk of {} inputs", N::MAX_INPUTS);
+      tracing::debug!("aggregating a chunk of {} inputs", N::MAX_INPUTS);
       plans.push(Plan {
         key: self.key,
         inputs: chunk,
@@ -381,7 +381,7 @@ impl<N: Network> Scheduler<N> {
 
     txn.put(scheduler_key::<D, _>(&self.key), self.serialize());
 
-    log::info!(
+    tracing::info!(
       "created {} plans containing {} payments to sign",
       plans.len(),
       payments_at_start - self.payments.len(),
@@ -408,7 +408,7 @@ impl<N: Network> Scheduler<N> {
     expected: u64,
     actual: Option<u64>,
   ) {
-    log::debug!("output expected to have {} had {:?} after fees", expected, actual);
+    tracing::debug!("output expected to have {} had {:?} after fees", expected, actual);
 
     // Get the payments this output is expected to handle
     let queued = self.queued_plans.get_mut(&expected).unwrap();
@@ -473,7 +473,11 @@ impl Network for Bitcoin {
         match self.rpc.get_block_number(&this_block_hash).await {
           Ok(number) => return number,
           Err(e) => {
-            log::error!("couldn't get the block number for {}: {}", hex::encode(this_block_hash), e)
+            tracing::error!(
+              "couldn't get the block number for {}: {}",
+              hex::encode(this_block_hash),
+              e
+            )
           }
         }
         sleep(Duration::from_secs(60)).await;
@@ -488,7 +492,7 @@ impl Network for Bitcoin {
           block = self.get_block(block_num).await;
           block.is_err()
         } {
-          log::error!("couldn't get block {}: {}", block_num, block.err().unwrap());
+          tracing::error!("couldn't get block {}: {}", block_num, block.err().unwrap());
           sleep(Duration::from_secs(60)).await;
         }
         block.unwrap()
@@ -12,7 +12,7 @@ use frost::{
 
 use serai_client::primitives::{NetworkId, Balance};
 
-use log::error;
+use tracing::error;
 
 use tokio::time::sleep;
 
@@ -149,7 +149,7 @@ impl<E: Eventuality> EventualitiesTracker<E> {
   }
 
   pub fn register(&mut self, block_number: usize, id: [u8; 32], eventuality: E) {
-    log::info!("registering eventuality for {}", hex::encode(id));
+    tracing::info!("registering eventuality for {}", hex::encode(id));
 
     let lookup = eventuality.lookup();
     if self.map.contains_key(&lookup) {
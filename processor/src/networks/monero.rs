@@ -291,7 +291,7 @@ impl Network for Monero {
       match Self::scanner(key).scan(&self.rpc, block).await {
         Ok(outputs) => break outputs,
         Err(e) => {
-          log::error!("couldn't scan block {}: {e:?}", hex::encode(block.id()));
+          tracing::error!("couldn't scan block {}: {e:?}", hex::encode(block.id()));
           sleep(Duration::from_secs(60)).await;
           continue;
         }
@@ -354,7 +354,11 @@ impl Network for Monero {
             tx = network.get_transaction(hash).await;
             tx.is_err()
           } {
-            log::error!("couldn't get transaction {}: {}", hex::encode(hash), tx.err().unwrap());
+            tracing::error!(
+              "couldn't get transaction {}: {}",
+              hex::encode(hash),
+              tx.err().unwrap()
+            );
             sleep(Duration::from_secs(60)).await;
           }
           tx.unwrap()
@@ -378,7 +382,7 @@ impl Network for Monero {
           block = self.get_block(block_num).await;
           block.is_err()
         } {
-          log::error!("couldn't get block {}: {}", block_num, block.err().unwrap());
+          tracing::error!("couldn't get block {}: {}", block_num, block.err().unwrap());
           sleep(Duration::from_secs(60)).await;
         }
         block.unwrap()
@@ -514,7 +518,7 @@ impl Network for Monero {
             }
           }
           TransactionError::RpcError(e) => {
-            log::error!("RpcError when preparing transaction: {e:?}");
+            tracing::error!("RpcError when preparing transaction: {e:?}");
             Err(NetworkError::ConnectionError)
           }
         },
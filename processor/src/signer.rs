@@ -9,7 +9,7 @@ use frost::{
   sign::{Writable, PreprocessMachine, SignMachine, SignatureMachine},
 };
 
-use log::{info, debug, warn, error};
+use tracing::{info, debug, warn, error};
 
 use scale::Encode;
 use messages::sign::*;
@@ -266,6 +266,7 @@ impl<N: Network, D: Db> Signer<N, D> {
     false
   }
 
+  #[tracing::instrument(skip_all, fields(id = %hex::encode(id), attempt = attempt))]
   async fn attempt(&mut self, txn: &mut D::Transaction<'_>, id: [u8; 32], attempt: u32) {
     if self.already_completed(txn, id) {
       return;
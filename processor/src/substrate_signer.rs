@@ -15,7 +15,7 @@ use frost::{
 };
 use frost_schnorrkel::Schnorrkel;
 
-use log::{info, debug, warn};
+use tracing::{info, debug, warn};
 
 use scale::Encode;
 use serai_client::{
@@ -39,6 +39,22 @@ fn sign_id(network: NetworkId, id: u32) -> [u8; 32] {
   res
 }
 
+// What's being signed
+#[derive(Clone, Debug)]
+enum Signable {
+  Batch(Batch),
+  Cosign { block_number: u64, block: [u8; 32] },
+}
+
+impl Signable {
+  fn message(&self) -> Vec<u8> {
+    match self {
+      Signable::Batch(batch) => batch_message(batch),
+      Signable::Cosign { block_number, block } => cosign_block_msg(*block_number, *block),
+    }
+  }
+}
+
 #[derive(Debug)]
 pub enum SubstrateSignerEvent {
   ProcessorMessage(ProcessorMessage),
@@ -83,7 +99,7 @@ pub struct SubstrateSigner<D: Db> {
   network: NetworkId,
   keys: ThresholdKeys<Ristretto>,
 
-  signable: HashMap<[u8; 32], Batch>,
+  signable: HashMap<[u8; 32], Signable>,
   attempt: HashMap<[u8; 32], u32>,
   preprocessing: HashMap<[u8; 32], AlgorithmSignMachine<Ristretto, Schnorrkel>>,
   signing: HashMap<[u8; 32], AlgorithmSignatureMachine<Ristretto, Schnorrkel>>,
@@ -125,13 +141,13 @@ impl<D: Db> SubstrateSigner<D> {
       // rebooted OR we detected the signed batch on chain
       // The latter is the expected flow for batches not actively being participated in
       None => {
-        warn!("not attempting batch {} #{}", hex::encode(id.id), id.attempt);
+        warn!("not attempting {} #{}", hex::encode(id.id), id.attempt);
         Err(())?;
       }
       Some(attempt) => {
         if attempt != &id.attempt {
           warn!(
-            "sent signing data for batch {} #{} yet we have attempt #{}",
+            "sent signing data for {} #{} yet we have attempt #{}",
             hex::encode(id.id),
             id.attempt,
             attempt
@@ -144,6 +160,7 @@ impl<D: Db> SubstrateSigner<D> {
     Ok(())
   }
 
+  #[tracing::instrument(skip_all, fields(id = %hex::encode(id), attempt = attempt))]
   async fn attempt(&mut self, txn: &mut D::Transaction<'_>, id: [u8; 32], attempt: u32) {
     // See above commentary for why this doesn't emit SignedBatch
     if SubstrateSignerDb::<D>::completed(txn, id) {
@@ -164,12 +181,10 @@ impl<D: Db> SubstrateSigner<D> {
     }
 
     // Start this attempt
-    let block = if let Some(batch) = self.signable.get(&id) {
-      batch.block
-    } else {
-      warn!("told to attempt signing a batch we aren't currently signing for");
+    if !self.signable.contains_key(&id) {
+      warn!("told to attempt signing something we aren't currently signing for");
       return;
-    };
+    }
 
     // Delete any existing machines
     self.preprocessing.remove(&id);
@@ -179,7 +194,7 @@ impl<D: Db> SubstrateSigner<D> {
     self.attempt.insert(id, attempt);
 
     let id = SignId { key: self.keys.group_key().to_bytes().to_vec(), id, attempt };
-    info!("signing batch {} #{}", hex::encode(id.id), id.attempt);
+    info!("signing {} #{}", hex::encode(id.id), id.attempt);
 
     // If we reboot mid-sign, the current design has us abort all signs and wait for latter
     // attempts/new signing protocols
@@ -195,7 +210,7 @@ impl<D: Db> SubstrateSigner<D> {
     // Only run if this hasn't already been attempted
     if SubstrateSignerDb::<D>::has_attempt(txn, &id) {
       warn!(
-        "already attempted batch {}, attempt #{}. this is an error if we didn't reboot",
+        "already attempted {}, attempt #{}. this is an error if we didn't reboot",
         hex::encode(id.id),
         id.attempt
       );
@@ -215,11 +230,16 @@ impl<D: Db> SubstrateSigner<D> {
     self.preprocessing.insert(id.id, machine);
 
     // Broadcast our preprocess
-    self.events.push_back(SubstrateSignerEvent::ProcessorMessage(
-      ProcessorMessage::BatchPreprocess { id, block, preprocess: preprocess.serialize() },
-    ));
+    let preprocess = preprocess.serialize();
+    self.events.push_back(SubstrateSignerEvent::ProcessorMessage(match &self.signable[&id.id] {
+      Signable::Batch(batch) => {
+        ProcessorMessage::BatchPreprocess { id, block: batch.block, preprocess }
+      }
+      Signable::Cosign { .. } => ProcessorMessage::CosignPreprocess { id, preprocess },
+    }));
   }
 
+  #[tracing::instrument(skip_all, fields(network = ?batch.network, batch = batch.id))]
   pub async fn sign(&mut self, txn: &mut D::Transaction<'_>, batch: Batch) {
     debug_assert_eq!(self.network, batch.network);
     let id = sign_id(batch.network, batch.id);
@@ -229,13 +249,54 @@ impl<D: Db> SubstrateSigner<D> {
       return;
     }
 
-    self.signable.insert(id, batch);
+    self.signable.insert(id, Signable::Batch(batch));
     self.attempt(txn, id, 0).await;
   }
 
+  #[tracing::instrument(skip_all, fields(block = block_number))]
+  async fn cosign(&mut self, txn: &mut D::Transaction<'_>, block_number: u64, block: [u8; 32]) {
+    if SubstrateSignerDb::<D>::completed(txn, block) {
+      debug!("Cosign order for block we've already cosigned");
+      return;
+    }
+
+    // Stop cosigning any prior blocks, as cosigning this block supersedes them
+    // Without this, cosigns which never complete would be kept in memory indefinitely, as they
+    // aren't re-attempted
+    let superseded = self
+      .signable
+      .iter()
+      .filter_map(|(id, signable)| match signable {
+        Signable::Cosign { block_number: prior, .. } => (*prior < block_number).then_some(*id),
+        Signable::Batch(_) => None,
+      })
+      .collect::<Vec<_>>();
+    for id in superseded {
+      debug!("cosign of {} was superseded", hex::encode(id));
+      self.signable.remove(&id);
+      self.attempt.remove(&id);
+      self.preprocessing.remove(&id);
+      self.signing.remove(&id);
+    }
+
+    self.signable.insert(block, Signable::Cosign { block_number, block });
+    self.attempt(txn, block, 0).await;
+  }
+
   pub async fn handle(&mut self, txn: &mut D::Transaction<'_>, msg: CoordinatorMessage) {
     match msg {
-      CoordinatorMessage::BatchPreprocesses { id, mut preprocesses } => {
+      CoordinatorMessage::CosignSubstrateBlock { id, block_number } => {
+        // The coordinator asks every set it's in to cosign, yet only the set whose keys we're
+        // currently using for Substrate does so
+        if id.key != self.keys.group_key().to_bytes().as_ref() {
+          debug!("not cosigning block {} as it's for another key", hex::encode(id.id));
+          return;
+        }
+        self.cosign(txn, block_number, id.id).await;
+      }
+
+      CoordinatorMessage::BatchPreprocesses { id, mut preprocesses } |
+      CoordinatorMessage::CosignPreprocesses { id, mut preprocesses } => {
         if self.verify_id(&id).is_err() {
           return;
         }
@@ -270,22 +331,24 @@ impl<D: Db> SubstrateSigner<D> {
           Err(e) => todo!("malicious signer: {:?}", e),
         };
 
-        let (machine, share) =
-          match machine.sign(preprocesses, &batch_message(&self.signable[&id.id])) {
-            Ok(res) => res,
-            Err(e) => todo!("malicious signer: {:?}", e),
-          };
+        let signable = &self.signable[&id.id];
+        let (machine, share) = match machine.sign(preprocesses, &signable.message()) {
+          Ok(res) => res,
+          Err(e) => todo!("malicious signer: {:?}", e),
+        };
         self.signing.insert(id.id, machine);
 
         // Broadcast our share
         let mut share_bytes = [0; 32];
         share_bytes.copy_from_slice(&share.serialize());
-        self.events.push_back(SubstrateSignerEvent::ProcessorMessage(
-          ProcessorMessage::BatchShare { id, share: share_bytes },
-        ));
+        self.events.push_back(SubstrateSignerEvent::ProcessorMessage(match signable {
+          Signable::Batch(_) => ProcessorMessage::BatchShare { id, share: share_bytes },
+          Signable::Cosign { .. } => ProcessorMessage::CosignShare { id, share: share_bytes },
+        }));
       }
 
-      CoordinatorMessage::BatchShares { id, mut shares } => {
+      CoordinatorMessage::BatchShares { id, mut shares } |
+      CoordinatorMessage::CosignShares { id, mut shares } => {
         if self.verify_id(&id).is_err() {
           return;
         }
@@ -329,21 +392,32 @@ impl<D: Db> SubstrateSigner<D> {
           Err(e) => todo!("malicious signer: {:?}", e),
         };
 
-        info!("signed batch {} with attempt #{}", hex::encode(id.id), id.attempt);
-
-        let batch =
-          SignedBatch { batch: self.signable.remove(&id.id).unwrap(), signature: sig.into() };
+        info!("signed {} with attempt #{}", hex::encode(id.id), id.attempt);
 
-        // Save the batch in case it's needed for recovery
-        SubstrateSignerDb::<D>::save_batch(txn, &batch);
+        let event = match self.signable.remove(&id.id).unwrap() {
+          Signable::Batch(batch) => {
+            let batch = SignedBatch { batch, signature: sig.into() };
+            // Save the batch in case it's needed for recovery
+            SubstrateSignerDb::<D>::save_batch(txn, &batch);
+            SubstrateSignerEvent::SignedBatch(batch)
+          }
+          Signable::Cosign { block_number, block } => {
+            SubstrateSignerEvent::ProcessorMessage(ProcessorMessage::CosignedBlock {
+              key: id.key.clone(),
+              block_number,
+              block,
+              signature: sig.to_bytes().to_vec(),
+            })
+          }
+        };
         SubstrateSignerDb::<D>::complete(txn, id.id);
 
-        // Stop trying to sign for this batch
+        // Stop trying to sign for this ID
         assert!(self.attempt.remove(&id.id).is_some());
         assert!(self.preprocessing.remove(&id.id).is_none());
         assert!(self.signing.remove(&id.id).is_none());
 
-        self.events.push_back(SubstrateSignerEvent::SignedBatch(batch));
+        self.events.push_back(event);
       }
 
       CoordinatorMessage::BatchReattempt { id } => {
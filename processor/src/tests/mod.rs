@@ -17,7 +17,7 @@ pub(crate) use addresses::test_addresses;
 
 // Effective Once
 lazy_static::lazy_static! {
-  static ref INIT_LOGGER: () = env_logger::init();
+  static ref INIT_LOGGER: () = tracing_subscriber::fmt::init();
 }
 
 #[macro_export]
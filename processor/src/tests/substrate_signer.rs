@@ -158,3 +158,229 @@ async fn test_substrate_signer() {
     assert!(signer.events.pop_front().is_none());
   }
 }
+
+#[tokio::test]
+async fn test_substrate_signer_cosign() {
+  let keys = key_gen::<_, Ristretto>(&mut OsRng);
+  let group_key = keys.values().next().unwrap().group_key().to_bytes();
+
+  let block_number = 100;
+  let mut block = [0; 32];
+  OsRng.fill_bytes(&mut block);
+  let id = SignId { key: group_key.to_vec(), id: block, attempt: 0 };
+
+  let mut signers = HashMap::new();
+  let mut dbs = HashMap::new();
+  for (i, keys) in &keys {
+    let mut signer = SubstrateSigner::<MemDb>::new(NetworkId::Monero, keys.clone());
+    let mut db = MemDb::new();
+    let mut txn = db.txn();
+    // Requests to cosign with another key should be ignored
+    signer
+      .handle(
+        &mut txn,
+        CoordinatorMessage::CosignSubstrateBlock {
+          id: SignId { key: vec![0xff; 32], ..id.clone() },
+          block_number,
+        },
+      )
+      .await;
+    assert!(signer.events.is_empty());
+    signer
+      .handle(&mut txn, CoordinatorMessage::CosignSubstrateBlock { id: id.clone(), block_number })
+      .await;
+    txn.commit();
+
+    signers.insert(*i, signer);
+    dbs.insert(*i, db);
+  }
+
+  // Use the first t participants as the signing set
+  let t = keys.values().next().unwrap().params().t();
+  let signing_set = (1 ..= t).map(|i| Participant::new(i).unwrap()).collect::<Vec<_>>();
+
+  // All participants should emit a preprocess
+  let mut preprocesses = HashMap::new();
+  for (i, signer) in &mut signers {
+    if let SubstrateSignerEvent::ProcessorMessage(ProcessorMessage::CosignPreprocess {
+      id: preprocess_id,
+      preprocess,
+    }) = signer.events.pop_front().unwrap()
+    {
+      assert_eq!(preprocess_id, id);
+      if signing_set.contains(i) {
+        preprocesses.insert(*i, preprocess);
+      }
+    } else {
+      panic!("didn't get cosign preprocess back");
+    }
+  }
+
+  let mut shares = HashMap::new();
+  for i in &signing_set {
+    let mut txn = dbs.get_mut(i).unwrap().txn();
+    signers
+      .get_mut(i)
+      .unwrap()
+      .handle(
+        &mut txn,
+        CoordinatorMessage::CosignPreprocesses {
+          id: id.clone(),
+          preprocesses: clone_without(&preprocesses, i),
+        },
+      )
+      .await;
+    txn.commit();
+
+    if let SubstrateSignerEvent::ProcessorMessage(ProcessorMessage::CosignShare {
+      id: share_id,
+      share,
+    }) = signers.get_mut(i).unwrap().events.pop_front().unwrap()
+    {
+      assert_eq!(share_id, id);
+      shares.insert(*i, share);
+    } else {
+      panic!("didn't get cosign share back");
+    }
+  }
+
+  for i in &signing_set {
+    let mut txn = dbs.get_mut(i).unwrap().txn();
+    signers
+      .get_mut(i)
+      .unwrap()
+      .handle(
+        &mut txn,
+        CoordinatorMessage::CosignShares { id: id.clone(), shares: clone_without(&shares, i) },
+      )
+      .await;
+    txn.commit();
+
+    if let SubstrateSignerEvent::ProcessorMessage(ProcessorMessage::CosignedBlock {
+      key,
+      block_number: cosigned_number,
+      block: cosigned_block,
+      signature,
+    }) = signers.get_mut(i).unwrap().events.pop_front().unwrap()
+    {
+      assert_eq!(key, group_key.to_vec());
+      assert_eq!((cosigned_number, cosigned_block), (block_number, block));
+      assert!(Public::from_raw(group_key).verify(
+        &cosign_block_msg(block_number, block),
+        &Signature::from_raw(signature.try_into().unwrap())
+      ));
+    } else {
+      panic!("didn't get cosigned block back");
+    }
+
+    // A completed cosign shouldn't be started again
+    let mut txn = dbs.get_mut(i).unwrap().txn();
+    signers
+      .get_mut(i)
+      .unwrap()
+      .handle(&mut txn, CoordinatorMessage::CosignSubstrateBlock { id: id.clone(), block_number })
+      .await;
+    txn.commit();
+  }
+
+  for (_, mut signer) in signers.drain() {
+    assert!(signer.events.pop_front().is_none());
+  }
+}
+
+#[tokio::test]
+async fn test_substrate_signer_cosign_superseded() {
+  let keys = key_gen::<_, Ristretto>(&mut OsRng);
+  let group_key = keys.values().next().unwrap().group_key().to_bytes();
+
+  let mut prior = [0; 32];
+  OsRng.fill_bytes(&mut prior);
+  let prior_id = SignId { key: group_key.to_vec(), id: prior, attempt: 0 };
+  let mut block = [0; 32];
+  OsRng.fill_bytes(&mut block);
+  let id = SignId { key: group_key.to_vec(), id: block, attempt: 0 };
+
+  let mut signers = HashMap::new();
+  let mut dbs = HashMap::new();
+  for (i, keys) in &keys {
+    let mut signer = SubstrateSigner::<MemDb>::new(NetworkId::Monero, keys.clone());
+    let mut db = MemDb::new();
+    let mut txn = db.txn();
+    signer
+      .handle(
+        &mut txn,
+        CoordinatorMessage::CosignSubstrateBlock { id: prior_id.clone(), block_number: 100 },
+      )
+      .await;
+    // Cosigning a later block should supersede the prior cosign
+    signer
+      .handle(
+        &mut txn,
+        CoordinatorMessage::CosignSubstrateBlock { id: id.clone(), block_number: 101 },
+      )
+      .await;
+    txn.commit();
+
+    signers.insert(*i, signer);
+    dbs.insert(*i, db);
+  }
+
+  let t = keys.values().next().unwrap().params().t();
+  let signing_set = (1 ..= t).map(|i| Participant::new(i).unwrap()).collect::<Vec<_>>();
+
+  // Both cosigns will have been preprocessed
+  let mut prior_preprocesses = HashMap::new();
+  let mut preprocesses = HashMap::new();
+  for (i, signer) in &mut signers {
+    for (expected_id, preprocesses) in
+      [(&prior_id, &mut prior_preprocesses), (&id, &mut preprocesses)]
+    {
+      if let SubstrateSignerEvent::ProcessorMessage(ProcessorMessage::CosignPreprocess {
+        id: preprocess_id,
+        preprocess,
+      }) = signer.events.pop_front().unwrap()
+      {
+        assert_eq!(&preprocess_id, expected_id);
+        if signing_set.contains(i) {
+          preprocesses.insert(*i, preprocess);
+        }
+      } else {
+        panic!("didn't get cosign preprocess back");
+      }
+    }
+  }
+
+  for i in &signing_set {
+    let mut txn = dbs.get_mut(i).unwrap().txn();
+    let signer = signers.get_mut(i).unwrap();
+
+    // The superseded cosign should no longer be signed for
+    signer
+      .handle(
+        &mut txn,
+        CoordinatorMessage::CosignPreprocesses {
+          id: prior_id.clone(),
+          preprocesses: clone_without(&prior_preprocesses, i),
+        },
+      )
+      .await;
+    assert!(signer.events.is_empty());
+
+    // While the latest cosign should still be
+    signer
+      .handle(
+        &mut txn,
+        CoordinatorMessage::CosignPreprocesses {
+          id: id.clone(),
+          preprocesses: clone_without(&preprocesses, i),
+        },
+      )
+      .await;
+    txn.commit();
+    assert!(matches!(
+      signer.events.pop_front().unwrap(),
+      SubstrateSignerEvent::ProcessorMessage(ProcessorMessage::CosignShare { id: share_id, .. })
+        if share_id == id
+    ));
+  }
+}
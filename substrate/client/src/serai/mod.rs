@@ -1,6 +1,9 @@
+use core::{future::Future, time::Duration};
+use std::sync::{Arc, RwLock};
+
 use thiserror::Error;
 
-use futures::stream::{Stream, StreamExt};
+use futures::stream::{self, Stream};
 
 use scale::{Encode, Decode, Compact};
 mod scale_value;
@@ -21,7 +24,10 @@ use subxt::{
     extrinsic_params::{BaseExtrinsicParams, BaseExtrinsicParamsBuilder},
   },
   tx::{Signer, Payload, TxClient},
-  rpc::types::{ChainBlock, ChainBlockExtrinsic},
+  rpc::{
+    types::{ChainBlock, ChainBlockExtrinsic},
+    Subscription,
+  },
   Config as SubxtConfig, OnlineClient,
 };
 
@@ -131,14 +137,128 @@ pub enum SeraiError {
   InvalidRuntime,
   #[error("node is faulty")]
   InvalidNode,
+  #[error("no healthy node was available")]
+  UnhealthyNode,
 }
 
+// The amount of attempts made for a request before its error is returned.
+const REQUEST_ATTEMPTS: u32 = 5;
+// The delay before the first retry, doubled after every further failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+// The maximum delay between attempts to re-establish a subscription.
+const MAX_SUBSCRIPTION_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A client for a Serai node.
+///
+/// The client may be configured with multiple nodes. If communication with the node currently in
+/// use fails, the client will fail over to the next healthy node and retry the request.
 #[derive(Clone)]
-pub struct Serai(OnlineClient<SeraiConfig>);
+pub struct Serai {
+  urls: Arc<Vec<String>>,
+  // The index of the node currently in use, and the client for it
+  client: Arc<RwLock<(usize, OnlineClient<SeraiConfig>)>>,
+}
 
 impl Serai {
   pub async fn new(url: &str) -> Result<Self, SeraiError> {
-    Ok(Serai(OnlineClient::<SeraiConfig>::from_url(url).await.map_err(SeraiError::RpcError)?))
+    Self::new_with_endpoints(&[url]).await
+  }
+
+  /// Create a client which fails over between the specified nodes.
+  ///
+  /// The first node which is reachable and healthy, in the order specified, will be used.
+  pub async fn new_with_endpoints(urls: &[&str]) -> Result<Self, SeraiError> {
+    assert!(!urls.is_empty(), "no Serai nodes were specified");
+    let urls = urls.iter().map(|url| url.to_string()).collect::<Vec<_>>();
+    let client = Self::connect(&urls, 0).await?;
+    Ok(Serai { urls: Arc::new(urls), client: Arc::new(RwLock::new(client)) })
+  }
+
+  /// The URLs of the nodes this client may use.
+  pub fn endpoints(&self) -> &[String] {
+    &self.urls
+  }
+
+  // Check a node is healthy, being reachable and not syncing
+  async fn healthy(client: &OnlineClient<SeraiConfig>) -> Result<(), SeraiError> {
+    let health = client.rpc().system_health().await.map_err(SeraiError::RpcError)?;
+    if health.is_syncing || (health.should_have_peers && (health.peers == 0)) {
+      Err(SeraiError::UnhealthyNode)?;
+    }
+    Ok(())
+  }
+
+  // Connect to the first healthy node, starting from the specified index
+  async fn connect(
+    urls: &[String],
+    start: usize,
+  ) -> Result<(usize, OnlineClient<SeraiConfig>), SeraiError> {
+    let mut last_err = SeraiError::UnhealthyNode;
+    for i in 0 .. urls.len() {
+      let index = (start + i) % urls.len();
+      let client = match OnlineClient::<SeraiConfig>::from_url(&urls[index]).await {
+        Ok(client) => client,
+        Err(e) => {
+          last_err = SeraiError::RpcError(e);
+          continue;
+        }
+      };
+      match Self::healthy(&client).await {
+        Ok(()) => return Ok((index, client)),
+        Err(e) => last_err = e,
+      }
+    }
+    Err(last_err)
+  }
+
+  fn client(&self) -> (usize, OnlineClient<SeraiConfig>) {
+    self.client.read().unwrap().clone()
+  }
+
+  // Fail over from the node at the specified index to the next healthy node
+  async fn failover(&self, failed: usize) {
+    // If another request already failed over, don't do so again
+    if self.client().0 != failed {
+      return;
+    }
+    // If no other node is healthy, continue using the existing client
+    let Ok(client) = Self::connect(&self.urls, failed + 1).await else { return };
+    let mut current = self.client.write().unwrap();
+    if current.0 == failed {
+      *current = client;
+    }
+  }
+
+  // Rebuild the connection, to the node currently in use if it's still healthy and to the next
+  // healthy node otherwise
+  async fn reconnect(&self) -> Result<(), SeraiError> {
+    let (index, _) = self.client();
+    let client = Self::connect(&self.urls, index).await?;
+    *self.client.write().unwrap() = client;
+    Ok(())
+  }
+
+  // Perform a request, retrying with exponential backoff (and failing over as needed) when
+  // communication with the node fails
+  async fn call<R, Fut: Future<Output = Result<R, SeraiError>>>(
+    &self,
+    request: impl Fn(OnlineClient<SeraiConfig>) -> Fut,
+  ) -> Result<R, SeraiError> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+      let (index, client) = self.client();
+      match request(client).await {
+        Err(SeraiError::RpcError(e)) if attempt < REQUEST_ATTEMPTS => {
+          log::warn!("request to serai node {} failed: {e}", self.urls[index]);
+          self.failover(index).await;
+          tokio::time::sleep(backoff).await;
+          backoff *= 2;
+          attempt += 1;
+        }
+        res => return res,
+      }
+    }
   }
 
   async fn storage<R: Decode>(
@@ -148,16 +268,16 @@ impl Serai {
     keys: Option<Vec<Value>>,
     block: [u8; 32],
   ) -> Result<Option<R>, SeraiError> {
-    let storage = self.0.storage();
     #[allow(clippy::unwrap_or_default)]
-    let address = subxt::dynamic::storage(pallet, name, keys.unwrap_or(vec![]));
-    debug_assert!(storage.validate(&address).is_ok(), "invalid storage address");
+    let address = &subxt::dynamic::storage(pallet, name, keys.unwrap_or(vec![]));
 
-    storage
-      .at(block.into())
-      .fetch(&address)
-      .await
-      .map_err(SeraiError::RpcError)?
+    self
+      .call(|client| async move {
+        let storage = client.storage();
+        debug_assert!(storage.validate(address).is_ok(), "invalid storage address");
+        storage.at(block.into()).fetch(address).await.map_err(SeraiError::RpcError)
+      })
+      .await?
       .map(|res| R::decode(&mut res.encoded()).map_err(|_| SeraiError::InvalidRuntime))
       .transpose()
   }
@@ -168,7 +288,13 @@ impl Serai {
     filter: impl Fn(&E) -> bool,
   ) -> Result<Vec<E>, SeraiError> {
     let mut res = vec![];
-    for event in self.0.events().at(block.into()).await.map_err(SeraiError::RpcError)?.iter() {
+    let events =
+      self
+        .call(|client| async move {
+          client.events().at(block.into()).await.map_err(SeraiError::RpcError)
+        })
+        .await?;
+    for event in events.iter() {
       let event = event.map_err(|_| SeraiError::InvalidRuntime)?;
       if PalletInfo::index::<P>().unwrap() == usize::from(event.pallet_index()) {
         let mut with_variant: &[u8] =
@@ -183,17 +309,27 @@ impl Serai {
   }
 
   pub async fn get_latest_block_hash(&self) -> Result<[u8; 32], SeraiError> {
-    Ok(self.0.rpc().finalized_head().await.map_err(SeraiError::RpcError)?.into())
+    Ok(
+      self
+        .call(
+          |client| async move { client.rpc().finalized_head().await.map_err(SeraiError::RpcError) },
+        )
+        .await?
+        .into(),
+    )
   }
 
   pub async fn get_latest_block(&self) -> Result<Block, SeraiError> {
     Block::new(
       self
-        .0
-        .rpc()
-        .block(Some(self.0.rpc().finalized_head().await.map_err(SeraiError::RpcError)?))
-        .await
-        .map_err(SeraiError::RpcError)?
+        .call(|client| async move {
+          let rpc = client.rpc();
+          rpc
+            .block(Some(rpc.finalized_head().await.map_err(SeraiError::RpcError)?))
+            .await
+            .map_err(SeraiError::RpcError)
+        })
+        .await?
         .ok_or(SeraiError::InvalidNode)?
         .block,
     )
@@ -209,8 +345,11 @@ impl Serai {
       return Ok(Some(true));
     }
 
-    let Some(finalized) =
-      self.0.rpc().header(Some(finalized)).await.map_err(SeraiError::RpcError)?
+    let Some(finalized) = self
+      .call(|client| async move {
+        client.rpc().header(Some(finalized)).await.map_err(SeraiError::RpcError)
+      })
+      .await?
     else {
       return Ok(None);
     };
@@ -224,8 +363,12 @@ impl Serai {
     // If we request the hash of this block's number, Substrate will return the hash on the main
     // chain
     // If that hash is this hash, this block is finalized
-    let Some(hash) =
-      self.0.rpc().block_hash(Some(header.number().into())).await.map_err(SeraiError::RpcError)?
+    let number = header.number();
+    let Some(hash) = self
+      .call(|client| async move {
+        client.rpc().block_hash(Some(number.into())).await.map_err(SeraiError::RpcError)
+      })
+      .await?
     else {
       // This is an error since there is a block at this index
       Err(SeraiError::InvalidNode)?
@@ -235,7 +378,11 @@ impl Serai {
   }
 
   pub async fn get_block(&self, hash: [u8; 32]) -> Result<Option<Block>, SeraiError> {
-    let Some(res) = self.0.rpc().block(Some(hash.into())).await.map_err(SeraiError::RpcError)?
+    let Some(res) = self
+      .call(|client| async move {
+        client.rpc().block(Some(hash.into())).await.map_err(SeraiError::RpcError)
+      })
+      .await?
     else {
       return Ok(None);
     };
@@ -254,33 +401,66 @@ impl Serai {
   // is_finalized method, which at least requires the header
   // In practice, the block is likely more useful than the header
   pub async fn get_block_by_number(&self, number: u64) -> Result<Option<Block>, SeraiError> {
-    let Some(hash) =
-      self.0.rpc().block_hash(Some(number.into())).await.map_err(SeraiError::RpcError)?
+    let Some(hash) = self
+      .call(|client| async move {
+        client.rpc().block_hash(Some(number.into())).await.map_err(SeraiError::RpcError)
+      })
+      .await?
     else {
       return Ok(None);
     };
     self.get_block(hash.into()).await
   }
 
+  async fn subscribe_finalized_block_headers(&self) -> Result<Subscription<Header>, SeraiError> {
+    self
+      .call(|client| async move {
+        client.rpc().subscribe_finalized_block_headers().await.map_err(SeraiError::RpcError)
+      })
+      .await
+  }
+
   /// A stream which yields whenever new block(s) have been finalized.
+  ///
+  /// If the underlying subscription fails, it will be transparently re-established, failing over
+  /// to another node as needed. Since blocks may have been finalized while resubscribing, a
+  /// notification is yielded upon every resubscription.
   pub async fn newly_finalized_block(
     &self,
-  ) -> Result<impl Stream<Item = Result<(), SeraiError>>, SeraiError> {
-    Ok(self.0.rpc().subscribe_finalized_block_headers().await.map_err(SeraiError::RpcError)?.map(
-      |next| {
-        next.map_err(SeraiError::RpcError)?;
-        Ok(())
+  ) -> Result<impl Stream<Item = Result<(), SeraiError>> + Unpin, SeraiError> {
+    let subscription = self.subscribe_finalized_block_headers().await?;
+    Ok(Box::pin(stream::unfold(
+      (self.clone(), Some(subscription), INITIAL_BACKOFF),
+      |(serai, subscription, mut backoff)| async move {
+        if let Some(mut subscription) = subscription {
+          if let Some(Ok(_)) = subscription.next().await {
+            return Some((Ok(()), (serai, Some(subscription), INITIAL_BACKOFF)));
+          }
+        } else {
+          // The prior attempt to resubscribe failed, so wait before trying again
+          tokio::time::sleep(backoff).await;
+          backoff = (backoff * 2).min(MAX_SUBSCRIPTION_BACKOFF);
+        }
+
+        // The subscription errored or was closed, so rebuild the connection and resubscribe
+        if let Err(e) = serai.reconnect().await {
+          return Some((Err(e), (serai, None, backoff)));
+        }
+        match serai.subscribe_finalized_block_headers().await {
+          Ok(resubscription) => Some((Ok(()), (serai, Some(resubscription), INITIAL_BACKOFF))),
+          Err(e) => Some((Err(e), (serai, None, backoff))),
+        }
       },
-    ))
+    )))
   }
 
   pub async fn get_nonce(&self, address: &SeraiAddress) -> Result<u32, SeraiError> {
+    let address = &sp_core::sr25519::Public(address.0).to_string();
     self
-      .0
-      .rpc()
-      .system_account_next_index(&sp_core::sr25519::Public(address.0).to_string())
+      .call(|client| async move {
+        client.rpc().system_account_next_index(address).await.map_err(SeraiError::RpcError)
+      })
       .await
-      .map_err(SeraiError::RpcError)
   }
 
   fn unsigned<P: 'static, C: Encode>(call: &C) -> Encoded {
@@ -308,7 +488,7 @@ impl Serai {
     nonce: u32,
     params: BaseExtrinsicParamsBuilder<SeraiConfig, Tip>,
   ) -> Result<Encoded, SeraiError> {
-    TxClient::new(self.0.offline())
+    TxClient::new(self.client().1.offline())
       .create_signed_with_nonce(payload, signer, nonce, params)
       .map(|tx| Encoded(tx.into_encoded()))
       // TODO: Don't have this potentially return an error (requires modifying the Payload type)
@@ -319,7 +499,11 @@ impl Serai {
     // Drop the hash, which is the hash of the raw TX, as TXs are allowed to share hashes and this
     // hash is practically useless/unsafe
     // If we are to return something, it should be block included in and position within block
-    self.0.rpc().submit_extrinsic(tx).await.map(|_| ()).map_err(SeraiError::RpcError)
+    self
+      .call(|client| async move {
+        client.rpc().submit_extrinsic(tx).await.map(|_| ()).map_err(SeraiError::RpcError)
+      })
+      .await
   }
 
   pub async fn get_sri_balance(
@@ -2,7 +2,7 @@ use sp_core::sr25519::Signature;
 
 use serai_runtime::{validator_sets, ValidatorSets, Runtime};
 pub use validator_sets::primitives;
-use primitives::{ValidatorSet, ValidatorSetData, KeyPair};
+use primitives::{Session, SessionSchedule, ValidatorSet, ValidatorSetData, KeyPair};
 
 use subxt::utils::Encoded;
 
@@ -31,6 +31,25 @@ impl Serai {
       .await
   }
 
+  /// The latest session for which validator sets have been declared.
+  pub async fn get_latest_session(&self, block: [u8; 32]) -> Result<Option<Session>, SeraiError> {
+    self.storage(PALLET, "LatestSession", None, block).await
+  }
+
+  /// The session currently in effect.
+  pub async fn get_current_session(&self, block: [u8; 32]) -> Result<Option<Session>, SeraiError> {
+    self.storage(PALLET, "CurrentSession", None, block).await
+  }
+
+  /// The schedule for a session which has started.
+  pub async fn get_session_schedule(
+    &self,
+    block: [u8; 32],
+    session: Session,
+  ) -> Result<Option<SessionSchedule>, SeraiError> {
+    self.storage(PALLET, "SessionSchedules", Some(vec![scale_value(session)]), block).await
+  }
+
   pub async fn get_validator_set(
     &self,
     set: ValidatorSet,
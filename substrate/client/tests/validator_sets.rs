@@ -33,19 +33,19 @@ serai_test!(
     let serai = serai().await;
 
     // Make sure the genesis is as expected
-    assert_eq!(
-      serai
-        .get_new_set_events(serai.get_block_by_number(0).await.unwrap().unwrap().hash())
-        .await
-        .unwrap(),
-      [NetworkId::Bitcoin, NetworkId::Ethereum, NetworkId::Monero]
-        .iter()
-        .copied()
-        .map(|network| ValidatorSetsEvent::NewSet {
-          set: ValidatorSet { session: Session(0), network }
-        })
-        .collect::<Vec<_>>(),
-    );
+    // The sets for the first session are declared by the genesis, with the sets for the session
+    // after declared when the session pallet builds its genesis
+    let genesis = serai.get_block_by_number(0).await.unwrap().unwrap().hash();
+    assert_eq!(serai.get_latest_session(genesis).await.unwrap(), Some(Session(1)));
+    for session in [Session(0), Session(1)] {
+      for network in [NetworkId::Bitcoin, NetworkId::Ethereum, NetworkId::Monero] {
+        assert!(serai
+          .get_validator_set(ValidatorSet { session, network })
+          .await
+          .unwrap()
+          .is_some());
+      }
+    }
 
     let set_data = serai.get_validator_set(set).await.unwrap().unwrap();
     assert_eq!(set_data.network, NETWORKS[&NetworkId::Bitcoin]);
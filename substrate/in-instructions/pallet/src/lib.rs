@@ -80,28 +80,19 @@ pub mod pallet {
   }
 
   fn key_for_network<T: Config>(network: NetworkId) -> Result<Public, InvalidTransaction> {
-    // TODO: Get the latest session
-    let session = Session(0);
+    let latest = ValidatorSets::<T>::latest_session().unwrap_or(Session(0));
 
-    let mut set = ValidatorSet { session, network };
     // TODO: If this session just set their keys, it'll invalidate any batches in the mempool
     // Should there be a transitory period/future-set cut off?
-    if let Some(keys) = ValidatorSets::<T>::keys(set) {
-      Ok(keys.0)
-    } else {
-      // If this set hasn't set their keys yet, use the previous set's
-      if set.session.0 == 0 {
-        // Since there haven't been any keys set, no signature can legitimately exist
-        Err(InvalidTransaction::BadProof)?;
-      }
-      set.session.0 -= 1;
-
+    // If the latest sets haven't set their keys yet, use the most recent set's which has
+    for session in (0 ..= latest.0).rev() {
+      let set = ValidatorSet { session: Session(session), network };
       if let Some(keys) = ValidatorSets::<T>::keys(set) {
-        Ok(keys.0)
-      } else {
-        Err(InvalidTransaction::BadProof)?
+        return Ok(keys.0);
       }
     }
+    // Since there haven't been any keys set, no signature can legitimately exist
+    Err(InvalidTransaction::BadProof)
   }
 
   #[pallet::call]
@@ -16,22 +16,14 @@ use sp_application_crypto::sr25519::Signature;
 use sp_std::vec::Vec;
 use sp_runtime::RuntimeDebug;
 
-use serai_primitives::{BlockHash, Balance, NetworkId, SeraiAddress, ExternalAddress, Data};
+use serai_primitives::{BlockHash, NetworkId, ExternalAddress};
+pub use serai_primitives::{InInstruction, InInstructionWithBalance};
 
 mod shorthand;
 pub use shorthand::*;
 
 pub const MAX_BATCH_SIZE: usize = 25_000; // ~25kb
 
-#[derive(
-  Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
-)]
-#[cfg_attr(feature = "std", derive(Zeroize))]
-pub enum InInstruction {
-  Transfer(SeraiAddress),
-  Dex(Data),
-}
-
 #[derive(
   Clone,
   PartialEq,
@@ -50,15 +42,6 @@ pub struct RefundableInInstruction {
   pub instruction: InInstruction,
 }
 
-#[derive(
-  Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
-)]
-#[cfg_attr(feature = "std", derive(Zeroize))]
-pub struct InInstructionWithBalance {
-  pub instruction: InInstruction,
-  pub balance: Balance,
-}
-
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode, TypeInfo, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Zeroize))]
 pub struct Batch {
@@ -6,6 +6,9 @@ use serde::{Serialize, Deserialize};
 use scale::{Encode, Decode, MaxEncodedLen};
 use scale_info::TypeInfo;
 
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+
 use sp_core::sr25519::Public;
 pub use sp_core::sr25519::Signature;
 #[cfg(feature = "std")]
@@ -31,6 +34,7 @@ pub type PublicKey = Public;
   TypeInfo,
 )]
 #[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
 pub struct SeraiAddress(pub [u8; 32]);
 impl SeraiAddress {
   pub fn new(key: [u8; 32]) -> SeraiAddress {
@@ -11,6 +11,9 @@ use serde::{Serialize, Deserialize};
 use scale::{Encode, Decode, MaxEncodedLen};
 use scale_info::TypeInfo;
 
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+
 /// The type used for amounts within Substrate.
 // Distinct from Amount due to Substrate's requirements on this type.
 // While Amount could have all the necessary traits implemented, not only are they many, it'd make
@@ -33,6 +36,7 @@ pub type SubstrateAmount = u64;
   TypeInfo,
 )]
 #[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
 pub struct Amount(pub SubstrateAmount);
 
 impl Add for Amount {
@@ -8,6 +8,9 @@ use serde::{Serialize, Deserialize};
 use scale::{Encode, Decode, MaxEncodedLen};
 use scale_info::TypeInfo;
 
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+
 use crate::{Coin, Amount};
 
 /// The type used for balances (a Coin and Balance).
@@ -16,6 +19,7 @@ use crate::{Coin, Amount};
   Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
 )]
 #[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
 pub struct Balance {
   pub coin: Coin,
   pub amount: Amount,
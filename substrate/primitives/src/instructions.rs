@@ -0,0 +1,56 @@
+#[cfg(feature = "std")]
+use zeroize::Zeroize;
+
+use serde::{Serialize, Deserialize};
+
+use scale::{Encode, Decode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{Balance, SeraiAddress, ExternalAddress, Data};
+
+// The SCALE indexes are explicit as they define the encoding, which must remain stable
+// borsh encodes variants by their order, so variants must only ever be appended
+#[derive(
+  Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
+)]
+#[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub enum InInstruction {
+  #[codec(index = 0)]
+  Transfer(SeraiAddress),
+  #[codec(index = 1)]
+  Dex(Data),
+}
+
+#[derive(
+  Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
+)]
+#[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct InInstructionWithBalance {
+  pub instruction: InInstruction,
+  pub balance: Balance,
+}
+
+#[derive(
+  Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
+)]
+#[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct OutInstruction {
+  pub address: ExternalAddress,
+  pub data: Option<Data>,
+}
+
+#[derive(
+  Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
+)]
+#[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct OutInstructionWithBalance {
+  pub instruction: OutInstruction,
+  pub balance: Balance,
+}
@@ -10,7 +10,14 @@ use serde::{Serialize, Deserialize};
 use scale::{Encode, Decode, MaxEncodedLen};
 use scale_info::TypeInfo;
 
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+
 use sp_core::{ConstU32, bounded::BoundedVec};
+#[cfg(all(feature = "borsh", not(feature = "std")))]
+extern crate alloc;
+#[cfg(all(feature = "borsh", not(feature = "std")))]
+use alloc::vec::Vec;
 
 pub use sp_application_crypto as crypto;
 
@@ -29,6 +36,32 @@ pub use balance::*;
 mod account;
 pub use account::*;
 
+mod validator_sets;
+pub use validator_sets::*;
+
+mod instructions;
+pub use instructions::*;
+
+/// Serialize a BoundedVec with borsh, as a Vec.
+#[cfg(feature = "borsh")]
+pub fn borsh_serialize_bounded_vec<W: borsh::io::Write, T: BorshSerialize, const B: u32>(
+  bounded: &BoundedVec<T, ConstU32<B>>,
+  writer: &mut W,
+) -> borsh::io::Result<()> {
+  BorshSerialize::serialize(bounded.as_slice(), writer)
+}
+
+/// Deserialize a BoundedVec with borsh, erroring if it exceeds its bound.
+#[cfg(feature = "borsh")]
+pub fn borsh_deserialize_bounded_vec<R: borsh::io::Read, T: BorshDeserialize, const B: u32>(
+  reader: &mut R,
+) -> borsh::io::Result<BoundedVec<T, ConstU32<B>>> {
+  let vec: Vec<T> = BorshDeserialize::deserialize_reader(reader)?;
+  vec.try_into().map_err(|_| {
+    borsh::io::Error::new(borsh::io::ErrorKind::InvalidData, "BoundedVec exceeded its bound")
+  })
+}
+
 // Monero, our current longest address candidate, has a longest address of featured
 // 1 (enum) + 1 (flags) + 64 (two keys) = 66
 // When JAMTIS arrives, it'll become 112 or potentially even 142 bytes
@@ -37,7 +70,17 @@ pub const MAX_ADDRESS_LEN: u32 = 196;
 #[derive(
   Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
 )]
-pub struct ExternalAddress(BoundedVec<u8, ConstU32<{ MAX_ADDRESS_LEN }>>);
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct ExternalAddress(
+  #[cfg_attr(
+    feature = "borsh",
+    borsh(
+      serialize_with = "borsh_serialize_bounded_vec",
+      deserialize_with = "borsh_deserialize_bounded_vec"
+    )
+  )]
+  BoundedVec<u8, ConstU32<{ MAX_ADDRESS_LEN }>>,
+);
 
 #[cfg(feature = "std")]
 impl Zeroize for ExternalAddress {
@@ -73,7 +116,17 @@ pub const MAX_DATA_LEN: u32 = 512;
 #[derive(
   Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
 )]
-pub struct Data(BoundedVec<u8, ConstU32<{ MAX_DATA_LEN }>>);
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct Data(
+  #[cfg_attr(
+    feature = "borsh",
+    borsh(
+      serialize_with = "borsh_serialize_bounded_vec",
+      deserialize_with = "borsh_deserialize_bounded_vec"
+    )
+  )]
+  BoundedVec<u8, ConstU32<{ MAX_DATA_LEN }>>,
+);
 
 #[cfg(feature = "std")]
 impl Zeroize for Data {
@@ -103,3 +156,18 @@ impl AsRef<[u8]> for Data {
     self.0.as_ref()
   }
 }
+
+#[test]
+fn stable_encodings() {
+  let instruction = InInstruction::Dex(Data::new(vec![]).unwrap());
+  assert_eq!(NetworkId::Monero.encode(), [3]);
+  assert_eq!(Coin::Monero.encode(), [4]);
+  assert_eq!(instruction.encode(), [1, 0]);
+
+  #[cfg(feature = "borsh")]
+  {
+    assert_eq!(borsh::to_vec(&NetworkId::Monero).unwrap(), [3]);
+    assert_eq!(borsh::to_vec(&Coin::Monero).unwrap(), [4]);
+    assert_eq!(borsh::to_vec(&instruction).unwrap(), [1, 0, 0, 0, 0]);
+  }
+}
@@ -9,6 +9,9 @@ use serde::{Serialize, Deserialize};
 use scale::{Encode, Decode, MaxEncodedLen};
 use scale_info::TypeInfo;
 
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+
 use sp_core::{ConstU32, bounded::BoundedVec};
 
 /// The type used to identify networks.
@@ -27,11 +30,17 @@ use sp_core::{ConstU32, bounded::BoundedVec};
   TypeInfo,
 )]
 #[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(
+  feature = "borsh",
+  derive(BorshSerialize, BorshDeserialize),
+  borsh(use_discriminant = true)
+)]
+// The discriminants are explicit as they define the encoding, which must remain stable
 pub enum NetworkId {
-  Serai,
-  Bitcoin,
-  Ethereum,
-  Monero,
+  Serai = 0,
+  Bitcoin = 1,
+  Ethereum = 2,
+  Monero = 3,
 }
 
 /// The type used to identify coins.
@@ -50,12 +59,17 @@ pub enum NetworkId {
   TypeInfo,
 )]
 #[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(
+  feature = "borsh",
+  derive(BorshSerialize, BorshDeserialize),
+  borsh(use_discriminant = true)
+)]
 pub enum Coin {
-  Serai,
-  Bitcoin,
-  Ether,
-  Dai,
-  Monero,
+  Serai = 0,
+  Bitcoin = 1,
+  Ether = 2,
+  Dai = 3,
+  Monero = 4,
 }
 
 impl Coin {
@@ -83,7 +97,15 @@ pub const MAX_COINS_PER_NETWORK: u32 = 8;
 #[derive(
   Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
 )]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
 pub struct Network {
+  #[cfg_attr(
+    feature = "borsh",
+    borsh(
+      serialize_with = "crate::borsh_serialize_bounded_vec",
+      deserialize_with = "crate::borsh_deserialize_bounded_vec"
+    )
+  )]
   coins: BoundedVec<Coin, ConstU32<{ MAX_COINS_PER_NETWORK }>>,
 }
 
@@ -0,0 +1,53 @@
+#[cfg(feature = "std")]
+use zeroize::Zeroize;
+
+use serde::{Serialize, Deserialize};
+
+use scale::{Encode, Decode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::NetworkId;
+
+/// The type used to identify a specific session of validators.
+#[derive(
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Hash,
+  Debug,
+  Serialize,
+  Deserialize,
+  Encode,
+  Decode,
+  TypeInfo,
+  MaxEncodedLen,
+)]
+#[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct Session(pub u32);
+
+/// The type used to identify a specific validator set during a specific session.
+#[derive(
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Hash,
+  Debug,
+  Serialize,
+  Deserialize,
+  Encode,
+  Decode,
+  TypeInfo,
+  MaxEncodedLen,
+)]
+#[cfg_attr(feature = "std", derive(Zeroize))]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+pub struct ValidatorSet {
+  pub session: Session,
+  pub network: NetworkId,
+}
@@ -302,6 +302,10 @@ impl in_instructions::Config for Runtime {
 
 impl validator_sets::Config for Runtime {
   type RuntimeEvent = RuntimeEvent;
+
+  // Keep the prior validator sets active for an hour after a session rotation
+  type HandoverPeriod = ConstU64<{ HOURS }>;
+  type NextSessionRotation = Babe;
 }
 
 pub struct IdentityValidatorIdOf;
@@ -317,7 +321,7 @@ impl session::Config for Runtime {
   type ValidatorIdOf = IdentityValidatorIdOf;
   type ShouldEndSession = Babe;
   type NextSessionRotation = Babe;
-  type SessionManager = (); // TODO?
+  type SessionManager = ValidatorSets;
   type SessionHandler = <SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
   type Keys = SessionKeys;
   type WeightInfo = session::weights::SubstrateWeight<Runtime>;
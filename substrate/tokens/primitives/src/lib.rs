@@ -10,28 +10,11 @@ use serde::{Serialize, Deserialize};
 use scale::{Encode, Decode, MaxEncodedLen};
 use scale_info::TypeInfo;
 
-use serai_primitives::{Balance, SeraiAddress, ExternalAddress, Data, pallet_address};
+use serai_primitives::{SeraiAddress, pallet_address};
+pub use serai_primitives::{OutInstruction, OutInstructionWithBalance};
 
 pub const ADDRESS: SeraiAddress = pallet_address(b"Tokens");
 
-#[derive(
-  Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
-)]
-#[cfg_attr(feature = "std", derive(Zeroize))]
-pub struct OutInstruction {
-  pub address: ExternalAddress,
-  pub data: Option<Data>,
-}
-
-#[derive(
-  Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
-)]
-#[cfg_attr(feature = "std", derive(Zeroize))]
-pub struct OutInstructionWithBalance {
-  pub instruction: OutInstruction,
-  pub balance: Balance,
-}
-
 #[derive(
   Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode, MaxEncodedLen, TypeInfo,
 )]
@@ -8,9 +8,10 @@ pub mod pallet {
   use sp_core::sr25519::{Public, Signature};
   use sp_std::vec::Vec;
   use sp_application_crypto::RuntimePublic;
+  use sp_runtime::SaturatedConversion;
 
   use frame_system::pallet_prelude::*;
-  use frame_support::pallet_prelude::*;
+  use frame_support::{pallet_prelude::*, traits::EstimateNextSessionRotation};
 
   use serai_primitives::*;
   pub use validator_sets_primitives as primitives;
@@ -19,6 +20,12 @@ pub mod pallet {
   #[pallet::config]
   pub trait Config: frame_system::Config<AccountId = Public> + TypeInfo {
     type RuntimeEvent: IsType<<Self as frame_system::Config>::RuntimeEvent> + From<Event<Self>>;
+
+    /// The amount of blocks the prior session's validator sets remain active for after a session
+    /// rotation.
+    type HandoverPeriod: Get<BlockNumberFor<Self>>;
+    /// The source of session rotations, used to estimate when the next session will start.
+    type NextSessionRotation: EstimateNextSessionRotation<BlockNumberFor<Self>>;
   }
 
   #[pallet::genesis_config]
@@ -53,6 +60,30 @@ pub mod pallet {
   pub type ValidatorSets<T: Config> =
     StorageMap<_, Twox64Concat, ValidatorSet, ValidatorSetData, OptionQuery>;
 
+  /// The networks Serai validates, and their definitions.
+  #[pallet::storage]
+  #[pallet::getter(fn network)]
+  pub type Networks<T: Config> = StorageMap<_, Twox64Concat, NetworkId, Network, OptionQuery>;
+
+  /// The latest session for which validator sets have been declared.
+  ///
+  /// This will be one session ahead of the current session, as the sets for the next session are
+  /// declared when the current session starts.
+  #[pallet::storage]
+  #[pallet::getter(fn latest_session)]
+  pub type LatestSession<T: Config> = StorageValue<_, Session, OptionQuery>;
+
+  /// The session currently in effect.
+  #[pallet::storage]
+  #[pallet::getter(fn current_session)]
+  pub type CurrentSession<T: Config> = StorageValue<_, Session, OptionQuery>;
+
+  /// The schedule for every session which has started.
+  #[pallet::storage]
+  #[pallet::getter(fn session_schedule)]
+  pub type SessionSchedules<T: Config> =
+    StorageMap<_, Twox64Concat, Session, SessionSchedule, OptionQuery>;
+
   /// The MuSig key for a validator set.
   #[pallet::storage]
   #[pallet::getter(fn musig_key)]
@@ -85,7 +116,10 @@ pub mod pallet {
       }
       let participants = BoundedVec::try_from(participants).unwrap();
 
+      LatestSession::<T>::set(Some(Session(0)));
       for (id, network) in self.networks.clone() {
+        Networks::<T>::set(id, Some(network.clone()));
+
         let set = ValidatorSet { session: Session(0), network: id };
         // TODO: Should this be split up? Substrate will read this entire struct into mem on every
         // read, not just accessed variables
@@ -111,6 +145,66 @@ pub mod pallet {
   }
 
   impl<T: Config> Pallet<T> {
+    /// The session whose validator set for this network is expected to set its keys.
+    ///
+    /// This is the earliest session, starting from the current session, whose validator set
+    /// hasn't set its keys. If every set has set its keys, this is the latest session.
+    pub fn key_gen_session(network: NetworkId) -> Session {
+      let latest = LatestSession::<T>::get().unwrap_or(Session(0));
+      let current = CurrentSession::<T>::get().unwrap_or(Session(0));
+      (current.0 ..= latest.0)
+        .map(Session)
+        .find(|session| Keys::<T>::get(ValidatorSet { session: *session, network }).is_none())
+        .unwrap_or(latest)
+    }
+
+    /// The validator sets currently active for a network.
+    ///
+    /// During a handover window, this will return both the retiring set and its successor, in
+    /// that order.
+    pub fn active_sets(network: NetworkId) -> Vec<ValidatorSet> {
+      let mut res = Vec::new();
+      let Some(current) = CurrentSession::<T>::get() else { return res };
+      let now = BlockNumber(frame_system::Pallet::<T>::block_number().saturated_into::<u64>());
+      if current.0 != 0 {
+        if let Some(schedule) = SessionSchedules::<T>::get(current) {
+          if schedule.in_handover(now) {
+            res.push(ValidatorSet { session: Session(current.0 - 1), network });
+          }
+        }
+      }
+      res.push(ValidatorSet { session: current, network });
+      res
+    }
+
+    /// Declare the validator sets for a new session.
+    ///
+    /// The sets are declared a session in advance of when they're expected to become active,
+    /// giving them the entirety of the current session to perform their DKGs.
+    fn new_sets(session: Session) {
+      // The sets for this session were already declared (such as at genesis)
+      if LatestSession::<T>::get().map(|latest| latest.0 >= session.0).unwrap_or(false) {
+        return;
+      }
+      // Sets are declared from the sets before them, so there's nothing to declare without a prior
+      // session
+      let Some(prior_session) = session.0.checked_sub(1) else { return };
+
+      for (network, _) in Networks::<T>::iter() {
+        let prior = ValidatorSet { session: Session(prior_session), network };
+        let Some(data) = ValidatorSets::<T>::get(prior) else { continue };
+
+        // TODO: Update the participants with the stake present at the end of the epoch
+        let set = ValidatorSet { session, network };
+        let participants =
+          data.participants.iter().map(|(participant, _)| *participant).collect::<Vec<_>>();
+        MuSigKeys::<T>::set(set, Some(musig_key(set, &participants)));
+        ValidatorSets::<T>::set(set, Some(data));
+        Self::deposit_event(Event::NewSet { set });
+      }
+      LatestSession::<T>::set(Some(session));
+    }
+
     fn verify_signature(
       set: ValidatorSet,
       key_pair: &KeyPair,
@@ -141,8 +235,7 @@ pub mod pallet {
     ) -> DispatchResult {
       ensure_none(origin)?;
 
-      // TODO: Get session
-      let session: Session = Session(0);
+      let session = Self::key_gen_session(network);
 
       // Confirm a key hasn't been set for this set instance
       let set = ValidatorSet { session, network };
@@ -167,8 +260,7 @@ pub mod pallet {
         Call::__Ignore(_, _) => unreachable!(),
       };
 
-      // TODO: Get the latest session
-      let session = Session(0);
+      let session = Self::key_gen_session(*network);
 
       let set = ValidatorSet { session, network: *network };
       match Self::verify_signature(set, key_pair, signature) {
@@ -189,7 +281,32 @@ pub mod pallet {
     }
   }
 
-  // TODO: Support session rotation
+  impl<T: Config> pallet_session::SessionManager<T::AccountId> for Pallet<T> {
+    fn new_session(new_index: u32) -> Option<Vec<T::AccountId>> {
+      Self::new_sets(Session(new_index));
+      // TODO: Have the Serai validator set decide the Babe/GRANDPA authorities
+      None
+    }
+
+    fn end_session(_: u32) {}
+
+    fn start_session(start_index: u32) {
+      let now = frame_system::Pallet::<T>::block_number();
+      let handover_end = now.saturating_add(T::HandoverPeriod::get());
+      let next_start = T::NextSessionRotation::estimate_next_session_rotation(now).0;
+
+      let session = Session(start_index);
+      CurrentSession::<T>::set(Some(session));
+      SessionSchedules::<T>::set(
+        session,
+        Some(SessionSchedule {
+          start: BlockNumber(now.saturated_into::<u64>()),
+          handover_end: BlockNumber(handover_end.saturated_into::<u64>()),
+          next_start: next_start.map(|block| BlockNumber(block.saturated_into::<u64>())),
+        }),
+      );
+    }
+  }
 }
 
 pub use pallet::*;
@@ -13,12 +13,17 @@ use sp_core::{ConstU32, sr25519::Public, bounded::BoundedVec};
 #[cfg(not(feature = "std"))]
 use sp_std::vec::Vec;
 
-use serai_primitives::{NetworkId, Network, Amount};
+use serai_primitives::{BlockNumber, Network, Amount};
+pub use serai_primitives::{Session, ValidatorSet};
 
 // Support keys up to 96 bytes (BLS12-381 G2).
 const MAX_KEY_LEN: u32 = 96;
 
-/// The type used to identify a specific session of validators.
+/// The schedule for a session.
+///
+/// Sessions are aligned with the stake epochs of the Serai network. When a session starts, the
+/// validator sets of the prior session remain active until the end of the handover window,
+/// allowing them to hand over to their successors.
 #[derive(
   Clone,
   Copy,
@@ -34,27 +39,23 @@ const MAX_KEY_LEN: u32 = 96;
   MaxEncodedLen,
 )]
 #[cfg_attr(feature = "std", derive(Zeroize))]
-pub struct Session(pub u32);
+pub struct SessionSchedule {
+  /// The block this session started at.
+  pub start: BlockNumber,
+  /// The block the handover window ends at, after which the prior session's sets are retired.
+  pub handover_end: BlockNumber,
+  /// The estimated block the next session will start at.
+  ///
+  /// The validator sets for the next session are declared when this session starts, giving them
+  /// until this block to perform their DKGs.
+  pub next_start: Option<BlockNumber>,
+}
 
-/// The type used to identify a specific validator set during a specific session.
-#[derive(
-  Clone,
-  Copy,
-  PartialEq,
-  Eq,
-  Hash,
-  Debug,
-  Serialize,
-  Deserialize,
-  Encode,
-  Decode,
-  TypeInfo,
-  MaxEncodedLen,
-)]
-#[cfg_attr(feature = "std", derive(Zeroize))]
-pub struct ValidatorSet {
-  pub session: Session,
-  pub network: NetworkId,
+impl SessionSchedule {
+  /// If the prior session's validator sets are still active as of the specified block.
+  pub fn in_handover(&self, block: BlockNumber) -> bool {
+    (self.start.0 <= block.0) && (block.0 < self.handover_end.0)
+  }
 }
 
 /// The data for a validator set.
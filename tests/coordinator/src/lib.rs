@@ -211,7 +211,7 @@ impl Processor {
           to: Service::Coordinator,
           intent: msg.intent(),
         },
-        serde_json::to_string(&msg).unwrap().into_bytes(),
+        msg.serialize(),
       )
       .await;
     self.next_send_id += 1;
@@ -226,6 +226,6 @@ impl Processor {
     assert_eq!(msg.id, self.next_recv_id);
     self.queue.ack(Service::Coordinator, msg.id).await;
     self.next_recv_id += 1;
-    serde_json::from_slice(&msg.msg).unwrap()
+    CoordinatorMessage::deserialize(&msg.msg).unwrap()
   }
 }
@@ -13,7 +13,6 @@ use serai_client::{
   primitives::{
     NetworkId, Coin, Amount, Balance, SeraiAddress, ExternalAddress, insecure_pair_from_name,
   },
-  validator_sets::primitives::{Session, ValidatorSet},
   in_instructions::primitives::Shorthand,
   tokens::primitives::OutInstruction,
   PairTrait, PairSigner,
@@ -33,118 +32,6 @@ async fn mint_and_burn_test() {
       let ops = Arc::new(ops);
       let serai = handles[0].serai(&ops).await;
 
-      // Helper to mine a block on each network
-      async fn mine_blocks(
-        handles: &Vec<Handles>,
-        ops: &DockerOperations,
-        producer: &mut usize,
-        count: usize,
-      ) {
-        // Pick a block producer via a round robin
-        let producer_handles = &handles[*producer];
-        *producer += 1;
-        *producer %= handles.len();
-
-        // Mine a Bitcoin block
-        let bitcoin_blocks = {
-          use bitcoin_serai::bitcoin::{
-            secp256k1::{SECP256K1, SecretKey},
-            PrivateKey, PublicKey,
-            consensus::Encodable,
-            network::constants::Network,
-            address::Address,
-          };
-
-          let addr = Address::p2pkh(
-            &PublicKey::from_private_key(
-              SECP256K1,
-              &PrivateKey::new(SecretKey::from_slice(&[0x01; 32]).unwrap(), Network::Bitcoin),
-            ),
-            Network::Regtest,
-          );
-
-          let rpc = producer_handles.bitcoin(ops).await;
-          let mut res = Vec::with_capacity(count);
-          for _ in 0 .. count {
-            let hash = rpc
-              .rpc_call::<Vec<String>>("generatetoaddress", serde_json::json!([1, addr]))
-              .await
-              .unwrap()
-              .swap_remove(0);
-
-            let mut bytes = vec![];
-            rpc
-              .get_block(&hex::decode(hash).unwrap().try_into().unwrap())
-              .await
-              .unwrap()
-              .consensus_encode(&mut bytes)
-              .unwrap();
-            res.push(serde_json::json!([hex::encode(bytes)]));
-          }
-          res
-        };
-
-        // Mine a Monero block
-        let monero_blocks = {
-          use curve25519_dalek::{constants::ED25519_BASEPOINT_POINT, scalar::Scalar};
-          use monero_serai::wallet::{
-            ViewPair,
-            address::{Network, AddressSpec},
-          };
-
-          let addr = ViewPair::new(ED25519_BASEPOINT_POINT, Zeroizing::new(Scalar::ONE))
-            .address(Network::Mainnet, AddressSpec::Standard)
-            .to_string();
-
-          let rpc = producer_handles.monero(ops).await;
-          let mut res = Vec::with_capacity(count);
-          for _ in 0 .. count {
-            let block =
-              rpc.get_block(rpc.generate_blocks(&addr, 1).await.unwrap()[0]).await.unwrap();
-
-            let mut txs = Vec::with_capacity(block.txs.len());
-            for tx in &block.txs {
-              txs.push(rpc.get_transaction(*tx).await.unwrap());
-            }
-            res.push((serde_json::json!([hex::encode(block.serialize())]), txs));
-          }
-          res
-        };
-
-        // Relay it to all other nodes
-        // If the producer is 0, the producer variable will be 1 since we already incremented
-        // it
-        // With 4 nodes, this will run 1 .. 4, which is the correct range
-        for receiver in *producer .. (*producer + (handles.len() - 1)) {
-          let receiver = receiver % handles.len();
-          let handles = &handles[receiver];
-
-          {
-            let rpc = handles.bitcoin(ops).await;
-            for block in &bitcoin_blocks {
-              let _: () = rpc.rpc_call("submitblock", block.clone()).await.unwrap();
-            }
-          }
-
-          {
-            let rpc = handles.monero(ops).await;
-
-            for (block, txs) in &monero_blocks {
-              // Broadcast the Monero TXs, as they're not simply included with the block
-              for tx in txs {
-                // Ignore any errors since the TX already being present will return an error
-                let _ = rpc.publish_transaction(tx).await;
-              }
-
-              #[derive(Debug, serde::Deserialize)]
-              struct EmptyResponse {}
-              let _: EmptyResponse =
-                rpc.json_rpc_call("submit_block", Some(block.clone())).await.unwrap();
-            }
-          }
-        }
-      }
-
       // Mine blocks to create mature funds
       mine_blocks(&handles, &ops, &mut 0, 101).await;
 
@@ -185,42 +72,8 @@ async fn mint_and_burn_test() {
       };
 
       // Get the generated keys
-      let (bitcoin_key_pair, monero_key_pair) = {
-        let key_pair = {
-          let serai = &serai;
-          move |additional, network| async move {
-            // If this is an additional key pair, it should've completed with the first barring
-            // misc latency, so only sleep up to 5 minutes
-            // If this is the first key pair, wait up to 10 minutes
-            let halt_at = if additional { 5 * 10 } else { 10 * 10 };
-            let print_at = halt_at / 2;
-            for i in 0 .. halt_at {
-              if let Some(key_pair) =
-                serai.get_keys(ValidatorSet { network, session: Session(0) }).await.unwrap()
-              {
-                return key_pair;
-              }
-
-              if i == print_at {
-                println!(
-                  "waiting for {}key gen to complete, it has been {} minutes",
-                  if additional { "another " } else { "" },
-                  print_at / 10,
-                );
-              }
-              tokio::time::sleep(Duration::from_secs(6)).await;
-            }
-
-            panic!(
-              "{}key gen did not complete within {} minutes",
-              if additional { "another " } else { "" },
-              halt_at / 10,
-            );
-          }
-        };
-
-        (key_pair(false, NetworkId::Bitcoin).await, key_pair(true, NetworkId::Monero).await)
-      };
+      let bitcoin_key_pair = wait_for_key_pair(&serai, NetworkId::Bitcoin, false).await;
+      let monero_key_pair = wait_for_key_pair(&serai, NetworkId::Monero, true).await;
 
       // Create a Serai address to receive the sriBTC/sriXMR to
       let (serai_pair, serai_addr) = {
@@ -127,6 +127,15 @@ fn basic_functionality() {
     assert_eq!(&next_msg.msg, b"Hello, World, again!");
     bitcoin.ack(Service::Coordinator, 1).await;
 
+    // Acknowledging an already acknowledged message should be a no-op
+    bitcoin.ack(Service::Coordinator, 0).await;
+    bitcoin.ack(Service::Coordinator, 1).await;
+
+    // Acknowledged messages should remain available by ID, allowing replays
+    assert_eq!(bitcoin.get(Service::Coordinator, 0).await.unwrap(), msg);
+    assert_eq!(bitcoin.get(Service::Coordinator, 1).await.unwrap(), next_msg);
+    assert!(bitcoin.get(Service::Coordinator, 2).await.is_none());
+
     // No further messages should be available
     tokio::time::timeout(core::time::Duration::from_secs(10), bitcoin.next(Service::Coordinator))
       .await
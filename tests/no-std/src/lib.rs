@@ -13,8 +13,8 @@ pub use dleq;
 pub use schnorr_signatures;
 
 pub use dkg;
-/*
 pub use modular_frost;
+/*
 pub use frost_schnorrkel;
 */
 
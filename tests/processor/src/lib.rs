@@ -214,7 +214,7 @@ impl Coordinator {
           to: Service::Processor(self.network),
           intent: msg.intent(),
         },
-        serde_json::to_string(&msg).unwrap().into_bytes(),
+        msg.serialize(),
       )
       .await;
     self.next_send_id += 1;
@@ -232,7 +232,7 @@ impl Coordinator {
     assert_eq!(msg.id, self.next_recv_id);
     self.queue.ack(Service::Processor(self.network), msg.id).await;
     self.next_recv_id += 1;
-    serde_json::from_slice(&msg.msg).unwrap()
+    ProcessorMessage::deserialize(&msg.msg).unwrap()
   }
 
   pub async fn add_block(&self, ops: &DockerOperations) -> ([u8; 32], Vec<u8>) {